@@ -18,6 +18,9 @@ usage:
 
     # verbose output
     uv run scripts/transcoder/test-matrix.py -v
+
+    # check m4a bitrate and aac profile handling instead of the matrix
+    uv run scripts/transcoder/test-matrix.py --encoder-options
 """
 
 from __future__ import annotations
@@ -89,6 +92,7 @@ def transcode_file(
     url: str,
     auth_token: str | None,
     timeout: float = 60.0,
+    params: dict[str, str] | None = None,
 ) -> tuple[bytes | None, str | None]:
     """send file to transcoder and return result."""
     headers = {}
@@ -100,7 +104,7 @@ def transcode_file(
             files = {"file": (input_path.name, f)}
             response = httpx.post(
                 f"{url}/transcode",
-                params={"target": target_format},
+                params={"target": target_format, **(params or {})},
                 files=files,
                 headers=headers,
                 timeout=timeout,
@@ -139,6 +143,101 @@ def verify_audio(file_path: Path) -> bool:
         return False
 
 
+def probe_audio_stream(file_path: Path) -> dict[str, str]:
+    """codec, profile and bitrate of the first audio stream, via ffprobe."""
+    cmd = [
+        "ffprobe",
+        "-v",
+        "error",
+        "-select_streams",
+        "a:0",
+        "-show_entries",
+        "stream=codec_name,profile,bit_rate",
+        "-of",
+        "default=noprint_wrappers=1",
+        str(file_path),
+    ]
+    result = subprocess.run(cmd, capture_output=True, text=True)
+    if result.returncode != 0:
+        return {}
+    return dict(
+        line.split("=", 1) for line in result.stdout.splitlines() if "=" in line
+    )
+
+
+def run_encoder_option_tests(
+    samples_dir: Path,
+    url: str,
+    auth_token: str | None,
+    verbose: bool = False,
+) -> list[str]:
+    """check ?bitrate= and ?aac_profile= on m4a output. returns failures."""
+    input_path = samples_dir / "test.wav"
+    failures: list[str] = []
+
+    def check(name: str, ok: bool, detail: str) -> None:
+        if verbose or not ok:
+            print(f"  {name}: {'ok' if ok else 'FAILED'} ({detail})")
+        if not ok:
+            failures.append(f"{name}: {detail}")
+
+    # LC at an explicit bitrate
+    result, error = transcode_file(
+        input_path, "m4a", url, auth_token, params={"bitrate": "128"}
+    )
+    if error:
+        check("m4a lc 128k", False, error)
+    else:
+        output_path = samples_dir / "output_lc_128k.m4a"
+        output_path.write_bytes(result)
+        stream = probe_audio_stream(output_path)
+        bit_rate = int(stream.get("bit_rate", "0") or 0)
+        check(
+            "m4a lc 128k",
+            verify_audio(output_path)
+            and stream.get("profile") == "LC"
+            and 0 < bit_rate <= 140_000,
+            f"profile={stream.get('profile')} bit_rate={bit_rate}",
+        )
+
+    # HE needs libfdk_aac. without it the transcoder must refuse up front
+    # rather than fail inside ffmpeg
+    result, error = transcode_file(
+        input_path,
+        "m4a",
+        url,
+        auth_token,
+        params={"aac_profile": "he", "bitrate": "64"},
+    )
+    if error:
+        check(
+            "m4a he 64k",
+            error.startswith("HTTP 400") and "libfdk_aac" in error,
+            error,
+        )
+    else:
+        output_path = samples_dir / "output_he_64k.m4a"
+        output_path.write_bytes(result)
+        stream = probe_audio_stream(output_path)
+        check(
+            "m4a he 64k",
+            verify_audio(output_path) and stream.get("profile") == "HE-AAC",
+            f"profile={stream.get('profile')}",
+        )
+
+    # HE above its bitrate ceiling is refused either way
+    _, error = transcode_file(
+        input_path,
+        "m4a",
+        url,
+        auth_token,
+        params={"aac_profile": "he", "bitrate": "192"},
+    )
+    check("m4a he 192k", (error or "").startswith("HTTP 400"), error or "HTTP 200")
+
+    return failures
+
+
 def run_test(
     input_format: str,
     output_format: str,
@@ -263,6 +362,11 @@ def main():
         action="store_true",
         help="Verbose output",
     )
+    parser.add_argument(
+        "--encoder-options",
+        action="store_true",
+        help="Check m4a bitrate and aac profile handling instead of the matrix",
+    )
     parser.add_argument(
         "--keep-files",
         action="store_true",
@@ -293,6 +397,8 @@ def main():
         sys.exit(1)
 
     # determine formats to test
+    if args.encoder_options:
+        args.input_format = "wav"
     input_formats = [args.input_format] if args.input_format else INPUT_FORMATS
     output_formats = [args.output_format] if args.output_format else OUTPUT_FORMATS
 
@@ -322,6 +428,19 @@ def main():
             print(f"failed to generate {fmt} sample", file=sys.stderr)
             sys.exit(1)
 
+    if args.encoder_options:
+        print("\nrunning encoder option tests...")
+        failures = run_encoder_option_tests(
+            samples_dir, args.url, auth_token, args.verbose
+        )
+        if failures:
+            print(f"\n{len(failures)} failures:")
+            for failure in failures:
+                print(f"  {failure}")
+        if not args.keep_files:
+            shutil.rmtree(samples_dir)
+        sys.exit(0 if not failures else 1)
+
     # run test matrix
     print("\nrunning transcoding tests...")
     results: list[TestResult] = []
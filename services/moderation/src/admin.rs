@@ -3,15 +3,35 @@
 //! Uses htmx for interactivity with server-rendered HTML.
 
 use axum::{
-    extract::{Query, State},
-    http::header::CONTENT_TYPE,
+    body::Body,
+    extract::{Extension, Path, Query, State},
+    http::{
+        header::{CONTENT_DISPOSITION, CONTENT_TYPE},
+        StatusCode,
+    },
     response::{IntoResponse, Response},
     Json,
 };
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-
-use crate::db::LabelContext;
+use std::collections::{HashMap, HashSet};
+use std::time::Instant;
+use tokio::sync::{broadcast, mpsc};
+use tokio_stream::wrappers::ReceiverStream;
+
+use crate::audd::ScanResponse;
+use crate::auth::{Actor, Caller, RequireAdmin};
+use crate::db::{
+    current_label_values, AudioScanRow, AuditLogRow, BatchFlag, BatchSummary, CopyrightMatch,
+    FlagSort, FlagStatus, FlagsCursor, FlagsPage, FlagsPageQuery, LabelContext, LabelHistoryRow,
+    LabelTimestampRow, ResolutionReason, ReviewDecisionRow, SensitiveImageRow, SensitiveImageSort,
+    StoredNegation, UserReport,
+};
+use crate::enforcement::{enforce_confirmed, EnforcementStatus};
+use crate::handlers::normalize_score;
+use crate::ratelimit::{RateLimitExceeded, RateLimited};
+use crate::reasons::validate_reason;
+use crate::reporter_hash::display_reporter;
 use crate::state::{AppError, AppState};
 
 /// A flagged track pending review.
@@ -23,23 +43,56 @@ pub struct FlaggedTrack {
     pub created_at: String,
     /// Track status: pending review, resolved (false positive), or confirmed (takedown).
     pub resolved: bool,
+    /// When the flag was negated, if it's resolved.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resolved_at: Option<DateTime<Utc>>,
     /// Optional context about the track (title, artist, matches).
     #[serde(skip_serializing_if = "Option::is_none")]
     pub context: Option<LabelContext>,
+    /// Status of the enforcement callback, if the flag was confirmed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub enforcement: Option<String>,
+    /// Copyright labels issued for the URI. Lists show one entry per URI,
+    /// for the latest label; the flag detail has all of them.
+    pub label_count: i64,
 }
 
 /// Response for listing flagged tracks.
 #[derive(Debug, Serialize)]
 pub struct ListFlaggedResponse {
     pub tracks: Vec<FlaggedTrack>,
+    /// Flags matching the filter across all pages.
+    pub total: i64,
+    /// Pass as `cursor` (with the same `sort`) to fetch the next page (null on
+    /// the last page).
+    pub next_cursor: Option<String>,
 }
 
 /// Query parameters for listing flags.
 #[derive(Debug, Deserialize, Default)]
 pub struct ListFlagsQuery {
-    /// Filter: "pending" (default), "resolved", or "all"
+    /// Filter: "pending" (default), "resolved", "confirmed", or "all"
     #[serde(default = "default_filter")]
     pub filter: String,
+    /// Sort: "newest" (default), "oldest", or "most_matches"
+    #[serde(default)]
+    pub sort: String,
+    /// Cursor from the previous page's `next_cursor`.
+    pub cursor: Option<String>,
+    #[serde(default = "default_limit")]
+    pub limit: i64,
+    /// Artist handle substring (case-insensitive). A value starting with
+    /// `did:` is treated as `did`.
+    pub artist: Option<String>,
+    /// Exact artist DID.
+    pub did: Option<String>,
+    /// Case-insensitive search across track title, artist handle, and the
+    /// titles and artists of fingerprint matches.
+    pub q: Option<String>,
+    /// Only flags created at or after this time (RFC 3339).
+    pub from: Option<DateTime<Utc>>,
+    /// Only flags created before this time (RFC 3339).
+    pub to: Option<DateTime<Utc>>,
 }
 
 fn default_filter() -> String {
@@ -56,6 +109,10 @@ pub struct ResolveRequest {
     pub reason: Option<String>,
     /// Additional notes about the resolution.
     pub notes: Option<String>,
+    /// User report to resolve along with the flag, using the same notes. It
+    /// must be about the flag's URI. Either both change or neither does.
+    #[serde(default)]
+    pub close_report_id: Option<i32>,
 }
 
 fn default_val() -> String {
@@ -67,6 +124,105 @@ fn default_val() -> String {
 pub struct ResolveResponse {
     pub seq: i64,
     pub message: String,
+    /// The report resolved along with the flag, if one was asked for.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub closed_report_id: Option<i32>,
+}
+
+/// Most URIs a single bulk resolve may touch.
+pub const MAX_BULK_RESOLVE: usize = 200;
+
+/// Request to resolve (negate) many flags with a shared reason.
+#[derive(Debug, Deserialize)]
+pub struct BulkResolveRequest {
+    pub uris: Vec<String>,
+    #[serde(default = "default_val")]
+    pub val: String,
+    /// Reason for marking as false positive, applied to every URI.
+    pub reason: Option<String>,
+    /// Additional notes about the resolution, applied to every URI.
+    pub notes: Option<String>,
+    /// If true, resolve nothing unless every URI can be resolved.
+    #[serde(default)]
+    pub atomic: bool,
+}
+
+/// What happened to one URI in a bulk resolve.
+#[derive(Debug, Serialize, PartialEq, Eq)]
+pub struct BulkResolveOutcome {
+    pub uri: String,
+    pub resolved: bool,
+    /// Sequence number of the negation, when one was created.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub seq: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Response after a bulk resolve.
+#[derive(Debug, Serialize)]
+pub struct BulkResolveResponse {
+    pub resolved_count: usize,
+    /// One entry per distinct requested URI, in request order.
+    pub outcomes: Vec<BulkResolveOutcome>,
+}
+
+/// Request to confirm a flag as a real violation.
+#[derive(Debug, Deserialize)]
+pub struct ConfirmRequest {
+    pub uri: String,
+    /// Additional notes for the audit log.
+    pub notes: Option<String>,
+}
+
+/// Response after confirming a flag.
+#[derive(Debug, Serialize)]
+pub struct ConfirmResponse {
+    pub uri: String,
+    /// Enforcement status, or null when no enforcement URL is configured.
+    pub enforcement: Option<&'static str>,
+    pub message: String,
+}
+
+/// Request to re-run the copyright scan for a flag.
+#[derive(Debug, Deserialize)]
+pub struct RescanRequest {
+    pub uri: String,
+    /// Audio to scan; defaults to the URL stored in the flag's context.
+    pub audio_url: Option<String>,
+}
+
+/// Request to negate one specific label by seq.
+#[derive(Debug, Deserialize)]
+pub struct NegateSeqRequest {
+    pub seq: i64,
+    /// Reason for marking as false positive.
+    pub reason: Option<String>,
+    /// Additional notes about the resolution.
+    pub notes: Option<String>,
+}
+
+/// Most labels a single `/admin/rebroadcast` re-sends.
+pub const MAX_REBROADCAST: i64 = 1000;
+
+/// Request to re-send stored labels to connected subscribers.
+#[derive(Debug, Deserialize)]
+pub struct RebroadcastRequest {
+    /// Labels with a seq above this are re-sent, oldest first.
+    pub since_seq: i64,
+    /// Defaults to, and is capped at, `MAX_REBROADCAST`.
+    pub limit: Option<i64>,
+}
+
+/// Response after a rebroadcast.
+#[derive(Debug, Serialize)]
+pub struct RebroadcastResponse {
+    /// Seqs pushed to subscribers, in order.
+    pub seqs: Vec<i64>,
+    /// Subscribers connected when the labels were sent.
+    pub subscribers: usize,
+    /// Pass as `since_seq` to continue, when the limit was hit.
+    pub next_since_seq: Option<i64>,
 }
 
 /// Request to store label context (for backfill).
@@ -85,6 +241,8 @@ pub struct ContextPayload {
     pub artist_did: Option<String>,
     pub highest_score: Option<f64>,
     pub matches: Option<Vec<crate::db::CopyrightMatch>>,
+    /// Where the track's audio can be fetched, for rescans.
+    pub audio_url: Option<String>,
 }
 
 /// Response after storing context.
@@ -93,6 +251,16 @@ pub struct StoreContextResponse {
     pub message: String,
 }
 
+/// Request to correct a URI's stored track context. Omitted fields are left
+/// as they are.
+#[derive(Debug, Deserialize)]
+pub struct EditContextRequest {
+    pub uri: String,
+    pub track_title: Option<String>,
+    pub artist_handle: Option<String>,
+    pub track_id: Option<i64>,
+}
+
 /// Request to check which URIs have active labels.
 #[derive(Debug, Deserialize)]
 pub struct ActiveLabelsRequest {
@@ -156,6 +324,29 @@ pub struct RemoveSensitiveImageResponse {
     pub message: String,
 }
 
+/// Query parameters for listing sensitive images.
+#[derive(Debug, Deserialize)]
+pub struct SensitiveImagesQuery {
+    /// Exact match on who flagged it (e.g. "claude-auto").
+    pub flagged_by: Option<String>,
+    /// Sort: "newest" (default) or "severity"
+    #[serde(default)]
+    pub sort: String,
+    #[serde(default = "default_limit")]
+    pub limit: i64,
+    #[serde(default)]
+    pub offset: i64,
+}
+
+/// Paginated sensitive images with full metadata.
+#[derive(Debug, Serialize)]
+pub struct SensitiveImagesPage {
+    pub images: Vec<SensitiveImageRow>,
+    pub total: i64,
+    pub limit: i64,
+    pub offset: i64,
+}
+
 /// Request to create a review batch.
 #[derive(Debug, Deserialize)]
 pub struct CreateBatchRequest {
@@ -174,43 +365,442 @@ pub struct CreateBatchResponse {
     pub flag_count: usize,
 }
 
-/// List all flagged tracks - returns JSON for API, HTML for htmx.
+/// Query parameters for listing review batches.
+#[derive(Debug, Deserialize)]
+pub struct BatchListQuery {
+    #[serde(default = "default_batch_limit")]
+    pub limit: i64,
+}
+
+fn default_batch_limit() -> i64 {
+    50
+}
+
+/// A review batch in the batch list.
+#[derive(Debug, Serialize)]
+pub struct BatchListItem {
+    #[serde(flatten)]
+    pub batch: BatchSummary,
+    pub url: String,
+}
+
+/// Response for listing review batches.
+#[derive(Debug, Serialize)]
+pub struct BatchListResponse {
+    pub batches: Vec<BatchListItem>,
+}
+
+/// Query parameters for a URI's label history.
+#[derive(Debug, Deserialize)]
+pub struct UriHistoryQuery {
+    pub uri: String,
+}
+
+/// Full label timeline for a single URI.
+#[derive(Debug, Serialize)]
+pub struct UriHistoryResponse {
+    pub uri: String,
+    /// Label values currently in force after replaying the timeline.
+    pub current: Vec<String>,
+    /// Every label row for the URI (positives and negations), ordered by seq.
+    pub entries: Vec<LabelHistoryRow>,
+    /// Track context and resolution reason, if stored.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub context: Option<LabelContext>,
+}
+
+/// Everything known about one flagged URI, for investigating disputes.
+#[derive(Debug, Serialize)]
+pub struct FlagDetailResponse {
+    /// Label timeline and stored context.
+    #[serde(flatten)]
+    pub history: UriHistoryResponse,
+    /// Audio scans recorded for the URI.
+    pub scans: Vec<AudioScanRow>,
+    /// User reports about the same track.
+    pub reports: Vec<UserReport>,
+    /// Review batch entries for the URI.
+    pub batch_decisions: Vec<BatchFlag>,
+    /// Review decisions on the URI, from batches and the dashboard, newest
+    /// first.
+    pub review_decisions: Vec<ReviewDecisionRow>,
+    /// Audit log entries targeting the URI, newest first.
+    pub audit: Vec<AuditLogRow>,
+}
+
+/// Query parameters for the audit log.
+#[derive(Debug, Deserialize)]
+pub struct AuditLogQuery {
+    pub action: Option<String>,
+    /// Exact target (a URI, image id, report id, ...).
+    pub target: Option<String>,
+    pub actor: Option<String>,
+    #[serde(default = "default_limit")]
+    pub limit: i64,
+}
+
+fn default_limit() -> i64 {
+    50
+}
+
+/// Response listing audit log entries.
+#[derive(Debug, Serialize)]
+pub struct AuditLogResponse {
+    pub entries: Vec<AuditLogRow>,
+}
+
+/// Query parameters for label timestamp drift.
+#[derive(Debug, Deserialize)]
+pub struct LabelTimestampsQuery {
+    /// Restrict to a single URI.
+    pub uri: Option<String>,
+    /// Only rows where `created_at` and `cts` differ by at least this much.
+    #[serde(default)]
+    pub min_skew_seconds: i64,
+    #[serde(default = "default_limit")]
+    pub limit: i64,
+}
+
+/// Labels with both signed and insertion timestamps.
+#[derive(Debug, Serialize)]
+pub struct LabelTimestampsResponse {
+    pub labels: Vec<LabelTimestampRow>,
+    /// How many returned rows have a `cts` earlier than a lower seq.
+    pub out_of_order_count: usize,
+}
+
+/// List flagged tracks a page at a time - returns JSON for API, HTML for htmx.
 pub async fn list_flagged(
     State(state): State<AppState>,
     Query(query): Query<ListFlagsQuery>,
 ) -> Result<Json<ListFlaggedResponse>, AppError> {
     let db = state.db.as_ref().ok_or(AppError::LabelerNotConfigured)?;
-    let all_tracks = db.get_pending_flags().await?;
-    let tracks = filter_tracks(all_tracks, &query.filter);
-    Ok(Json(ListFlaggedResponse { tracks }))
+    let page = db.get_flags_page(&flags_page_query(&query)?).await?;
+    Ok(Json(ListFlaggedResponse {
+        tracks: page.tracks,
+        total: page.total,
+        next_cursor: page.next_cursor,
+    }))
 }
 
 /// Render flags as HTML partial for htmx.
+///
+/// Without a cursor this renders the filter row and first page; with a cursor
+/// it renders just the next page of cards, to replace the "load more" button.
 pub async fn list_flagged_html(
     State(state): State<AppState>,
     Query(query): Query<ListFlagsQuery>,
 ) -> Result<Response, AppError> {
     let db = state.db.as_ref().ok_or(AppError::LabelerNotConfigured)?;
-    let all_tracks = db.get_pending_flags().await?;
-    let tracks = filter_tracks(all_tracks, &query.filter);
+    let page_query = flags_page_query(&query)?;
+    let page = db.get_flags_page(&page_query).await?;
+
+    let html = if query.cursor.is_some() {
+        render_flags_page(&page, &page_query)
+    } else {
+        render_flags_list(&page, &page_query)
+    };
+
+    Ok(([(CONTENT_TYPE, "text/html; charset=utf-8")], html).into_response())
+}
+
+/// Rows buffered between the database and a slow CSV download.
+const CSV_EXPORT_BUFFER: usize = 64;
+
+/// Column headers for the flags CSV export.
+const FLAG_CSV_HEADER: [&str; 10] = [
+    "seq",
+    "uri",
+    "track_title",
+    "artist_handle",
+    "created_at",
+    "resolved",
+    "resolution_reason",
+    "resolution_notes",
+    "match_count",
+    "top_match",
+];
+
+/// Export flags as CSV, with the same filters as the JSON listing.
+///
+/// Every matching flag is exported (`cursor` and `limit` are ignored). Rows
+/// are streamed from the database straight into the response, so the export
+/// is never buffered in full.
+pub async fn export_flags_csv(
+    State(state): State<AppState>,
+    Query(query): Query<ListFlagsQuery>,
+) -> Result<Response, AppError> {
+    let db = state.db.clone().ok_or(AppError::LabelerNotConfigured)?;
+    let page_query = flags_page_query(&query)?;
+
+    let (tx, rx) = mpsc::channel::<Result<String, sqlx::Error>>(CSV_EXPORT_BUFFER);
+    tokio::spawn(async move {
+        if tx.send(Ok(csv_record(&FLAG_CSV_HEADER))).await.is_err() {
+            return;
+        }
+        // a failed send means the client went away, which ends the query
+        let tx_ref = &tx;
+        let result = db
+            .for_each_flag(&page_query, |track| async move {
+                tx_ref.send(Ok(flag_csv_record(&track))).await.is_ok()
+            })
+            .await;
+        if let Err(e) = result {
+            tracing::error!(error = %e, "flags export failed");
+            // aborts the response body so the download doesn't look complete
+            let _ = tx.send(Err(e)).await;
+        }
+    });
+
+    Ok((
+        [
+            (CONTENT_TYPE, "text/csv; charset=utf-8"),
+            (CONTENT_DISPOSITION, "attachment; filename=\"flags.csv\""),
+        ],
+        Body::from_stream(ReceiverStream::new(rx)),
+    )
+        .into_response())
+}
+
+/// One CSV row for a flag, in `FLAG_CSV_HEADER` order.
+fn flag_csv_record(track: &FlaggedTrack) -> String {
+    let empty = LabelContext::default();
+    let context = track.context.as_ref().unwrap_or(&empty);
+    let matches = context.matches.as_deref().unwrap_or_default();
+    let top_match = matches
+        .iter()
+        .max_by(|a, b| a.score.total_cmp(&b.score))
+        .map(|m| format!("{} - {}", m.title, m.artist))
+        .unwrap_or_default();
+
+    csv_record(&[
+        &track.seq.to_string(),
+        &track.uri,
+        context.track_title.as_deref().unwrap_or(""),
+        context.artist_handle.as_deref().unwrap_or(""),
+        &track.created_at,
+        if track.resolved { "true" } else { "false" },
+        context
+            .resolution_reason
+            .as_ref()
+            .map(|r| r.label())
+            .unwrap_or(""),
+        context.resolution_notes.as_deref().unwrap_or(""),
+        &matches.len().to_string(),
+        &top_match,
+    ])
+}
+
+/// Join fields into a CRLF-terminated CSV record (RFC 4180).
+fn csv_record(fields: &[&str]) -> String {
+    let mut record = fields
+        .iter()
+        .map(|field| csv_field(field))
+        .collect::<Vec<_>>()
+        .join(",");
+    record.push_str("\r\n");
+    record
+}
+
+/// Quote a CSV field if it contains a delimiter, quote, or line break.
+///
+/// Fields that a spreadsheet would read as a formula (leading `=`, `+`, `-`
+/// or `@`) get a `'` prefix, since titles, handles and notes are user input.
+fn csv_field(field: &str) -> String {
+    let field = if field.starts_with(['=', '+', '-', '@']) {
+        format!("'{}", field)
+    } else {
+        field.to_string()
+    };
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field
+    }
+}
+
+fn flags_page_query(query: &ListFlagsQuery) -> Result<FlagsPageQuery, AppError> {
+    let non_empty = |v: &Option<String>| {
+        v.as_deref()
+            .map(|s| s.trim().trim_start_matches('@'))
+            .filter(|s| !s.is_empty())
+            .map(str::to_string)
+    };
+    let mut artist = non_empty(&query.artist);
+    let mut did = non_empty(&query.did);
+    if did.is_none() && artist.as_deref().is_some_and(|a| a.starts_with("did:")) {
+        did = artist.take();
+    }
+
+    let sort = FlagSort::parse(&query.sort);
+    let cursor = query
+        .cursor
+        .as_deref()
+        .map(|c| {
+            FlagsCursor::parse(c, sort)
+                .ok_or_else(|| AppError::BadRequest(format!("invalid cursor for sort: {}", c)))
+        })
+        .transpose()?;
+
+    Ok(FlagsPageQuery {
+        status: FlagStatus::parse(&query.filter),
+        sort,
+        cursor,
+        limit: query.limit.clamp(1, 200),
+        artist,
+        did,
+        q: query
+            .q
+            .as_deref()
+            .map(str::trim)
+            .filter(|q| !q.is_empty())
+            .map(str::to_string),
+        from: query.from,
+        to: query.to,
+    })
+}
+
+/// Query string carrying a flags query's sort and search filters into
+/// follow-up requests.
+fn flag_list_params(query: &FlagsPageQuery) -> String {
+    format!(
+        "&amp;sort={}{}",
+        query.sort.as_str(),
+        search_filter_params(query)
+    )
+}
+
+/// Query string carrying a flags query's artist and text search filters.
+fn search_filter_params(query: &FlagsPageQuery) -> String {
+    let mut params = String::new();
+    if let Some(artist) = &query.artist {
+        params.push_str(&format!("&amp;artist={}", url_encode(artist)));
+    }
+    if let Some(did) = &query.did {
+        params.push_str(&format!("&amp;did={}", url_encode(did)));
+    }
+    if let Some(q) = &query.q {
+        params.push_str(&format!("&amp;q={}", url_encode(q)));
+    }
+    params
+}
+
+/// Get the full label history for a URI.
+///
+/// Unlike the flag list, this includes every negation and expired label so
+/// moderators can see how a URI that was flagged and cleared several times
+/// reached its current state.
+pub async fn uri_history(
+    State(state): State<AppState>,
+    Query(query): Query<UriHistoryQuery>,
+) -> Result<Json<UriHistoryResponse>, AppError> {
+    let db = state.db.as_ref().ok_or(AppError::LabelerNotConfigured)?;
+    Ok(Json(fetch_uri_history(db, query.uri).await?))
+}
+
+/// Render a URI's label history as an HTML timeline partial for htmx.
+pub async fn uri_history_html(
+    State(state): State<AppState>,
+    Query(query): Query<UriHistoryQuery>,
+) -> Result<Response, AppError> {
+    let db = state.db.as_ref().ok_or(AppError::LabelerNotConfigured)?;
+    let history = fetch_uri_history(db, query.uri).await?;
+    let html = render_uri_history(&history);
+    Ok(([(CONTENT_TYPE, "text/html; charset=utf-8")], html).into_response())
+}
+
+/// Get everything about one URI: labels, context, scans, reports, batch
+/// review decisions and audit entries.
+pub async fn flag_detail(
+    State(state): State<AppState>,
+    Query(query): Query<UriHistoryQuery>,
+) -> Result<Json<FlagDetailResponse>, AppError> {
+    let db = state.db.as_ref().ok_or(AppError::LabelerNotConfigured)?;
+    Ok(Json(fetch_flag_detail(db, query.uri).await?))
+}
 
-    let html = render_flags_list(&tracks, &query.filter);
+/// Render a URI's full detail as an HTML partial for htmx.
+pub async fn flag_detail_html(
+    State(state): State<AppState>,
+    Query(query): Query<UriHistoryQuery>,
+) -> Result<Response, AppError> {
+    let db = state.db.as_ref().ok_or(AppError::LabelerNotConfigured)?;
+    let detail = fetch_flag_detail(db, query.uri).await?;
+    let html = render_flag_detail(&detail);
+    Ok(([(CONTENT_TYPE, "text/html; charset=utf-8")], html).into_response())
+}
 
+/// Render a URI's review decisions as an HTML partial for htmx.
+pub async fn review_decisions_html(
+    State(state): State<AppState>,
+    Query(query): Query<UriHistoryQuery>,
+) -> Result<Response, AppError> {
+    let db = state.db.as_ref().ok_or(AppError::LabelerNotConfigured)?;
+    let decisions = db
+        .get_review_decisions(&[query.uri.trim().to_string()])
+        .await?;
+    let html = detail_list(&review_decision_items(&decisions), "no review decisions");
     Ok(([(CONTENT_TYPE, "text/html; charset=utf-8")], html).into_response())
 }
 
-/// Filter tracks based on filter parameter.
-fn filter_tracks(tracks: Vec<FlaggedTrack>, filter: &str) -> Vec<FlaggedTrack> {
-    match filter {
-        "resolved" => tracks.into_iter().filter(|t| t.resolved).collect(),
-        "all" => tracks,
-        _ => tracks.into_iter().filter(|t| !t.resolved).collect(), // "pending" is default
+/// Audit log entries included in a flag's detail.
+const DETAIL_AUDIT_LIMIT: i64 = 100;
+
+async fn fetch_flag_detail(
+    db: &crate::db::LabelDb,
+    uri: String,
+) -> Result<FlagDetailResponse, AppError> {
+    let history = fetch_uri_history(db, uri).await?;
+    let uri = history.uri.as_str();
+    let track_id = history.context.as_ref().and_then(|c| c.track_id);
+
+    let scans = db.get_audio_scans_for_uri(uri).await?;
+    let reports = db.get_reports_for_track(uri, track_id).await?;
+    let batch_decisions = db.get_batch_decisions(uri).await?;
+    let review_decisions = db
+        .get_review_decisions(std::slice::from_ref(&history.uri))
+        .await?;
+    let audit = db
+        .list_audit_log(None, Some(uri), None, DETAIL_AUDIT_LIMIT)
+        .await?;
+
+    Ok(FlagDetailResponse {
+        history,
+        scans,
+        reports,
+        batch_decisions,
+        review_decisions,
+        audit,
+    })
+}
+
+async fn fetch_uri_history(
+    db: &crate::db::LabelDb,
+    uri: String,
+) -> Result<UriHistoryResponse, AppError> {
+    let uri = uri.trim().to_string();
+    if uri.is_empty() {
+        return Err(AppError::BadRequest("uri is required".to_string()));
     }
+
+    let entries = db.get_label_history(&uri).await?;
+    let context = db.get_context(&uri).await?;
+    let current = current_label_values(&entries, chrono::Utc::now());
+
+    Ok(UriHistoryResponse {
+        uri,
+        current,
+        entries,
+        context,
+    })
 }
 
 /// Resolve (negate) a copyright flag, marking it as a false positive.
 pub async fn resolve_flag(
     State(state): State<AppState>,
+    _: RequireAdmin,
+    _: RateLimited,
+    actor: Actor,
     Json(request): Json<ResolveRequest>,
 ) -> Result<Json<ResolveResponse>, AppError> {
     let db = state.db.as_ref().ok_or(AppError::LabelerNotConfigured)?;
@@ -219,30 +809,89 @@ pub async fn resolve_flag(
         .as_ref()
         .ok_or(AppError::LabelerNotConfigured)?;
 
-    // Parse the reason
-    let reason = request
-        .reason
-        .as_deref()
-        .and_then(crate::db::ResolutionReason::from_str);
+    let reason = validate_reason(db, request.reason.as_deref()).await?;
+    check_resolution_notes(reason.as_ref(), request.notes.as_deref())?;
 
     tracing::info!(
         uri = %request.uri,
         val = %request.val,
         reason = ?reason,
         notes = ?request.notes,
+        actor = actor.as_str(),
         "resolving flag (creating negation)"
     );
 
     // Create a negation label
-    let label = crate::labels::Label::new(signer.did(), &request.uri, &request.val).negated();
+    let src = label_src(db, signer, &request.uri, &request.val).await?;
+    let label = crate::labels::Label::new(&src, &request.uri, &request.val).negated();
     let label = signer.sign_label(label)?;
 
-    let seq = db.store_label(&label).await?;
-
-    // Store resolution reason in context
-    if let Some(r) = reason {
-        db.store_resolution(&request.uri, r, request.notes.as_deref())
+    let (seq, closed_report) = match request.close_report_id {
+        Some(report_id) => {
+            let Some((seq, report)) = db
+                .store_negation_closing_report(
+                    &label,
+                    reason.as_ref(),
+                    request.notes.as_deref(),
+                    report_id,
+                    actor.as_str(),
+                )
+                .await?
+            else {
+                let report = db
+                    .get_report(report_id)
+                    .await?
+                    .ok_or_else(|| AppError::NotFound(format!("report {report_id} not found")))?;
+                return Err(AppError::Conflict(
+                    if report.target_uri.as_deref() != Some(request.uri.as_str()) {
+                        format!(
+                            "report {report_id} is not about {}; the flag was not resolved",
+                            request.uri
+                        )
+                    } else {
+                        format!(
+                            "report {report_id} is already {}; the flag was not resolved",
+                            report.status
+                        )
+                    },
+                ));
+            };
+            (seq, Some(report))
+        }
+        None => {
+            // Store the negation and resolution reason together
+            let seq = db
+                .store_negation(
+                    &label,
+                    reason.as_ref(),
+                    request.notes.as_deref(),
+                    actor.as_str(),
+                )
+                .await?;
+            db.record_audit_event(
+                "resolve_flag",
+                &request.uri,
+                actor.as_str(),
+                resolution_details(
+                    seq,
+                    &request.val,
+                    request.reason.as_deref(),
+                    request.notes.as_deref(),
+                ),
+            )
             .await?;
+            (seq, None)
+        }
+    };
+
+    if let Some(report) = &closed_report {
+        tracing::info!(
+            report_id = report.id,
+            uri = %request.uri,
+            seq,
+            "user report resolved with its flag"
+        );
+        crate::report_callback::notify_report_resolved(&state, report).await;
     }
 
     // Broadcast to subscribers
@@ -250,16 +899,35 @@ pub async fn resolve_flag(
         let _ = tx.send((seq, label));
     }
 
+    let message = match &closed_report {
+        Some(report) => format!(
+            "created negation label for {} and resolved report {}",
+            request.uri, report.id
+        ),
+        None => format!("created negation label for {}", request.uri),
+    };
     Ok(Json(ResolveResponse {
         seq,
-        message: format!("created negation label for {}", request.uri),
+        message,
+        closed_report_id: closed_report.map(|report| report.id),
     }))
 }
 
-/// Resolve flag and return HTML response for htmx.
-pub async fn resolve_flag_htmx(
+/// Resolve many flags at once with a shared reason and notes.
+///
+/// Each URI must have an active label for `val`; the rest are reported as
+/// failures. Negations for the resolvable URIs are stored in one transaction
+/// and then broadcast. With `atomic`, any failure means nothing is resolved.
+///
+/// Each distinct URI counts as one request against the caller's rate limit;
+/// a batch larger than the remaining allowance gets a 429 and resolves
+/// nothing.
+pub async fn resolve_bulk(
     State(state): State<AppState>,
-    axum::Form(request): axum::Form<ResolveRequest>,
+    _: RequireAdmin,
+    caller: Option<Extension<Caller>>,
+    actor: Actor,
+    Json(request): Json<BulkResolveRequest>,
 ) -> Result<Response, AppError> {
     let db = state.db.as_ref().ok_or(AppError::LabelerNotConfigured)?;
     let signer = state
@@ -267,75 +935,739 @@ pub async fn resolve_flag_htmx(
         .as_ref()
         .ok_or(AppError::LabelerNotConfigured)?;
 
-    // Parse the reason
-    let reason = request
-        .reason
-        .as_deref()
-        .and_then(crate::db::ResolutionReason::from_str);
+    if request.uris.is_empty() {
+        return Err(AppError::BadRequest("uris is empty".to_string()));
+    }
+    if request.uris.len() > MAX_BULK_RESOLVE {
+        return Err(AppError::BadRequest(format!(
+            "at most {} uris per bulk resolve",
+            MAX_BULK_RESOLVE
+        )));
+    }
+    if let Some(Extension(caller)) = &caller {
+        let cost = request.uris.iter().collect::<HashSet<_>>().len();
+        if let Err(retry_after) = state.rate_limiter.check_n(caller, cost, Instant::now()) {
+            return Ok(RateLimitExceeded { retry_after }.into_response());
+        }
+    }
+
+    let reason = validate_reason(db, request.reason.as_deref()).await?;
+    check_resolution_notes(reason.as_ref(), request.notes.as_deref())?;
+
+    let active: HashSet<String> = db
+        .get_active_label_values(&request.uris)
+        .await?
+        .into_iter()
+        .filter_map(|(uri, val)| (val == request.val).then_some(uri))
+        .collect();
+    let (to_resolve, mut outcomes) =
+        plan_bulk_resolve(&request.uris, &request.val, &active, request.atomic);
 
     tracing::info!(
-        uri = %request.uri,
+        requested = outcomes.len(),
+        resolvable = to_resolve.len(),
         val = %request.val,
         reason = ?reason,
-        notes = ?request.notes,
-        "resolving flag via htmx"
+        atomic = request.atomic,
+        actor = actor.as_str(),
+        "bulk resolving flags"
     );
 
-    // Create a negation label
-    let label = crate::labels::Label::new(signer.did(), &request.uri, &request.val).negated();
-    let label = signer.sign_label(label)?;
-
-    let seq = db.store_label(&label).await?;
-
-    // Store resolution reason in context
-    if let Some(r) = reason {
-        db.store_resolution(&request.uri, r, request.notes.as_deref())
-            .await?;
+    let mut labels = Vec::with_capacity(to_resolve.len());
+    for uri in &to_resolve {
+        let src = label_src(db, signer, uri, &request.val).await?;
+        let label = crate::labels::Label::new(&src, uri, &request.val).negated();
+        labels.push(signer.sign_label(label)?);
     }
+    let stored = db
+        .store_negations(&labels, reason.as_ref(), request.notes.as_deref(), request.atomic)
+        .await?;
 
-    // Broadcast to subscribers
-    if let Some(tx) = &state.label_tx {
-        let _ = tx.send((seq, label));
+    for (label, stored) in labels.into_iter().zip(stored) {
+        let Some(outcome) = outcomes.iter_mut().find(|o| o.uri == label.uri) else {
+            continue;
+        };
+        match stored {
+            StoredNegation::Created(seq) => {
+                outcome.resolved = true;
+                outcome.seq = Some(seq);
+                let mut details = resolution_details(
+                    seq,
+                    &request.val,
+                    request.reason.as_deref(),
+                    request.notes.as_deref(),
+                );
+                details["bulk"] = serde_json::json!(true);
+                db.record_audit_event("resolve_flag", &label.uri, actor.as_str(), details)
+                    .await?;
+                if let Some(tx) = &state.label_tx {
+                    let _ = tx.send((seq, label));
+                }
+            }
+            // resolved by someone else since the active check above
+            StoredNegation::NotActive => {
+                outcome.error = Some(format!("no active {} label", request.val));
+            }
+            StoredNegation::Skipped => {
+                outcome.error = Some("skipped: another uri failed (atomic)".to_string());
+            }
+        }
     }
 
-    // Return success toast + trigger refresh
-    let reason_label = reason.map(|r| r.label()).unwrap_or("unknown");
-    let html = format!(
-        r#"<div id="toast" class="toast success" hx-swap-oob="true">resolved: {} (seq: {})</div>"#,
-        reason_label, seq
-    );
+    let resolved_count = outcomes.iter().filter(|o| o.resolved).count();
+    Ok(Json(BulkResolveResponse {
+        resolved_count,
+        outcomes,
+    })
+    .into_response())
+}
 
-    Ok((
-        [(CONTENT_TYPE, "text/html; charset=utf-8")],
-        [(
-            axum::http::header::HeaderName::from_static("hx-trigger"),
-            "flagsUpdated",
-        )],
-        html,
-    )
-        .into_response())
+/// Split a bulk resolve into the URIs to negate and a pending outcome per
+/// distinct URI.
+///
+/// URIs without an active label fail. Under `atomic`, one failure means
+/// nothing is resolved and the otherwise-resolvable URIs are marked skipped.
+fn plan_bulk_resolve(
+    uris: &[String],
+    val: &str,
+    active: &HashSet<String>,
+    atomic: bool,
+) -> (Vec<String>, Vec<BulkResolveOutcome>) {
+    let mut seen = HashSet::new();
+    let mut outcomes: Vec<BulkResolveOutcome> = uris
+        .iter()
+        .filter(|uri| seen.insert(uri.as_str()))
+        .map(|uri| BulkResolveOutcome {
+            uri: uri.clone(),
+            resolved: false,
+            seq: None,
+            error: (!active.contains(uri)).then(|| format!("no active {} label", val)),
+        })
+        .collect();
+
+    let any_failed = outcomes.iter().any(|o| o.error.is_some());
+    if atomic && any_failed {
+        for outcome in outcomes.iter_mut().filter(|o| o.error.is_none()) {
+            outcome.error = Some("skipped: another uri failed (atomic)".to_string());
+        }
+        return (Vec::new(), outcomes);
+    }
+
+    let to_resolve = outcomes
+        .iter()
+        .filter(|o| o.error.is_none())
+        .map(|o| o.uri.clone())
+        .collect();
+    (to_resolve, outcomes)
 }
 
-/// Get which URIs have active (non-negated) copyright-violation labels.
+/// Negate one specific label by seq.
 ///
-/// Used by the backend to determine which tracks are still flagged.
-pub async fn get_active_labels(
+/// Unlike `resolve_flag`, which negates whatever `uri`+`val` is current, this
+/// copies the uri, val and cid from the exact label row, so a URI carrying
+/// several labels (e.g. one per record version) can be cleared one at a time.
+pub async fn negate_seq(
     State(state): State<AppState>,
-    Json(request): Json<ActiveLabelsRequest>,
-) -> Result<Json<ActiveLabelsResponse>, AppError> {
+    _: RequireAdmin,
+    _: RateLimited,
+    actor: Actor,
+    Json(request): Json<NegateSeqRequest>,
+) -> Result<Json<ResolveResponse>, AppError> {
     let db = state.db.as_ref().ok_or(AppError::LabelerNotConfigured)?;
+    let signer = state
+        .signer
+        .as_ref()
+        .ok_or(AppError::LabelerNotConfigured)?;
 
-    tracing::debug!(uri_count = request.uris.len(), "checking active labels");
+    let row = db
+        .get_label_by_seq(request.seq)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("no label with seq {}", request.seq)))?;
+    let label = negation_for(&row, signer)?;
 
-    let active_uris = db.get_active_labels(&request.uris).await?;
+    let reason = validate_reason(db, request.reason.as_deref()).await?;
+    check_resolution_notes(reason.as_ref(), request.notes.as_deref())?;
 
-    tracing::debug!(
-        active_count = active_uris.len(),
-        "returning active labels"
+    tracing::info!(
+        target_seq = row.seq,
+        uri = %row.uri,
+        val = %row.val,
+        reason = ?reason,
+        "negating label by seq"
     );
 
-    Ok(Json(ActiveLabelsResponse { active_uris }))
-}
+    let label = signer.sign_label(label)?;
+    // Store the negation and resolution reason together
+    let seq = db
+        .store_negation(
+            &label,
+            reason.as_ref(),
+            request.notes.as_deref(),
+            actor.as_str(),
+        )
+        .await?;
+
+    let mut details = resolution_details(
+        seq,
+        &row.val,
+        request.reason.as_deref(),
+        request.notes.as_deref(),
+    );
+    details["negated_seq"] = serde_json::json!(row.seq);
+    db.record_audit_event("negate_seq", &row.uri, actor.as_str(), details)
+        .await?;
+
+    if let Some(tx) = &state.label_tx {
+        let _ = tx.send((seq, label));
+    }
+
+    Ok(Json(ResolveResponse {
+        seq,
+        message: format!("created negation of seq {} for {}", row.seq, row.uri),
+        closed_report_id: None,
+    }))
+}
+
+/// Push stored labels after `since_seq` onto the replay channel.
+///
+/// Labels keep their original seq and signature, so a consumer that already
+/// applied one can drop the repeat by seq. Returns the seqs sent.
+async fn rebroadcast_labels(
+    db: &crate::db::LabelDb,
+    tx: &broadcast::Sender<(i64, crate::labels::Label)>,
+    since_seq: i64,
+    limit: i64,
+) -> Result<Vec<i64>, AppError> {
+    let rows = db.get_labels_since(since_seq, limit).await?;
+    let mut seqs = Vec::with_capacity(rows.len());
+    for row in &rows {
+        // no receivers is fine; nobody connected means nobody missed anything
+        let _ = tx.send((row.seq, row.to_label()));
+        seqs.push(row.seq);
+    }
+    Ok(seqs)
+}
+
+/// Re-send stored labels to connected `subscribeLabels` clients.
+///
+/// An operational escape hatch for subscribers that reconnected without a
+/// cursor, or after a fix that needs labels pushed again.
+pub async fn rebroadcast(
+    State(state): State<AppState>,
+    _: RequireAdmin,
+    _: RateLimited,
+    actor: Actor,
+    Json(request): Json<RebroadcastRequest>,
+) -> Result<Json<RebroadcastResponse>, AppError> {
+    let db = state.db.as_ref().ok_or(AppError::LabelerNotConfigured)?;
+    let tx = state
+        .label_replay_tx
+        .as_ref()
+        .ok_or(AppError::LabelerNotConfigured)?;
+    if request.since_seq < 0 {
+        return Err(AppError::BadRequest("since_seq must be >= 0".to_string()));
+    }
+    let limit = request
+        .limit
+        .unwrap_or(MAX_REBROADCAST)
+        .clamp(1, MAX_REBROADCAST);
+
+    let subscribers = tx.receiver_count();
+    let seqs = rebroadcast_labels(db, tx, request.since_seq, limit).await?;
+    let next_since_seq = match seqs.last() {
+        Some(&last) if seqs.len() as i64 == limit => Some(last),
+        _ => None,
+    };
+
+    tracing::info!(
+        since_seq = request.since_seq,
+        count = seqs.len(),
+        subscribers,
+        actor = actor.as_str(),
+        "rebroadcasting labels"
+    );
+    db.record_audit_event(
+        "rebroadcast_labels",
+        &request.since_seq.to_string(),
+        actor.as_str(),
+        serde_json::json!({
+            "since_seq": request.since_seq,
+            "count": seqs.len(),
+            "first_seq": seqs.first(),
+            "last_seq": seqs.last(),
+            "subscribers": subscribers,
+        }),
+    )
+    .await?;
+
+    Ok(Json(RebroadcastResponse {
+        seqs,
+        subscribers,
+        next_since_seq,
+    }))
+}
+
+/// Reject resolutions missing notes their reason requires.
+fn check_resolution_notes(
+    reason: Option<&ResolutionReason>,
+    notes: Option<&str>,
+) -> Result<(), AppError> {
+    let has_notes = notes.is_some_and(|n| !n.trim().is_empty());
+    match reason {
+        Some(r) if r.requires_notes() && !has_notes => Err(AppError::BadRequest(format!(
+            "{} resolutions need notes",
+            r.label()
+        ))),
+        _ => Ok(()),
+    }
+}
+
+/// Audit details for a flag resolution.
+fn resolution_details(
+    seq: i64,
+    val: &str,
+    reason: Option<&str>,
+    notes: Option<&str>,
+) -> serde_json::Value {
+    serde_json::json!({
+        "seq": seq,
+        "val": val,
+        "reason": reason,
+        "notes": notes,
+    })
+}
+
+/// Build an unsigned negation of exactly this label.
+///
+/// Only labels we issued (as the labeler or a namespace DID) can be negated,
+/// and negating a negation is refused.
+fn negation_for(
+    row: &crate::db::LabelRow,
+    signer: &crate::labels::LabelSigner,
+) -> Result<crate::labels::Label, AppError> {
+    if !signer.issues_as(&row.src) {
+        return Err(AppError::BadRequest(format!(
+            "seq {} was issued by {}, not this labeler",
+            row.seq, row.src
+        )));
+    }
+    if row.neg {
+        return Err(AppError::BadRequest(format!(
+            "seq {} is already a negation",
+            row.seq
+        )));
+    }
+
+    let mut label = crate::labels::Label::new(&row.src, &row.uri, &row.val).negated();
+    if let Some(cid) = &row.cid {
+        label = label.with_cid(cid);
+    }
+    Ok(label)
+}
+
+/// The DID to issue `uri`'s `val` label (or its negation) as: the source of
+/// the label in force, which may predate the URI's namespace DID, or the
+/// namespace DID for a brand-new label.
+pub(crate) async fn label_src(
+    db: &crate::db::LabelDb,
+    signer: &crate::labels::LabelSigner,
+    uri: &str,
+    val: &str,
+) -> Result<String, sqlx::Error> {
+    let active = db
+        .get_active_label_from(&signer.issuing_dids(), uri, val)
+        .await?;
+    Ok(active.map_or_else(|| signer.did_for(uri).to_string(), |row| row.src))
+}
+
+/// Resolve flag and return HTML response for htmx.
+pub async fn resolve_flag_htmx(
+    State(state): State<AppState>,
+    _: RequireAdmin,
+    _: RateLimited,
+    actor: Actor,
+    axum::Form(request): axum::Form<ResolveRequest>,
+) -> Result<Response, AppError> {
+    let db = state.db.as_ref().ok_or(AppError::LabelerNotConfigured)?;
+    let signer = state
+        .signer
+        .as_ref()
+        .ok_or(AppError::LabelerNotConfigured)?;
+
+    let reason = validate_reason(db, request.reason.as_deref()).await?;
+    check_resolution_notes(reason.as_ref(), request.notes.as_deref())?;
+
+    tracing::info!(
+        uri = %request.uri,
+        val = %request.val,
+        reason = ?reason,
+        notes = ?request.notes,
+        actor = actor.as_str(),
+        "resolving flag via htmx"
+    );
+
+    // The flag being resolved, so its card can be re-rendered below
+    let flag = db.get_flag(&request.uri).await?;
+
+    // Create a negation label
+    let src = label_src(db, signer, &request.uri, &request.val).await?;
+    let label = crate::labels::Label::new(&src, &request.uri, &request.val).negated();
+    let label = signer.sign_label(label)?;
+
+    // Store the negation and resolution reason together
+    let seq = db
+        .store_negation(
+            &label,
+            reason.as_ref(),
+            request.notes.as_deref(),
+            actor.as_str(),
+        )
+        .await?;
+
+    db.record_audit_event(
+        "resolve_flag",
+        &request.uri,
+        actor.as_str(),
+        resolution_details(
+            seq,
+            &request.val,
+            request.reason.as_deref(),
+            request.notes.as_deref(),
+        ),
+    )
+    .await?;
+
+    // Broadcast to subscribers
+    if let Some(tx) = &state.label_tx {
+        let _ = tx.send((seq, label));
+    }
+
+    // Return success toast + the resolved card, swapped in place
+    let card = match flag {
+        Some(flag) => db.get_flag_by_seq(flag.seq).await?,
+        None => None,
+    };
+    let reason_label = reason.as_ref().map(|r| r.label()).unwrap_or("unknown");
+    let html = format!(
+        r#"<div id="toast" class="toast success" hx-swap-oob="true">resolved: {} (seq: {})</div>
+        {}"#,
+        reason_label,
+        seq,
+        card.map(|card| flag_card_html(&card, true))
+            .unwrap_or_default()
+    );
+
+    Ok((
+        [(CONTENT_TYPE, "text/html; charset=utf-8")],
+        [(
+            axum::http::header::HeaderName::from_static("hx-trigger"),
+            "flagsUpdated",
+        )],
+        html,
+    )
+        .into_response())
+}
+
+/// Request to reverse a flag resolution.
+#[derive(Debug, Deserialize)]
+pub struct UnresolveRequest {
+    pub uri: String,
+    #[serde(default = "default_val")]
+    pub val: String,
+    /// Why the resolution is being reversed.
+    pub reason: String,
+}
+
+/// How long after a resolution the dashboard offers to undo it.
+const UNDO_GRACE_SECS: i64 = 10 * 60;
+
+/// Build a fresh flag to replace a negation.
+///
+/// Only a flag whose latest event is a negation can be unresolved; the new
+/// label keeps the original `cid`.
+fn unresolve_label(
+    latest: Option<&crate::db::LabelRow>,
+    uri: &str,
+    val: &str,
+) -> Result<crate::labels::Label, AppError> {
+    let row = latest.ok_or_else(|| AppError::NotFound(format!("no {} label for {}", val, uri)))?;
+    if !row.neg {
+        return Err(AppError::Conflict(format!(
+            "{} is not resolved (seq {} is not a negation)",
+            uri, row.seq
+        )));
+    }
+    let label = crate::labels::Label::new(&row.src, &row.uri, &row.val);
+    Ok(match &row.cid {
+        Some(cid) => label.with_cid(cid),
+        None => label,
+    })
+}
+
+/// Re-emit a resolved flag, clearing its resolution. Returns the new seq.
+async fn unresolve(
+    state: &AppState,
+    actor: &Actor,
+    request: &UnresolveRequest,
+) -> Result<i64, AppError> {
+    let db = state.db.as_ref().ok_or(AppError::LabelerNotConfigured)?;
+    let signer = state
+        .signer
+        .as_ref()
+        .ok_or(AppError::LabelerNotConfigured)?;
+
+    if request.reason.trim().is_empty() {
+        return Err(AppError::BadRequest("reason is required".to_string()));
+    }
+
+    let latest = db
+        .get_latest_label_from(&signer.issuing_dids(), &request.uri, &request.val)
+        .await?;
+    let label = unresolve_label(latest.as_ref(), &request.uri, &request.val)?;
+
+    tracing::info!(
+        uri = %request.uri,
+        val = %request.val,
+        reason = %request.reason,
+        actor = actor.as_str(),
+        "unresolving flag (re-emitting label)"
+    );
+
+    let negation_seq = latest.map(|row| row.seq).unwrap_or_default();
+    let label = signer.sign_label(label)?;
+    let seq = db
+        .store_unresolve(&label, negation_seq, actor.as_str(), &request.reason)
+        .await?
+        .ok_or_else(|| {
+            AppError::Conflict(format!(
+                "{} changed while unresolving (seq {} is no longer its latest event)",
+                request.uri, negation_seq
+            ))
+        })?;
+
+    if let Some(tx) = &state.label_tx {
+        let _ = tx.send((seq, label));
+    }
+
+    Ok(seq)
+}
+
+/// Reverse an accidental resolution by re-emitting the flag.
+///
+/// Returns 409 if the flag isn't currently resolved.
+pub async fn unresolve_flag(
+    State(state): State<AppState>,
+    _: RequireAdmin,
+    _: RateLimited,
+    actor: Actor,
+    Json(request): Json<UnresolveRequest>,
+) -> Result<Json<ResolveResponse>, AppError> {
+    let seq = unresolve(&state, &actor, &request).await?;
+    Ok(Json(ResolveResponse {
+        seq,
+        message: format!("re-emitted {} for {}", request.val, request.uri),
+        closed_report_id: None,
+    }))
+}
+
+/// Unresolve a flag and return HTML response for htmx.
+pub async fn unresolve_flag_htmx(
+    State(state): State<AppState>,
+    _: RequireAdmin,
+    _: RateLimited,
+    actor: Actor,
+    axum::Form(request): axum::Form<UnresolveRequest>,
+) -> Result<Response, AppError> {
+    let seq = unresolve(&state, &actor, &request).await?;
+    let html = format!(
+        r#"<div id="toast" class="toast success" hx-swap-oob="true">flag restored (seq: {})</div>"#,
+        seq
+    );
+
+    Ok((
+        [(CONTENT_TYPE, "text/html; charset=utf-8")],
+        [(
+            axum::http::header::HeaderName::from_static("hx-trigger"),
+            "flagsUpdated",
+        )],
+        html,
+    )
+        .into_response())
+}
+
+/// Whether the card should still offer to undo a resolution.
+fn undo_available(track: &FlaggedTrack, now: DateTime<Utc>) -> bool {
+    track
+        .resolved_at
+        .is_some_and(|at| (now - at).num_seconds() < UNDO_GRACE_SECS)
+}
+
+/// Minimum time between rescans of the same URI, to bound AuDD spend.
+const RESCAN_COOLDOWN_SECS: i64 = 60 * 60;
+
+/// Re-run the AuDD scan for a flag and return its refreshed card for htmx.
+///
+/// The fresh matches replace the stored ones. Each URI can be rescanned once
+/// per `RESCAN_COOLDOWN_SECS`, counted from when the rescan was requested, so
+/// a scan that fails still uses up the window.
+pub async fn rescan_flag(
+    State(state): State<AppState>,
+    _: RequireAdmin,
+    actor: Actor,
+    axum::Form(request): axum::Form<RescanRequest>,
+) -> Result<Response, AppError> {
+    let db = state.db.as_ref().ok_or(AppError::LabelerNotConfigured)?;
+    let not_found = || AppError::NotFound(format!("no active flag for {}", request.uri));
+
+    let flag = db.get_flag(&request.uri).await?.ok_or_else(not_found)?;
+    let audio_url = rescan_audio_url(request.audio_url.as_deref(), flag.context.as_ref())
+        .ok_or_else(|| AppError::BadRequest(format!("no audio url known for {}", request.uri)))?;
+
+    let cutoff = Utc::now() - chrono::Duration::seconds(RESCAN_COOLDOWN_SECS);
+    if !db.claim_rescan(&request.uri, cutoff).await? {
+        return Err(AppError::TooManyRequests(format!(
+            "{} was rescanned within the last hour",
+            request.uri
+        )));
+    }
+
+    tracing::info!(
+        uri = %request.uri,
+        audio_url = %audio_url,
+        actor = actor.as_str(),
+        "rescanning flag"
+    );
+
+    let scan = crate::audd::scan_url(&state, &audio_url).await?;
+    db.store_context(&request.uri, &rescan_context(&scan, &audio_url))
+        .await?;
+
+    db.record_audit_event(
+        "rescan_flag",
+        &request.uri,
+        actor.as_str(),
+        serde_json::json!({
+            "audio_url": audio_url,
+            "match_count": scan.matches.len(),
+            "is_flagged": scan.is_flagged,
+            "dominant_match": scan.dominant_match,
+            "dominant_match_pct": scan.dominant_match_pct,
+        }),
+    )
+    .await?;
+
+    let flag = db.get_flag(&request.uri).await?.ok_or_else(not_found)?;
+    let html = format!(
+        r#"{}
+        <div id="toast" class="toast success" hx-swap-oob="true">rescanned: {} matches</div>"#,
+        render_flag_card(&flag),
+        scan.matches.len()
+    );
+
+    Ok(([(CONTENT_TYPE, "text/html; charset=utf-8")], html).into_response())
+}
+
+/// Audio URL for a rescan: the one supplied, else the one in the context.
+fn rescan_audio_url(supplied: Option<&str>, context: Option<&LabelContext>) -> Option<String> {
+    supplied
+        .map(str::trim)
+        .filter(|url| !url.is_empty())
+        .map(str::to_string)
+        .or_else(|| context.and_then(|c| c.audio_url.clone()))
+}
+
+/// Context update from a rescan. Keeps the audio URL so later rescans don't
+/// need it supplied.
+fn rescan_context(scan: &ScanResponse, audio_url: &str) -> LabelContext {
+    let matches = scan
+        .matches
+        .iter()
+        .map(|m| CopyrightMatch {
+            title: m.title.clone(),
+            artist: m.artist.clone(),
+            score: normalize_score(f64::from(m.score)),
+        })
+        .collect();
+    LabelContext {
+        highest_score: Some(normalize_score(f64::from(scan.highest_score))),
+        matches: Some(matches),
+        audio_url: Some(audio_url.to_string()),
+        ..Default::default()
+    }
+}
+
+/// Confirm a flag as a real violation and ask the backend to enforce it.
+///
+/// Confirming again after enforcement failed retries the callback.
+pub async fn confirm_flag(
+    State(state): State<AppState>,
+    _: RequireAdmin,
+    _: RateLimited,
+    actor: Actor,
+    Json(request): Json<ConfirmRequest>,
+) -> Result<Json<ConfirmResponse>, AppError> {
+    let db = state.db.as_ref().ok_or(AppError::LabelerNotConfigured)?;
+
+    let active = db
+        .get_active_labels(std::slice::from_ref(&request.uri))
+        .await?;
+    if active.is_empty() {
+        return Err(AppError::NotFound(format!(
+            "no active flag for {}",
+            request.uri
+        )));
+    }
+
+    tracing::info!(
+        uri = %request.uri,
+        notes = ?request.notes,
+        actor = actor.as_str(),
+        "confirming flag as violation"
+    );
+
+    db.store_confirmation(&request.uri, actor.as_str(), request.notes.as_deref())
+        .await?;
+    db.record_audit_event(
+        "confirm_flag",
+        &request.uri,
+        actor.as_str(),
+        serde_json::json!({ "notes": request.notes }),
+    )
+    .await?;
+
+    let enforcement = enforce_confirmed(&state, db, &request.uri, actor.as_str()).await?;
+    let message = match enforcement {
+        Some(_) => format!("confirmed {}, enforcement requested", request.uri),
+        None => format!("confirmed {} (enforcement not configured)", request.uri),
+    };
+
+    Ok(Json(ConfirmResponse {
+        uri: request.uri,
+        enforcement: enforcement.map(|status| status.as_str()),
+        message,
+    }))
+}
+
+/// Get which URIs have active (non-negated) copyright-violation labels.
+///
+/// Used by the backend to determine which tracks are still flagged.
+pub async fn get_active_labels(
+    State(state): State<AppState>,
+    Json(request): Json<ActiveLabelsRequest>,
+) -> Result<Json<ActiveLabelsResponse>, AppError> {
+    let db = state.db.as_ref().ok_or(AppError::LabelerNotConfigured)?;
+
+    tracing::debug!(uri_count = request.uris.len(), "checking active labels");
+
+    let active_uris = db.get_active_labels(&request.uris).await?;
+
+    tracing::debug!(
+        active_count = active_uris.len(),
+        "returning active labels"
+    );
+
+    Ok(Json(ActiveLabelsResponse { active_uris }))
+}
 
 /// Get all current active label values for the requested URIs.
 ///
@@ -406,6 +1738,7 @@ pub async fn get_negated_labels(
 /// Store context for a label (for backfill without re-emitting labels).
 pub async fn store_context(
     State(state): State<AppState>,
+    _: RequireAdmin,
     Json(request): Json<StoreContextRequest>,
 ) -> Result<Json<StoreContextResponse>, AppError> {
     let db = state.db.as_ref().ok_or(AppError::LabelerNotConfigured)?;
@@ -421,6 +1754,9 @@ pub async fn store_context(
         matches: request.context.matches,
         resolution_reason: None,
         resolution_notes: None,
+        audio_url: request.context.audio_url,
+        confirmed_by: None,
+        confirmed_at: None,
     };
 
     db.store_context(&request.uri, &label_ctx).await?;
@@ -430,21 +1766,82 @@ pub async fn store_context(
     }))
 }
 
+/// Correct the track title, artist handle or track id stored for a labeled
+/// URI, e.g. when backfilled context went stale after a re-upload. Returns
+/// the updated context.
+pub async fn edit_context(
+    State(state): State<AppState>,
+    _: RequireAdmin,
+    actor: Actor,
+    Json(request): Json<EditContextRequest>,
+) -> Result<Json<LabelContext>, AppError> {
+    let db = state.db.as_ref().ok_or(AppError::LabelerNotConfigured)?;
+    let edit = context_edit(&request)?;
+
+    if db.get_label_history(&request.uri).await?.is_empty() {
+        return Err(AppError::NotFound(format!("no labels for {}", request.uri)));
+    }
+    let before = db.get_context(&request.uri).await?.unwrap_or_default();
+    db.store_context(&request.uri, &edit).await?;
+    let after = db.get_context(&request.uri).await?.unwrap_or_default();
+
+    db.record_audit_event(
+        "edit_context",
+        &request.uri,
+        actor.as_str(),
+        serde_json::json!({
+            "before": {
+                "track_title": before.track_title,
+                "artist_handle": before.artist_handle,
+                "track_id": before.track_id,
+            },
+            "after": {
+                "track_title": after.track_title,
+                "artist_handle": after.artist_handle,
+                "track_id": after.track_id,
+            },
+        }),
+    )
+    .await?;
+
+    Ok(Json(after))
+}
+
+/// The partial context an edit stores. Blank strings count as omitted, since
+/// `store_context` keeps whatever is stored for fields left unset.
+fn context_edit(request: &EditContextRequest) -> Result<LabelContext, AppError> {
+    let text = |value: &Option<String>| {
+        value
+            .as_deref()
+            .map(str::trim)
+            .filter(|v| !v.is_empty())
+            .map(str::to_string)
+    };
+    let edit = LabelContext {
+        track_title: text(&request.track_title),
+        artist_handle: text(&request.artist_handle).map(|h| h.trim_start_matches('@').to_string()),
+        track_id: request.track_id,
+        ..Default::default()
+    };
+    if edit.track_title.is_none() && edit.artist_handle.is_none() && edit.track_id.is_none() {
+        return Err(AppError::BadRequest(
+            "set at least one of track_title, artist_handle, track_id".to_string(),
+        ));
+    }
+    Ok(edit)
+}
+
 /// Create a review batch from pending flags.
 pub async fn create_batch(
     State(state): State<AppState>,
+    _: RequireAdmin,
     Json(request): Json<CreateBatchRequest>,
 ) -> Result<Json<CreateBatchResponse>, AppError> {
     let db = state.db.as_ref().ok_or(AppError::LabelerNotConfigured)?;
 
     // Get URIs to include
     let uris = if request.uris.is_empty() {
-        let pending = db.get_pending_flags().await?;
-        pending
-            .into_iter()
-            .filter(|t| !t.resolved)
-            .map(|t| t.uri)
-            .collect()
+        db.get_pending_flag_uris().await?
     } else {
         request.uris
     };
@@ -462,29 +1859,105 @@ pub async fn create_batch(
         "creating review batch"
     );
 
-    db.create_batch(&id, &uris, request.created_by.as_deref())
+    let expires_at = Utc::now() + state.review_batch_ttl;
+    db.create_batch(&id, &uris, request.created_by.as_deref(), expires_at)
         .await?;
 
-    let url = format!("/admin/review/{}", id);
+    let url = review_url(&id);
 
     Ok(Json(CreateBatchResponse { id, url, flag_count }))
 }
 
-/// Generate a short, URL-safe batch ID.
-fn generate_batch_id() -> String {
-    use std::time::{SystemTime, UNIX_EPOCH};
-    let now = SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .unwrap()
-        .as_millis();
-    let rand_part: u32 = rand::random();
-    format!("{:x}{:x}", (now as u64) & 0xFFFFFFFF, rand_part & 0xFFFF)
+/// Where a batch is reviewed.
+fn review_url(batch_id: &str) -> String {
+    format!("/admin/review/{}", batch_id)
 }
 
-/// Add a sensitive image entry.
-pub async fn add_sensitive_image(
+/// List review batches with their progress.
+pub async fn list_batches(
     State(state): State<AppState>,
-    Json(request): Json<AddSensitiveImageRequest>,
+    Query(query): Query<BatchListQuery>,
+) -> Result<Json<BatchListResponse>, AppError> {
+    let db = state.db.as_ref().ok_or(AppError::LabelerNotConfigured)?;
+    let batches = fetch_batches(db, &query).await?;
+    Ok(Json(BatchListResponse { batches }))
+}
+
+/// List review batches as HTML for htmx.
+pub async fn list_batches_html(
+    State(state): State<AppState>,
+    Query(query): Query<BatchListQuery>,
+) -> Result<Response, AppError> {
+    let db = state.db.as_ref().ok_or(AppError::LabelerNotConfigured)?;
+    let batches = fetch_batches(db, &query).await?;
+    let html = render_batches(&batches);
+    Ok(([(CONTENT_TYPE, "text/html; charset=utf-8")], html).into_response())
+}
+
+async fn fetch_batches(
+    db: &crate::db::LabelDb,
+    query: &BatchListQuery,
+) -> Result<Vec<BatchListItem>, AppError> {
+    Ok(db
+        .list_batches(query.limit.clamp(1, 200))
+        .await?
+        .into_iter()
+        .map(|batch| BatchListItem {
+            url: review_url(&batch.id),
+            batch,
+        })
+        .collect())
+}
+
+/// Delete an abandoned batch. Batches with submitted decisions are kept as
+/// the record of those decisions (409).
+pub async fn delete_batch(
+    State(state): State<AppState>,
+    _: RequireAdmin,
+    Path(batch_id): Path<String>,
+    actor: Actor,
+) -> Result<StatusCode, AppError> {
+    let db = state.db.as_ref().ok_or(AppError::LabelerNotConfigured)?;
+
+    if !db.delete_undecided_batch(&batch_id).await? {
+        return Err(match db.get_batch(&batch_id).await? {
+            Some(_) => AppError::Conflict(format!(
+                "batch {} has submitted decisions and can't be deleted",
+                batch_id
+            )),
+            None => AppError::NotFound("batch not found".to_string()),
+        });
+    }
+
+    tracing::info!(batch_id = %batch_id, actor = actor.as_str(), "deleted review batch");
+    db.record_audit_event(
+        "delete_batch",
+        &batch_id,
+        actor.as_str(),
+        serde_json::Value::Null,
+    )
+    .await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Generate a short, URL-safe batch ID.
+pub(crate) fn generate_batch_id() -> String {
+    use std::time::{SystemTime, UNIX_EPOCH};
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_millis();
+    let rand_part: u32 = rand::random();
+    format!("{:x}{:x}", (now as u64) & 0xFFFFFFFF, rand_part & 0xFFFF)
+}
+
+/// Add a sensitive image entry.
+pub async fn add_sensitive_image(
+    State(state): State<AppState>,
+    _: RequireAdmin,
+    actor: Actor,
+    Json(request): Json<AddSensitiveImageRequest>,
 ) -> Result<Json<AddSensitiveImageResponse>, AppError> {
     let db = state.db.as_ref().ok_or(AppError::LabelerNotConfigured)?;
 
@@ -509,18 +1982,84 @@ pub async fn add_sensitive_image(
             request.url.as_deref(),
             request.reason.as_deref(),
             request.flagged_by.as_deref(),
+            None,
+            &[],
         )
         .await?;
 
+    let target = request
+        .image_id
+        .as_deref()
+        .or(request.url.as_deref())
+        .unwrap_or_default();
+    db.record_audit_event(
+        "add_sensitive_image",
+        target,
+        actor.as_str(),
+        serde_json::json!({
+            "id": id,
+            "image_id": request.image_id,
+            "url": request.url,
+            "reason": request.reason,
+            "flagged_by": request.flagged_by,
+        }),
+    )
+    .await?;
+
     Ok(Json(AddSensitiveImageResponse {
         id,
         message: "sensitive image added".to_string(),
     }))
 }
 
+/// List sensitive images with metadata.
+///
+/// Unlike the public `/sensitive-images`, this includes reason, flagger, and
+/// timestamps so admins can review and prune the list.
+pub async fn list_sensitive_images(
+    State(state): State<AppState>,
+    Query(query): Query<SensitiveImagesQuery>,
+) -> Result<Json<SensitiveImagesPage>, AppError> {
+    let db = state.db.as_ref().ok_or(AppError::LabelerNotConfigured)?;
+    Ok(Json(fetch_sensitive_images(db, &query).await?))
+}
+
+/// Render sensitive images as HTML partial for htmx.
+pub async fn list_sensitive_images_html(
+    State(state): State<AppState>,
+    Query(query): Query<SensitiveImagesQuery>,
+) -> Result<Response, AppError> {
+    let db = state.db.as_ref().ok_or(AppError::LabelerNotConfigured)?;
+    let page = fetch_sensitive_images(db, &query).await?;
+    let sort = SensitiveImageSort::parse(&query.sort);
+    let html = render_sensitive_images(&page, query.flagged_by.as_deref(), sort);
+    Ok(([(CONTENT_TYPE, "text/html; charset=utf-8")], html).into_response())
+}
+
+async fn fetch_sensitive_images(
+    db: &crate::db::LabelDb,
+    query: &SensitiveImagesQuery,
+) -> Result<SensitiveImagesPage, AppError> {
+    let flagged_by = query.flagged_by.as_deref().filter(|s| !s.is_empty());
+    let limit = query.limit.clamp(1, 200);
+    let offset = query.offset.max(0);
+    let sort = SensitiveImageSort::parse(&query.sort);
+    let (images, total) = db
+        .list_sensitive_images(flagged_by, sort, limit, offset)
+        .await?;
+    Ok(SensitiveImagesPage {
+        images,
+        total,
+        limit,
+        offset,
+    })
+}
+
 /// Remove a sensitive image entry.
 pub async fn remove_sensitive_image(
     State(state): State<AppState>,
+    _: RequireAdmin,
+    actor: Actor,
     Json(request): Json<RemoveSensitiveImageRequest>,
 ) -> Result<Json<RemoveSensitiveImageResponse>, AppError> {
     let db = state.db.as_ref().ok_or(AppError::LabelerNotConfigured)?;
@@ -528,6 +2067,15 @@ pub async fn remove_sensitive_image(
     tracing::info!(id = request.id, "removing sensitive image");
 
     let removed = db.remove_sensitive_image(request.id).await?;
+    if removed {
+        db.record_audit_event(
+            "remove_sensitive_image",
+            &request.id.to_string(),
+            actor.as_str(),
+            serde_json::json!({ "id": request.id }),
+        )
+        .await?;
+    }
 
     let message = if removed {
         format!("sensitive image {} removed", request.id)
@@ -538,6 +2086,64 @@ pub async fn remove_sensitive_image(
     Ok(Json(RemoveSensitiveImageResponse { removed, message }))
 }
 
+/// Compare each label's signed `cts` against its `created_at`.
+///
+/// `cts` is what subscribers see; `created_at` is when we inserted the row.
+/// They diverge for backfilled labels, and a backfill can insert labels out of
+/// `cts` order relative to seq, which breaks subscribers that sort by `cts`.
+pub async fn label_timestamps(
+    State(state): State<AppState>,
+    Query(query): Query<LabelTimestampsQuery>,
+) -> Result<Json<LabelTimestampsResponse>, AppError> {
+    let db = state.db.as_ref().ok_or(AppError::LabelerNotConfigured)?;
+    let uri = query.uri.as_deref().filter(|s| !s.is_empty());
+    let labels = db
+        .get_label_timestamps(uri, query.min_skew_seconds.max(0), query.limit.clamp(1, 1000))
+        .await?;
+    let out_of_order_count = labels.iter().filter(|l| l.out_of_cts_order).count();
+    Ok(Json(LabelTimestampsResponse {
+        labels,
+        out_of_order_count,
+    }))
+}
+
+/// List recent manual moderation actions.
+///
+/// GET /admin/audit (also served as /admin/audit-log)
+pub async fn list_audit_log(
+    State(state): State<AppState>,
+    Query(query): Query<AuditLogQuery>,
+) -> Result<Json<AuditLogResponse>, AppError> {
+    let db = state.db.as_ref().ok_or(AppError::LabelerNotConfigured)?;
+    Ok(Json(fetch_audit_log(db, &query).await?))
+}
+
+/// Render recent audit log entries as HTML partial for htmx.
+pub async fn list_audit_log_html(
+    State(state): State<AppState>,
+    Query(query): Query<AuditLogQuery>,
+) -> Result<Response, AppError> {
+    let db = state.db.as_ref().ok_or(AppError::LabelerNotConfigured)?;
+    let response = fetch_audit_log(db, &query).await?;
+    let html = render_audit_log(&response.entries);
+    Ok(([(CONTENT_TYPE, "text/html; charset=utf-8")], html).into_response())
+}
+
+async fn fetch_audit_log(
+    db: &crate::db::LabelDb,
+    query: &AuditLogQuery,
+) -> Result<AuditLogResponse, AppError> {
+    let entries = db
+        .list_audit_log(
+            query.action.as_deref().filter(|s| !s.is_empty()),
+            query.target.as_deref().filter(|s| !s.is_empty()),
+            query.actor.as_deref().filter(|s| !s.is_empty()),
+            query.limit.clamp(1, 200),
+        )
+        .await?;
+    Ok(AuditLogResponse { entries })
+}
+
 /// Serve the admin UI HTML from static file.
 pub async fn admin_ui() -> Result<Response, AppError> {
     let html = tokio::fs::read_to_string("static/admin.html").await?;
@@ -545,36 +2151,92 @@ pub async fn admin_ui() -> Result<Response, AppError> {
 }
 
 /// Render the flags list as HTML with filter controls.
-fn render_flags_list(tracks: &[FlaggedTrack], current_filter: &str) -> String {
+fn render_flags_list(page: &FlagsPage, query: &FlagsPageQuery) -> String {
+    let current_filter = query.status.as_str();
     let pending_active = if current_filter == "pending" { " active" } else { "" };
     let resolved_active = if current_filter == "resolved" { " active" } else { "" };
+    let confirmed_active = if current_filter == "confirmed" { " active" } else { "" };
     let all_active = if current_filter == "all" { " active" } else { "" };
 
-    let count = tracks.len();
+    let count = page.total;
     let count_label = match current_filter {
         "pending" => format!("{} pending", count),
         "resolved" => format!("{} resolved", count),
+        "confirmed" => format!("{} confirmed", count),
         _ => format!("{} total", count),
     };
 
+    let list_params = flag_list_params(query);
+    let search_value = query.did.as_ref().or(query.artist.as_ref());
+    let search_params = search_filter_params(query);
+    let sort_options: String = [
+        (FlagSort::Newest, "newest first"),
+        (FlagSort::Oldest, "oldest first"),
+        (FlagSort::MostMatches, "most matches"),
+    ]
+    .iter()
+    .map(|(sort, label)| {
+        let selected = if *sort == query.sort { " selected" } else { "" };
+        format!(
+            "<option value=\"{}\"{}>{}</option>",
+            sort.as_str(),
+            selected,
+            label
+        )
+    })
+    .collect();
+    // resolved flags have nothing to select
+    let bulk_resolve = if query.status == FlagStatus::Resolved {
+        ""
+    } else {
+        "<div class=\"bulk-resolve\">\
+            <button type=\"button\" id=\"bulk-resolve-btn\" class=\"btn btn-warning\" onclick=\"showBulkReasonSelect(this)\" disabled>resolve selected</button>\
+        </div>"
+    };
     let filter_buttons = format!(
         "<div class=\"filter-row\">\
             <span class=\"filter-label\">show:</span>\
-            <button type=\"button\" class=\"filter-btn{}\" hx-get=\"/admin/flags-html?filter=pending\" hx-target=\"#flags-list\">pending</button>\
-            <button type=\"button\" class=\"filter-btn{}\" hx-get=\"/admin/flags-html?filter=resolved\" hx-target=\"#flags-list\">resolved</button>\
-            <button type=\"button\" class=\"filter-btn{}\" hx-get=\"/admin/flags-html?filter=all\" hx-target=\"#flags-list\">all</button>\
+            <button type=\"button\" class=\"filter-btn{}\" hx-get=\"/admin/flags-html?filter=pending{}\" hx-target=\"#flags-list\">pending</button>\
+            <button type=\"button\" class=\"filter-btn{}\" hx-get=\"/admin/flags-html?filter=resolved{}\" hx-target=\"#flags-list\">resolved</button>\
+            <button type=\"button\" class=\"filter-btn{}\" hx-get=\"/admin/flags-html?filter=confirmed{}\" hx-target=\"#flags-list\">confirmed</button>\
+            <button type=\"button\" class=\"filter-btn{}\" hx-get=\"/admin/flags-html?filter=all{}\" hx-target=\"#flags-list\">all</button>\
+            <select class=\"flag-sort\" name=\"sort\" hx-get=\"/admin/flags-html?filter={}{}\" hx-target=\"#flags-list\" hx-trigger=\"change\">{}</select>\
+            <form class=\"flag-search\" hx-get=\"/admin/flags-html\" hx-target=\"#flags-list\">\
+                <input type=\"hidden\" name=\"filter\" value=\"{}\">\
+                <input type=\"hidden\" name=\"sort\" value=\"{}\">\
+                <input type=\"search\" name=\"artist\" value=\"{}\" placeholder=\"artist handle or did\">\
+                <input type=\"search\" name=\"q\" value=\"{}\" placeholder=\"title or matched song\">\
+                <button type=\"submit\" class=\"filter-btn\">search</button>\
+            </form>\
+            {}\
             <span class=\"filter-count\">{}</span>\
         </div>",
         pending_active,
+        list_params,
         resolved_active,
+        list_params,
+        confirmed_active,
+        list_params,
         all_active,
+        list_params,
+        current_filter,
+        search_params,
+        sort_options,
+        current_filter,
+        query.sort.as_str(),
+        html_escape(search_value.map(String::as_str).unwrap_or_default()),
+        html_escape(query.q.as_deref().unwrap_or_default()),
+        bulk_resolve,
         count_label,
     );
 
-    if tracks.is_empty() {
+    if page.tracks.is_empty() {
         let empty_msg = match current_filter {
+            _ if query.q.is_some() => "no flags match this search",
+            _ if search_value.is_some() => "no flags for this artist",
             "pending" => "no pending flags",
             "resolved" => "no resolved flags",
+            "confirmed" => "no confirmed flags",
             _ => "no flagged tracks",
         };
         return format!(
@@ -583,16 +2245,27 @@ fn render_flags_list(tracks: &[FlaggedTrack], current_filter: &str) -> String {
         );
     }
 
-    let cards: Vec<String> = tracks.iter().map(render_flag_card).collect();
-    format!("{}\n{}", filter_buttons, cards.join("\n"))
+    format!("{}\n{}", filter_buttons, render_flags_page(page, query))
 }
 
-/// Extract namespace from AT URI (e.g., "fm.plyr.dev" from "at://did:plc:xxx/fm.plyr.dev.track/yyy")
-fn extract_namespace(uri: &str) -> Option<&str> {
-    // URI format: at://did:plc:xxx/fm.plyr[.env].track/rkey
-    let collection = uri.split('/').nth(3)?;
-    // Strip ".track" suffix to get namespace
-    collection.strip_suffix(".track")
+/// Render one page of flag cards, followed by a "load more" button that
+/// swaps itself for the next page.
+fn render_flags_page(page: &FlagsPage, query: &FlagsPageQuery) -> String {
+    let cards: Vec<String> = page.tracks.iter().map(render_flag_card).collect();
+    let load_more = page
+        .next_cursor
+        .as_deref()
+        .map(|cursor| {
+            format!(
+                r#"<button type="button" class="btn btn-secondary load-more" hx-get="/admin/flags-html?filter={}&amp;cursor={}&amp;limit={}{}" hx-target="this" hx-swap="outerHTML">load more</button>"#,
+                query.status.as_str(),
+                url_encode(cursor),
+                query.limit,
+                flag_list_params(query)
+            )
+        })
+        .unwrap_or_default();
+    format!("{}\n{}", cards.join("\n"), load_more)
 }
 
 /// Determine environment from namespace
@@ -606,7 +2279,18 @@ fn namespace_to_env(namespace: &str) -> Option<(&'static str, &'static str)> {
 }
 
 /// Render a single flag card as HTML.
-fn render_flag_card(track: &FlaggedTrack) -> String {
+pub(crate) fn render_flag_card(track: &FlaggedTrack) -> String {
+    flag_card_html(track, false)
+}
+
+/// DOM id of a flag's card, from the flag label's seq.
+fn flag_card_id(seq: i64) -> String {
+    format!("flag-{}", seq)
+}
+
+/// Render a flag card, optionally marked for htmx to swap out-of-band over
+/// the card with the same id.
+fn flag_card_html(track: &FlaggedTrack, swap_oob: bool) -> String {
     let ctx = track.context.as_ref();
     let has_context = ctx.is_some_and(|c| c.track_title.is_some() || c.artist_handle.is_some());
 
@@ -647,7 +2331,7 @@ fn render_flag_card(track: &FlaggedTrack) -> String {
     };
 
     // Add environment badge for non-production namespaces
-    let env_badge = extract_namespace(&track.uri)
+    let env_badge = crate::labels::uri_namespace(&track.uri)
         .and_then(namespace_to_env)
         .map(|(label, short)| {
             format!(
@@ -657,6 +2341,16 @@ fn render_flag_card(track: &FlaggedTrack) -> String {
         })
         .unwrap_or_default();
 
+    // Re-flags of the same URI are listed once, under the latest label
+    let flag_count_badge = if track.label_count > 1 {
+        format!(
+            r#"<span class="badge reflagged" title="open details for every label">flagged {} times</span>"#,
+            track.label_count
+        )
+    } else {
+        String::new()
+    };
+
     // Show match count instead of score (AuDD doesn't provide scores in accurate_offsets mode)
     let match_count_badge = ctx
         .and_then(|c| c.matches.as_ref())
@@ -669,12 +2363,42 @@ fn render_flag_card(track: &FlaggedTrack) -> String {
         })
         .unwrap_or_default();
 
-    let status_badge = if track.resolved {
-        r#"<span class="badge resolved">resolved</span>"#
+    let licensed =
+        ctx.and_then(|c| c.resolution_reason.as_ref()) == Some(&ResolutionReason::Licensed);
+    let confirmed_by = ctx.and_then(|c| c.confirmed_by.as_deref());
+    let status_badge = if track.resolved && licensed {
+        // Licensed flags stay listed but carry no enforcement
+        r#"<span class="badge licensed">licensed</span>"#.to_string()
+    } else if track.resolved {
+        r#"<span class="badge resolved">resolved</span>"#.to_string()
+    } else if let Some(by) = confirmed_by {
+        format!(
+            r#"<span class="badge confirmed" title="confirmed by {}">confirmed</span>"#,
+            html_escape(by)
+        )
     } else {
-        r#"<span class="badge pending">pending</span>"#
+        r#"<span class="badge pending">pending</span>"#.to_string()
     };
 
+    let enforcement = track
+        .enforcement
+        .as_deref()
+        .and_then(EnforcementStatus::from_str);
+    let enforcement_badge = enforcement
+        .map(|status| {
+            let text = match status {
+                EnforcementStatus::Pending => "enforcement pending",
+                EnforcementStatus::Sent => "enforcement sent",
+                EnforcementStatus::Failed => "enforcement failed",
+            };
+            format!(
+                r#"<span class="badge enforcement-{}">{}</span>"#,
+                status.as_str(),
+                text
+            )
+        })
+        .unwrap_or_default();
+
     let matches_html = ctx
         .and_then(|c| c.matches.as_ref())
         .filter(|m| !m.is_empty())
@@ -712,31 +2436,119 @@ fn render_flag_card(track: &FlaggedTrack) -> String {
             .and_then(|c| c.resolution_notes.as_ref())
             .map(|n| format!(r#"<div class="resolution-notes">{}</div>"#, html_escape(n)))
             .unwrap_or_default();
+        let undo_html = if undo_available(track, Utc::now()) {
+            let vals = serde_json::json!({
+                "uri": track.uri,
+                "val": track.val,
+                "reason": "undone from dashboard",
+            });
+            format!(
+                r#"<button type="button" class="btn btn-secondary"
+                        hx-post="/admin/unresolve-htmx"
+                        hx-vals="{}"
+                        hx-swap="none">
+                    undo
+                </button>"#,
+                html_escape(&vals.to_string())
+            )
+        } else {
+            String::new()
+        };
         format!(
             r#"<div class="resolution-info">
                 <span class="resolution-reason">{}</span>
                 {}
-            </div>"#,
-            reason_text, notes_html
+            </div>
+            {}"#,
+            reason_text, notes_html, undo_html
         )
     } else {
+        // Confirming again retries a failed enforcement; a pending or sent
+        // one needs no further action
+        let confirm_label = match enforcement {
+            None => Some("confirm violation"),
+            Some(EnforcementStatus::Failed) => Some("retry enforcement"),
+            Some(_) => None,
+        };
+        let confirm_html = confirm_label
+            .map(|label| {
+                format!(
+                    r#"<button type="button" class="btn btn-danger" data-uri="{}" onclick="confirmFlag(this)">
+                    {}
+                </button>"#,
+                    html_escape(&track.uri),
+                    label
+                )
+            })
+            .unwrap_or_default();
         // Multi-step flow: button -> reason select -> confirm
         format!(
             r#"<div class="resolve-flow" data-uri="{}" data-val="{}">
                 <button type="button" class="btn btn-warning" onclick="showReasonSelect(this)">
                     mark false positive
                 </button>
-            </div>"#,
+            </div>
+            {}"#,
             html_escape(&track.uri),
-            html_escape(&track.val)
+            html_escape(&track.val),
+            confirm_html
         )
     };
 
+    // Rescans need somewhere to fetch the audio from
+    let rescan_button = ctx
+        .and_then(|c| c.audio_url.as_ref())
+        .filter(|_| !track.resolved)
+        .map(|_| {
+            format!(
+                r#"<button type="button" class="btn btn-secondary"
+                        hx-post="/admin/flags/rescan"
+                        hx-vals="{}"
+                        hx-target="closest .flag-card"
+                        hx-swap="outerHTML">
+                    rescan
+                </button>"#,
+                html_escape(&serde_json::json!({ "uri": track.uri }).to_string())
+            )
+        })
+        .unwrap_or_default();
+
+    // Opens an inline form prefilled with the stored context
+    let stored_title = ctx.and_then(|c| c.track_title.as_deref());
+    let stored_handle = ctx.and_then(|c| c.artist_handle.as_deref());
+    let edit_button = format!(
+        r#"<div class="context-edit" data-uri="{}" data-title="{}" data-handle="{}" data-track-id="{}">
+                <button type="button" class="btn btn-secondary" onclick="showContextEdit(this)">edit</button>
+            </div>"#,
+        html_escape(&track.uri),
+        html_escape(stored_title.unwrap_or_default()),
+        html_escape(stored_handle.unwrap_or_default()),
+        ctx.and_then(|c| c.track_id)
+            .map(|id| id.to_string())
+            .unwrap_or_default()
+    );
+
+    let encoded_uri = html_escape(&url_encode(&track.uri));
     let resolved_class = if track.resolved { " resolved" } else { "" };
+    let oob_attr = if swap_oob {
+        r#" hx-swap-oob="true""#
+    } else {
+        ""
+    };
+    let select_box = if track.resolved {
+        String::new()
+    } else {
+        format!(
+            r#"<input type="checkbox" class="flag-select" value="{}" data-val="{}" onchange="updateBulkSelection()">"#,
+            html_escape(&track.uri),
+            html_escape(&track.val)
+        )
+    };
 
     format!(
-        r#"<div class="flag-card{}">
+        r##"<div class="flag-card{}" id="{}"{}>
             <div class="flag-header">
+                {}
                 <div class="track-info">
                     {}
                     <div class="uri">{}</div>
@@ -745,29 +2557,1323 @@ fn render_flag_card(track: &FlaggedTrack) -> String {
                     {}
                     {}
                     {}
+                    {}
+                    {}
                 </div>
             </div>
             {}
             <div class="flag-actions">
                 {}
+                {}
+                {}
+                <button type="button" class="btn btn-secondary"
+                        hx-get="/admin/uri-history-html?uri={}"
+                        hx-target="#uri-history">
+                    history
+                </button>
+                <button type="button" class="btn btn-secondary"
+                        hx-get="/admin/flags/detail-html?uri={}"
+                        hx-target="#uri-history">
+                    details
+                </button>
             </div>
-        </div>"#,
+            <details class="decision-history"
+                     hx-get="/admin/flags/decisions-html?uri={}"
+                     hx-trigger="toggle once"
+                     hx-target="find .decision-list">
+                <summary>review decisions</summary>
+                <div class="decision-list"></div>
+            </details>
+        </div>"##,
         resolved_class,
+        flag_card_id(track.seq),
+        oob_attr,
+        select_box,
         track_info,
         html_escape(&track.uri),
         env_badge,
+        flag_count_badge,
         match_count_badge,
         status_badge,
+        enforcement_badge,
         matches_html,
-        action_button
+        action_button,
+        rescan_button,
+        edit_button,
+        encoded_uri,
+        encoded_uri,
+        encoded_uri
+    )
+}
+
+/// Render a page of sensitive images with remove buttons and pagination.
+fn render_sensitive_images(
+    page: &SensitiveImagesPage,
+    flagged_by: Option<&str>,
+    sort: SensitiveImageSort,
+) -> String {
+    let header = format!(
+        r#"<div class="filter-row"><span class="filter-count">{} sensitive images</span></div>"#,
+        page.total
+    );
+
+    if page.images.is_empty() {
+        return format!(r#"{}<div class="empty">no sensitive images</div>"#, header);
+    }
+
+    let cards: Vec<String> = page
+        .images
+        .iter()
+        .map(|image| {
+            let target = match (&image.image_id, &image.url) {
+                (Some(id), _) => html_escape(id),
+                (None, Some(url)) => format!(
+                    r#"<a href="{0}" target="_blank" rel="noopener">{0}</a>"#,
+                    html_escape(url)
+                ),
+                (None, None) => "(no image id or url)".to_string(),
+            };
+            let reason = image
+                .reason
+                .as_deref()
+                .map(|r| format!(r#"<div class="scan-explanation">{}</div>"#, html_escape(r)))
+                .unwrap_or_default();
+            let severity_badge = image
+                .severity
+                .as_deref()
+                .map(|s| format!(r#"<span class="badge pending">{}</span>"#, html_escape(s)))
+                .unwrap_or_default();
+            let categories: Vec<String> = image
+                .violated_categories
+                .as_ref()
+                .and_then(|v| serde_json::from_value(v.clone()).ok())
+                .unwrap_or_default();
+            let categories_html = if categories.is_empty() {
+                String::new()
+            } else {
+                format!(
+                    r#"<div class="scan-categories">{}</div>"#,
+                    html_escape(&categories.join(", "))
+                )
+            };
+            format!(
+                r#"<div class="flag-card sensitive-card">
+            <div class="flag-header">
+                <div class="track-info">
+                    <div class="uri">{}</div>
+                    <div class="scan-meta">{} · {}</div>
+                </div>
+                <div class="flag-badges">
+                    {}
+                    <button type="button" class="btn btn-warning" data-id="{}" onclick="removeSensitiveImage(this)">
+                        remove
+                    </button>
+                </div>
+            </div>
+            {}
+            {}
+        </div>"#,
+                target,
+                image.flagged_at.format("%Y-%m-%d %H:%M UTC"),
+                html_escape(image.flagged_by.as_deref().unwrap_or("unknown")),
+                severity_badge,
+                image.id,
+                categories_html,
+                reason
+            )
+        })
+        .collect();
+
+    let filter_param = flagged_by
+        .filter(|f| !f.is_empty())
+        .map(|f| format!("&flagged_by={}", url_encode(f)))
+        .unwrap_or_default()
+        + &format!("&sort={}", sort.as_str());
+    let pager_button = |offset: i64, label: &str| {
+        format!(
+            r##"<button type="button" class="btn btn-secondary" hx-get="/admin/sensitive-images-html?limit={}&offset={}{}" hx-target="#sensitive-images-list">{}</button>"##,
+            page.limit,
+            offset,
+            html_escape(&filter_param),
+            label
+        )
+    };
+    let mut pager = Vec::new();
+    let (prev_label, next_label) = match sort {
+        SensitiveImageSort::Newest => ("newer", "older"),
+        SensitiveImageSort::Severity => ("previous", "next"),
+    };
+    if page.offset > 0 {
+        pager.push(pager_button((page.offset - page.limit).max(0), prev_label));
+    }
+    if page.offset + page.limit < page.total {
+        pager.push(pager_button(page.offset + page.limit, next_label));
+    }
+
+    format!(
+        r#"{}
+{}
+<div class="pager">{}</div>"#,
+        header,
+        cards.join("\n"),
+        pager.join("")
     )
 }
 
+/// Render a URI's label timeline.
+fn render_uri_history(history: &UriHistoryResponse) -> String {
+    let title = history
+        .context
+        .as_ref()
+        .and_then(|c| c.track_title.as_deref())
+        .map(|t| format!(r#"<div class="track-title">{}</div>"#, html_escape(t)))
+        .unwrap_or_default();
+
+    let current = if history.current.is_empty() {
+        r#"<span class="badge resolved">no active labels</span>"#.to_string()
+    } else {
+        history
+            .current
+            .iter()
+            .map(|v| format!(r#"<span class="badge pending">{}</span>"#, html_escape(v)))
+            .collect::<Vec<_>>()
+            .join(" ")
+    };
+
+    if history.entries.is_empty() {
+        return format!(
+            r#"<div class="history-header">{}<div class="uri">{}</div></div>
+<div class="empty">no labels for this uri</div>"#,
+            title,
+            html_escape(&history.uri)
+        );
+    }
+
+    // resolution context is stored per URI, so it's shown on the latest negation
+    let last_negation = history.entries.iter().rev().find(|e| e.neg).map(|e| e.seq);
+    let resolution = history.context.as_ref().and_then(|c| {
+        c.resolution_reason.as_ref().map(|r| {
+            let notes = c
+                .resolution_notes
+                .as_deref()
+                .map(|n| format!(r#"<div class="resolution-notes">{}</div>"#, html_escape(n)))
+                .unwrap_or_default();
+            format!(
+                r#"<div class="resolution-info"><span class="resolution-reason">{}</span>{}</div>"#,
+                r.label(),
+                notes
+            )
+        })
+    });
+
+    let now = chrono::Utc::now();
+    let items: Vec<String> = history
+        .entries
+        .iter()
+        .map(|entry| {
+            let action = if entry.neg {
+                r#"<span class="badge resolved">negated</span>"#
+            } else {
+                r#"<span class="badge pending">labeled</span>"#
+            };
+            let expiry = match entry.exp {
+                Some(exp) if crate::db::is_expired(Some(exp), now) => {
+                    format!(" · expired {}", exp.format("%Y-%m-%d %H:%M UTC"))
+                }
+                Some(exp) => format!(" · expires {}", exp.format("%Y-%m-%d %H:%M UTC")),
+                None => String::new(),
+            };
+            let reason = if Some(entry.seq) == last_negation {
+                resolution.clone().unwrap_or_default()
+            } else {
+                String::new()
+            };
+            format!(
+                r#"<li class="timeline-entry{}">
+                    <div class="timeline-head">
+                        {}
+                        <span class="timeline-val">{}</span>
+                        <span class="timeline-meta">#{} · {}{}</span>
+                    </div>
+                    {}
+                </li>"#,
+                if entry.neg { " negation" } else { "" },
+                action,
+                html_escape(&entry.val),
+                entry.seq,
+                entry.cts.format("%Y-%m-%d %H:%M UTC"),
+                expiry,
+                reason
+            )
+        })
+        .collect();
+
+    format!(
+        r#"<div class="history-header">
+    {}
+    <div class="uri">{}</div>
+    <div class="flag-badges">{}</div>
+</div>
+<ol class="timeline">
+{}
+</ol>"#,
+        title,
+        html_escape(&history.uri),
+        current,
+        items.join("\n")
+    )
+}
+
+/// Render audit log entries, newest first.
+fn render_audit_log(entries: &[AuditLogRow]) -> String {
+    if entries.is_empty() {
+        return r#"<div class="empty">no audit entries</div>"#.to_string();
+    }
+
+    let items: Vec<String> = entries
+        .iter()
+        .map(|entry| {
+            let details = entry
+                .details
+                .as_ref()
+                .filter(|d| !d.is_null())
+                .map(|d| {
+                    format!(
+                        r#"<div class="resolution-notes">{}</div>"#,
+                        html_escape(&d.to_string())
+                    )
+                })
+                .unwrap_or_default();
+            format!(
+                r#"<li class="timeline-entry">
+                    <div class="timeline-head">
+                        <span class="badge">{}</span>
+                        <span class="timeline-val">{}</span>
+                        <span class="timeline-meta">{} · {}</span>
+                    </div>
+                    {}
+                </li>"#,
+                html_escape(&entry.action),
+                html_escape(&entry.target),
+                html_escape(&entry.actor),
+                entry.created_at.format("%Y-%m-%d %H:%M UTC"),
+                details
+            )
+        })
+        .collect();
+
+    format!(r#"<ol class="timeline">{}</ol>"#, items.join("\n"))
+}
+
+/// Render review batches with their progress. Only batches without decisions
+/// can be deleted.
+fn render_batches(batches: &[BatchListItem]) -> String {
+    if batches.is_empty() {
+        return r#"<div class="empty">no review batches</div>"#.to_string();
+    }
+
+    let items: Vec<String> = batches
+        .iter()
+        .map(|item| {
+            let batch = &item.batch;
+            let status_class = match batch.status.as_str() {
+                "completed" => "resolved",
+                "expired" => "dismissed",
+                _ => "pending",
+            };
+            let expiry = match batch.expires_at {
+                Some(exp) if batch.status == "pending" => {
+                    format!(" · expires {}", exp.format("%Y-%m-%d %H:%M UTC"))
+                }
+                Some(exp) if batch.status == "expired" => {
+                    format!(" · expired {}", exp.format("%Y-%m-%d %H:%M UTC"))
+                }
+                _ => String::new(),
+            };
+            let claim = match &batch.assigned_to {
+                Some(reviewer) if batch.status == "pending" => {
+                    format!(" · claimed by {}", html_escape(reviewer))
+                }
+                _ => String::new(),
+            };
+            let delete_button = if batch.decided == 0 {
+                format!(
+                    r#"<button type="button" class="btn btn-secondary"
+                            hx-delete="/admin/batches/{}"
+                            hx-confirm="delete this batch?"
+                            hx-on::after-request="if (event.detail.successful) refreshBatchesList()">
+                        delete
+                    </button>"#,
+                    html_escape(&url_encode(&batch.id))
+                )
+            } else {
+                String::new()
+            };
+            format!(
+                r#"<li class="timeline-entry">
+                    <div class="timeline-head">
+                        <span class="badge {}">{}</span>
+                        <a class="timeline-val" href="{}" target="_blank" rel="noopener">{}</a>
+                        <span class="timeline-meta">{} · {}{}{}</span>
+                    </div>
+                    <div class="batch-progress">{} decided · {} pending · {} total</div>
+                    {}
+                </li>"#,
+                status_class,
+                html_escape(&batch.status),
+                html_escape(&item.url),
+                html_escape(&batch.id),
+                html_escape(batch.created_by.as_deref().unwrap_or("unknown")),
+                batch.created_at.format("%Y-%m-%d %H:%M UTC"),
+                expiry,
+                claim,
+                batch.decided,
+                batch.pending,
+                batch.total,
+                delete_button
+            )
+        })
+        .collect();
+
+    format!(r#"<ol class="timeline">{}</ol>"#, items.join("\n"))
+}
+
+/// Render a URI's full detail: the label timeline followed by one section
+/// per related record type.
+fn render_flag_detail(detail: &FlagDetailResponse) -> String {
+    let scans: Vec<String> = detail
+        .scans
+        .iter()
+        .map(|scan| {
+            let verdict = if scan.is_flagged {
+                r#"<span class="badge pending">flagged</span>"#
+            } else {
+                r#"<span class="badge resolved">clear</span>"#
+            };
+            let dominant = scan
+                .dominant_match
+                .as_deref()
+                .unwrap_or("no dominant match");
+            format!(
+                r#"<li class="timeline-entry">
+                    <div class="timeline-head">
+                        {}
+                        <span class="timeline-val">{}</span>
+                        <span class="timeline-meta">#{} · {}% dominant · {} matches · {}</span>
+                    </div>
+                </li>"#,
+                verdict,
+                html_escape(dominant),
+                scan.id,
+                scan.dominant_match_pct,
+                scan.matches.as_array().map_or(0, Vec::len),
+                scan.scanned_at.format("%Y-%m-%d %H:%M UTC")
+            )
+        })
+        .collect();
+
+    let reports: Vec<String> = detail
+        .reports
+        .iter()
+        .map(|report| {
+            let reporter = report
+                .reporter_handle
+                .as_deref()
+                .unwrap_or_else(|| display_reporter(&report.reporter_did));
+            let description = report
+                .description
+                .as_deref()
+                .map(|d| format!(r#"<div class="resolution-notes">{}</div>"#, html_escape(d)))
+                .unwrap_or_default();
+            format!(
+                r#"<li class="timeline-entry">
+                    <div class="timeline-head">
+                        <span class="badge">{}</span>
+                        <span class="timeline-val">{}</span>
+                        <span class="timeline-meta">report {} · by {} · {}</span>
+                    </div>
+                    {}
+                </li>"#,
+                html_escape(&report.status),
+                html_escape(&report.reason),
+                report.id,
+                html_escape(reporter),
+                report.created_at.format("%Y-%m-%d %H:%M UTC"),
+                description
+            )
+        })
+        .collect();
+
+    let batches: Vec<String> = detail
+        .batch_decisions
+        .iter()
+        .map(|entry| {
+            let reviewed_at = entry
+                .reviewed_at
+                .map(|at| format!(" · {}", at.format("%Y-%m-%d %H:%M UTC")))
+                .unwrap_or_default();
+            format!(
+                r#"<li class="timeline-entry">
+                    <div class="timeline-head">
+                        <span class="badge">{}</span>
+                        <span class="timeline-val">{}</span>
+                        <span class="timeline-meta">batch{}</span>
+                    </div>
+                </li>"#,
+                html_escape(entry.decision.as_deref().unwrap_or("not reviewed")),
+                html_escape(&entry.batch_id),
+                reviewed_at
+            )
+        })
+        .collect();
+
+    let decisions = review_decision_items(&detail.review_decisions);
+
+    format!(
+        r#"<div class="flag-detail">
+            {}
+            <h4 class="detail-heading">scans ({})</h4>
+            {}
+            <h4 class="detail-heading">reports ({})</h4>
+            {}
+            <h4 class="detail-heading">review batches ({})</h4>
+            {}
+            <h4 class="detail-heading">review decisions ({})</h4>
+            {}
+            <h4 class="detail-heading">audit log</h4>
+            {}
+        </div>"#,
+        render_uri_history(&detail.history),
+        scans.len(),
+        detail_list(&scans, "no stored scans"),
+        reports.len(),
+        detail_list(&reports, "no reports for this track"),
+        batches.len(),
+        detail_list(&batches, "not in any review batch"),
+        decisions.len(),
+        detail_list(&decisions, "no review decisions"),
+        render_audit_log(&detail.audit)
+    )
+}
+
+/// One timeline entry per review decision.
+fn review_decision_items(decisions: &[ReviewDecisionRow]) -> Vec<String> {
+    decisions
+        .iter()
+        .map(|d| {
+            let source = d
+                .batch_id
+                .as_deref()
+                .map(|id| format!("batch {}", html_escape(id)))
+                .unwrap_or_else(|| "dashboard".to_string());
+            let notes = d
+                .notes
+                .as_deref()
+                .map(|n| format!(r#"<div class="resolution-notes">{}</div>"#, html_escape(n)))
+                .unwrap_or_default();
+            format!(
+                r#"<li class="timeline-entry">
+                    <div class="timeline-head">
+                        <span class="badge">{}</span>
+                        <span class="timeline-val">{}</span>
+                        <span class="timeline-meta">{} · {}</span>
+                    </div>
+                    {}
+                </li>"#,
+                html_escape(&d.decision),
+                html_escape(&d.actor),
+                source,
+                d.created_at.format("%Y-%m-%d %H:%M UTC"),
+                notes
+            )
+        })
+        .collect()
+}
+
+/// A timeline of pre-rendered items, or a placeholder when there are none.
+fn detail_list(items: &[String], empty: &str) -> String {
+    if items.is_empty() {
+        format!(r#"<div class="empty">{}</div>"#, empty)
+    } else {
+        format!(r#"<ol class="timeline">{}</ol>"#, items.join("\n"))
+    }
+}
+
+/// Percent-encode a query parameter value.
+pub(crate) fn url_encode(s: &str) -> String {
+    s.bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
 /// Simple HTML escaping.
-fn html_escape(s: &str) -> String {
+pub(crate) fn html_escape(s: &str) -> String {
     s.replace('&', "&amp;")
         .replace('<', "&lt;")
         .replace('>', "&gt;")
         .replace('"', "&quot;")
         .replace('\'', "&#039;")
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::LabelRow;
+
+    const OUR_DID: &str = "did:plc:plyr-labeler";
+
+    fn signer() -> crate::labels::LabelSigner {
+        crate::labels::LabelSigner::from_hex(&format!("{:064x}", 1), OUR_DID)
+            .unwrap()
+            .with_namespace_dids(vec![(
+                "fm.plyr.dev".to_string(),
+                "did:plc:plyr-labeler-dev".to_string(),
+            )])
+    }
+
+    fn label_row(seq: i64, cid: &str) -> LabelRow {
+        LabelRow {
+            seq,
+            src: OUR_DID.to_string(),
+            uri: "at://did:plc:artist/fm.plyr.track/abc".to_string(),
+            cid: Some(cid.to_string()),
+            val: "copyright-violation".to_string(),
+            neg: false,
+            cts: chrono::Utc::now(),
+            exp: None,
+            sig: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_negation_for_specific_seq() {
+        // two labels on the same URI for different record versions
+        let first = label_row(10, "bafy-v1");
+        let second = label_row(11, "bafy-v2");
+
+        let negation = negation_for(&first, &signer()).unwrap();
+        assert_eq!(negation.neg, Some(true));
+        assert_eq!(negation.src, OUR_DID);
+        assert_eq!(negation.uri, first.uri);
+        assert_eq!(negation.val, first.val);
+        assert_eq!(negation.cid.as_deref(), Some("bafy-v1"));
+
+        let negation = negation_for(&second, &signer()).unwrap();
+        assert_eq!(negation.cid.as_deref(), Some("bafy-v2"));
+    }
+
+    #[test]
+    fn test_negation_for_rejects_foreign_and_negated_labels() {
+        let mut foreign = label_row(12, "bafy");
+        foreign.src = "did:plc:someone-else".to_string();
+        assert!(matches!(
+            negation_for(&foreign, &signer()),
+            Err(AppError::BadRequest(_))
+        ));
+
+        let mut negated = label_row(13, "bafy");
+        negated.neg = true;
+        assert!(matches!(
+            negation_for(&negated, &signer()),
+            Err(AppError::BadRequest(_))
+        ));
+    }
+
+    #[test]
+    fn test_negation_for_dev_label_keeps_dev_source() {
+        let mut dev = label_row(14, "bafy");
+        dev.uri = "at://did:plc:artist/fm.plyr.dev.track/abc".to_string();
+        dev.src = "did:plc:plyr-labeler-dev".to_string();
+
+        let negation = negation_for(&dev, &signer()).unwrap();
+        assert_eq!(negation.src, "did:plc:plyr-labeler-dev");
+        assert_eq!(signer().did_for(&dev.uri), negation.src);
+    }
+
+    #[test]
+    fn test_unresolve_pending_flag_conflicts() {
+        let pending = label_row(20, "bafy");
+        let err = unresolve_label(Some(&pending), &pending.uri, &pending.val).unwrap_err();
+        assert!(matches!(err, AppError::Conflict(_)));
+        assert_eq!(
+            err.into_response().status(),
+            axum::http::StatusCode::CONFLICT
+        );
+
+        assert!(matches!(
+            unresolve_label(None, &pending.uri, &pending.val),
+            Err(AppError::NotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_unresolve_reemits_negated_flag() {
+        let mut negation = label_row(21, "bafy");
+        negation.neg = true;
+        let label = unresolve_label(Some(&negation), &negation.uri, &negation.val).unwrap();
+        assert_eq!(label.src, OUR_DID);
+        assert_eq!(label.uri, negation.uri);
+        assert_eq!(label.val, "copyright-violation");
+        assert_eq!(label.cid.as_deref(), Some("bafy"));
+        assert_eq!(label.neg, None);
+    }
+
+    #[test]
+    fn test_undo_offered_within_grace_window() {
+        let now = Utc::now();
+        let mut track = FlaggedTrack {
+            seq: 1,
+            uri: "at://did:plc:artist/fm.plyr.track/abc".to_string(),
+            val: "copyright-violation".to_string(),
+            created_at: "2026-01-01 00:00:00".to_string(),
+            resolved: true,
+            resolved_at: Some(now - chrono::Duration::minutes(1)),
+            context: None,
+            enforcement: None,
+            label_count: 1,
+        };
+        assert!(undo_available(&track, now));
+        assert!(render_flag_card(&track).contains("/admin/unresolve-htmx"));
+
+        track.resolved_at = Some(now - chrono::Duration::seconds(UNDO_GRACE_SECS));
+        assert!(!undo_available(&track, now));
+        assert!(!render_flag_card(&track).contains("/admin/unresolve-htmx"));
+    }
+
+    fn uris(names: &[&str]) -> Vec<String> {
+        names
+            .iter()
+            .map(|n| format!("at://did:plc:artist/fm.plyr.track/{}", n))
+            .collect()
+    }
+
+    #[test]
+    fn test_bulk_resolve_partial_failure_keeps_others() {
+        let requested = uris(&["a", "b", "c"]);
+        // "b" was already resolved, so it has no active label
+        let active: HashSet<String> = uris(&["a", "c"]).into_iter().collect();
+
+        let (to_resolve, outcomes) =
+            plan_bulk_resolve(&requested, "copyright-violation", &active, false);
+        assert_eq!(to_resolve, uris(&["a", "c"]));
+        assert_eq!(outcomes.len(), 3);
+        assert!(outcomes[0].error.is_none());
+        assert_eq!(
+            outcomes[1].error.as_deref(),
+            Some("no active copyright-violation label")
+        );
+        assert!(outcomes[2].error.is_none());
+    }
+
+    #[test]
+    fn test_bulk_resolve_atomic_rolls_back_on_failure() {
+        let requested = uris(&["a", "b", "c"]);
+        let active: HashSet<String> = uris(&["a", "c"]).into_iter().collect();
+
+        let (to_resolve, outcomes) =
+            plan_bulk_resolve(&requested, "copyright-violation", &active, true);
+        assert!(to_resolve.is_empty());
+        assert!(outcomes.iter().all(|o| !o.resolved && o.error.is_some()));
+
+        // with nothing failing, atomic resolves everything
+        let all_active: HashSet<String> = requested.iter().cloned().collect();
+        let (to_resolve, _) =
+            plan_bulk_resolve(&requested, "copyright-violation", &all_active, true);
+        assert_eq!(to_resolve, requested);
+    }
+
+    #[test]
+    fn test_bulk_resolve_dedupes_uris() {
+        let requested = uris(&["a", "a", "b"]);
+        let active: HashSet<String> = requested.iter().cloned().collect();
+
+        let (to_resolve, outcomes) =
+            plan_bulk_resolve(&requested, "copyright-violation", &active, false);
+        assert_eq!(to_resolve, uris(&["a", "b"]));
+        assert_eq!(outcomes.len(), 2);
+    }
+
+    #[test]
+    fn test_render_sensitive_images_shows_severity() {
+        let image = |id, severity: Option<&str>| SensitiveImageRow {
+            id,
+            image_id: Some(format!("img-{}", id)),
+            url: None,
+            reason: Some("plain string reason".to_string()),
+            flagged_at: chrono::Utc::now(),
+            flagged_by: Some("claude-auto".to_string()),
+            severity: severity.map(str::to_string),
+            violated_categories: severity.map(|_| serde_json::json!(["graphic_violence"])),
+        };
+        let page = SensitiveImagesPage {
+            images: vec![image(1, Some("high")), image(2, None)],
+            total: 60,
+            limit: 50,
+            offset: 0,
+        };
+
+        let html = render_sensitive_images(&page, None, SensitiveImageSort::Severity);
+        assert!(html.contains(r#"<span class="badge pending">high</span>"#));
+        assert!(html.contains("graphic_violence"));
+        // legacy rows without severity still render their plain reason
+        assert_eq!(html.matches("plain string reason").count(), 2);
+        assert_eq!(html.matches("badge pending").count(), 1);
+        assert!(html.contains("&amp;sort=severity"));
+    }
+
+    /// Minimal RFC 4180 parser, to check the export round-trips.
+    fn parse_csv(input: &str) -> Vec<Vec<String>> {
+        let mut records = Vec::new();
+        let mut record = Vec::new();
+        let mut field = String::new();
+        let mut chars = input.chars().peekable();
+        let mut quoted = false;
+        while let Some(c) = chars.next() {
+            match (quoted, c) {
+                (true, '"') if chars.peek() == Some(&'"') => {
+                    chars.next();
+                    field.push('"');
+                }
+                (true, '"') => quoted = false,
+                (true, c) => field.push(c),
+                (false, '"') => quoted = true,
+                (false, ',') => record.push(std::mem::take(&mut field)),
+                (false, '\r') => {}
+                (false, '\n') => {
+                    record.push(std::mem::take(&mut field));
+                    records.push(std::mem::take(&mut record));
+                }
+                (false, c) => field.push(c),
+            }
+        }
+        records
+    }
+
+    #[test]
+    fn test_flags_csv_round_trips() {
+        let seeded = vec![
+            FlaggedTrack {
+                seq: 42,
+                uri: "at://did:plc:artist/fm.plyr.track/abc".to_string(),
+                val: "copyright-violation".to_string(),
+                created_at: "2026-01-02 03:04:05".to_string(),
+                resolved: true,
+                resolved_at: None,
+                context: Some(LabelContext {
+                    track_title: Some("Hello, \"World\"\nPart 2".to_string()),
+                    artist_handle: Some("artist.bsky.social".to_string()),
+                    matches: Some(vec![
+                        CopyrightMatch {
+                            title: "Low".to_string(),
+                            artist: "Someone".to_string(),
+                            score: 0.2,
+                        },
+                        CopyrightMatch {
+                            title: "Top, Song".to_string(),
+                            artist: "Band".to_string(),
+                            score: 0.9,
+                        },
+                    ]),
+                    resolution_reason: Some(ResolutionReason::Licensed),
+                    resolution_notes: Some("license on file\r\nsee ticket".to_string()),
+                    ..Default::default()
+                }),
+                enforcement: None,
+                label_count: 1,
+            },
+            FlaggedTrack {
+                seq: 7,
+                uri: "at://did:plc:other/fm.plyr.track/xyz".to_string(),
+                val: "copyright-violation".to_string(),
+                created_at: "2026-01-01 00:00:00".to_string(),
+                resolved: false,
+                resolved_at: None,
+                context: None,
+                enforcement: None,
+                label_count: 1,
+            },
+        ];
+
+        let mut csv = csv_record(&FLAG_CSV_HEADER);
+        for track in &seeded {
+            csv.push_str(&flag_csv_record(track));
+        }
+
+        let records = parse_csv(&csv);
+        assert_eq!(records.len(), 3);
+        assert_eq!(records[0], FLAG_CSV_HEADER);
+        assert_eq!(
+            records[1],
+            [
+                "42",
+                "at://did:plc:artist/fm.plyr.track/abc",
+                "Hello, \"World\"\nPart 2",
+                "artist.bsky.social",
+                "2026-01-02 03:04:05",
+                "true",
+                "licensed",
+                "license on file\r\nsee ticket",
+                "2",
+                "Top, Song - Band",
+            ]
+        );
+        assert_eq!(
+            records[2],
+            [
+                "7",
+                "at://did:plc:other/fm.plyr.track/xyz",
+                "",
+                "",
+                "2026-01-01 00:00:00",
+                "false",
+                "",
+                "",
+                "0",
+                "",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_flag_search_carries_into_pagination() {
+        let uri = "/admin/flags-html?filter=all&q=+drake+&sort=oldest"
+            .parse()
+            .unwrap();
+        let Query(query) = Query::<ListFlagsQuery>::try_from_uri(&uri).unwrap();
+        let page_query = flags_page_query(&query).unwrap();
+        assert_eq!(page_query.q.as_deref(), Some("drake"));
+
+        let page = FlagsPage {
+            tracks: Vec::new(),
+            total: 120,
+            next_cursor: Some("57".to_string()),
+        };
+        let html = render_flags_page(&page, &page_query);
+        assert!(html.contains("cursor=57"));
+        assert!(html.contains("&amp;sort=oldest&amp;q=drake"));
+
+        // a blank search is no search
+        let uri = "/admin/flags-html?q=++".parse().unwrap();
+        let Query(query) = Query::<ListFlagsQuery>::try_from_uri(&uri).unwrap();
+        assert_eq!(flags_page_query(&query).unwrap().q, None);
+    }
+
+    #[test]
+    fn test_csv_field_quoting() {
+        assert_eq!(csv_field("plain"), "plain");
+        assert_eq!(csv_field("a,b"), "\"a,b\"");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+        assert_eq!(csv_field("two\nlines"), "\"two\nlines\"");
+        assert_eq!(csv_record(&["a", "", "b c"]), "a,,b c\r\n");
+    }
+
+    #[test]
+    fn test_csv_field_neutralizes_formulas() {
+        assert_eq!(csv_field("=HYPERLINK(\"x\")"), "\"'=HYPERLINK(\"\"x\"\")\"");
+        assert_eq!(csv_field("+1"), "'+1");
+        assert_eq!(csv_field("-cmd"), "'-cmd");
+        assert_eq!(csv_field("@SUM(A1)"), "'@SUM(A1)");
+        // only a leading character counts
+        assert_eq!(csv_field("a=b"), "a=b");
+        assert_eq!(csv_field("artist.bsky.social"), "artist.bsky.social");
+    }
+
+    #[test]
+    fn test_export_respects_list_filters() {
+        let uri = "/admin/flags/export.csv?filter=all&artist=@Knock\
+                   &from=2026-01-01T00:00:00Z&to=2026-02-01T00:00:00Z"
+            .parse()
+            .unwrap();
+        let Query(query) = Query::<ListFlagsQuery>::try_from_uri(&uri).unwrap();
+        let page_query = flags_page_query(&query).unwrap();
+        assert_eq!(page_query.status, FlagStatus::All);
+        assert_eq!(page_query.artist.as_deref(), Some("Knock"));
+        let from = "2026-01-01T00:00:00Z".parse().ok();
+        let to = "2026-02-01T00:00:00Z".parse().ok();
+        assert_eq!((page_query.from, page_query.to), (from, to));
+    }
+
+    #[test]
+    fn test_flag_card_shows_enforcement_status() {
+        let card = |enforcement: Option<&str>| {
+            render_flag_card(&FlaggedTrack {
+                seq: 1,
+                uri: "at://did:plc:artist/fm.plyr.track/abc".to_string(),
+                val: "copyright-violation".to_string(),
+                created_at: "2026-01-01 00:00:00".to_string(),
+                resolved: false,
+                resolved_at: None,
+                context: None,
+                enforcement: enforcement.map(str::to_string),
+                label_count: 1,
+            })
+        };
+
+        let unconfirmed = card(None);
+        assert!(unconfirmed.contains("confirm violation"));
+        assert!(!unconfirmed.contains("enforcement-"));
+
+        let sent = card(Some("sent"));
+        assert!(sent.contains(r#"<span class="badge enforcement-sent">enforcement sent</span>"#));
+        assert!(!sent.contains("confirmFlag"));
+
+        let failed = card(Some("failed"));
+        assert!(failed.contains("enforcement failed"));
+        assert!(failed.contains("retry enforcement"));
+    }
+
+    #[test]
+    fn test_licensed_resolution_needs_notes() {
+        let licensed = Some(&ResolutionReason::Licensed);
+        assert!(check_resolution_notes(licensed, None).is_err());
+        assert!(check_resolution_notes(licensed, Some("  ")).is_err());
+        assert!(check_resolution_notes(licensed, Some("sync license, ticket 42")).is_ok());
+        assert!(check_resolution_notes(Some(&ResolutionReason::FingerprintNoise), None).is_ok());
+        assert!(check_resolution_notes(None, None).is_ok());
+    }
+
+    #[test]
+    fn test_flag_card_shows_licensed_badge() {
+        let card = |reason: ResolutionReason| {
+            render_flag_card(&FlaggedTrack {
+                seq: 1,
+                uri: "at://did:plc:artist/fm.plyr.track/abc".to_string(),
+                val: "copyright-violation".to_string(),
+                created_at: "2026-01-01 00:00:00".to_string(),
+                resolved: true,
+                resolved_at: None,
+                context: Some(LabelContext {
+                    matches: Some(vec![CopyrightMatch {
+                        title: "Song".to_string(),
+                        artist: "Band".to_string(),
+                        score: 0.9,
+                    }]),
+                    resolution_reason: Some(reason),
+                    resolution_notes: Some("sync license, ticket 42".to_string()),
+                    ..Default::default()
+                }),
+                enforcement: None,
+                label_count: 1,
+            })
+        };
+
+        let licensed = card(ResolutionReason::Licensed);
+        assert!(licensed.contains(r#"<span class="badge licensed">licensed</span>"#));
+        assert!(!licensed.contains("badge resolved"));
+        assert!(licensed.contains("sync license, ticket 42"));
+        // the matches stay visible for reference
+        assert!(licensed.contains("1 matches"));
+
+        let noise = card(ResolutionReason::FingerprintNoise);
+        assert!(noise.contains(r#"<span class="badge resolved">resolved</span>"#));
+        assert!(!noise.contains("badge licensed"));
+    }
+
+    #[test]
+    fn test_flag_card_shows_confirmation() {
+        let card = |resolved: bool| {
+            render_flag_card(&FlaggedTrack {
+                seq: 1,
+                uri: "at://did:plc:artist/fm.plyr.track/abc".to_string(),
+                val: "copyright-violation".to_string(),
+                created_at: "2026-01-01 00:00:00".to_string(),
+                resolved,
+                resolved_at: None,
+                context: Some(LabelContext {
+                    confirmed_by: Some("alice".to_string()),
+                    confirmed_at: Some(Utc::now()),
+                    ..Default::default()
+                }),
+                enforcement: None,
+                label_count: 1,
+            })
+        };
+
+        let confirmed = card(false);
+        assert!(confirmed.contains(
+            r#"<span class="badge confirmed" title="confirmed by alice">confirmed</span>"#
+        ));
+        assert!(!confirmed.contains("badge pending"));
+
+        // clearing it later wins over the earlier confirmation
+        let cleared = card(true);
+        assert!(cleared.contains("badge resolved"));
+        assert!(!cleared.contains("badge confirmed"));
+    }
+
+    #[test]
+    fn test_flag_card_shows_reflag_count() {
+        let card = |label_count: i64| {
+            render_flag_card(&FlaggedTrack {
+                seq: 3,
+                uri: "at://did:plc:artist/fm.plyr.track/abc".to_string(),
+                val: "copyright-violation".to_string(),
+                created_at: "2026-01-01 00:00:00".to_string(),
+                resolved: false,
+                resolved_at: None,
+                context: None,
+                enforcement: None,
+                label_count,
+            })
+        };
+
+        assert!(card(2).contains(">flagged 2 times</span>"));
+        assert!(!card(1).contains("badge reflagged"));
+    }
+
+    #[test]
+    fn test_rescan_audio_url_prefers_supplied() {
+        let context = LabelContext {
+            audio_url: Some("https://r2.plyr.fm/audio/stored.mp3".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            rescan_audio_url(Some(" https://cdn/other.mp3 "), Some(&context)).as_deref(),
+            Some("https://cdn/other.mp3")
+        );
+        assert_eq!(
+            rescan_audio_url(Some(""), Some(&context)).as_deref(),
+            Some("https://r2.plyr.fm/audio/stored.mp3")
+        );
+        assert_eq!(rescan_audio_url(None, None), None);
+    }
+
+    #[test]
+    fn test_rescan_context_replaces_matches() {
+        let scan = ScanResponse {
+            matches: vec![crate::audd::AuddMatch {
+                artist: "Band".to_string(),
+                title: "Song".to_string(),
+                album: None,
+                score: 85,
+                isrc: None,
+                timecode: None,
+                timecode_ms: None,
+                offset_ms: None,
+            }],
+            is_flagged: true,
+            dominant_match_pct: 100,
+            dominant_match: Some("Band - Song".to_string()),
+            sustained_song_count: 1,
+            highest_score: 0,
+            raw_response: serde_json::Value::Null,
+        };
+
+        let context = rescan_context(&scan, "https://r2.plyr.fm/audio/a.mp3");
+        let matches = context.matches.unwrap();
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].title, "Song");
+        assert_eq!(matches[0].score, 0.85);
+        assert_eq!(context.highest_score, Some(0.0));
+        assert_eq!(
+            context.audio_url.as_deref(),
+            Some("https://r2.plyr.fm/audio/a.mp3")
+        );
+        // untouched fields stay None so store_context keeps the stored values
+        assert!(context.track_title.is_none());
+    }
+
+    #[test]
+    fn test_flag_card_rescan_needs_audio_url() {
+        let mut track = FlaggedTrack {
+            seq: 1,
+            uri: "at://did:plc:artist/fm.plyr.track/abc".to_string(),
+            val: "copyright-violation".to_string(),
+            created_at: "2026-01-01 00:00:00".to_string(),
+            resolved: false,
+            resolved_at: None,
+            context: None,
+            enforcement: None,
+            label_count: 1,
+        };
+        assert!(!render_flag_card(&track).contains("/admin/flags/rescan"));
+
+        track.context = Some(LabelContext {
+            audio_url: Some("https://r2.plyr.fm/audio/a.mp3".to_string()),
+            ..Default::default()
+        });
+        let html = render_flag_card(&track);
+        assert!(html.contains(r#"hx-post="/admin/flags/rescan""#));
+        assert!(html.contains(
+            r#"hx-vals="{&quot;uri&quot;:&quot;at://did:plc:artist/fm.plyr.track/abc&quot;}""#
+        ));
+    }
+
+    #[test]
+    fn test_context_edit_keeps_only_set_fields() {
+        let request =
+            |title: Option<&str>, handle: Option<&str>, track_id: Option<i64>| EditContextRequest {
+                uri: "at://did:plc:artist/fm.plyr.track/abc".to_string(),
+                track_title: title.map(str::to_string),
+                artist_handle: handle.map(str::to_string),
+                track_id,
+            };
+
+        let edit = context_edit(&request(Some(" New Title "), Some("  "), None)).unwrap();
+        assert_eq!(edit.track_title.as_deref(), Some("New Title"));
+        assert!(edit.artist_handle.is_none());
+        assert!(edit.track_id.is_none());
+        assert!(edit.matches.is_none());
+
+        let edit = context_edit(&request(None, Some("@artist.bsky.social"), Some(7))).unwrap();
+        assert_eq!(edit.artist_handle.as_deref(), Some("artist.bsky.social"));
+        assert_eq!(edit.track_id, Some(7));
+
+        assert!(matches!(
+            context_edit(&request(Some(""), None, None)),
+            Err(AppError::BadRequest(_))
+        ));
+    }
+
+    #[test]
+    fn test_flag_card_offers_context_edit() {
+        let html = render_flag_card(&FlaggedTrack {
+            seq: 1,
+            uri: "at://did:plc:artist/fm.plyr.track/abc".to_string(),
+            val: "copyright-violation".to_string(),
+            created_at: "2026-01-01 00:00:00".to_string(),
+            resolved: true,
+            resolved_at: None,
+            context: Some(LabelContext {
+                track_title: Some("Old \"Title\"".to_string()),
+                track_id: Some(42),
+                ..Default::default()
+            }),
+            enforcement: None,
+            label_count: 1,
+        });
+        assert!(html.contains(r#"<div class="flag-card resolved" id="flag-1">"#));
+        assert!(html.contains(r#"onclick="showContextEdit(this)""#));
+        assert!(html.contains(r#"data-title="Old &quot;Title&quot;""#));
+        assert!(html.contains(r#"data-handle="""#));
+        assert!(html.contains(r#"data-track-id="42""#));
+        assert!(html.contains(
+            r#"hx-get="/admin/flags/decisions-html?uri=at%3A%2F%2Fdid%3Aplc%3Aartist%2Ffm.plyr.track%2Fabc""#
+        ));
+    }
+
+    #[test]
+    fn test_flag_detail_renders_sections() {
+        let uri = "at://did:plc:artist/fm.plyr.track/abc";
+        let detail = FlagDetailResponse {
+            history: UriHistoryResponse {
+                uri: uri.to_string(),
+                current: vec!["copyright-violation".to_string()],
+                entries: vec![],
+                context: None,
+            },
+            scans: vec![AudioScanRow {
+                id: 7,
+                uri: Some(uri.to_string()),
+                filename: Some("a.mp3".to_string()),
+                content_hash: "abc".to_string(),
+                size_bytes: 1024,
+                is_flagged: true,
+                dominant_match: Some("Song <A>".to_string()),
+                dominant_match_pct: 80,
+                sustained_song_count: 1,
+                matches: serde_json::json!([{}, {}]),
+                scanned_at: Utc::now(),
+            }],
+            reports: vec![],
+            batch_decisions: vec![BatchFlag {
+                id: 1,
+                batch_id: "batch-1".to_string(),
+                uri: uri.to_string(),
+                reviewed: true,
+                reviewed_at: None,
+                decision: Some("confirm".to_string()),
+            }],
+            review_decisions: vec![ReviewDecisionRow {
+                id: 1,
+                uri: uri.to_string(),
+                batch_id: None,
+                decision: "clear".to_string(),
+                actor: "alice".to_string(),
+                notes: Some("their own <release>".to_string()),
+                created_at: Utc::now(),
+            }],
+            audit: vec![],
+        };
+
+        let html = render_flag_detail(&detail);
+        assert!(html.contains("scans (1)"));
+        assert!(html.contains("review decisions (1)"));
+        assert!(html.contains("their own &lt;release&gt;"));
+        assert!(html.contains("Song &lt;A&gt;"));
+        assert!(html.contains("2 matches"));
+        assert!(html.contains("no reports for this track"));
+        assert!(html.contains("batch-1"));
+
+        let json = serde_json::to_value(&detail).unwrap();
+        assert_eq!(json["uri"], uri);
+        assert_eq!(json["scans"][0]["id"], 7);
+        assert_eq!(json["review_decisions"][0]["decision"], "clear");
+    }
+
+    #[test]
+    fn test_batch_list_only_deletes_undecided() {
+        let batch = |id: &str, decided: i64| BatchListItem {
+            batch: BatchSummary {
+                id: id.to_string(),
+                created_at: Utc::now(),
+                expires_at: None,
+                status: "pending".to_string(),
+                created_by: Some("alice".to_string()),
+                assigned_to: None,
+                total: 5,
+                decided,
+                pending: 5 - decided,
+            },
+            url: review_url(id),
+        };
+
+        let html = render_batches(&[batch("fresh", 0)]);
+        assert!(html.contains(r#"href="/admin/review/fresh""#));
+        assert!(html.contains("0 decided · 5 pending · 5 total"));
+        assert!(html.contains(r#"hx-delete="/admin/batches/fresh""#));
+
+        let html = render_batches(&[batch("started", 2)]);
+        assert!(html.contains("2 decided · 3 pending · 5 total"));
+        assert!(!html.contains("hx-delete"));
+
+        let json = serde_json::to_value(batch("fresh", 0)).unwrap();
+        assert_eq!(json["id"], "fresh");
+        assert_eq!(json["pending"], 5);
+        assert_eq!(json["url"], "/admin/review/fresh");
+
+        assert!(render_batches(&[]).contains("no review batches"));
+    }
+
+    #[test]
+    fn test_batch_list_shows_expiry() {
+        let expires_at = "2026-03-15T12:00:00Z".parse::<DateTime<Utc>>().unwrap();
+        let mut item = BatchListItem {
+            batch: BatchSummary {
+                id: "b1".to_string(),
+                created_at: expires_at - chrono::Duration::days(14),
+                expires_at: Some(expires_at),
+                status: "pending".to_string(),
+                created_by: None,
+                assigned_to: Some("bob".to_string()),
+                total: 3,
+                decided: 1,
+                pending: 2,
+            },
+            url: review_url("b1"),
+        };
+        let html = render_batches(std::slice::from_ref(&item));
+        assert!(html.contains("expires 2026-03-15 12:00 UTC"));
+        assert!(html.contains("claimed by bob"));
+
+        item.batch.status = "expired".to_string();
+        let html = render_batches(&[item]);
+        assert!(html.contains("expired 2026-03-15 12:00 UTC"));
+        assert!(html.contains(r#"<span class="badge dismissed">expired</span>"#));
+    }
+}
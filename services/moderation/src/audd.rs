@@ -1,18 +1,52 @@
 //! AuDD audio fingerprinting integration.
 
 use std::collections::{HashMap, HashSet};
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 
-use axum::{extract::State, Json};
+use axum::{
+    extract::{multipart::Field, Multipart, State},
+    http::{header, HeaderMap, StatusCode},
+    Json,
+};
 use serde::{Deserialize, Serialize};
-use tracing::info;
+use sha2::{Digest, Sha256};
+use tokio::io::AsyncWriteExt;
+use tokio_util::io::ReaderStream;
+use tracing::{info, warn};
 
+use crate::db::NewAudioScan;
+use crate::isrc::IsrcAllowlist;
 use crate::state::{AppError, AppState};
+use crate::transcoder::SampleRange;
+
+/// Largest audio file accepted by `/scan-audio`.
+pub const MAX_AUDIO_UPLOAD_BYTES: usize = 100 * 1024 * 1024;
+
+/// Request body limit for `/scan-audio`: the file plus multipart framing.
+pub const MAX_AUDIO_BODY_BYTES: usize = MAX_AUDIO_UPLOAD_BYTES + 64 * 1024;
+
+/// Attempts per recognition pass while AuDD is rate limiting us.
+const AUDD_MAX_ATTEMPTS: u32 = 3;
+
+/// Longest wait before retrying a rate-limited request. When AuDD asks for
+/// longer (e.g. the daily quota is spent), the scan fails instead of holding
+/// the request open.
+const AUDD_MAX_RETRY_WAIT: Duration = Duration::from_secs(30);
+
+/// AuDD `error_code`s for an exhausted request limit.
+const AUDD_LIMIT_ERROR_CODES: [i64; 2] = [901, 902];
 
 // --- request/response types ---
 
 #[derive(Debug, Deserialize)]
 pub struct ScanRequest {
     pub audio_url: String,
+    /// Recognize only this range of the audio, cut out by the transcoder.
+    /// Match offsets are then relative to the start of the sample.
+    #[serde(default)]
+    pub sample: Option<SampleRange>,
 }
 
 #[derive(Debug, Serialize)]
@@ -33,6 +67,14 @@ pub struct ScanResponse {
     pub raw_response: serde_json::Value,
 }
 
+#[derive(Debug, Serialize)]
+pub struct ScanAudioResponse {
+    /// Row id in `audio_scans`
+    pub scan_id: i64,
+    #[serde(flatten)]
+    pub scan: ScanResponse,
+}
+
 #[derive(Debug, Serialize, Clone)]
 pub struct AuddMatch {
     pub artist: String,
@@ -42,9 +84,14 @@ pub struct AuddMatch {
     pub score: i32,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub isrc: Option<String>,
+    /// Where in the reference song the match starts, as AuDD sent it.
+    /// Kept for display; `timecode_ms` is the same position in milliseconds.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub timecode: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    pub timecode_ms: Option<i64>,
+    /// Where in our track the matched segment starts, in milliseconds.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub offset_ms: Option<i64>,
 }
 
@@ -83,41 +130,168 @@ pub struct AuddSong {
     pub song_link: Option<String>,
 }
 
-// --- handler ---
+// --- handlers ---
 
 /// Scan audio for copyright matches via AuDD.
 pub async fn scan(
     State(state): State<AppState>,
     Json(request): Json<ScanRequest>,
 ) -> Result<Json<ScanResponse>, AppError> {
-    info!(audio_url = %request.audio_url, "scanning audio");
+    match &request.sample {
+        Some(range) => Ok(Json(scan_sample(&state, &request.audio_url, range).await?)),
+        None => Ok(Json(scan_url(&state, &request.audio_url).await?)),
+    }
+}
 
+/// Scan a range of the audio at a URL: the transcoder cuts the range out
+/// and the cut is uploaded to AuDD as a file.
+async fn scan_sample(
+    state: &AppState,
+    audio_url: &str,
+    range: &SampleRange,
+) -> Result<ScanResponse, AppError> {
+    range.validate()?;
+    let transcoder = state
+        .transcoder
+        .as_ref()
+        .ok_or(AppError::TranscoderNotConfigured)?;
+    info!(
+        audio_url,
+        start_secs = range.start_secs,
+        duration_secs = range.duration_secs,
+        "scanning audio sample"
+    );
+
+    let sample = transcoder.sample_url(audio_url, range).await?;
     let client = reqwest::Client::new();
-    let response = client
-        .post(&state.audd_api_url)
-        .form(&[
-            ("api_token", &state.audd_api_token),
-            ("url", &request.audio_url),
-            ("accurate_offsets", &"1".to_string()),
-        ])
-        .send()
-        .await
-        .map_err(|e| AppError::Audd(format!("request failed: {e}")))?;
+    let recognition = recognize(state.audd_fallback_enabled, |pass| {
+        let client = client.clone();
+        let (url, accurate_offsets) = audd_endpoint(state, pass);
+        let form = file_form(
+            state.audd_api_token.clone(),
+            accurate_offsets,
+            reqwest::multipart::Part::bytes(sample.clone()).file_name("sample.mp3"),
+        );
+        async move { send_audd(client.post(&url).multipart(form)).await }
+    })
+    .await?;
 
-    let raw_response: serde_json::Value = response
-        .json()
-        .await
-        .map_err(|e| AppError::Audd(format!("failed to parse response: {e}")))?;
+    Ok(evaluate(state, recognition))
+}
 
-    let audd_response: AuddResponse = serde_json::from_value(raw_response.clone())
-        .map_err(|e| AppError::Audd(format!("failed to parse audd response: {e}")))?;
+/// Scan audio at a URL (AuDD fetches it), without recording the result.
+pub async fn scan_url(state: &AppState, audio_url: &str) -> Result<ScanResponse, AppError> {
+    info!(audio_url, "scanning audio");
 
-    if audd_response.status.as_deref() == Some("error") {
-        return Err(AppError::Audd(format!(
-            "audd returned error: {}",
-            raw_response
-        )));
+    let client = reqwest::Client::new();
+    let recognition = recognize(state.audd_fallback_enabled, |pass| {
+        let client = client.clone();
+        let (url, accurate_offsets) = audd_endpoint(state, pass);
+        let form = [
+            ("api_token", state.audd_api_token.clone()),
+            ("url", audio_url.to_string()),
+            ("accurate_offsets", accurate_offsets.to_string()),
+        ];
+        async move { call_audd(&client, &url, &form).await }
+    })
+    .await?;
+
+    Ok(evaluate(state, recognition))
+}
+
+/// Scan an uploaded audio file (multipart `file` field) and record the result.
+///
+/// For clients with local files (e.g. the CLI): the upload is spooled to a
+/// temp file, sent to AuDD as a file upload, and the scan is stored in
+/// `audio_scans`. The temp file is removed however the request ends. An
+/// optional `uri` field ties the scan to a track, so it shows up in that
+/// flag's detail view.
+pub async fn scan_audio(
+    State(state): State<AppState>,
+    mut multipart: Multipart,
+) -> Result<Json<ScanAudioResponse>, AppError> {
+    let db = state.db.as_ref().ok_or(AppError::LabelerNotConfigured)?;
+
+    let spool = TempUpload::new();
+    let mut upload = None;
+    let mut uri = None;
+    while let Some(mut field) = multipart
+        .next_field()
+        .await
+        .map_err(|e| AppError::BadRequest(format!("multipart error: {e}")))?
+    {
+        match field.name() {
+            Some("file") if upload.is_none() => {
+                upload = Some(spool_upload(&mut field, spool.path()).await?);
+            }
+            Some("uri") => {
+                let value = field
+                    .text()
+                    .await
+                    .map_err(|e| AppError::BadRequest(format!("multipart error: {e}")))?;
+                uri = Some(value.trim().to_string()).filter(|v| !v.is_empty());
+            }
+            _ => {}
+        }
     }
+    let upload = upload.ok_or_else(|| AppError::BadRequest("missing 'file' field".to_string()))?;
+
+    info!(
+        filename = upload.filename.as_deref().unwrap_or("none"),
+        size = upload.size_bytes,
+        "scanning uploaded audio"
+    );
+
+    let client = reqwest::Client::new();
+    let recognition = recognize(state.audd_fallback_enabled, |pass| {
+        let client = client.clone();
+        let (url, accurate_offsets) = audd_endpoint(&state, pass);
+        let api_token = state.audd_api_token.clone();
+        let path = spool.path().to_path_buf();
+        let filename = upload
+            .filename
+            .clone()
+            .unwrap_or_else(|| "audio".to_string());
+        let size_bytes = upload.size_bytes as u64;
+        async move {
+            // stream the spooled file rather than holding it in memory
+            let file = tokio::fs::File::open(&path).await?;
+            let body = reqwest::Body::wrap_stream(ReaderStream::new(file));
+            let form = file_form(
+                api_token,
+                accurate_offsets,
+                reqwest::multipart::Part::stream_with_length(body, size_bytes).file_name(filename),
+            );
+            send_audd(client.post(&url).multipart(form)).await
+        }
+    })
+    .await?;
+
+    let scan = evaluate(&state, recognition);
+    let scan_id = db
+        .store_audio_scan(&NewAudioScan {
+            uri: uri.as_deref(),
+            filename: upload.filename.as_deref(),
+            content_hash: &upload.content_hash,
+            size_bytes: upload.size_bytes as i64,
+            is_flagged: scan.is_flagged,
+            dominant_match: scan.dominant_match.as_deref(),
+            dominant_match_pct: scan.dominant_match_pct,
+            sustained_song_count: scan.sustained_song_count,
+            matches: serde_json::to_value(&scan.matches).unwrap_or_default(),
+            raw_response: &scan.raw_response,
+        })
+        .await?;
+
+    Ok(Json(ScanAudioResponse { scan_id, scan }))
+}
+
+/// Score a recognition against the flagging thresholds.
+fn evaluate(state: &AppState, recognition: Recognition) -> ScanResponse {
+    let Recognition {
+        response: audd_response,
+        raw_response,
+    } = recognition;
 
     let matches = extract_matches(&audd_response);
     let (dominant_match, dominant_match_pct) = find_dominant_match(&matches);
@@ -130,19 +304,21 @@ pub async fn scan(
     // - mix/collage: several distinct songs are each sustained across
     //   multiple segments, though none dominates (Twitch/YouTube-style
     //   per-segment detection catches these; the dominant test alone cannot)
-    let is_flagged = dominant_match_pct >= state.copyright_score_threshold
+    let over_threshold = dominant_match_pct >= state.copyright_score_threshold
         || sustained_song_count >= state.copyright_mix_song_threshold;
+    let is_flagged = apply_isrc_allowlist(over_threshold, &matches, &state.isrc_allowlist);
 
     info!(
         match_count = matches.len(),
         dominant_match_pct,
         dominant_match = dominant_match.as_deref().unwrap_or("none"),
         sustained_song_count,
+        allowlisted = over_threshold && !is_flagged,
         is_flagged,
         "scan complete"
     );
 
-    Ok(Json(ScanResponse {
+    ScanResponse {
         matches,
         is_flagged,
         dominant_match_pct,
@@ -150,7 +326,285 @@ pub async fn scan(
         sustained_song_count,
         highest_score: 0, // AudD doesn't return scores
         raw_response,
-    }))
+    }
+}
+
+/// Drop a flag when every match is against a licensed catalog.
+fn apply_isrc_allowlist(
+    is_flagged: bool,
+    matches: &[AuddMatch],
+    allowlist: &IsrcAllowlist,
+) -> bool {
+    is_flagged && !allowlist.covers(matches)
+}
+
+// --- uploads ---
+
+/// An uploaded file spooled to disk.
+#[derive(Debug)]
+struct SpooledUpload {
+    filename: Option<String>,
+    size_bytes: usize,
+    /// SHA-256 of the file, hex-encoded
+    content_hash: String,
+}
+
+/// Temp file path that is removed when dropped.
+pub(crate) struct TempUpload {
+    path: PathBuf,
+}
+
+impl TempUpload {
+    pub(crate) fn new() -> Self {
+        let name = format!("moderation-audio-{:016x}", rand::random::<u64>());
+        Self {
+            path: std::env::temp_dir().join(name),
+        }
+    }
+
+    pub(crate) fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for TempUpload {
+    fn drop(&mut self) {
+        // the file may never have been created if the upload was rejected early
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+/// Stream a multipart field to `path`, enforcing `MAX_AUDIO_UPLOAD_BYTES`.
+async fn spool_upload(field: &mut Field<'_>, path: &Path) -> Result<SpooledUpload, AppError> {
+    let filename = field.file_name().map(str::to_string);
+    let mut file = tokio::fs::File::create(path).await?;
+    let mut hasher = Sha256::new();
+    let mut size_bytes = 0;
+
+    while let Some(chunk) = field
+        .chunk()
+        .await
+        .map_err(|e| AppError::BadRequest(format!("failed to read file: {e}")))?
+    {
+        size_bytes += chunk.len();
+        if size_bytes > MAX_AUDIO_UPLOAD_BYTES {
+            return Err(AppError::BadRequest(format!(
+                "audio exceeds {MAX_AUDIO_UPLOAD_BYTES} bytes"
+            )));
+        }
+        hasher.update(&chunk);
+        file.write_all(&chunk).await?;
+    }
+    file.flush().await?;
+
+    if size_bytes == 0 {
+        return Err(AppError::BadRequest("'file' is empty".to_string()));
+    }
+
+    Ok(SpooledUpload {
+        filename,
+        size_bytes,
+        content_hash: hex::encode(hasher.finalize()),
+    })
+}
+
+// --- recognition ---
+
+/// Which AuDD request a recognition attempt is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RecognitionPass {
+    Primary,
+    Fallback,
+}
+
+/// The AuDD response a scan is based on.
+struct Recognition {
+    response: AuddResponse,
+    raw_response: serde_json::Value,
+}
+
+/// AuDD endpoint and `accurate_offsets` setting for a recognition pass.
+fn audd_endpoint(state: &AppState, pass: RecognitionPass) -> (String, &'static str) {
+    match pass {
+        RecognitionPass::Primary => (state.audd_api_url.clone(), "1"),
+        // the fallback drops accurate_offsets, letting AuDD use its
+        // default matcher which sometimes finds what the strict one misses
+        RecognitionPass::Fallback => (state.audd_fallback_url.clone(), "0"),
+    }
+}
+
+/// Multipart form uploading `file` as the audio to recognize.
+fn file_form(
+    api_token: String,
+    accurate_offsets: &'static str,
+    file: reqwest::multipart::Part,
+) -> reqwest::multipart::Form {
+    reqwest::multipart::Form::new()
+        .text("api_token", api_token)
+        .text("accurate_offsets", accurate_offsets)
+        .part("file", file)
+}
+
+/// POST a recognition request and return the raw JSON body.
+async fn call_audd(
+    client: &reqwest::Client,
+    url: &str,
+    form: &[(&str, String)],
+) -> Result<serde_json::Value, AppError> {
+    send_audd(client.post(url).form(form)).await
+}
+
+/// Send a prepared recognition request and return the raw JSON body.
+///
+/// A 429 comes back as `AuddRateLimited`, carrying AuDD's `Retry-After`.
+async fn send_audd(request: reqwest::RequestBuilder) -> Result<serde_json::Value, AppError> {
+    let response = request
+        .send()
+        .await
+        .map_err(|e| AppError::Audd(format!("request failed: {e}")))?;
+
+    log_audd_quota(response.headers());
+    if response.status() == StatusCode::TOO_MANY_REQUESTS {
+        return Err(AppError::AuddRateLimited {
+            retry_after: retry_after(response.headers()),
+        });
+    }
+
+    response
+        .json()
+        .await
+        .map_err(|e| AppError::Audd(format!("failed to parse response: {e}")))
+}
+
+/// Log AuDD's remaining quota, when the response reports it.
+fn log_audd_quota(headers: &HeaderMap) {
+    let header = |name: &str| headers.get(name).and_then(|v| v.to_str().ok());
+    let remaining = header("x-ratelimit-remaining");
+    if remaining.is_some() {
+        info!(
+            remaining,
+            limit = header("x-ratelimit-limit"),
+            reset = header("x-ratelimit-reset"),
+            "audd quota"
+        );
+    }
+}
+
+/// `Retry-After` in seconds. The HTTP-date form isn't used by AuDD.
+fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    headers
+        .get(header::RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim()
+        .parse()
+        .ok()
+        .map(Duration::from_secs)
+}
+
+/// Parse a raw AuDD body, surfacing `status: error` as a failure.
+fn parse_audd_response(raw_response: &serde_json::Value) -> Result<AuddResponse, AppError> {
+    let audd_response: AuddResponse = serde_json::from_value(raw_response.clone())
+        .map_err(|e| AppError::Audd(format!("failed to parse audd response: {e}")))?;
+
+    if audd_response.status.as_deref() == Some("error") {
+        let code = raw_response["error"]["error_code"].as_i64();
+        if code.is_some_and(|code| AUDD_LIMIT_ERROR_CODES.contains(&code)) {
+            return Err(AppError::AuddRateLimited { retry_after: None });
+        }
+        return Err(AppError::Audd(format!(
+            "audd returned error: {}",
+            raw_response
+        )));
+    }
+
+    Ok(audd_response)
+}
+
+/// Wait before retry `attempt` (1-based) of a rate-limited request: AuDD's
+/// `Retry-After` when it sent one, else 1s, 2s, 4s, ... Up to half again is
+/// added from `jitter` (in `[0, 1)`), so workers limited together don't retry
+/// together. `None` when the wait would exceed `AUDD_MAX_RETRY_WAIT`.
+fn audd_retry_delay(attempt: u32, retry_after: Option<Duration>, jitter: f64) -> Option<Duration> {
+    let base = retry_after.unwrap_or_else(|| Duration::from_secs(1 << (attempt - 1).min(4)));
+    (base <= AUDD_MAX_RETRY_WAIT).then(|| base + base.mul_f64(jitter.clamp(0.0, 1.0) / 2.0))
+}
+
+/// Make one recognition call, retrying while AuDD is rate limiting us.
+async fn call_with_backoff<F, Fut>(
+    call: &mut F,
+    pass: RecognitionPass,
+) -> Result<(AuddResponse, serde_json::Value), AppError>
+where
+    F: FnMut(RecognitionPass) -> Fut,
+    Fut: Future<Output = Result<serde_json::Value, AppError>>,
+{
+    let mut attempt = 1;
+    loop {
+        let result = call(pass)
+            .await
+            .and_then(|raw| parse_audd_response(&raw).map(|parsed| (parsed, raw)));
+        let Err(AppError::AuddRateLimited { retry_after }) = &result else {
+            return result;
+        };
+        let delay = (attempt < AUDD_MAX_ATTEMPTS)
+            .then(|| audd_retry_delay(attempt, *retry_after, rand::random()))
+            .flatten();
+        let Some(delay) = delay else {
+            warn!(?pass, attempt, ?retry_after, "audd rate limited, giving up");
+            return result;
+        };
+        warn!(?pass, attempt, ?delay, "audd rate limited, backing off");
+        tokio::time::sleep(delay).await;
+        attempt += 1;
+    }
+}
+
+/// Run recognition, optionally retrying with the fallback pass when the
+/// primary succeeds but matches nothing.
+///
+/// A failed fallback never fails the scan — the (empty) primary result stands.
+async fn recognize<F, Fut>(fallback_enabled: bool, mut call: F) -> Result<Recognition, AppError>
+where
+    F: FnMut(RecognitionPass) -> Fut,
+    Fut: Future<Output = Result<serde_json::Value, AppError>>,
+{
+    let (response, raw_response) = call_with_backoff(&mut call, RecognitionPass::Primary).await?;
+
+    if !fallback_enabled || !extract_matches(&response).is_empty() {
+        return Ok(Recognition {
+            response,
+            raw_response,
+        });
+    }
+
+    info!("primary recognition found no matches, retrying with fallback");
+
+    let fallback = call_with_backoff(&mut call, RecognitionPass::Fallback).await;
+
+    match fallback {
+        Ok((fallback_response, fallback_raw)) => {
+            let match_count = extract_matches(&fallback_response).len();
+            if match_count == 0 {
+                return Ok(Recognition {
+                    response,
+                    raw_response,
+                });
+            }
+            info!(match_count, "fallback recognition produced matches");
+            Ok(Recognition {
+                response: fallback_response,
+                raw_response: fallback_raw,
+            })
+        }
+        Err(e) => {
+            warn!(error = %e, "fallback recognition failed, keeping primary result");
+            Ok(Recognition {
+                response,
+                raw_response,
+            })
+        }
+    }
 }
 
 // --- helpers ---
@@ -194,26 +648,44 @@ fn parse_song(song: &AuddSong, offset: Option<&serde_json::Value>) -> AuddMatch
         score: song.score.unwrap_or(0),
         isrc: song.isrc.clone(),
         timecode: song.timecode.clone(),
+        timecode_ms: song.timecode.as_deref().and_then(parse_timecode_to_ms),
         offset_ms,
     }
 }
 
+/// Parse an AuDD timecode to milliseconds: `ss`, `mm:ss` or `hh:mm:ss`, with
+/// optionally fractional seconds (`01:02.5`).
 fn parse_timecode_to_ms(timecode: &str) -> Option<i64> {
-    let parts: Vec<&str> = timecode.split(':').collect();
-    match parts.len() {
-        2 => {
-            let mins: i64 = parts[0].parse().ok()?;
-            let secs: i64 = parts[1].parse().ok()?;
-            Some((mins * 60 + secs) * 1000)
+    let number = |s: &str| -> Option<i64> {
+        if s.is_empty() || !s.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
         }
-        3 => {
-            let hours: i64 = parts[0].parse().ok()?;
-            let mins: i64 = parts[1].parse().ok()?;
-            let secs: i64 = parts[2].parse().ok()?;
-            Some((hours * 3600 + mins * 60 + secs) * 1000)
+        s.parse().ok()
+    };
+
+    let mut parts = timecode.trim().rsplit(':');
+    let secs = parts.next()?;
+    let (whole, fraction) = match secs.split_once('.') {
+        Some((whole, fraction)) => (whole, Some(fraction)),
+        None => (secs, None),
+    };
+    // checked throughout: a timecode too large for i64 is as unparseable
+    // as a malformed one
+    let mut ms = number(whole)?.checked_mul(1000)?;
+    if let Some(fraction) = fraction {
+        // milliseconds are the first three digits of the fraction, padded
+        number(fraction)?;
+        let millis: String = fraction.chars().chain("00".chars()).take(3).collect();
+        ms = ms.checked_add(number(&millis)?)?;
+    }
+    for unit in [60_000, 3_600_000] {
+        match parts.next() {
+            Some(part) => ms = ms.checked_add(number(part)?.checked_mul(unit)?)?,
+            None => return Some(ms),
         }
-        _ => None,
     }
+    // more than hours
+    parts.next().is_none().then_some(ms)
 }
 
 /// Minimum distinct segments a song must match at to count as sustained.
@@ -282,10 +754,80 @@ mod tests {
             score: 0,
             isrc: None,
             timecode: Some(timecode.to_string()),
+            timecode_ms: parse_timecode_to_ms(timecode),
             offset_ms: None,
         }
     }
 
+    #[test]
+    fn timecodes_parse_to_milliseconds() {
+        for (timecode, ms) in [
+            ("00:30", Some(30_000)),
+            ("1:05", Some(65_000)),
+            ("12:00", Some(720_000)),
+            ("01:02:03", Some(3_723_000)),
+            ("45", Some(45_000)),
+            ("00:30.5", Some(30_500)),
+            ("00:30.25", Some(30_250)),
+            ("00:30.1234", Some(30_123)),
+            (" 02:00 ", Some(120_000)),
+            ("", None),
+            ("00:", None),
+            ("-1:00", None),
+            ("00:3x", None),
+            ("00:30.", None),
+            ("1:00:00:00", None),
+            ("9223372036854775807", None),
+            ("2562047788016:00:00", None),
+            ("0:153722867280912:55.808", None),
+        ] {
+            assert_eq!(parse_timecode_to_ms(timecode), ms, "{timecode:?}");
+        }
+    }
+
+    #[test]
+    fn matches_carry_both_offsets_in_milliseconds() {
+        let response: AuddResponse = serde_json::from_value(serde_json::json!({
+            "status": "success",
+            "result": [
+                {
+                    "offset": "01:30",
+                    "songs": [{ "artist": "A", "title": "Song", "timecode": "00:42" }]
+                },
+                {
+                    "offset": 12000,
+                    "songs": [{ "artist": "B", "title": "Song", "timecode": "1:02:03.5" }]
+                },
+                {
+                    "offset": null,
+                    "songs": [{ "artist": "C", "title": "Song", "timecode": "soon" }]
+                }
+            ]
+        }))
+        .unwrap();
+
+        let matches = extract_matches(&response);
+        let offsets: Vec<_> = matches
+            .iter()
+            .map(|m| (m.offset_ms, m.timecode.as_deref(), m.timecode_ms))
+            .collect();
+        assert_eq!(
+            offsets,
+            [
+                (Some(90_000), Some("00:42"), Some(42_000)),
+                (Some(12_000), Some("1:02:03.5"), Some(3_723_500)),
+                (None, Some("soon"), None),
+            ]
+        );
+
+        let json = serde_json::to_value(&matches[0]).unwrap();
+        assert_eq!(json["timecode"], "00:42");
+        assert_eq!(json["timecode_ms"], 42_000);
+        assert_eq!(json["offset_ms"], 90_000);
+        let unparsed = serde_json::to_value(&matches[2]).unwrap();
+        assert!(unparsed.get("timecode_ms").is_none());
+    }
+
     #[test]
     fn mix_of_sustained_songs_is_counted() {
         // a DJ mix: four songs, each recognized at three distinct positions,
@@ -321,6 +863,26 @@ mod tests {
         assert_eq!(pct, 100);
     }
 
+    #[test]
+    fn allowlisted_isrc_match_is_not_flagged() {
+        let allowlist = IsrcAllowlist::new(["USRC17607839".to_string()]);
+        let licensed = |t: &str| AuddMatch {
+            isrc: Some("USRC17607839".to_string()),
+            ..m("Licensed Artist", "Licensed Song", t)
+        };
+        let rip: Vec<AuddMatch> = (0..10).map(|i| licensed(&format!("00:{i:02}"))).collect();
+        let (_, pct) = find_dominant_match(&rip);
+        assert_eq!(pct, 100);
+        assert!(!apply_isrc_allowlist(true, &rip, &allowlist));
+
+        // anything off the allowlist (or without an ISRC) keeps the flag
+        let mut mixed = rip.clone();
+        mixed.push(m("Other Artist", "Other Song", "00:30"));
+        assert!(apply_isrc_allowlist(true, &mixed, &allowlist));
+        mixed.last_mut().unwrap().isrc = Some("GBAYE0000351".to_string());
+        assert!(apply_isrc_allowlist(true, &mixed, &allowlist));
+    }
+
     #[test]
     fn scattered_one_off_matches_are_not_sustained() {
         // the false-positive mode: unrelated songs each matching once
@@ -331,6 +893,174 @@ mod tests {
         assert_eq!(count_sustained_songs(&matches), 0);
     }
 
+    fn empty_response() -> serde_json::Value {
+        serde_json::json!({ "status": "success", "result": [] })
+    }
+
+    fn matched_response() -> serde_json::Value {
+        serde_json::json!({
+            "status": "success",
+            "result": [{
+                "offset": "00:30",
+                "songs": [{ "artist": "Artist", "title": "Song", "timecode": "00:30" }]
+            }]
+        })
+    }
+
+    #[tokio::test]
+    async fn fallback_runs_when_primary_is_empty() {
+        let mut passes = Vec::new();
+        let recognition = recognize(true, |pass| {
+            passes.push(pass);
+            let body = match pass {
+                RecognitionPass::Primary => empty_response(),
+                RecognitionPass::Fallback => matched_response(),
+            };
+            async move { Ok(body) }
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(passes, [RecognitionPass::Primary, RecognitionPass::Fallback]);
+        let matches = extract_matches(&recognition.response);
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].title, "Song");
+    }
+
+    #[tokio::test]
+    async fn fallback_is_skipped_when_disabled() {
+        let mut passes = Vec::new();
+        let recognition = recognize(false, |pass| {
+            passes.push(pass);
+            async move { Ok(empty_response()) }
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(passes, [RecognitionPass::Primary]);
+        assert!(extract_matches(&recognition.response).is_empty());
+    }
+
+    #[tokio::test]
+    async fn fallback_is_skipped_when_primary_matches() {
+        let mut passes = Vec::new();
+        recognize(true, |pass| {
+            passes.push(pass);
+            async move { Ok(matched_response()) }
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(passes, [RecognitionPass::Primary]);
+    }
+
+    #[tokio::test]
+    async fn rate_limited_request_is_retried_after_retry_after() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+
+        use axum::response::IntoResponse;
+
+        // a stand-in AuDD that answers the first request with a 429
+        let hits = Arc::new(AtomicUsize::new(0));
+        let app = axum::Router::new().route(
+            "/",
+            axum::routing::post({
+                let hits = hits.clone();
+                move || {
+                    let first = hits.fetch_add(1, Ordering::SeqCst) == 0;
+                    async move {
+                        if first {
+                            (StatusCode::TOO_MANY_REQUESTS, [(header::RETRY_AFTER, "0")])
+                                .into_response()
+                        } else {
+                            Json(matched_response()).into_response()
+                        }
+                    }
+                }
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await });
+
+        let client = reqwest::Client::new();
+        let form = [("api_token", "token".to_string())];
+        let recognition = recognize(false, |_| call_audd(&client, &url, &form))
+            .await
+            .unwrap();
+
+        assert_eq!(hits.load(Ordering::SeqCst), 2);
+        assert_eq!(extract_matches(&recognition.response).len(), 1);
+    }
+
+    #[tokio::test]
+    async fn long_rate_limit_fails_without_retrying() {
+        let mut calls = 0;
+        let result = recognize(true, |_| {
+            calls += 1;
+            async move {
+                Err(AppError::AuddRateLimited {
+                    retry_after: Some(Duration::from_secs(3600)),
+                })
+            }
+        })
+        .await;
+
+        assert_eq!(calls, 1);
+        assert!(matches!(
+            result,
+            Err(AppError::AuddRateLimited {
+                retry_after: Some(_)
+            })
+        ));
+    }
+
+    #[test]
+    fn quota_error_body_is_rate_limited() {
+        let body = serde_json::json!({
+            "status": "error",
+            "error": { "error_code": 902, "error_message": "limit reached" }
+        });
+        assert!(matches!(
+            parse_audd_response(&body),
+            Err(AppError::AuddRateLimited { retry_after: None })
+        ));
+
+        let body = serde_json::json!({
+            "status": "error",
+            "error": { "error_code": 300, "error_message": "bad audio" }
+        });
+        assert!(matches!(parse_audd_response(&body), Err(AppError::Audd(_))));
+    }
+
+    #[test]
+    fn retry_delay_honors_retry_after_with_jitter() {
+        assert_eq!(audd_retry_delay(1, None, 0.0), Some(Duration::from_secs(1)));
+        assert_eq!(audd_retry_delay(3, None, 0.0), Some(Duration::from_secs(4)));
+        assert_eq!(
+            audd_retry_delay(1, None, 1.0),
+            Some(Duration::from_millis(1500))
+        );
+        assert_eq!(
+            audd_retry_delay(1, Some(Duration::from_secs(10)), 0.5),
+            Some(Duration::from_millis(12_500))
+        );
+        assert_eq!(
+            audd_retry_delay(1, Some(AUDD_MAX_RETRY_WAIT + Duration::from_secs(1)), 0.0),
+            None
+        );
+
+        let mut headers = HeaderMap::new();
+        headers.insert(header::RETRY_AFTER, "7".parse().unwrap());
+        assert_eq!(retry_after(&headers), Some(Duration::from_secs(7)));
+        headers.insert(
+            header::RETRY_AFTER,
+            "Wed, 21 Oct 2026 07:28:00 GMT".parse().unwrap(),
+        );
+        assert_eq!(retry_after(&headers), None);
+    }
+
     #[test]
     fn repeated_matches_at_one_position_are_not_sustained() {
         let matches = vec![
@@ -341,4 +1071,18 @@ mod tests {
 
         assert_eq!(count_sustained_songs(&matches), 0);
     }
+
+    #[test]
+    fn temp_upload_is_removed_on_drop() {
+        let upload = TempUpload::new();
+        let path = upload.path().to_path_buf();
+        assert_ne!(path, TempUpload::new().path());
+
+        std::fs::write(&path, b"audio").unwrap();
+        drop(upload);
+        assert!(!path.exists());
+
+        // dropping before anything was written is fine too
+        drop(TempUpload::new());
+    }
 }
@@ -1,52 +1,434 @@
 //! Authentication middleware.
 
-use axum::{extract::Request, http::StatusCode, middleware::Next, response::Response};
+use std::convert::Infallible;
+
+use axum::{
+    async_trait,
+    extract::{FromRequestParts, Request},
+    http::{header, request::Parts, HeaderMap, StatusCode},
+    middleware::Next,
+    response::Response,
+};
+use chrono::Utc;
+use sha2::{Digest, Sha256};
 use tracing::warn;
 
-/// Auth middleware that checks X-Moderation-Key header for protected endpoints.
+use crate::session::{login_page, session_cookie, SessionKey};
+use crate::state::AppError;
+
+/// Header naming the person behind an admin request, for the audit log.
+pub const ACTOR_HEADER: &str = "X-Moderation-Actor";
+
+/// Who performed an admin action, from the optional `X-Moderation-Actor`
+/// header. Missing or blank headers are recorded as "unknown" rather than
+/// rejecting the request.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Actor(pub String);
+
+impl Actor {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Whether the caller didn't name themselves.
+    pub fn is_unknown(&self) -> bool {
+        self.0 == "unknown"
+    }
+
+    fn from_header(value: Option<&str>) -> Self {
+        let name = value
+            .map(str::trim)
+            .filter(|v| !v.is_empty())
+            .unwrap_or("unknown");
+        Self(name.to_string())
+    }
+}
+
+#[async_trait]
+impl<S: Send + Sync> FromRequestParts<S> for Actor {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        Ok(Self::from_header(
+            parts
+                .headers
+                .get(ACTOR_HEADER)
+                .and_then(|v| v.to_str().ok()),
+        ))
+    }
+}
+
+/// What an authenticated caller may do.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Role {
+    /// Full access, including resolving flags and managing sensitive images.
+    Admin,
+    /// Dashboard access only; mutating admin endpoints return 403.
+    ReadOnly,
+}
+
+impl Role {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Admin => "admin",
+            Self::ReadOnly => "readonly",
+        }
+    }
+
+    /// Parse from string.
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "admin" => Some(Self::Admin),
+            "readonly" => Some(Self::ReadOnly),
+            _ => None,
+        }
+    }
+}
+
+/// The credential behind a request, for per-credential rate limits.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Caller {
+    /// A token sent in a header, as the backend does for service calls.
+    Token(String),
+    /// A browser session from `/admin/login`.
+    Session(String),
+}
+
+impl Caller {
+    /// Identify a credential without keeping the secret itself around.
+    fn fingerprint(credential: &str) -> String {
+        hex::encode(&Sha256::digest(credential.as_bytes())[..8])
+    }
+
+    /// Stable identity for the credential, e.g. `session:1a2b3c4d5e6f7a8b`.
+    /// Unlike [`Actor`], the client can't choose it, so it's what ownership
+    /// checks such as batch claims compare.
+    pub fn id(&self) -> String {
+        match self {
+            Self::Token(fingerprint) => format!("token:{fingerprint}"),
+            Self::Session(fingerprint) => format!("session:{fingerprint}"),
+        }
+    }
+}
+
+#[async_trait]
+impl<S: Send + Sync> FromRequestParts<S> for Caller {
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        parts
+            .extensions
+            .get::<Caller>()
+            .cloned()
+            .ok_or_else(|| AppError::Unauthorized("no credential on request".to_string()))
+    }
+}
+
+/// Tokens accepted by the auth middleware.
+#[derive(Debug, Clone, Default)]
+pub struct AuthTokens {
+    /// `MODERATION_AUTH_TOKEN`, granting the admin role.
+    pub admin: Option<String>,
+    /// `MODERATION_READONLY_TOKENS`, granting the read-only role.
+    pub readonly: Vec<String>,
+    /// Signs browser sessions issued by `/admin/login`.
+    pub sessions: Option<SessionKey>,
+}
+
+impl AuthTokens {
+    /// Role for a presented token, if it matches any configured token.
+    pub fn role_for(&self, token: &str) -> Option<Role> {
+        let matches = |expected: &String| constant_time_eq(token.as_bytes(), expected.as_bytes());
+        if self.admin.iter().any(matches) {
+            Some(Role::Admin)
+        } else if self.readonly.iter().any(matches) {
+            Some(Role::ReadOnly)
+        } else {
+            None
+        }
+    }
+}
+
+/// Extractor for endpoints that change moderation state. Rejects callers
+/// authenticated with a read-only token.
+#[derive(Debug, Clone, Copy)]
+pub struct RequireAdmin;
+
+#[async_trait]
+impl<S: Send + Sync> FromRequestParts<S> for RequireAdmin {
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        match parts.extensions.get::<Role>() {
+            Some(Role::Admin) => Ok(Self),
+            _ => Err(AppError::Forbidden(
+                "read-only access cannot modify moderation state".to_string(),
+            )),
+        }
+    }
+}
+
+/// Auth middleware that checks `Authorization: Bearer`, `X-Moderation-Key` or
+/// a session cookie for protected endpoints and attaches the caller's
+/// [`Role`] and [`Caller`] to the request.
+///
+/// The dashboard (`/admin`) and review pages (`/admin/review/:id`) need the
+/// same credentials as the API, but a browser without them gets the login
+/// form instead of a bare 401.
 pub async fn auth_middleware(
-    req: Request,
+    mut req: Request,
     next: Next,
-    auth_token: Option<String>,
+    tokens: AuthTokens,
 ) -> Result<Response, StatusCode> {
     let path = req.uri().path();
 
     // Public endpoints - no auth required
-    // Note: /admin and /admin/review/:id serve HTML, auth is handled client-side for API calls
-    // Static files must be public for admin UI CSS/JS to load
-    let is_review_page = path.starts_with("/admin/review/")
-        && !path.ends_with("/data")
-        && !path.ends_with("/submit");
+    // Static files must be public for the login form's CSS to load
     if path == "/"
         || path == "/health"
         || path == "/sensitive-images"
-        || path == "/admin"
-        || is_review_page
+        || path == "/report-reasons"
+        || path == "/admin/login"
+        || path == "/admin/logout"
         || path.starts_with("/static/")
         || path.starts_with("/xrpc/com.atproto.label.")
     {
         return Ok(next.run(req).await);
     }
+    let is_page = path == "/admin"
+        || (path.starts_with("/admin/review/")
+            && !path.ends_with("/data")
+            && !path.ends_with("/submit"));
 
-    let Some(expected_token) = auth_token else {
+    if tokens.admin.is_none() {
         warn!("no MODERATION_AUTH_TOKEN set - rejecting protected request");
         return Err(StatusCode::SERVICE_UNAVAILABLE);
+    }
+
+    let (role, caller) = match check_token(req.headers(), &tokens) {
+        Ok(auth) => auth,
+        Err(status) if is_page => return Ok(login_page(status)),
+        Err(status) => return Err(status),
     };
+    req.extensions_mut().insert(role);
+    req.extensions_mut().insert(caller);
+    Ok(next.run(req).await)
+}
 
-    let token = req
-        .headers()
-        .get("X-Moderation-Key")
-        .and_then(|v| v.to_str().ok());
+/// Validate the caller's token and return its role. The bearer header is
+/// preferred when both are sent. Browsers without a token header fall back
+/// to the session cookie.
+fn check_token(headers: &HeaderMap, tokens: &AuthTokens) -> Result<(Role, Caller), StatusCode> {
+    let bearer = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "));
+    let token = bearer.or_else(|| {
+        headers
+            .get("X-Moderation-Key")
+            .and_then(|v| v.to_str().ok())
+    });
 
     match token {
-        Some(t) if t == expected_token => Ok(next.run(req).await),
-        Some(_) => {
-            warn!("invalid auth token provided");
-            Err(StatusCode::UNAUTHORIZED)
+        Some(t) => {
+            let role = tokens.role_for(t).ok_or_else(|| {
+                warn!("invalid auth token provided");
+                StatusCode::UNAUTHORIZED
+            })?;
+            Ok((role, Caller::Token(Caller::fingerprint(t))))
         }
-        None => {
-            warn!("missing X-Moderation-Key header");
-            Err(StatusCode::UNAUTHORIZED)
+        None => check_session(headers, tokens),
+    }
+}
+
+fn check_session(headers: &HeaderMap, tokens: &AuthTokens) -> Result<(Role, Caller), StatusCode> {
+    let (Some(cookie), Some(sessions)) = (session_cookie(headers), &tokens.sessions) else {
+        warn!("missing Authorization or X-Moderation-Key header");
+        return Err(StatusCode::UNAUTHORIZED);
+    };
+    let role = sessions
+        .verify(cookie, Utc::now())
+        .map(|session| session.role)
+        .map_err(|e| {
+            warn!(error = %e, "rejected session cookie");
+            StatusCode::UNAUTHORIZED
+        })?;
+    Ok((role, Caller::Session(Caller::fingerprint(cookie))))
+}
+
+/// Compare secrets without short-circuiting on the first differing byte.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter().zip(b).fold(0u8, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, middleware, routing::get, Router};
+    use tower::ServiceExt;
+
+    fn tokens() -> AuthTokens {
+        AuthTokens {
+            admin: Some("secret".to_string()),
+            readonly: vec!["viewer-1".to_string(), "viewer-2".to_string()],
+            sessions: None,
         }
     }
+
+    fn protected_app() -> Router {
+        Router::new()
+            .route("/admin/flags", get(|| async { "ok" }))
+            .layer(middleware::from_fn(|req, next| {
+                auth_middleware(req, next, tokens())
+            }))
+    }
+
+    fn page_app(tokens: AuthTokens) -> Router {
+        Router::new()
+            .route("/admin", get(|| async { "dashboard" }))
+            .route("/admin/review/:id", get(|| async { "review page" }))
+            .layer(middleware::from_fn(move |req, next| {
+                auth_middleware(req, next, tokens.clone())
+            }))
+    }
+
+    /// Status and body of a review page request.
+    async fn review_page_with(
+        tokens: AuthTokens,
+        header: Option<(&str, &str)>,
+    ) -> (StatusCode, String) {
+        let mut request = Request::builder().uri("/admin/review/batch-1");
+        if let Some((name, value)) = header {
+            request = request.header(name, value);
+        }
+        let response = page_app(tokens)
+            .oneshot(request.body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let status = response.status();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        (status, String::from_utf8(body.to_vec()).unwrap())
+    }
+
+    async fn status_with(header: Option<(&str, &str)>) -> StatusCode {
+        let mut request = Request::builder().uri("/admin/flags");
+        if let Some((name, value)) = header {
+            request = request.header(name, value);
+        }
+        protected_app()
+            .oneshot(request.body(Body::empty()).unwrap())
+            .await
+            .unwrap()
+            .status()
+    }
+
+    #[tokio::test]
+    async fn test_bearer_token_reaches_protected_endpoint() {
+        assert_eq!(
+            status_with(Some(("Authorization", "Bearer secret"))).await,
+            StatusCode::OK
+        );
+        assert_eq!(
+            status_with(Some(("X-Moderation-Key", "secret"))).await,
+            StatusCode::OK
+        );
+        assert_eq!(
+            status_with(Some(("Authorization", "Bearer wrong"))).await,
+            StatusCode::UNAUTHORIZED
+        );
+        assert_eq!(status_with(None).await, StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn test_pages_require_auth() {
+        let sessions = SessionKey::new("test-secret", chrono::Duration::hours(12));
+        let (session, _) = sessions.issue(Role::ReadOnly, Utc::now());
+        let tokens = AuthTokens {
+            sessions: Some(sessions),
+            ..tokens()
+        };
+
+        // anonymous and bad-token browsers get the login form, not the page
+        for header in [None, Some(("X-Moderation-Key", "wrong"))] {
+            let (status, body) = review_page_with(tokens.clone(), header).await;
+            assert_eq!(status, StatusCode::UNAUTHORIZED);
+            assert!(body.contains(r#"id="login-form""#), "{header:?}");
+            assert!(!body.contains("review page"));
+        }
+        let cookie = "mod_session=readonly.1.00".to_string();
+        let (status, body) =
+            review_page_with(tokens.clone(), Some(("Cookie", cookie.as_str()))).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        assert!(body.contains(r#"id="login-form""#));
+
+        let cookie = format!("mod_session={session}");
+        assert_eq!(
+            review_page_with(tokens.clone(), Some(("Cookie", cookie.as_str()))).await,
+            (StatusCode::OK, "review page".to_string())
+        );
+        assert_eq!(
+            review_page_with(tokens.clone(), Some(("X-Moderation-Key", "secret"))).await,
+            (StatusCode::OK, "review page".to_string())
+        );
+
+        let dashboard = page_app(tokens)
+            .oneshot(
+                Request::builder()
+                    .uri("/admin")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(dashboard.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[test]
+    fn test_bearer_preferred_over_moderation_key() {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::AUTHORIZATION, "Bearer wrong".parse().unwrap());
+        headers.insert("X-Moderation-Key", "secret".parse().unwrap());
+        assert_eq!(
+            check_token(&headers, &tokens()),
+            Err(StatusCode::UNAUTHORIZED)
+        );
+    }
+
+    #[test]
+    fn test_token_roles() {
+        let tokens = tokens();
+        assert_eq!(tokens.role_for("secret"), Some(Role::Admin));
+        assert_eq!(tokens.role_for("viewer-2"), Some(Role::ReadOnly));
+        assert_eq!(tokens.role_for("viewer"), None);
+        assert_eq!(AuthTokens::default().role_for(""), None);
+    }
+
+    #[test]
+    fn test_caller_id_names_the_credential() {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::AUTHORIZATION, "Bearer secret".parse().unwrap());
+        let (_, caller) = check_token(&headers, &tokens()).unwrap();
+        let id = caller.id();
+        assert!(id.starts_with("token:"), "{id}");
+        assert_eq!(id.len(), "token:".len() + 16);
+        assert!(!id.contains("secret"));
+
+        // the same credential is always the same caller
+        let (_, again) = check_token(&headers, &tokens()).unwrap();
+        assert_eq!(again.id(), id);
+        assert_ne!(Caller::Session(Caller::fingerprint("secret")).id(), id);
+    }
+
+    #[test]
+    fn test_actor_defaults_to_unknown() {
+        assert_eq!(Actor::from_header(Some("alice")).as_str(), "alice");
+        assert_eq!(Actor::from_header(Some("  alice ")).as_str(), "alice");
+        assert_eq!(Actor::from_header(Some("   ")).as_str(), "unknown");
+        assert_eq!(Actor::from_header(None).as_str(), "unknown");
+    }
 }
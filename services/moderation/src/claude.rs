@@ -1,4 +1,7 @@
 //! Claude API client for image moderation using structured outputs.
+//!
+//! Structured outputs are a beta feature. With the beta header disabled the
+//! client asks for JSON in the prompt instead and parses it out of the reply.
 
 use base64::{engine::general_purpose::STANDARD, Engine};
 use serde::{Deserialize, Serialize};
@@ -6,7 +9,10 @@ use tracing::info;
 
 const CLAUDE_API_URL: &str = "https://api.anthropic.com/v1/messages";
 const ANTHROPIC_VERSION: &str = "2023-06-01";
-const STRUCTURED_OUTPUTS_BETA: &str = "structured-outputs-2025-11-13";
+/// Default `anthropic-beta` header value enabling structured outputs.
+pub const STRUCTURED_OUTPUTS_BETA: &str = "structured-outputs-2025-11-13";
+/// Default response token limit; verdicts are a few dozen tokens.
+pub const DEFAULT_MAX_TOKENS: u32 = 1024;
 
 /// Result of image moderation analysis.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -17,10 +23,131 @@ pub struct ModerationResult {
     pub explanation: String,
 }
 
+impl ModerationResult {
+    /// Verdict recorded when the model refuses to analyze an image.
+    ///
+    /// A refusal usually means the image is egregiously unsafe, so with
+    /// `as_unsafe` it is a high-severity verdict and gets auto-flagged.
+    /// Otherwise the severity is left unrecognized, which holds it for review.
+    pub fn refused(as_unsafe: bool) -> Self {
+        let severity = if as_unsafe {
+            Severity::High.as_str()
+        } else {
+            "refused"
+        };
+        Self {
+            is_safe: false,
+            violated_categories: Vec::new(),
+            severity: severity.to_string(),
+            explanation: "claude refused to analyze the image".to_string(),
+        }
+    }
+}
+
+/// Why an analysis request produced no verdict.
+#[derive(Debug, thiserror::Error)]
+pub enum ClaudeError {
+    /// The model declined to analyze the image. This says something about the
+    /// content rather than the API, so retrying won't help. The request was
+    /// still billed.
+    #[error("claude refused to analyze the image")]
+    Refusal {
+        model: String,
+        usage: Usage,
+        request_id: Option<String>,
+    },
+    /// The response hit `max_tokens` before the structured output finished.
+    #[error("response was cut off due to max_tokens limit")]
+    Truncated,
+    /// Transport, HTTP status, or malformed response.
+    #[error("{0}")]
+    Api(String),
+}
+
+impl From<reqwest::Error> for ClaudeError {
+    fn from(e: reqwest::Error) -> Self {
+        Self::Api(format!("request failed: {e}"))
+    }
+}
+
+/// Token usage reported by the API for a single request.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct Usage {
+    pub input_tokens: u32,
+    pub output_tokens: u32,
+}
+
+/// Moderation verdict plus request metadata.
+#[derive(Debug, Clone)]
+pub struct ImageAnalysis {
+    pub result: ModerationResult,
+    pub usage: Usage,
+    /// Model that actually served the request, as reported by the API.
+    pub model: String,
+    /// Anthropic `request-id` response header, for tracing billing disputes.
+    pub request_id: Option<String>,
+}
+
+/// Per-million-token prices (input, output) in USD, keyed by model prefix.
+const MODEL_PRICING: &[(&str, f64, f64)] = &[
+    ("claude-sonnet-4", 3.0, 15.0),
+    ("claude-haiku-4", 1.0, 5.0),
+    ("claude-3-5-haiku", 0.8, 4.0),
+    ("claude-opus-4-5", 5.0, 25.0),
+    ("claude-opus-4", 15.0, 75.0),
+];
+
+/// Estimate the USD cost of a request, or `None` for an unpriced model.
+pub fn estimate_cost_usd(model: &str, input_tokens: u32, output_tokens: u32) -> Option<f64> {
+    let (_, input_price, output_price) = MODEL_PRICING
+        .iter()
+        .find(|(prefix, _, _)| model.starts_with(prefix))?;
+    Some(
+        (input_tokens as f64 * input_price + output_tokens as f64 * output_price) / 1_000_000.0,
+    )
+}
+
+/// Severity of a moderation verdict, ordered from least to most severe.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Safe,
+    Low,
+    Medium,
+    High,
+}
+
+impl Severity {
+    /// Parse from the schema's severity enum.
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "safe" => Some(Self::Safe),
+            "low" => Some(Self::Low),
+            "medium" => Some(Self::Medium),
+            "high" => Some(Self::High),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Safe => "safe",
+            Self::Low => "low",
+            Self::Medium => "medium",
+            Self::High => "high",
+        }
+    }
+}
+
 /// Claude API client for image moderation.
 pub struct ClaudeClient {
     api_key: String,
+    /// Messages API endpoint.
+    api_url: String,
     model: String,
+    max_tokens: u32,
+    /// `anthropic-beta` header for structured outputs; `None` falls back to
+    /// prompt-based JSON.
+    structured_outputs_beta: Option<String>,
     http: reqwest::Client,
 }
 
@@ -28,29 +155,142 @@ impl ClaudeClient {
     pub fn new(api_key: String, model: Option<String>) -> Self {
         Self {
             api_key,
+            api_url: CLAUDE_API_URL.to_string(),
             model: model.unwrap_or_else(|| "claude-sonnet-4-5-20250929".to_string()),
+            max_tokens: DEFAULT_MAX_TOKENS,
+            structured_outputs_beta: Some(STRUCTURED_OUTPUTS_BETA.to_string()),
             http: reqwest::Client::new(),
         }
     }
 
+    /// Set the response token limit.
+    pub fn with_max_tokens(mut self, max_tokens: u32) -> Self {
+        self.max_tokens = max_tokens;
+        self
+    }
+
+    /// Set the structured outputs beta header, or `None` to disable
+    /// structured outputs and ask for JSON in the prompt.
+    pub fn with_structured_outputs_beta(mut self, beta: Option<String>) -> Self {
+        self.structured_outputs_beta = beta;
+        self
+    }
+
+    /// Send requests to another Messages API endpoint.
+    pub fn with_api_url(mut self, api_url: impl Into<String>) -> Self {
+        self.api_url = api_url.into();
+        self
+    }
+
+    /// Model used for requests that don't specify one.
+    pub fn model(&self) -> &str {
+        &self.model
+    }
+
     /// Analyze an image for policy violations using structured outputs.
     pub async fn analyze_image(
         &self,
         image_bytes: &[u8],
         media_type: &str,
-    ) -> anyhow::Result<ModerationResult> {
+    ) -> Result<ImageAnalysis, ClaudeError> {
+        self.request_analysis(&self.model, MODERATION_PROMPT, image_bytes, media_type)
+            .await
+    }
+
+    /// Get a second opinion on a first-pass verdict.
+    ///
+    /// The adjudication prompt includes the first verdict and asks the model
+    /// to confirm it only if the violation is clear. `model` defaults to the
+    /// client's model.
+    pub async fn adjudicate_image(
+        &self,
+        image_bytes: &[u8],
+        media_type: &str,
+        first: &ModerationResult,
+        model: Option<&str>,
+    ) -> Result<ImageAnalysis, ClaudeError> {
+        let prompt = adjudication_prompt(first);
+        self.request_analysis(
+            model.unwrap_or(&self.model),
+            &prompt,
+            image_bytes,
+            media_type,
+        )
+        .await
+    }
+
+    async fn request_analysis(
+        &self,
+        model: &str,
+        prompt: &str,
+        image_bytes: &[u8],
+        media_type: &str,
+    ) -> Result<ImageAnalysis, ClaudeError> {
+        let request = self.build_request(model, prompt, image_bytes, media_type);
+
+        info!(
+            model = %model,
+            structured = self.structured_outputs_beta.is_some(),
+            "analyzing image"
+        );
+
+        let mut builder = self
+            .http
+            .post(&self.api_url)
+            .header("x-api-key", &self.api_key)
+            .header("anthropic-version", ANTHROPIC_VERSION)
+            .header("content-type", "application/json");
+        if let Some(beta) = &self.structured_outputs_beta {
+            builder = builder.header("anthropic-beta", beta);
+        }
+        let response = builder.json(&request).send().await?;
+
+        let request_id = response
+            .headers()
+            .get("request-id")
+            .and_then(|v| v.to_str().ok())
+            .map(str::to_string);
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(ClaudeError::Api(format!(
+                "claude API error {status} (request-id {}): {body}",
+                request_id.as_deref().unwrap_or("none")
+            )));
+        }
+
+        let response: ClaudeResponse = response.json().await?;
+
+        parse_response(response, model, request_id)
+    }
+
+    /// Build the Messages API request body.
+    ///
+    /// With structured outputs the verdict schema goes in `output_format`;
+    /// without, the prompt asks for the same fields as bare JSON.
+    fn build_request(
+        &self,
+        model: &str,
+        prompt: &str,
+        image_bytes: &[u8],
+        media_type: &str,
+    ) -> serde_json::Value {
         let b64 = STANDARD.encode(image_bytes);
+        let prompt = match self.structured_outputs_beta {
+            Some(_) => prompt.to_string(),
+            None => format!("{prompt}\n\n{JSON_RESPONSE_INSTRUCTIONS}"),
+        };
 
-        // Build request with structured output schema
-        let request = serde_json::json!({
-            "model": self.model,
-            "max_tokens": 1024,
+        let mut request = serde_json::json!({
+            "model": model,
+            "max_tokens": self.max_tokens,
             "messages": [{
                 "role": "user",
                 "content": [
                     {
                         "type": "text",
-                        "text": MODERATION_PROMPT
+                        "text": prompt
                     },
                     {
                         "type": "image",
@@ -61,9 +301,12 @@ impl ClaudeClient {
                         }
                     }
                 ]
-            }],
+            }]
+        });
+
+        if self.structured_outputs_beta.is_some() {
             // Structured output schema - guarantees valid JSON matching this schema
-            "output_format": {
+            request["output_format"] = serde_json::json!({
                 "type": "json_schema",
                 "schema": {
                     "type": "object",
@@ -90,63 +333,79 @@ impl ClaudeClient {
                     "required": ["is_safe", "violated_categories", "severity", "explanation"],
                     "additionalProperties": false
                 }
-            }
-        });
+            });
+        }
 
-        info!(model = %self.model, "analyzing image with structured outputs");
+        request
+    }
+}
 
-        let response = self
-            .http
-            .post(CLAUDE_API_URL)
-            .header("x-api-key", &self.api_key)
-            .header("anthropic-version", ANTHROPIC_VERSION)
-            .header("anthropic-beta", STRUCTURED_OUTPUTS_BETA)
-            .header("content-type", "application/json")
-            .json(&request)
-            .send()
-            .await?;
+/// Turn an API response into an analysis.
+///
+/// `requested_model` is only used if the response omits `model`.
+fn parse_response(
+    response: ClaudeResponse,
+    requested_model: &str,
+    request_id: Option<String>,
+) -> Result<ImageAnalysis, ClaudeError> {
+    let usage = response.usage.unwrap_or_default();
+    let model = response
+        .model
+        .unwrap_or_else(|| requested_model.to_string());
 
-        if !response.status().is_success() {
-            let status = response.status();
-            let body = response.text().await.unwrap_or_default();
-            anyhow::bail!("claude API error {status}: {body}");
+    match response.stop_reason.as_deref() {
+        Some("refusal") => {
+            return Err(ClaudeError::Refusal {
+                model,
+                usage,
+                request_id,
+            })
         }
+        Some("max_tokens") => return Err(ClaudeError::Truncated),
+        _ => {}
+    }
 
-        let response: ClaudeResponse = response.json().await?;
-
-        // Check for refusal
-        if response.stop_reason == Some("refusal".to_string()) {
-            anyhow::bail!("claude refused to analyze the image");
-        }
+    // Extract text content
+    let text = response
+        .content
+        .into_iter()
+        .find_map(|block| {
+            if block.content_type == "text" {
+                block.text
+            } else {
+                None
+            }
+        })
+        .ok_or_else(|| ClaudeError::Api("no text content in response".to_string()))?;
 
-        // Check for max_tokens cutoff
-        if response.stop_reason == Some("max_tokens".to_string()) {
-            anyhow::bail!("response was cut off due to max_tokens limit");
-        }
+    let result = parse_verdict(&text)
+        .map_err(|e| ClaudeError::Api(format!("failed to parse verdict: {e}")))?;
 
-        // Extract text content - guaranteed to be valid JSON matching our schema
-        let text = response
-            .content
-            .into_iter()
-            .find_map(|block| {
-                if block.content_type == "text" {
-                    block.text
-                } else {
-                    None
-                }
-            })
-            .ok_or_else(|| anyhow::anyhow!("no text content in response"))?;
+    Ok(ImageAnalysis {
+        result,
+        usage,
+        model,
+        request_id,
+    })
+}
 
-        // Direct JSON parse - no string manipulation needed thanks to structured outputs
-        serde_json::from_str(&text)
-            .map_err(|e| anyhow::anyhow!("failed to parse structured output: {e}"))
-    }
+/// Parse a verdict from the response text.
+///
+/// Structured outputs return bare JSON. Prompt-based replies may wrap it in
+/// prose or a code fence, so fall back to the outermost `{...}`.
+fn parse_verdict(text: &str) -> Result<ModerationResult, serde_json::Error> {
+    serde_json::from_str(text).or_else(|e| match (text.find('{'), text.rfind('}')) {
+        (Some(start), Some(end)) if start < end => serde_json::from_str(&text[start..=end]),
+        _ => Err(e),
+    })
 }
 
 #[derive(Debug, Deserialize)]
 struct ClaudeResponse {
+    model: Option<String>,
     content: Vec<ContentBlock>,
     stop_reason: Option<String>,
+    usage: Option<Usage>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -169,6 +428,27 @@ Note: Artistic nudity in album art (like classic rock covers) may be acceptable
 
 Analyze the image and provide your moderation decision."#;
 
+/// Appended to the prompt when structured outputs are disabled.
+const JSON_RESPONSE_INSTRUCTIONS: &str = r#"Respond with only a JSON object, no other text, with these fields:
+- "is_safe": boolean, whether the image passes moderation
+- "violated_categories": array of strings, policy categories violated (empty if safe)
+- "severity": one of "safe", "low", "medium", "high"
+- "explanation": string, brief explanation of the moderation decision"#;
+
+/// Build the second-pass prompt, embedding the first verdict.
+fn adjudication_prompt(first: &ModerationResult) -> String {
+    let verdict = serde_json::to_string(first).unwrap_or_default();
+    format!(
+        r#"{MODERATION_PROMPT}
+
+Another moderator has already reviewed this image and reached this verdict:
+
+{verdict}
+
+Independently re-examine the image. Single-reviewer false positives are common for borderline artwork, so only mark the image unsafe if the violation is clear. If you disagree, mark it safe and explain why."#
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -190,4 +470,167 @@ mod tests {
         assert_eq!(result.violated_categories, vec!["explicit_sexual"]);
         assert_eq!(result.severity, "high");
     }
+
+    #[test]
+    fn test_parse_response_reports_served_model() {
+        let body = r#"{
+            "id": "msg_01",
+            "type": "message",
+            "role": "assistant",
+            "model": "claude-sonnet-4-5-20250929",
+            "content": [{"type": "text", "text": "{\"is_safe\": true, \"violated_categories\": [], \"severity\": \"safe\", \"explanation\": \"fine\"}"}],
+            "stop_reason": "end_turn",
+            "usage": {"input_tokens": 1200, "output_tokens": 40}
+        }"#;
+        let response: ClaudeResponse = serde_json::from_str(body).unwrap();
+        let analysis = parse_response(
+            response,
+            "claude-sonnet-4-5",
+            Some("req_011CabcDEF".to_string()),
+        )
+        .unwrap();
+
+        assert_eq!(analysis.model, "claude-sonnet-4-5-20250929");
+        assert_eq!(analysis.request_id.as_deref(), Some("req_011CabcDEF"));
+        assert_eq!(analysis.usage.input_tokens, 1200);
+        assert!(analysis.result.is_safe);
+    }
+
+    #[test]
+    fn test_parse_response_falls_back_to_requested_model() {
+        let body = r#"{"content": [{"type": "text", "text": "{\"is_safe\": true, \"violated_categories\": [], \"severity\": \"safe\", \"explanation\": \"\"}"}], "stop_reason": "end_turn"}"#;
+        let response: ClaudeResponse = serde_json::from_str(body).unwrap();
+        let analysis = parse_response(response, "claude-haiku-4-5", None).unwrap();
+        assert_eq!(analysis.model, "claude-haiku-4-5");
+        assert!(analysis.request_id.is_none());
+    }
+
+    fn response_with_stop_reason(stop_reason: &str, text: &str) -> ClaudeResponse {
+        serde_json::from_value(serde_json::json!({
+            "model": "claude-sonnet-4-5-20250929",
+            "content": [{"type": "text", "text": text}],
+            "stop_reason": stop_reason,
+            "usage": {"input_tokens": 1200, "output_tokens": 3}
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_parse_response_refusal() {
+        let response = response_with_stop_reason("refusal", "");
+        let err =
+            parse_response(response, "claude-sonnet-4-5", Some("req_1".to_string())).unwrap_err();
+        match err {
+            ClaudeError::Refusal {
+                model,
+                usage,
+                request_id,
+            } => {
+                assert_eq!(model, "claude-sonnet-4-5-20250929");
+                assert_eq!(usage.input_tokens, 1200);
+                assert_eq!(request_id.as_deref(), Some("req_1"));
+            }
+            other => panic!("expected refusal, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_parse_response_truncated() {
+        let response = response_with_stop_reason("max_tokens", r#"{"is_safe": tr"#);
+        let err = parse_response(response, "claude-sonnet-4-5", None).unwrap_err();
+        assert!(matches!(err, ClaudeError::Truncated));
+    }
+
+    #[test]
+    fn test_parse_response_malformed_output_is_api_error() {
+        let response = response_with_stop_reason("end_turn", "not json");
+        let err = parse_response(response, "claude-sonnet-4-5", None).unwrap_err();
+        assert!(matches!(err, ClaudeError::Api(_)));
+    }
+
+    #[test]
+    fn test_refused_verdict_is_unsafe_without_severity() {
+        let result = ModerationResult::refused(false);
+        assert!(!result.is_safe);
+        assert_eq!(Severity::parse(&result.severity), None);
+    }
+
+    #[test]
+    fn test_refused_verdict_as_unsafe_is_high_severity() {
+        let result = ModerationResult::refused(true);
+        assert!(!result.is_safe);
+        assert_eq!(Severity::parse(&result.severity), Some(Severity::High));
+    }
+
+    #[test]
+    fn test_request_uses_configured_max_tokens() {
+        let client = ClaudeClient::new("key".to_string(), None).with_max_tokens(256);
+        let request = client.build_request("claude-haiku-4-5", "prompt", b"img", "image/png");
+        assert_eq!(request["max_tokens"], 256);
+        assert_eq!(request["model"], "claude-haiku-4-5");
+
+        let default = ClaudeClient::new("key".to_string(), None);
+        let request = default.build_request("claude-haiku-4-5", "prompt", b"img", "image/png");
+        assert_eq!(request["max_tokens"], DEFAULT_MAX_TOKENS);
+    }
+
+    #[test]
+    fn test_request_without_structured_outputs_asks_for_json() {
+        let structured = ClaudeClient::new("key".to_string(), None);
+        let request = structured.build_request("m", MODERATION_PROMPT, b"img", "image/png");
+        assert_eq!(request["output_format"]["type"], "json_schema");
+        assert_eq!(
+            request["messages"][0]["content"][0]["text"],
+            MODERATION_PROMPT
+        );
+
+        let plain = ClaudeClient::new("key".to_string(), None).with_structured_outputs_beta(None);
+        let request = plain.build_request("m", MODERATION_PROMPT, b"img", "image/png");
+        assert!(request.get("output_format").is_none());
+        let text = request["messages"][0]["content"][0]["text"]
+            .as_str()
+            .unwrap();
+        assert!(text.starts_with(MODERATION_PROMPT));
+        assert!(text.ends_with(JSON_RESPONSE_INSTRUCTIONS));
+    }
+
+    #[test]
+    fn test_parse_verdict_from_prose_reply() {
+        let text = "Here is my decision:\n```json\n{\"is_safe\": false, \"violated_categories\": [\"gore\"], \"severity\": \"medium\", \"explanation\": \"blood\"}\n```";
+        let result = parse_verdict(text).unwrap();
+        assert!(!result.is_safe);
+        assert_eq!(result.severity, "medium");
+
+        assert!(parse_verdict("I can't tell.").is_err());
+    }
+
+    #[test]
+    fn test_estimate_cost() {
+        let cost = estimate_cost_usd("claude-sonnet-4-5-20250929", 1_000_000, 100_000).unwrap();
+        assert!((cost - 4.5).abs() < 1e-9);
+        assert!(estimate_cost_usd("gpt-4o", 1000, 1000).is_none());
+    }
+
+    #[test]
+    fn test_adjudication_prompt_includes_first_verdict() {
+        let first = ModerationResult {
+            is_safe: false,
+            violated_categories: vec!["explicit_sexual".to_string()],
+            severity: "medium".to_string(),
+            explanation: "partial nudity".to_string(),
+        };
+        let prompt = adjudication_prompt(&first);
+        assert!(prompt.starts_with(MODERATION_PROMPT));
+        assert!(prompt.contains(r#""severity":"medium""#));
+        assert!(prompt.contains("partial nudity"));
+    }
+
+    #[test]
+    fn test_severity_ordering() {
+        assert!(Severity::High > Severity::Medium);
+        assert!(Severity::Medium > Severity::Low);
+        assert!(Severity::Low > Severity::Safe);
+        assert_eq!(Severity::parse("MEDIUM"), Some(Severity::Medium));
+        assert_eq!(Severity::parse("extreme"), None);
+    }
 }
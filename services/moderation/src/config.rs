@@ -1,33 +1,222 @@
 //! Configuration loading from environment variables.
 
-use anyhow::anyhow;
+use anyhow::{anyhow, Context};
+use axum::http::HeaderName;
 use std::env;
+use std::path::Path;
+use tracing_subscriber::{filter::LevelFilter, EnvFilter};
+
+use crate::auth::AuthTokens;
+use crate::claude::{Severity, DEFAULT_MAX_TOKENS, STRUCTURED_OUTPUTS_BETA};
+use crate::enforcement::EnforcementConfig;
+use crate::ratelimit::{
+    IpBlock, DEFAULT_CLIENT_IP_HEADER, DEFAULT_LOGIN_LIMIT, DEFAULT_QUERY_LABELS_LIMIT,
+    DEFAULT_SERVICE_LIMIT, DEFAULT_SESSION_LIMIT, DEFAULT_SUBSCRIBE_LABELS_LIMIT,
+};
+use crate::report_callback::ReportCallbackConfig;
+use crate::reporter_hash::ReporterHasher;
+use crate::reports::{
+    DEFAULT_ESCALATION_THRESHOLD, DEFAULT_REPORTS_PER_DAY, DEFAULT_REPORTS_PER_HOUR,
+};
+use crate::review::DEFAULT_BATCH_TTL_DAYS;
+use crate::session::{SessionKey, DEFAULT_SESSION_TTL_HOURS};
+use crate::storage::StorageConfig;
+use crate::transcoder::TranscoderConfig;
+use crate::webhook::WebhookConfig;
 
 /// Service configuration loaded from environment.
 pub struct Config {
     pub host: String,
     pub port: u16,
     pub auth_token: Option<String>,
+    /// Comma-separated tokens granting read-only dashboard access
+    pub readonly_tokens: Vec<String>,
+    /// Signs admin session cookies, keyed by `MODERATION_SESSION_SECRET` and
+    /// valid for `MODERATION_SESSION_TTL_HOURS` (default: 12)
+    pub session_key: SessionKey,
+    /// Whether `session_key` was generated at startup because no secret is
+    /// set, in which case sessions end on restart
+    pub session_key_generated: bool,
+    /// Label-changing requests per minute for each header token (default: 600)
+    pub rate_limit_service: usize,
+    /// Label-changing requests per minute for each browser session (default: 60)
+    pub rate_limit_session: usize,
+    /// `queryLabels` requests per minute from one client IP (default: 120;
+    /// 0 turns the limit off)
+    pub xrpc_query_limit: usize,
+    /// `subscribeLabels` connections per minute from one client IP
+    /// (default: 240; 0 turns the limit off)
+    pub xrpc_subscribe_limit: usize,
+    /// `/admin/login` attempts per minute from one client IP (default: 10;
+    /// 0 turns the limit off)
+    pub login_limit: usize,
+    /// Comma-separated addresses or CIDR blocks exempt from the XRPC limits
+    pub xrpc_rate_limit_allowlist: Vec<IpBlock>,
+    /// Header holding the client's address behind the proxy
+    /// (default: fly-client-ip). Set it empty to use the connecting address.
+    pub client_ip_header: Option<HeaderName>,
+    /// Reports one reporter DID may file per hour (default: 10)
+    pub reports_per_hour: i64,
+    /// Reports one reporter DID may file per day (default: 50)
+    pub reports_per_day: i64,
+    /// Distinct reporters that escalate an open report (default: 5)
+    pub report_escalation_threshold: i64,
+    /// Open a review batch for escalated copyright reports about an AT-URI
+    /// (default: false)
+    pub report_escalation_batch: bool,
+    /// Store an HMAC of each reporter's DID instead of the DID, and no
+    /// handle (default: false). Needs `reporter_hash_secret`.
+    pub hash_reporters: bool,
+    /// Key for hashing reporter DIDs. Changing it splits every reporter's
+    /// history and report limits in two, so keep it stable.
+    pub reporter_hash_secret: Option<String>,
     pub audd_api_token: String,
     pub audd_api_url: String,
+    /// Retry with an alternate recognition mode when the first pass finds
+    /// nothing (default: false, since it doubles AuDD cost for clean uploads)
+    pub audd_fallback_enabled: bool,
+    /// Endpoint for the fallback pass (default: same as `audd_api_url`)
+    pub audd_fallback_url: String,
     pub database_url: Option<String>,
     pub labeler_did: Option<String>,
+    /// DIDs that labels on non-production namespaces are issued as, from
+    /// `MODERATION_LABELER_NAMESPACE_DIDS` (`namespace=did` pairs, e.g.
+    /// `fm.plyr.dev=did:plc:xxx`). Other namespaces use `labeler_did`.
+    pub labeler_namespace_dids: Vec<(String, String)>,
+    /// Hex-encoded signing key, from `MODERATION_LABELER_SIGNING_KEY_FILE`
+    /// if set, otherwise `MODERATION_LABELER_SIGNING_KEY`
     pub labeler_signing_key: Option<String>,
     /// Anthropic API key for Claude image moderation
     pub claude_api_key: Option<String>,
     /// Claude model to use (default: claude-sonnet-4-5-20250929)
     pub claude_model: String,
+    /// Response token limit for Claude requests (default: 1024)
+    pub claude_max_tokens: u32,
+    /// `anthropic-beta` header enabling structured outputs
+    /// (default: structured-outputs-2025-11-13). Set it empty to disable
+    /// structured outputs and parse JSON from the reply text instead.
+    pub claude_structured_outputs_beta: Option<String>,
+    /// Public base URL for R2 images, used for admin thumbnails (optional)
+    pub image_base_url: Option<String>,
+    /// Minimum severity at which an unsafe image is auto-flagged (default: medium).
+    /// Unsafe results below this are held for admin review instead.
+    pub image_autoflag_severity: Severity,
+    /// Ask for a second opinion before auto-flagging medium severity results;
+    /// flag only if both passes agree (default: false)
+    pub image_second_opinion: bool,
+    /// Model for the second opinion (default: same model, adjudication prompt)
+    pub image_second_opinion_model: Option<String>,
+    /// Treat a Claude refusal to analyze an image as a high-severity unsafe
+    /// verdict and auto-flag it; when false, refusals are held for review
+    /// (default: true)
+    pub claude_refusal_as_unsafe: bool,
     /// Minimum percentage of matches that must belong to a single song to flag (default: 30)
     /// AudD doesn't return confidence scores, so we use match frequency as a proxy.
     pub copyright_score_threshold: i32,
+    /// Public base URL of this service, used for links in notifications
+    /// (default: https://moderation.plyr.fm)
+    pub public_url: String,
+    /// PLC directory used to resolve the labeler DID for health checks
+    /// (default: https://plc.directory)
+    pub plc_directory_url: String,
+    /// Outbound webhook URL for moderation events (optional)
+    pub webhook_url: Option<String>,
+    /// Shared secret sent with webhook deliveries (optional)
+    pub webhook_secret: Option<String>,
+    /// Send `image.flagged` events when an image is auto-flagged (default: false)
+    pub webhook_image_flagged: bool,
+    /// Send `report.escalated` events when a report is escalated (default: true)
+    pub webhook_report_escalated: bool,
+    /// Backend URL called when a flag is confirmed as a violation (optional)
+    pub enforcement_url: Option<String>,
+    /// Shared key sent with enforcement callbacks (optional)
+    pub enforcement_key: Option<String>,
+    /// Backend URL called when a user report is resolved or dismissed (optional)
+    pub report_resolved_url: Option<String>,
+    /// Shared key sent with report resolution callbacks (optional)
+    pub report_resolved_key: Option<String>,
+    /// Transcoder base URL, used to cut a sample range out of audio before
+    /// recognition (optional; without it scans can't ask for a sample)
+    pub transcoder_url: Option<String>,
+    /// Bearer token for the transcoder (optional)
+    pub transcoder_auth_token: Option<String>,
+    /// S3-compatible endpoint (R2) for report screenshot uploads (optional;
+    /// without it reports can't carry an uploaded screenshot)
+    pub storage_endpoint: Option<String>,
+    /// Bucket screenshots are stored in
+    pub storage_bucket: Option<String>,
+    /// Signing region (default: auto, which R2 expects)
+    pub storage_region: String,
+    pub storage_access_key_id: Option<String>,
+    pub storage_secret_access_key: Option<String>,
+    /// Delete labels this many days after they expire (default: unset, never prune)
+    pub label_prune_after_days: Option<i64>,
+    /// Days a review batch accepts decisions before it expires (default: 14)
+    pub review_batch_ttl_days: i64,
+    /// Delete negated labels and closed reports this many days after they
+    /// were resolved (default: unset, keep everything)
+    pub retention_days: Option<i64>,
+    /// Hours between retention runs (default: 24)
+    pub retention_interval_hours: u64,
     /// Minimum count of distinct songs each sustained across multiple segments
     /// to flag as a mix of copyrighted material (default: 3)
     pub copyright_mix_song_threshold: usize,
+    /// Comma-separated ISRCs from licensed catalogs; scans matching only
+    /// these aren't flagged. More can be added at `/admin/isrc-allowlist`.
+    pub isrc_allowlist: Vec<String>,
 }
 
 impl Config {
     /// Load configuration from environment variables.
     pub fn from_env() -> anyhow::Result<Self> {
+        let image_autoflag_severity = match env::var("MODERATION_IMAGE_AUTOFLAG_SEVERITY") {
+            Ok(v) => Severity::parse(&v).ok_or_else(|| {
+                anyhow!("MODERATION_IMAGE_AUTOFLAG_SEVERITY must be one of low, medium, high (got {v})")
+            })?,
+            Err(_) => Severity::Medium,
+        };
+        // unset keeps the default beta; set but empty disables structured outputs
+        let claude_structured_outputs_beta =
+            match env::var("MODERATION_CLAUDE_STRUCTURED_OUTPUTS_BETA") {
+                Ok(v) => Some(v.trim().to_string()).filter(|v| !v.is_empty()),
+                Err(_) => Some(STRUCTURED_OUTPUTS_BETA.to_string()),
+            };
+        let labeler_did = env::var("MODERATION_LABELER_DID").ok();
+        let labeler_namespace_dids =
+            parse_namespace_dids(env::var("MODERATION_LABELER_NAMESPACE_DIDS").ok())?;
+        let labeler_signing_key = load_signing_key(
+            env::var("MODERATION_LABELER_SIGNING_KEY_FILE").ok().as_deref(),
+            env::var("MODERATION_LABELER_SIGNING_KEY").ok(),
+        )?;
+        let session_secret = env::var("MODERATION_SESSION_SECRET")
+            .ok()
+            .filter(|s| !s.is_empty())
+            .map(String::into_bytes);
+        let session_ttl_hours = env::var("MODERATION_SESSION_TTL_HOURS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .filter(|hours| *hours > 0)
+            .unwrap_or(DEFAULT_SESSION_TTL_HOURS);
+        let database_url = env::var("MODERATION_DATABASE_URL").ok();
+        if database_url.is_some() && labeler_did.is_some() && labeler_signing_key.is_none() {
+            return Err(anyhow!(
+                "MODERATION_LABELER_DID is set but no signing key was provided \
+                 (set MODERATION_LABELER_SIGNING_KEY_FILE or MODERATION_LABELER_SIGNING_KEY)"
+            ));
+        }
+        let hash_reporters = env::var("MODERATION_HASH_REPORTERS")
+            .map(|v| v == "true" || v == "1")
+            .unwrap_or(false);
+        let reporter_hash_secret = env::var("MODERATION_REPORTER_HASH_SECRET")
+            .ok()
+            .filter(|secret| !secret.is_empty());
+        if hash_reporters && reporter_hash_secret.is_none() {
+            return Err(anyhow!(
+                "MODERATION_HASH_REPORTERS is on but MODERATION_REPORTER_HASH_SECRET is not set"
+            ));
+        }
+        let audd_api_url = env::var("MODERATION_AUDD_API_URL")
+            .unwrap_or_else(|_| "https://enterprise.audd.io/".to_string());
         Ok(Self {
             host: env::var("MODERATION_HOST").unwrap_or_else(|_| "0.0.0.0".to_string()),
             port: env::var("MODERATION_PORT")
@@ -35,16 +224,143 @@ impl Config {
                 .and_then(|v| v.parse().ok())
                 .unwrap_or(8083),
             auth_token: env::var("MODERATION_AUTH_TOKEN").ok(),
+            readonly_tokens: parse_token_list(env::var("MODERATION_READONLY_TOKENS").ok()),
+            session_key: SessionKey::new(
+                session_secret
+                    .clone()
+                    .unwrap_or_else(|| rand::random::<[u8; 32]>().to_vec()),
+                chrono::Duration::hours(session_ttl_hours),
+            ),
+            session_key_generated: session_secret.is_none(),
+            rate_limit_service: env::var("MODERATION_RATE_LIMIT_SERVICE")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(DEFAULT_SERVICE_LIMIT),
+            rate_limit_session: env::var("MODERATION_RATE_LIMIT_SESSION")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(DEFAULT_SESSION_LIMIT),
+            xrpc_query_limit: env::var("MODERATION_XRPC_QUERY_LIMIT")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(DEFAULT_QUERY_LABELS_LIMIT),
+            xrpc_subscribe_limit: env::var("MODERATION_XRPC_SUBSCRIBE_LIMIT")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(DEFAULT_SUBSCRIBE_LABELS_LIMIT),
+            login_limit: env::var("MODERATION_LOGIN_LIMIT")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(DEFAULT_LOGIN_LIMIT),
+            xrpc_rate_limit_allowlist: parse_ip_blocks(
+                env::var("MODERATION_XRPC_RATE_LIMIT_ALLOWLIST").ok(),
+            )?,
+            client_ip_header: parse_client_ip_header(env::var("MODERATION_CLIENT_IP_HEADER").ok())?,
+            reports_per_hour: env::var("MODERATION_REPORTS_PER_HOUR")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .filter(|n| *n > 0)
+                .unwrap_or(DEFAULT_REPORTS_PER_HOUR),
+            reports_per_day: env::var("MODERATION_REPORTS_PER_DAY")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .filter(|n| *n > 0)
+                .unwrap_or(DEFAULT_REPORTS_PER_DAY),
+            report_escalation_threshold: env::var("MODERATION_REPORT_ESCALATION_THRESHOLD")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .filter(|n| *n > 0)
+                .unwrap_or(DEFAULT_ESCALATION_THRESHOLD),
+            report_escalation_batch: env::var("MODERATION_REPORT_ESCALATION_BATCH")
+                .map(|v| v == "true" || v == "1")
+                .unwrap_or(false),
+            hash_reporters,
+            reporter_hash_secret,
             audd_api_token: env::var("MODERATION_AUDD_API_TOKEN")
                 .map_err(|_| anyhow!("MODERATION_AUDD_API_TOKEN is required"))?,
-            audd_api_url: env::var("MODERATION_AUDD_API_URL")
-                .unwrap_or_else(|_| "https://enterprise.audd.io/".to_string()),
-            database_url: env::var("MODERATION_DATABASE_URL").ok(),
-            labeler_did: env::var("MODERATION_LABELER_DID").ok(),
-            labeler_signing_key: env::var("MODERATION_LABELER_SIGNING_KEY").ok(),
+            audd_fallback_enabled: env::var("MODERATION_AUDD_FALLBACK_ENABLED")
+                .map(|v| v == "true" || v == "1")
+                .unwrap_or(false),
+            audd_fallback_url: env::var("MODERATION_AUDD_FALLBACK_URL")
+                .unwrap_or_else(|_| audd_api_url.clone()),
+            audd_api_url,
+            database_url,
+            labeler_did,
+            labeler_namespace_dids,
+            labeler_signing_key,
             claude_api_key: env::var("ANTHROPIC_API_KEY").ok(),
             claude_model: env::var("MODERATION_CLAUDE_MODEL")
                 .unwrap_or_else(|_| "claude-sonnet-4-5-20250929".to_string()),
+            claude_max_tokens: env::var("MODERATION_CLAUDE_MAX_TOKENS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(DEFAULT_MAX_TOKENS),
+            claude_structured_outputs_beta,
+            image_base_url: env::var("MODERATION_IMAGE_BASE_URL")
+                .ok()
+                .map(|u| u.trim_end_matches('/').to_string()),
+            image_autoflag_severity,
+            image_second_opinion: env::var("MODERATION_IMAGE_SECOND_OPINION")
+                .map(|v| v == "true" || v == "1")
+                .unwrap_or(false),
+            image_second_opinion_model: env::var("MODERATION_IMAGE_SECOND_OPINION_MODEL")
+                .ok()
+                .filter(|m| !m.is_empty()),
+            claude_refusal_as_unsafe: env::var("MODERATION_CLAUDE_REFUSAL_AS_UNSAFE")
+                .map(|v| v == "true" || v == "1")
+                .unwrap_or(true),
+            public_url: env::var("MODERATION_PUBLIC_URL")
+                .map(|u| u.trim_end_matches('/').to_string())
+                .unwrap_or_else(|_| "https://moderation.plyr.fm".to_string()),
+            plc_directory_url: env::var("MODERATION_PLC_DIRECTORY_URL")
+                .unwrap_or_else(|_| "https://plc.directory".to_string()),
+            webhook_url: env::var("MODERATION_WEBHOOK_URL")
+                .ok()
+                .filter(|u| !u.is_empty()),
+            webhook_secret: env::var("MODERATION_WEBHOOK_SECRET").ok(),
+            webhook_image_flagged: env::var("MODERATION_WEBHOOK_IMAGE_FLAGGED")
+                .map(|v| v == "true" || v == "1")
+                .unwrap_or(false),
+            webhook_report_escalated: env::var("MODERATION_WEBHOOK_REPORT_ESCALATED")
+                .map(|v| v == "true" || v == "1")
+                .unwrap_or(true),
+            enforcement_url: env::var("MODERATION_ENFORCEMENT_URL")
+                .ok()
+                .filter(|u| !u.is_empty()),
+            enforcement_key: env::var("MODERATION_ENFORCEMENT_KEY").ok(),
+            report_resolved_url: env::var("MODERATION_REPORT_RESOLVED_URL")
+                .ok()
+                .filter(|u| !u.is_empty()),
+            report_resolved_key: env::var("MODERATION_REPORT_RESOLVED_KEY").ok(),
+            transcoder_url: env::var("MODERATION_TRANSCODER_URL")
+                .ok()
+                .filter(|u| !u.is_empty()),
+            transcoder_auth_token: env::var("MODERATION_TRANSCODER_AUTH_TOKEN").ok(),
+            storage_endpoint: env::var("MODERATION_STORAGE_ENDPOINT")
+                .ok()
+                .filter(|u| !u.is_empty()),
+            storage_bucket: env::var("MODERATION_STORAGE_BUCKET").ok(),
+            storage_region: env::var("MODERATION_STORAGE_REGION")
+                .unwrap_or_else(|_| "auto".to_string()),
+            storage_access_key_id: env::var("MODERATION_STORAGE_ACCESS_KEY_ID").ok(),
+            storage_secret_access_key: env::var("MODERATION_STORAGE_SECRET_ACCESS_KEY").ok(),
+            label_prune_after_days: env::var("MODERATION_LABEL_PRUNE_AFTER_DAYS")
+                .ok()
+                .and_then(|v| v.parse().ok()),
+            review_batch_ttl_days: env::var("MODERATION_REVIEW_BATCH_TTL_DAYS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .filter(|days| *days > 0)
+                .unwrap_or(DEFAULT_BATCH_TTL_DAYS),
+            retention_days: env::var("MODERATION_RETENTION_DAYS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .filter(|days| *days > 0 && i32::try_from(*days).is_ok()),
+            retention_interval_hours: env::var("MODERATION_RETENTION_INTERVAL_HOURS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .filter(|hours| *hours > 0)
+                .unwrap_or(24),
             copyright_score_threshold: env::var("MODERATION_COPYRIGHT_SCORE_THRESHOLD")
                 .ok()
                 .and_then(|v| v.parse().ok())
@@ -53,6 +369,7 @@ impl Config {
                 .ok()
                 .and_then(|v| v.parse().ok())
                 .unwrap_or(3),
+            isrc_allowlist: parse_token_list(env::var("MODERATION_ISRC_ALLOWLIST").ok()),
         })
     }
 
@@ -61,6 +378,71 @@ impl Config {
         self.claude_api_key.is_some() && self.database_url.is_some()
     }
 
+    /// Webhook settings, if a webhook URL is configured.
+    pub fn webhook(&self) -> Option<WebhookConfig> {
+        self.webhook_url.as_ref().map(|url| WebhookConfig {
+            url: url.clone(),
+            secret: self.webhook_secret.clone(),
+            image_flagged_enabled: self.webhook_image_flagged,
+            report_escalated_enabled: self.webhook_report_escalated,
+        })
+    }
+
+    /// Enforcement callback settings, if an enforcement URL is configured.
+    pub fn enforcement(&self) -> Option<EnforcementConfig> {
+        self.enforcement_url.as_ref().map(|url| EnforcementConfig {
+            url: url.clone(),
+            key: self.enforcement_key.clone(),
+        })
+    }
+
+    /// Report resolution callback settings, if a callback URL is configured.
+    pub fn report_callback(&self) -> Option<ReportCallbackConfig> {
+        self.report_resolved_url
+            .as_ref()
+            .map(|url| ReportCallbackConfig {
+                url: url.clone(),
+                key: self.report_resolved_key.clone(),
+            })
+    }
+
+    /// Transcoder settings, if a transcoder URL is configured.
+    pub fn transcoder(&self) -> Option<TranscoderConfig> {
+        self.transcoder_url.as_ref().map(|url| TranscoderConfig {
+            url: url.clone(),
+            auth_token: self.transcoder_auth_token.clone(),
+        })
+    }
+
+    /// Screenshot storage settings, if the endpoint, bucket and credentials
+    /// are all configured.
+    pub fn storage(&self) -> Option<StorageConfig> {
+        Some(StorageConfig {
+            endpoint: self.storage_endpoint.clone()?,
+            bucket: self.storage_bucket.clone()?,
+            region: self.storage_region.clone(),
+            access_key_id: self.storage_access_key_id.clone()?,
+            secret_access_key: self.storage_secret_access_key.clone()?,
+        })
+    }
+
+    /// Reporter hashing, if `MODERATION_HASH_REPORTERS` is on.
+    pub fn reporter_hasher(&self) -> Option<ReporterHasher> {
+        self.reporter_hash_secret
+            .as_ref()
+            .filter(|_| self.hash_reporters)
+            .map(|secret| ReporterHasher::new(secret.as_bytes()))
+    }
+
+    /// Tokens and session key accepted by the auth middleware.
+    pub fn auth_tokens(&self) -> AuthTokens {
+        AuthTokens {
+            admin: self.auth_token.clone(),
+            readonly: self.readonly_tokens.clone(),
+            sessions: Some(self.session_key.clone()),
+        }
+    }
+
     /// Check if labeler is fully configured.
     pub fn labeler_enabled(&self) -> bool {
         self.database_url.is_some()
@@ -68,3 +450,194 @@ impl Config {
             && self.labeler_signing_key.is_some()
     }
 }
+
+/// Log filter for the tracing subscriber.
+///
+/// `RUST_LOG` takes precedence when set. Otherwise `LOG_LEVEL` (trace, debug,
+/// info, warn or error) sets the level for every module. With neither, only
+/// errors are logged.
+pub fn log_filter() -> EnvFilter {
+    let rust_log = env::var("RUST_LOG").ok();
+    let log_level = env::var("LOG_LEVEL").ok();
+    match base_log_level(rust_log.as_deref(), log_level.as_deref()) {
+        Some(level) => EnvFilter::builder()
+            .with_default_directive(level.into())
+            .parse_lossy(""),
+        None => EnvFilter::from_default_env(),
+    }
+}
+
+/// Level from `LOG_LEVEL`, unless a non-empty `RUST_LOG` overrides it.
+/// Unrecognized levels are ignored.
+fn base_log_level(rust_log: Option<&str>, log_level: Option<&str>) -> Option<LevelFilter> {
+    if rust_log.is_some_and(|v| !v.trim().is_empty()) {
+        return None;
+    }
+    log_level?.trim().parse().ok()
+}
+
+/// Split a comma-separated token list, dropping blank entries.
+fn parse_token_list(raw: Option<String>) -> Vec<String> {
+    raw.unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|t| !t.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+/// Parse comma-separated addresses and CIDR blocks.
+fn parse_ip_blocks(raw: Option<String>) -> anyhow::Result<Vec<IpBlock>> {
+    parse_token_list(raw)
+        .iter()
+        .map(|block| {
+            block
+                .parse()
+                .map_err(|e| anyhow!("MODERATION_XRPC_RATE_LIMIT_ALLOWLIST: {e}"))
+        })
+        .collect()
+}
+
+/// The client IP header, defaulting to Fly's. Empty means none.
+fn parse_client_ip_header(raw: Option<String>) -> anyhow::Result<Option<HeaderName>> {
+    let name = raw.unwrap_or_else(|| DEFAULT_CLIENT_IP_HEADER.to_string());
+    let name = name.trim();
+    if name.is_empty() {
+        return Ok(None);
+    }
+    let name = name
+        .parse()
+        .map_err(|_| anyhow!("MODERATION_CLIENT_IP_HEADER is not a valid header name ({name})"))?;
+    Ok(Some(name))
+}
+
+/// Parse comma-separated `namespace=did` pairs.
+fn parse_namespace_dids(raw: Option<String>) -> anyhow::Result<Vec<(String, String)>> {
+    parse_token_list(raw)
+        .into_iter()
+        .map(|pair| {
+            let (namespace, did) = pair
+                .split_once('=')
+                .map(|(ns, did)| (ns.trim(), did.trim()))
+                .filter(|(ns, did)| !ns.is_empty() && did.starts_with("did:"))
+                .ok_or_else(|| {
+                    anyhow!("MODERATION_LABELER_NAMESPACE_DIDS entries must be namespace=did (got {pair})")
+                })?;
+            Ok((namespace.to_string(), did.to_string()))
+        })
+        .collect()
+}
+
+/// Resolve the labeler signing key, preferring a mounted secret file over the
+/// inline env var. Surrounding whitespace (e.g. a trailing newline) is trimmed.
+fn load_signing_key(file: Option<&str>, inline: Option<String>) -> anyhow::Result<Option<String>> {
+    let key = match file.filter(|p| !p.is_empty()) {
+        Some(path) => {
+            let contents = std::fs::read_to_string(Path::new(path)).with_context(|| {
+                format!("failed to read MODERATION_LABELER_SIGNING_KEY_FILE ({path})")
+            })?;
+            let key = contents.trim().to_string();
+            if key.is_empty() {
+                return Err(anyhow!("MODERATION_LABELER_SIGNING_KEY_FILE ({path}) is empty"));
+            }
+            Some(key)
+        }
+        None => inline.map(|k| k.trim().to_string()).filter(|k| !k.is_empty()),
+    };
+    Ok(key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_signing_key_from_file_is_preferred_and_trimmed() {
+        let path = env::temp_dir().join(format!("moderation-signing-key-{}", std::process::id()));
+        std::fs::write(&path, "  abcdef0123\n").unwrap();
+
+        let key = load_signing_key(path.to_str(), Some("inline".to_string())).unwrap();
+        assert_eq!(key.as_deref(), Some("abcdef0123"));
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_signing_key_falls_back_to_env() {
+        let key = load_signing_key(None, Some(" abcdef\n".to_string())).unwrap();
+        assert_eq!(key.as_deref(), Some("abcdef"));
+        assert!(load_signing_key(None, None).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_signing_key_file_errors() {
+        assert!(load_signing_key(Some("/nonexistent/moderation-key"), None).is_err());
+
+        let path = env::temp_dir().join(format!("moderation-empty-key-{}", std::process::id()));
+        std::fs::write(&path, "\n").unwrap();
+        assert!(load_signing_key(path.to_str(), Some("inline".to_string())).is_err());
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_log_level_applies_without_rust_log() {
+        assert_eq!(
+            base_log_level(None, Some("debug")),
+            Some(LevelFilter::DEBUG)
+        );
+        assert_eq!(
+            base_log_level(Some(""), Some(" WARN ")),
+            Some(LevelFilter::WARN)
+        );
+        assert_eq!(base_log_level(None, Some("loud")), None);
+        assert_eq!(base_log_level(None, None), None);
+    }
+
+    #[test]
+    fn test_rust_log_takes_precedence() {
+        assert_eq!(
+            base_log_level(Some("moderation=trace"), Some("error")),
+            None
+        );
+    }
+
+    #[test]
+    fn test_readonly_tokens_are_split_and_trimmed() {
+        assert_eq!(
+            parse_token_list(Some(" alpha, ,beta ,".to_string())),
+            vec!["alpha".to_string(), "beta".to_string()]
+        );
+        assert!(parse_token_list(None).is_empty());
+    }
+
+    #[test]
+    fn test_namespace_dids_are_parsed() {
+        assert_eq!(
+            parse_namespace_dids(Some(
+                "fm.plyr.dev = did:plc:dev, fm.plyr.stg=did:plc:stg".to_string()
+            ))
+            .unwrap(),
+            vec![
+                ("fm.plyr.dev".to_string(), "did:plc:dev".to_string()),
+                ("fm.plyr.stg".to_string(), "did:plc:stg".to_string()),
+            ]
+        );
+        assert!(parse_namespace_dids(None).unwrap().is_empty());
+        assert!(parse_namespace_dids(Some("fm.plyr.dev".to_string())).is_err());
+        assert!(parse_namespace_dids(Some("fm.plyr.dev=plc:dev".to_string())).is_err());
+    }
+
+    #[test]
+    fn test_xrpc_rate_limit_settings() {
+        let allowlist = parse_ip_blocks(Some("10.0.0.0/8, fdaa::/16,".to_string())).unwrap();
+        assert_eq!(allowlist.len(), 2);
+        assert!(parse_ip_blocks(None).unwrap().is_empty());
+        assert!(parse_ip_blocks(Some("10.0.0.0/40".to_string())).is_err());
+
+        let header = |raw: Option<&str>| parse_client_ip_header(raw.map(String::from));
+        assert_eq!(header(None).unwrap().unwrap(), DEFAULT_CLIENT_IP_HEADER);
+        assert_eq!(header(Some("X-Real-IP")).unwrap().unwrap(), "x-real-ip");
+        assert!(header(Some(" ")).unwrap().is_none());
+        assert!(header(Some("bad header")).is_err());
+    }
+}
@@ -1,11 +1,16 @@
 //! Database operations for the labeler.
 
-use chrono::{DateTime, Utc};
+use std::collections::BTreeMap;
+use std::future::Future;
+
+use chrono::{DateTime, NaiveDate, Utc};
+use futures::TryStreamExt;
 use serde::{Deserialize, Serialize};
-use sqlx::{postgres::PgPoolOptions, FromRow, PgPool};
+use sqlx::{postgres::PgPoolOptions, FromRow, PgConnection, PgExecutor, PgPool};
 
 use crate::admin::FlaggedTrack;
 use crate::labels::Label;
+use crate::review::DEFAULT_BATCH_TTL_DAYS;
 
 /// Sensitive image record from the database.
 #[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
@@ -21,6 +26,615 @@ pub struct SensitiveImageRow {
     pub flagged_at: DateTime<Utc>,
     /// Admin who flagged it
     pub flagged_by: Option<String>,
+    /// Scan severity, when flagged from an image scan (null for manual entries
+    /// and rows from before severity was recorded)
+    pub severity: Option<String>,
+    /// Scan's violated categories (JSON array)
+    pub violated_categories: Option<serde_json::Value>,
+}
+
+/// Progress of one sensitive-image re-verification run.
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct ReverifyRunRow {
+    pub id: i64,
+    pub started_by: String,
+    pub started_at: DateTime<Utc>,
+    /// Last time the run made progress (or was resumed).
+    pub updated_at: DateTime<Utc>,
+    pub finished_at: Option<DateTime<Utc>>,
+    /// `sensitive_images.id` of the last image processed; the run resumes
+    /// after it.
+    pub last_image_id: i64,
+    /// Images re-scanned.
+    pub checked: i32,
+    /// Images now judged safe and removed from sensitive images.
+    pub cleared: i32,
+    /// Images that couldn't be fetched or scanned; they stay flagged.
+    pub failed: i32,
+}
+
+/// What re-verification did with one image.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReverifyOutcome {
+    /// Still unsafe (or held by an unsafe override); left flagged.
+    Kept,
+    /// Now safe; removed from sensitive images.
+    Cleared,
+    /// Fetch or scan failed; left flagged.
+    Failed,
+}
+
+impl ReverifyOutcome {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Kept => "kept",
+            Self::Cleared => "cleared",
+            Self::Failed => "failed",
+        }
+    }
+}
+
+/// Columns selected into `SensitiveImageRow`.
+const SENSITIVE_IMAGE_COLUMNS: &str =
+    "id, image_id, url, reason, flagged_at, flagged_by, severity, violated_categories";
+
+/// ORDER BY for `SensitiveImageSort::Severity`: high first, unscored last.
+const SENSITIVE_IMAGE_SEVERITY_ORDER: &str =
+    "CASE severity WHEN 'high' THEN 3 WHEN 'medium' THEN 2 \
+     WHEN 'low' THEN 1 ELSE 0 END DESC, flagged_at DESC, id DESC";
+
+/// Ordering for the sensitive images listing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SensitiveImageSort {
+    /// Most recently flagged first (default).
+    #[default]
+    Newest,
+    /// Highest scan severity first, so the worst content is triaged first.
+    Severity,
+}
+
+impl SensitiveImageSort {
+    /// Parse from the admin `sort` param; unknown values mean newest.
+    pub fn parse(s: &str) -> Self {
+        match s {
+            "severity" => Self::Severity,
+            _ => Self::Newest,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Newest => "newest",
+            Self::Severity => "severity",
+        }
+    }
+
+    fn order_by(&self) -> &'static str {
+        match self {
+            Self::Newest => "flagged_at DESC, id DESC",
+            Self::Severity => SENSITIVE_IMAGE_SEVERITY_ORDER,
+        }
+    }
+}
+
+/// Stored Claude image scan.
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct ImageScanRow {
+    pub id: i64,
+    pub image_id: String,
+    pub is_safe: bool,
+    pub violated_categories: Option<serde_json::Value>,
+    pub severity: Option<String>,
+    pub explanation: Option<String>,
+    pub scanned_at: DateTime<Utc>,
+    pub model: Option<String>,
+    /// URL the image was fetched from, for URL scans
+    pub source_url: Option<String>,
+    /// Admin review state for unsafe results below the auto-flag threshold:
+    /// needs_review, approved, flagged, or overridden (null when no review
+    /// was needed)
+    pub review_state: Option<String>,
+    pub input_tokens: Option<i32>,
+    pub output_tokens: Option<i32>,
+    /// Estimated USD cost, as priced when the scan ran (null for unpriced
+    /// models and scans recorded before costs were stored)
+    #[serde(skip)]
+    pub cost_usd: Option<f64>,
+    /// Hex SHA-256 of the scanned bytes
+    pub content_hash: Option<String>,
+    /// For second-opinion passes, the id of the scan being re-checked
+    pub second_opinion_of: Option<i64>,
+    /// Anthropic request id, for tracing billing disputes
+    pub request_id: Option<String>,
+}
+
+/// Insert a signed label and return its sequence number.
+async fn insert_label<'e>(
+    executor: impl PgExecutor<'e>,
+    label: &Label,
+) -> Result<i64, sqlx::Error> {
+    let sig = label.sig.as_ref().map(|b| b.to_vec()).unwrap_or_default();
+    let cts: DateTime<Utc> = label.cts.parse().unwrap_or_else(|_| Utc::now());
+    let exp: Option<DateTime<Utc>> = label.exp.as_ref().and_then(|e| e.parse().ok());
+
+    sqlx::query_scalar::<_, i64>(
+        r#"
+        INSERT INTO labels (src, uri, cid, val, neg, cts, exp, sig)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+        RETURNING seq
+        "#,
+    )
+    .bind(&label.src)
+    .bind(&label.uri)
+    .bind(&label.cid)
+    .bind(&label.val)
+    .bind(label.neg.unwrap_or(false))
+    .bind(cts)
+    .bind(exp)
+    .bind(sig)
+    .fetch_one(executor)
+    .await
+}
+
+/// Read the positive label in force for a (source, URI, value) tuple: the
+/// latest event, if it isn't a negation and hasn't expired.
+async fn select_active_label<'e>(
+    executor: impl PgExecutor<'e>,
+    src: &str,
+    uri: &str,
+    val: &str,
+) -> Result<Option<LabelRow>, sqlx::Error> {
+    sqlx::query_as::<_, LabelRow>(
+        r#"
+        SELECT seq, src, uri, cid, val, neg, cts, exp, sig
+        FROM (
+            SELECT seq, src, uri, cid, val, neg, cts, exp, sig
+            FROM labels
+            WHERE src = $1 AND uri = $2 AND val = $3
+            ORDER BY seq DESC
+            LIMIT 1
+        ) latest
+        WHERE neg = false
+          AND (exp IS NULL OR exp > NOW())
+        "#,
+    )
+    .bind(src)
+    .bind(uri)
+    .bind(val)
+    .fetch_optional(executor)
+    .await
+}
+
+/// Append an audit log entry, returning its id.
+async fn insert_audit_event<'e>(
+    executor: impl PgExecutor<'e>,
+    action: &str,
+    target: &str,
+    actor: &str,
+    details: serde_json::Value,
+) -> Result<i64, sqlx::Error> {
+    sqlx::query_scalar::<_, i64>(
+        r#"
+        INSERT INTO audit_log (action, target, actor, details)
+        VALUES ($1, $2, $3, $4)
+        RETURNING id
+        "#,
+    )
+    .bind(action)
+    .bind(target)
+    .bind(actor)
+    .bind(details)
+    .fetch_one(executor)
+    .await
+}
+
+/// Keep a webhook delivery in `failed_deliveries`.
+async fn insert_failed_delivery<'e>(
+    executor: impl PgExecutor<'e>,
+    target: &str,
+    payload: &serde_json::Value,
+    error: &str,
+    attempts: i32,
+) -> Result<FailedDelivery, sqlx::Error> {
+    sqlx::query_as::<_, FailedDelivery>(
+        r#"
+        INSERT INTO failed_deliveries (target, event, payload, error, attempts)
+        VALUES ($1, $2, $3, $4, $5)
+        RETURNING *
+        "#,
+    )
+    .bind(target)
+    .bind(payload["event"].as_str().unwrap_or("unknown"))
+    .bind(payload)
+    .bind(error)
+    .bind(attempts)
+    .fetch_one(executor)
+    .await
+}
+
+/// Insert a positive label unless the same (source, URI, value) already has
+/// one in force, holding a transaction-scoped advisory lock on the URI so
+/// concurrent callers can't both find nothing active and both insert.
+async fn insert_label_unless_active(
+    conn: &mut PgConnection,
+    label: &Label,
+) -> Result<StoredLabel, sqlx::Error> {
+    sqlx::query("SELECT pg_advisory_xact_lock(hashtext($1))")
+        .bind(&label.uri)
+        .execute(&mut *conn)
+        .await?;
+    if let Some(active) = select_active_label(&mut *conn, &label.src, &label.uri, &label.val).await? {
+        return Ok(StoredLabel::Existing(active));
+    }
+    Ok(StoredLabel::Created(insert_label(&mut *conn, label).await?))
+}
+
+/// Build the WHERE clause for `query_labels`.
+///
+/// Placeholders are numbered in bind order: URI patterns, then sources, then
+/// label values, then the cursor.
+fn label_query_where(
+    uri_patterns: &[String],
+    sources: Option<&[String]>,
+    vals: Option<&[String]>,
+    has_cursor: bool,
+) -> String {
+    let mut conditions = Vec::new();
+    let mut param_idx = 1;
+
+    // URI pattern matching
+    let uri_conditions: Vec<String> = uri_patterns
+        .iter()
+        .map(|p| {
+            let idx = param_idx;
+            param_idx += 1;
+            if p.contains('*') {
+                format!("uri LIKE ${}", idx)
+            } else {
+                format!("uri = ${}", idx)
+            }
+        })
+        .collect();
+
+    if !uri_conditions.is_empty() {
+        conditions.push(format!("({})", uri_conditions.join(" OR ")));
+    }
+
+    // Source and label value filtering
+    for (column, values) in [("src", sources), ("val", vals)] {
+        if let Some(values) = values {
+            if !values.is_empty() {
+                let placeholders: Vec<String> = values
+                    .iter()
+                    .map(|_| {
+                        let idx = param_idx;
+                        param_idx += 1;
+                        format!("${}", idx)
+                    })
+                    .collect();
+                conditions.push(format!("{} IN ({})", column, placeholders.join(", ")));
+            }
+        }
+    }
+
+    // Cursor for pagination
+    if has_cursor {
+        conditions.push(format!("seq > ${}", param_idx));
+    }
+
+    // Expired labels are no longer in effect
+    conditions.push("(exp IS NULL OR exp > NOW())".to_string());
+
+    format!("WHERE {}", conditions.join(" AND "))
+}
+
+/// String values to bind for `label_query_where`, in placeholder order. The
+/// cursor, if any, is bound after these.
+fn label_query_binds(
+    uri_patterns: &[String],
+    sources: Option<&[String]>,
+    vals: Option<&[String]>,
+) -> Vec<String> {
+    uri_patterns
+        .iter()
+        .map(|p| p.replace('*', "%"))
+        .chain(sources.unwrap_or_default().iter().cloned())
+        .chain(vals.unwrap_or_default().iter().cloned())
+        .collect()
+}
+
+/// Upsert a URI's resolution reason without overwriting other context.
+///
+/// URIs that never had context get a row holding just the resolution, so the
+/// reason still shows on the flag card. Resolving a flag also drops any
+/// confirmation, so a later flag on the URI doesn't inherit it.
+async fn upsert_resolution<'e>(
+    executor: impl PgExecutor<'e>,
+    uri: &str,
+    reason: &ResolutionReason,
+    notes: Option<&str>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO label_context (uri, resolution_reason, resolution_notes)
+        VALUES ($1, $2, $3)
+        ON CONFLICT (uri) DO UPDATE SET
+            resolution_reason = EXCLUDED.resolution_reason,
+            resolution_notes = EXCLUDED.resolution_notes,
+            confirmed_by = NULL,
+            confirmed_at = NULL
+        "#,
+    )
+    .bind(uri)
+    .bind(reason.as_str())
+    .bind(notes)
+    .execute(executor)
+    .await?;
+
+    Ok(())
+}
+
+/// Record who confirmed a URI's flag as a violation, without overwriting
+/// other context. A later confirmation replaces the earlier one.
+async fn upsert_confirmation<'e>(
+    executor: impl PgExecutor<'e>,
+    uri: &str,
+    confirmed_by: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO label_context (uri, confirmed_by, confirmed_at)
+        VALUES ($1, $2, NOW())
+        ON CONFLICT (uri) DO UPDATE SET
+            confirmed_by = EXCLUDED.confirmed_by,
+            confirmed_at = EXCLUDED.confirmed_at
+        "#,
+    )
+    .bind(uri)
+    .bind(confirmed_by)
+    .execute(executor)
+    .await?;
+
+    Ok(())
+}
+
+/// Drop a URI's confirmation when its flag is negated without a resolution.
+async fn clear_confirmation<'e>(executor: impl PgExecutor<'e>, uri: &str) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "UPDATE label_context SET confirmed_by = NULL, confirmed_at = NULL WHERE uri = $1",
+    )
+    .bind(uri)
+    .execute(executor)
+    .await?;
+
+    Ok(())
+}
+
+/// Record a review decision on a flag.
+async fn insert_review_decision<'e>(
+    executor: impl PgExecutor<'e>,
+    uri: &str,
+    batch_id: Option<&str>,
+    decision: &str,
+    actor: &str,
+    notes: Option<&str>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO review_decisions (uri, batch_id, decision, actor, notes)
+        VALUES ($1, $2, $3, $4, $5)
+        "#,
+    )
+    .bind(uri)
+    .bind(batch_id)
+    .bind(decision)
+    .bind(actor)
+    .bind(notes)
+    .execute(executor)
+    .await?;
+
+    Ok(())
+}
+
+/// Insert a negation along with its resolution reason (or, without one, drop
+/// the URI's confirmation) and `actor`'s `clear` review decision. Returns the
+/// seq.
+async fn insert_negation(
+    conn: &mut PgConnection,
+    label: &Label,
+    reason: Option<&ResolutionReason>,
+    notes: Option<&str>,
+    actor: &str,
+) -> Result<i64, sqlx::Error> {
+    let seq = insert_label(&mut *conn, label).await?;
+    match reason {
+        Some(r) => upsert_resolution(&mut *conn, &label.uri, r, notes).await?,
+        None => clear_confirmation(&mut *conn, &label.uri).await?,
+    }
+    insert_review_decision(&mut *conn, &label.uri, None, "clear", actor, notes).await?;
+    Ok(seq)
+}
+
+/// Columns selected into `UserReport`, with the report's supporter count.
+const REPORT_COLUMNS: &str = "user_reports.*, \
+     (SELECT COUNT(*) FROM report_supporters s WHERE s.report_id = user_reports.id) AS supporter_count";
+
+/// Read a user report by id.
+async fn select_report<'e>(
+    executor: impl PgExecutor<'e>,
+    id: i32,
+) -> Result<UserReport, sqlx::Error> {
+    sqlx::query_as::<_, UserReport>(&format!(
+        "SELECT {REPORT_COLUMNS} FROM user_reports WHERE id = $1"
+    ))
+    .bind(id)
+    .fetch_one(executor)
+    .await
+}
+
+/// Columns selected into `ImageScanRow`.
+const IMAGE_SCAN_COLUMNS: &str = "id, image_id, is_safe, violated_categories, severity, explanation, \
+     scanned_at, model, source_url, review_state, input_tokens, output_tokens, cost_usd, \
+     content_hash, second_opinion_of, request_id";
+
+/// A new image scan to record.
+#[derive(Debug)]
+pub struct NewImageScan<'a> {
+    pub image_id: &'a str,
+    pub is_safe: bool,
+    pub violated_categories: &'a [String],
+    pub severity: &'a str,
+    pub explanation: &'a str,
+    pub model: &'a str,
+    /// Set when the service fetched the image itself.
+    pub source_url: Option<&'a str>,
+    /// `needs_review` when the result was held for an admin, `overridden`
+    /// when a human override decided the outcome.
+    pub review_state: Option<&'a str>,
+    pub input_tokens: u32,
+    pub output_tokens: u32,
+    /// Estimated USD cost at current prices (None for unpriced models).
+    pub cost_usd: Option<f64>,
+    pub content_hash: &'a str,
+    /// Set on a second-opinion pass to link it to the first scan.
+    pub second_opinion_of: Option<i64>,
+    pub request_id: Option<&'a str>,
+}
+
+/// A new audio scan to record.
+#[derive(Debug)]
+pub struct NewAudioScan<'a> {
+    /// Track the scan belongs to, if the client said.
+    pub uri: Option<&'a str>,
+    /// Filename the client uploaded, if it sent one.
+    pub filename: Option<&'a str>,
+    /// SHA-256 of the uploaded bytes, hex-encoded.
+    pub content_hash: &'a str,
+    pub size_bytes: i64,
+    pub is_flagged: bool,
+    pub dominant_match: Option<&'a str>,
+    pub dominant_match_pct: i32,
+    pub sustained_song_count: usize,
+    pub matches: serde_json::Value,
+    pub raw_response: &'a serde_json::Value,
+}
+
+/// A stored audio scan (without the raw AuDD response).
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct AudioScanRow {
+    pub id: i64,
+    pub uri: Option<String>,
+    pub filename: Option<String>,
+    pub content_hash: String,
+    pub size_bytes: i64,
+    pub is_flagged: bool,
+    pub dominant_match: Option<String>,
+    pub dominant_match_pct: i32,
+    pub sustained_song_count: i32,
+    pub matches: serde_json::Value,
+    pub scanned_at: DateTime<Utc>,
+}
+
+/// A human verdict on an image that overrides automated scans.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OverrideVerdict {
+    Safe,
+    Unsafe,
+}
+
+impl OverrideVerdict {
+    /// Parse from string.
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "safe" => Some(Self::Safe),
+            "unsafe" => Some(Self::Unsafe),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Safe => "safe",
+            Self::Unsafe => "unsafe",
+        }
+    }
+}
+
+/// Stored image override.
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct ImageOverrideRow {
+    pub id: i64,
+    pub image_id: Option<String>,
+    pub content_hash: Option<String>,
+    pub verdict: String,
+    pub reviewed_by: String,
+    pub notes: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl ImageOverrideRow {
+    pub fn verdict(&self) -> Option<OverrideVerdict> {
+        OverrideVerdict::from_str(&self.verdict)
+    }
+}
+
+/// What applying an image override changed.
+#[derive(Debug, Clone, Copy)]
+pub struct AppliedOverride {
+    pub override_id: i64,
+    /// Sensitive entries removed by a safe override.
+    pub removed: u64,
+    /// Sensitive entry created by an unsafe override.
+    pub sensitive_image_id: Option<i64>,
+}
+
+/// Entry in the moderation audit log.
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct AuditLogRow {
+    pub id: i64,
+    pub action: String,
+    pub target: String,
+    pub actor: String,
+    pub details: Option<serde_json::Value>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// A webhook delivery that failed every attempt (or, for escalations, hasn't
+/// got through yet), kept so it can be inspected and retried.
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct FailedDelivery {
+    pub id: i64,
+    /// URL the delivery was sent to.
+    pub target: String,
+    pub event: String,
+    /// The envelope as it was sent.
+    pub payload: serde_json::Value,
+    /// Why the latest attempt failed.
+    pub error: String,
+    pub attempts: i32,
+    pub created_at: DateTime<Utc>,
+    pub retried_at: Option<DateTime<Utc>>,
+    /// Set once a delivery or retry got through.
+    pub delivered_at: Option<DateTime<Utc>>,
+}
+
+/// A confirmed flag awaiting (or done with) its enforcement callback.
+#[derive(Debug, Clone, FromRow)]
+pub struct EnforcementRequestRow {
+    pub uri: String,
+    pub track_id: Option<i64>,
+    pub confirmed_by: String,
+}
+
+/// Filters for listing image scans.
+#[derive(Debug, Default)]
+pub struct ImageScanFilter<'a> {
+    pub image_id: Option<&'a str>,
+    pub is_safe: Option<bool>,
+    pub severity: Option<&'a str>,
+    pub since: Option<DateTime<Utc>>,
+    pub until: Option<DateTime<Utc>>,
 }
 
 /// User-submitted content report.
@@ -37,12 +651,176 @@ pub struct UserReport {
     pub reason: String,
     pub description: Option<String>,
     pub screenshot_url: Option<String>,
+    /// Storage key of a screenshot uploaded with the report. Served to admins
+    /// only, through `/admin/reports/:id/screenshot`.
+    pub screenshot_key: Option<String>,
     pub status: String,
     pub admin_notes: Option<String>,
     pub resolved_by: Option<String>,
     pub created_at: DateTime<Utc>,
     pub updated_at: Option<DateTime<Utc>>,
     pub resolved_at: Option<DateTime<Utc>>,
+    /// Label emitted from this report, if it was resolved with one.
+    pub label_seq: Option<i64>,
+    /// Other reporters who filed the same report while it was unresolved.
+    #[sqlx(default)]
+    #[serde(default)]
+    pub supporter_count: i64,
+}
+
+/// Position of the last report on a page, newest first.
+///
+/// `id` breaks ties between reports filed in the same microsecond, matching
+/// the `(created_at, id)` row comparison in `list_reports`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReportsCursor {
+    pub created_at: DateTime<Utc>,
+    pub id: i32,
+}
+
+impl ReportsCursor {
+    /// The cursor for the page after `report`.
+    pub fn after(report: &UserReport) -> Self {
+        Self {
+            created_at: report.created_at,
+            id: report.id,
+        }
+    }
+
+    /// Encode for the `cursor` param: microseconds since the epoch (postgres
+    /// timestamp precision, so it round-trips exactly) and the id.
+    pub fn encode(&self) -> String {
+        format!("{}:{}", self.created_at.timestamp_micros(), self.id)
+    }
+
+    /// Parse a cursor produced by `encode`.
+    pub fn parse(s: &str) -> Option<Self> {
+        let (micros, id) = s.split_once(':')?;
+        Some(Self {
+            created_at: DateTime::from_timestamp_micros(micros.parse().ok()?)?,
+            id: id.parse().ok()?,
+        })
+    }
+}
+
+/// Filters for listing user reports. Every one that's set must match.
+#[derive(Debug, Default)]
+pub struct ReportFilter<'a> {
+    pub status: Option<&'a str>,
+    pub target_type: Option<&'a str>,
+    pub target_id: Option<&'a str>,
+    pub target_uri: Option<&'a str>,
+    /// Substring of the description or admin notes, case-insensitive.
+    pub q: Option<&'a str>,
+}
+
+impl ReportFilter<'_> {
+    /// SQL conditions and the values they bind, numbered from `$1`.
+    fn conditions(&self) -> (Vec<String>, Vec<String>) {
+        let mut conditions = Vec::new();
+        let mut binds = Vec::new();
+        for (column, value) in [
+            ("status", self.status),
+            ("target_type", self.target_type),
+            ("target_id", self.target_id),
+            ("target_uri", self.target_uri),
+        ] {
+            if let Some(value) = value {
+                binds.push(value.to_string());
+                conditions.push(format!("{} = ${}", column, binds.len()));
+            }
+        }
+        if let Some(q) = self.q {
+            binds.push(handle_like_pattern(q));
+            conditions.push(format!(
+                "{} ILIKE ${} ESCAPE '\\'",
+                REPORT_SEARCH_DOCUMENT,
+                binds.len()
+            ));
+        }
+        (conditions, binds)
+    }
+}
+
+/// Text the reports `q` search matches against. The migration indexes this
+/// exact expression, which the planner needs to use the index.
+const REPORT_SEARCH_DOCUMENT: &str =
+    "(COALESCE(description, '') || ' ' || COALESCE(admin_notes, ''))";
+
+/// One page of user reports.
+#[derive(Debug, Clone)]
+pub struct ReportsPage {
+    pub reports: Vec<UserReport>,
+    /// Cursor for the next page, or `None` on the last one.
+    pub next_cursor: Option<String>,
+}
+
+/// Number of user reports with a given reason and status.
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct ReportCount {
+    pub reason: String,
+    pub status: String,
+    pub count: i64,
+}
+
+/// One reporter's recent reports, for the per-reporter report limits.
+#[derive(Debug, Clone, Default, FromRow)]
+pub struct ReporterActivity {
+    /// Reports filed in the last hour.
+    pub last_hour: i64,
+    /// Reports filed in the last day.
+    pub last_day: i64,
+    pub oldest_last_hour: Option<DateTime<Utc>>,
+    pub oldest_last_day: Option<DateTime<Utc>>,
+}
+
+/// One reporter's reports, for judging how credible a new one is.
+#[derive(Debug, Clone, Default)]
+pub struct ReporterReports {
+    /// Reports they filed, per (reason, status).
+    pub counts: Vec<ReportCount>,
+    /// Reports they joined as a supporter rather than filing.
+    pub supported: i64,
+    /// Their newest reports first.
+    pub recent: Vec<UserReport>,
+}
+
+/// What filing a user report did.
+#[derive(Debug, Clone)]
+pub enum FiledReport {
+    /// A new report was created.
+    Created(UserReport),
+    /// An unresolved report already covered the same target and reason; the
+    /// reporter was added to it as a supporter.
+    Supported(UserReport),
+    /// The reporter had already filed or supported this unresolved report;
+    /// nothing was written.
+    AlreadyReported(UserReport),
+}
+
+/// What closing several reports at once did.
+#[derive(Debug, Clone)]
+pub enum BulkReportResolution {
+    /// These ids don't exist, so nothing was changed.
+    Missing(Vec<i32>),
+    Closed {
+        /// Reports that were open and are now closed.
+        resolved: Vec<UserReport>,
+        /// Ids that were already resolved or dismissed, with that status.
+        already_closed: Vec<(i32, String)>,
+    },
+}
+
+/// Rows removed by one retention run.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PruneSummary {
+    /// Labels removed: negations and the labels they negated.
+    pub labels: u64,
+    /// Resolved or dismissed reports removed.
+    pub reports: u64,
+    /// Storage keys of screenshots uploaded with the removed reports, for
+    /// the caller to delete from the bucket.
+    pub screenshot_keys: Vec<String>,
 }
 
 /// Review batch for mobile-friendly flag review.
@@ -51,10 +829,87 @@ pub struct ReviewBatch {
     pub id: String,
     pub created_at: DateTime<Utc>,
     pub expires_at: Option<DateTime<Utc>>,
-    /// Status: pending, completed.
+    /// Status: pending, completed, expired.
     pub status: String,
     /// Who created this batch.
     pub created_by: Option<String>,
+    /// Who claimed the batch for review, as shown to other reviewers.
+    /// Cleared when it completes or expires.
+    pub assigned_to: Option<String>,
+    /// The claimant's credential (`Caller::id`), which decides whose claim
+    /// it is. Cleared along with `assigned_to`.
+    #[serde(default, skip_serializing)]
+    pub assigned_caller: Option<String>,
+}
+
+impl ReviewBatch {
+    /// Whether the batch has passed its deadline. Pending batches count as
+    /// expired as soon as `expires_at` passes, before the sweeper marks them.
+    pub fn is_expired(&self, now: DateTime<Utc>) -> bool {
+        match self.status.as_str() {
+            "expired" => true,
+            "pending" => self.expires_at.is_some_and(|exp| exp <= now),
+            _ => false,
+        }
+    }
+
+    /// The reviewer holding the batch, while it's still open for decisions.
+    /// A claim without a credential (`assigned_caller`) can't be checked
+    /// against anyone, so the batch counts as unclaimed.
+    pub fn active_claim(&self, now: DateTime<Utc>) -> Option<&str> {
+        if self.status == "pending" && !self.is_expired(now) && self.assigned_caller.is_some() {
+            self.assigned_to.as_deref()
+        } else {
+            None
+        }
+    }
+
+    /// The active claim's holder, unless `caller` (a `Caller::id`) holds it.
+    pub fn claimed_by_other(&self, caller: &str, now: DateTime<Utc>) -> Option<&str> {
+        self.active_claim(now)
+            .filter(|_| self.assigned_caller.as_deref() != Some(caller))
+    }
+}
+
+/// A review batch with its decision progress.
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct BatchSummary {
+    pub id: String,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub status: String,
+    pub created_by: Option<String>,
+    pub assigned_to: Option<String>,
+    /// Flags in the batch.
+    pub total: i64,
+    /// Flags with a submitted decision.
+    pub decided: i64,
+    /// Flags still awaiting a decision.
+    pub pending: i64,
+}
+
+/// Flags in a batch sharing a decision (no decision yet when not reviewed).
+#[derive(Debug, Clone, FromRow)]
+pub struct BatchDecisionCount {
+    pub decision: Option<String>,
+    pub reviewed: bool,
+    pub count: i64,
+    /// Latest `reviewed_at` among these flags.
+    pub last_reviewed_at: Option<DateTime<Utc>>,
+}
+
+/// What happened to one decision in a batch submission.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DecisionOutcome {
+    /// Recorded now, with the negation's sequence number if it cleared the flag.
+    Applied { seq: Option<i64> },
+    /// The flag already had this decision in the batch, e.g. a retried
+    /// submission; nothing was written.
+    AlreadyProcessed,
+    /// The flag already had a different decision in the batch.
+    Conflicting { existing: String },
+    /// The URI isn't a flag in the batch.
+    NotInBatch,
 }
 
 /// A flag within a review batch.
@@ -69,6 +924,20 @@ pub struct BatchFlag {
     pub decision: Option<String>,
 }
 
+/// One review decision on a flag.
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct ReviewDecisionRow {
+    pub id: i64,
+    pub uri: String,
+    /// The review batch it was made in; null for dashboard decisions.
+    pub batch_id: Option<String>,
+    /// "clear", "defer" or "confirm".
+    pub decision: String,
+    pub actor: String,
+    pub notes: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
 /// Type alias for context row from database query.
 type ContextRow = (
     Option<i64>,    // track_id
@@ -79,77 +948,513 @@ type ContextRow = (
     Option<serde_json::Value>, // matches
     Option<String>, // resolution_reason
     Option<String>, // resolution_notes
+    Option<String>, // audio_url
+    Option<String>, // confirmed_by
+    Option<DateTime<Utc>>, // confirmed_at
 );
 
-/// Type alias for flagged track row from database query.
-type FlaggedRow = (
-    i64,            // seq
-    String,         // uri
-    String,         // val
-    DateTime<Utc>,  // cts
-    Option<i64>,    // track_id
-    Option<String>, // track_title
-    Option<String>, // artist_handle
-    Option<String>, // artist_did
-    Option<f64>,    // highest_score
-    Option<serde_json::Value>, // matches
-    Option<String>, // resolution_reason
-    Option<String>, // resolution_notes
-);
-
-/// Copyright match info stored alongside labels.
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct CopyrightMatch {
-    pub title: String,
-    pub artist: String,
-    pub score: f64,
+/// Flagged track row from database query (see `flagged_rows_sql`).
+#[derive(Debug, Default, sqlx::FromRow)]
+struct FlaggedRow {
+    seq: i64,
+    uri: String,
+    val: String,
+    cts: DateTime<Utc>,
+    track_id: Option<i64>,
+    track_title: Option<String>,
+    artist_handle: Option<String>,
+    artist_did: Option<String>,
+    highest_score: Option<f64>,
+    matches: Option<serde_json::Value>,
+    resolution_reason: Option<String>,
+    resolution_notes: Option<String>,
+    resolved_at: Option<DateTime<Utc>>,
+    match_count: i64,
+    enforcement_status: Option<String>,
+    audio_url: Option<String>,
+    confirmed_by: Option<String>,
+    confirmed_at: Option<DateTime<Utc>>,
+    label_count: i64,
 }
 
-/// Reason for resolving a false positive.
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
-#[serde(rename_all = "snake_case")]
-pub enum ResolutionReason {
-    /// Artist uploaded their own distributed music
-    OriginalArtist,
-    /// Artist has licensing/permission for the content
-    Licensed,
-    /// Fingerprint matcher produced a false match
-    FingerprintNoise,
-    /// Legal cover version or remix
-    CoverVersion,
-    /// Content was deleted from plyr.fm
-    ContentDeleted,
-    /// Other reason (see resolution_notes)
-    Other,
+/// Which flags to list, by resolution status.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FlagStatus {
+    /// Not yet negated (default).
+    #[default]
+    Pending,
+    /// Negated at least once (marked false positive).
+    Resolved,
+    /// Not negated, and confirmed as a violation.
+    Confirmed,
+    All,
 }
 
-impl ResolutionReason {
-    /// Human-readable label for the reason.
-    pub fn label(&self) -> &'static str {
-        match self {
-            Self::OriginalArtist => "original artist",
-            Self::Licensed => "licensed",
-            Self::FingerprintNoise => "fingerprint noise",
-            Self::CoverVersion => "cover/remix",
-            Self::ContentDeleted => "content deleted",
-            Self::Other => "other",
+impl FlagStatus {
+    /// Parse from the admin `filter` param; unknown values mean pending.
+    pub fn parse(s: &str) -> Self {
+        match s {
+            "resolved" => Self::Resolved,
+            "confirmed" => Self::Confirmed,
+            "all" => Self::All,
+            _ => Self::Pending,
         }
     }
 
-    /// Parse from string.
-    pub fn from_str(s: &str) -> Option<Self> {
-        match s {
-            "original_artist" => Some(Self::OriginalArtist),
-            "licensed" => Some(Self::Licensed),
-            "fingerprint_noise" => Some(Self::FingerprintNoise),
-            "cover_version" => Some(Self::CoverVersion),
-            "content_deleted" => Some(Self::ContentDeleted),
-            "other" => Some(Self::Other),
-            _ => None,
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Pending => "pending",
+            Self::Resolved => "resolved",
+            Self::Confirmed => "confirmed",
+            Self::All => "all",
         }
     }
 }
 
+/// Ordering for the flags list.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FlagSort {
+    /// Most recent label first (default).
+    #[default]
+    Newest,
+    Oldest,
+    /// Most fingerprint matches first; flags without matches go last.
+    MostMatches,
+}
+
+impl FlagSort {
+    /// Parse from the admin `sort` param; unknown values mean newest.
+    pub fn parse(s: &str) -> Self {
+        match s {
+            "oldest" => Self::Oldest,
+            "most_matches" => Self::MostMatches,
+            _ => Self::Newest,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Newest => "newest",
+            Self::Oldest => "oldest",
+            Self::MostMatches => "most_matches",
+        }
+    }
+
+    fn order_by(&self) -> String {
+        match self {
+            Self::Newest => "l.seq DESC".to_string(),
+            Self::Oldest => "l.seq ASC".to_string(),
+            Self::MostMatches => format!("{} DESC, l.seq DESC", MATCH_COUNT_SQL),
+        }
+    }
+
+    /// Keyset condition for rows after `cursor`, with placeholders starting at `idx`.
+    fn after_cursor(&self, idx: usize) -> String {
+        match self {
+            Self::Newest => format!("l.seq < ${}", idx),
+            Self::Oldest => format!("l.seq > ${}", idx),
+            Self::MostMatches => format!("({}, l.seq) < (${}, ${})", MATCH_COUNT_SQL, idx, idx + 1),
+        }
+    }
+}
+
+/// Match count used for sorting; flags with no (or non-array) matches count
+/// as `NO_MATCHES` so they sort after flags with zero matches.
+const MATCH_COUNT_SQL: &str = "COALESCE(CASE WHEN jsonb_typeof(c.matches) = 'array' \
+     THEN jsonb_array_length(c.matches) END, -1)::BIGINT";
+
+const NO_MATCHES: i64 = -1;
+
+/// Position of the last flag on a page, in the order of its `FlagSort`.
+///
+/// Ordering (match count, then seq) is the same as SQL's row comparison in
+/// `FlagSort::after_cursor`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct FlagsCursor {
+    pub match_count: i64,
+    pub seq: i64,
+}
+
+impl FlagsCursor {
+    /// Encode for the `cursor` param. Only `most_matches` needs the count.
+    pub fn encode(&self, sort: FlagSort) -> String {
+        match sort {
+            FlagSort::MostMatches => format!("{}:{}", self.match_count, self.seq),
+            FlagSort::Newest | FlagSort::Oldest => self.seq.to_string(),
+        }
+    }
+
+    /// Parse a cursor produced by `encode` for the same sort.
+    pub fn parse(s: &str, sort: FlagSort) -> Option<Self> {
+        match sort {
+            FlagSort::MostMatches => {
+                let (count, seq) = s.split_once(':')?;
+                Some(Self {
+                    match_count: count.parse().ok()?,
+                    seq: seq.parse().ok()?,
+                })
+            }
+            FlagSort::Newest | FlagSort::Oldest => Some(Self {
+                match_count: NO_MATCHES,
+                seq: s.parse().ok()?,
+            }),
+        }
+    }
+}
+
+/// Page request for flagged tracks.
+#[derive(Debug, Clone, Default)]
+pub struct FlagsPageQuery {
+    pub status: FlagStatus,
+    pub sort: FlagSort,
+    /// Only flags after this position (the previous page's `next_cursor`).
+    pub cursor: Option<FlagsCursor>,
+    pub limit: i64,
+    /// Case-insensitive substring of the artist handle.
+    pub artist: Option<String>,
+    /// Exact artist DID.
+    pub did: Option<String>,
+    /// Case-insensitive substring of the track title, artist handle, or a
+    /// match's title or artist.
+    pub q: Option<String>,
+    /// Only flags created at or after this time.
+    pub from: Option<DateTime<Utc>>,
+    /// Only flags created before this time.
+    pub to: Option<DateTime<Utc>>,
+}
+
+impl FlagsPageQuery {
+    /// WHERE conditions for this query, and the values to bind in order.
+    ///
+    /// Artist filters match against `label_context`, so flags without context
+    /// drop out only when one of them is set.
+    fn conditions(&self) -> (Vec<String>, Vec<String>) {
+        let mut conditions = vec![
+            "l.val = 'copyright-violation'".to_string(),
+            "l.neg = false".to_string(),
+            "(l.exp IS NULL OR l.exp > NOW())".to_string(),
+            FLAG_LATEST_SQL.to_string(),
+        ];
+        match self.status {
+            FlagStatus::Pending => conditions.push(format!("NOT {}", FLAG_RESOLVED_SQL)),
+            FlagStatus::Resolved => conditions.push(FLAG_RESOLVED_SQL.to_string()),
+            FlagStatus::Confirmed => {
+                conditions.push(format!("NOT {}", FLAG_RESOLVED_SQL));
+                conditions.push("c.confirmed_at IS NOT NULL".to_string());
+            }
+            FlagStatus::All => {}
+        }
+
+        let mut binds = Vec::new();
+        if let Some(artist) = &self.artist {
+            binds.push(handle_like_pattern(artist));
+            conditions.push(format!(
+                "LOWER(c.artist_handle) LIKE ${} ESCAPE '\\'",
+                binds.len()
+            ));
+        }
+        if let Some(did) = &self.did {
+            binds.push(did.clone());
+            conditions.push(format!("c.artist_did = ${}", binds.len()));
+        }
+        if let Some(q) = &self.q {
+            binds.push(handle_like_pattern(q));
+            conditions.push(format!(
+                "{} ILIKE ${} ESCAPE '\\'",
+                flag_search_document("c."),
+                binds.len()
+            ));
+        }
+        if let Some(from) = self.from {
+            binds.push(from.to_rfc3339());
+            conditions.push(format!("l.cts >= ${}::timestamptz", binds.len()));
+        }
+        if let Some(to) = self.to {
+            binds.push(to.to_rfc3339());
+            conditions.push(format!("l.cts < ${}::timestamptz", binds.len()));
+        }
+        (conditions, binds)
+    }
+}
+
+/// Text the flags `q` search matches against: track title, artist handle,
+/// and the title and artist of every fingerprint match.
+///
+/// `prefix` qualifies the `label_context` columns. The migration indexes the
+/// unqualified form, and the planner only uses the index when the query's
+/// expression is identical, so both must come from here.
+fn flag_search_document(prefix: &str) -> String {
+    format!(
+        "(COALESCE({p}track_title, '') || ' ' || COALESCE({p}artist_handle, '') || ' ' || \
+         COALESCE(jsonb_path_query_array({p}matches, '$[*].title')::text, '') || ' ' || \
+         COALESCE(jsonb_path_query_array({p}matches, '$[*].artist')::text, ''))",
+        p = prefix
+    )
+}
+
+/// Lowercased `%substring%` LIKE pattern with wildcards in the input escaped.
+fn handle_like_pattern(s: &str) -> String {
+    let escaped = s
+        .to_lowercase()
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_");
+    format!("%{}%", escaped)
+}
+
+/// A page of flagged tracks.
+#[derive(Debug)]
+pub struct FlagsPage {
+    pub tracks: Vec<FlaggedTrack>,
+    /// Flags matching the status filter across all pages.
+    pub total: i64,
+    /// Cursor for the next page, if there is one.
+    pub next_cursor: Option<String>,
+}
+
+/// SQL for "this is its URI's latest copyright flag".
+///
+/// A URI flagged more than once (retries, re-flags after a resolution) is
+/// listed and counted once, under its newest flag. A newer flag that has
+/// already expired doesn't hide one still in force.
+const FLAG_LATEST_SQL: &str = "NOT EXISTS (SELECT 1 FROM labels p \
+     WHERE p.uri = l.uri AND p.val = 'copyright-violation' AND p.neg = false AND p.seq > l.seq \
+       AND (p.exp IS NULL OR p.exp > NOW()))";
+
+/// How many copyright flags were issued for the flag's URI.
+const FLAG_COUNT_SQL: &str = "(SELECT COUNT(*) FROM labels p \
+     WHERE p.uri = l.uri AND p.val = 'copyright-violation' AND p.neg = false)";
+
+/// SQL for "this flag has a copyright negation from the same source".
+/// Only negations issued after the flag count, so a flag re-emitted by an
+/// unresolve is pending again; a negation under another DID (e.g. a
+/// namespace DID) leaves a flag issued as the labeler DID in force.
+/// Combined with `FLAG_LATEST_SQL`, only a negation of the newest flag
+/// resolves the URI.
+const FLAG_RESOLVED_SQL: &str = "EXISTS (SELECT 1 FROM labels n \
+     WHERE n.src = l.src AND n.uri = l.uri AND n.val = 'copyright-violation' \
+       AND n.neg = true AND n.seq > l.seq)";
+
+/// When the flag was first negated, or NULL if it's still pending.
+const FLAG_RESOLVED_AT_SQL: &str = "(SELECT MIN(n.created_at) FROM labels n \
+     WHERE n.src = l.src AND n.uri = l.uri AND n.val = 'copyright-violation' \
+       AND n.neg = true AND n.seq > l.seq)";
+
+/// SELECT for flagged rows (see `FlaggedRow`) matching `where_clause`.
+fn flagged_rows_sql(where_clause: &str, sort: FlagSort) -> String {
+    format!(
+        r#"
+        SELECT l.seq, l.uri, l.val, l.cts,
+               c.track_id, c.track_title, c.artist_handle, c.artist_did, c.highest_score, c.matches,
+               c.resolution_reason, c.resolution_notes, {} AS resolved_at, {} AS match_count,
+               e.status AS enforcement_status, c.audio_url, c.confirmed_by, c.confirmed_at,
+               {} AS label_count
+        FROM labels l
+        LEFT JOIN label_context c ON l.uri = c.uri
+        LEFT JOIN enforcement_requests e ON l.uri = e.uri
+        WHERE {}
+        ORDER BY {}
+        "#,
+        FLAG_RESOLVED_AT_SQL,
+        MATCH_COUNT_SQL,
+        FLAG_COUNT_SQL,
+        where_clause,
+        sort.order_by()
+    )
+}
+
+/// Build a flagged track from a joined label/context row.
+fn flagged_track_from_row(row: FlaggedRow) -> FlaggedTrack {
+    let context = if row.track_id.is_some()
+        || row.track_title.is_some()
+        || row.artist_handle.is_some()
+        || row.resolution_reason.is_some()
+        || row.resolution_notes.is_some()
+        || row.audio_url.is_some()
+        || row.confirmed_at.is_some()
+    {
+        Some(LabelContext {
+            track_id: row.track_id,
+            track_title: row.track_title,
+            artist_handle: row.artist_handle,
+            artist_did: row.artist_did,
+            highest_score: row.highest_score,
+            matches: row.matches.and_then(|v| serde_json::from_value(v).ok()),
+            resolution_reason: row.resolution_reason.map(ResolutionReason::from),
+            resolution_notes: row.resolution_notes,
+            audio_url: row.audio_url,
+            confirmed_by: row.confirmed_by,
+            confirmed_at: row.confirmed_at,
+        })
+    } else {
+        None
+    };
+
+    FlaggedTrack {
+        seq: row.seq,
+        uri: row.uri,
+        val: row.val,
+        created_at: row.cts.format("%Y-%m-%d %H:%M:%S").to_string(),
+        resolved: row.resolved_at.is_some(),
+        resolved_at: row.resolved_at,
+        context,
+        enforcement: row.enforcement_status,
+        label_count: row.label_count,
+    }
+}
+
+/// Cursor for the page after `rows`, if the fetch returned more than `limit`.
+fn next_flags_cursor(rows: &mut Vec<FlaggedRow>, limit: usize, sort: FlagSort) -> Option<String> {
+    if rows.len() > limit {
+        rows.truncate(limit);
+        rows.last().map(|row| {
+            FlagsCursor {
+                match_count: row.match_count,
+                seq: row.seq,
+            }
+            .encode(sort)
+        })
+    } else {
+        None
+    }
+}
+
+/// Whether a label with the given expiry has lapsed.
+///
+/// Expired labels stay in the log (until pruned) but no longer count as active.
+pub fn is_expired(exp: Option<DateTime<Utc>>, now: DateTime<Utc>) -> bool {
+    exp.is_some_and(|e| e <= now)
+}
+
+/// Copyright match info stored alongside labels.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CopyrightMatch {
+    pub title: String,
+    pub artist: String,
+    pub score: f64,
+}
+
+/// Reason for resolving a false positive.
+///
+/// The set of valid reasons lives in the `resolution_reasons` table. The named
+/// variants are the seeded reasons that code treats specially; any other key
+/// is `Custom`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(from = "String", into = "String")]
+pub enum ResolutionReason {
+    /// Artist uploaded their own distributed music
+    OriginalArtist,
+    /// Artist has licensing/permission for the content
+    Licensed,
+    /// Fingerprint matcher produced a false match
+    FingerprintNoise,
+    /// Legal cover version or remix
+    CoverVersion,
+    /// Content was deleted from plyr.fm
+    ContentDeleted,
+    /// Other reason (see resolution_notes)
+    Other,
+    /// Reason added through the admin API, by key
+    Custom(String),
+}
+
+impl ResolutionReason {
+    /// Human-readable label for the reason. Custom reasons use their key;
+    /// their labels are in the `resolution_reasons` table.
+    pub fn label(&self) -> &str {
+        match self {
+            Self::OriginalArtist => "original artist",
+            Self::Licensed => "licensed",
+            Self::FingerprintNoise => "fingerprint noise",
+            Self::CoverVersion => "cover/remix",
+            Self::ContentDeleted => "content deleted",
+            Self::Other => "other",
+            Self::Custom(key) => key,
+        }
+    }
+
+    /// Stored form: the key in `resolution_reasons`, matching the serde
+    /// representation.
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::OriginalArtist => "original_artist",
+            Self::Licensed => "licensed",
+            Self::FingerprintNoise => "fingerprint_noise",
+            Self::CoverVersion => "cover_version",
+            Self::ContentDeleted => "content_deleted",
+            Self::Other => "other",
+            Self::Custom(key) => key,
+        }
+    }
+
+    /// Whether resolving with this reason needs notes. Licensed flags stay
+    /// on record, so the notes say where the license lives.
+    pub fn requires_notes(&self) -> bool {
+        matches!(self, Self::Licensed)
+    }
+
+    /// Wrap a reason key. This doesn't check the key exists; validate
+    /// incoming keys against `resolution_reasons` first.
+    ///
+    /// Also accepts the underscore-less forms older versions stored.
+    pub fn from_key(key: &str) -> Self {
+        match key {
+            "original_artist" | "originalartist" => Self::OriginalArtist,
+            "licensed" => Self::Licensed,
+            "fingerprint_noise" | "fingerprintnoise" => Self::FingerprintNoise,
+            "cover_version" | "coverversion" => Self::CoverVersion,
+            "content_deleted" | "contentdeleted" => Self::ContentDeleted,
+            "other" => Self::Other,
+            _ => Self::Custom(key.to_string()),
+        }
+    }
+}
+
+impl From<String> for ResolutionReason {
+    fn from(key: String) -> Self {
+        Self::from_key(&key)
+    }
+}
+
+impl From<ResolutionReason> for String {
+    fn from(reason: ResolutionReason) -> Self {
+        reason.as_str().to_string()
+    }
+}
+
+/// A row in `resolution_reasons`.
+#[derive(Debug, Clone, sqlx::FromRow, Serialize)]
+pub struct ResolutionReasonRow {
+    pub key: String,
+    pub label: String,
+    pub description: String,
+    /// Inactive reasons are kept for old resolutions but can't be chosen.
+    pub active: bool,
+    pub created_at: DateTime<Utc>,
+}
+
+/// A row in `report_reasons`.
+#[derive(Debug, Clone, sqlx::FromRow, Serialize)]
+pub struct ReportReasonRow {
+    pub key: String,
+    pub label: String,
+    pub description: String,
+    /// Reports with this reason must carry a `target_uri`.
+    pub requires_target_uri: bool,
+    /// Inactive reasons stay on old reports but can't be used for new ones.
+    pub active: bool,
+    pub created_at: DateTime<Utc>,
+}
+
+/// A row in `isrc_allowlist`.
+#[derive(Debug, Clone, sqlx::FromRow, Serialize)]
+pub struct IsrcAllowlistRow {
+    pub isrc: String,
+    pub note: String,
+    pub added_by: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
 /// Context stored alongside a label for display in admin UI.
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct LabelContext {
@@ -165,6 +1470,14 @@ pub struct LabelContext {
     /// Additional notes about the resolution.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub resolution_notes: Option<String>,
+    /// Where the track's audio can be fetched, for rescans.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub audio_url: Option<String>,
+    /// Who confirmed the flag as a violation (set on confirmation).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub confirmed_by: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub confirmed_at: Option<DateTime<Utc>>,
 }
 
 /// Database connection pool and operations.
@@ -187,6 +1500,169 @@ pub struct LabelRow {
     pub sig: Vec<u8>,
 }
 
+/// Pages of every exported table, read inside one `REPEATABLE READ`
+/// transaction. See `LabelDb::export_snapshot`.
+pub struct ExportSnapshot {
+    tx: sqlx::Transaction<'static, sqlx::Postgres>,
+}
+
+impl ExportSnapshot {
+    /// Labels with a seq above `after_seq`, in seq order.
+    pub async fn labels(
+        &mut self,
+        after_seq: i64,
+        limit: i64,
+    ) -> Result<Vec<ExportedLabel>, sqlx::Error> {
+        sqlx::query_as::<_, ExportedLabel>(
+            "SELECT seq, src, uri, cid, val, neg, cts, exp, encode(sig, 'hex') AS sig, created_at \
+             FROM labels WHERE seq > $1 ORDER BY seq LIMIT $2",
+        )
+        .bind(after_seq)
+        .bind(limit)
+        .fetch_all(&mut *self.tx)
+        .await
+    }
+
+    /// Label contexts with a URI after `after_uri`, in URI order.
+    pub async fn label_contexts(
+        &mut self,
+        after_uri: &str,
+        limit: i64,
+    ) -> Result<Vec<ExportedLabelContext>, sqlx::Error> {
+        sqlx::query_as::<_, ExportedLabelContext>(
+            r#"
+            SELECT uri, track_id, track_title, artist_handle, artist_did, highest_score, matches,
+                   resolution_reason, resolution_notes, audio_url, rescanned_at, confirmed_by,
+                   confirmed_at, created_at
+            FROM label_context
+            WHERE uri > $1
+            ORDER BY uri
+            LIMIT $2
+            "#,
+        )
+        .bind(after_uri)
+        .bind(limit)
+        .fetch_all(&mut *self.tx)
+        .await
+    }
+
+    /// Sensitive images with an id above `after_id`, in id order.
+    pub async fn sensitive_images(
+        &mut self,
+        after_id: i64,
+        limit: i64,
+    ) -> Result<Vec<SensitiveImageRow>, sqlx::Error> {
+        sqlx::query_as::<_, SensitiveImageRow>(&format!(
+            "SELECT {SENSITIVE_IMAGE_COLUMNS} FROM sensitive_images \
+             WHERE id > $1 ORDER BY id LIMIT $2"
+        ))
+        .bind(after_id)
+        .bind(limit)
+        .fetch_all(&mut *self.tx)
+        .await
+    }
+
+    /// Reports with an id above `after_id`, in id order, with their
+    /// supporters.
+    pub async fn reports(
+        &mut self,
+        after_id: i32,
+        limit: i64,
+    ) -> Result<Vec<ExportedReport>, sqlx::Error> {
+        sqlx::query_as::<_, ExportedReport>(&format!(
+            "SELECT {REPORT_COLUMNS}, \
+             ARRAY(SELECT s.reporter_did FROM report_supporters s \
+                   WHERE s.report_id = user_reports.id ORDER BY s.reporter_did) AS supporters \
+             FROM user_reports WHERE id > $1 ORDER BY id LIMIT $2"
+        ))
+        .bind(after_id)
+        .bind(limit)
+        .fetch_all(&mut *self.tx)
+        .await
+    }
+}
+
+/// Outcome of `store_label_unless_active`.
+#[derive(Debug)]
+pub enum StoredLabel {
+    /// Stored under this sequence number.
+    Created(i64),
+    /// Not stored: this label was already in force.
+    Existing(LabelRow),
+}
+
+/// Outcome of one negation in `LabelDb::store_negations`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StoredNegation {
+    /// The negation was stored with this sequence number.
+    Created(i64),
+    /// The label was no longer active, so nothing was stored.
+    NotActive,
+    /// Not stored because another label in an atomic batch wasn't active.
+    Skipped,
+}
+
+/// One entry in a URI's label timeline.
+#[derive(Debug, Clone, sqlx::FromRow, Serialize)]
+pub struct LabelHistoryRow {
+    pub seq: i64,
+    pub src: String,
+    pub val: String,
+    pub neg: bool,
+    pub cts: DateTime<Utc>,
+    pub exp: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// A label's signed `cts` next to its insertion time, for diagnosing drift.
+#[derive(Debug, Clone, sqlx::FromRow, Serialize)]
+pub struct LabelTimestampRow {
+    pub seq: i64,
+    pub uri: String,
+    pub val: String,
+    pub neg: bool,
+    /// Signed creation timestamp served to subscribers.
+    pub cts: DateTime<Utc>,
+    /// When the row was inserted into this database.
+    pub created_at: DateTime<Utc>,
+    /// `created_at - cts` in seconds (large for backfilled labels).
+    pub skew_seconds: i64,
+    /// True when `cts` is earlier than the previous label's `cts` in seq order,
+    /// i.e. a subscriber ordering by `cts` would see this label out of order.
+    #[sqlx(skip)]
+    pub out_of_cts_order: bool,
+}
+
+/// Flag rows whose `cts` goes backwards relative to the preceding seq.
+///
+/// Rows must be sorted by seq ascending.
+pub fn mark_out_of_cts_order(rows: &mut [LabelTimestampRow]) {
+    let mut max_cts: Option<DateTime<Utc>> = None;
+    for row in rows.iter_mut() {
+        row.out_of_cts_order = max_cts.is_some_and(|m| row.cts < m);
+        max_cts = Some(max_cts.map_or(row.cts, |m| m.max(row.cts)));
+    }
+}
+
+/// Label values still in force after replaying a URI's timeline.
+///
+/// Walks entries in seq order: a positive label sets the value, a negation
+/// clears it. Values whose latest positive label has expired are dropped.
+pub fn current_label_values(history: &[LabelHistoryRow], now: DateTime<Utc>) -> Vec<String> {
+    let mut active: Vec<(String, Option<DateTime<Utc>>)> = Vec::new();
+    for row in history {
+        active.retain(|(val, _)| val != &row.val);
+        if !row.neg {
+            active.push((row.val.clone(), row.exp));
+        }
+    }
+    active
+        .into_iter()
+        .filter(|(_, exp)| !is_expired(*exp, now))
+        .map(|(val, _)| val)
+        .collect()
+}
+
 impl LabelDb {
     /// Connect to the database.
     pub async fn connect(database_url: &str) -> Result<Self, sqlx::Error> {
@@ -231,6 +1707,9 @@ impl LabelDb {
         sqlx::query("CREATE INDEX IF NOT EXISTS idx_labels_val ON labels(val)")
             .execute(&self.pool)
             .await?;
+        sqlx::query("CREATE INDEX IF NOT EXISTS idx_labels_created_at ON labels(created_at)")
+            .execute(&self.pool)
+            .await?;
 
         // Label context table for admin UI display
         sqlx::query(
@@ -254,6 +1733,11 @@ impl LabelDb {
             .execute(&self.pool)
             .await?;
 
+        // track_id is missing from the CREATE TABLE above
+        sqlx::query("ALTER TABLE label_context ADD COLUMN IF NOT EXISTS track_id BIGINT")
+            .execute(&self.pool)
+            .await?;
+
         // Add resolution columns (migration-safe: only adds if missing)
         sqlx::query("ALTER TABLE label_context ADD COLUMN IF NOT EXISTS resolution_reason TEXT")
             .execute(&self.pool)
@@ -261,6 +1745,31 @@ impl LabelDb {
         sqlx::query("ALTER TABLE label_context ADD COLUMN IF NOT EXISTS resolution_notes TEXT")
             .execute(&self.pool)
             .await?;
+        sqlx::query("ALTER TABLE label_context ADD COLUMN IF NOT EXISTS audio_url TEXT")
+            .execute(&self.pool)
+            .await?;
+        sqlx::query("ALTER TABLE label_context ADD COLUMN IF NOT EXISTS rescanned_at TIMESTAMPTZ")
+            .execute(&self.pool)
+            .await?;
+        sqlx::query("ALTER TABLE label_context ADD COLUMN IF NOT EXISTS confirmed_by TEXT")
+            .execute(&self.pool)
+            .await?;
+        sqlx::query("ALTER TABLE label_context ADD COLUMN IF NOT EXISTS confirmed_at TIMESTAMPTZ")
+            .execute(&self.pool)
+            .await?;
+
+        // Trigram index for the flags `q` search, so substring matches
+        // (including inside the matches jsonb) don't scan every row
+        sqlx::query("CREATE EXTENSION IF NOT EXISTS pg_trgm")
+            .execute(&self.pool)
+            .await?;
+        sqlx::query(&format!(
+            "CREATE INDEX IF NOT EXISTS idx_label_context_search ON label_context \
+             USING GIN ({} gin_trgm_ops)",
+            flag_search_document("")
+        ))
+        .execute(&self.pool)
+        .await?;
 
         // Sensitive images table for content moderation
         sqlx::query(
@@ -284,6 +1793,14 @@ impl LabelDb {
         sqlx::query("CREATE INDEX IF NOT EXISTS idx_sensitive_images_url ON sensitive_images(url)")
             .execute(&self.pool)
             .await?;
+        sqlx::query("ALTER TABLE sensitive_images ADD COLUMN IF NOT EXISTS severity TEXT")
+            .execute(&self.pool)
+            .await?;
+        sqlx::query(
+            "ALTER TABLE sensitive_images ADD COLUMN IF NOT EXISTS violated_categories JSONB",
+        )
+        .execute(&self.pool)
+        .await?;
 
         // Image scans table for tracking automated moderation
         sqlx::query(
@@ -303,53 +1820,275 @@ impl LabelDb {
         .execute(&self.pool)
         .await?;
 
-        sqlx::query("CREATE INDEX IF NOT EXISTS idx_image_scans_image_id ON image_scans(image_id)")
+        sqlx::query("ALTER TABLE image_scans ADD COLUMN IF NOT EXISTS source_url TEXT")
             .execute(&self.pool)
             .await?;
-        sqlx::query("CREATE INDEX IF NOT EXISTS idx_image_scans_is_safe ON image_scans(is_safe)")
+        sqlx::query("ALTER TABLE image_scans ADD COLUMN IF NOT EXISTS review_state TEXT")
             .execute(&self.pool)
             .await?;
-
-        // Review batches for mobile-friendly flag review
         sqlx::query(
-            r#"
-            CREATE TABLE IF NOT EXISTS review_batches (
-                id TEXT PRIMARY KEY,
-                created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
-                expires_at TIMESTAMPTZ,
-                status TEXT NOT NULL DEFAULT 'pending',
-                created_by TEXT
-            )
-            "#,
+            "CREATE INDEX IF NOT EXISTS idx_image_scans_review_state ON image_scans(review_state)",
         )
         .execute(&self.pool)
         .await?;
-
-        // Flags within review batches
+        sqlx::query("ALTER TABLE image_scans ADD COLUMN IF NOT EXISTS input_tokens INTEGER")
+            .execute(&self.pool)
+            .await?;
+        sqlx::query("ALTER TABLE image_scans ADD COLUMN IF NOT EXISTS output_tokens INTEGER")
+            .execute(&self.pool)
+            .await?;
         sqlx::query(
-            r#"
-            CREATE TABLE IF NOT EXISTS batch_flags (
-                id BIGSERIAL PRIMARY KEY,
-                batch_id TEXT NOT NULL REFERENCES review_batches(id) ON DELETE CASCADE,
-                uri TEXT NOT NULL,
-                reviewed BOOLEAN NOT NULL DEFAULT FALSE,
-                reviewed_at TIMESTAMPTZ,
-                decision TEXT,
-                UNIQUE(batch_id, uri)
-            )
-            "#,
+            "CREATE INDEX IF NOT EXISTS idx_image_scans_scanned_at ON image_scans(scanned_at DESC)",
         )
         .execute(&self.pool)
         .await?;
 
-        sqlx::query("CREATE INDEX IF NOT EXISTS idx_batch_flags_batch_id ON batch_flags(batch_id)")
+        sqlx::query("ALTER TABLE image_scans ADD COLUMN IF NOT EXISTS content_hash TEXT")
             .execute(&self.pool)
             .await?;
-
-        // User reports table for content moderation reports
         sqlx::query(
-            r#"
-            CREATE TABLE IF NOT EXISTS user_reports (
+            "ALTER TABLE image_scans ADD COLUMN IF NOT EXISTS second_opinion_of BIGINT REFERENCES image_scans(id)",
+        )
+        .execute(&self.pool)
+        .await?;
+        sqlx::query("ALTER TABLE image_scans ADD COLUMN IF NOT EXISTS cost_usd DOUBLE PRECISION")
+            .execute(&self.pool)
+            .await?;
+        sqlx::query(
+            "CREATE INDEX IF NOT EXISTS idx_image_scans_content_hash ON image_scans(content_hash)",
+        )
+        .execute(&self.pool)
+        .await?;
+        sqlx::query("ALTER TABLE image_scans ADD COLUMN IF NOT EXISTS request_id TEXT")
+            .execute(&self.pool)
+            .await?;
+
+        // Human overrides of automated image verdicts, keyed by id and/or hash
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS image_overrides (
+                id BIGSERIAL PRIMARY KEY,
+                image_id TEXT,
+                content_hash TEXT,
+                verdict TEXT NOT NULL,
+                reviewed_by TEXT NOT NULL,
+                notes TEXT,
+                created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+            )
+            "#,
+        )
+        .execute(&self.pool)
+        .await?;
+        sqlx::query(
+            "CREATE INDEX IF NOT EXISTS idx_image_overrides_image_id ON image_overrides(image_id)",
+        )
+        .execute(&self.pool)
+        .await?;
+        sqlx::query(
+            "CREATE INDEX IF NOT EXISTS idx_image_overrides_content_hash ON image_overrides(content_hash)",
+        )
+        .execute(&self.pool)
+        .await?;
+
+        // Sensitive image re-verification runs; at most one is open at a time
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS image_reverify_runs (
+                id BIGSERIAL PRIMARY KEY,
+                started_by TEXT NOT NULL,
+                started_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+                updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+                finished_at TIMESTAMPTZ,
+                last_image_id BIGINT NOT NULL DEFAULT 0,
+                checked INTEGER NOT NULL DEFAULT 0,
+                cleared INTEGER NOT NULL DEFAULT 0,
+                failed INTEGER NOT NULL DEFAULT 0
+            )
+            "#,
+        )
+        .execute(&self.pool)
+        .await?;
+        sqlx::query(
+            "CREATE UNIQUE INDEX IF NOT EXISTS idx_image_reverify_runs_open \
+             ON image_reverify_runs ((true)) WHERE finished_at IS NULL",
+        )
+        .execute(&self.pool)
+        .await?;
+
+        // Audit log of manual moderation actions
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS audit_log (
+                id BIGSERIAL PRIMARY KEY,
+                action TEXT NOT NULL,
+                target TEXT NOT NULL,
+                actor TEXT NOT NULL,
+                details JSONB,
+                created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+            )
+            "#,
+        )
+        .execute(&self.pool)
+        .await?;
+        sqlx::query(
+            "CREATE INDEX IF NOT EXISTS idx_audit_log_created_at ON audit_log(created_at DESC)",
+        )
+        .execute(&self.pool)
+        .await?;
+        sqlx::query("CREATE INDEX IF NOT EXISTS idx_audit_log_target ON audit_log(target)")
+            .execute(&self.pool)
+            .await?;
+
+        // Webhook deliveries that ran out of attempts
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS failed_deliveries (
+                id BIGSERIAL PRIMARY KEY,
+                target TEXT NOT NULL,
+                event TEXT NOT NULL,
+                payload JSONB NOT NULL,
+                error TEXT NOT NULL,
+                attempts INT NOT NULL,
+                created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+                retried_at TIMESTAMPTZ,
+                delivered_at TIMESTAMPTZ
+            )
+            "#,
+        )
+        .execute(&self.pool)
+        .await?;
+        sqlx::query(
+            "CREATE INDEX IF NOT EXISTS idx_failed_deliveries_created_at ON failed_deliveries(created_at DESC)",
+        )
+        .execute(&self.pool)
+        .await?;
+
+        // Latest enforcement callback per confirmed flag (attempts themselves
+        // are in the audit log)
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS enforcement_requests (
+                uri TEXT PRIMARY KEY,
+                track_id BIGINT,
+                confirmed_by TEXT NOT NULL,
+                status TEXT NOT NULL,
+                attempts INT NOT NULL DEFAULT 0,
+                last_response_status INT,
+                created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+                updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+            )
+            "#,
+        )
+        .execute(&self.pool)
+        .await?;
+        sqlx::query(
+            "CREATE INDEX IF NOT EXISTS idx_enforcement_requests_status ON enforcement_requests(status)",
+        )
+        .execute(&self.pool)
+        .await?;
+
+        // Admin sessions ended by logout, kept until they would have expired
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS revoked_sessions (
+                id TEXT PRIMARY KEY,
+                expires_at TIMESTAMPTZ NOT NULL
+            )
+            "#,
+        )
+        .execute(&self.pool)
+        .await?;
+
+        sqlx::query("CREATE INDEX IF NOT EXISTS idx_image_scans_image_id ON image_scans(image_id)")
+            .execute(&self.pool)
+            .await?;
+        sqlx::query("CREATE INDEX IF NOT EXISTS idx_image_scans_is_safe ON image_scans(is_safe)")
+            .execute(&self.pool)
+            .await?;
+
+        // Review batches for mobile-friendly flag review
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS review_batches (
+                id TEXT PRIMARY KEY,
+                created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+                expires_at TIMESTAMPTZ,
+                status TEXT NOT NULL DEFAULT 'pending',
+                created_by TEXT
+            )
+            "#,
+        )
+        .execute(&self.pool)
+        .await?;
+
+        // Flags within review batches
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS batch_flags (
+                id BIGSERIAL PRIMARY KEY,
+                batch_id TEXT NOT NULL REFERENCES review_batches(id) ON DELETE CASCADE,
+                uri TEXT NOT NULL,
+                reviewed BOOLEAN NOT NULL DEFAULT FALSE,
+                reviewed_at TIMESTAMPTZ,
+                decision TEXT,
+                UNIQUE(batch_id, uri)
+            )
+            "#,
+        )
+        .execute(&self.pool)
+        .await?;
+
+        // Batches created before expiry was enforced get the default lifetime
+        sqlx::query(
+            r#"
+            UPDATE review_batches
+            SET expires_at = created_at + make_interval(days => $1)
+            WHERE expires_at IS NULL
+            "#,
+        )
+        .bind(DEFAULT_BATCH_TTL_DAYS as i32)
+        .execute(&self.pool)
+        .await?;
+        sqlx::query("ALTER TABLE review_batches ADD COLUMN IF NOT EXISTS assigned_to TEXT")
+            .execute(&self.pool)
+            .await?;
+        sqlx::query("ALTER TABLE review_batches ADD COLUMN IF NOT EXISTS assigned_caller TEXT")
+            .execute(&self.pool)
+            .await?;
+
+        sqlx::query("CREATE INDEX IF NOT EXISTS idx_batch_flags_batch_id ON batch_flags(batch_id)")
+            .execute(&self.pool)
+            .await?;
+        sqlx::query("CREATE INDEX IF NOT EXISTS idx_batch_flags_uri ON batch_flags(uri)")
+            .execute(&self.pool)
+            .await?;
+
+        // Every review decision on a flag, from batches and the dashboard
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS review_decisions (
+                id BIGSERIAL PRIMARY KEY,
+                uri TEXT NOT NULL,
+                batch_id TEXT,
+                decision TEXT NOT NULL,
+                actor TEXT NOT NULL,
+                notes TEXT,
+                created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+            )
+            "#,
+        )
+        .execute(&self.pool)
+        .await?;
+        sqlx::query(
+            "CREATE INDEX IF NOT EXISTS idx_review_decisions_uri ON review_decisions(uri, created_at DESC)",
+        )
+        .execute(&self.pool)
+        .await?;
+
+        // User reports table for content moderation reports
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS user_reports (
                 id SERIAL PRIMARY KEY,
                 reporter_did TEXT NOT NULL,
                 reporter_handle TEXT,
@@ -383,12 +2122,43 @@ impl LabelDb {
         sqlx::query("ALTER TABLE user_reports ADD COLUMN IF NOT EXISTS target_url TEXT")
             .execute(&self.pool)
             .await?;
+        sqlx::query("ALTER TABLE user_reports ADD COLUMN IF NOT EXISTS label_seq BIGINT")
+            .execute(&self.pool)
+            .await?;
+        sqlx::query("ALTER TABLE user_reports ADD COLUMN IF NOT EXISTS screenshot_key TEXT")
+            .execute(&self.pool)
+            .await?;
+        // Resolution callback to the backend: 'pending', 'sent' or 'failed',
+        // null until a report is first closed with a callback configured
+        sqlx::query("ALTER TABLE user_reports ADD COLUMN IF NOT EXISTS callback_status TEXT")
+            .execute(&self.pool)
+            .await?;
+        sqlx::query(
+            "ALTER TABLE user_reports ADD COLUMN IF NOT EXISTS callback_delivered_at TIMESTAMPTZ",
+        )
+        .execute(&self.pool)
+        .await?;
+        sqlx::query("ALTER TABLE user_reports ADD COLUMN IF NOT EXISTS callback_last_error TEXT")
+            .execute(&self.pool)
+            .await?;
+        // Until when the replica delivering a pending callback owns it
+        sqlx::query(
+            "ALTER TABLE user_reports ADD COLUMN IF NOT EXISTS callback_lease_until TIMESTAMPTZ",
+        )
+        .execute(&self.pool)
+        .await?;
 
         sqlx::query(
             "CREATE INDEX IF NOT EXISTS idx_user_reports_reporter ON user_reports(reporter_did)",
         )
         .execute(&self.pool)
         .await?;
+        // Reporter history: a reporter's newest reports without a sort
+        sqlx::query(
+            "CREATE INDEX IF NOT EXISTS idx_user_reports_reporter_created ON user_reports(reporter_did, created_at DESC)",
+        )
+        .execute(&self.pool)
+        .await?;
         sqlx::query("CREATE INDEX IF NOT EXISTS idx_user_reports_status ON user_reports(status)")
             .execute(&self.pool)
             .await?;
@@ -402,85 +2172,302 @@ impl LabelDb {
         )
         .execute(&self.pool)
         .await?;
+        sqlx::query(
+            "CREATE INDEX IF NOT EXISTS idx_user_reports_target_uri ON user_reports(target_uri)",
+        )
+        .execute(&self.pool)
+        .await?;
+        // Trigram index for the reports `q` search (pg_trgm is created above)
+        sqlx::query(&format!(
+            "CREATE INDEX IF NOT EXISTS idx_user_reports_search ON user_reports \
+             USING GIN ({REPORT_SEARCH_DOCUMENT} gin_trgm_ops)"
+        ))
+        .execute(&self.pool)
+        .await?;
 
-        Ok(())
-    }
-
-    /// Store or update label context for a URI.
-    pub async fn store_context(
-        &self,
-        uri: &str,
-        context: &LabelContext,
-    ) -> Result<(), sqlx::Error> {
-        let matches_json = context
-            .matches
-            .as_ref()
-            .map(|m| serde_json::to_value(m).unwrap_or_default());
-        let reason_str = context
-            .resolution_reason
-            .map(|r| format!("{:?}", r).to_lowercase());
-
+        // Reporters who filed the same report as an unresolved one
         sqlx::query(
             r#"
-            INSERT INTO label_context (uri, track_id, track_title, artist_handle, artist_did, highest_score, matches, resolution_reason, resolution_notes)
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
-            ON CONFLICT (uri) DO UPDATE SET
-                track_id = COALESCE(EXCLUDED.track_id, label_context.track_id),
-                track_title = COALESCE(EXCLUDED.track_title, label_context.track_title),
-                artist_handle = COALESCE(EXCLUDED.artist_handle, label_context.artist_handle),
-                artist_did = COALESCE(EXCLUDED.artist_did, label_context.artist_did),
-                highest_score = COALESCE(EXCLUDED.highest_score, label_context.highest_score),
-                matches = COALESCE(EXCLUDED.matches, label_context.matches),
-                resolution_reason = COALESCE(EXCLUDED.resolution_reason, label_context.resolution_reason),
-                resolution_notes = COALESCE(EXCLUDED.resolution_notes, label_context.resolution_notes)
+            CREATE TABLE IF NOT EXISTS report_supporters (
+                report_id INTEGER NOT NULL REFERENCES user_reports(id) ON DELETE CASCADE,
+                reporter_did TEXT NOT NULL,
+                created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+                PRIMARY KEY (report_id, reporter_did)
+            )
             "#,
         )
-        .bind(uri)
-        .bind(context.track_id)
-        .bind(&context.track_title)
-        .bind(&context.artist_handle)
-        .bind(&context.artist_did)
-        .bind(context.highest_score)
-        .bind(matches_json)
-        .bind(reason_str)
-        .bind(&context.resolution_notes)
+        .execute(&self.pool)
+        .await?;
+        sqlx::query(
+            "CREATE INDEX IF NOT EXISTS idx_report_supporters_reporter ON report_supporters(reporter_did)",
+        )
         .execute(&self.pool)
         .await?;
 
-        Ok(())
-    }
-
-    /// Store resolution reason for a URI (without overwriting other context).
-    pub async fn store_resolution(
-        &self,
-        uri: &str,
-        reason: ResolutionReason,
-        notes: Option<&str>,
-    ) -> Result<(), sqlx::Error> {
-        let reason_str = format!("{:?}", reason).to_lowercase();
+        // Audio scans uploaded directly to this service (e.g. from the CLI)
         sqlx::query(
             r#"
-            INSERT INTO label_context (uri, resolution_reason, resolution_notes)
-            VALUES ($1, $2, $3)
-            ON CONFLICT (uri) DO UPDATE SET
-                resolution_reason = EXCLUDED.resolution_reason,
-                resolution_notes = EXCLUDED.resolution_notes
+            CREATE TABLE IF NOT EXISTS audio_scans (
+                id BIGSERIAL PRIMARY KEY,
+                filename TEXT,
+                content_hash TEXT NOT NULL,
+                size_bytes BIGINT NOT NULL,
+                is_flagged BOOLEAN NOT NULL,
+                dominant_match TEXT,
+                dominant_match_pct INTEGER NOT NULL,
+                sustained_song_count INTEGER NOT NULL,
+                matches JSONB NOT NULL,
+                raw_response JSONB NOT NULL,
+                scanned_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+            )
             "#,
         )
-        .bind(uri)
-        .bind(reason_str)
-        .bind(notes)
         .execute(&self.pool)
         .await?;
-
-        Ok(())
-    }
-
-    /// Get label context for a URI.
-    pub async fn get_context(&self, uri: &str) -> Result<Option<LabelContext>, sqlx::Error> {
+        sqlx::query(
+            "CREATE INDEX IF NOT EXISTS idx_audio_scans_content_hash ON audio_scans(content_hash)",
+        )
+        .execute(&self.pool)
+        .await?;
+        sqlx::query("ALTER TABLE audio_scans ADD COLUMN IF NOT EXISTS uri TEXT")
+            .execute(&self.pool)
+            .await?;
+        sqlx::query("CREATE INDEX IF NOT EXISTS idx_audio_scans_uri ON audio_scans(uri)")
+            .execute(&self.pool)
+            .await?;
+
+        // Resolution reasons offered to reviewers; label_context.resolution_reason
+        // holds a key from here
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS resolution_reasons (
+                key TEXT PRIMARY KEY,
+                label TEXT NOT NULL,
+                description TEXT NOT NULL DEFAULT '',
+                active BOOLEAN NOT NULL DEFAULT TRUE,
+                created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+            )
+            "#,
+        )
+        .execute(&self.pool)
+        .await?;
+        sqlx::query(
+            r#"
+            INSERT INTO resolution_reasons (key, label, description) VALUES
+                ('original_artist', 'original artist', 'Artist uploaded their own distributed music'),
+                ('licensed', 'licensed', 'Artist has licensing/permission for the content'),
+                ('fingerprint_noise', 'fingerprint noise', 'Fingerprint matcher produced a false match'),
+                ('cover_version', 'cover/remix', 'Legal cover version or remix'),
+                ('content_deleted', 'content deleted', 'Content was deleted from plyr.fm'),
+                ('other', 'other', 'Other reason (see notes)')
+            ON CONFLICT (key) DO NOTHING
+            "#,
+        )
+        .execute(&self.pool)
+        .await?;
+
+        // Reasons users can pick when reporting; user_reports.reason holds a
+        // key from here
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS report_reasons (
+                key TEXT PRIMARY KEY,
+                label TEXT NOT NULL,
+                description TEXT NOT NULL DEFAULT '',
+                requires_target_uri BOOLEAN NOT NULL DEFAULT FALSE,
+                active BOOLEAN NOT NULL DEFAULT TRUE,
+                created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+            )
+            "#,
+        )
+        .execute(&self.pool)
+        .await?;
+        sqlx::query(
+            r#"
+            INSERT INTO report_reasons (key, label, description) VALUES
+                ('copyright', 'copyright infringement', 'Uses someone else''s work without permission'),
+                ('abuse', 'abuse or harassment', 'Targets or harasses a person'),
+                ('spam', 'spam', 'Unwanted, repetitive or misleading content'),
+                ('explicit', 'explicit content', 'Sexual or graphic content'),
+                ('other', 'other', 'Something else (see description)')
+            ON CONFLICT (key) DO NOTHING
+            "#,
+        )
+        .execute(&self.pool)
+        .await?;
+
+        // Monthly counts of rows removed by the retention pruner, so stats
+        // survive the rows themselves
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS retention_archive (
+                kind TEXT NOT NULL,
+                detail TEXT NOT NULL,
+                month DATE NOT NULL,
+                count BIGINT NOT NULL,
+                PRIMARY KEY (kind, detail, month)
+            )
+            "#,
+        )
+        .execute(&self.pool)
+        .await?;
+
+        // ISRCs from licensed catalogs; matches against these don't flag
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS isrc_allowlist (
+                isrc TEXT PRIMARY KEY,
+                note TEXT NOT NULL DEFAULT '',
+                added_by TEXT,
+                created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+            )
+            "#,
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Store or update label context for a URI.
+    pub async fn store_context(
+        &self,
+        uri: &str,
+        context: &LabelContext,
+    ) -> Result<(), sqlx::Error> {
+        let matches_json = context
+            .matches
+            .as_ref()
+            .map(|m| serde_json::to_value(m).unwrap_or_default());
+        let reason_str = context.resolution_reason.as_ref().map(|r| r.as_str());
+
+        sqlx::query(
+            r#"
+            INSERT INTO label_context (uri, track_id, track_title, artist_handle, artist_did, highest_score, matches, resolution_reason, resolution_notes, audio_url)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+            ON CONFLICT (uri) DO UPDATE SET
+                track_id = COALESCE(EXCLUDED.track_id, label_context.track_id),
+                track_title = COALESCE(EXCLUDED.track_title, label_context.track_title),
+                artist_handle = COALESCE(EXCLUDED.artist_handle, label_context.artist_handle),
+                artist_did = COALESCE(EXCLUDED.artist_did, label_context.artist_did),
+                highest_score = COALESCE(EXCLUDED.highest_score, label_context.highest_score),
+                matches = COALESCE(EXCLUDED.matches, label_context.matches),
+                resolution_reason = COALESCE(EXCLUDED.resolution_reason, label_context.resolution_reason),
+                resolution_notes = COALESCE(EXCLUDED.resolution_notes, label_context.resolution_notes),
+                audio_url = COALESCE(EXCLUDED.audio_url, label_context.audio_url)
+            "#,
+        )
+        .bind(uri)
+        .bind(context.track_id)
+        .bind(&context.track_title)
+        .bind(&context.artist_handle)
+        .bind(&context.artist_did)
+        .bind(context.highest_score)
+        .bind(matches_json)
+        .bind(reason_str)
+        .bind(&context.resolution_notes)
+        .bind(&context.audio_url)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Record a flag's confirmation as a violation (without overwriting other
+    /// context) and the `confirm` review decision, in one transaction.
+    pub async fn store_confirmation(
+        &self,
+        uri: &str,
+        confirmed_by: &str,
+        notes: Option<&str>,
+    ) -> Result<(), sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+        upsert_confirmation(&mut *tx, uri, confirmed_by).await?;
+        insert_review_decision(&mut *tx, uri, None, "confirm", confirmed_by, notes).await?;
+        tx.commit().await
+    }
+
+    /// Store a negation label along with its resolution reason (without
+    /// overwriting other context) and `actor`'s `clear` review decision, in
+    /// one transaction. Returns the seq.
+    pub async fn store_negation(
+        &self,
+        label: &Label,
+        reason: Option<&ResolutionReason>,
+        notes: Option<&str>,
+        actor: &str,
+    ) -> Result<i64, sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+        let seq = insert_negation(&mut tx, label, reason, notes, actor).await?;
+        tx.commit().await?;
+        Ok(seq)
+    }
+
+    /// Re-emit a resolved flag: store `label`, clear the URI's resolution
+    /// (keeping the rest of its context) and record the `unresolve_flag`
+    /// audit entry, in one transaction. Returns the new seq.
+    ///
+    /// Runs under the URI's lock and re-checks that the latest event is still
+    /// the negation `negation_seq`, so two undos can't both re-emit; returns
+    /// `None` without writing anything if it isn't.
+    pub async fn store_unresolve(
+        &self,
+        label: &Label,
+        negation_seq: i64,
+        actor: &str,
+        reason: &str,
+    ) -> Result<Option<i64>, sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+        sqlx::query("SELECT pg_advisory_xact_lock(hashtext($1))")
+            .bind(&label.uri)
+            .execute(&mut *tx)
+            .await?;
+        let latest: Option<(i64, bool)> = sqlx::query_as(
+            r#"
+            SELECT seq, neg FROM labels
+            WHERE src = $1 AND uri = $2 AND val = $3
+            ORDER BY seq DESC
+            LIMIT 1
+            "#,
+        )
+        .bind(&label.src)
+        .bind(&label.uri)
+        .bind(&label.val)
+        .fetch_optional(&mut *tx)
+        .await?;
+        if latest != Some((negation_seq, true)) {
+            return Ok(None);
+        }
+
+        let seq = insert_label(&mut *tx, label).await?;
+        sqlx::query(
+            "UPDATE label_context SET resolution_reason = NULL, resolution_notes = NULL WHERE uri = $1",
+        )
+        .bind(&label.uri)
+        .execute(&mut *tx)
+        .await?;
+        insert_audit_event(
+            &mut *tx,
+            "unresolve_flag",
+            &label.uri,
+            actor,
+            serde_json::json!({
+                "seq": seq,
+                "val": label.val,
+                "reason": reason,
+                "negation_seq": negation_seq,
+            }),
+        )
+        .await?;
+        tx.commit().await?;
+        Ok(Some(seq))
+    }
+
+    /// Get label context for a URI.
+    pub async fn get_context(&self, uri: &str) -> Result<Option<LabelContext>, sqlx::Error> {
         let row: Option<ContextRow> = sqlx::query_as(
                 r#"
-                SELECT track_id, track_title, artist_handle, artist_did, highest_score, matches, resolution_reason, resolution_notes
+                SELECT track_id, track_title, artist_handle, artist_did, highest_score, matches, resolution_reason, resolution_notes, audio_url, confirmed_by, confirmed_at
                 FROM label_context
                 WHERE uri = $1
                 "#,
@@ -499,6 +2486,9 @@ impl LabelDb {
                 matches,
                 resolution_reason,
                 resolution_notes,
+                audio_url,
+                confirmed_by,
+                confirmed_at,
             )| {
                 LabelContext {
                     track_id,
@@ -507,98 +2497,210 @@ impl LabelDb {
                     artist_did,
                     highest_score,
                     matches: matches.and_then(|v| serde_json::from_value(v).ok()),
-                    resolution_reason: resolution_reason
-                        .and_then(|s| ResolutionReason::from_str(&s)),
+                    resolution_reason: resolution_reason.map(ResolutionReason::from),
                     resolution_notes,
+                    audio_url,
+                    confirmed_by,
+                    confirmed_at,
                 }
             },
         ))
     }
 
+    /// Record a rescan of `uri` unless one happened after `cutoff`.
+    ///
+    /// Returns false when the URI was rescanned too recently. The check and
+    /// the update are one statement, so concurrent rescans can't both pass.
+    pub async fn claim_rescan(
+        &self,
+        uri: &str,
+        cutoff: DateTime<Utc>,
+    ) -> Result<bool, sqlx::Error> {
+        let claimed = sqlx::query_scalar::<_, String>(
+            r#"
+            INSERT INTO label_context (uri, rescanned_at)
+            VALUES ($1, NOW())
+            ON CONFLICT (uri) DO UPDATE SET rescanned_at = NOW()
+            WHERE label_context.rescanned_at IS NULL OR label_context.rescanned_at < $2
+            RETURNING uri
+            "#,
+        )
+        .bind(uri)
+        .bind(cutoff)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(claimed.is_some())
+    }
+
     /// Store a signed label and return its sequence number.
     pub async fn store_label(&self, label: &Label) -> Result<i64, sqlx::Error> {
-        let sig = label.sig.as_ref().map(|b| b.to_vec()).unwrap_or_default();
-        let cts: DateTime<Utc> = label.cts.parse().unwrap_or_else(|_| Utc::now());
-        let exp: Option<DateTime<Utc>> = label.exp.as_ref().and_then(|e| e.parse().ok());
+        insert_label(&self.pool, label).await
+    }
+
+    /// Store a signed positive label unless the same (source, URI, value)
+    /// already has one in force, in which case that label is returned and
+    /// nothing is written.
+    ///
+    /// The check and the insert run under a transaction-scoped advisory lock
+    /// on the URI, so concurrent calls can't both find nothing active and
+    /// both emit.
+    pub async fn store_label_unless_active(
+        &self,
+        label: &Label,
+    ) -> Result<StoredLabel, sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+        let stored = insert_label_unless_active(&mut tx, label).await?;
+        tx.commit().await?;
+        Ok(stored)
+    }
+
+    /// Store signed negations, and the shared resolution reason for each URI,
+    /// in one transaction. Returns one outcome per label, in input order.
+    ///
+    /// Each negated label is locked and re-checked inside the transaction, so
+    /// a URI resolved by someone else since the caller looked comes back
+    /// `NotActive` instead of getting a second negation. With `atomic`, any
+    /// such URI means nothing is written and the rest come back `Skipped`.
+    pub async fn store_negations(
+        &self,
+        labels: &[Label],
+        reason: Option<&ResolutionReason>,
+        notes: Option<&str>,
+        atomic: bool,
+    ) -> Result<Vec<StoredNegation>, sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+
+        // lock in URI order so overlapping bulk resolves can't deadlock; a
+        // resolve that got here first has committed by the time we re-check
+        let mut by_uri: Vec<&Label> = labels.iter().collect();
+        by_uri.sort_by(|a, b| a.uri.cmp(&b.uri));
+        for label in by_uri {
+            sqlx::query(
+                r#"
+                SELECT seq FROM labels
+                WHERE src = $1 AND uri = $2 AND val = $3 AND neg = false
+                ORDER BY seq DESC
+                LIMIT 1
+                FOR UPDATE
+                "#,
+            )
+            .bind(&label.src)
+            .bind(&label.uri)
+            .bind(&label.val)
+            .execute(&mut *tx)
+            .await?;
+        }
 
-        let row = sqlx::query_scalar::<_, i64>(
+        let mut still_active = Vec::with_capacity(labels.len());
+        for label in labels {
+            let active = select_active_label(&mut *tx, &label.src, &label.uri, &label.val).await?;
+            still_active.push(active.is_some());
+        }
+        if atomic && still_active.contains(&false) {
+            return Ok(still_active
+                .into_iter()
+                .map(|active| match active {
+                    true => StoredNegation::Skipped,
+                    false => StoredNegation::NotActive,
+                })
+                .collect());
+        }
+
+        let mut stored = Vec::with_capacity(labels.len());
+        for (label, active) in labels.iter().zip(still_active) {
+            if !active {
+                stored.push(StoredNegation::NotActive);
+                continue;
+            }
+            stored.push(StoredNegation::Created(insert_label(&mut *tx, label).await?));
+            match reason {
+                Some(r) => upsert_resolution(&mut *tx, &label.uri, r, notes).await?,
+                None => clear_confirmation(&mut *tx, &label.uri).await?,
+            }
+        }
+        tx.commit().await?;
+        Ok(stored)
+    }
+
+    /// Store a negation as [`Self::store_negation`] does, and resolve the
+    /// report about the same URI with the same notes, in one transaction that
+    /// also writes the `resolve_flag` and `resolve_report` audit entries.
+    /// Returns `None`, with nothing written, if the report doesn't exist, is
+    /// about another URI, or is already resolved or dismissed.
+    pub async fn store_negation_closing_report(
+        &self,
+        label: &Label,
+        reason: Option<&ResolutionReason>,
+        notes: Option<&str>,
+        report_id: i32,
+        actor: &str,
+    ) -> Result<Option<(i64, UserReport)>, sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+        let seq = insert_negation(&mut tx, label, reason, notes, actor).await?;
+
+        let report = sqlx::query_as::<_, UserReport>(&format!(
             r#"
-            INSERT INTO labels (src, uri, cid, val, neg, cts, exp, sig)
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
-            RETURNING seq
-            "#,
-        )
-        .bind(&label.src)
+            UPDATE user_reports
+            SET status = 'resolved', admin_notes = $1, resolved_by = $2, resolved_at = NOW(), updated_at = NOW()
+            WHERE id = $3 AND target_uri = $4 AND status NOT IN ('resolved', 'dismissed')
+            RETURNING {REPORT_COLUMNS}
+            "#
+        ))
+        .bind(notes)
+        .bind(actor)
+        .bind(report_id)
         .bind(&label.uri)
-        .bind(&label.cid)
-        .bind(&label.val)
-        .bind(label.neg.unwrap_or(false))
-        .bind(cts)
-        .bind(exp)
-        .bind(sig)
-        .fetch_one(&self.pool)
+        .fetch_optional(&mut *tx)
         .await?;
+        // dropping the transaction rolls the negation back
+        let Some(report) = report else {
+            return Ok(None);
+        };
 
-        Ok(row)
+        insert_audit_event(
+            &mut *tx,
+            "resolve_flag",
+            &label.uri,
+            actor,
+            serde_json::json!({
+                "seq": seq,
+                "val": label.val,
+                "reason": reason.map(ResolutionReason::as_str),
+                "notes": notes,
+            }),
+        )
+        .await?;
+        insert_audit_event(
+            &mut *tx,
+            "resolve_report",
+            &report.id.to_string(),
+            actor,
+            serde_json::json!({
+                "status": report.status,
+                "admin_notes": report.admin_notes,
+                "resolved_by": report.resolved_by,
+                "supporters": report.supporter_count,
+                "flag_seq": seq,
+            }),
+        )
+        .await?;
+        tx.commit().await?;
+        Ok(Some((seq, report)))
     }
 
     /// Query labels matching URI patterns.
     ///
     /// Patterns can contain `*` as a wildcard (e.g., `at://did:plc:*`).
+    /// Labels whose `exp` has passed are omitted.
     pub async fn query_labels(
         &self,
         uri_patterns: &[String],
         sources: Option<&[String]>,
+        vals: Option<&[String]>,
         cursor: Option<&str>,
         limit: i64,
     ) -> Result<(Vec<LabelRow>, Option<String>), sqlx::Error> {
-        // Build dynamic query
-        let mut conditions = Vec::new();
-        let mut param_idx = 1;
-
-        // URI pattern matching
-        let uri_conditions: Vec<String> = uri_patterns
-            .iter()
-            .map(|p| {
-                let idx = param_idx;
-                param_idx += 1;
-                if p.contains('*') {
-                    format!("uri LIKE ${}", idx)
-                } else {
-                    format!("uri = ${}", idx)
-                }
-            })
-            .collect();
-
-        if !uri_conditions.is_empty() {
-            conditions.push(format!("({})", uri_conditions.join(" OR ")));
-        }
-
-        // Source filtering
-        if let Some(srcs) = sources {
-            if !srcs.is_empty() {
-                let placeholders: Vec<String> = srcs
-                    .iter()
-                    .map(|_| {
-                        let idx = param_idx;
-                        param_idx += 1;
-                        format!("${}", idx)
-                    })
-                    .collect();
-                conditions.push(format!("src IN ({})", placeholders.join(", ")));
-            }
-        }
-
-        // Cursor for pagination
-        if cursor.is_some() {
-            conditions.push(format!("seq > ${}", param_idx));
-        }
-
-        let where_clause = if conditions.is_empty() {
-            String::new()
-        } else {
-            format!("WHERE {}", conditions.join(" AND "))
-        };
+        let where_clause = label_query_where(uri_patterns, sources, vals, cursor.is_some());
 
         let query = format!(
             r#"
@@ -614,21 +2716,9 @@ impl LabelDb {
 
         // Build query with parameters
         let mut q = sqlx::query_as::<_, LabelRow>(&query);
-
-        // Bind URI patterns (converting * to %)
-        for pattern in uri_patterns {
-            let sql_pattern = pattern.replace('*', "%");
-            q = q.bind(sql_pattern);
-        }
-
-        // Bind sources
-        if let Some(srcs) = sources {
-            for src in srcs {
-                q = q.bind(src);
-            }
+        for value in label_query_binds(uri_patterns, sources, vals) {
+            q = q.bind(value);
         }
-
-        // Bind cursor
         if let Some(c) = cursor {
             let cursor_seq: i64 = c.parse().unwrap_or(0);
             q = q.bind(cursor_seq);
@@ -647,6 +2737,32 @@ impl LabelDb {
         Ok((rows, next_cursor))
     }
 
+    /// Count labels `query_labels` would return from `cursor` on, ignoring
+    /// the page limit.
+    pub async fn count_labels(
+        &self,
+        uri_patterns: &[String],
+        sources: Option<&[String]>,
+        vals: Option<&[String]>,
+        cursor: Option<&str>,
+    ) -> Result<i64, sqlx::Error> {
+        let query = format!(
+            "SELECT COUNT(*) FROM labels {}",
+            label_query_where(uri_patterns, sources, vals, cursor.is_some())
+        );
+
+        let mut q = sqlx::query_scalar::<_, i64>(&query);
+        for value in label_query_binds(uri_patterns, sources, vals) {
+            q = q.bind(value);
+        }
+        if let Some(c) = cursor {
+            let cursor_seq: i64 = c.parse().unwrap_or(0);
+            q = q.bind(cursor_seq);
+        }
+
+        q.fetch_one(&self.pool).await
+    }
+
     /// Get labels since a sequence number (for subscribeLabels).
     pub async fn get_labels_since(
         &self,
@@ -668,23 +2784,163 @@ impl LabelDb {
         .await
     }
 
-    /// Get the latest sequence number.
-    pub async fn get_latest_seq(&self) -> Result<i64, sqlx::Error> {
-        sqlx::query_scalar::<_, Option<i64>>("SELECT MAX(seq) FROM labels")
-            .fetch_one(&self.pool)
-            .await
-            .map(|s| s.unwrap_or(0))
+    /// Get every label row for a URI, oldest first.
+    ///
+    /// Includes negations and expired labels, so admins can see how the
+    /// current state was reached.
+    pub async fn get_label_history(&self, uri: &str) -> Result<Vec<LabelHistoryRow>, sqlx::Error> {
+        sqlx::query_as::<_, LabelHistoryRow>(
+            r#"
+            SELECT seq, src, val, neg, cts, exp, created_at
+            FROM labels
+            WHERE uri = $1
+            ORDER BY seq ASC
+            "#,
+        )
+        .bind(uri)
+        .fetch_all(&self.pool)
+        .await
     }
 
-    /// Get every URI holding an active label of the given values.
+    /// Get labels with both `cts` and `created_at`, oldest seq first.
     ///
-    /// Same event-sourced resolution as `get_active_label_values`, but keyed
-    /// by value instead of URI so callers can reconcile projections without
-    /// knowing which URIs might be labeled.
-    pub async fn get_active_labels_by_value(
+    /// Filters to one URI if given, and to rows whose absolute skew is at least
+    /// `min_skew_seconds`.
+    pub async fn get_label_timestamps(
         &self,
-        values: &[String],
-    ) -> Result<Vec<(String, String)>, sqlx::Error> {
+        uri: Option<&str>,
+        min_skew_seconds: i64,
+        limit: i64,
+    ) -> Result<Vec<LabelTimestampRow>, sqlx::Error> {
+        let mut rows = sqlx::query_as::<_, LabelTimestampRow>(
+            r#"
+            SELECT seq, uri, val, neg, cts, created_at,
+                   EXTRACT(EPOCH FROM (created_at - cts))::BIGINT AS skew_seconds
+            FROM labels
+            WHERE ($1::TEXT IS NULL OR uri = $1)
+              AND ABS(EXTRACT(EPOCH FROM (created_at - cts))) >= $2
+            ORDER BY seq ASC
+            LIMIT $3
+            "#,
+        )
+        .bind(uri)
+        .bind(min_skew_seconds as f64)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+        mark_out_of_cts_order(&mut rows);
+        Ok(rows)
+    }
+
+    /// Delete labels that expired more than `older_than_days` ago.
+    ///
+    /// Returns the number of rows removed.
+    pub async fn prune_expired_labels(&self, older_than_days: i64) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query(
+            r#"
+            DELETE FROM labels
+            WHERE exp IS NOT NULL
+              AND exp < NOW() - make_interval(days => $1)
+            "#,
+        )
+        .bind(older_than_days as i32)
+        .execute(&self.pool)
+        .await?;
+        Ok(result.rows_affected())
+    }
+
+    /// Delete resolved history older than `older_than_days`: labels whose
+    /// latest entry is a negation made before the cutoff (the negation and
+    /// everything it negated, so nothing is left to reactivate), and reports
+    /// resolved or dismissed before it. Removed rows are counted into
+    /// `retention_archive` by kind, label value or report reason, and month.
+    pub async fn prune_resolved(&self, older_than_days: i64) -> Result<PruneSummary, sqlx::Error> {
+        let days = i32::try_from(older_than_days).map_err(|e| sqlx::Error::Encode(Box::new(e)))?;
+        let mut tx = self.pool.begin().await?;
+
+        let labels: i64 = sqlx::query_scalar(
+            r#"
+            WITH latest AS (
+                SELECT DISTINCT ON (src, uri, val) src, uri, val, seq, neg, cts
+                FROM labels
+                ORDER BY src, uri, val, seq DESC
+            ),
+            pruned AS (
+                DELETE FROM labels l
+                USING latest n
+                WHERE n.neg
+                  AND n.cts < NOW() - make_interval(days => $1)
+                  AND l.src = n.src AND l.uri = n.uri AND l.val = n.val
+                  AND l.seq <= n.seq
+                RETURNING l.val, l.neg, l.cts
+            ),
+            archived AS (
+                INSERT INTO retention_archive (kind, detail, month, count)
+                SELECT CASE WHEN neg THEN 'negation' ELSE 'label' END, val,
+                       date_trunc('month', cts)::date, COUNT(*)
+                FROM pruned
+                GROUP BY 1, 2, 3
+                ON CONFLICT (kind, detail, month)
+                DO UPDATE SET count = retention_archive.count + EXCLUDED.count
+            )
+            SELECT COUNT(*) FROM pruned
+            "#,
+        )
+        .bind(days)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        let (reports, screenshot_keys): (i64, Option<Vec<String>>) = sqlx::query_as(
+            r#"
+            WITH pruned AS (
+                DELETE FROM user_reports
+                WHERE status IN ('resolved', 'dismissed')
+                  AND COALESCE(resolved_at, updated_at, created_at)
+                      < NOW() - make_interval(days => $1)
+                RETURNING reason, created_at, screenshot_key
+            ),
+            archived AS (
+                INSERT INTO retention_archive (kind, detail, month, count)
+                SELECT 'report', reason, date_trunc('month', created_at)::date, COUNT(*)
+                FROM pruned
+                GROUP BY 1, 2, 3
+                ON CONFLICT (kind, detail, month)
+                DO UPDATE SET count = retention_archive.count + EXCLUDED.count
+            )
+            SELECT COUNT(*),
+                   ARRAY_AGG(screenshot_key) FILTER (WHERE screenshot_key IS NOT NULL)
+            FROM pruned
+            "#,
+        )
+        .bind(days)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(PruneSummary {
+            labels: labels as u64,
+            reports: reports as u64,
+            screenshot_keys: screenshot_keys.unwrap_or_default(),
+        })
+    }
+
+    /// Get the latest sequence number.
+    pub async fn get_latest_seq(&self) -> Result<i64, sqlx::Error> {
+        sqlx::query_scalar::<_, Option<i64>>("SELECT MAX(seq) FROM labels")
+            .fetch_one(&self.pool)
+            .await
+            .map(|s| s.unwrap_or(0))
+    }
+
+    /// Get every URI holding an active label of the given values.
+    ///
+    /// Same event-sourced resolution as `get_active_label_values`, but keyed
+    /// by value instead of URI so callers can reconcile projections without
+    /// knowing which URIs might be labeled.
+    pub async fn get_active_labels_by_value(
+        &self,
+        values: &[String],
+    ) -> Result<Vec<(String, String)>, sqlx::Error> {
         if values.is_empty() {
             return Ok(Vec::new());
         }
@@ -743,6 +2999,110 @@ impl LabelDb {
         .await
     }
 
+    /// Get a single label row by its sequence number.
+    pub async fn get_label_by_seq(&self, seq: i64) -> Result<Option<LabelRow>, sqlx::Error> {
+        sqlx::query_as::<_, LabelRow>(
+            r#"
+            SELECT seq, src, uri, cid, val, neg, cts, exp, sig
+            FROM labels
+            WHERE seq = $1
+            "#,
+        )
+        .bind(seq)
+        .fetch_optional(&self.pool)
+        .await
+    }
+
+    /// Get the latest label event for a (source, URI, value) tuple, negation
+    /// or not.
+    pub async fn get_latest_label(
+        &self,
+        src: &str,
+        uri: &str,
+        val: &str,
+    ) -> Result<Option<LabelRow>, sqlx::Error> {
+        sqlx::query_as::<_, LabelRow>(
+            r#"
+            SELECT seq, src, uri, cid, val, neg, cts, exp, sig
+            FROM labels
+            WHERE src = $1 AND uri = $2 AND val = $3
+            ORDER BY seq DESC
+            LIMIT 1
+            "#,
+        )
+        .bind(src)
+        .bind(uri)
+        .bind(val)
+        .fetch_optional(&self.pool)
+        .await
+    }
+
+    /// Get the latest label event for a URI and value issued as any of
+    /// `srcs`, negation or not.
+    pub async fn get_latest_label_from(
+        &self,
+        srcs: &[String],
+        uri: &str,
+        val: &str,
+    ) -> Result<Option<LabelRow>, sqlx::Error> {
+        sqlx::query_as::<_, LabelRow>(
+            r#"
+            SELECT seq, src, uri, cid, val, neg, cts, exp, sig
+            FROM labels
+            WHERE src = ANY($1) AND uri = $2 AND val = $3
+            ORDER BY seq DESC
+            LIMIT 1
+            "#,
+        )
+        .bind(srcs)
+        .bind(uri)
+        .bind(val)
+        .fetch_optional(&self.pool)
+        .await
+    }
+
+    /// Get the newest positive label in force for a URI and value issued as
+    /// any of `srcs`. Each source's label stands on its own, so this is the
+    /// one a negation has to come from.
+    pub async fn get_active_label_from(
+        &self,
+        srcs: &[String],
+        uri: &str,
+        val: &str,
+    ) -> Result<Option<LabelRow>, sqlx::Error> {
+        sqlx::query_as::<_, LabelRow>(
+            r#"
+            SELECT seq, src, uri, cid, val, neg, cts, exp, sig
+            FROM (
+                SELECT DISTINCT ON (src) seq, src, uri, cid, val, neg, cts, exp, sig
+                FROM labels
+                WHERE src = ANY($1) AND uri = $2 AND val = $3
+                ORDER BY src, seq DESC
+            ) latest
+            WHERE neg = false
+              AND (exp IS NULL OR exp > NOW())
+            ORDER BY seq DESC
+            LIMIT 1
+            "#,
+        )
+        .bind(srcs)
+        .bind(uri)
+        .bind(val)
+        .fetch_optional(&self.pool)
+        .await
+    }
+
+    /// Get the current positive label for a (source, URI, value) tuple, if it
+    /// is still in force (latest event not a negation, not expired).
+    pub async fn get_active_label(
+        &self,
+        src: &str,
+        uri: &str,
+        val: &str,
+    ) -> Result<Option<LabelRow>, sqlx::Error> {
+        select_active_label(&self.pool, src, uri, val).await
+    }
+
     /// Get URIs that have an active copyright-violation label.
     ///
     /// Kept as a compatibility projection for the copyright reconciliation
@@ -756,11 +3116,12 @@ impl LabelDb {
             .collect())
     }
 
-    /// Get URIs that have an explicit negation (dismissal) copyright label.
+    /// Get URIs whose latest copyright label is a negation (dismissal).
     ///
     /// A negation means a moderator reviewed the flag and dismissed it. This is
     /// the only signal the backend uses to clear a flag — absence of an active
-    /// label is not a resolution, since flags no longer auto-emit a label.
+    /// label is not a resolution, since flags no longer auto-emit a label. An
+    /// unresolve re-emits the label, so only the latest event counts.
     pub async fn get_negated_labels(&self, uris: &[String]) -> Result<Vec<String>, sqlx::Error> {
         if uris.is_empty() {
             return Ok(Vec::new());
@@ -768,9 +3129,14 @@ impl LabelDb {
 
         sqlx::query_scalar::<_, String>(
             r#"
-            SELECT DISTINCT uri
-            FROM labels
-            WHERE val = 'copyright-violation' AND neg = true AND uri = ANY($1)
+            SELECT uri
+            FROM (
+                SELECT DISTINCT ON (uri) uri, neg
+                FROM labels
+                WHERE val = 'copyright-violation' AND uri = ANY($1)
+                ORDER BY uri, seq DESC
+            ) latest
+            WHERE neg = true
             "#,
         )
         .bind(uris)
@@ -778,250 +3144,596 @@ impl LabelDb {
         .await
     }
 
-    /// Get all copyright-violation labels with their resolution status and context.
+    /// Get a page of copyright flags with context, newest first.
     ///
-    /// A label is resolved if there's a negation label for the same uri+val.
-    pub async fn get_pending_flags(&self) -> Result<Vec<FlaggedTrack>, sqlx::Error> {
-        // Get all copyright-violation labels with context via LEFT JOIN
-        let rows: Vec<FlaggedRow> = sqlx::query_as(
-            r#"
-                SELECT l.seq, l.uri, l.val, l.cts,
-                       c.track_id, c.track_title, c.artist_handle, c.artist_did, c.highest_score, c.matches,
-                       c.resolution_reason, c.resolution_notes
-                FROM labels l
-                LEFT JOIN label_context c ON l.uri = c.uri
-                WHERE l.val = 'copyright-violation' AND l.neg = false
-                ORDER BY l.seq DESC
-                "#,
-        )
-        .fetch_all(&self.pool)
-        .await?;
+    /// Resolution status is computed in SQL, and expired labels are skipped.
+    pub async fn get_flags_page(&self, page: &FlagsPageQuery) -> Result<FlagsPage, sqlx::Error> {
+        let (conditions, binds) = page.conditions();
+        let where_clause = conditions.join(" AND ");
+
+        let count_query = format!(
+            "SELECT COUNT(*) FROM labels l LEFT JOIN label_context c ON l.uri = c.uri WHERE {}",
+            where_clause
+        );
+        let mut count = sqlx::query_scalar(&count_query);
+        for value in &binds {
+            count = count.bind(value);
+        }
+        let total: i64 = count.fetch_one(&self.pool).await?;
+
+        let page_where = match page.cursor {
+            Some(_) => format!(
+                "{} AND {}",
+                where_clause,
+                page.sort.after_cursor(binds.len() + 1)
+            ),
+            None => where_clause,
+        };
+        let limit = page.limit.max(1);
+        let query = format!(
+            "{} LIMIT {}",
+            flagged_rows_sql(&page_where, page.sort),
+            limit + 1
+        );
+        let mut q = sqlx::query_as(&query);
+        for value in &binds {
+            q = q.bind(value);
+        }
+        if let Some(cursor) = page.cursor {
+            if page.sort == FlagSort::MostMatches {
+                q = q.bind(cursor.match_count);
+            }
+            q = q.bind(cursor.seq);
+        }
+        let mut rows: Vec<FlaggedRow> = q.fetch_all(&self.pool).await?;
+
+        let next_cursor = next_flags_cursor(&mut rows, limit as usize, page.sort);
+        let tracks = rows.into_iter().map(flagged_track_from_row).collect();
+
+        Ok(FlagsPage {
+            tracks,
+            total,
+            next_cursor,
+        })
+    }
+
+    /// Visit every flag matching `query`, in its sort order, without paging.
+    ///
+    /// Rows are streamed from the database as `visit` consumes them, so a full
+    /// export never sits in memory. `cursor` and `limit` are ignored. Stops
+    /// early when `visit` returns false.
+    pub async fn for_each_flag<F, Fut>(
+        &self,
+        query: &FlagsPageQuery,
+        mut visit: F,
+    ) -> Result<(), sqlx::Error>
+    where
+        F: FnMut(FlaggedTrack) -> Fut,
+        Fut: Future<Output = bool>,
+    {
+        let (conditions, binds) = query.conditions();
+        let sql = flagged_rows_sql(&conditions.join(" AND "), query.sort);
+        let mut q = sqlx::query_as::<_, FlaggedRow>(&sql);
+        for value in &binds {
+            q = q.bind(value);
+        }
+
+        let mut rows = q.fetch(&self.pool);
+        while let Some(row) = rows.try_next().await? {
+            if !visit(flagged_track_from_row(row)).await {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    /// Get the active flag for a single URI, with its context.
+    pub async fn get_flag(&self, uri: &str) -> Result<Option<FlaggedTrack>, sqlx::Error> {
+        let query = format!(
+            "{} LIMIT 1",
+            flagged_rows_sql(
+                "l.uri = $1 AND l.val = 'copyright-violation' AND l.neg = false \
+                 AND (l.exp IS NULL OR l.exp > NOW())",
+                FlagSort::Newest
+            )
+        );
+        let row: Option<FlaggedRow> = sqlx::query_as(&query)
+            .bind(uri)
+            .fetch_optional(&self.pool)
+            .await?;
+        Ok(row.map(flagged_track_from_row))
+    }
+
+    /// Get the flag issued as label `seq`, resolved or not, with its context.
+    pub async fn get_flag_by_seq(&self, seq: i64) -> Result<Option<FlaggedTrack>, sqlx::Error> {
+        let query = flagged_rows_sql(
+            "l.seq = $1 AND l.val = 'copyright-violation' AND l.neg = false",
+            FlagSort::Newest,
+        );
+        let row: Option<FlaggedRow> = sqlx::query_as(&query)
+            .bind(seq)
+            .fetch_optional(&self.pool)
+            .await?;
+        Ok(row.map(flagged_track_from_row))
+    }
+
+    /// Copyright flags on tracks by any of `artist_dids`, or whose context
+    /// has `handle` as the artist handle (case-insensitive), newest first.
+    pub async fn search_flags(
+        &self,
+        artist_dids: &[String],
+        handle: Option<&str>,
+        limit: i64,
+    ) -> Result<Vec<FlaggedTrack>, sqlx::Error> {
+        let where_clause = format!(
+            "l.val = 'copyright-violation' AND l.neg = false \
+             AND (l.exp IS NULL OR l.exp > NOW()) AND {} \
+             AND (split_part(l.uri, '/', 3) = ANY($1) OR c.artist_did = ANY($1) \
+                  OR lower(c.artist_handle) = lower($2))",
+            FLAG_LATEST_SQL
+        );
+        let query = format!(
+            "{} LIMIT $3",
+            flagged_rows_sql(&where_clause, FlagSort::Newest)
+        );
+        let rows: Vec<FlaggedRow> = sqlx::query_as(&query)
+            .bind(artist_dids)
+            .bind(handle)
+            .bind(limit)
+            .fetch_all(&self.pool)
+            .await?;
+        Ok(rows.into_iter().map(flagged_track_from_row).collect())
+    }
 
-        // Get all negation labels
-        let negated_uris: std::collections::HashSet<String> = sqlx::query_scalar::<_, String>(
+    /// DIDs of artists whose flag context has `handle` (case-insensitive).
+    pub async fn artist_dids_for_handle(&self, handle: &str) -> Result<Vec<String>, sqlx::Error> {
+        sqlx::query_scalar::<_, String>(
             r#"
-            SELECT DISTINCT uri
-            FROM labels
-            WHERE val = 'copyright-violation' AND neg = true
+            SELECT DISTINCT artist_did FROM label_context
+            WHERE artist_did IS NOT NULL AND lower(artist_handle) = lower($1)
             "#,
         )
+        .bind(handle)
         .fetch_all(&self.pool)
-        .await?
-        .into_iter()
-        .collect();
-
-        let tracks = rows
-            .into_iter()
-            .map(
-                |(
-                    seq,
-                    uri,
-                    val,
-                    cts,
-                    track_id,
-                    track_title,
-                    artist_handle,
-                    artist_did,
-                    highest_score,
-                    matches,
-                    resolution_reason,
-                    resolution_notes,
-                )| {
-                    let context = if track_id.is_some()
-                        || track_title.is_some()
-                        || artist_handle.is_some()
-                        || resolution_reason.is_some()
-                    {
-                        Some(LabelContext {
-                            track_id,
-                            track_title,
-                            artist_handle,
-                            artist_did,
-                            highest_score,
-                            matches: matches.and_then(|v| serde_json::from_value(v).ok()),
-                            resolution_reason: resolution_reason
-                                .and_then(|s| ResolutionReason::from_str(&s)),
-                            resolution_notes,
-                        })
-                    } else {
-                        None
-                    };
-
-                    FlaggedTrack {
-                        seq,
-                        uri: uri.clone(),
-                        val,
-                        created_at: cts.format("%Y-%m-%d %H:%M:%S").to_string(),
-                        resolved: negated_uris.contains(&uri),
-                        context,
-                    }
-                },
-            )
-            .collect();
+        .await
+    }
 
-        Ok(tracks)
+    /// Get URIs of all unresolved, unexpired copyright flags.
+    pub async fn get_pending_flag_uris(&self) -> Result<Vec<String>, sqlx::Error> {
+        let query = format!(
+            r#"
+            SELECT l.uri
+            FROM labels l
+            WHERE l.val = 'copyright-violation' AND l.neg = false
+              AND (l.exp IS NULL OR l.exp > NOW())
+              AND {} AND NOT {}
+            ORDER BY l.seq DESC
+            "#,
+            FLAG_LATEST_SQL, FLAG_RESOLVED_SQL
+        );
+        sqlx::query_scalar::<_, String>(&query)
+            .fetch_all(&self.pool)
+            .await
     }
 
     // -------------------------------------------------------------------------
-    // Review batches
+    // Resolution reasons
     // -------------------------------------------------------------------------
 
-    /// Create a review batch with the given flags.
-    pub async fn create_batch(
+    /// List resolution reasons, oldest first. Inactive reasons are included
+    /// only if asked for.
+    pub async fn list_resolution_reasons(
         &self,
-        id: &str,
-        uris: &[String],
-        created_by: Option<&str>,
-    ) -> Result<ReviewBatch, sqlx::Error> {
-        let batch = sqlx::query_as::<_, ReviewBatch>(
+        include_inactive: bool,
+    ) -> Result<Vec<ResolutionReasonRow>, sqlx::Error> {
+        sqlx::query_as::<_, ResolutionReasonRow>(
             r#"
-            INSERT INTO review_batches (id, created_by)
-            VALUES ($1, $2)
-            RETURNING id, created_at, expires_at, status, created_by
+            SELECT key, label, description, active, created_at
+            FROM resolution_reasons
+            WHERE active OR $1
+            ORDER BY created_at, key
             "#,
         )
-        .bind(id)
-        .bind(created_by)
+        .bind(include_inactive)
+        .fetch_all(&self.pool)
+        .await
+    }
+
+    /// Get a resolution reason by key, active or not.
+    pub async fn get_resolution_reason(
+        &self,
+        key: &str,
+    ) -> Result<Option<ResolutionReasonRow>, sqlx::Error> {
+        sqlx::query_as::<_, ResolutionReasonRow>(
+            "SELECT key, label, description, active, created_at FROM resolution_reasons WHERE key = $1",
+        )
+        .bind(key)
+        .fetch_optional(&self.pool)
+        .await
+    }
+
+    /// Add a resolution reason, or update and reactivate an existing one.
+    pub async fn upsert_resolution_reason(
+        &self,
+        key: &str,
+        label: &str,
+        description: &str,
+    ) -> Result<ResolutionReasonRow, sqlx::Error> {
+        sqlx::query_as::<_, ResolutionReasonRow>(
+            r#"
+            INSERT INTO resolution_reasons (key, label, description)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (key) DO UPDATE SET
+                label = EXCLUDED.label,
+                description = EXCLUDED.description,
+                active = TRUE
+            RETURNING key, label, description, active, created_at
+            "#,
+        )
+        .bind(key)
+        .bind(label)
+        .bind(description)
         .fetch_one(&self.pool)
-        .await?;
+        .await
+    }
 
-        for uri in uris {
-            sqlx::query(
-                r#"
-                INSERT INTO batch_flags (batch_id, uri)
-                VALUES ($1, $2)
-                ON CONFLICT (batch_id, uri) DO NOTHING
-                "#,
-            )
-            .bind(id)
-            .bind(uri)
+    /// Stop offering a resolution reason. Existing resolutions keep it.
+    /// Returns false if no reason has the key.
+    pub async fn deactivate_resolution_reason(&self, key: &str) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query("UPDATE resolution_reasons SET active = FALSE WHERE key = $1")
+            .bind(key)
             .execute(&self.pool)
             .await?;
-        }
+        Ok(result.rows_affected() > 0)
+    }
 
-        Ok(batch)
+    // -------------------------------------------------------------------------
+    // Report reasons
+    // -------------------------------------------------------------------------
+
+    /// List report reasons, oldest first. Inactive reasons are included only
+    /// if asked for.
+    pub async fn list_report_reasons(
+        &self,
+        include_inactive: bool,
+    ) -> Result<Vec<ReportReasonRow>, sqlx::Error> {
+        sqlx::query_as::<_, ReportReasonRow>(
+            r#"
+            SELECT key, label, description, requires_target_uri, active, created_at
+            FROM report_reasons
+            WHERE active OR $1
+            ORDER BY created_at, key
+            "#,
+        )
+        .bind(include_inactive)
+        .fetch_all(&self.pool)
+        .await
     }
 
-    /// Get a batch by ID.
-    pub async fn get_batch(&self, id: &str) -> Result<Option<ReviewBatch>, sqlx::Error> {
-        sqlx::query_as::<_, ReviewBatch>(
+    /// Get a report reason by key, active or not.
+    pub async fn get_report_reason(
+        &self,
+        key: &str,
+    ) -> Result<Option<ReportReasonRow>, sqlx::Error> {
+        sqlx::query_as::<_, ReportReasonRow>(
             r#"
-            SELECT id, created_at, expires_at, status, created_by
-            FROM review_batches
-            WHERE id = $1
+            SELECT key, label, description, requires_target_uri, active, created_at
+            FROM report_reasons
+            WHERE key = $1
             "#,
         )
-        .bind(id)
+        .bind(key)
         .fetch_optional(&self.pool)
         .await
     }
 
-    /// Get all flags in a batch with their context.
-    pub async fn get_batch_flags(&self, batch_id: &str) -> Result<Vec<FlaggedTrack>, sqlx::Error> {
-        let rows: Vec<FlaggedRow> = sqlx::query_as(
+    /// Add a report reason, or update and reactivate an existing one.
+    pub async fn upsert_report_reason(
+        &self,
+        key: &str,
+        label: &str,
+        description: &str,
+        requires_target_uri: bool,
+    ) -> Result<ReportReasonRow, sqlx::Error> {
+        sqlx::query_as::<_, ReportReasonRow>(
             r#"
-            SELECT l.seq, l.uri, l.val, l.cts,
-                   c.track_id, c.track_title, c.artist_handle, c.artist_did, c.highest_score, c.matches,
-                   c.resolution_reason, c.resolution_notes
-            FROM batch_flags bf
-            JOIN labels l ON l.uri = bf.uri AND l.val = 'copyright-violation' AND l.neg = false
-            LEFT JOIN label_context c ON l.uri = c.uri
-            WHERE bf.batch_id = $1
-            ORDER BY l.seq DESC
+            INSERT INTO report_reasons (key, label, description, requires_target_uri)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (key) DO UPDATE SET
+                label = EXCLUDED.label,
+                description = EXCLUDED.description,
+                requires_target_uri = EXCLUDED.requires_target_uri,
+                active = TRUE
+            RETURNING key, label, description, requires_target_uri, active, created_at
             "#,
         )
-        .bind(batch_id)
+        .bind(key)
+        .bind(label)
+        .bind(description)
+        .bind(requires_target_uri)
+        .fetch_one(&self.pool)
+        .await
+    }
+
+    /// Stop accepting a report reason. Existing reports keep it.
+    /// Returns false if no reason has the key.
+    pub async fn deactivate_report_reason(&self, key: &str) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query("UPDATE report_reasons SET active = FALSE WHERE key = $1")
+            .bind(key)
+            .execute(&self.pool)
+            .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    // -------------------------------------------------------------------------
+    // ISRC allowlist
+    // -------------------------------------------------------------------------
+
+    /// List allowlisted ISRCs, oldest first.
+    pub async fn list_isrc_allowlist(&self) -> Result<Vec<IsrcAllowlistRow>, sqlx::Error> {
+        sqlx::query_as::<_, IsrcAllowlistRow>(
+            "SELECT isrc, note, added_by, created_at FROM isrc_allowlist ORDER BY created_at, isrc",
+        )
         .fetch_all(&self.pool)
+        .await
+    }
+
+    /// Allowlist an ISRC, or update the note on an existing entry.
+    pub async fn upsert_isrc_allowlist(
+        &self,
+        isrc: &str,
+        note: &str,
+        added_by: &str,
+    ) -> Result<IsrcAllowlistRow, sqlx::Error> {
+        sqlx::query_as::<_, IsrcAllowlistRow>(
+            r#"
+            INSERT INTO isrc_allowlist (isrc, note, added_by)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (isrc) DO UPDATE SET note = EXCLUDED.note
+            RETURNING isrc, note, added_by, created_at
+            "#,
+        )
+        .bind(isrc)
+        .bind(note)
+        .bind(added_by)
+        .fetch_one(&self.pool)
+        .await
+    }
+
+    /// Remove an ISRC from the allowlist. Returns false if it wasn't there.
+    pub async fn remove_isrc_allowlist(&self, isrc: &str) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query("DELETE FROM isrc_allowlist WHERE isrc = $1")
+            .bind(isrc)
+            .execute(&self.pool)
+            .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    // -------------------------------------------------------------------------
+    // Review batches
+    // -------------------------------------------------------------------------
+
+    /// Create a review batch with the given flags.
+    pub async fn create_batch(
+        &self,
+        id: &str,
+        uris: &[String],
+        created_by: Option<&str>,
+        expires_at: DateTime<Utc>,
+    ) -> Result<ReviewBatch, sqlx::Error> {
+        let batch = sqlx::query_as::<_, ReviewBatch>(
+            r#"
+            INSERT INTO review_batches (id, created_by, expires_at)
+            VALUES ($1, $2, $3)
+            RETURNING id, created_at, expires_at, status, created_by, assigned_to, assigned_caller
+            "#,
+        )
+        .bind(id)
+        .bind(created_by)
+        .bind(expires_at)
+        .fetch_one(&self.pool)
         .await?;
 
-        let batch_uris: Vec<String> = rows.iter().map(|r| r.1.clone()).collect();
-        let negated_uris: std::collections::HashSet<String> = if !batch_uris.is_empty() {
-            sqlx::query_scalar::<_, String>(
+        for uri in uris {
+            sqlx::query(
                 r#"
-                SELECT DISTINCT uri
-                FROM labels
-                WHERE val = 'copyright-violation' AND neg = true AND uri = ANY($1)
+                INSERT INTO batch_flags (batch_id, uri)
+                VALUES ($1, $2)
+                ON CONFLICT (batch_id, uri) DO NOTHING
                 "#,
             )
-            .bind(&batch_uris)
-            .fetch_all(&self.pool)
-            .await?
-            .into_iter()
-            .collect()
-        } else {
-            std::collections::HashSet::new()
-        };
+            .bind(id)
+            .bind(uri)
+            .execute(&self.pool)
+            .await?;
+        }
 
-        let tracks = rows
-            .into_iter()
-            .map(
-                |(
-                    seq,
-                    uri,
-                    val,
-                    cts,
-                    track_id,
-                    track_title,
-                    artist_handle,
-                    artist_did,
-                    highest_score,
-                    matches,
-                    resolution_reason,
-                    resolution_notes,
-                )| {
-                    let context = if track_id.is_some()
-                        || track_title.is_some()
-                        || artist_handle.is_some()
-                        || resolution_reason.is_some()
-                    {
-                        Some(LabelContext {
-                            track_id,
-                            track_title,
-                            artist_handle,
-                            artist_did,
-                            highest_score,
-                            matches: matches.and_then(|v| serde_json::from_value(v).ok()),
-                            resolution_reason: resolution_reason
-                                .and_then(|s| ResolutionReason::from_str(&s)),
-                            resolution_notes,
-                        })
-                    } else {
-                        None
-                    };
+        Ok(batch)
+    }
 
-                    FlaggedTrack {
-                        seq,
-                        uri: uri.clone(),
-                        val,
-                        created_at: cts.format("%Y-%m-%d %H:%M:%S").to_string(),
-                        resolved: negated_uris.contains(&uri),
-                        context,
-                    }
-                },
-            )
-            .collect();
+    /// Get a batch by ID.
+    pub async fn get_batch(&self, id: &str) -> Result<Option<ReviewBatch>, sqlx::Error> {
+        sqlx::query_as::<_, ReviewBatch>(
+            r#"
+            SELECT id, created_at, expires_at, status, created_by, assigned_to, assigned_caller
+            FROM review_batches
+            WHERE id = $1
+            "#,
+        )
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await
+    }
 
-        Ok(tracks)
+    /// List batches with flag counts, newest first.
+    pub async fn list_batches(&self, limit: i64) -> Result<Vec<BatchSummary>, sqlx::Error> {
+        sqlx::query_as::<_, BatchSummary>(
+            r#"
+            SELECT b.id, b.created_at, b.expires_at, b.status, b.created_by, b.assigned_to,
+                   COUNT(f.id) AS total,
+                   COUNT(f.id) FILTER (WHERE f.reviewed) AS decided,
+                   COUNT(f.id) FILTER (WHERE NOT f.reviewed) AS pending
+            FROM review_batches b
+            LEFT JOIN batch_flags f ON f.batch_id = b.id
+            GROUP BY b.id
+            ORDER BY b.created_at DESC
+            LIMIT $1
+            "#,
+        )
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await
     }
 
-    /// Update batch status.
-    pub async fn update_batch_status(&self, id: &str, status: &str) -> Result<bool, sqlx::Error> {
-        let result = sqlx::query("UPDATE review_batches SET status = $1 WHERE id = $2")
-            .bind(status)
-            .bind(id)
-            .execute(&self.pool)
+    /// Delete a batch and its flags, unless any decision was submitted.
+    /// Returns false if nothing was deleted.
+    pub async fn delete_undecided_batch(&self, id: &str) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query(
+            r#"
+            DELETE FROM review_batches
+            WHERE id = $1
+              AND NOT EXISTS (SELECT 1 FROM batch_flags WHERE batch_id = $1 AND reviewed)
+            "#,
+        )
+        .bind(id)
+        .execute(&self.pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Get all flags in a batch with their context.
+    pub async fn get_batch_flags(&self, batch_id: &str) -> Result<Vec<FlaggedTrack>, sqlx::Error> {
+        let query = format!(
+            r#"
+            SELECT l.seq, l.uri, l.val, l.cts,
+                   c.track_id, c.track_title, c.artist_handle, c.artist_did, c.highest_score, c.matches,
+                   c.resolution_reason, c.resolution_notes, {} AS resolved_at, {} AS match_count,
+                   e.status AS enforcement_status, c.audio_url, c.confirmed_by, c.confirmed_at,
+                   {} AS label_count
+            FROM batch_flags bf
+            JOIN labels l ON l.uri = bf.uri AND l.val = 'copyright-violation' AND l.neg = false
+            LEFT JOIN label_context c ON l.uri = c.uri
+            LEFT JOIN enforcement_requests e ON l.uri = e.uri
+            WHERE bf.batch_id = $1
+              AND (l.exp IS NULL OR l.exp > NOW()) AND {}
+            ORDER BY l.seq DESC
+            "#,
+            FLAG_RESOLVED_AT_SQL, MATCH_COUNT_SQL, FLAG_COUNT_SQL, FLAG_LATEST_SQL
+        );
+        let rows: Vec<FlaggedRow> = sqlx::query_as(&query)
+            .bind(batch_id)
+            .fetch_all(&self.pool)
             .await?;
+
+        Ok(rows.into_iter().map(flagged_track_from_row).collect())
+    }
+
+    /// Mark pending batches past their deadline as expired.
+    /// Returns how many were marked.
+    pub async fn expire_batches(&self) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query(
+            r#"
+            UPDATE review_batches
+            SET status = 'expired', assigned_to = NULL, assigned_caller = NULL
+            WHERE status = 'pending'
+              AND expires_at <= NOW()
+            "#,
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(result.rows_affected())
+    }
+
+    /// Update batch status. Leaving `pending` releases any claim.
+    pub async fn update_batch_status(&self, id: &str, status: &str) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query(
+            r#"
+            UPDATE review_batches
+            SET status = $1,
+                assigned_to = CASE WHEN $1 = 'pending' THEN assigned_to END,
+                assigned_caller = CASE WHEN $1 = 'pending' THEN assigned_caller END
+            WHERE id = $2
+            "#,
+        )
+        .bind(status)
+        .bind(id)
+        .execute(&self.pool)
+        .await?;
         Ok(result.rows_affected() > 0)
     }
 
-    /// Mark a flag in a batch as reviewed.
-    pub async fn mark_flag_reviewed(
+    /// Claim an open batch for the credential `caller`, shown as `reviewer`.
+    /// Fails (returns `None`) if the batch is closed or held by another
+    /// credential, unless `force` is set.
+    pub async fn claim_batch(
+        &self,
+        id: &str,
+        caller: &str,
+        reviewer: &str,
+        force: bool,
+    ) -> Result<Option<ReviewBatch>, sqlx::Error> {
+        sqlx::query_as::<_, ReviewBatch>(
+            r#"
+            UPDATE review_batches
+            SET assigned_to = $3, assigned_caller = $2
+            WHERE id = $1
+              AND status = 'pending'
+              AND (expires_at IS NULL OR expires_at > NOW())
+              AND (assigned_caller IS NULL OR assigned_caller = $2 OR $4)
+            RETURNING id, created_at, expires_at, status, created_by, assigned_to, assigned_caller
+            "#,
+        )
+        .bind(id)
+        .bind(caller)
+        .bind(reviewer)
+        .bind(force)
+        .fetch_optional(&self.pool)
+        .await
+    }
+
+    /// Record a decision for a flag in a batch, in one transaction with its
+    /// effect: the negation and resolution when it clears the flag, or the
+    /// reviewer's confirmation when it confirms one.
+    ///
+    /// The flag's batch row is locked first, so only the first decision for
+    /// a flag is applied: repeating it (a retried submission) writes nothing,
+    /// and a different decision (a concurrent reviewer) is refused.
+    pub async fn apply_batch_decision(
         &self,
         batch_id: &str,
         uri: &str,
         decision: &str,
-    ) -> Result<bool, sqlx::Error> {
-        let result = sqlx::query(
+        reviewer: &str,
+        notes: Option<&str>,
+        negation: Option<(&Label, &ResolutionReason, &str)>,
+    ) -> Result<DecisionOutcome, sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+
+        let existing: Option<(bool, Option<String>)> = sqlx::query_as(
+            r#"
+            SELECT reviewed, decision FROM batch_flags
+            WHERE batch_id = $1 AND uri = $2
+            FOR UPDATE
+            "#,
+        )
+        .bind(batch_id)
+        .bind(uri)
+        .fetch_optional(&mut *tx)
+        .await?;
+        match existing {
+            None => return Ok(DecisionOutcome::NotInBatch),
+            Some((true, existing)) => {
+                let existing = existing.unwrap_or_default();
+                return Ok(if existing == decision {
+                    DecisionOutcome::AlreadyProcessed
+                } else {
+                    DecisionOutcome::Conflicting { existing }
+                });
+            }
+            Some((false, _)) => {}
+        }
+
+        sqlx::query(
             r#"
             UPDATE batch_flags
             SET reviewed = true, reviewed_at = NOW(), decision = $1
@@ -1031,9 +3743,73 @@ impl LabelDb {
         .bind(decision)
         .bind(batch_id)
         .bind(uri)
-        .execute(&self.pool)
+        .execute(&mut *tx)
         .await?;
-        Ok(result.rows_affected() > 0)
+
+        let mut seq = None;
+        if let Some((label, reason, notes)) = negation {
+            seq = Some(insert_label(&mut *tx, label).await?);
+            upsert_resolution(&mut *tx, uri, reason, Some(notes)).await?;
+        }
+        if decision == "confirm" {
+            upsert_confirmation(&mut *tx, uri, reviewer).await?;
+        }
+        insert_review_decision(&mut *tx, uri, Some(batch_id), decision, reviewer, notes).await?;
+
+        tx.commit().await?;
+        Ok(DecisionOutcome::Applied { seq })
+    }
+
+    /// Count a batch's flags by decision, for progress tracking.
+    pub async fn get_batch_decision_counts(
+        &self,
+        batch_id: &str,
+    ) -> Result<Vec<BatchDecisionCount>, sqlx::Error> {
+        sqlx::query_as::<_, BatchDecisionCount>(
+            r#"
+            SELECT decision, reviewed, COUNT(*) AS count, MAX(reviewed_at) AS last_reviewed_at
+            FROM batch_flags
+            WHERE batch_id = $1
+            GROUP BY decision, reviewed
+            "#,
+        )
+        .bind(batch_id)
+        .fetch_all(&self.pool)
+        .await
+    }
+
+
+    /// Every review decision on the given URIs, newest first.
+    pub async fn get_review_decisions(
+        &self,
+        uris: &[String],
+    ) -> Result<Vec<ReviewDecisionRow>, sqlx::Error> {
+        sqlx::query_as::<_, ReviewDecisionRow>(
+            r#"
+            SELECT id, uri, batch_id, decision, actor, notes, created_at
+            FROM review_decisions
+            WHERE uri = ANY($1)
+            ORDER BY created_at DESC, id DESC
+            "#,
+        )
+        .bind(uris)
+        .fetch_all(&self.pool)
+        .await
+    }
+
+    /// Every review batch entry for a URI, newest batch first.
+    pub async fn get_batch_decisions(&self, uri: &str) -> Result<Vec<BatchFlag>, sqlx::Error> {
+        sqlx::query_as::<_, BatchFlag>(
+            r#"
+            SELECT id, batch_id, uri, reviewed, reviewed_at, decision
+            FROM batch_flags
+            WHERE uri = $1
+            ORDER BY id DESC
+            "#,
+        )
+        .bind(uri)
+        .fetch_all(&self.pool)
+        .await
     }
 
     /// Get pending (non-reviewed) flags from a batch.
@@ -1055,25 +3831,70 @@ impl LabelDb {
 
     /// Get all sensitive images.
     pub async fn get_sensitive_images(&self) -> Result<Vec<SensitiveImageRow>, sqlx::Error> {
-        sqlx::query_as::<_, SensitiveImageRow>(
-            "SELECT id, image_id, url, reason, flagged_at, flagged_by FROM sensitive_images ORDER BY flagged_at DESC",
+        let query = format!(
+            "SELECT {} FROM sensitive_images ORDER BY flagged_at DESC",
+            SENSITIVE_IMAGE_COLUMNS
+        );
+        sqlx::query_as::<_, SensitiveImageRow>(&query)
+            .fetch_all(&self.pool)
+            .await
+    }
+
+    /// List sensitive images in the given order, with the total match count.
+    pub async fn list_sensitive_images(
+        &self,
+        flagged_by: Option<&str>,
+        sort: SensitiveImageSort,
+        limit: i64,
+        offset: i64,
+    ) -> Result<(Vec<SensitiveImageRow>, i64), sqlx::Error> {
+        let total = sqlx::query_scalar::<_, i64>(
+            "SELECT COUNT(*) FROM sensitive_images WHERE $1::TEXT IS NULL OR flagged_by = $1",
         )
-        .fetch_all(&self.pool)
-        .await
+        .bind(flagged_by)
+        .fetch_one(&self.pool)
+        .await?;
+
+        let query = format!(
+            r#"
+            SELECT {}
+            FROM sensitive_images
+            WHERE $1::TEXT IS NULL OR flagged_by = $1
+            ORDER BY {}
+            LIMIT $2 OFFSET $3
+            "#,
+            SENSITIVE_IMAGE_COLUMNS,
+            sort.order_by()
+        );
+        let rows = sqlx::query_as::<_, SensitiveImageRow>(&query)
+            .bind(flagged_by)
+            .bind(limit)
+            .bind(offset)
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok((rows, total))
     }
 
     /// Add a sensitive image entry.
+    ///
+    /// `severity` and `violated_categories` come from the image scan that
+    /// flagged it, when there is one.
     pub async fn add_sensitive_image(
         &self,
         image_id: Option<&str>,
         url: Option<&str>,
         reason: Option<&str>,
         flagged_by: Option<&str>,
+        severity: Option<&str>,
+        violated_categories: &[String],
     ) -> Result<i64, sqlx::Error> {
+        let categories_json = (!violated_categories.is_empty())
+            .then(|| serde_json::to_value(violated_categories).unwrap_or_default());
         sqlx::query_scalar::<_, i64>(
             r#"
-            INSERT INTO sensitive_images (image_id, url, reason, flagged_by)
-            VALUES ($1, $2, $3, $4)
+            INSERT INTO sensitive_images (image_id, url, reason, flagged_by, severity, violated_categories)
+            VALUES ($1, $2, $3, $4, $5, $6)
             RETURNING id
             "#,
         )
@@ -1081,6 +3902,8 @@ impl LabelDb {
         .bind(url)
         .bind(reason)
         .bind(flagged_by)
+        .bind(severity)
+        .bind(categories_json)
         .fetch_one(&self.pool)
         .await
     }
@@ -1095,242 +3918,4708 @@ impl LabelDb {
     }
 
     // -------------------------------------------------------------------------
-    // Image scans
+    // Sensitive image re-verification
     // -------------------------------------------------------------------------
 
-    /// Store an image scan result.
-    pub async fn store_image_scan(
+    /// The most recent re-verification run, finished or not.
+    pub async fn get_latest_reverify_run(&self) -> Result<Option<ReverifyRunRow>, sqlx::Error> {
+        sqlx::query_as::<_, ReverifyRunRow>(
+            "SELECT * FROM image_reverify_runs ORDER BY id DESC LIMIT 1",
+        )
+        .fetch_optional(&self.pool)
+        .await
+    }
+
+    /// Start a re-verification run. Returns `None` if one is already open.
+    pub async fn create_reverify_run(
         &self,
-        image_id: &str,
-        is_safe: bool,
-        violated_categories: &[String],
-        severity: &str,
-        explanation: &str,
-        model: &str,
-    ) -> Result<i64, sqlx::Error> {
-        let categories_json = serde_json::to_value(violated_categories).unwrap_or_default();
-        sqlx::query_scalar::<_, i64>(
+        started_by: &str,
+    ) -> Result<Option<ReverifyRunRow>, sqlx::Error> {
+        sqlx::query_as::<_, ReverifyRunRow>(
             r#"
-            INSERT INTO image_scans (image_id, is_safe, violated_categories, severity, explanation, model)
-            VALUES ($1, $2, $3, $4, $5, $6)
-            RETURNING id
+            INSERT INTO image_reverify_runs (started_by)
+            VALUES ($1)
+            ON CONFLICT DO NOTHING
+            RETURNING *
+            "#,
+        )
+        .bind(started_by)
+        .fetch_optional(&self.pool)
+        .await
+    }
+
+    /// Take over an open run whose last progress is older than `stale_after`.
+    /// Returns `None` if the run finished or made progress in the meantime.
+    pub async fn resume_reverify_run(
+        &self,
+        id: i64,
+        stale_after: chrono::Duration,
+    ) -> Result<Option<ReverifyRunRow>, sqlx::Error> {
+        sqlx::query_as::<_, ReverifyRunRow>(
+            r#"
+            UPDATE image_reverify_runs
+            SET updated_at = NOW()
+            WHERE id = $1 AND finished_at IS NULL AND updated_at < $2
+            RETURNING *
+            "#,
+        )
+        .bind(id)
+        .bind(Utc::now() - stale_after)
+        .fetch_optional(&self.pool)
+        .await
+    }
+
+    /// The next Claude-flagged sensitive image after `after_id`, by id.
+    pub async fn next_reverify_image(
+        &self,
+        after_id: i64,
+    ) -> Result<Option<SensitiveImageRow>, sqlx::Error> {
+        let query = format!(
+            "SELECT {} FROM sensitive_images \
+             WHERE flagged_by = 'claude-auto' AND id > $1 ORDER BY id LIMIT 1",
+            SENSITIVE_IMAGE_COLUMNS
+        );
+        sqlx::query_as::<_, SensitiveImageRow>(&query)
+            .bind(after_id)
+            .fetch_optional(&self.pool)
+            .await
+    }
+
+    /// Record one processed image on a run.
+    pub async fn record_reverify_progress(
+        &self,
+        run_id: i64,
+        image_id: i64,
+        outcome: ReverifyOutcome,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            UPDATE image_reverify_runs
+            SET last_image_id = $2,
+                checked = checked + CASE WHEN $3 = 'failed' THEN 0 ELSE 1 END,
+                cleared = cleared + CASE WHEN $3 = 'cleared' THEN 1 ELSE 0 END,
+                failed = failed + CASE WHEN $3 = 'failed' THEN 1 ELSE 0 END,
+                updated_at = NOW()
+            WHERE id = $1
             "#,
         )
+        .bind(run_id)
         .bind(image_id)
-        .bind(is_safe)
-        .bind(categories_json)
-        .bind(severity)
-        .bind(explanation)
-        .bind(model)
+        .bind(outcome.as_str())
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Mark a run finished.
+    pub async fn finish_reverify_run(&self, run_id: i64) -> Result<ReverifyRunRow, sqlx::Error> {
+        sqlx::query_as::<_, ReverifyRunRow>(
+            r#"
+            UPDATE image_reverify_runs
+            SET finished_at = NOW(), updated_at = NOW()
+            WHERE id = $1
+            RETURNING *
+            "#,
+        )
+        .bind(run_id)
         .fetch_one(&self.pool)
         .await
     }
 
-    /// Get image scan stats for cost tracking.
-    pub async fn get_image_scan_stats(&self) -> Result<ImageScanStats, sqlx::Error> {
-        let row: (i64, i64, i64) = sqlx::query_as(
+    /// Check whether an image already has a sensitive entry.
+    pub async fn has_sensitive_image(&self, image_id: &str) -> Result<bool, sqlx::Error> {
+        sqlx::query_scalar::<_, bool>(
+            "SELECT EXISTS(SELECT 1 FROM sensitive_images WHERE image_id = $1)",
+        )
+        .bind(image_id)
+        .fetch_one(&self.pool)
+        .await
+    }
+
+    /// Remove sensitive entries for an image id, or for any image previously
+    /// scanned with the given content hash.
+    pub async fn remove_sensitive_images_for(
+        &self,
+        image_id: Option<&str>,
+        content_hash: Option<&str>,
+    ) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query(
             r#"
-            SELECT
-                COUNT(*) as total,
-                COUNT(*) FILTER (WHERE is_safe = true) as safe,
-                COUNT(*) FILTER (WHERE is_safe = false) as flagged
-            FROM image_scans
+            DELETE FROM sensitive_images
+            WHERE image_id = $1
+               OR image_id IN (SELECT image_id FROM image_scans WHERE content_hash = $2)
             "#,
         )
-        .fetch_one(&self.pool)
+        .bind(image_id)
+        .bind(content_hash)
+        .execute(&self.pool)
         .await?;
-
-        Ok(ImageScanStats {
-            total: row.0,
-            safe: row.1,
-            flagged: row.2,
-        })
+        Ok(result.rows_affected())
     }
 
     // -------------------------------------------------------------------------
-    // User reports
+    // Image overrides
     // -------------------------------------------------------------------------
 
-    /// Create a new user report.
-    #[allow(clippy::too_many_arguments)]
-    pub async fn create_report(
+    /// Record a human override for an image and apply it, in one transaction.
+    ///
+    /// A safe verdict removes sensitive entries for the image id, or for any
+    /// image previously scanned with the content hash. An unsafe verdict adds
+    /// an entry for `image_id` unless it already has one. Scans held for
+    /// review are marked overridden, and the override goes to the audit log.
+    pub async fn apply_image_override(
         &self,
-        reporter_did: &str,
-        reporter_handle: Option<&str>,
-        target_type: &str,
-        target_id: &str,
-        target_name: Option<&str>,
-        target_url: Option<&str>,
-        target_uri: Option<&str>,
-        reason: &str,
-        description: Option<&str>,
-        screenshot_url: Option<&str>,
-    ) -> Result<UserReport, sqlx::Error> {
-        sqlx::query_as::<_, UserReport>(
+        image_id: Option<&str>,
+        content_hash: Option<&str>,
+        verdict: OverrideVerdict,
+        reviewed_by: &str,
+        notes: Option<&str>,
+    ) -> Result<AppliedOverride, sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+        let (removed, sensitive_image_id) = match (verdict, image_id) {
+            (OverrideVerdict::Safe, _) => {
+                let result = sqlx::query(
+                    r#"
+                    DELETE FROM sensitive_images
+                    WHERE image_id = $1
+                       OR image_id IN (SELECT image_id FROM image_scans WHERE content_hash = $2)
+                    "#,
+                )
+                .bind(image_id)
+                .bind(content_hash)
+                .execute(&mut *tx)
+                .await?;
+                (result.rows_affected(), None)
+            }
+            (OverrideVerdict::Unsafe, Some(image_id)) => {
+                let id = sqlx::query_scalar::<_, i64>(
+                    r#"
+                    INSERT INTO sensitive_images (image_id, reason, flagged_by)
+                    SELECT $1, $2, $3
+                    WHERE NOT EXISTS (SELECT 1 FROM sensitive_images WHERE image_id = $1)
+                    RETURNING id
+                    "#,
+                )
+                .bind(image_id)
+                .bind(notes)
+                .bind(reviewed_by)
+                .fetch_optional(&mut *tx)
+                .await?;
+                (0, id)
+            }
+            // override_image_scan requires an image id for unsafe verdicts
+            (OverrideVerdict::Unsafe, None) => (0, None),
+        };
+
+        let override_id = sqlx::query_scalar::<_, i64>(
             r#"
-            INSERT INTO user_reports (reporter_did, reporter_handle, target_type, target_id, target_name, target_url, target_uri, reason, description, screenshot_url)
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
-            RETURNING *
+            INSERT INTO image_overrides (image_id, content_hash, verdict, reviewed_by, notes)
+            VALUES ($1, $2, $3, $4, $5)
+            RETURNING id
             "#,
         )
-        .bind(reporter_did)
-        .bind(reporter_handle)
-        .bind(target_type)
-        .bind(target_id)
-        .bind(target_name)
-        .bind(target_url)
-        .bind(target_uri)
-        .bind(reason)
-        .bind(description)
-        .bind(screenshot_url)
-        .fetch_one(&self.pool)
+        .bind(image_id)
+        .bind(content_hash)
+        .bind(verdict.as_str())
+        .bind(reviewed_by)
+        .bind(notes)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        sqlx::query(
+            r#"
+            UPDATE image_scans SET review_state = 'overridden'
+            WHERE review_state = 'needs_review'
+              AND (image_id = $1 OR content_hash = $2)
+            "#,
+        )
+        .bind(image_id)
+        .bind(content_hash)
+        .execute(&mut *tx)
+        .await?;
+
+        insert_audit_event(
+            &mut *tx,
+            "image_override",
+            image_id.or(content_hash).unwrap_or_default(),
+            reviewed_by,
+            serde_json::json!({
+                "override_id": override_id,
+                "image_id": image_id,
+                "content_hash": content_hash,
+                "verdict": verdict.as_str(),
+                "notes": notes,
+                "removed": removed,
+                "sensitive_image_id": sensitive_image_id,
+            }),
+        )
+        .await?;
+
+        tx.commit().await?;
+        Ok(AppliedOverride {
+            override_id,
+            removed,
+            sensitive_image_id,
+        })
+    }
+
+    /// Get the most recent override matching an image id or content hash.
+    pub async fn get_image_override(
+        &self,
+        image_id: &str,
+        content_hash: &str,
+    ) -> Result<Option<ImageOverrideRow>, sqlx::Error> {
+        sqlx::query_as::<_, ImageOverrideRow>(
+            r#"
+            SELECT id, image_id, content_hash, verdict, reviewed_by, notes, created_at
+            FROM image_overrides
+            WHERE image_id = $1 OR content_hash = $2
+            ORDER BY created_at DESC, id DESC
+            LIMIT 1
+            "#,
+        )
+        .bind(image_id)
+        .bind(content_hash)
+        .fetch_optional(&self.pool)
         .await
     }
 
-    /// List user reports with optional filtering.
-    pub async fn list_reports(
+    // -------------------------------------------------------------------------
+    // Audit log
+    // -------------------------------------------------------------------------
+
+    /// Record a manual moderation action.
+    pub async fn record_audit_event(
+        &self,
+        action: &str,
+        target: &str,
+        actor: &str,
+        details: serde_json::Value,
+    ) -> Result<i64, sqlx::Error> {
+        insert_audit_event(&self.pool, action, target, actor, details).await
+    }
+
+    /// List audit log entries, newest first. Each filter is an exact match.
+    pub async fn list_audit_log(
         &self,
-        status: Option<&str>,
-        target_type: Option<&str>,
+        action: Option<&str>,
+        target: Option<&str>,
+        actor: Option<&str>,
         limit: i64,
-        offset: i64,
-    ) -> Result<Vec<UserReport>, sqlx::Error> {
-        let mut query = String::from(
-            "SELECT * FROM user_reports WHERE 1=1",
-        );
-        let mut param_idx = 1;
+    ) -> Result<Vec<AuditLogRow>, sqlx::Error> {
+        sqlx::query_as::<_, AuditLogRow>(
+            r#"
+            SELECT id, action, target, actor, details, created_at
+            FROM audit_log
+            WHERE ($1::TEXT IS NULL OR action = $1)
+              AND ($2::TEXT IS NULL OR target = $2)
+              AND ($3::TEXT IS NULL OR actor = $3)
+            ORDER BY created_at DESC, id DESC
+            LIMIT $4
+            "#,
+        )
+        .bind(action)
+        .bind(target)
+        .bind(actor)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await
+    }
 
-        if status.is_some() {
-            query.push_str(&format!(" AND status = ${}", param_idx));
-            param_idx += 1;
-        }
-        if target_type.is_some() {
-            query.push_str(&format!(" AND target_type = ${}", param_idx));
-            param_idx += 1;
-        }
+    // -------------------------------------------------------------------------
+    // Sessions
+    // -------------------------------------------------------------------------
 
-        query.push_str(&format!(" ORDER BY created_at DESC LIMIT ${} OFFSET ${}", param_idx, param_idx + 1));
+    /// Persist a logged-out session id, dropping entries that have expired.
+    pub async fn revoke_session(
+        &self,
+        id: &str,
+        expires_at: DateTime<Utc>,
+    ) -> Result<(), sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+        sqlx::query("DELETE FROM revoked_sessions WHERE expires_at <= NOW()")
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query(
+            "INSERT INTO revoked_sessions (id, expires_at) VALUES ($1, $2) ON CONFLICT (id) DO NOTHING",
+        )
+        .bind(id)
+        .bind(expires_at)
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+        Ok(())
+    }
 
-        let mut q = sqlx::query_as::<_, UserReport>(&query);
+    /// Revoked session ids that haven't expired yet, for loading at startup.
+    pub async fn get_revoked_sessions(
+        &self,
+    ) -> Result<Vec<(String, DateTime<Utc>)>, sqlx::Error> {
+        sqlx::query_as(
+            "SELECT id, expires_at FROM revoked_sessions WHERE expires_at > NOW()",
+        )
+        .fetch_all(&self.pool)
+        .await
+    }
 
-        if let Some(s) = status {
-            q = q.bind(s);
-        }
-        if let Some(t) = target_type {
-            q = q.bind(t);
-        }
+    // -------------------------------------------------------------------------
+    // Failed deliveries
+    // -------------------------------------------------------------------------
 
-        q = q.bind(limit).bind(offset);
+    /// Keep a webhook delivery that ran out of attempts. Returns its id.
+    pub async fn record_failed_delivery(
+        &self,
+        target: &str,
+        payload: &serde_json::Value,
+        error: &str,
+        attempts: i32,
+    ) -> Result<i64, sqlx::Error> {
+        let delivery = insert_failed_delivery(&self.pool, target, payload, error, attempts).await?;
+        Ok(delivery.id)
+    }
 
-        q.fetch_all(&self.pool).await
+    /// Failed deliveries, newest first. Ones a retry got through are left
+    /// out unless `include_delivered`.
+    pub async fn list_failed_deliveries(
+        &self,
+        include_delivered: bool,
+        limit: i64,
+    ) -> Result<Vec<FailedDelivery>, sqlx::Error> {
+        sqlx::query_as::<_, FailedDelivery>(
+            r#"
+            SELECT * FROM failed_deliveries
+            WHERE $1 OR delivered_at IS NULL
+            ORDER BY created_at DESC, id DESC
+            LIMIT $2
+            "#,
+        )
+        .bind(include_delivered)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await
     }
 
-    /// Get a user report by ID.
-    pub async fn get_report(&self, id: i32) -> Result<Option<UserReport>, sqlx::Error> {
-        sqlx::query_as::<_, UserReport>("SELECT * FROM user_reports WHERE id = $1")
+    pub async fn get_failed_delivery(
+        &self,
+        id: i64,
+    ) -> Result<Option<FailedDelivery>, sqlx::Error> {
+        sqlx::query_as::<_, FailedDelivery>("SELECT * FROM failed_deliveries WHERE id = $1")
             .bind(id)
             .fetch_optional(&self.pool)
             .await
     }
 
-    /// Resolve a user report.
-    pub async fn resolve_report(
+    /// Record a retry: delivered when `error` is `None`, otherwise the new
+    /// error replaces the old one.
+    pub async fn record_delivery_retry(
         &self,
-        id: i32,
-        status: &str,
-        admin_notes: Option<&str>,
-        resolved_by: &str,
-    ) -> Result<Option<UserReport>, sqlx::Error> {
-        sqlx::query_as::<_, UserReport>(
+        id: i64,
+        error: Option<&str>,
+    ) -> Result<Option<FailedDelivery>, sqlx::Error> {
+        sqlx::query_as::<_, FailedDelivery>(
             r#"
-            UPDATE user_reports
-            SET status = $1, admin_notes = $2, resolved_by = $3, resolved_at = NOW(), updated_at = NOW()
-            WHERE id = $4
+            UPDATE failed_deliveries
+            SET attempts = attempts + 1,
+                retried_at = NOW(),
+                error = COALESCE($2, error),
+                delivered_at = CASE WHEN $2::TEXT IS NULL THEN NOW() END
+            WHERE id = $1
             RETURNING *
             "#,
         )
-        .bind(status)
-        .bind(admin_notes)
-        .bind(resolved_by)
         .bind(id)
+        .bind(error)
         .fetch_optional(&self.pool)
         .await
     }
-}
-
-/// Statistics for image scans.
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct ImageScanStats {
-    pub total: i64,
-    pub safe: i64,
-    pub flagged: i64,
-}
 
-impl LabelRow {
-    /// Convert database row to Label struct.
-    pub fn to_label(&self) -> Label {
-        Label {
-            ver: Some(1),
-            src: self.src.clone(),
-            uri: self.uri.clone(),
-            cid: self.cid.clone(),
-            val: self.val.clone(),
-            neg: if self.neg { Some(true) } else { None },
-            cts: self.cts.format("%Y-%m-%dT%H:%M:%S%.3fZ").to_string(),
-            exp: self
-                .exp
-                .map(|e| e.format("%Y-%m-%dT%H:%M:%S%.3fZ").to_string()),
-            sig: Some(bytes::Bytes::from(self.sig.clone())),
-        }
+    /// Record the first round of attempts at a delivery that was kept before
+    /// it was sent: delivered when `error` is `None`, otherwise the last error.
+    pub async fn record_delivery_attempts(
+        &self,
+        id: i64,
+        attempts: i32,
+        error: Option<&str>,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            UPDATE failed_deliveries
+            SET attempts = attempts + $2,
+                error = COALESCE($3, error),
+                delivered_at = CASE WHEN $3::TEXT IS NULL THEN NOW() END
+            WHERE id = $1
+            "#,
+        )
+        .bind(id)
+        .bind(attempts)
+        .bind(error)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
     }
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
 
-    #[test]
-    fn test_resolution_reason_from_str() {
-        assert_eq!(
-            ResolutionReason::from_str("original_artist"),
-            Some(ResolutionReason::OriginalArtist)
-        );
-        assert_eq!(
-            ResolutionReason::from_str("licensed"),
-            Some(ResolutionReason::Licensed)
-        );
-        assert_eq!(
-            ResolutionReason::from_str("fingerprint_noise"),
-            Some(ResolutionReason::FingerprintNoise)
-        );
-        assert_eq!(
-            ResolutionReason::from_str("cover_version"),
-            Some(ResolutionReason::CoverVersion)
-        );
-        assert_eq!(
-            ResolutionReason::from_str("other"),
-            Some(ResolutionReason::Other)
-        );
-        assert_eq!(ResolutionReason::from_str("invalid"), None);
-    }
+    // -------------------------------------------------------------------------
+    // Enforcement
+    // -------------------------------------------------------------------------
 
-    #[test]
+    /// Record a new enforcement request as pending.
+    ///
+    /// Returns false if one for the URI is already pending, so callers don't
+    /// start a second delivery alongside one that is still retrying.
+    pub async fn start_enforcement(
+        &self,
+        uri: &str,
+        track_id: Option<i64>,
+        confirmed_by: &str,
+    ) -> Result<bool, sqlx::Error> {
+        let started = sqlx::query_scalar::<_, String>(
+            r#"
+            INSERT INTO enforcement_requests (uri, track_id, confirmed_by, status)
+            VALUES ($1, $2, $3, 'pending')
+            ON CONFLICT (uri) DO UPDATE SET
+                track_id = EXCLUDED.track_id,
+                confirmed_by = EXCLUDED.confirmed_by,
+                status = 'pending',
+                attempts = 0,
+                last_response_status = NULL,
+                updated_at = NOW()
+            WHERE enforcement_requests.status <> 'pending'
+            RETURNING uri
+            "#,
+        )
+        .bind(uri)
+        .bind(track_id)
+        .bind(confirmed_by)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(started.is_some())
+    }
+
+    /// Record the outcome of one enforcement attempt.
+    pub async fn record_enforcement_attempt(
+        &self,
+        uri: &str,
+        status: &str,
+        response_status: Option<i32>,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            UPDATE enforcement_requests
+            SET status = $2, attempts = attempts + 1, last_response_status = $3, updated_at = NOW()
+            WHERE uri = $1
+            "#,
+        )
+        .bind(uri)
+        .bind(status)
+        .bind(response_status)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Enforcement requests still waiting on a successful delivery.
+    pub async fn get_pending_enforcements(
+        &self,
+    ) -> Result<Vec<EnforcementRequestRow>, sqlx::Error> {
+        sqlx::query_as::<_, EnforcementRequestRow>(
+            r#"
+            SELECT uri, track_id, confirmed_by
+            FROM enforcement_requests
+            WHERE status = 'pending'
+            ORDER BY created_at
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await
+    }
+
+    // -------------------------------------------------------------------------
+    // Image scans
+    // -------------------------------------------------------------------------
+
+    /// Store an image scan result.
+    pub async fn store_image_scan(&self, scan: &NewImageScan<'_>) -> Result<i64, sqlx::Error> {
+        let categories_json = serde_json::to_value(scan.violated_categories).unwrap_or_default();
+        sqlx::query_scalar::<_, i64>(
+            r#"
+            INSERT INTO image_scans (image_id, is_safe, violated_categories, severity, explanation, model,
+                                     source_url, review_state, input_tokens, output_tokens, cost_usd,
+                                     content_hash, second_opinion_of, request_id)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14)
+            RETURNING id
+            "#,
+        )
+        .bind(scan.image_id)
+        .bind(scan.is_safe)
+        .bind(categories_json)
+        .bind(scan.severity)
+        .bind(scan.explanation)
+        .bind(scan.model)
+        .bind(scan.source_url)
+        .bind(scan.review_state)
+        .bind(scan.input_tokens as i32)
+        .bind(scan.output_tokens as i32)
+        .bind(scan.cost_usd)
+        .bind(scan.content_hash)
+        .bind(scan.second_opinion_of)
+        .bind(scan.request_id)
+        .fetch_one(&self.pool)
+        .await
+    }
+
+    /// List image scans matching a filter, newest first, with the total match count.
+    pub async fn list_image_scans(
+        &self,
+        filter: &ImageScanFilter<'_>,
+        limit: i64,
+        offset: i64,
+    ) -> Result<(Vec<ImageScanRow>, i64), sqlx::Error> {
+        let mut conditions = Vec::new();
+        let mut param_idx = 1;
+
+        if filter.image_id.is_some() {
+            conditions.push(format!("image_id = ${}", param_idx));
+            param_idx += 1;
+        }
+        if filter.is_safe.is_some() {
+            conditions.push(format!("is_safe = ${}", param_idx));
+            param_idx += 1;
+        }
+        if filter.severity.is_some() {
+            conditions.push(format!("severity = ${}", param_idx));
+            param_idx += 1;
+        }
+        if filter.since.is_some() {
+            conditions.push(format!("scanned_at >= ${}", param_idx));
+            param_idx += 1;
+        }
+        if filter.until.is_some() {
+            conditions.push(format!("scanned_at < ${}", param_idx));
+            param_idx += 1;
+        }
+
+        let where_clause = if conditions.is_empty() {
+            String::new()
+        } else {
+            format!("WHERE {}", conditions.join(" AND "))
+        };
+
+        let count_query = format!("SELECT COUNT(*) FROM image_scans {}", where_clause);
+        let mut count_q = sqlx::query_scalar::<_, i64>(&count_query);
+        if let Some(v) = filter.image_id {
+            count_q = count_q.bind(v);
+        }
+        if let Some(v) = filter.is_safe {
+            count_q = count_q.bind(v);
+        }
+        if let Some(v) = filter.severity {
+            count_q = count_q.bind(v);
+        }
+        if let Some(v) = filter.since {
+            count_q = count_q.bind(v);
+        }
+        if let Some(v) = filter.until {
+            count_q = count_q.bind(v);
+        }
+        let total = count_q.fetch_one(&self.pool).await?;
+
+        let query = format!(
+            r#"
+            SELECT {}
+            FROM image_scans
+            {}
+            ORDER BY scanned_at DESC, id DESC
+            LIMIT ${} OFFSET ${}
+            "#,
+            IMAGE_SCAN_COLUMNS,
+            where_clause,
+            param_idx,
+            param_idx + 1
+        );
+        let mut q = sqlx::query_as::<_, ImageScanRow>(&query);
+        if let Some(v) = filter.image_id {
+            q = q.bind(v);
+        }
+        if let Some(v) = filter.is_safe {
+            q = q.bind(v);
+        }
+        if let Some(v) = filter.severity {
+            q = q.bind(v);
+        }
+        if let Some(v) = filter.since {
+            q = q.bind(v);
+        }
+        if let Some(v) = filter.until {
+            q = q.bind(v);
+        }
+        let rows = q.bind(limit).bind(offset).fetch_all(&self.pool).await?;
+
+        Ok((rows, total))
+    }
+
+    /// Get image scans held for admin review, oldest first.
+    pub async fn get_image_review_queue(&self) -> Result<Vec<ImageScanRow>, sqlx::Error> {
+        let query = format!(
+            "SELECT {} FROM image_scans WHERE review_state = 'needs_review' ORDER BY scanned_at ASC",
+            IMAGE_SCAN_COLUMNS
+        );
+        sqlx::query_as::<_, ImageScanRow>(&query)
+            .fetch_all(&self.pool)
+            .await
+    }
+
+    /// Get second-opinion passes recorded for the given scans.
+    pub async fn get_second_opinions(
+        &self,
+        scan_ids: &[i64],
+    ) -> Result<Vec<ImageScanRow>, sqlx::Error> {
+        if scan_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+        let query = format!(
+            "SELECT {} FROM image_scans WHERE second_opinion_of = ANY($1)",
+            IMAGE_SCAN_COLUMNS
+        );
+        sqlx::query_as::<_, ImageScanRow>(&query)
+            .bind(scan_ids)
+            .fetch_all(&self.pool)
+            .await
+    }
+
+    /// Get a single image scan by ID.
+    pub async fn get_image_scan(&self, id: i64) -> Result<Option<ImageScanRow>, sqlx::Error> {
+        let query = format!("SELECT {} FROM image_scans WHERE id = $1", IMAGE_SCAN_COLUMNS);
+        sqlx::query_as::<_, ImageScanRow>(&query)
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await
+    }
+
+    /// Set the review state of an image scan.
+    pub async fn set_image_scan_review_state(
+        &self,
+        id: i64,
+        review_state: &str,
+    ) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query("UPDATE image_scans SET review_state = $1 WHERE id = $2")
+            .bind(review_state)
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Get image scan stats for cost tracking.
+    pub async fn get_image_scan_stats(&self) -> Result<ImageScanStats, sqlx::Error> {
+        let row: (i64, i64, i64) = sqlx::query_as(
+            r#"
+            SELECT
+                COUNT(*) as total,
+                COUNT(*) FILTER (WHERE is_safe = true) as safe,
+                COUNT(*) FILTER (WHERE is_safe = false) as flagged
+            FROM image_scans
+            "#,
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(ImageScanStats {
+            total: row.0,
+            safe: row.1,
+            flagged: row.2,
+        })
+    }
+
+    // -------------------------------------------------------------------------
+    // Audio scans
+    // -------------------------------------------------------------------------
+
+    /// Store an audio scan result.
+    pub async fn store_audio_scan(&self, scan: &NewAudioScan<'_>) -> Result<i64, sqlx::Error> {
+        sqlx::query_scalar::<_, i64>(
+            r#"
+            INSERT INTO audio_scans (filename, content_hash, size_bytes, is_flagged, dominant_match,
+                                     dominant_match_pct, sustained_song_count, matches, raw_response,
+                                     uri)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+            RETURNING id
+            "#,
+        )
+        .bind(scan.filename)
+        .bind(scan.content_hash)
+        .bind(scan.size_bytes)
+        .bind(scan.is_flagged)
+        .bind(scan.dominant_match)
+        .bind(scan.dominant_match_pct)
+        .bind(scan.sustained_song_count as i32)
+        .bind(&scan.matches)
+        .bind(scan.raw_response)
+        .bind(scan.uri)
+        .fetch_one(&self.pool)
+        .await
+    }
+
+    /// Audio scans recorded for a URI, newest first.
+    pub async fn get_audio_scans_for_uri(
+        &self,
+        uri: &str,
+    ) -> Result<Vec<AudioScanRow>, sqlx::Error> {
+        sqlx::query_as::<_, AudioScanRow>(
+            r#"
+            SELECT id, uri, filename, content_hash, size_bytes, is_flagged, dominant_match,
+                   dominant_match_pct, sustained_song_count, matches, scanned_at
+            FROM audio_scans
+            WHERE uri = $1
+            ORDER BY scanned_at DESC, id DESC
+            "#,
+        )
+        .bind(uri)
+        .fetch_all(&self.pool)
+        .await
+    }
+
+    // -------------------------------------------------------------------------
+    // User reports
+    // -------------------------------------------------------------------------
+
+    /// The unresolved report about `target_type`/`target_id` for `reason`
+    /// that `reporter_did` filed or supports, if any.
+    pub async fn find_own_open_report(
+        &self,
+        reporter_did: &str,
+        target_type: &str,
+        target_id: &str,
+        reason: &str,
+    ) -> Result<Option<UserReport>, sqlx::Error> {
+        sqlx::query_as::<_, UserReport>(&format!(
+            r#"
+            SELECT {REPORT_COLUMNS} FROM user_reports
+            WHERE target_type = $2 AND target_id = $3 AND reason = $4
+              AND status IN ('open', 'investigating', 'escalated')
+              AND (reporter_did = $1 OR EXISTS (
+                  SELECT 1 FROM report_supporters s
+                  WHERE s.report_id = user_reports.id AND s.reporter_did = $1
+              ))
+            ORDER BY created_at
+            LIMIT 1
+            "#
+        ))
+        .bind(reporter_did)
+        .bind(target_type)
+        .bind(target_id)
+        .bind(reason)
+        .fetch_optional(&self.pool)
+        .await
+    }
+
+    /// File a user report. If an unresolved (open, investigating or escalated)
+    /// report already covers the same target and reason, the reporter is added
+    /// to it as a supporter instead of creating another report.
+    ///
+    /// Filings for the same target and reason are serialized with an advisory
+    /// lock, so simultaneous reports can't both create a new one.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn file_report(
+        &self,
+        reporter_did: &str,
+        reporter_handle: Option<&str>,
+        target_type: &str,
+        target_id: &str,
+        target_name: Option<&str>,
+        target_url: Option<&str>,
+        target_uri: Option<&str>,
+        reason: &str,
+        description: Option<&str>,
+        screenshot_url: Option<&str>,
+        screenshot_key: Option<&str>,
+    ) -> Result<FiledReport, sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query("SELECT pg_advisory_xact_lock(hashtext($1 || ':' || $2 || ':' || $3))")
+            .bind(target_type)
+            .bind(target_id)
+            .bind(reason)
+            .execute(&mut *tx)
+            .await?;
+
+        let existing: Option<(i32, String)> = sqlx::query_as(
+            r#"
+            SELECT id, reporter_did FROM user_reports
+            WHERE target_type = $1 AND target_id = $2 AND reason = $3
+              AND status IN ('open', 'investigating', 'escalated')
+            ORDER BY created_at
+            LIMIT 1
+            "#,
+        )
+        .bind(target_type)
+        .bind(target_id)
+        .bind(reason)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        let filed = match existing {
+            Some((id, primary)) => {
+                let added = primary != reporter_did
+                    && sqlx::query(
+                        r#"
+                        INSERT INTO report_supporters (report_id, reporter_did)
+                        VALUES ($1, $2)
+                        ON CONFLICT DO NOTHING
+                        "#,
+                    )
+                    .bind(id)
+                    .bind(reporter_did)
+                    .execute(&mut *tx)
+                    .await?
+                    .rows_affected()
+                        > 0;
+                let report = select_report(&mut *tx, id).await?;
+                if added {
+                    FiledReport::Supported(report)
+                } else {
+                    FiledReport::AlreadyReported(report)
+                }
+            }
+            None => {
+                let id: i32 = sqlx::query_scalar(
+                    r#"
+                    INSERT INTO user_reports (reporter_did, reporter_handle, target_type, target_id, target_name, target_url, target_uri, reason, description, screenshot_url, screenshot_key)
+                    VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+                    RETURNING id
+                    "#,
+                )
+                .bind(reporter_did)
+                .bind(reporter_handle)
+                .bind(target_type)
+                .bind(target_id)
+                .bind(target_name)
+                .bind(target_url)
+                .bind(target_uri)
+                .bind(reason)
+                .bind(description)
+                .bind(screenshot_url)
+                .bind(screenshot_key)
+                .fetch_one(&mut *tx)
+                .await?;
+                FiledReport::Created(select_report(&mut *tx, id).await?)
+            }
+        };
+
+        tx.commit().await?;
+        Ok(filed)
+    }
+
+    /// Count a reporter's reports over the last hour and day, in one query.
+    /// Supporting an existing report counts as filing one.
+    pub async fn get_reporter_activity(
+        &self,
+        reporter_did: &str,
+    ) -> Result<ReporterActivity, sqlx::Error> {
+        sqlx::query_as::<_, ReporterActivity>(
+            r#"
+            SELECT
+                COUNT(*) FILTER (WHERE created_at > NOW() - INTERVAL '1 hour') AS last_hour,
+                COUNT(*) AS last_day,
+                MIN(created_at) FILTER (WHERE created_at > NOW() - INTERVAL '1 hour') AS oldest_last_hour,
+                MIN(created_at) AS oldest_last_day
+            FROM (
+                SELECT created_at FROM user_reports WHERE reporter_did = $1
+                UNION ALL
+                SELECT created_at FROM report_supporters WHERE reporter_did = $1
+            ) filed
+            WHERE created_at > NOW() - INTERVAL '1 day'
+            "#,
+        )
+        .bind(reporter_did)
+        .fetch_one(&self.pool)
+        .await
+    }
+
+    /// List user reports with optional filtering.
+    ///
+    /// Newest first. Pass the previous page's `next_cursor` as `cursor`;
+    /// reports filed while paging land before it and don't shift later pages.
+    /// `offset` is the deprecated way to page and is applied after `cursor`.
+    pub async fn list_reports(
+        &self,
+        filter: &ReportFilter<'_>,
+        cursor: Option<ReportsCursor>,
+        limit: i64,
+        offset: i64,
+    ) -> Result<ReportsPage, sqlx::Error> {
+        let (conditions, binds) = filter.conditions();
+        let mut query = format!("SELECT {REPORT_COLUMNS} FROM user_reports WHERE 1=1");
+        for condition in &conditions {
+            query.push_str(" AND ");
+            query.push_str(condition);
+        }
+        let mut param_idx = binds.len() + 1;
+
+        if cursor.is_some() {
+            query.push_str(&format!(
+                " AND (created_at, id) < (${}, ${})",
+                param_idx,
+                param_idx + 1
+            ));
+            param_idx += 2;
+        }
+
+        // one extra row tells us whether there's another page
+        query.push_str(&format!(
+            " ORDER BY created_at DESC, id DESC LIMIT ${} OFFSET ${}",
+            param_idx,
+            param_idx + 1
+        ));
+
+        let mut q = sqlx::query_as::<_, UserReport>(&query);
+
+        for value in binds {
+            q = q.bind(value);
+        }
+        if let Some(c) = cursor {
+            q = q.bind(c.created_at).bind(c.id);
+        }
+
+        q = q.bind(limit + 1).bind(offset);
+
+        let mut reports = q.fetch_all(&self.pool).await?;
+        let next_cursor = if reports.len() as i64 > limit {
+            reports.truncate(limit as usize);
+            reports.last().map(|r| ReportsCursor::after(r).encode())
+        } else {
+            None
+        };
+        Ok(ReportsPage {
+            reports,
+            next_cursor,
+        })
+    }
+
+    /// Reports about a track, matched by its URI or (for track reports) its id.
+    pub async fn get_reports_for_track(
+        &self,
+        uri: &str,
+        track_id: Option<i64>,
+    ) -> Result<Vec<UserReport>, sqlx::Error> {
+        sqlx::query_as::<_, UserReport>(&format!(
+            r#"
+            SELECT {REPORT_COLUMNS} FROM user_reports
+            WHERE target_uri = $1 OR (target_type = 'track' AND target_id = $2)
+            ORDER BY created_at DESC
+            "#
+        ))
+        .bind(uri)
+        .bind(track_id.map(|id| id.to_string()))
+        .fetch_all(&self.pool)
+        .await
+    }
+
+    /// Reports about, or filed by, any of `dids` or the account with `handle`,
+    /// newest first. Reports about an account match its DID as the target id
+    /// or the repo of the target URI.
+    pub async fn search_reports(
+        &self,
+        dids: &[String],
+        handle: Option<&str>,
+        limit: i64,
+    ) -> Result<Vec<UserReport>, sqlx::Error> {
+        sqlx::query_as::<_, UserReport>(&format!(
+            r#"
+            SELECT {REPORT_COLUMNS} FROM user_reports
+            WHERE target_id = ANY($1)
+               OR split_part(target_uri, '/', 3) = ANY($1)
+               OR reporter_did = ANY($1)
+               OR lower(reporter_handle) = lower($2)
+               OR lower(target_url) = '/u/' || lower($2)
+            ORDER BY created_at DESC
+            LIMIT $3
+            "#
+        ))
+        .bind(dids)
+        .bind(handle)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await
+    }
+
+    /// Get a user report by ID.
+    pub async fn get_report(&self, id: i32) -> Result<Option<UserReport>, sqlx::Error> {
+        sqlx::query_as::<_, UserReport>(&format!(
+            "SELECT {REPORT_COLUMNS} FROM user_reports WHERE id = $1"
+        ))
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await
+    }
+
+    /// Mark an open report as escalated, opening a review batch for
+    /// `batch_uri` if given, recording the audit entry and keeping the
+    /// webhook delivery `delivery` builds for the escalated report (target
+    /// and envelope) in `failed_deliveries` until it's sent, all in one
+    /// transaction. Returns `None` if the report isn't open, e.g. another
+    /// filing already escalated it.
+    pub async fn escalate_report(
+        &self,
+        id: i32,
+        threshold: i64,
+        batch: Option<(&str, &str, DateTime<Utc>)>,
+        delivery: impl FnOnce(&UserReport) -> Option<(String, serde_json::Value)>,
+    ) -> Result<Option<(UserReport, Option<FailedDelivery>)>, sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+        let Some(report) = sqlx::query_as::<_, UserReport>(&format!(
+            r#"
+            UPDATE user_reports
+            SET status = 'escalated', updated_at = NOW()
+            WHERE id = $1 AND status = 'open'
+            RETURNING {REPORT_COLUMNS}
+            "#
+        ))
+        .bind(id)
+        .fetch_optional(&mut *tx)
+        .await?
+        else {
+            return Ok(None);
+        };
+
+        if let Some((batch_id, uri, expires_at)) = batch {
+            sqlx::query(
+                r#"
+                INSERT INTO review_batches (id, created_by, expires_at)
+                VALUES ($1, 'escalation', $2)
+                "#,
+            )
+            .bind(batch_id)
+            .bind(expires_at)
+            .execute(&mut *tx)
+            .await?;
+            sqlx::query("INSERT INTO batch_flags (batch_id, uri) VALUES ($1, $2)")
+                .bind(batch_id)
+                .bind(uri)
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        insert_audit_event(
+            &mut *tx,
+            "escalate_report",
+            &id.to_string(),
+            "system",
+            serde_json::json!({
+                "reporters": report.supporter_count + 1,
+                "threshold": threshold,
+                "batch_id": batch.map(|(batch_id, _, _)| batch_id),
+            }),
+        )
+        .await?;
+        let delivery = match delivery(&report) {
+            Some((target, payload)) => Some(
+                insert_failed_delivery(&mut *tx, &target, &payload, "not delivered yet", 0).await?,
+            ),
+            None => None,
+        };
+        tx.commit().await?;
+        Ok(Some((report, delivery)))
+    }
+
+    /// Resolve a user report. Its supporters are covered by the same
+    /// resolution.
+    pub async fn resolve_report(
+        &self,
+        id: i32,
+        status: &str,
+        admin_notes: Option<&str>,
+        resolved_by: &str,
+    ) -> Result<Option<UserReport>, sqlx::Error> {
+        sqlx::query_as::<_, UserReport>(&format!(
+            r#"
+            UPDATE user_reports
+            SET status = $1, admin_notes = $2, resolved_by = $3, resolved_at = NOW(), updated_at = NOW()
+            WHERE id = $4
+            RETURNING {REPORT_COLUMNS}
+            "#
+        ))
+        .bind(status)
+        .bind(admin_notes)
+        .bind(resolved_by)
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await
+    }
+
+    /// Store `label` for a report, unless the same label is already in force,
+    /// and resolve the report with it. The label, the resolution and the
+    /// `label_report` audit entry are written in one transaction; if the
+    /// report was resolved or dismissed in the meantime nothing is written
+    /// and `None` is returned. `admin_notes` defaults to naming the label.
+    pub async fn resolve_report_with_label(
+        &self,
+        id: i32,
+        label: &Label,
+        admin_notes: Option<&str>,
+        resolved_by: &str,
+    ) -> Result<Option<(UserReport, StoredLabel)>, sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+        let stored = insert_label_unless_active(&mut tx, label).await?;
+        let seq = match &stored {
+            StoredLabel::Created(seq) => *seq,
+            StoredLabel::Existing(active) => active.seq,
+        };
+        let admin_notes = admin_notes
+            .map(str::to_string)
+            .unwrap_or_else(|| format!("{} label (seq {})", label.val, seq));
+
+        let Some(report) = sqlx::query_as::<_, UserReport>(&format!(
+            r#"
+            UPDATE user_reports
+            SET status = 'resolved', label_seq = $1, admin_notes = $2, resolved_by = $3,
+                resolved_at = NOW(), updated_at = NOW()
+            WHERE id = $4 AND status NOT IN ('resolved', 'dismissed')
+            RETURNING {REPORT_COLUMNS}
+            "#
+        ))
+        .bind(seq)
+        .bind(&admin_notes)
+        .bind(resolved_by)
+        .bind(id)
+        .fetch_optional(&mut *tx)
+        .await?
+        else {
+            return Ok(None);
+        };
+
+        insert_audit_event(
+            &mut *tx,
+            "label_report",
+            &id.to_string(),
+            resolved_by,
+            serde_json::json!({
+                "uri": label.uri,
+                "val": label.val,
+                "seq": seq,
+                "created": matches!(stored, StoredLabel::Created(_)),
+                "admin_notes": admin_notes,
+            }),
+        )
+        .await?;
+        tx.commit().await?;
+        Ok(Some((report, stored)))
+    }
+
+    /// Close the open reports among `ids` with the same status and notes, in
+    /// one transaction that also writes a `resolve_report` audit entry for
+    /// each. Reports already resolved or dismissed are left alone. If any id
+    /// doesn't exist, nothing is changed.
+    pub async fn resolve_reports(
+        &self,
+        ids: &[i32],
+        status: &str,
+        admin_notes: Option<&str>,
+        resolved_by: &str,
+        actor: &str,
+    ) -> Result<BulkReportResolution, sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+
+        let found: Vec<(i32, String)> = sqlx::query_as(
+            "SELECT id, status FROM user_reports WHERE id = ANY($1) ORDER BY id FOR UPDATE",
+        )
+        .bind(ids)
+        .fetch_all(&mut *tx)
+        .await?;
+        let missing: Vec<i32> = ids
+            .iter()
+            .copied()
+            .filter(|id| !found.iter().any(|(found, _)| found == id))
+            .collect();
+        if !missing.is_empty() {
+            return Ok(BulkReportResolution::Missing(missing));
+        }
+        let (already_closed, open): (Vec<_>, Vec<_>) = found
+            .into_iter()
+            .partition(|(_, current)| current == "resolved" || current == "dismissed");
+        let open: Vec<i32> = open.into_iter().map(|(id, _)| id).collect();
+
+        let resolved = sqlx::query_as::<_, UserReport>(&format!(
+            r#"
+            UPDATE user_reports
+            SET status = $1, admin_notes = $2, resolved_by = $3, resolved_at = NOW(), updated_at = NOW()
+            WHERE id = ANY($4)
+            RETURNING {REPORT_COLUMNS}
+            "#
+        ))
+        .bind(status)
+        .bind(admin_notes)
+        .bind(resolved_by)
+        .bind(&open)
+        .fetch_all(&mut *tx)
+        .await?;
+
+        for report in &resolved {
+            insert_audit_event(
+                &mut *tx,
+                "resolve_report",
+                &report.id.to_string(),
+                actor,
+                serde_json::json!({
+                    "status": status,
+                    "admin_notes": admin_notes,
+                    "resolved_by": resolved_by,
+                    "supporters": report.supporter_count,
+                    "bulk": true,
+                }),
+            )
+            .await?;
+        }
+
+        tx.commit().await?;
+        Ok(BulkReportResolution::Closed {
+            resolved,
+            already_closed,
+        })
+    }
+
+    /// DIDs of the reporters who joined a report as supporters, oldest first.
+    pub async fn get_report_supporters(&self, id: i32) -> Result<Vec<String>, sqlx::Error> {
+        sqlx::query_scalar::<_, String>(
+            "SELECT reporter_did FROM report_supporters WHERE report_id = $1 ORDER BY created_at",
+        )
+        .bind(id)
+        .fetch_all(&self.pool)
+        .await
+    }
+
+    /// Mark a closed report's resolution callback as pending, leased to the
+    /// caller for `lease`.
+    ///
+    /// Returns false if the report already had a callback, so resolving it
+    /// again doesn't notify the reporters twice.
+    pub async fn start_report_callback(
+        &self,
+        id: i32,
+        lease: std::time::Duration,
+    ) -> Result<bool, sqlx::Error> {
+        let started = sqlx::query_scalar::<_, i32>(
+            r#"
+            UPDATE user_reports
+            SET callback_status = 'pending',
+                callback_lease_until = NOW() + make_interval(secs => $2)
+            WHERE id = $1 AND callback_status IS NULL
+            RETURNING id
+            "#,
+        )
+        .bind(id)
+        .bind(lease.as_secs_f64())
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(started.is_some())
+    }
+
+    /// Record the outcome of one resolution callback attempt. A callback
+    /// that's still pending has its lease renewed for `lease`; a finished
+    /// one gives the lease up.
+    pub async fn record_report_callback_attempt(
+        &self,
+        id: i32,
+        status: &str,
+        error: Option<&str>,
+        lease: std::time::Duration,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            UPDATE user_reports
+            SET callback_status = $2,
+                callback_last_error = $3,
+                callback_delivered_at = CASE WHEN $2 = 'sent' THEN NOW() END,
+                callback_lease_until = CASE
+                    WHEN $2 = 'pending' THEN NOW() + make_interval(secs => $4)
+                END
+            WHERE id = $1
+            "#,
+        )
+        .bind(id)
+        .bind(status)
+        .bind(error)
+        .bind(lease.as_secs_f64())
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Claim closed reports whose resolution callback is pending and not
+    /// leased to anyone, e.g. because the replica delivering it stopped,
+    /// leasing them to the caller for `lease`.
+    ///
+    /// Rows are locked with `SKIP LOCKED`, so replicas starting together
+    /// claim disjoint sets instead of both delivering.
+    pub async fn claim_pending_report_callbacks(
+        &self,
+        lease: std::time::Duration,
+    ) -> Result<Vec<UserReport>, sqlx::Error> {
+        sqlx::query_as::<_, UserReport>(&format!(
+            r#"
+            UPDATE user_reports
+            SET callback_lease_until = NOW() + make_interval(secs => $1)
+            WHERE id IN (
+                SELECT id FROM user_reports
+                WHERE callback_status = 'pending'
+                  AND (callback_lease_until IS NULL OR callback_lease_until < NOW())
+                ORDER BY resolved_at
+                FOR UPDATE SKIP LOCKED
+            )
+            RETURNING {REPORT_COLUMNS}
+            "#
+        ))
+        .bind(lease.as_secs_f64())
+        .fetch_all(&self.pool)
+        .await
+    }
+
+    /// Count user reports for each (reason, status) pair that has any.
+    pub async fn report_counts(&self) -> Result<Vec<ReportCount>, sqlx::Error> {
+        sqlx::query_as::<_, ReportCount>(
+            r#"
+            SELECT reason, status, COUNT(*) AS count
+            FROM user_reports
+            GROUP BY reason, status
+            ORDER BY reason, status
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await
+    }
+
+    /// A reporter's report counts, the reports they supported, and their
+    /// `recent` newest reports (none when it's 0).
+    pub async fn get_reporter_reports(
+        &self,
+        reporter_did: &str,
+        recent: i64,
+    ) -> Result<ReporterReports, sqlx::Error> {
+        let counts = sqlx::query_as::<_, ReportCount>(
+            r#"
+            SELECT reason, status, COUNT(*) AS count
+            FROM user_reports
+            WHERE reporter_did = $1
+            GROUP BY reason, status
+            ORDER BY reason, status
+            "#,
+        )
+        .bind(reporter_did)
+        .fetch_all(&self.pool)
+        .await?;
+        let supported = sqlx::query_scalar::<_, i64>(
+            "SELECT COUNT(*) FROM report_supporters WHERE reporter_did = $1",
+        )
+        .bind(reporter_did)
+        .fetch_one(&self.pool)
+        .await?;
+        let recent = if recent > 0 {
+            sqlx::query_as::<_, UserReport>(&format!(
+                r#"
+                SELECT {REPORT_COLUMNS} FROM user_reports
+                WHERE reporter_did = $1
+                ORDER BY created_at DESC, id DESC
+                LIMIT $2
+                "#
+            ))
+            .bind(reporter_did)
+            .bind(recent)
+            .fetch_all(&self.pool)
+            .await?
+        } else {
+            Vec::new()
+        };
+
+        Ok(ReporterReports {
+            counts,
+            supported,
+            recent,
+        })
+    }
+
+    /// Reporters and supporters still stored as DIDs rather than hashes.
+    pub async fn list_unhashed_reporters(&self) -> Result<Vec<String>, sqlx::Error> {
+        sqlx::query_scalar::<_, String>(
+            r#"
+            SELECT reporter_did FROM user_reports WHERE reporter_did LIKE 'did:%'
+            UNION
+            SELECT reporter_did FROM report_supporters WHERE reporter_did LIKE 'did:%'
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await
+    }
+
+    /// Replace each `(did, hash)` pair's DID with the hash, wherever it's
+    /// stored as a reporter or supporter, and drop the handles stored with
+    /// the replaced reports. Returns the reports and supporter entries
+    /// changed. Runs in one transaction, so a failure leaves nothing half
+    /// hashed.
+    pub async fn replace_reporters(
+        &self,
+        mapping: &[(String, String)],
+    ) -> Result<(u64, u64), sqlx::Error> {
+        let (dids, hashes): (Vec<&str>, Vec<&str>) = mapping
+            .iter()
+            .map(|(did, hash)| (did.as_str(), hash.as_str()))
+            .unzip();
+        let mut tx = self.pool.begin().await?;
+        let reports = sqlx::query(
+            r#"
+            UPDATE user_reports r
+            SET reporter_did = m.hash, reporter_handle = NULL
+            FROM unnest($1::text[], $2::text[]) AS m(did, hash)
+            WHERE r.reporter_did = m.did
+            "#,
+        )
+        .bind(&dids)
+        .bind(&hashes)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+        let supporters = sqlx::query(
+            r#"
+            UPDATE report_supporters s
+            SET reporter_did = m.hash
+            FROM unnest($1::text[], $2::text[]) AS m(did, hash)
+            WHERE s.reporter_did = m.did
+            "#,
+        )
+        .bind(&dids)
+        .bind(&hashes)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+        tx.commit().await?;
+        Ok((reports, supporters))
+    }
+
+    // -------------------------------------------------------------------------
+    // Dashboard stats
+    // -------------------------------------------------------------------------
+
+    /// Compute the admin dashboard statistics.
+    ///
+    /// Flag counts follow the same rules as the flags list: positive,
+    /// unexpired copyright labels, one per URI, resolved once the latest is
+    /// negated.
+    pub async fn get_dashboard_stats(&self) -> Result<DashboardStats, sqlx::Error> {
+        let counts_query = format!(
+            r#"
+            SELECT
+                (SELECT COUNT(*) FROM labels l
+                 WHERE l.val = 'copyright-violation' AND l.neg = false
+                   AND (l.exp IS NULL OR l.exp > NOW()) AND {latest}
+                   AND NOT {resolved}) AS pending,
+                (SELECT COUNT(*) FROM labels l
+                 WHERE l.val = 'copyright-violation' AND l.neg = false
+                   AND (l.exp IS NULL OR l.exp > NOW()) AND {latest}
+                   AND {resolved}) AS resolved,
+                (SELECT COUNT(*) FROM user_reports WHERE status IN ('open', 'escalated')) AS open_reports,
+                (SELECT COUNT(*) FROM sensitive_images
+                 WHERE flagged_at > NOW() - INTERVAL '7 days') AS sensitive_images_this_week
+            "#,
+            latest = FLAG_LATEST_SQL,
+            resolved = FLAG_RESOLVED_SQL
+        );
+        let counts: (i64, i64, i64, i64) =
+            sqlx::query_as(&counts_query).fetch_one(&self.pool).await?;
+
+        // every day in the window gets a row, including days with no flags
+        let flags_per_day: Vec<(NaiveDate, i64)> = sqlx::query_as(&format!(
+            r#"
+            SELECT d.day::date, COUNT(l.seq)
+            FROM generate_series(
+                date_trunc('day', NOW()) - INTERVAL '{} days',
+                date_trunc('day', NOW()),
+                INTERVAL '1 day'
+            ) AS d(day)
+            LEFT JOIN labels l
+              ON l.val = 'copyright-violation' AND l.neg = false
+             AND l.cts >= d.day AND l.cts < d.day + INTERVAL '1 day'
+            GROUP BY d.day
+            ORDER BY d.day
+            "#,
+            STATS_DAYS - 1
+        ))
+        .fetch_all(&self.pool)
+        .await?;
+
+        let resolution_reasons: Vec<(String, i64)> = sqlx::query_as(
+            r#"
+            SELECT resolution_reason, COUNT(*)
+            FROM label_context
+            WHERE resolution_reason IS NOT NULL
+            GROUP BY resolution_reason
+            ORDER BY COUNT(*) DESC, resolution_reason
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(DashboardStats {
+            pending_flags: counts.0,
+            resolved_flags: counts.1,
+            flags_per_day: flags_per_day
+                .into_iter()
+                .map(|(date, count)| DailyCount { date, count })
+                .collect(),
+            resolution_reasons: resolution_reasons
+                .into_iter()
+                .map(|(reason, count)| ReasonCount { reason, count })
+                .collect(),
+            open_reports: counts.2,
+            sensitive_images_this_week: counts.3,
+        })
+    }
+
+    /// Counts for the dashboard summary header, in one query.
+    pub async fn get_summary_counts(&self) -> Result<SummaryCounts, sqlx::Error> {
+        let query = format!(
+            r#"
+            SELECT
+                (SELECT COUNT(*) FROM labels l
+                 WHERE l.val = 'copyright-violation' AND l.neg = false
+                   AND (l.exp IS NULL OR l.exp > NOW()) AND {latest}
+                   AND NOT {resolved}) AS pending_flags,
+                (SELECT COUNT(*) FROM user_reports WHERE status IN ('open', 'escalated')) AS open_reports,
+                (SELECT COUNT(*) FROM image_scans
+                 WHERE review_state = 'needs_review') AS image_review_queue,
+                (SELECT COUNT(DISTINCT uri) FROM labels
+                 WHERE val = 'copyright-violation' AND neg = true
+                   AND created_at >= date_trunc('day', NOW())) AS resolved_today
+            "#,
+            latest = FLAG_LATEST_SQL,
+            resolved = FLAG_RESOLVED_SQL
+        );
+        sqlx::query_as::<_, SummaryCounts>(&query)
+            .fetch_one(&self.pool)
+            .await
+    }
+
+    /// Report statistics for the days `from` through `to`, inclusive.
+    ///
+    /// Reason and target type counts cover reports filed in the window; the
+    /// dismissal rate and median time to resolution cover reports closed in
+    /// it, wherever they were filed.
+    pub async fn report_window_stats(
+        &self,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Result<ReportWindowStats, sqlx::Error> {
+        let filed: Vec<(String, String, i64)> = sqlx::query_as(
+            r#"
+            SELECT reason, target_type, COUNT(*)
+            FROM user_reports
+            WHERE created_at >= $1::date AND created_at < $2::date + 1
+            GROUP BY reason, target_type
+            "#,
+        )
+        .bind(from)
+        .bind(to)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let (closed, dismissed, median_resolution_secs): (i64, i64, Option<f64>) = sqlx::query_as(
+            r#"
+            SELECT
+                COUNT(*),
+                COUNT(*) FILTER (WHERE status = 'dismissed'),
+                percentile_cont(0.5) WITHIN GROUP (
+                    ORDER BY EXTRACT(EPOCH FROM resolved_at - created_at)::float8
+                )
+            FROM user_reports
+            WHERE status IN ('resolved', 'dismissed')
+              AND resolved_at >= $1::date AND resolved_at < $2::date + 1
+            "#,
+        )
+        .bind(from)
+        .bind(to)
+        .fetch_one(&self.pool)
+        .await?;
+
+        // every day in the window gets a row, including quiet ones
+        let per_day: Vec<(NaiveDate, i64, i64)> = sqlx::query_as(
+            r#"
+            SELECT d.day::date,
+                (SELECT COUNT(*) FROM user_reports r
+                 WHERE r.created_at >= d.day AND r.created_at < d.day + INTERVAL '1 day'),
+                (SELECT COUNT(*) FROM user_reports r
+                 WHERE r.status IN ('resolved', 'dismissed')
+                   AND r.resolved_at >= d.day AND r.resolved_at < d.day + INTERVAL '1 day')
+            FROM generate_series($1::date::timestamptz, $2::date::timestamptz, INTERVAL '1 day')
+                AS d(day)
+            ORDER BY d.day
+            "#,
+        )
+        .bind(from)
+        .bind(to)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut stats = ReportWindowStats {
+            from,
+            to,
+            closed,
+            dismissed,
+            dismissal_rate: (closed > 0).then(|| dismissed as f64 / closed as f64),
+            median_resolution_secs,
+            per_day: per_day
+                .into_iter()
+                .map(|(date, filed, closed)| ReportDay {
+                    date,
+                    filed,
+                    closed,
+                })
+                .collect(),
+            ..Default::default()
+        };
+        for (reason, target_type, count) in filed {
+            stats.filed += count;
+            *stats.by_reason.entry(reason).or_default() += count;
+            *stats.by_target_type.entry(target_type).or_default() += count;
+        }
+        Ok(stats)
+    }
+
+    /// Open a consistent snapshot for `/admin/export`: a read-only
+    /// `REPEATABLE READ` transaction, so every page sees the database as of
+    /// the first read, however long the download takes.
+    pub async fn export_snapshot(&self) -> Result<ExportSnapshot, sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+        sqlx::query("SET TRANSACTION ISOLATION LEVEL REPEATABLE READ, READ ONLY")
+            .execute(&mut *tx)
+            .await?;
+        Ok(ExportSnapshot { tx })
+    }
+
+    /// Write exported records back, in one transaction.
+    ///
+    /// Safe to repeat: labels are keyed by seq and skipped if already
+    /// present (a stored label is never rewritten), while contexts (by URI),
+    /// sensitive images and reports (by id) are overwritten with the exported
+    /// values. Sequences are then moved past the imported ids so new rows
+    /// don't collide with them. Header records are ignored.
+    pub async fn import_records(
+        &self,
+        records: &[ExportRecord],
+    ) -> Result<ImportCounts, sqlx::Error> {
+        let mut counts = ImportCounts::default();
+        let mut tx = self.pool.begin().await?;
+        for record in records {
+            match record {
+                ExportRecord::Header { .. } => {}
+                ExportRecord::Label(label) => {
+                    sqlx::query(
+                        r#"
+                        INSERT INTO labels (seq, src, uri, cid, val, neg, cts, exp, sig, created_at)
+                        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, decode($9, 'hex'), $10)
+                        ON CONFLICT (seq) DO NOTHING
+                        "#,
+                    )
+                    .bind(label.seq)
+                    .bind(&label.src)
+                    .bind(&label.uri)
+                    .bind(&label.cid)
+                    .bind(&label.val)
+                    .bind(label.neg)
+                    .bind(label.cts)
+                    .bind(label.exp)
+                    .bind(&label.sig)
+                    .bind(label.created_at)
+                    .execute(&mut *tx)
+                    .await?;
+                    counts.labels += 1;
+                }
+                ExportRecord::LabelContext(context) => {
+                    sqlx::query(
+                        r#"
+                        INSERT INTO label_context (uri, track_id, track_title, artist_handle,
+                            artist_did, highest_score, matches, resolution_reason, resolution_notes,
+                            audio_url, rescanned_at, confirmed_by, confirmed_at, created_at)
+                        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14)
+                        ON CONFLICT (uri) DO UPDATE SET
+                            track_id = EXCLUDED.track_id,
+                            track_title = EXCLUDED.track_title,
+                            artist_handle = EXCLUDED.artist_handle,
+                            artist_did = EXCLUDED.artist_did,
+                            highest_score = EXCLUDED.highest_score,
+                            matches = EXCLUDED.matches,
+                            resolution_reason = EXCLUDED.resolution_reason,
+                            resolution_notes = EXCLUDED.resolution_notes,
+                            audio_url = EXCLUDED.audio_url,
+                            rescanned_at = EXCLUDED.rescanned_at,
+                            confirmed_by = EXCLUDED.confirmed_by,
+                            confirmed_at = EXCLUDED.confirmed_at,
+                            created_at = EXCLUDED.created_at
+                        "#,
+                    )
+                    .bind(&context.uri)
+                    .bind(context.track_id)
+                    .bind(&context.track_title)
+                    .bind(&context.artist_handle)
+                    .bind(&context.artist_did)
+                    .bind(context.highest_score)
+                    .bind(&context.matches)
+                    .bind(&context.resolution_reason)
+                    .bind(&context.resolution_notes)
+                    .bind(&context.audio_url)
+                    .bind(context.rescanned_at)
+                    .bind(&context.confirmed_by)
+                    .bind(context.confirmed_at)
+                    .bind(context.created_at)
+                    .execute(&mut *tx)
+                    .await?;
+                    counts.label_contexts += 1;
+                }
+                ExportRecord::SensitiveImage(image) => {
+                    sqlx::query(
+                        r#"
+                        INSERT INTO sensitive_images (id, image_id, url, reason, flagged_at,
+                            flagged_by, severity, violated_categories)
+                        VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+                        ON CONFLICT (id) DO UPDATE SET
+                            image_id = EXCLUDED.image_id,
+                            url = EXCLUDED.url,
+                            reason = EXCLUDED.reason,
+                            flagged_at = EXCLUDED.flagged_at,
+                            flagged_by = EXCLUDED.flagged_by,
+                            severity = EXCLUDED.severity,
+                            violated_categories = EXCLUDED.violated_categories
+                        "#,
+                    )
+                    .bind(image.id)
+                    .bind(&image.image_id)
+                    .bind(&image.url)
+                    .bind(&image.reason)
+                    .bind(image.flagged_at)
+                    .bind(&image.flagged_by)
+                    .bind(&image.severity)
+                    .bind(&image.violated_categories)
+                    .execute(&mut *tx)
+                    .await?;
+                    counts.sensitive_images += 1;
+                }
+                ExportRecord::Report(exported) => {
+                    let report = &exported.report;
+                    sqlx::query(
+                        r#"
+                        INSERT INTO user_reports (id, reporter_did, reporter_handle, target_type,
+                            target_id, target_name, target_url, target_uri, reason, description,
+                            screenshot_url, screenshot_key, status, admin_notes, resolved_by,
+                            created_at, updated_at, resolved_at, label_seq)
+                        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15,
+                            $16, $17, $18, $19)
+                        ON CONFLICT (id) DO UPDATE SET
+                            reporter_did = EXCLUDED.reporter_did,
+                            reporter_handle = EXCLUDED.reporter_handle,
+                            target_type = EXCLUDED.target_type,
+                            target_id = EXCLUDED.target_id,
+                            target_name = EXCLUDED.target_name,
+                            target_url = EXCLUDED.target_url,
+                            target_uri = EXCLUDED.target_uri,
+                            reason = EXCLUDED.reason,
+                            description = EXCLUDED.description,
+                            screenshot_url = EXCLUDED.screenshot_url,
+                            screenshot_key = EXCLUDED.screenshot_key,
+                            status = EXCLUDED.status,
+                            admin_notes = EXCLUDED.admin_notes,
+                            resolved_by = EXCLUDED.resolved_by,
+                            created_at = EXCLUDED.created_at,
+                            updated_at = EXCLUDED.updated_at,
+                            resolved_at = EXCLUDED.resolved_at,
+                            label_seq = EXCLUDED.label_seq
+                        "#,
+                    )
+                    .bind(report.id)
+                    .bind(&report.reporter_did)
+                    .bind(&report.reporter_handle)
+                    .bind(&report.target_type)
+                    .bind(&report.target_id)
+                    .bind(&report.target_name)
+                    .bind(&report.target_url)
+                    .bind(&report.target_uri)
+                    .bind(&report.reason)
+                    .bind(&report.description)
+                    .bind(&report.screenshot_url)
+                    .bind(&report.screenshot_key)
+                    .bind(&report.status)
+                    .bind(&report.admin_notes)
+                    .bind(&report.resolved_by)
+                    .bind(report.created_at)
+                    .bind(report.updated_at)
+                    .bind(report.resolved_at)
+                    .bind(report.label_seq)
+                    .execute(&mut *tx)
+                    .await?;
+                    sqlx::query(
+                        "INSERT INTO report_supporters (report_id, reporter_did) \
+                         SELECT $1, unnest($2::text[]) ON CONFLICT DO NOTHING",
+                    )
+                    .bind(report.id)
+                    .bind(&exported.supporters)
+                    .execute(&mut *tx)
+                    .await?;
+                    counts.reports += 1;
+                }
+            }
+        }
+        advance_sequence(&mut *tx, "labels", "seq").await?;
+        advance_sequence(&mut *tx, "sensitive_images", "id").await?;
+        advance_sequence(&mut *tx, "user_reports", "id").await?;
+        tx.commit().await?;
+        Ok(counts)
+    }
+}
+
+/// Number of days covered by the dashboard's flags-per-day series.
+pub const STATS_DAYS: i64 = 30;
+
+/// Aggregate counts for the admin dashboard header.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DashboardStats {
+    pub pending_flags: i64,
+    pub resolved_flags: i64,
+    /// Copyright flags created per day, oldest first, covering the last
+    /// [`STATS_DAYS`] days (today included).
+    pub flags_per_day: Vec<DailyCount>,
+    /// Resolved flags grouped by resolution reason, most common first.
+    pub resolution_reasons: Vec<ReasonCount>,
+    pub open_reports: i64,
+    /// Images added to sensitive_images in the last 7 days.
+    pub sensitive_images_this_week: i64,
+}
+
+/// Counts for the dashboard summary header.
+#[derive(Debug, Clone, FromRow)]
+pub struct SummaryCounts {
+    pub pending_flags: i64,
+    pub open_reports: i64,
+    /// Image scans waiting for review.
+    pub image_review_queue: i64,
+    /// Copyright flags negated since midnight UTC.
+    pub resolved_today: i64,
+}
+
+/// Report statistics over a date window, for trust-and-safety reporting.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ReportWindowStats {
+    /// First day of the window.
+    pub from: NaiveDate,
+    /// Last day of the window, included.
+    pub to: NaiveDate,
+    /// Reports filed in the window.
+    pub filed: i64,
+    pub by_reason: BTreeMap<String, i64>,
+    pub by_target_type: BTreeMap<String, i64>,
+    /// Reports resolved or dismissed in the window.
+    pub closed: i64,
+    pub dismissed: i64,
+    /// Share of closed reports that were dismissed; `None` if none closed.
+    pub dismissal_rate: Option<f64>,
+    /// Median seconds from filing to closing, over reports closed in the
+    /// window.
+    pub median_resolution_secs: Option<f64>,
+    /// Reports filed and closed per day, oldest first.
+    pub per_day: Vec<ReportDay>,
+}
+
+/// Reports filed and closed on a single day.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReportDay {
+    pub date: NaiveDate,
+    pub filed: i64,
+    pub closed: i64,
+}
+
+/// Count of events on a single day (UTC).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DailyCount {
+    pub date: NaiveDate,
+    pub count: i64,
+}
+
+/// Count of resolutions with a given reason.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReasonCount {
+    pub reason: String,
+    pub count: i64,
+}
+
+/// Statistics for image scans.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImageScanStats {
+    pub total: i64,
+    pub safe: i64,
+    pub flagged: i64,
+}
+
+/// One line of an `/admin/export` snapshot. Serialized with a `kind` tag, so
+/// a snapshot is newline-delimited JSON that can be filtered with `jq`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ExportRecord {
+    /// First line of every snapshot.
+    Header {
+        version: u32,
+        exported_at: DateTime<Utc>,
+    },
+    Label(ExportedLabel),
+    LabelContext(ExportedLabelContext),
+    SensitiveImage(SensitiveImageRow),
+    Report(ExportedReport),
+}
+
+/// A stored label, as exported.
+#[derive(Debug, Clone, PartialEq, FromRow, Serialize, Deserialize)]
+pub struct ExportedLabel {
+    pub seq: i64,
+    pub src: String,
+    pub uri: String,
+    pub cid: Option<String>,
+    pub val: String,
+    pub neg: bool,
+    pub cts: DateTime<Utc>,
+    pub exp: Option<DateTime<Utc>>,
+    /// Signature bytes, hex-encoded.
+    pub sig: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// A `label_context` row, resolution included, as exported. `matches` is
+/// kept as stored rather than parsed into `CopyrightMatch`.
+#[derive(Debug, Clone, PartialEq, FromRow, Serialize, Deserialize)]
+pub struct ExportedLabelContext {
+    pub uri: String,
+    pub track_id: Option<i64>,
+    pub track_title: Option<String>,
+    pub artist_handle: Option<String>,
+    pub artist_did: Option<String>,
+    pub highest_score: Option<f64>,
+    pub matches: Option<serde_json::Value>,
+    pub resolution_reason: Option<String>,
+    pub resolution_notes: Option<String>,
+    pub audio_url: Option<String>,
+    pub rescanned_at: Option<DateTime<Utc>>,
+    pub confirmed_by: Option<String>,
+    pub confirmed_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// A user report with the DIDs of its supporters, as exported. Callback
+/// delivery state isn't exported.
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct ExportedReport {
+    #[sqlx(flatten)]
+    #[serde(flatten)]
+    pub report: UserReport,
+    #[serde(default)]
+    pub supporters: Vec<String>,
+}
+
+/// Records written by one `/admin/import` batch, by kind.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct ImportCounts {
+    pub labels: u64,
+    pub label_contexts: u64,
+    pub sensitive_images: u64,
+    pub reports: u64,
+}
+
+impl ImportCounts {
+    pub fn add(&mut self, other: ImportCounts) {
+        self.labels += other.labels;
+        self.label_contexts += other.label_contexts;
+        self.sensitive_images += other.sensitive_images;
+        self.reports += other.reports;
+    }
+}
+
+/// Move a serial column's sequence up to the largest value in the table.
+/// Never moves it back: label seqs already handed to subscribers must not be
+/// issued again.
+async fn advance_sequence<'e>(
+    executor: impl PgExecutor<'e>,
+    table: &str,
+    column: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(&format!(
+        "SELECT setval(pg_get_serial_sequence('{table}', '{column}'), m) \
+         FROM (SELECT MAX({column}) AS m FROM {table}) t \
+         WHERE m > COALESCE(pg_sequence_last_value(\
+             pg_get_serial_sequence('{table}', '{column}')::regclass), 0)"
+    ))
+    .execute(executor)
+    .await?;
+    Ok(())
+}
+
+impl LabelRow {
+    /// Convert database row to Label struct.
+    pub fn to_label(&self) -> Label {
+        Label {
+            ver: Some(1),
+            src: self.src.clone(),
+            uri: self.uri.clone(),
+            cid: self.cid.clone(),
+            val: self.val.clone(),
+            neg: if self.neg { Some(true) } else { None },
+            cts: self.cts.format("%Y-%m-%dT%H:%M:%S%.3fZ").to_string(),
+            exp: self
+                .exp
+                .map(|e| e.format("%Y-%m-%dT%H:%M:%S%.3fZ").to_string()),
+            sig: Some(bytes::Bytes::from(self.sig.clone())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolution_reason_from_key() {
+        assert_eq!(
+            ResolutionReason::from_key("original_artist"),
+            ResolutionReason::OriginalArtist
+        );
+        assert_eq!(
+            ResolutionReason::from_key("licensed"),
+            ResolutionReason::Licensed
+        );
+        assert_eq!(
+            ResolutionReason::from_key("fingerprint_noise"),
+            ResolutionReason::FingerprintNoise
+        );
+        assert_eq!(
+            ResolutionReason::from_key("cover_version"),
+            ResolutionReason::CoverVersion
+        );
+        assert_eq!(ResolutionReason::from_key("other"), ResolutionReason::Other);
+        assert_eq!(
+            ResolutionReason::from_key("fair_use"),
+            ResolutionReason::Custom("fair_use".to_string())
+        );
+    }
+
+    #[test]
+    fn test_custom_resolution_reason_round_trips() {
+        let reason = ResolutionReason::from_key("distributor_error");
+        assert_eq!(reason.as_str(), "distributor_error");
+        assert_eq!(reason.label(), "distributor_error");
+        assert!(!reason.requires_notes());
+        let json = serde_json::to_value(&reason).unwrap();
+        assert_eq!(json, serde_json::json!("distributor_error"));
+        assert_eq!(
+            serde_json::from_value::<ResolutionReason>(json).unwrap(),
+            reason
+        );
+    }
+
+    #[test]
     fn test_resolution_reason_labels() {
         assert_eq!(ResolutionReason::OriginalArtist.label(), "original artist");
         assert_eq!(ResolutionReason::Licensed.label(), "licensed");
         assert_eq!(
-            ResolutionReason::FingerprintNoise.label(),
-            "fingerprint noise"
+            ResolutionReason::FingerprintNoise.label(),
+            "fingerprint noise"
+        );
+        assert_eq!(ResolutionReason::CoverVersion.label(), "cover/remix");
+        assert_eq!(ResolutionReason::Other.label(), "other");
+    }
+
+    #[test]
+    fn test_expired_labels_are_inactive() {
+        let now = Utc::now();
+        assert!(is_expired(Some(now - chrono::Duration::hours(1)), now));
+        assert!(is_expired(Some(now), now));
+        assert!(!is_expired(Some(now + chrono::Duration::hours(1)), now));
+        assert!(!is_expired(None, now));
+    }
+
+    fn flag_row(seq: i64, match_count: i64) -> FlaggedRow {
+        FlaggedRow {
+            seq,
+            uri: format!("at://did:plc:x/fm.plyr.track/{seq}"),
+            val: "copyright-violation".to_string(),
+            cts: Utc::now(),
+            match_count,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_label_query_where_filters_by_val_across_uris() {
+        let uris = vec![
+            "at://did:plc:a/fm.plyr.track/1".to_string(),
+            "at://did:plc:b/*".to_string(),
+        ];
+        let vals = vec!["copyright-violation".to_string()];
+
+        assert_eq!(
+            label_query_where(&uris, None, Some(&vals), false),
+            "WHERE (uri = $1 OR uri LIKE $2) AND val IN ($3) AND (exp IS NULL OR exp > NOW())"
+        );
+
+        // combined with sources and a cursor, placeholders follow bind order
+        let srcs = vec!["did:plc:labeler".to_string()];
+        let vals = vec!["copyright-violation".to_string(), "sensitive".to_string()];
+        assert_eq!(
+            label_query_where(&uris, Some(&srcs), Some(&vals), true),
+            "WHERE (uri = $1 OR uri LIKE $2) AND src IN ($3) AND val IN ($4, $5) \
+             AND seq > $6 AND (exp IS NULL OR exp > NOW())"
+        );
+
+        // empty vals list doesn't filter
+        assert!(!label_query_where(&uris, None, Some(&[]), false).contains("val IN"));
+    }
+
+    #[test]
+    fn test_label_query_binds_match_placeholders() {
+        let uris = vec![
+            "at://did:plc:a/fm.plyr.track/1".to_string(),
+            "at://did:plc:b/*".to_string(),
+        ];
+        let srcs = vec!["did:plc:labeler".to_string()];
+        let vals = vec!["copyright-violation".to_string(), "sensitive".to_string()];
+
+        let binds = label_query_binds(&uris, Some(&srcs), Some(&vals));
+        assert_eq!(
+            binds,
+            [
+                "at://did:plc:a/fm.plyr.track/1",
+                "at://did:plc:b/%",
+                "did:plc:labeler",
+                "copyright-violation",
+                "sensitive",
+            ]
+        );
+
+        // the page query and the count share these binds, so each string
+        // placeholder gets exactly one value and the cursor comes last
+        let where_clause = label_query_where(&uris, Some(&srcs), Some(&vals), true);
+        assert!(where_clause.contains(&format!("${}", binds.len())));
+        assert!(where_clause.contains(&format!("seq > ${}", binds.len() + 1)));
+        assert!(!where_clause.contains(&format!("${}", binds.len() + 2)));
+
+        assert_eq!(label_query_binds(&uris, None, Some(&[])).len(), 2);
+    }
+
+    #[test]
+    fn test_next_flags_cursor() {
+        // fetched limit + 1: there is another page, cursor is the last kept seq
+        let mut rows: Vec<FlaggedRow> = [10, 9, 8, 7].into_iter().map(|s| flag_row(s, 0)).collect();
+        assert_eq!(
+            next_flags_cursor(&mut rows, 3, FlagSort::Newest),
+            Some("8".to_string())
+        );
+        assert_eq!(rows.len(), 3);
+
+        // last page
+        let mut rows: Vec<FlaggedRow> = [2, 1].into_iter().map(|s| flag_row(s, 0)).collect();
+        assert_eq!(next_flags_cursor(&mut rows, 3, FlagSort::Newest), None);
+        assert_eq!(rows.len(), 2);
+
+        // most_matches carries the match count so the next page resumes mid-tie
+        let mut rows = vec![flag_row(5, 4), flag_row(9, 2), flag_row(3, 2)];
+        let cursor = next_flags_cursor(&mut rows, 2, FlagSort::MostMatches).unwrap();
+        assert_eq!(cursor, "2:9");
+        assert_eq!(
+            FlagsCursor::parse(&cursor, FlagSort::MostMatches),
+            Some(FlagsCursor {
+                match_count: 2,
+                seq: 9
+            })
+        );
+        assert_eq!(FlagsCursor::parse("9", FlagSort::MostMatches), None);
+    }
+
+    #[test]
+    fn test_most_matches_sorts_null_matches_last() {
+        // flags in SQL order for `most_matches`: match count desc, then seq desc.
+        // FlagsCursor's ordering is the keyset comparison, so sorting cursors
+        // descending must reproduce it.
+        let mut keys = [
+            FlagsCursor {
+                match_count: NO_MATCHES,
+                seq: 40,
+            },
+            FlagsCursor {
+                match_count: 0,
+                seq: 10,
+            },
+            FlagsCursor {
+                match_count: 3,
+                seq: 20,
+            },
+            FlagsCursor {
+                match_count: NO_MATCHES,
+                seq: 30,
+            },
+        ];
+        keys.sort_by(|a, b| b.cmp(a));
+        let seqs: Vec<i64> = keys.iter().map(|k| k.seq).collect();
+        assert_eq!(seqs, vec![20, 10, 40, 30]);
+
+        assert!(MATCH_COUNT_SQL.ends_with(&format!("{})::BIGINT", NO_MATCHES)));
+        assert_eq!(
+            FlagSort::MostMatches.order_by(),
+            format!("{} DESC, l.seq DESC", MATCH_COUNT_SQL)
+        );
+    }
+
+    #[test]
+    fn test_flag_sort_parse() {
+        assert_eq!(FlagSort::parse("oldest"), FlagSort::Oldest);
+        assert_eq!(FlagSort::parse("most_matches"), FlagSort::MostMatches);
+        assert_eq!(FlagSort::parse("newest"), FlagSort::Newest);
+        assert_eq!(FlagSort::parse(""), FlagSort::Newest);
+        assert_eq!(FlagSort::Oldest.after_cursor(3), "l.seq > $3");
+    }
+
+    #[test]
+    fn test_flag_artist_filter_is_case_insensitive() {
+        assert_eq!(handle_like_pattern("Knock2One"), "%knock2one%");
+        // LIKE wildcards in the search are literal
+        assert_eq!(handle_like_pattern("a_b%"), "%a\\_b\\%%");
+
+        let query = FlagsPageQuery {
+            artist: Some("Knock2One".to_string()),
+            ..Default::default()
+        };
+        let (conditions, binds) = query.conditions();
+        assert!(conditions.contains(&"LOWER(c.artist_handle) LIKE $1 ESCAPE '\\'".to_string()));
+        assert_eq!(binds, vec!["%knock2one%"]);
+    }
+
+    #[test]
+    fn test_flags_without_context_excluded_only_when_filtering() {
+        // no filter: nothing touches label_context, so the LEFT JOIN keeps
+        // flags that have no context row
+        let (conditions, binds) = FlagsPageQuery::default().conditions();
+        assert!(conditions.iter().all(|c| !c.contains("c.")));
+        assert!(binds.is_empty());
+
+        // filtering on the artist requires a matching context row
+        let query = FlagsPageQuery {
+            artist: Some("knock".to_string()),
+            did: Some("did:plc:abc".to_string()),
+            ..Default::default()
+        };
+        let (conditions, binds) = query.conditions();
+        assert!(conditions.contains(&"c.artist_did = $2".to_string()));
+        assert_eq!(binds, vec!["%knock%", "did:plc:abc"]);
+    }
+
+    #[test]
+    fn test_flag_search_filter() {
+        let query = FlagsPageQuery {
+            q: Some("Drake 100%".to_string()),
+            ..Default::default()
+        };
+        let (conditions, binds) = query.conditions();
+        let search = conditions.last().unwrap();
+        assert!(search.starts_with("(COALESCE(c.track_title, '')"));
+        assert!(search.contains("jsonb_path_query_array(c.matches, '$[*].title')"));
+        assert!(search.contains("jsonb_path_query_array(c.matches, '$[*].artist')"));
+        assert!(search.ends_with("ILIKE $1 ESCAPE '\\'"));
+        assert_eq!(binds, vec!["%drake 100\\%%"]);
+
+        // the indexed expression is the same one, unqualified
+        assert_eq!(
+            flag_search_document("c.").replace("c.", ""),
+            flag_search_document("")
+        );
+    }
+
+    #[test]
+    fn test_report_filter_conditions() {
+        let filter = ReportFilter {
+            status: Some("open"),
+            target_id: Some("4821"),
+            q: Some("50% off"),
+            ..Default::default()
+        };
+        let (conditions, binds) = filter.conditions();
+        assert_eq!(conditions[..2], ["status = $1", "target_id = $2"]);
+        assert_eq!(
+            conditions[2],
+            format!("{REPORT_SEARCH_DOCUMENT} ILIKE $3 ESCAPE '\\'")
+        );
+        assert_eq!(binds, vec!["open", "4821", "%50\\% off%"]);
+
+        let (conditions, binds) = ReportFilter::default().conditions();
+        assert!(conditions.is_empty() && binds.is_empty());
+    }
+
+    #[test]
+    fn test_flag_date_range_filter() {
+        let query = FlagsPageQuery {
+            did: Some("did:plc:abc".to_string()),
+            from: Some("2026-01-01T00:00:00Z".parse().unwrap()),
+            to: Some("2026-02-01T00:00:00Z".parse().unwrap()),
+            ..Default::default()
+        };
+        let (conditions, binds) = query.conditions();
+        assert!(conditions.contains(&"l.cts >= $2::timestamptz".to_string()));
+        assert!(conditions.contains(&"l.cts < $3::timestamptz".to_string()));
+        assert_eq!(
+            binds,
+            vec![
+                "did:plc:abc",
+                "2026-01-01T00:00:00+00:00",
+                "2026-02-01T00:00:00+00:00"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_sensitive_image_sort() {
+        assert_eq!(
+            SensitiveImageSort::parse("severity"),
+            SensitiveImageSort::Severity
+        );
+        assert_eq!(SensitiveImageSort::parse(""), SensitiveImageSort::Newest);
+        assert_eq!(
+            SensitiveImageSort::parse("bogus"),
+            SensitiveImageSort::Newest
+        );
+        // unscored rows (manual entries, pre-severity rows) rank below low
+        let order = SensitiveImageSort::Severity.order_by();
+        assert!(order.starts_with("CASE severity WHEN 'high' THEN 3"));
+        assert!(order.contains("ELSE 0 END DESC"));
+    }
+
+    #[test]
+    fn test_flag_status_parse() {
+        assert_eq!(FlagStatus::parse("resolved"), FlagStatus::Resolved);
+        assert_eq!(FlagStatus::parse("confirmed"), FlagStatus::Confirmed);
+        assert_eq!(FlagStatus::parse("all"), FlagStatus::All);
+        assert_eq!(FlagStatus::parse("pending"), FlagStatus::Pending);
+        assert_eq!(FlagStatus::parse("bogus"), FlagStatus::Pending);
+    }
+
+    #[test]
+    fn test_mark_out_of_cts_order() {
+        let now = Utc::now();
+        let row = |seq, minutes_ago: i64| LabelTimestampRow {
+            seq,
+            uri: "at://did:plc:x/fm.plyr.track/1".to_string(),
+            val: "copyright-violation".to_string(),
+            neg: false,
+            cts: now - chrono::Duration::minutes(minutes_ago),
+            created_at: now,
+            skew_seconds: minutes_ago * 60,
+            out_of_cts_order: false,
+        };
+
+        // seq 3 is a backfill with a cts older than seq 2
+        let mut rows = vec![row(1, 30), row(2, 10), row(3, 20), row(4, 5)];
+        mark_out_of_cts_order(&mut rows);
+        let flags: Vec<bool> = rows.iter().map(|r| r.out_of_cts_order).collect();
+        assert_eq!(flags, vec![false, false, true, false]);
+    }
+
+    #[test]
+    fn test_current_label_values_replays_history() {
+        let now = Utc::now();
+        let row = |seq, val: &str, neg, exp| LabelHistoryRow {
+            seq,
+            src: "did:plc:labeler".to_string(),
+            val: val.to_string(),
+            neg,
+            cts: now,
+            exp,
+            created_at: now,
+        };
+
+        // flagged, negated, flagged again -> active
+        let history = vec![
+            row(1, "copyright-violation", false, None),
+            row(2, "copyright-violation", true, None),
+            row(3, "copyright-violation", false, None),
+            row(4, "sensitive", false, None),
+            row(5, "sensitive", true, None),
+        ];
+        assert_eq!(current_label_values(&history, now), vec!["copyright-violation"]);
+
+        // latest positive label has lapsed
+        let expired = vec![row(
+            1,
+            "copyright-violation",
+            false,
+            Some(now - chrono::Duration::hours(1)),
+        )];
+        assert!(current_label_values(&expired, now).is_empty());
+    }
+
+    #[test]
+    fn test_resolution_reason_round_trips() {
+        for reason in [
+            ResolutionReason::OriginalArtist,
+            ResolutionReason::Licensed,
+            ResolutionReason::FingerprintNoise,
+            ResolutionReason::CoverVersion,
+            ResolutionReason::ContentDeleted,
+            ResolutionReason::Other,
+        ] {
+            assert_eq!(ResolutionReason::from_key(reason.as_str()), reason);
+            assert_eq!(
+                serde_json::to_value(&reason).unwrap(),
+                serde_json::json!(reason.as_str())
+            );
+        }
+        // rows written before reasons were stored in snake_case
+        assert_eq!(
+            ResolutionReason::from_key("fingerprintnoise"),
+            ResolutionReason::FingerprintNoise
+        );
+    }
+
+    #[test]
+    fn test_resolution_without_prior_context() {
+        // the minimal row store_negation writes for a URI with no context
+        let row = FlaggedRow {
+            seq: 7,
+            uri: "at://did:plc:artist/fm.plyr.track/abc".to_string(),
+            val: "copyright-violation".to_string(),
+            cts: Utc::now(),
+            resolution_reason: Some(ResolutionReason::OriginalArtist.as_str().to_string()),
+            resolution_notes: Some("their own release".to_string()),
+            resolved_at: Some(Utc::now()),
+            ..Default::default()
+        };
+        let track = flagged_track_from_row(row);
+        let context = track
+            .context
+            .expect("resolution alone should yield context");
+        assert_eq!(
+            context.resolution_reason,
+            Some(ResolutionReason::OriginalArtist)
+        );
+        assert_eq!(
+            context.resolution_notes.as_deref(),
+            Some("their own release")
+        );
+        assert!(context.track_title.is_none());
+    }
+
+    #[test]
+    fn test_reports_cursor_round_trips() {
+        let cursor = ReportsCursor {
+            created_at: DateTime::from_timestamp_micros(1_760_600_000_123_456).unwrap(),
+            id: 42,
+        };
+        assert_eq!(cursor.encode(), "1760600000123456:42");
+        assert_eq!(ReportsCursor::parse(&cursor.encode()), Some(cursor));
+        assert_eq!(ReportsCursor::parse("42"), None);
+        assert_eq!(ReportsCursor::parse("soon:42"), None);
+        assert_eq!(ReportsCursor::parse("1760600000123456:"), None);
+    }
+
+    #[test]
+    fn test_label_context_default() {
+        let ctx = LabelContext::default();
+        assert!(ctx.track_title.is_none());
+        assert!(ctx.resolution_reason.is_none());
+        assert!(ctx.resolution_notes.is_none());
+    }
+
+    /// Runs against `MODERATION_TEST_DATABASE_URL` when it's set; skipped
+    /// otherwise.
+    #[tokio::test]
+    async fn test_expired_labels_are_hidden_then_pruned() {
+        let Ok(url) = std::env::var("MODERATION_TEST_DATABASE_URL") else {
+            return;
+        };
+        let db = LabelDb::connect(&url).await.unwrap();
+        db.migrate().await.unwrap();
+
+        let run = rand::random::<u32>();
+        let uri = format!("at://did:plc:expiry{run}/fm.plyr.track/1");
+        let expiring = |exp: Option<DateTime<Utc>>| {
+            let mut label = Label::new("did:plc:labeler", &uri, "copyright-violation");
+            label.exp = exp.map(|e| e.to_rfc3339());
+            label
+        };
+        let start = db.get_latest_seq().await.unwrap();
+        let live = db.store_label(&expiring(None)).await.unwrap();
+        let lapsed = db
+            .store_label(&expiring(Some(Utc::now() - chrono::Duration::hours(1))))
+            .await
+            .unwrap();
+        db.store_label(&expiring(Some(Utc::now() - chrono::Duration::days(40))))
+            .await
+            .unwrap();
+
+        let (rows, _) = db
+            .query_labels(std::slice::from_ref(&uri), None, None, None, 50)
+            .await
+            .unwrap();
+        assert_eq!(rows.iter().map(|r| r.seq).collect::<Vec<_>>(), vec![live]);
+        // a URI whose only flag has lapsed isn't pending
+        let lapsed_only = format!("at://did:plc:expiry{run}/fm.plyr.track/2");
+        let mut lapsed_label = Label::new("did:plc:labeler", &lapsed_only, "copyright-violation");
+        lapsed_label.exp = Some((Utc::now() - chrono::Duration::hours(1)).to_rfc3339());
+        db.store_label(&lapsed_label).await.unwrap();
+        let pending = db.get_pending_flag_uris().await.unwrap();
+        assert!(pending.contains(&uri));
+        assert!(!pending.contains(&lapsed_only));
+
+        // Only the label that lapsed 40 days ago is past a 30 day retention
+        assert!(db.prune_expired_labels(30).await.unwrap() >= 1);
+        let stored: Vec<_> = db
+            .get_labels_since(start, 10_000)
+            .await
+            .unwrap()
+            .into_iter()
+            .filter(|r| r.uri == uri)
+            .map(|r| r.seq)
+            .collect();
+        assert_eq!(stored, vec![live, lapsed]);
+    }
+
+    /// Runs against `MODERATION_TEST_DATABASE_URL` when it's set; skipped
+    /// otherwise.
+    #[tokio::test]
+    async fn test_image_scan_keeps_model_and_request_id() {
+        let Ok(url) = std::env::var("MODERATION_TEST_DATABASE_URL") else {
+            return;
+        };
+        let db = LabelDb::connect(&url).await.unwrap();
+        db.migrate().await.unwrap();
+
+        let run = rand::random::<u32>();
+        let image = format!("scan-meta-{run}");
+        let request_id = format!("req_{run}");
+        db.store_image_scan(&NewImageScan {
+            image_id: &image,
+            is_safe: true,
+            violated_categories: &[],
+            severity: "none",
+            explanation: "fine",
+            model: "claude-test",
+            source_url: None,
+            review_state: None,
+            input_tokens: 1,
+            output_tokens: 1,
+            cost_usd: None,
+            content_hash: &format!("{run:064x}"),
+            second_opinion_of: None,
+            request_id: Some(&request_id),
+        })
+        .await
+        .unwrap();
+
+        let filter = ImageScanFilter {
+            image_id: Some(&image),
+            ..Default::default()
+        };
+        let (rows, total) = db.list_image_scans(&filter, 10, 0).await.unwrap();
+        assert_eq!(total, 1);
+        assert_eq!(rows[0].model.as_deref(), Some("claude-test"));
+        assert_eq!(rows[0].request_id.as_deref(), Some(request_id.as_str()));
+    }
+
+    /// Runs against `MODERATION_TEST_DATABASE_URL` when it's set; skipped
+    /// otherwise.
+    #[tokio::test]
+    async fn test_image_override_applies_with_its_audit_entry() {
+        let Ok(url) = std::env::var("MODERATION_TEST_DATABASE_URL") else {
+            return;
+        };
+        let db = LabelDb::connect(&url).await.unwrap();
+        db.migrate().await.unwrap();
+
+        let run = rand::random::<u32>();
+        let image = format!("override-{run}");
+        let reupload = format!("override-{run}-reupload");
+        let hash = format!("{run:064x}");
+        let held = db
+            .store_image_scan(&NewImageScan {
+                image_id: &reupload,
+                is_safe: false,
+                violated_categories: &[],
+                severity: "low",
+                explanation: "borderline",
+                model: "test",
+                source_url: None,
+                review_state: Some("needs_review"),
+                input_tokens: 1,
+                output_tokens: 1,
+                cost_usd: None,
+                content_hash: &hash,
+                second_opinion_of: None,
+                request_id: None,
+            })
+            .await
+            .unwrap();
+        db.add_sensitive_image(Some(&image), None, None, None, None, &[])
+            .await
+            .unwrap();
+        db.add_sensitive_image(Some(&reupload), None, None, None, None, &[])
+            .await
+            .unwrap();
+        let sensitive = |id: String| {
+            let pool = db.pool.clone();
+            async move {
+                sqlx::query_scalar::<_, i64>(
+                    "SELECT COUNT(*) FROM sensitive_images WHERE image_id = $1",
+                )
+                .bind(id)
+                .fetch_one(&pool)
+                .await
+                .unwrap()
+            }
+        };
+
+        // a safe override clears the image and anything scanned with its bytes
+        let safe = db
+            .apply_image_override(
+                Some(&image),
+                Some(&hash),
+                OverrideVerdict::Safe,
+                "alice",
+                None,
+            )
+            .await
+            .unwrap();
+        assert_eq!(safe.removed, 2);
+        assert_eq!(sensitive(image.clone()).await, 0);
+        assert_eq!(sensitive(reupload.clone()).await, 0);
+        let scan = db.get_image_scan(held).await.unwrap().unwrap();
+        assert_eq!(scan.review_state.as_deref(), Some("overridden"));
+        let found = db.get_image_override(&reupload, &hash).await.unwrap();
+        assert_eq!(found.unwrap().id, safe.override_id);
+
+        // an unsafe override flags the image once, however often it's repeated
+        let unsafe_verdict = || {
+            db.apply_image_override(Some(&image), None, OverrideVerdict::Unsafe, "bob", None)
+        };
+        let first = unsafe_verdict().await.unwrap();
+        let second = unsafe_verdict().await.unwrap();
+        assert!(first.sensitive_image_id.is_some());
+        assert!(second.sensitive_image_id.is_none());
+        assert_eq!(sensitive(image.clone()).await, 1);
+
+        let audited = db
+            .list_audit_log(Some("image_override"), Some(&image), None, 200)
+            .await
+            .unwrap();
+        assert_eq!(audited.len(), 3);
+        assert_eq!(audited[2].actor, "alice");
+        assert_eq!(audited[2].details.as_ref().unwrap()["removed"], 2);
+    }
+
+    /// Runs against `MODERATION_TEST_DATABASE_URL` when it's set; skipped
+    /// otherwise.
+    #[tokio::test]
+    async fn test_concurrent_skip_if_active_emits_once() {
+        let Ok(url) = std::env::var("MODERATION_TEST_DATABASE_URL") else {
+            return;
+        };
+        let db = LabelDb::connect(&url).await.unwrap();
+        db.migrate().await.unwrap();
+
+        let run = rand::random::<u32>();
+        let uri = format!("at://did:plc:skip{run}/fm.plyr.track/1");
+        let label = Label::new("did:plc:labeler", &uri, "copyright-violation");
+        let outcomes = futures::future::join_all(
+            (0..5).map(|_| db.store_label_unless_active(&label)),
+        )
+        .await;
+
+        let mut created = Vec::new();
+        let mut existing = Vec::new();
+        for outcome in outcomes {
+            match outcome.unwrap() {
+                StoredLabel::Created(seq) => created.push(seq),
+                StoredLabel::Existing(row) => existing.push(row.seq),
+            }
+        }
+        assert_eq!(created.len(), 1);
+        assert!(existing.iter().all(|seq| *seq == created[0]));
+
+        // once negated, the next call emits again
+        db.store_label(&label.clone().negated()).await.unwrap();
+        assert!(matches!(
+            db.store_label_unless_active(&label).await.unwrap(),
+            StoredLabel::Created(_)
+        ));
+    }
+
+    /// Runs against `MODERATION_TEST_DATABASE_URL` when it's set; skipped
+    /// otherwise.
+    #[tokio::test]
+    async fn test_query_labels_filters_by_value() {
+        let Ok(url) = std::env::var("MODERATION_TEST_DATABASE_URL") else {
+            return;
+        };
+        let db = LabelDb::connect(&url).await.unwrap();
+        db.migrate().await.unwrap();
+
+        let run = rand::random::<u32>();
+        let pattern = format!("at://did:plc:vals{run}/*");
+        let mut seqs = std::collections::HashMap::new();
+        for (track, val) in [(1, "copyright-violation"), (2, "explicit"), (3, "sexual")] {
+            let uri = format!("at://did:plc:vals{run}/fm.plyr.track/{track}");
+            let seq = db
+                .store_label(&Label::new("did:plc:labeler", &uri, val))
+                .await
+                .unwrap();
+            seqs.insert(val, seq);
+        }
+        let query = |vals: Option<Vec<String>>| {
+            let (db, pattern) = (&db, pattern.clone());
+            async move {
+                let (rows, _) = db
+                    .query_labels(&[pattern], None, vals.as_deref(), None, 50)
+                    .await
+                    .unwrap();
+                rows.into_iter().map(|r| r.seq).collect::<Vec<_>>()
+            }
+        };
+
+        assert_eq!(query(None).await.len(), 3);
+        assert_eq!(
+            query(Some(vec!["explicit".to_string(), "sexual".to_string()])).await,
+            vec![seqs["explicit"], seqs["sexual"]]
+        );
+        // an empty list doesn't filter
+        assert_eq!(query(Some(vec![])).await.len(), 3);
+    }
+
+    /// Runs against `MODERATION_TEST_DATABASE_URL` when it's set; skipped
+    /// otherwise.
+    #[tokio::test]
+    async fn test_flags_page_filters_by_artist() {
+        let Ok(url) = std::env::var("MODERATION_TEST_DATABASE_URL") else {
+            return;
+        };
+        let db = LabelDb::connect(&url).await.unwrap();
+        db.migrate().await.unwrap();
+
+        let run = rand::random::<u32>();
+        let mut uris = Vec::new();
+        for (n, handle) in [format!("a_b{run}.test"), format!("axb{run}.test")]
+            .into_iter()
+            .enumerate()
+        {
+            let uri = format!("at://did:plc:artist{run}x{n}/fm.plyr.track/1");
+            db.store_label(&Label::new("did:plc:labeler", &uri, "copyright-violation"))
+                .await
+                .unwrap();
+            let context = LabelContext {
+                artist_handle: Some(handle),
+                artist_did: Some(format!("did:plc:artist{run}x{n}")),
+                ..Default::default()
+            };
+            db.store_context(&uri, &context).await.unwrap();
+            uris.push(uri);
+        }
+        let page = |artist: Option<String>, did: Option<String>| {
+            let db = &db;
+            async move {
+                let query = FlagsPageQuery {
+                    status: FlagStatus::All,
+                    limit: 50,
+                    artist,
+                    did,
+                    ..Default::default()
+                };
+                let page = db.get_flags_page(&query).await.unwrap();
+                (page.total, page.tracks.into_iter().map(|t| t.uri).collect::<Vec<_>>())
+            }
+        };
+
+        // case-insensitive, and `_` in the filter is literal
+        let (total, tracks) = page(Some(format!("A_B{run}")), None).await;
+        assert_eq!((total, tracks), (1, vec![uris[0].clone()]));
+        let (total, tracks) = page(None, Some(format!("did:plc:artist{run}x1"))).await;
+        assert_eq!((total, tracks), (1, vec![uris[1].clone()]));
+        let (total, _) = page(Some(format!("{run}.test")), None).await;
+        assert_eq!(total, 2);
+    }
+
+    /// Runs against `MODERATION_TEST_DATABASE_URL` when it's set; skipped
+    /// otherwise.
+    #[tokio::test]
+    async fn test_flags_page_sorts_and_pages_by_matches() {
+        let Ok(url) = std::env::var("MODERATION_TEST_DATABASE_URL") else {
+            return;
+        };
+        let db = LabelDb::connect(&url).await.unwrap();
+        db.migrate().await.unwrap();
+
+        let run = rand::random::<u32>();
+        let hit = CopyrightMatch {
+            title: "song".to_string(),
+            artist: "someone".to_string(),
+            score: 0.9,
+        };
+        // stored oldest first: 1 match, none, 2 matches, 0 matches
+        let mut uris = Vec::new();
+        for (n, matches) in [Some(vec![hit.clone()]), None, Some(vec![hit.clone(), hit]), Some(vec![])]
+            .into_iter()
+            .enumerate()
+        {
+            let uri = format!("at://did:plc:sort{run}/fm.plyr.track/{n}");
+            db.store_label(&Label::new("did:plc:labeler", &uri, "copyright-violation"))
+                .await
+                .unwrap();
+            let context = LabelContext {
+                artist_handle: Some(format!("sort{run}.test")),
+                matches,
+                ..Default::default()
+            };
+            db.store_context(&uri, &context).await.unwrap();
+            uris.push(uri);
+        }
+
+        // walks every page of two for `sort`, returning the uri indexes in order
+        let walk = |sort: FlagSort| {
+            let db = &db;
+            let uris = &uris;
+            async move {
+                let mut order = Vec::new();
+                let mut cursor = None;
+                loop {
+                    let query = FlagsPageQuery {
+                        status: FlagStatus::All,
+                        sort,
+                        cursor,
+                        limit: 2,
+                        artist: Some(format!("sort{run}.test")),
+                        ..Default::default()
+                    };
+                    let page = db.get_flags_page(&query).await.unwrap();
+                    assert_eq!(page.total, 4);
+                    order.extend(
+                        page.tracks
+                            .iter()
+                            .map(|t| uris.iter().position(|u| *u == t.uri).unwrap()),
+                    );
+                    match page.next_cursor {
+                        Some(next) => cursor = Some(FlagsCursor::parse(&next, sort).unwrap()),
+                        None => return order,
+                    }
+                }
+            }
+        };
+
+        assert_eq!(walk(FlagSort::Newest).await, vec![3, 2, 1, 0]);
+        assert_eq!(walk(FlagSort::Oldest).await, vec![0, 1, 2, 3]);
+        assert_eq!(walk(FlagSort::MostMatches).await, vec![2, 0, 3, 1]);
+    }
+
+    /// Runs against `MODERATION_TEST_DATABASE_URL` when it's set; skipped
+    /// otherwise.
+    #[tokio::test]
+    async fn test_store_negations_rechecks_active_labels() {
+        let Ok(url) = std::env::var("MODERATION_TEST_DATABASE_URL") else {
+            return;
+        };
+        let db = LabelDb::connect(&url).await.unwrap();
+        db.migrate().await.unwrap();
+
+        let run = rand::random::<u32>();
+        let labels: Vec<Label> = (0..3)
+            .map(|n| {
+                Label::new(
+                    "did:plc:labeler",
+                    format!("at://did:plc:bulk{run}/fm.plyr.track/{n}"),
+                    "copyright-violation",
+                )
+            })
+            .collect();
+        for label in &labels {
+            db.store_label(label).await.unwrap();
+        }
+        // resolved elsewhere after the caller's active check
+        db.store_label(&labels[1].clone().negated()).await.unwrap();
+        let negations: Vec<Label> = labels.iter().map(|l| l.clone().negated()).collect();
+
+        let stored = db
+            .store_negations(&negations, Some(&ResolutionReason::Other), None, true)
+            .await
+            .unwrap();
+        assert_eq!(
+            stored,
+            vec![
+                StoredNegation::Skipped,
+                StoredNegation::NotActive,
+                StoredNegation::Skipped
+            ]
+        );
+        let active = db
+            .get_active_label("did:plc:labeler", &labels[0].uri, "copyright-violation")
+            .await
+            .unwrap();
+        assert!(active.is_some(), "atomic failure must write nothing");
+
+        let stored = db
+            .store_negations(&negations, Some(&ResolutionReason::Other), None, false)
+            .await
+            .unwrap();
+        assert!(matches!(stored[0], StoredNegation::Created(_)));
+        assert_eq!(stored[1], StoredNegation::NotActive);
+        assert!(matches!(stored[2], StoredNegation::Created(_)));
+
+        // a second pass finds nothing left to negate
+        let stored = db
+            .store_negations(&negations, None, None, false)
+            .await
+            .unwrap();
+        assert!(stored.iter().all(|s| *s == StoredNegation::NotActive));
+    }
+
+    /// Runs against `MODERATION_TEST_DATABASE_URL` when it's set; skipped
+    /// otherwise.
+    #[tokio::test]
+    async fn test_enforcement_request_lifecycle() {
+        let Ok(url) = std::env::var("MODERATION_TEST_DATABASE_URL") else {
+            return;
+        };
+        let db = LabelDb::connect(&url).await.unwrap();
+        db.migrate().await.unwrap();
+
+        let run = rand::random::<u32>();
+        let uri = format!("at://did:plc:enforce{run}/fm.plyr.track/1");
+        db.store_label(&Label::new("did:plc:labeler", &uri, "copyright-violation"))
+            .await
+            .unwrap();
+        let context = LabelContext {
+            artist_did: Some(format!("did:plc:enforce{run}")),
+            ..Default::default()
+        };
+        db.store_context(&uri, &context).await.unwrap();
+        let pending = |db: &LabelDb| {
+            let uri = uri.clone();
+            let db = db.clone();
+            async move {
+                db.get_pending_enforcements()
+                    .await
+                    .unwrap()
+                    .into_iter()
+                    .filter(|r| r.uri == uri)
+                    .collect::<Vec<_>>()
+            }
+        };
+
+        assert!(db.start_enforcement(&uri, Some(7), "alice").await.unwrap());
+        // a retry while the first delivery is still pending doesn't start another
+        assert!(!db.start_enforcement(&uri, Some(7), "bob").await.unwrap());
+        let rows = pending(&db).await;
+        assert_eq!(rows.len(), 1);
+        assert_eq!((rows[0].track_id, rows[0].confirmed_by.as_str()), (Some(7), "alice"));
+
+        db.record_enforcement_attempt(&uri, "failed", Some(502))
+            .await
+            .unwrap();
+        assert!(pending(&db).await.is_empty());
+        let query = FlagsPageQuery {
+            status: FlagStatus::All,
+            limit: 10,
+            did: Some(format!("did:plc:enforce{run}")),
+            ..Default::default()
+        };
+        let page = db.get_flags_page(&query).await.unwrap();
+        assert_eq!(page.tracks[0].enforcement.as_deref(), Some("failed"));
+
+        // a failed request can be started again
+        assert!(db.start_enforcement(&uri, Some(7), "bob").await.unwrap());
+        assert_eq!(pending(&db).await[0].confirmed_by, "bob");
+    }
+
+    /// Runs against `MODERATION_TEST_DATABASE_URL` when it's set; skipped
+    /// otherwise.
+    #[tokio::test]
+    async fn test_revoked_sessions_persist_until_expiry() {
+        let Ok(url) = std::env::var("MODERATION_TEST_DATABASE_URL") else {
+            return;
+        };
+        let db = LabelDb::connect(&url).await.unwrap();
+        db.migrate().await.unwrap();
+
+        let run = rand::random::<u32>();
+        let live = format!("live-{run}");
+        let lapsed = format!("lapsed-{run}");
+        db.revoke_session(&live, Utc::now() + chrono::Duration::hours(1))
+            .await
+            .unwrap();
+        db.revoke_session(&lapsed, Utc::now() - chrono::Duration::hours(1))
+            .await
+            .unwrap();
+        // revoking twice is harmless
+        db.revoke_session(&live, Utc::now() + chrono::Duration::hours(1))
+            .await
+            .unwrap();
+
+        let revoked: Vec<String> = db
+            .get_revoked_sessions()
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|(id, _)| id)
+            .collect();
+        assert!(revoked.contains(&live));
+        assert!(!revoked.contains(&lapsed));
+    }
+
+    /// Runs against `MODERATION_TEST_DATABASE_URL` when it's set; skipped
+    /// otherwise.
+    #[tokio::test]
+    async fn test_resolution_stored_without_prior_context() {
+        let Ok(url) = std::env::var("MODERATION_TEST_DATABASE_URL") else {
+            return;
+        };
+        let db = LabelDb::connect(&url).await.unwrap();
+        db.migrate().await.unwrap();
+
+        let run = rand::random::<u32>();
+        let uri = format!("at://did:plc:bare{run}/fm.plyr.track/1");
+        let label = Label::new("did:plc:labeler", &uri, "copyright-violation");
+        db.store_label(&label).await.unwrap();
+        db.store_negation(
+            &label.negated(),
+            Some(&ResolutionReason::CoverVersion),
+            Some("live cover"),
+            "alice",
+        )
+        .await
+        .unwrap();
+
+        let context = db.get_flag(&uri).await.unwrap().unwrap().context.unwrap();
+        assert_eq!(context.resolution_reason, Some(ResolutionReason::CoverVersion));
+        assert_eq!(context.resolution_notes.as_deref(), Some("live cover"));
+
+        // a reason stored by an older version still reads back
+        let legacy = format!("at://did:plc:bare{run}/fm.plyr.track/2");
+        sqlx::query("INSERT INTO label_context (uri, resolution_reason) VALUES ($1, 'fingerprintnoise')")
+            .bind(&legacy)
+            .execute(&db.pool)
+            .await
+            .unwrap();
+        let context = db.get_context(&legacy).await.unwrap().unwrap();
+        assert_eq!(
+            context.resolution_reason,
+            Some(ResolutionReason::FingerprintNoise)
+        );
+    }
+
+    /// Runs against `MODERATION_TEST_DATABASE_URL` when it's set; skipped
+    /// otherwise.
+    #[tokio::test]
+    async fn test_unresolve_reemits_once_with_its_audit_entry() {
+        let Ok(url) = std::env::var("MODERATION_TEST_DATABASE_URL") else {
+            return;
+        };
+        let db = LabelDb::connect(&url).await.unwrap();
+        db.migrate().await.unwrap();
+
+        let run = rand::random::<u32>();
+        let uri = format!("at://did:plc:undo{run}/fm.plyr.track/1");
+        let label = Label::new("did:plc:labeler", &uri, "copyright-violation");
+        db.store_label(&label).await.unwrap();
+        let negation_seq = db
+            .store_negation(
+                &label.clone().negated(),
+                Some(&ResolutionReason::Licensed),
+                Some("oops"),
+                "alice",
+            )
+            .await
+            .unwrap();
+
+        // two undos racing for the same negation: exactly one re-emits
+        let (a, b) = futures::join!(
+            db.store_unresolve(&label, negation_seq, "alice", "misclick"),
+            db.store_unresolve(&label, negation_seq, "bob", "misclick"),
+        );
+        let seqs: Vec<i64> = [a.unwrap(), b.unwrap()].into_iter().flatten().collect();
+        assert_eq!(seqs.len(), 1);
+
+        let active = db
+            .get_active_label("did:plc:labeler", &uri, "copyright-violation")
+            .await
+            .unwrap();
+        assert_eq!(active.map(|row| row.seq), Some(seqs[0]));
+        let context = db.get_context(&uri).await.unwrap().unwrap();
+        assert!(context.resolution_reason.is_none());
+        assert!(context.resolution_notes.is_none());
+
+        let audited = db
+            .list_audit_log(Some("unresolve_flag"), Some(&uri), None, 10)
+            .await
+            .unwrap();
+        assert_eq!(audited.len(), 1);
+        let details = audited[0].details.as_ref().unwrap();
+        assert_eq!(details["seq"], seqs[0]);
+        assert_eq!(details["negation_seq"], negation_seq);
+    }
+
+    /// Runs against `MODERATION_TEST_DATABASE_URL` when it's set; skipped
+    /// otherwise.
+    #[tokio::test]
+    async fn test_count_labels_matches_query_labels() {
+        let Ok(url) = std::env::var("MODERATION_TEST_DATABASE_URL") else {
+            return;
+        };
+        let db = LabelDb::connect(&url).await.unwrap();
+        db.migrate().await.unwrap();
+
+        let run = rand::random::<u32>();
+        let mut seqs = Vec::new();
+        for (n, val) in ["copyright-violation", "sensitive", "copyright-violation"]
+            .into_iter()
+            .enumerate()
+        {
+            let uri = format!("at://did:plc:count{run}/fm.plyr.track/{n}");
+            seqs.push(db.store_label(&Label::new("did:plc:labeler", &uri, val)).await.unwrap());
+        }
+        let patterns = vec![format!("at://did:plc:count{run}/*")];
+        let copyright = vec!["copyright-violation".to_string()];
+        let first = seqs[0].to_string();
+
+        for (vals, cursor, expected) in [
+            (None, None, 3),
+            (Some(copyright.as_slice()), None, 2),
+            (None, Some(first.as_str()), 2),
+            (Some(copyright.as_slice()), Some(first.as_str()), 1),
+        ] {
+            let total = db.count_labels(&patterns, None, vals, cursor).await.unwrap();
+            // a page of one still counts everything past the cursor
+            let (page, _) = db
+                .query_labels(&patterns, None, vals, cursor, 1)
+                .await
+                .unwrap();
+            let (all, _) = db
+                .query_labels(&patterns, None, vals, cursor, 50)
+                .await
+                .unwrap();
+            assert_eq!(total, expected);
+            assert_eq!(page.len(), 1);
+            assert_eq!(all.len() as i64, total);
+        }
+    }
+
+    /// Runs against `MODERATION_TEST_DATABASE_URL` when it's set; skipped
+    /// otherwise.
+    #[tokio::test]
+    async fn test_expire_batches_marks_only_overdue_pending() {
+        let Ok(url) = std::env::var("MODERATION_TEST_DATABASE_URL") else {
+            return;
+        };
+        let db = LabelDb::connect(&url).await.unwrap();
+        db.migrate().await.unwrap();
+        let run = rand::random::<u32>();
+        let now = Utc::now();
+
+        let overdue = format!("overdue-{run}");
+        let fresh = format!("fresh-{run}");
+        let done = format!("done-{run}");
+        db.create_batch(&overdue, &[], None, now - chrono::Duration::hours(1))
+            .await
+            .unwrap();
+        db.create_batch(&fresh, &[], None, now + chrono::Duration::days(1))
+            .await
+            .unwrap();
+        db.create_batch(&done, &[], None, now - chrono::Duration::hours(1))
+            .await
+            .unwrap();
+        sqlx::query("UPDATE review_batches SET status = 'completed' WHERE id = $1")
+            .bind(&done)
+            .execute(&db.pool)
+            .await
+            .unwrap();
+
+        assert!(db.expire_batches().await.unwrap() >= 1);
+
+        let status = |b: Option<ReviewBatch>| b.unwrap().status;
+        assert_eq!(status(db.get_batch(&overdue).await.unwrap()), "expired");
+        assert_eq!(status(db.get_batch(&fresh).await.unwrap()), "pending");
+        assert_eq!(status(db.get_batch(&done).await.unwrap()), "completed");
+    }
+
+    /// Runs against `MODERATION_TEST_DATABASE_URL` when it's set; skipped
+    /// otherwise.
+    #[tokio::test]
+    async fn test_resolve_reports_skips_closed_and_needs_every_id() {
+        let Ok(url) = std::env::var("MODERATION_TEST_DATABASE_URL") else {
+            return;
+        };
+        let db = LabelDb::connect(&url).await.unwrap();
+        db.migrate().await.unwrap();
+
+        let run = rand::random::<u32>();
+        let report = |status: &'static str| {
+            let db = &db;
+            async move {
+                sqlx::query_scalar::<_, i32>(
+                    "INSERT INTO user_reports (reporter_did, target_type, target_id, reason, status) \
+                     VALUES ($1, 'track', '1', 'spam', $2) RETURNING id",
+                )
+                .bind(format!("did:plc:bulk{run}"))
+                .bind(status)
+                .fetch_one(&db.pool)
+                .await
+                .unwrap()
+            }
+        };
+        let status_of = |id: i32| {
+            let db = &db;
+            async move { db.get_report(id).await.unwrap().unwrap().status }
+        };
+        let audited = |ids: Vec<i32>| {
+            let db = &db;
+            async move {
+                let targets: Vec<String> = ids.iter().map(i32::to_string).collect();
+                sqlx::query_scalar::<_, i64>(
+                    "SELECT COUNT(*) FROM audit_log WHERE action = 'resolve_report' AND target = ANY($1)",
+                )
+                .bind(targets)
+                .fetch_one(&db.pool)
+                .await
+                .unwrap()
+            }
+        };
+
+        let open = report("open").await;
+        let escalated = report("escalated").await;
+        let dismissed = report("dismissed").await;
+        let ids = [open, dismissed, escalated];
+        let resolution = db
+            .resolve_reports(&ids, "resolved", Some("spam wave"), "alice", "alice")
+            .await
+            .unwrap();
+        let BulkReportResolution::Closed {
+            resolved,
+            already_closed,
+        } = resolution
+        else {
+            panic!("every id exists");
+        };
+        let mut resolved: Vec<i32> = resolved.iter().map(|r| r.id).collect();
+        resolved.sort();
+        assert_eq!(resolved, vec![open, escalated]);
+        assert_eq!(already_closed, vec![(dismissed, "dismissed".to_string())]);
+        assert_eq!(status_of(open).await, "resolved");
+        assert_eq!(status_of(dismissed).await, "dismissed");
+        assert_eq!(audited(vec![open, escalated, dismissed]).await, 2);
+
+        // one unknown id and nothing happens, not even to the real reports
+        let still_open = report("open").await;
+        let unknown = i32::MAX - (run % 1000) as i32;
+        let resolution = db
+            .resolve_reports(&[still_open, unknown], "dismissed", None, "alice", "alice")
+            .await
+            .unwrap();
+        assert!(matches!(resolution, BulkReportResolution::Missing(ids) if ids == vec![unknown]));
+        assert_eq!(status_of(still_open).await, "open");
+        assert_eq!(audited(vec![still_open]).await, 0);
+
+        sqlx::query("DELETE FROM user_reports WHERE reporter_did = $1")
+            .bind(format!("did:plc:bulk{run}"))
+            .execute(&db.pool)
+            .await
+            .unwrap();
+    }
+
+    /// Runs against `MODERATION_TEST_DATABASE_URL` when it's set; skipped
+    /// otherwise.
+    #[tokio::test]
+    async fn test_negation_closing_report_is_all_or_nothing() {
+        let Ok(url) = std::env::var("MODERATION_TEST_DATABASE_URL") else {
+            return;
+        };
+        let db = LabelDb::connect(&url).await.unwrap();
+        db.migrate().await.unwrap();
+
+        let run = rand::random::<u32>();
+        let reporter = format!("did:plc:closing{run}");
+        let report = |status: &'static str, target_uri: &str| {
+            let (db, reporter) = (&db, &reporter);
+            let target_uri = target_uri.to_string();
+            async move {
+                sqlx::query_scalar::<_, i32>(
+                    "INSERT INTO user_reports (reporter_did, target_type, target_id, target_uri, reason, status) \
+                     VALUES ($1, 'track', '1', $2, 'copyright', $3) RETURNING id",
+                )
+                .bind(reporter)
+                .bind(target_uri)
+                .bind(status)
+                .fetch_one(&db.pool)
+                .await
+                .unwrap()
+            }
+        };
+        let labels_for = |uri: String| {
+            let db = &db;
+            async move {
+                sqlx::query_scalar::<_, bool>("SELECT neg FROM labels WHERE uri = $1")
+                    .bind(uri)
+                    .fetch_all(&db.pool)
+                    .await
+                    .unwrap()
+            }
+        };
+        let reason = ResolutionReason::Licensed;
+
+        let resolved = format!("at://did:plc:closing{run}/fm.plyr.track/1");
+        let open = report("open", &resolved).await;
+        let negation = Label::new("did:plc:labeler", &resolved, "copyright-violation").negated();
+        let (seq, closed) = db
+            .store_negation_closing_report(
+                &negation,
+                Some(&reason),
+                Some("license on file"),
+                open,
+                "alice",
+            )
+            .await
+            .unwrap()
+            .expect("the report is open");
+        assert!(seq > 0);
+        assert_eq!(closed.id, open);
+        assert_eq!(closed.status, "resolved");
+        assert_eq!(closed.admin_notes.as_deref(), Some("license on file"));
+        assert_eq!(closed.resolved_by.as_deref(), Some("alice"));
+        assert_eq!(labels_for(resolved.clone()).await, vec![true]);
+        let context = db.get_context(&resolved).await.unwrap().unwrap();
+        assert_eq!(context.resolution_notes.as_deref(), Some("license on file"));
+        let audited: Vec<String> = sqlx::query_scalar(
+            "SELECT action FROM audit_log WHERE target IN ($1, $2) ORDER BY id",
+        )
+        .bind(&resolved)
+        .bind(open.to_string())
+        .fetch_all(&db.pool)
+        .await
+        .unwrap();
+        assert_eq!(audited, ["resolve_flag", "resolve_report"]);
+
+        // a closed, unknown or unrelated report means the negation isn't
+        // stored either
+        let untouched = format!("at://did:plc:closing{run}/fm.plyr.track/2");
+        let dismissed = report("dismissed", &untouched).await;
+        let unknown = i32::MAX - (run % 1000) as i32;
+        let elsewhere = report("open", &resolved).await;
+        for report_id in [dismissed, unknown, elsewhere] {
+            let negation =
+                Label::new("did:plc:labeler", &untouched, "copyright-violation").negated();
+            let outcome = db
+                .store_negation_closing_report(
+                    &negation,
+                    Some(&reason),
+                    Some("nope"),
+                    report_id,
+                    "alice",
+                )
+                .await
+                .unwrap();
+            assert!(outcome.is_none());
+        }
+        assert!(labels_for(untouched.clone()).await.is_empty());
+        assert!(db.get_context(&untouched).await.unwrap().is_none());
+        let dismissed = db.get_report(dismissed).await.unwrap().unwrap();
+        assert_eq!(dismissed.status, "dismissed");
+        assert!(dismissed.admin_notes.is_none());
+        let elsewhere = db.get_report(elsewhere).await.unwrap().unwrap();
+        assert_eq!(elsewhere.status, "open");
+
+        sqlx::query("DELETE FROM labels WHERE uri = $1")
+            .bind(&resolved)
+            .execute(&db.pool)
+            .await
+            .unwrap();
+        sqlx::query("DELETE FROM label_context WHERE uri = $1")
+            .bind(&resolved)
+            .execute(&db.pool)
+            .await
+            .unwrap();
+        sqlx::query("DELETE FROM user_reports WHERE reporter_did = $1")
+            .bind(&reporter)
+            .execute(&db.pool)
+            .await
+            .unwrap();
+    }
+
+    /// Runs against `MODERATION_TEST_DATABASE_URL` when it's set; skipped
+    /// otherwise.
+    #[tokio::test]
+    async fn test_prune_resolved_keeps_recent_rows() {
+        let Ok(url) = std::env::var("MODERATION_TEST_DATABASE_URL") else {
+            return;
+        };
+        let db = LabelDb::connect(&url).await.unwrap();
+        db.migrate().await.unwrap();
+
+        // the label value and report reason tag this run's rows
+        let run = rand::random::<u32>();
+        let tag = format!("retention-test-{run}");
+        let label = |uri: &str, neg: bool, days_ago: i32| {
+            let (db, tag) = (&db, &tag);
+            let uri = format!("at://did:plc:retention{run}/fm.plyr.track/{uri}");
+            async move {
+                sqlx::query(
+                    "INSERT INTO labels (src, uri, val, neg, cts, sig) \
+                     VALUES ('did:plc:labeler', $1, $2, $3, NOW() - make_interval(days => $4), '\\x00')",
+                )
+                .bind(uri)
+                .bind(tag)
+                .bind(neg)
+                .bind(days_ago)
+                .execute(&db.pool)
+                .await
+                .unwrap();
+            }
+        };
+        let report = |status: &str, days_ago: i32| {
+            let (db, tag) = (&db, &tag);
+            let status = status.to_string();
+            async move {
+                sqlx::query(
+                    "INSERT INTO user_reports (reporter_did, target_type, target_id, reason, status, created_at, resolved_at, screenshot_key) \
+                     VALUES ('did:plc:reporter', 'track', '1', $1, $2, NOW() - make_interval(days => $3), \
+                             CASE WHEN $2 = 'open' THEN NULL ELSE NOW() - make_interval(days => $3) END, \
+                             $1 || '/' || $2 || '-' || $3::text || '.png')",
+                )
+                .bind(tag)
+                .bind(status)
+                .bind(days_ago)
+                .execute(&db.pool)
+                .await
+                .unwrap();
+            }
+        };
+
+        // cleared long ago: flag and negation both go
+        label("old", false, 100).await;
+        label("old", true, 90).await;
+        // cleared recently: kept
+        label("recent", false, 100).await;
+        label("recent", true, 5).await;
+        // old but never cleared: kept
+        label("active", false, 100).await;
+        // cleared, then flagged again: the latest label is active, so kept
+        label("reflagged", false, 100).await;
+        label("reflagged", true, 90).await;
+        label("reflagged", false, 80).await;
+
+        report("resolved", 90).await;
+        report("dismissed", 90).await;
+        report("resolved", 5).await;
+        report("open", 90).await;
+
+        let pruned = db.prune_resolved(30).await.unwrap();
+        let mut screenshot_keys: Vec<_> = pruned
+            .screenshot_keys
+            .into_iter()
+            .filter(|key| key.starts_with(&format!("{tag}/")))
+            .collect();
+        screenshot_keys.sort();
+        assert_eq!(
+            screenshot_keys,
+            vec![
+                format!("{tag}/dismissed-90.png"),
+                format!("{tag}/resolved-90.png")
+            ]
+        );
+
+        let remaining: Vec<(String, bool)> = sqlx::query_as(
+            "SELECT split_part(uri, '/', 5), neg FROM labels WHERE val = $1 ORDER BY seq",
+        )
+        .bind(&tag)
+        .fetch_all(&db.pool)
+        .await
+        .unwrap();
+        let remaining: Vec<(&str, bool)> =
+            remaining.iter().map(|(u, n)| (u.as_str(), *n)).collect();
+        assert_eq!(
+            remaining,
+            [
+                ("recent", false),
+                ("recent", true),
+                ("active", false),
+                ("reflagged", false),
+                ("reflagged", true),
+                ("reflagged", false),
+            ]
+        );
+
+        let statuses: Vec<String> =
+            sqlx::query_scalar("SELECT status FROM user_reports WHERE reason = $1 ORDER BY id")
+                .bind(&tag)
+                .fetch_all(&db.pool)
+                .await
+                .unwrap();
+        assert_eq!(statuses, ["resolved", "open"]);
+
+        let archived: Vec<(String, i64)> = sqlx::query_as(
+            "SELECT kind, SUM(count)::BIGINT FROM retention_archive \
+             WHERE detail = $1 GROUP BY kind ORDER BY kind",
+        )
+        .bind(&tag)
+        .fetch_all(&db.pool)
+        .await
+        .unwrap();
+        assert_eq!(
+            archived,
+            [
+                ("label".to_string(), 1),
+                ("negation".to_string(), 1),
+                ("report".to_string(), 2)
+            ]
+        );
+    }
+
+    /// Runs against `MODERATION_TEST_DATABASE_URL` when it's set; skipped
+    /// otherwise.
+    #[tokio::test]
+    async fn test_claim_batch_respects_credentials() {
+        let Ok(url) = std::env::var("MODERATION_TEST_DATABASE_URL") else {
+            return;
+        };
+        let db = LabelDb::connect(&url).await.unwrap();
+        db.migrate().await.unwrap();
+        let run = rand::random::<u32>();
+        let id = format!("claim-{run}");
+        db.create_batch(&id, &[], None, Utc::now() + chrono::Duration::days(1))
+            .await
+            .unwrap();
+
+        let held = db.claim_batch(&id, "session:aaaa", "alice", false).await.unwrap();
+        assert_eq!(held.unwrap().assigned_caller.as_deref(), Some("session:aaaa"));
+        assert!(db.claim_batch(&id, "session:bbbb", "bob", false).await.unwrap().is_none());
+        assert!(db.claim_batch(&id, "session:aaaa", "alice", false).await.unwrap().is_some());
+
+        // a name recorded without a credential leaves the batch open
+        sqlx::query("UPDATE review_batches SET assigned_caller = NULL WHERE id = $1")
+            .bind(&id)
+            .execute(&db.pool)
+            .await
+            .unwrap();
+        let taken = db.claim_batch(&id, "session:bbbb", "bob", false).await.unwrap();
+        assert_eq!(taken.unwrap().assigned_to.as_deref(), Some("bob"));
+    }
+
+    /// Runs against `MODERATION_TEST_DATABASE_URL` when it's set; skipped
+    /// otherwise.
+    #[tokio::test]
+    async fn test_resubmitted_batch_decisions_are_not_reapplied() {
+        let Ok(url) = std::env::var("MODERATION_TEST_DATABASE_URL") else {
+            return;
+        };
+        let db = LabelDb::connect(&url).await.unwrap();
+        db.migrate().await.unwrap();
+
+        let run = rand::random::<u32>();
+        let batch_id = format!("resubmit-test-{run}");
+        let cleared = format!("at://did:plc:resubmit{run}/fm.plyr.track/1");
+        let deferred = format!("at://did:plc:resubmit{run}/fm.plyr.track/2");
+        db.create_batch(
+            &batch_id,
+            &[cleared.clone(), deferred.clone()],
+            None,
+            Utc::now() + chrono::Duration::days(1),
+        )
+        .await
+        .unwrap();
+
+        let negation = Label::new("did:plc:labeler", &cleared, "copyright-violation").negated();
+        let reason = ResolutionReason::FingerprintNoise;
+        let submit = || {
+            let (db, batch_id, cleared, deferred) = (&db, &batch_id, &cleared, &deferred);
+            let (negation, reason) = (&negation, &reason);
+            async move {
+                vec![
+                    db.apply_batch_decision(
+                        batch_id,
+                        cleared,
+                        "clear",
+                        "reviewer",
+                        Some("matched a sample pack"),
+                        Some((negation, reason, "batch review: cleared")),
+                    )
+                    .await
+                    .unwrap(),
+                    db.apply_batch_decision(batch_id, deferred, "defer", "reviewer", None, None)
+                        .await
+                        .unwrap(),
+                ]
+            }
+        };
+        let label_count = || {
+            let (db, cleared) = (&db, &cleared);
+            async move {
+                sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM labels WHERE uri = $1")
+                    .bind(cleared)
+                    .fetch_one(&db.pool)
+                    .await
+                    .unwrap()
+            }
+        };
+
+        let uris = [cleared.clone(), deferred.clone()];
+        let decision_count = || {
+            let (db, uris) = (&db, &uris);
+            async move { db.get_review_decisions(uris).await.unwrap().len() }
+        };
+
+        let first = submit().await;
+        assert!(matches!(
+            first[0],
+            DecisionOutcome::Applied { seq: Some(_) }
+        ));
+        assert_eq!(first[1], DecisionOutcome::Applied { seq: None });
+        assert_eq!(label_count().await, 1);
+        assert_eq!(decision_count().await, 2);
+
+        let history = db
+            .get_review_decisions(std::slice::from_ref(&cleared))
+            .await
+            .unwrap();
+        assert_eq!(history[0].decision, "clear");
+        assert_eq!(history[0].batch_id.as_deref(), Some(batch_id.as_str()));
+        assert_eq!(history[0].notes.as_deref(), Some("matched a sample pack"));
+
+        // the identical body again changes nothing
+        let second = submit().await;
+        assert_eq!(second, vec![DecisionOutcome::AlreadyProcessed; 2]);
+        assert_eq!(label_count().await, 1);
+        assert_eq!(decision_count().await, 2);
+
+        assert_eq!(
+            db.apply_batch_decision(&batch_id, &deferred, "confirm", "reviewer", None, None)
+                .await
+                .unwrap(),
+            DecisionOutcome::Conflicting {
+                existing: "defer".to_string()
+            }
         );
-        assert_eq!(ResolutionReason::CoverVersion.label(), "cover/remix");
-        assert_eq!(ResolutionReason::Other.label(), "other");
+        assert_eq!(
+            db.apply_batch_decision(&batch_id, "at://elsewhere", "defer", "reviewer", None, None)
+                .await
+                .unwrap(),
+            DecisionOutcome::NotInBatch
+        );
+
+        sqlx::query("DELETE FROM review_batches WHERE id = $1")
+            .bind(&batch_id)
+            .execute(&db.pool)
+            .await
+            .unwrap();
+        sqlx::query("DELETE FROM review_decisions WHERE uri = ANY($1)")
+            .bind(&uris[..])
+            .execute(&db.pool)
+            .await
+            .unwrap();
+        sqlx::query("DELETE FROM labels WHERE uri = $1")
+            .bind(&cleared)
+            .execute(&db.pool)
+            .await
+            .unwrap();
     }
 
-    #[test]
-    fn test_label_context_default() {
-        let ctx = LabelContext::default();
-        assert!(ctx.track_title.is_none());
-        assert!(ctx.resolution_reason.is_none());
-        assert!(ctx.resolution_notes.is_none());
+    /// Runs against `MODERATION_TEST_DATABASE_URL` when it's set; skipped
+    /// otherwise.
+    #[tokio::test]
+    async fn test_partial_context_update_keeps_other_fields() {
+        let Ok(url) = std::env::var("MODERATION_TEST_DATABASE_URL") else {
+            return;
+        };
+        let db = LabelDb::connect(&url).await.unwrap();
+        db.migrate().await.unwrap();
+
+        let uri = format!(
+            "at://did:plc:context{}/fm.plyr.track/1",
+            rand::random::<u32>()
+        );
+        db.store_context(
+            &uri,
+            &LabelContext {
+                track_id: Some(42),
+                track_title: Some("Old Title".to_string()),
+                artist_handle: Some("artist.bsky.social".to_string()),
+                highest_score: Some(0.9),
+                audio_url: Some("https://r2.plyr.fm/audio/a.mp3".to_string()),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+
+        db.store_context(
+            &uri,
+            &LabelContext {
+                track_title: Some("New Title".to_string()),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+
+        let context = db.get_context(&uri).await.unwrap().unwrap();
+        assert_eq!(context.track_title.as_deref(), Some("New Title"));
+        assert_eq!(context.track_id, Some(42));
+        assert_eq!(context.artist_handle.as_deref(), Some("artist.bsky.social"));
+        assert_eq!(context.highest_score, Some(0.9));
+        assert_eq!(
+            context.audio_url.as_deref(),
+            Some("https://r2.plyr.fm/audio/a.mp3")
+        );
+    }
+
+    /// Runs against `MODERATION_TEST_DATABASE_URL` when it's set; skipped
+    /// otherwise.
+    #[tokio::test]
+    async fn test_confirmed_batch_decision_is_recorded() {
+        let Ok(url) = std::env::var("MODERATION_TEST_DATABASE_URL") else {
+            return;
+        };
+        let db = LabelDb::connect(&url).await.unwrap();
+        db.migrate().await.unwrap();
+
+        let run = rand::random::<u32>();
+        let batch_id = format!("confirm-test-{run}");
+        let artist = format!("did:plc:confirm{run}");
+        let uri = format!("at://{artist}/fm.plyr.track/1");
+        sqlx::query(
+            "INSERT INTO labels (src, uri, val, neg, cts, sig) \
+             VALUES ('did:plc:labeler', $1, 'copyright-violation', false, NOW(), '\\x00')",
+        )
+        .bind(&uri)
+        .execute(&db.pool)
+        .await
+        .unwrap();
+        db.store_context(
+            &uri,
+            &LabelContext {
+                artist_did: Some(artist.clone()),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        db.create_batch(
+            &batch_id,
+            std::slice::from_ref(&uri),
+            None,
+            Utc::now() + chrono::Duration::days(1),
+        )
+        .await
+        .unwrap();
+
+        let confirmed_flags = || {
+            let (db, artist) = (&db, &artist);
+            async move {
+                db.get_flags_page(&FlagsPageQuery {
+                    status: FlagStatus::Confirmed,
+                    did: Some(artist.clone()),
+                    limit: 10,
+                    ..Default::default()
+                })
+                .await
+                .unwrap()
+                .total
+            }
+        };
+        assert_eq!(confirmed_flags().await, 0);
+
+        assert_eq!(
+            db.apply_batch_decision(&batch_id, &uri, "confirm", "alice", None, None)
+                .await
+                .unwrap(),
+            DecisionOutcome::Applied { seq: None }
+        );
+
+        let context = db.get_context(&uri).await.unwrap().unwrap();
+        assert_eq!(context.confirmed_by.as_deref(), Some("alice"));
+        assert!(context.confirmed_at.is_some());
+        assert_eq!(context.artist_did.as_deref(), Some(artist.as_str()));
+        assert_eq!(confirmed_flags().await, 1);
+
+        sqlx::query("DELETE FROM review_batches WHERE id = $1")
+            .bind(&batch_id)
+            .execute(&db.pool)
+            .await
+            .unwrap();
+        sqlx::query("DELETE FROM labels WHERE uri = $1")
+            .bind(&uri)
+            .execute(&db.pool)
+            .await
+            .unwrap();
+        sqlx::query("DELETE FROM review_decisions WHERE uri = $1")
+            .bind(&uri)
+            .execute(&db.pool)
+            .await
+            .unwrap();
+    }
+
+    /// Runs against `MODERATION_TEST_DATABASE_URL` when it's set; skipped
+    /// otherwise.
+    #[tokio::test]
+    async fn test_negation_clears_confirmation_before_reflag() {
+        let Ok(url) = std::env::var("MODERATION_TEST_DATABASE_URL") else {
+            return;
+        };
+        let db = LabelDb::connect(&url).await.unwrap();
+        db.migrate().await.unwrap();
+
+        let run = rand::random::<u32>();
+        let artist = format!("did:plc:reconfirm{run}");
+        let confirmed_flags = || {
+            let (db, artist) = (&db, &artist);
+            async move {
+                db.get_flags_page(&FlagsPageQuery {
+                    status: FlagStatus::Confirmed,
+                    did: Some(artist.clone()),
+                    limit: 10,
+                    ..Default::default()
+                })
+                .await
+                .unwrap()
+                .total
+            }
+        };
+
+        // negated with a reason, and without one (bulk resolves allow that)
+        for (n, reason) in [Some(ResolutionReason::Licensed), None].iter().enumerate() {
+            let uri = format!("at://{artist}/fm.plyr.track/{n}");
+            let label = Label::new("did:plc:labeler", &uri, "copyright-violation");
+            db.store_label(&label).await.unwrap();
+            db.store_context(
+                &uri,
+                &LabelContext {
+                    artist_did: Some(artist.clone()),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+            db.store_confirmation(&uri, "alice", None).await.unwrap();
+            assert_eq!(confirmed_flags().await, 1);
+
+            match reason {
+                Some(r) => {
+                    db.store_negation(&label.clone().negated(), Some(r), None, "bob")
+                        .await
+                        .unwrap();
+                }
+                None => {
+                    let negation = label.clone().negated();
+                    db.store_negations(std::slice::from_ref(&negation), None, None, false)
+                        .await
+                        .unwrap();
+                }
+            }
+            let context = db.get_context(&uri).await.unwrap().unwrap();
+            assert!(context.confirmed_by.is_none());
+            assert!(context.confirmed_at.is_none());
+
+            // flagged again, it needs a fresh confirmation
+            db.store_label(&label).await.unwrap();
+            assert_eq!(confirmed_flags().await, 0, "{reason:?}");
+        }
+    }
+
+    /// Runs against `MODERATION_TEST_DATABASE_URL` when it's set; skipped
+    /// otherwise.
+    #[tokio::test]
+    async fn test_single_flag_decisions_recorded_with_their_writes() {
+        let Ok(url) = std::env::var("MODERATION_TEST_DATABASE_URL") else {
+            return;
+        };
+        let db = LabelDb::connect(&url).await.unwrap();
+        db.migrate().await.unwrap();
+
+        let run = rand::random::<u32>();
+        let uri = format!("at://did:plc:single{run}/fm.plyr.track/1");
+        let label = Label::new("did:plc:labeler", &uri, "copyright-violation");
+        db.store_label(&label).await.unwrap();
+        db.store_confirmation(&uri, "alice", Some("clear rip"))
+            .await
+            .unwrap();
+        let seq = db
+            .store_negation(
+                &label.negated(),
+                Some(&ResolutionReason::Licensed),
+                Some("license on file"),
+                "bob",
+            )
+            .await
+            .unwrap();
+
+        let history = db
+            .get_review_decisions(std::slice::from_ref(&uri))
+            .await
+            .unwrap();
+        let decided: Vec<_> = history
+            .iter()
+            .map(|d| (d.decision.as_str(), d.actor.as_str(), d.notes.as_deref()))
+            .collect();
+        assert_eq!(
+            decided,
+            [
+                ("clear", "bob", Some("license on file")),
+                ("confirm", "alice", Some("clear rip")),
+            ]
+        );
+        assert!(history.iter().all(|d| d.batch_id.is_none()));
+        let latest = db
+            .get_latest_label("did:plc:labeler", &uri, "copyright-violation")
+            .await
+            .unwrap();
+        assert_eq!(latest.map(|row| (row.seq, row.neg)), Some((seq, true)));
+    }
+
+    /// Runs against `MODERATION_TEST_DATABASE_URL` when it's set; skipped
+    /// otherwise.
+    #[tokio::test]
+    async fn test_prod_label_on_dev_uri_negated_as_prod() {
+        let Ok(url) = std::env::var("MODERATION_TEST_DATABASE_URL") else {
+            return;
+        };
+        let db = LabelDb::connect(&url).await.unwrap();
+        db.migrate().await.unwrap();
+
+        let run = rand::random::<u32>();
+        let (prod, dev) = (format!("did:plc:prod{run}"), format!("did:plc:dev{run}"));
+        let signer = crate::labels::LabelSigner::from_hex(&format!("{:064x}", 1), &prod)
+            .unwrap()
+            .with_namespace_dids(vec![("fm.plyr.dev".to_string(), dev.clone())]);
+        let artist = format!("did:plc:nsartist{run}");
+        let flag = |n: u32| {
+            let uri = format!("at://{artist}/fm.plyr.dev.track/{n}");
+            Label::new(&prod, &uri, "copyright-violation")
+        };
+        let status_of = |uri: String| {
+            let (db, artist) = (&db, &artist);
+            async move {
+                let mut statuses = Vec::new();
+                for status in [FlagStatus::Pending, FlagStatus::Resolved] {
+                    let page = db
+                        .get_flags_page(&FlagsPageQuery {
+                            status,
+                            did: Some(artist.clone()),
+                            limit: 10,
+                            ..Default::default()
+                        })
+                        .await
+                        .unwrap();
+                    if page.tracks.iter().any(|t| t.uri == uri) {
+                        statuses.push(status);
+                    }
+                }
+                statuses
+            }
+        };
+
+        // flagged as prod before the dev namespace got its own DID
+        let (resolved, untouched) = (flag(1), flag(2));
+        for label in [&resolved, &untouched] {
+            db.store_label(label).await.unwrap();
+            db.store_context(
+                &label.uri,
+                &LabelContext {
+                    artist_did: Some(artist.clone()),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        }
+
+        let src = crate::admin::label_src(&db, &signer, &resolved.uri, "copyright-violation")
+            .await
+            .unwrap();
+        assert_eq!(src, prod);
+        let negation = Label::new(&src, &resolved.uri, "copyright-violation").negated();
+        let seq = db
+            .store_negation(&negation, Some(&ResolutionReason::Licensed), None, "alice")
+            .await
+            .unwrap();
+        assert_eq!(status_of(resolved.uri.clone()).await, [FlagStatus::Resolved]);
+        let latest = db
+            .get_latest_label_from(&signer.issuing_dids(), &resolved.uri, "copyright-violation")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!((latest.seq, latest.src.as_str(), latest.neg), (seq, prod.as_str(), true));
+
+        // a negation under the namespace DID doesn't clear the prod flag
+        let stray = Label::new(&dev, &untouched.uri, "copyright-violation").negated();
+        db.store_label(&stray).await.unwrap();
+        assert_eq!(status_of(untouched.uri.clone()).await, [FlagStatus::Pending]);
+
+        // with nothing in force, a new label goes out as the namespace DID
+        let src = crate::admin::label_src(&db, &signer, &resolved.uri, "copyright-violation")
+            .await
+            .unwrap();
+        assert_eq!(src, dev);
+    }
+
+    /// Runs against `MODERATION_TEST_DATABASE_URL` when it's set; skipped
+    /// otherwise.
+    #[tokio::test]
+    async fn test_reflag_after_negation_lists_once_as_pending() {
+        let Ok(url) = std::env::var("MODERATION_TEST_DATABASE_URL") else {
+            return;
+        };
+        let db = LabelDb::connect(&url).await.unwrap();
+        db.migrate().await.unwrap();
+
+        let artist = format!("did:plc:reflag{}", rand::random::<u32>());
+        let uri = format!("at://{artist}/fm.plyr.track/1");
+        // flag, negate, re-flag
+        for neg in [false, true, false] {
+            sqlx::query(
+                "INSERT INTO labels (src, uri, val, neg, cts, sig) \
+                 VALUES ('did:plc:labeler', $1, 'copyright-violation', $2, NOW(), '\\x00')",
+            )
+            .bind(&uri)
+            .bind(neg)
+            .execute(&db.pool)
+            .await
+            .unwrap();
+        }
+        db.store_context(
+            &uri,
+            &LabelContext {
+                artist_did: Some(artist.clone()),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+
+        let flags = |status: FlagStatus| {
+            let (db, artist) = (&db, &artist);
+            async move {
+                db.get_flags_page(&FlagsPageQuery {
+                    status,
+                    did: Some(artist.clone()),
+                    limit: 10,
+                    ..Default::default()
+                })
+                .await
+                .unwrap()
+            }
+        };
+        let latest_seq: i64 = sqlx::query_scalar("SELECT MAX(seq) FROM labels WHERE uri = $1")
+            .bind(&uri)
+            .fetch_one(&db.pool)
+            .await
+            .unwrap();
+
+        let pending = flags(FlagStatus::Pending).await;
+        assert_eq!(pending.total, 1);
+        assert_eq!(pending.tracks.len(), 1);
+        assert_eq!(pending.tracks[0].seq, latest_seq);
+        assert_eq!(pending.tracks[0].label_count, 2);
+        assert!(!pending.tracks[0].resolved);
+        assert_eq!(flags(FlagStatus::Resolved).await.total, 0);
+        assert_eq!(flags(FlagStatus::All).await.tracks.len(), 1);
+
+        sqlx::query("DELETE FROM labels WHERE uri = $1")
+            .bind(&uri)
+            .execute(&db.pool)
+            .await
+            .unwrap();
+        sqlx::query("DELETE FROM label_context WHERE uri = $1")
+            .bind(&uri)
+            .execute(&db.pool)
+            .await
+            .unwrap();
+    }
+
+    /// Runs against `MODERATION_TEST_DATABASE_URL` when it's set; skipped
+    /// otherwise.
+    #[tokio::test]
+    async fn test_label_for_closed_report_writes_nothing() {
+        let Ok(url) = std::env::var("MODERATION_TEST_DATABASE_URL") else {
+            return;
+        };
+        let db = LabelDb::connect(&url).await.unwrap();
+        db.migrate().await.unwrap();
+
+        let run = rand::random::<u32>();
+        let uri = format!("at://did:plc:closedreport{run}/fm.plyr.track/1");
+        let FiledReport::Created(report) = db
+            .file_report(
+                &format!("did:plc:reporter{run}"),
+                None,
+                "track",
+                &format!("closedreport{run}"),
+                None,
+                None,
+                Some(&uri),
+                "copyright",
+                None,
+                None,
+                None,
+            )
+            .await
+            .unwrap()
+        else {
+            panic!("expected a new report");
+        };
+        let label = Label::new("did:plc:labeler", &uri, "copyright-violation");
+
+        // dismissed after the handler looked: no label, no resolution
+        db.resolve_report(report.id, "dismissed", None, "bob")
+            .await
+            .unwrap();
+        assert!(db
+            .resolve_report_with_label(report.id, &label, None, "alice")
+            .await
+            .unwrap()
+            .is_none());
+        assert!(db
+            .get_active_label("did:plc:labeler", &uri, "copyright-violation")
+            .await
+            .unwrap()
+            .is_none());
+        let report = db.get_report(report.id).await.unwrap().unwrap();
+        assert_eq!(report.status, "dismissed");
+        assert_eq!(report.label_seq, None);
+    }
+
+    /// Runs against `MODERATION_TEST_DATABASE_URL` when it's set; skipped
+    /// otherwise.
+    #[tokio::test]
+    async fn test_report_callback_sent_once_and_delivery_recorded() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let Ok(url) = std::env::var("MODERATION_TEST_DATABASE_URL") else {
+            return;
+        };
+        let db = std::sync::Arc::new(LabelDb::connect(&url).await.unwrap());
+        db.migrate().await.unwrap();
+
+        let hits = std::sync::Arc::new(AtomicUsize::new(0));
+        let app = axum::Router::new().route(
+            "/reports/resolved",
+            axum::routing::post({
+                let hits = hits.clone();
+                move || {
+                    hits.fetch_add(1, Ordering::SeqCst);
+                    async { axum::http::StatusCode::NO_CONTENT }
+                }
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let callback_url = format!("http://{}/reports/resolved", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await });
+        let notifier = crate::report_callback::ReportCallbackNotifier::new(
+            crate::report_callback::ReportCallbackConfig {
+                url: callback_url,
+                key: None,
+            },
+        );
+
+        let run = rand::random::<u32>();
+        let FiledReport::Created(report) = db
+            .file_report(
+                &format!("did:plc:reporter{run}"),
+                None,
+                "track",
+                &format!("callbackonce{run}"),
+                None,
+                None,
+                None,
+                "spam",
+                None,
+                None,
+                None,
+            )
+            .await
+            .unwrap()
+        else {
+            panic!("expected a new report");
+        };
+        let delivered_at = || async {
+            sqlx::query_as::<_, (Option<String>, Option<DateTime<Utc>>)>(
+                "SELECT callback_status, callback_delivered_at FROM user_reports WHERE id = $1",
+            )
+            .bind(report.id)
+            .fetch_one(&db.pool)
+            .await
+            .unwrap()
+        };
+
+        let resolved = db
+            .resolve_report(report.id, "resolved", None, "alice")
+            .await
+            .unwrap()
+            .unwrap();
+        notifier.notify(db.clone(), &resolved).await.unwrap();
+        let mut state = delivered_at().await;
+        for _ in 0..50 {
+            if state.1.is_some() {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+            state = delivered_at().await;
+        }
+        assert_eq!(state.0.as_deref(), Some("sent"));
+        assert!(state.1.is_some());
+        assert_eq!(hits.load(Ordering::SeqCst), 1);
+
+        // closing it again doesn't notify the reporters twice
+        let dismissed = db
+            .resolve_report(report.id, "dismissed", None, "bob")
+            .await
+            .unwrap()
+            .unwrap();
+        notifier.notify(db.clone(), &dismissed).await.unwrap();
+        tokio::time::sleep(std::time::Duration::from_millis(300)).await;
+        assert_eq!(hits.load(Ordering::SeqCst), 1);
+        assert_eq!(delivered_at().await, state);
+    }
+
+    /// Runs against `MODERATION_TEST_DATABASE_URL` when it's set; skipped
+    /// otherwise.
+    #[tokio::test]
+    async fn test_pending_report_callbacks_claimed_once_lease_lapses() {
+        let Ok(url) = std::env::var("MODERATION_TEST_DATABASE_URL") else {
+            return;
+        };
+        let db = LabelDb::connect(&url).await.unwrap();
+        db.migrate().await.unwrap();
+
+        let run = rand::random::<u32>();
+        let FiledReport::Created(report) = db
+            .file_report(
+                &format!("did:plc:reporter{run}"),
+                None,
+                "track",
+                &format!("callbacklease{run}"),
+                None,
+                None,
+                None,
+                "spam",
+                None,
+                None,
+                None,
+            )
+            .await
+            .unwrap()
+        else {
+            panic!("expected a new report");
+        };
+        db.resolve_report(report.id, "resolved", None, "alice")
+            .await
+            .unwrap();
+        let lease = std::time::Duration::from_secs(60);
+        assert!(db.start_report_callback(report.id, lease).await.unwrap());
+        assert!(!db.start_report_callback(report.id, lease).await.unwrap());
+        let claimed = |claimed: Vec<UserReport>| claimed.iter().any(|r| r.id == report.id);
+
+        // still leased to the replica that started it
+        assert!(!claimed(db.claim_pending_report_callbacks(lease).await.unwrap()));
+
+        // that replica went away: the next claim takes it, and only once
+        sqlx::query(
+            "UPDATE user_reports SET callback_lease_until = NOW() - INTERVAL '1 second' WHERE id = $1",
+        )
+        .bind(report.id)
+        .execute(&db.pool)
+        .await
+        .unwrap();
+        let (first, second) = tokio::join!(
+            db.claim_pending_report_callbacks(lease),
+            db.claim_pending_report_callbacks(lease)
+        );
+        assert!(claimed(first.unwrap()) ^ claimed(second.unwrap()));
+        assert!(!claimed(db.claim_pending_report_callbacks(lease).await.unwrap()));
+
+        // once delivered it's never claimed again
+        db.record_report_callback_attempt(report.id, "sent", None, lease)
+            .await
+            .unwrap();
+        assert!(!claimed(db.claim_pending_report_callbacks(lease).await.unwrap()));
+    }
+
+    /// Runs against `MODERATION_TEST_DATABASE_URL` when it's set; skipped
+    /// otherwise.
+    #[tokio::test]
+    async fn test_report_pages_skip_reports_filed_mid_pagination() {
+        let Ok(url) = std::env::var("MODERATION_TEST_DATABASE_URL") else {
+            return;
+        };
+        let db = LabelDb::connect(&url).await.unwrap();
+        db.migrate().await.unwrap();
+
+        // a status no real report has keeps this run's rows to themselves
+        let status = format!("keyset-test-{}", rand::random::<u32>());
+        let file = |created_at: DateTime<Utc>| {
+            let (db, status) = (&db, &status);
+            async move {
+                sqlx::query_scalar::<_, i32>(
+                    "INSERT INTO user_reports (reporter_did, target_type, target_id, reason, status, created_at) \
+                     VALUES ('did:plc:reporter', 'track', '1', 'other', $1, $2) RETURNING id",
+                )
+                .bind(status)
+                .bind(created_at)
+                .fetch_one(&db.pool)
+                .await
+                .unwrap()
+            }
+        };
+
+        let base = Utc::now() - chrono::Duration::hours(1);
+        let mut seeded = Vec::new();
+        for minutes in [1, 2, 3, 3, 3, 4, 5] {
+            // three share a timestamp, so the id tiebreak is exercised
+            let created_at = base - chrono::Duration::minutes(minutes);
+            seeded.push((created_at, file(created_at).await));
+        }
+        seeded.sort_by(|a, b| b.cmp(a));
+        let expected: Vec<i32> = seeded.iter().map(|(_, id)| *id).collect();
+
+        let mut seen = Vec::new();
+        let mut cursor = None;
+        loop {
+            let filter = ReportFilter {
+                status: Some(&status),
+                target_type: Some("track"),
+                ..Default::default()
+            };
+            let page = db.list_reports(&filter, cursor, 3, 0).await.unwrap();
+            assert!(page.reports.len() <= 3);
+            seen.extend(page.reports.iter().map(|r| r.id));
+            let Some(next) = page.next_cursor else {
+                break;
+            };
+            cursor = Some(ReportsCursor::parse(&next).unwrap());
+            // new reports arrive between pages; with offset paging these
+            // would push already-seen rows onto the next page
+            file(Utc::now()).await;
+            file(base).await;
+        }
+
+        let unique: std::collections::HashSet<i32> = seen.iter().copied().collect();
+        assert_eq!(unique.len(), seen.len(), "duplicates in {seen:?}");
+        assert_eq!(seen, expected);
+
+        sqlx::query("DELETE FROM user_reports WHERE status = $1")
+            .bind(&status)
+            .execute(&db.pool)
+            .await
+            .unwrap();
+    }
+
+    /// Runs against `MODERATION_TEST_DATABASE_URL` when it's set; skipped
+    /// otherwise.
+    #[tokio::test]
+    async fn test_report_search_filters_compose() {
+        let Ok(url) = std::env::var("MODERATION_TEST_DATABASE_URL") else {
+            return;
+        };
+        let db = LabelDb::connect(&url).await.unwrap();
+        db.migrate().await.unwrap();
+
+        let run = rand::random::<u32>();
+        let reporter = format!("did:plc:search{run}");
+        let track = format!("search-{run}");
+        let uri = format!("at://did:plc:search{run}/fm.plyr.track/1");
+        let file = |target_type: &'static str,
+                    target_id: String,
+                    target_uri: Option<String>,
+                    status: &'static str,
+                    description: &'static str,
+                    admin_notes: Option<&'static str>| {
+            let (db, reporter) = (&db, &reporter);
+            async move {
+                sqlx::query_scalar::<_, i32>(
+                    "INSERT INTO user_reports \
+                     (reporter_did, target_type, target_id, target_uri, reason, status, description, admin_notes) \
+                     VALUES ($1, $2, $3, $4, 'copyright', $5, $6, $7) RETURNING id",
+                )
+                .bind(reporter)
+                .bind(target_type)
+                .bind(target_id)
+                .bind(target_uri)
+                .bind(status)
+                .bind(description)
+                .bind(admin_notes)
+                .fetch_one(&db.pool)
+                .await
+                .unwrap()
+            }
+        };
+        async fn ids(db: &LabelDb, filter: ReportFilter<'_>) -> Vec<i32> {
+            let mut ids: Vec<i32> = db
+                .list_reports(&filter, None, 100, 0)
+                .await
+                .unwrap()
+                .reports
+                .iter()
+                .map(|r| r.id)
+                .collect();
+            ids.sort();
+            ids
+        }
+
+        let ripped = file(
+            "track",
+            track.clone(),
+            Some(uri.clone()),
+            "open",
+            "this is a RIP of my single",
+            None,
+        )
+        .await;
+        let noted = file(
+            "track",
+            track.clone(),
+            Some(uri.clone()),
+            "resolved",
+            "stolen",
+            Some("confirmed rip, label sent"),
+        )
+        .await;
+        // target_id matches exactly, not as a prefix
+        file(
+            "track",
+            format!("{track}-other"),
+            None,
+            "open",
+            "another rip",
+            None,
+        )
+        .await;
+        // same id, different target type
+        let comment = file("comment", track.clone(), None, "open", "rip", None).await;
+
+        let by_track = ReportFilter {
+            target_type: Some("track"),
+            target_id: Some(&track),
+            ..Default::default()
+        };
+        assert_eq!(ids(&db, by_track).await, vec![ripped, noted]);
+        let by_uri = ReportFilter {
+            target_uri: Some(&uri),
+            ..Default::default()
+        };
+        assert_eq!(ids(&db, by_uri).await, vec![ripped, noted]);
+
+        // q matches the description or the admin notes, ignoring case
+        let by_text = ReportFilter {
+            target_id: Some(&track),
+            q: Some("rip"),
+            ..Default::default()
+        };
+        assert_eq!(ids(&db, by_text).await, vec![ripped, noted, comment]);
+        let open_text = ReportFilter {
+            status: Some("open"),
+            target_type: Some("track"),
+            target_id: Some(&track),
+            q: Some("rip"),
+            ..Default::default()
+        };
+        assert_eq!(ids(&db, open_text).await, vec![ripped]);
+        let notes_only = ReportFilter {
+            target_uri: Some(&uri),
+            q: Some("label sent"),
+            ..Default::default()
+        };
+        assert_eq!(ids(&db, notes_only).await, vec![noted]);
+        // wildcards in q are literal
+        let wildcard = ReportFilter {
+            target_id: Some(&track),
+            q: Some("%"),
+            ..Default::default()
+        };
+        assert!(ids(&db, wildcard).await.is_empty());
+
+        sqlx::query("DELETE FROM user_reports WHERE reporter_did = $1")
+            .bind(&reporter)
+            .execute(&db.pool)
+            .await
+            .unwrap();
+    }
+
+    /// Runs against `MODERATION_TEST_DATABASE_URL` when it's set; skipped
+    /// otherwise.
+    #[tokio::test]
+    async fn test_reporter_reports() {
+        let Ok(url) = std::env::var("MODERATION_TEST_DATABASE_URL") else {
+            return;
+        };
+        let db = LabelDb::connect(&url).await.unwrap();
+        db.migrate().await.unwrap();
+
+        let run = rand::random::<u32>();
+        let reporter = format!("did:plc:history{run}");
+        let other = format!("did:plc:history{run}-other");
+        let file = |reporter: String, reason: &'static str, status: &'static str| {
+            let db = &db;
+            async move {
+                sqlx::query_scalar::<_, i32>(
+                    "INSERT INTO user_reports (reporter_did, target_type, target_id, reason, status) \
+                     VALUES ($1, 'track', '1', $2, $3) RETURNING id",
+                )
+                .bind(reporter)
+                .bind(reason)
+                .bind(status)
+                .fetch_one(&db.pool)
+                .await
+                .unwrap()
+            }
+        };
+
+        file(reporter.clone(), "spam", "dismissed").await;
+        file(reporter.clone(), "spam", "dismissed").await;
+        let newest = file(reporter.clone(), "copyright", "open").await;
+        let joined = file(other.clone(), "copyright", "open").await;
+        sqlx::query("INSERT INTO report_supporters (report_id, reporter_did) VALUES ($1, $2)")
+            .bind(joined)
+            .bind(&reporter)
+            .execute(&db.pool)
+            .await
+            .unwrap();
+
+        let reports = db.get_reporter_reports(&reporter, 2).await.unwrap();
+        let counts: Vec<(&str, &str, i64)> = reports
+            .counts
+            .iter()
+            .map(|c| (c.reason.as_str(), c.status.as_str(), c.count))
+            .collect();
+        assert_eq!(
+            counts,
+            vec![("copyright", "open", 1), ("spam", "dismissed", 2)]
+        );
+        assert_eq!(reports.supported, 1);
+        assert_eq!(reports.recent.len(), 2);
+        assert_eq!(reports.recent[0].id, newest);
+
+        let summary = db.get_reporter_reports(&reporter, 0).await.unwrap();
+        assert!(summary.recent.is_empty());
+
+        sqlx::query("DELETE FROM user_reports WHERE reporter_did = ANY($1)")
+            .bind(vec![reporter, other])
+            .execute(&db.pool)
+            .await
+            .unwrap();
+    }
+
+    /// Runs against `MODERATION_TEST_DATABASE_URL` when it's set; skipped
+    /// otherwise.
+    #[tokio::test]
+    async fn test_replace_reporters() {
+        let Ok(url) = std::env::var("MODERATION_TEST_DATABASE_URL") else {
+            return;
+        };
+        let db = LabelDb::connect(&url).await.unwrap();
+        db.migrate().await.unwrap();
+
+        let run = rand::random::<u32>();
+        let reporter = format!("did:plc:hashme{run}");
+        let supporter = format!("did:plc:hashme{run}-supporter");
+        let id = sqlx::query_scalar::<_, i32>(
+            "INSERT INTO user_reports (reporter_did, reporter_handle, target_type, target_id, reason) \
+             VALUES ($1, 'someone.bsky.social', 'track', '1', 'spam') RETURNING id",
+        )
+        .bind(&reporter)
+        .fetch_one(&db.pool)
+        .await
+        .unwrap();
+        sqlx::query("INSERT INTO report_supporters (report_id, reporter_did) VALUES ($1, $2)")
+            .bind(id)
+            .bind(&supporter)
+            .execute(&db.pool)
+            .await
+            .unwrap();
+
+        let unhashed = db.list_unhashed_reporters().await.unwrap();
+        assert!(unhashed.contains(&reporter) && unhashed.contains(&supporter));
+
+        let mapping = vec![
+            (reporter.clone(), format!("hash:{run}a")),
+            (supporter.clone(), format!("hash:{run}b")),
+        ];
+        assert_eq!(db.replace_reporters(&mapping).await.unwrap(), (1, 1));
+        let report = db.get_report(id).await.unwrap().unwrap();
+        assert_eq!(report.reporter_did, format!("hash:{run}a"));
+        assert_eq!(report.reporter_handle, None);
+        assert_eq!(
+            db.get_report_supporters(id).await.unwrap(),
+            vec![format!("hash:{run}b")]
+        );
+
+        let unhashed = db.list_unhashed_reporters().await.unwrap();
+        assert!(!unhashed.contains(&reporter) && !unhashed.contains(&supporter));
+        // running it again changes nothing
+        assert_eq!(db.replace_reporters(&mapping).await.unwrap(), (0, 0));
+
+        sqlx::query("DELETE FROM user_reports WHERE id = $1")
+            .bind(id)
+            .execute(&db.pool)
+            .await
+            .unwrap();
+    }
+
+    /// Runs against `MODERATION_TEST_DATABASE_URL` when it's set; skipped
+    /// otherwise.
+    #[tokio::test]
+    async fn test_report_window_stats() {
+        let Ok(url) = std::env::var("MODERATION_TEST_DATABASE_URL") else {
+            return;
+        };
+        let db = LabelDb::connect(&url).await.unwrap();
+        db.migrate().await.unwrap();
+
+        // a random day decades back keeps this run's window to itself
+        let from = NaiveDate::from_ymd_opt(1990, 1, 1).unwrap()
+            + chrono::Days::new(u64::from(rand::random::<u32>() % 10_000));
+        let to = from + chrono::Days::new(2);
+        let noon = from.and_hms_opt(12, 0, 0).unwrap().and_utc();
+        // (reason, target type, status, hours until closed)
+        let reports = [
+            ("copyright", "track", "resolved", Some(2)),
+            ("copyright", "track", "dismissed", Some(4)),
+            ("spam", "comment", "resolved", Some(30)),
+            ("spam", "track", "open", None),
+        ];
+        for (reason, target_type, status, hours) in reports {
+            sqlx::query(
+                "INSERT INTO user_reports (reporter_did, target_type, target_id, reason, status, created_at, resolved_at) \
+                 VALUES ('did:plc:reporter', $1, '1', $2, $3, $4, $5)",
+            )
+            .bind(target_type)
+            .bind(reason)
+            .bind(status)
+            .bind(noon)
+            .bind(hours.map(|h| noon + chrono::Duration::hours(h)))
+            .execute(&db.pool)
+            .await
+            .unwrap();
+        }
+
+        let stats = db.report_window_stats(from, to).await.unwrap();
+        assert_eq!(stats.filed, 4);
+        assert_eq!(stats.by_reason["copyright"], 2);
+        assert_eq!(stats.by_reason["spam"], 2);
+        assert_eq!(stats.by_target_type["track"], 3);
+        assert_eq!(stats.by_target_type["comment"], 1);
+        assert_eq!(stats.closed, 3);
+        assert_eq!(stats.dismissed, 1);
+        assert_eq!(stats.dismissal_rate, Some(1.0 / 3.0));
+        assert_eq!(stats.median_resolution_secs, Some(4.0 * 3600.0));
+        let per_day: Vec<(i64, i64)> = stats.per_day.iter().map(|d| (d.filed, d.closed)).collect();
+        assert_eq!(per_day, [(4, 2), (0, 1), (0, 0)]);
+
+        // a window with nothing closed has no rate or median
+        let before = from - chrono::Days::new(1);
+        let stats = db.report_window_stats(before, before).await.unwrap();
+        assert_eq!(stats.closed, 0);
+        assert_eq!(stats.dismissal_rate, None);
+        assert_eq!(stats.median_resolution_secs, None);
+
+        sqlx::query("DELETE FROM user_reports WHERE created_at = $1")
+            .bind(noon)
+            .execute(&db.pool)
+            .await
+            .unwrap();
     }
 }
@@ -0,0 +1,304 @@
+//! Labeler DID document health check.
+//!
+//! Bluesky only trusts our labels if the labeler DID resolves and its
+//! `#atproto_label` verification method matches the key we sign with. A new
+//! signing key deployed without updating the DID document breaks that
+//! silently, so `/admin/health` resolves the DID and compares the keys.
+//! Resolutions are cached for a few minutes.
+
+use std::future::Future;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use axum::{
+    extract::State,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use tokio::sync::Mutex;
+
+use crate::state::{AppError, AppState};
+
+/// How long a resolved DID document is trusted before resolving again.
+const DID_CACHE_TTL: Duration = Duration::from_secs(300);
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Verification method id fragment for the label signing key.
+const LABEL_KEY_FRAGMENT: &str = "#atproto_label";
+
+/// DID resolution errors.
+#[derive(Debug, thiserror::Error)]
+pub enum DidError {
+    #[error("unsupported DID method: {0}")]
+    UnsupportedMethod(String),
+
+    #[error("failed to fetch DID document: {0}")]
+    Fetch(#[from] reqwest::Error),
+}
+
+/// Outcome of comparing the published label key with our signing key.
+#[derive(Debug, Clone, Serialize)]
+pub struct DidCheck {
+    pub did: String,
+    pub ok: bool,
+    /// Key derived from the configured signing key.
+    pub expected_key: String,
+    /// `#atproto_label` key from the DID document, if listed.
+    pub published_key: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub resolved_at: DateTime<Utc>,
+}
+
+impl DidCheck {
+    fn new(did: &str, expected_key: &str, published_key: Option<String>) -> Self {
+        let error = match &published_key {
+            None => Some(format!(
+                "DID document for {did} has no {LABEL_KEY_FRAGMENT} verification method"
+            )),
+            Some(key) if key != expected_key => Some(format!(
+                "{LABEL_KEY_FRAGMENT} key mismatch: DID document publishes {key}, \
+                 signing key is {expected_key}"
+            )),
+            Some(_) => None,
+        };
+        Self {
+            did: did.to_string(),
+            ok: error.is_none(),
+            expected_key: expected_key.to_string(),
+            published_key,
+            error,
+            resolved_at: Utc::now(),
+        }
+    }
+}
+
+/// Resolves the labeler DID, caching the latest check.
+pub struct DidResolver {
+    http: reqwest::Client,
+    plc_url: String,
+    cache: Mutex<Option<(Instant, Arc<DidCheck>)>>,
+}
+
+impl DidResolver {
+    pub fn new(plc_url: impl Into<String>) -> Self {
+        let http = reqwest::Client::builder()
+            .timeout(REQUEST_TIMEOUT)
+            .build()
+            .unwrap_or_default();
+        Self {
+            http,
+            plc_url: plc_url.into(),
+            cache: Mutex::new(None),
+        }
+    }
+
+    /// Check the published label key for `did`, using the cached result if
+    /// it is still fresh.
+    pub async fn check(&self, did: &str, expected_key: &str) -> Result<Arc<DidCheck>, DidError> {
+        self.get_or_refresh(Instant::now(), || async move {
+            let doc = self.resolve(did).await?;
+            Ok(DidCheck::new(did, expected_key, label_key(&doc)))
+        })
+        .await
+    }
+
+    /// Fetch the DID document for a did:plc or did:web identifier.
+    async fn resolve(&self, did: &str) -> Result<serde_json::Value, DidError> {
+        let url = did_document_url(&self.plc_url, did)?;
+        let doc = self
+            .http
+            .get(url)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        Ok(doc)
+    }
+
+    /// Failed resolutions are not cached, so the next request retries.
+    async fn get_or_refresh<F, Fut>(
+        &self,
+        now: Instant,
+        refresh: F,
+    ) -> Result<Arc<DidCheck>, DidError>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<DidCheck, DidError>>,
+    {
+        let mut entry = self.cache.lock().await;
+        if let Some((resolved_at, check)) = entry.as_ref() {
+            if now.saturating_duration_since(*resolved_at) < DID_CACHE_TTL {
+                return Ok(check.clone());
+            }
+        }
+        let check = Arc::new(refresh().await?);
+        *entry = Some((now, check.clone()));
+        Ok(check)
+    }
+}
+
+/// Response for the admin health check.
+#[derive(Debug, Serialize)]
+pub struct AdminHealthResponse {
+    pub status: &'static str,
+    pub labeler_enabled: bool,
+    pub did: DidHealth,
+}
+
+/// DID sub-check: the key comparison, or why the DID couldn't be resolved.
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+pub enum DidHealth {
+    Checked(Arc<DidCheck>),
+    Unresolved {
+        did: String,
+        ok: bool,
+        error: String,
+    },
+}
+
+/// Health check including DID resolution. Returns 503 when the DID can't be
+/// resolved or doesn't publish our signing key.
+pub async fn admin_health(State(state): State<AppState>) -> Result<Response, AppError> {
+    let signer = state
+        .signer
+        .as_ref()
+        .ok_or(AppError::LabelerNotConfigured)?;
+    let did = match state
+        .did_resolver
+        .check(signer.did(), &signer.public_key_multibase())
+        .await
+    {
+        Ok(check) => DidHealth::Checked(check),
+        Err(e) => DidHealth::Unresolved {
+            did: signer.did().to_string(),
+            ok: false,
+            error: e.to_string(),
+        },
+    };
+    let healthy = matches!(&did, DidHealth::Checked(check) if check.ok);
+    let (code, status) = if healthy {
+        (StatusCode::OK, "ok")
+    } else {
+        (StatusCode::SERVICE_UNAVAILABLE, "degraded")
+    };
+    let body = AdminHealthResponse {
+        status,
+        labeler_enabled: state.db.is_some(),
+        did,
+    };
+    Ok((code, Json(body)).into_response())
+}
+
+/// Where to fetch the DID document: the PLC directory for did:plc, the
+/// well-known path for did:web.
+fn did_document_url(plc_url: &str, did: &str) -> Result<String, DidError> {
+    if did.starts_with("did:plc:") {
+        Ok(format!("{}/{did}", plc_url.trim_end_matches('/')))
+    } else if let Some(host) = did.strip_prefix("did:web:") {
+        // ports are percent-encoded in did:web identifiers
+        Ok(format!(
+            "https://{}/.well-known/did.json",
+            host.replace("%3A", ":")
+        ))
+    } else {
+        Err(DidError::UnsupportedMethod(did.to_string()))
+    }
+}
+
+/// The `publicKeyMultibase` of the `#atproto_label` verification method.
+fn label_key(doc: &serde_json::Value) -> Option<String> {
+    doc.get("verificationMethod")?
+        .as_array()?
+        .iter()
+        .find(|method| {
+            method
+                .get("id")
+                .and_then(|id| id.as_str())
+                .is_some_and(|id| id.ends_with(LABEL_KEY_FRAGMENT))
+        })?
+        .get("publicKeyMultibase")?
+        .as_str()
+        .map(String::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DID: &str = "did:plc:labeler";
+    const KEY: &str = "zQ3shVc2UkAfJCdc1TR8E66J85h48P43r93q8jGPkPpjF9Ef9";
+
+    fn doc(methods: serde_json::Value) -> serde_json::Value {
+        serde_json::json!({ "id": DID, "verificationMethod": methods })
+    }
+
+    #[test]
+    fn test_did_document_url() {
+        assert_eq!(
+            did_document_url("https://plc.directory/", DID).unwrap(),
+            "https://plc.directory/did:plc:labeler"
+        );
+        assert_eq!(
+            did_document_url("https://plc.directory", "did:web:mod.plyr.fm%3A8443").unwrap(),
+            "https://mod.plyr.fm:8443/.well-known/did.json"
+        );
+        assert!(did_document_url("https://plc.directory", "did:key:z123").is_err());
+    }
+
+    #[test]
+    fn test_label_key_picks_atproto_label() {
+        let doc = doc(serde_json::json!([
+            { "id": "did:plc:labeler#atproto", "publicKeyMultibase": "zSigning" },
+            { "id": "did:plc:labeler#atproto_label", "publicKeyMultibase": KEY },
+        ]));
+        assert_eq!(label_key(&doc).as_deref(), Some(KEY));
+        assert_eq!(label_key(&serde_json::json!({ "id": DID })), None);
+    }
+
+    #[test]
+    fn test_check_reports_mismatch() {
+        let check = DidCheck::new(DID, KEY, Some(KEY.to_string()));
+        assert!(check.ok);
+        assert!(check.error.is_none());
+
+        let check = DidCheck::new(DID, KEY, Some("zOldKey".to_string()));
+        assert!(!check.ok);
+        assert!(check.error.unwrap().contains("key mismatch"));
+
+        let check = DidCheck::new(DID, KEY, None);
+        assert!(!check.ok);
+        assert!(check.error.unwrap().contains("no #atproto_label"));
+    }
+
+    #[tokio::test]
+    async fn test_resolution_cached_until_ttl() {
+        let resolver = DidResolver::new("https://plc.directory");
+        let start = Instant::now();
+        let first = resolver
+            .get_or_refresh(start, || async { Ok(DidCheck::new(DID, KEY, None)) })
+            .await
+            .unwrap();
+        let cached = resolver
+            .get_or_refresh(start + Duration::from_secs(60), || async {
+                Ok(DidCheck::new(DID, KEY, Some(KEY.to_string())))
+            })
+            .await
+            .unwrap();
+        assert!(Arc::ptr_eq(&first, &cached));
+
+        let refreshed = resolver
+            .get_or_refresh(start + DID_CACHE_TTL, || async {
+                Ok(DidCheck::new(DID, KEY, Some(KEY.to_string())))
+            })
+            .await
+            .unwrap();
+        assert!(refreshed.ok);
+    }
+}
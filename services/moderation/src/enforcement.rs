@@ -0,0 +1,276 @@
+//! Enforcement callbacks for confirmed copyright violations.
+//!
+//! When a reviewer confirms a flag, the backend is POSTed the track so it can
+//! hide or take it down. Every attempt is written to the audit log and the
+//! latest outcome to `enforcement_requests`, which the flag cards read.
+//! Deliveries retry with backoff, and requests still pending when the service
+//! stops are resumed on the next start.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde::Serialize;
+use tracing::{info, warn};
+
+use crate::db::{EnforcementRequestRow, LabelDb};
+use crate::state::AppState;
+use crate::webhook::retry_delay;
+
+/// Header carrying the shared key so the backend can verify the caller.
+pub const ENFORCEMENT_KEY_HEADER: &str = "X-Moderation-Enforcement-Key";
+
+/// Delivery attempts before marking an enforcement request as failed.
+const MAX_ATTEMPTS: u32 = 5;
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Enforcement callback settings from config.
+#[derive(Debug, Clone)]
+pub struct EnforcementConfig {
+    pub url: String,
+    pub key: Option<String>,
+}
+
+/// Where an enforcement request stands.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EnforcementStatus {
+    /// Not yet accepted by the backend; still retrying.
+    Pending,
+    /// The backend accepted the request.
+    Sent,
+    /// Every attempt failed.
+    Failed,
+}
+
+impl EnforcementStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Pending => "pending",
+            Self::Sent => "sent",
+            Self::Failed => "failed",
+        }
+    }
+
+    /// Parse from string.
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "pending" => Some(Self::Pending),
+            "sent" => Some(Self::Sent),
+            "failed" => Some(Self::Failed),
+            _ => None,
+        }
+    }
+}
+
+/// Body POSTed to the enforcement URL.
+#[derive(Debug, Clone, Serialize)]
+pub struct EnforcementRequest {
+    pub uri: String,
+    pub track_id: Option<i64>,
+    /// Who confirmed the violation.
+    pub confirmed_by: String,
+}
+
+impl From<EnforcementRequestRow> for EnforcementRequest {
+    fn from(row: EnforcementRequestRow) -> Self {
+        Self {
+            uri: row.uri,
+            track_id: row.track_id,
+            confirmed_by: row.confirmed_by,
+        }
+    }
+}
+
+/// Sends enforcement callbacks to the backend.
+pub struct EnforcementNotifier {
+    config: EnforcementConfig,
+    http: reqwest::Client,
+}
+
+impl EnforcementNotifier {
+    pub fn new(config: EnforcementConfig) -> Self {
+        let http = reqwest::Client::builder()
+            .timeout(REQUEST_TIMEOUT)
+            .build()
+            .unwrap_or_default();
+        Self { config, http }
+    }
+
+    /// Record an enforcement request as pending and start delivering it.
+    ///
+    /// Does nothing if a delivery for the URI is already in progress.
+    pub async fn enforce(
+        &self,
+        db: Arc<LabelDb>,
+        request: EnforcementRequest,
+    ) -> Result<(), sqlx::Error> {
+        let started = db
+            .start_enforcement(&request.uri, request.track_id, &request.confirmed_by)
+            .await?;
+        if started {
+            self.spawn_delivery(db, request);
+        } else {
+            info!(uri = %request.uri, "enforcement already pending");
+        }
+        Ok(())
+    }
+
+    /// Restart deliveries left pending by a previous run.
+    pub async fn resume_pending(&self, db: Arc<LabelDb>) -> Result<usize, sqlx::Error> {
+        let pending = db.get_pending_enforcements().await?;
+        let count = pending.len();
+        for row in pending {
+            self.spawn_delivery(db.clone(), row.into());
+        }
+        Ok(count)
+    }
+
+    fn spawn_delivery(&self, db: Arc<LabelDb>, request: EnforcementRequest) {
+        let http = self.http.clone();
+        let config = self.config.clone();
+        tokio::spawn(async move {
+            deliver(&http, &config, &db, &request).await;
+        });
+    }
+}
+
+/// Ask the backend to enforce a confirmed flag, if enforcement is configured.
+///
+/// Returns the new status, or `None` when no enforcement URL is set.
+pub async fn enforce_confirmed(
+    state: &AppState,
+    db: &Arc<LabelDb>,
+    uri: &str,
+    confirmed_by: &str,
+) -> Result<Option<EnforcementStatus>, sqlx::Error> {
+    let Some(notifier) = &state.enforcement else {
+        return Ok(None);
+    };
+    let track_id = db.get_context(uri).await?.and_then(|c| c.track_id);
+    notifier
+        .enforce(
+            db.clone(),
+            EnforcementRequest {
+                uri: uri.to_string(),
+                track_id,
+                confirmed_by: confirmed_by.to_string(),
+            },
+        )
+        .await?;
+    Ok(Some(EnforcementStatus::Pending))
+}
+
+/// POST the request until the backend accepts it or attempts run out,
+/// recording each attempt.
+async fn deliver(
+    http: &reqwest::Client,
+    config: &EnforcementConfig,
+    db: &LabelDb,
+    request: &EnforcementRequest,
+) {
+    let uri = request.uri.as_str();
+    for attempt in 1..=MAX_ATTEMPTS {
+        let mut post = http.post(&config.url).json(request);
+        if let Some(key) = &config.key {
+            post = post.header(ENFORCEMENT_KEY_HEADER, key);
+        }
+        let result = post.send().await.map(|response| response.status());
+
+        let response_status = result.as_ref().ok().map(|s| i32::from(s.as_u16()));
+        let delivered = result.as_ref().is_ok_and(|s| s.is_success());
+        let status = attempt_status(delivered, attempt);
+
+        let details = serde_json::json!({
+            "attempt": attempt,
+            "track_id": request.track_id,
+            "status": status.as_str(),
+            "response_status": response_status,
+            "error": result.as_ref().err().map(|e| e.to_string()),
+        });
+        if let Err(e) = db
+            .record_audit_event("enforcement_attempt", uri, &request.confirmed_by, details)
+            .await
+        {
+            warn!(uri, error = %e, "failed to audit enforcement attempt");
+        }
+        if let Err(e) = db
+            .record_enforcement_attempt(uri, status.as_str(), response_status)
+            .await
+        {
+            warn!(uri, error = %e, "failed to record enforcement status");
+        }
+
+        match status {
+            EnforcementStatus::Sent => {
+                info!(uri, attempt, "enforcement sent");
+                return;
+            }
+            EnforcementStatus::Failed => {
+                warn!(uri, attempt, "giving up on enforcement");
+                return;
+            }
+            EnforcementStatus::Pending => {
+                warn!(uri, attempt, status = ?response_status, "enforcement attempt failed");
+                tokio::time::sleep(retry_delay(attempt)).await;
+            }
+        }
+    }
+}
+
+/// Status after an attempt: sent on success, failed once attempts run out.
+fn attempt_status(delivered: bool, attempt: u32) -> EnforcementStatus {
+    if delivered {
+        EnforcementStatus::Sent
+    } else if attempt >= MAX_ATTEMPTS {
+        EnforcementStatus::Failed
+    } else {
+        EnforcementStatus::Pending
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_attempt_status() {
+        assert_eq!(attempt_status(true, 1), EnforcementStatus::Sent);
+        assert_eq!(attempt_status(false, 1), EnforcementStatus::Pending);
+        assert_eq!(attempt_status(true, MAX_ATTEMPTS), EnforcementStatus::Sent);
+        assert_eq!(
+            attempt_status(false, MAX_ATTEMPTS),
+            EnforcementStatus::Failed
+        );
+    }
+
+    #[test]
+    fn test_status_round_trips() {
+        for status in [
+            EnforcementStatus::Pending,
+            EnforcementStatus::Sent,
+            EnforcementStatus::Failed,
+        ] {
+            assert_eq!(EnforcementStatus::from_str(status.as_str()), Some(status));
+        }
+        assert_eq!(EnforcementStatus::from_str("queued"), None);
+    }
+
+    #[test]
+    fn test_request_body() {
+        let body = serde_json::to_value(EnforcementRequest::from(EnforcementRequestRow {
+            uri: "at://did:plc:artist/fm.plyr.track/abc".to_string(),
+            track_id: Some(42),
+            confirmed_by: "alice".to_string(),
+        }))
+        .unwrap();
+
+        assert_eq!(
+            body,
+            serde_json::json!({
+                "uri": "at://did:plc:artist/fm.plyr.track/abc",
+                "track_id": 42,
+                "confirmed_by": "alice",
+            })
+        );
+    }
+}
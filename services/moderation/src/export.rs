@@ -0,0 +1,383 @@
+//! Moderation state snapshots, for backups and migrating between databases.
+//!
+//! `GET /admin/export` streams labels, label contexts (resolutions included),
+//! sensitive images and user reports as newline-delimited JSON: a header
+//! line, then one [`ExportRecord`] per line. Tables are read in keyset pages
+//! straight into the response, so the snapshot is never held in memory, and
+//! all pages come from one `REPEATABLE READ` transaction, so rows written
+//! mid-export can't leave it inconsistent (a context without its label, a
+//! page boundary that skips a row).
+//! `POST /admin/import` reads the same document back in batches; see
+//! [`LabelDb::import_records`] for why importing it twice is harmless.
+
+use axum::{
+    body::Body,
+    extract::State,
+    http::header::{CONTENT_DISPOSITION, CONTENT_TYPE},
+    response::{IntoResponse, Response},
+    Json,
+};
+use chrono::Utc;
+use futures::StreamExt;
+use serde::Serialize;
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+use tracing::info;
+
+use crate::auth::{Actor, RequireAdmin};
+use crate::db::{ExportRecord, ImportCounts, LabelDb};
+use crate::ratelimit::RateLimited;
+use crate::state::{AppError, AppState};
+
+/// Snapshot format written by this build; imports of any other are refused.
+pub const EXPORT_VERSION: u32 = 1;
+
+/// Rows read from the database per query while exporting.
+const EXPORT_PAGE_SIZE: i64 = 500;
+
+/// Lines buffered between the database reader and the response body.
+const EXPORT_BUFFER: usize = 64;
+
+/// Records written per import transaction.
+const IMPORT_BATCH_SIZE: usize = 500;
+
+/// Longest line accepted on import. Records are small; this only stops a
+/// body without newlines from being buffered whole.
+const MAX_IMPORT_LINE_BYTES: usize = 1024 * 1024;
+
+type LineSender = mpsc::Sender<Result<String, sqlx::Error>>;
+
+/// Stream a snapshot of all moderation state as newline-delimited JSON.
+///
+/// GET /admin/export
+pub async fn export_state(
+    State(state): State<AppState>,
+    _: RequireAdmin,
+    actor: Actor,
+) -> Result<Response, AppError> {
+    let db = state.db.clone().ok_or(AppError::LabelerNotConfigured)?;
+
+    // the snapshot holds every report and reporter DID, so who took one is
+    // worth keeping
+    db.record_audit_event(
+        "export_state",
+        "moderation-state",
+        actor.as_str(),
+        serde_json::json!({ "version": EXPORT_VERSION }),
+    )
+    .await?;
+
+    let (tx, rx) = mpsc::channel(EXPORT_BUFFER);
+    tokio::spawn(async move {
+        if let Err(e) = write_snapshot(&db, &tx).await {
+            tracing::error!(error = %e, "moderation export failed");
+            // aborts the response body so the download doesn't look complete
+            let _ = tx.send(Err(e)).await;
+        }
+    });
+
+    Ok((
+        [
+            (CONTENT_TYPE, "application/x-ndjson"),
+            (
+                CONTENT_DISPOSITION,
+                "attachment; filename=\"moderation-export.ndjson\"",
+            ),
+        ],
+        Body::from_stream(ReceiverStream::new(rx)),
+    )
+        .into_response())
+}
+
+/// Write every table to `tx`, a page at a time. Stops early (without an
+/// error) when the client goes away.
+async fn write_snapshot(db: &LabelDb, tx: &LineSender) -> Result<(), sqlx::Error> {
+    let mut snapshot = db.export_snapshot().await?;
+    let header = ExportRecord::Header {
+        version: EXPORT_VERSION,
+        exported_at: Utc::now(),
+    };
+    if !send(tx, &header).await {
+        return Ok(());
+    }
+
+    let mut after_seq = 0;
+    loop {
+        let page = snapshot.labels(after_seq, EXPORT_PAGE_SIZE).await?;
+        let Some(last) = page.last() else { break };
+        after_seq = last.seq;
+        for label in page {
+            if !send(tx, &ExportRecord::Label(label)).await {
+                return Ok(());
+            }
+        }
+    }
+
+    let mut after_uri = String::new();
+    loop {
+        let page = snapshot
+            .label_contexts(&after_uri, EXPORT_PAGE_SIZE)
+            .await?;
+        let Some(last) = page.last() else { break };
+        after_uri = last.uri.clone();
+        for context in page {
+            if !send(tx, &ExportRecord::LabelContext(context)).await {
+                return Ok(());
+            }
+        }
+    }
+
+    let mut after_id = 0;
+    loop {
+        let page = snapshot
+            .sensitive_images(after_id, EXPORT_PAGE_SIZE)
+            .await?;
+        let Some(last) = page.last() else { break };
+        after_id = last.id;
+        for image in page {
+            if !send(tx, &ExportRecord::SensitiveImage(image)).await {
+                return Ok(());
+            }
+        }
+    }
+
+    let mut after_id = 0;
+    loop {
+        let page = snapshot.reports(after_id, EXPORT_PAGE_SIZE).await?;
+        let Some(last) = page.last() else { break };
+        after_id = last.report.id;
+        for report in page {
+            if !send(tx, &ExportRecord::Report(report)).await {
+                return Ok(());
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Send one record as a line. False once the receiver is gone.
+async fn send(tx: &LineSender, record: &ExportRecord) -> bool {
+    let mut line = serde_json::to_string(record).expect("export records serialize");
+    line.push('\n');
+    tx.send(Ok(line)).await.is_ok()
+}
+
+/// Response after an import.
+#[derive(Debug, Serialize)]
+pub struct ImportResponse {
+    pub imported: ImportCounts,
+}
+
+/// Restore a snapshot written by `/admin/export`.
+///
+/// Records are written in batches of [`IMPORT_BATCH_SIZE`], each in its own
+/// transaction. If a line fails to parse, batches before it stay written;
+/// since importing is idempotent, fix the file and import it again.
+///
+/// POST /admin/import
+pub async fn import_state(
+    State(state): State<AppState>,
+    _: RequireAdmin,
+    _: RateLimited,
+    actor: Actor,
+    body: Body,
+) -> Result<Json<ImportResponse>, AppError> {
+    let db = state.db.as_ref().ok_or(AppError::LabelerNotConfigured)?;
+
+    let mut importer = Importer::new(db);
+    let mut lines = LineBuffer::default();
+    let mut stream = body.into_data_stream();
+    while let Some(chunk) = stream.next().await {
+        let chunk = chunk.map_err(|e| AppError::BadRequest(format!("failed to read body: {e}")))?;
+        for line in lines.push(&chunk)? {
+            importer.line(&line).await?;
+        }
+    }
+    if let Some(line) = lines.finish() {
+        importer.line(&line).await?;
+    }
+    let imported = importer.finish().await?;
+
+    info!(?imported, "moderation state imported");
+    db.record_audit_event(
+        "import_state",
+        "moderation-state",
+        actor.as_str(),
+        serde_json::to_value(imported).unwrap_or_default(),
+    )
+    .await?;
+    Ok(Json(ImportResponse { imported }))
+}
+
+/// Parses import lines and writes them in batches.
+struct Importer<'a> {
+    db: &'a LabelDb,
+    batch: Vec<ExportRecord>,
+    counts: ImportCounts,
+    line_no: usize,
+    saw_header: bool,
+}
+
+impl<'a> Importer<'a> {
+    fn new(db: &'a LabelDb) -> Self {
+        Self {
+            db,
+            batch: Vec::with_capacity(IMPORT_BATCH_SIZE),
+            counts: ImportCounts::default(),
+            line_no: 0,
+            saw_header: false,
+        }
+    }
+
+    async fn line(&mut self, line: &[u8]) -> Result<(), AppError> {
+        self.line_no += 1;
+        let Some(record) = parse_line(self.line_no, line)? else {
+            return Ok(());
+        };
+        check_header(self.saw_header, &record, self.line_no)?;
+        self.saw_header = true;
+        self.batch.push(record);
+        if self.batch.len() >= IMPORT_BATCH_SIZE {
+            self.flush().await?;
+        }
+        Ok(())
+    }
+
+    async fn flush(&mut self) -> Result<(), AppError> {
+        let counts = self.db.import_records(&self.batch).await?;
+        self.counts.add(counts);
+        self.batch.clear();
+        Ok(())
+    }
+
+    async fn finish(mut self) -> Result<ImportCounts, AppError> {
+        if !self.saw_header {
+            return Err(AppError::BadRequest("snapshot is empty".to_string()));
+        }
+        self.flush().await?;
+        Ok(self.counts)
+    }
+}
+
+/// Parse one line of a snapshot; blank lines are skipped.
+fn parse_line(line_no: usize, line: &[u8]) -> Result<Option<ExportRecord>, AppError> {
+    if line.iter().all(u8::is_ascii_whitespace) {
+        return Ok(None);
+    }
+    serde_json::from_slice(line)
+        .map(Some)
+        .map_err(|e| AppError::BadRequest(format!("line {line_no}: {e}")))
+}
+
+/// The first record must be a header for a version we can read, and only
+/// the first.
+fn check_header(saw_header: bool, record: &ExportRecord, line_no: usize) -> Result<(), AppError> {
+    match (saw_header, record) {
+        (false, ExportRecord::Header { version, .. }) if *version == EXPORT_VERSION => Ok(()),
+        (false, ExportRecord::Header { version, .. }) => Err(AppError::BadRequest(format!(
+            "unsupported snapshot version {version} (expected {EXPORT_VERSION})"
+        ))),
+        (false, _) => Err(AppError::BadRequest(
+            "snapshot must start with a header line".to_string(),
+        )),
+        (true, ExportRecord::Header { .. }) => Err(AppError::BadRequest(format!(
+            "line {line_no}: unexpected second header"
+        ))),
+        (true, _) => Ok(()),
+    }
+}
+
+/// Splits a byte stream into lines, refusing any longer than
+/// [`MAX_IMPORT_LINE_BYTES`].
+#[derive(Default)]
+struct LineBuffer {
+    pending: Vec<u8>,
+}
+
+impl LineBuffer {
+    /// Add a chunk and take the lines it completes, without their `\n`.
+    fn push(&mut self, chunk: &[u8]) -> Result<Vec<Vec<u8>>, AppError> {
+        self.pending.extend_from_slice(chunk);
+        let mut lines = Vec::new();
+        while let Some(end) = self.pending.iter().position(|b| *b == b'\n') {
+            let mut line: Vec<u8> = self.pending.drain(..=end).collect();
+            line.pop();
+            lines.push(line);
+        }
+        if self.pending.len() > MAX_IMPORT_LINE_BYTES {
+            return Err(AppError::BadRequest(format!(
+                "line longer than {MAX_IMPORT_LINE_BYTES} bytes"
+            )));
+        }
+        Ok(lines)
+    }
+
+    /// The last line, if the body didn't end with a newline.
+    fn finish(self) -> Option<Vec<u8>> {
+        (!self.pending.is_empty()).then_some(self.pending)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn header(version: u32) -> ExportRecord {
+        ExportRecord::Header {
+            version,
+            exported_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_line_buffer_splits_across_chunks() {
+        let mut lines = LineBuffer::default();
+        assert!(lines.push(b"{\"a\":").unwrap().is_empty());
+        assert_eq!(
+            lines.push(b"1}\n{\"b\":2}\n{\"c\"").unwrap(),
+            vec![b"{\"a\":1}".to_vec(), b"{\"b\":2}".to_vec()]
+        );
+        assert_eq!(lines.push(b":3}").unwrap(), Vec::<Vec<u8>>::new());
+        assert_eq!(lines.finish(), Some(b"{\"c\":3}".to_vec()));
+
+        let mut lines = LineBuffer::default();
+        assert!(lines.push(&b"x".repeat(MAX_IMPORT_LINE_BYTES + 1)).is_err());
+    }
+
+    #[test]
+    fn test_parse_line() {
+        assert!(parse_line(1, b"  \r").unwrap().is_none());
+        let line = serde_json::to_vec(&header(EXPORT_VERSION)).unwrap();
+        assert!(matches!(
+            parse_line(1, &line).unwrap(),
+            Some(ExportRecord::Header { version, .. }) if version == EXPORT_VERSION
+        ));
+
+        let err = parse_line(7, br#"{"kind":"nonsense"}"#).unwrap_err();
+        assert!(matches!(err, AppError::BadRequest(message) if message.starts_with("line 7:")));
+    }
+
+    #[test]
+    fn test_check_header() {
+        let label = serde_json::from_value::<ExportRecord>(serde_json::json!({
+            "kind": "label",
+            "seq": 1,
+            "src": "did:plc:labeler",
+            "uri": "at://did:plc:a/fm.plyr.track/1",
+            "cid": null,
+            "val": "copyright-violation",
+            "neg": false,
+            "cts": "2026-01-01T00:00:00Z",
+            "exp": null,
+            "sig": "00",
+            "created_at": "2026-01-01T00:00:00Z",
+        }))
+        .unwrap();
+
+        assert!(check_header(false, &header(EXPORT_VERSION), 1).is_ok());
+        assert!(check_header(false, &header(EXPORT_VERSION + 1), 1).is_err());
+        assert!(check_header(false, &label, 1).is_err());
+        assert!(check_header(true, &label, 2).is_ok());
+        assert!(check_header(true, &header(EXPORT_VERSION), 2).is_err());
+    }
+}
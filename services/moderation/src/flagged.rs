@@ -0,0 +1,362 @@
+//! Per-URI flag status for clients that check one track at a time.
+//!
+//! `GET /is-flagged?uri=` answers from the URI's label timeline with the same
+//! seq-ordered replay as the admin timeline, so a flag that was negated and
+//! re-applied reads as flagged. Answers are cached for a few seconds per URI
+//! to keep repeated polling off the database.
+//!
+//! `GET /admin/label-state?uri=` reports why a URI is or isn't active
+//! (never flagged, negated or expired), for backend reconciliation. It isn't
+//! cached.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use axum::{
+    extract::{Query, State},
+    Json,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::db::{
+    current_label_values, is_expired, LabelContext, LabelHistoryRow, ResolutionReason,
+};
+use crate::state::{AppError, AppState};
+
+/// How long an answer is served from memory. A label change can take this
+/// long to show up.
+const FLAG_STATUS_TTL: Duration = Duration::from_secs(10);
+
+/// Most URIs cached at once; past this, expired answers are dropped, and if
+/// that isn't enough the cache starts over.
+const FLAG_STATUS_CACHE_MAX: usize = 10_000;
+
+/// The label value that makes a track flagged.
+const FLAG_VAL: &str = "copyright-violation";
+
+/// Query parameters for the flag probe.
+#[derive(Debug, Deserialize)]
+pub struct IsFlaggedParams {
+    pub uri: String,
+}
+
+/// Whether a URI is currently flagged.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct IsFlaggedResponse {
+    pub uri: String,
+    pub flagged: bool,
+    /// The label value in force, when flagged.
+    pub val: Option<String>,
+    /// How the flag was dismissed, when the latest copyright label is a
+    /// negation.
+    pub resolution: Option<FlagResolution>,
+}
+
+/// A dismissed flag's resolution.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct FlagResolution {
+    pub negated_at: DateTime<Utc>,
+    pub reason: Option<ResolutionReason>,
+    pub notes: Option<String>,
+}
+
+/// Query parameters for the label state lookup.
+#[derive(Debug, Deserialize)]
+pub struct LabelStateParams {
+    pub uri: String,
+}
+
+/// Where a URI's copyright label stands, from its latest copyright event.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(tag = "state", rename_all = "lowercase")]
+pub enum LabelState {
+    /// No copyright label was ever emitted.
+    Unflagged,
+    /// The latest label is in force.
+    Active { seq: i64, since: DateTime<Utc> },
+    /// The latest label is a negation.
+    Negated { seq: i64, negated_at: DateTime<Utc> },
+    /// The latest label passed its `exp`.
+    Expired { seq: i64, expired_at: DateTime<Utc> },
+}
+
+/// A URI's label state.
+#[derive(Debug, Serialize)]
+pub struct LabelStateResponse {
+    pub uri: String,
+    #[serde(flatten)]
+    pub state: LabelState,
+}
+
+/// Recent answers, keyed by URI.
+#[derive(Default)]
+pub struct FlagStatusCache {
+    entries: Mutex<HashMap<String, (Instant, Arc<IsFlaggedResponse>)>>,
+}
+
+impl FlagStatusCache {
+    fn get(&self, uri: &str, now: Instant) -> Option<Arc<IsFlaggedResponse>> {
+        let entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        entries
+            .get(uri)
+            .filter(|(cached_at, _)| now.saturating_duration_since(*cached_at) < FLAG_STATUS_TTL)
+            .map(|(_, status)| status.clone())
+    }
+
+    fn insert(&self, status: Arc<IsFlaggedResponse>, now: Instant) {
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        if entries.len() >= FLAG_STATUS_CACHE_MAX {
+            entries.retain(|_, (cached_at, _)| {
+                now.saturating_duration_since(*cached_at) < FLAG_STATUS_TTL
+            });
+            if entries.len() >= FLAG_STATUS_CACHE_MAX {
+                entries.clear();
+            }
+        }
+        entries.insert(status.uri.clone(), (now, status));
+    }
+}
+
+/// Check whether a single URI is flagged.
+pub async fn is_flagged(
+    State(state): State<AppState>,
+    Query(params): Query<IsFlaggedParams>,
+) -> Result<Json<IsFlaggedResponse>, AppError> {
+    let db = state.db.as_ref().ok_or(AppError::LabelerNotConfigured)?;
+    let uri = params.uri.trim();
+    if !uri.starts_with("at://") {
+        return Err(AppError::BadRequest("uri must be an AT URI".to_string()));
+    }
+
+    let now = Instant::now();
+    if let Some(status) = state.flag_status_cache.get(uri, now) {
+        return Ok(Json(status.as_ref().clone()));
+    }
+
+    let history = db.get_label_history(uri).await?;
+    let context = db.get_context(uri).await?;
+    let status = Arc::new(flag_status(uri, &history, context.as_ref(), Utc::now()));
+    state.flag_status_cache.insert(status.clone(), now);
+    Ok(Json(status.as_ref().clone()))
+}
+
+/// Report whether a URI's copyright label is unflagged, active, negated or
+/// expired.
+pub async fn label_state(
+    State(state): State<AppState>,
+    Query(params): Query<LabelStateParams>,
+) -> Result<Json<LabelStateResponse>, AppError> {
+    let db = state.db.as_ref().ok_or(AppError::LabelerNotConfigured)?;
+    let uri = params.uri.trim();
+    if !uri.starts_with("at://") {
+        return Err(AppError::BadRequest("uri must be an AT URI".to_string()));
+    }
+
+    let history = db.get_label_history(uri).await?;
+    Ok(Json(LabelStateResponse {
+        uri: uri.to_string(),
+        state: label_state_from(&history, Utc::now()),
+    }))
+}
+
+/// Label state from a URI's label timeline (oldest first). Only the latest
+/// copyright event counts, as in `current_label_values`.
+fn label_state_from(history: &[LabelHistoryRow], now: DateTime<Utc>) -> LabelState {
+    let Some(latest) = history.iter().rev().find(|row| row.val == FLAG_VAL) else {
+        return LabelState::Unflagged;
+    };
+    if latest.neg {
+        return LabelState::Negated {
+            seq: latest.seq,
+            negated_at: latest.cts,
+        };
+    }
+    match latest.exp {
+        Some(exp) if is_expired(Some(exp), now) => LabelState::Expired {
+            seq: latest.seq,
+            expired_at: exp,
+        },
+        _ => LabelState::Active {
+            seq: latest.seq,
+            since: latest.cts,
+        },
+    }
+}
+
+/// Flag status from a URI's label timeline (oldest first) and context.
+fn flag_status(
+    uri: &str,
+    history: &[LabelHistoryRow],
+    context: Option<&LabelContext>,
+    now: DateTime<Utc>,
+) -> IsFlaggedResponse {
+    let flagged = current_label_values(history, now)
+        .iter()
+        .any(|val| val == FLAG_VAL);
+    let resolution = history
+        .iter()
+        .rev()
+        .find(|row| row.val == FLAG_VAL)
+        .filter(|latest| latest.neg)
+        .map(|negation| FlagResolution {
+            negated_at: negation.cts,
+            reason: context.and_then(|c| c.resolution_reason.clone()),
+            notes: context.and_then(|c| c.resolution_notes.clone()),
+        });
+
+    IsFlaggedResponse {
+        uri: uri.to_string(),
+        flagged,
+        val: flagged.then(|| FLAG_VAL.to_string()),
+        resolution,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const URI: &str = "at://did:plc:artist/fm.plyr.track/abc";
+
+    fn row(seq: i64, neg: bool, cts: DateTime<Utc>) -> LabelHistoryRow {
+        LabelHistoryRow {
+            seq,
+            src: "did:plc:labeler".to_string(),
+            val: FLAG_VAL.to_string(),
+            neg,
+            cts,
+            exp: None,
+            created_at: cts,
+        }
+    }
+
+    #[test]
+    fn test_flagged_and_negated_uris() {
+        let now = Utc::now();
+        let hour = chrono::Duration::hours(1);
+
+        let status = flag_status(URI, &[row(1, false, now - hour)], None, now);
+        assert!(status.flagged);
+        assert_eq!(status.val.as_deref(), Some(FLAG_VAL));
+        assert!(status.resolution.is_none());
+
+        let context = LabelContext {
+            resolution_reason: Some(ResolutionReason::Licensed),
+            resolution_notes: Some("distributor confirmed".to_string()),
+            ..Default::default()
+        };
+        let history = [row(1, false, now - hour * 2), row(2, true, now - hour)];
+        let status = flag_status(URI, &history, Some(&context), now);
+        assert!(!status.flagged);
+        assert!(status.val.is_none());
+        assert_eq!(
+            status.resolution,
+            Some(FlagResolution {
+                negated_at: now - hour,
+                reason: Some(ResolutionReason::Licensed),
+                notes: Some("distributor confirmed".to_string()),
+            })
+        );
+
+        // re-flagged after the negation
+        let history = [
+            row(1, false, now - hour * 3),
+            row(2, true, now - hour * 2),
+            row(3, false, now - hour),
+        ];
+        let status = flag_status(URI, &history, Some(&context), now);
+        assert!(status.flagged);
+        assert!(status.resolution.is_none());
+
+        let status = flag_status(URI, &[], None, now);
+        assert!(!status.flagged);
+        assert!(status.resolution.is_none());
+    }
+
+    #[test]
+    fn test_label_states() {
+        let now = Utc::now();
+        let hour = chrono::Duration::hours(1);
+
+        assert_eq!(label_state_from(&[], now), LabelState::Unflagged);
+        let mut other = row(1, false, now - hour);
+        other.val = "explicit".to_string();
+        assert_eq!(label_state_from(&[other], now), LabelState::Unflagged);
+
+        assert_eq!(
+            label_state_from(&[row(1, false, now - hour)], now),
+            LabelState::Active {
+                seq: 1,
+                since: now - hour
+            }
+        );
+
+        let history = [row(1, false, now - hour * 2), row(2, true, now - hour)];
+        assert_eq!(
+            label_state_from(&history, now),
+            LabelState::Negated {
+                seq: 2,
+                negated_at: now - hour
+            }
+        );
+
+        let mut expiring = row(1, false, now - hour * 2);
+        expiring.exp = Some(now - hour);
+        assert_eq!(
+            label_state_from(&[expiring.clone()], now),
+            LabelState::Expired {
+                seq: 1,
+                expired_at: now - hour
+            }
+        );
+        expiring.exp = Some(now + hour);
+        assert!(matches!(
+            label_state_from(&[expiring], now),
+            LabelState::Active { seq: 1, .. }
+        ));
+    }
+
+    #[test]
+    fn test_label_state_serializes_tagged() {
+        let at = "2026-01-02T00:00:00Z".parse::<DateTime<Utc>>().unwrap();
+        let response = LabelStateResponse {
+            uri: URI.to_string(),
+            state: LabelState::Negated {
+                seq: 2,
+                negated_at: at,
+            },
+        };
+        assert_eq!(
+            serde_json::to_value(&response).unwrap(),
+            serde_json::json!({
+                "uri": URI,
+                "state": "negated",
+                "seq": 2,
+                "negated_at": "2026-01-02T00:00:00Z",
+            })
+        );
+
+        let response = LabelStateResponse {
+            uri: URI.to_string(),
+            state: LabelState::Unflagged,
+        };
+        assert_eq!(
+            serde_json::to_value(&response).unwrap(),
+            serde_json::json!({ "uri": URI, "state": "unflagged" })
+        );
+    }
+
+    #[test]
+    fn test_flag_status_cache_expires() {
+        let cache = FlagStatusCache::default();
+        let now = Instant::now();
+        let status = Arc::new(flag_status(URI, &[], None, Utc::now()));
+        cache.insert(status.clone(), now);
+
+        assert_eq!(cache.get(URI, now + Duration::from_secs(5)), Some(status));
+        assert!(cache.get(URI, now + FLAG_STATUS_TTL).is_none());
+        assert!(cache.get("at://other", now).is_none());
+    }
+}
@@ -2,15 +2,24 @@
 
 use axum::{
     extract::{Multipart, State},
-    response::Html,
+    http::{header, HeaderMap, StatusCode},
+    response::{Html, IntoResponse, Response},
     Json,
 };
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use tracing::info;
 
-use crate::db::{CopyrightMatch, LabelContext};
+use crate::admin::url_encode;
+
+use crate::auth::RequireAdmin;
+use crate::claude::{estimate_cost_usd, ClaudeError, ImageAnalysis, ModerationResult, Severity};
+use crate::db::{CopyrightMatch, LabelContext, NewImageScan, OverrideVerdict, StoredLabel};
+use crate::image_fetch;
 use crate::labels::Label;
+use crate::ratelimit::RateLimited;
 use crate::state::{AppError, AppState};
+use crate::webhook::ImageFlaggedEvent;
 
 // --- types ---
 
@@ -29,6 +38,8 @@ pub struct EmitLabelContext {
     pub artist_did: Option<String>,
     pub highest_score: Option<f64>,
     pub matches: Option<Vec<CopyrightMatch>>,
+    /// Where the track's audio can be fetched, for admin rescans.
+    pub audio_url: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -45,6 +56,11 @@ pub struct EmitLabelRequest {
     pub neg: bool,
     /// Optional context for admin UI display
     pub context: Option<EmitLabelContext>,
+    /// If true, return the existing label instead of emitting a duplicate when
+    /// the URI already has an active label with the same value. Ignored for
+    /// negations.
+    #[serde(default)]
+    pub skip_if_active: bool,
 }
 
 fn default_label_val() -> String {
@@ -53,7 +69,7 @@ fn default_label_val() -> String {
 
 /// Normalize a score from integer (0-100) to float (0.0-1.0) range.
 /// AuDD returns scores as integers like 85 meaning 85%.
-fn normalize_score(score: f64) -> f64 {
+pub(crate) fn normalize_score(score: f64) -> f64 {
     if score > 1.0 {
         score / 100.0
     } else {
@@ -65,6 +81,9 @@ fn normalize_score(score: f64) -> f64 {
 pub struct EmitLabelResponse {
     pub seq: i64,
     pub label: Label,
+    /// False when `skip_if_active` matched an existing label and nothing was
+    /// emitted.
+    pub created: bool,
 }
 
 /// Response for sensitive images endpoint.
@@ -163,6 +182,8 @@ pub async fn landing(State(state): State<AppState>) -> Html<String> {
 /// Emit a new label (internal API).
 pub async fn emit_label(
     State(state): State<AppState>,
+    _: RequireAdmin,
+    _: RateLimited,
     Json(request): Json<EmitLabelRequest>,
 ) -> Result<Json<EmitLabelResponse>, AppError> {
     let db = state.db.as_ref().ok_or(AppError::LabelerNotConfigured)?;
@@ -173,9 +194,10 @@ pub async fn emit_label(
 
     info!(uri = %request.uri, val = %request.val, neg = request.neg, "emitting label");
 
-    // Create and sign the label
-    let mut label = Label::new(signer.did(), &request.uri, &request.val);
-    if let Some(cid) = request.cid {
+    // Create and sign the label, as the source of any label already in force
+    let src = crate::admin::label_src(db, signer, &request.uri, &request.val).await?;
+    let mut label = Label::new(&src, &request.uri, &request.val);
+    if let Some(cid) = request.cid.clone() {
         label = label.with_cid(cid);
     }
     if request.neg {
@@ -183,9 +205,28 @@ pub async fn emit_label(
     }
     let label = signer.sign_label(label)?;
 
-    // Store in database
-    let seq = db.store_label(&label).await?;
-    info!(seq, uri = %request.uri, "label stored");
+    // Store in database, unless skip_if_active finds the label already in
+    // force. Negations are always stored
+    let stored = if request.skip_if_active && !request.neg {
+        db.store_label_unless_active(&label).await?
+    } else {
+        StoredLabel::Created(db.store_label(&label).await?)
+    };
+    let (seq, label, created) = match stored {
+        StoredLabel::Existing(existing) => {
+            info!(seq = existing.seq, uri = %request.uri, "label already active, skipping");
+            (existing.seq, existing.to_label(), false)
+        }
+        StoredLabel::Created(seq) => {
+            info!(seq, uri = %request.uri, "label stored");
+
+            // Broadcast to subscribers
+            if let Some(tx) = &state.label_tx {
+                let _ = tx.send((seq, label.clone()));
+            }
+            (seq, label, true)
+        }
+    };
 
     // Store context if provided (for admin UI)
     if let Some(ctx) = request.context {
@@ -206,6 +247,9 @@ pub async fn emit_label(
             }),
             resolution_reason: None,
             resolution_notes: None,
+            audio_url: ctx.audio_url,
+            confirmed_by: None,
+            confirmed_at: None,
         };
         if let Err(e) = db.store_context(&request.uri, &label_ctx).await {
             // Log but don't fail - context is supplementary
@@ -213,21 +257,24 @@ pub async fn emit_label(
         }
     }
 
-    // Broadcast to subscribers
-    if let Some(tx) = &state.label_tx {
-        let _ = tx.send((seq, label.clone()));
-    }
-
-    Ok(Json(EmitLabelResponse { seq, label }))
+    Ok(Json(EmitLabelResponse {
+        seq,
+        label,
+        created,
+    }))
 }
 
 /// Get all sensitive images (public endpoint).
 ///
 /// Returns image_ids (R2 storage IDs) and urls (full URLs) for all flagged images.
 /// Clients should check both lists when determining if an image is sensitive.
+///
+/// The response carries an `ETag`; pollers that send it back in
+/// `If-None-Match` get an empty 304 until the list changes.
 pub async fn get_sensitive_images(
     State(state): State<AppState>,
-) -> Result<Json<SensitiveImagesResponse>, AppError> {
+    headers: HeaderMap,
+) -> Result<Response, AppError> {
     let db = state.db.as_ref().ok_or(AppError::LabelerNotConfigured)?;
 
     let images = db.get_sensitive_images().await?;
@@ -235,7 +282,43 @@ pub async fn get_sensitive_images(
     let image_ids: Vec<String> = images.iter().filter_map(|i| i.image_id.clone()).collect();
     let urls: Vec<String> = images.iter().filter_map(|i| i.url.clone()).collect();
 
-    Ok(Json(SensitiveImagesResponse { image_ids, urls }))
+    Ok(sensitive_images_response(
+        SensitiveImagesResponse { image_ids, urls },
+        &headers,
+    ))
+}
+
+/// The sensitive images list with its ETag, or 304 if the caller already
+/// has it.
+fn sensitive_images_response(body: SensitiveImagesResponse, headers: &HeaderMap) -> Response {
+    let json = serde_json::to_vec(&body).unwrap_or_default();
+    // Weak, since it names the list rather than these exact bytes: a proxy
+    // or compression layer can re-encode the body without invalidating it
+    let etag = format!("W/\"{}\"", hex::encode(&Sha256::digest(&json)[..16]));
+    let etag_header = [(header::ETAG, etag.clone())];
+
+    if etag_matches(headers, &etag) {
+        return (StatusCode::NOT_MODIFIED, etag_header).into_response();
+    }
+    (
+        etag_header,
+        [(header::CONTENT_TYPE, "application/json")],
+        json,
+    )
+        .into_response()
+}
+
+/// Whether `If-None-Match` lists `etag` (weak comparison) or is `*`.
+fn etag_matches(headers: &HeaderMap, etag: &str) -> bool {
+    let opaque = |tag: &str| tag.trim_start_matches("W/").to_string();
+    let etag = opaque(etag);
+    headers
+        .get_all(header::IF_NONE_MATCH)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split(','))
+        .map(|tag| tag.trim())
+        .any(|tag| tag == "*" || opaque(tag) == etag)
 }
 
 // --- image moderation ---
@@ -247,6 +330,14 @@ pub struct ScanImageResponse {
     pub reason: Option<String>,
     pub severity: String,
     pub violated_categories: Vec<String>,
+    /// Unsafe but below the auto-flag threshold: held for admin review
+    /// rather than added to sensitive images.
+    pub needs_review: bool,
+    /// Second-pass verdict, when a medium-severity result was re-checked.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub second_opinion: Option<ModerationResult>,
+    /// Estimated USD cost across all passes (null for unpriced models).
+    pub cost_usd: Option<f64>,
 }
 
 /// Scan an image for policy violations using Claude vision.
@@ -255,20 +346,16 @@ pub struct ScanImageResponse {
 /// - `image`: the image file to scan
 /// - `image_id`: identifier for tracking (e.g., R2 file ID)
 ///
-/// Returns moderation result. If image is not safe, it's automatically
-/// added to the sensitive_images table.
+/// Returns moderation result. If image is not safe at or above the configured
+/// severity threshold, it's automatically added to the sensitive_images table;
+/// lower-severity unsafe results are held in the admin review queue.
 pub async fn scan_image(
     State(state): State<AppState>,
     mut multipart: Multipart,
 ) -> Result<Json<ScanImageResponse>, AppError> {
-    let claude = state
-        .claude
-        .as_ref()
-        .ok_or(AppError::ImageModerationNotConfigured)?;
-    let db = state
-        .db
-        .as_ref()
-        .ok_or(AppError::ImageModerationNotConfigured)?;
+    if state.claude.is_none() || state.db.is_none() {
+        return Err(AppError::ImageModerationNotConfigured);
+    }
 
     let mut image_bytes: Option<Vec<u8>> = None;
     let mut image_id: Option<String> = None;
@@ -315,45 +402,325 @@ pub async fn scan_image(
 
     info!(image_id = %image_id, size = image_bytes.len(), "scanning image");
 
-    // Call Claude for analysis
-    let result = claude
-        .analyze_image(&image_bytes, &media_type)
-        .await
-        .map_err(|e| AppError::Claude(e.to_string()))?;
-
-    // Store scan result for cost tracking
-    db.store_image_scan(
-        &image_id,
-        result.is_safe,
-        &result.violated_categories,
-        &result.severity,
-        &result.explanation,
-        "claude-sonnet-4-5-20250929", // TODO: get from client
+    let response = moderate_image(&state, &image_id, &image_bytes, &media_type, None).await?;
+    Ok(Json(response))
+}
+
+/// Request to scan an image the service fetches itself.
+#[derive(Debug, Deserialize)]
+pub struct ScanImageUrlRequest {
+    /// https URL of the image (e.g., a bluesky CDN avatar)
+    pub url: String,
+    /// identifier for tracking (e.g., R2 file ID or avatar key)
+    pub image_id: String,
+}
+
+/// Scan an image by URL using Claude vision.
+///
+/// JSON variant of `scan_image` for images that live at external URLs. The
+/// fetch is SSRF-guarded (see `image_fetch`); the source URL is recorded on the
+/// scan row and used as the sensitive image's `url` when flagged.
+pub async fn scan_image_url(
+    State(state): State<AppState>,
+    Json(request): Json<ScanImageUrlRequest>,
+) -> Result<Json<ScanImageResponse>, AppError> {
+    // fail before fetching anything if moderation isn't configured
+    if state.claude.is_none() || state.db.is_none() {
+        return Err(AppError::ImageModerationNotConfigured);
+    }
+
+    info!(image_id = %request.image_id, url = %request.url, "fetching image for scan");
+
+    let image = image_fetch::fetch_image(&request.url).await?;
+
+    info!(image_id = %request.image_id, size = image.bytes.len(), "scanning image");
+
+    let response = moderate_image(
+        &state,
+        &request.image_id,
+        &image.bytes,
+        image.media_type,
+        Some(&request.url),
     )
     .await?;
+    Ok(Json(response))
+}
+
+/// What to do with a scan result.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ScanDisposition {
+    /// Passed moderation.
+    Safe,
+    /// Unsafe at or above the threshold: publish to sensitive_images.
+    Flag,
+    /// Unsafe but below the threshold: hold for an admin.
+    NeedsReview,
+    /// A human override already decided this image; take no action.
+    Overridden(OverrideVerdict),
+}
+
+/// Decide a scan's disposition from its verdict and the auto-flag threshold.
+///
+/// An unrecognized severity on an unsafe verdict goes to review rather than
+/// being guessed at in either direction.
+pub(crate) fn scan_disposition(result: &ModerationResult, threshold: Severity) -> ScanDisposition {
+    if result.is_safe {
+        return ScanDisposition::Safe;
+    }
+    match Severity::parse(&result.severity) {
+        Some(severity) if severity >= threshold => ScanDisposition::Flag,
+        _ => ScanDisposition::NeedsReview,
+    }
+}
+
+/// Apply a stored human override to a scan's disposition.
+///
+/// An override always wins: a safe override must never be re-flagged, and an
+/// unsafe override already has its sensitive entry.
+pub(crate) fn apply_override(
+    disposition: ScanDisposition,
+    override_verdict: Option<OverrideVerdict>,
+) -> ScanDisposition {
+    match override_verdict {
+        Some(verdict) => ScanDisposition::Overridden(verdict),
+        None => disposition,
+    }
+}
+
+/// Whether a first-pass result should be re-checked before acting on it.
+///
+/// Only medium-severity auto-flags get a second pass: that band has the most
+/// single-model false positives, and high severity is trusted as-is.
+pub(crate) fn needs_second_opinion(result: &ModerationResult, disposition: ScanDisposition) -> bool {
+    disposition == ScanDisposition::Flag && Severity::parse(&result.severity) == Some(Severity::Medium)
+}
+
+/// Disposition after a second pass: flag only if both passes agree.
+pub(crate) fn second_opinion_disposition(second: &ModerationResult) -> ScanDisposition {
+    if second.is_safe {
+        ScanDisposition::NeedsReview
+    } else {
+        ScanDisposition::Flag
+    }
+}
 
-    // If not safe, add to sensitive images
-    if !result.is_safe {
-        info!(image_id = %image_id, severity = %result.severity, "flagging sensitive image");
-        db.add_sensitive_image(
-            Some(&image_id),
-            None,
-            Some(&result.explanation),
-            Some("claude-auto"),
-        )
+/// Hex SHA-256 of image bytes, used to match overrides across re-uploads.
+pub(crate) fn content_hash(bytes: &[u8]) -> String {
+    hex::encode(Sha256::digest(bytes))
+}
+
+/// Run Claude analysis on an image, record the scan, and flag it if unsafe
+/// enough to auto-flag.
+async fn moderate_image(
+    state: &AppState,
+    image_id: &str,
+    image_bytes: &[u8],
+    media_type: &str,
+    source_url: Option<&str>,
+) -> Result<ScanImageResponse, AppError> {
+    let claude = state
+        .claude
+        .as_ref()
+        .ok_or(AppError::ImageModerationNotConfigured)?;
+    let db = state
+        .db
+        .as_ref()
+        .ok_or(AppError::ImageModerationNotConfigured)?;
+
+    // Call Claude for analysis. A refusal is a verdict about the content, so
+    // it's recorded as unsafe (flagged, or held for review if refusals aren't
+    // trusted) instead of failing the scan; truncation and API errors are
+    // transient and surface to the caller.
+    let analysis = match claude.analyze_image(image_bytes, media_type).await {
+        Ok(analysis) => analysis,
+        Err(ClaudeError::Refusal {
+            model,
+            usage,
+            request_id,
+        }) => {
+            if state.image_refusal_as_unsafe {
+                tracing::warn!(image_id = %image_id, "claude refused to analyze image, flagging as unsafe");
+            } else {
+                tracing::warn!(image_id = %image_id, "claude refused to analyze image, holding for review");
+            }
+            ImageAnalysis {
+                result: ModerationResult::refused(state.image_refusal_as_unsafe),
+                usage,
+                model,
+                request_id,
+            }
+        }
+        Err(e) => return Err(e.into()),
+    };
+    let result = analysis.result.clone();
+
+    let hash = content_hash(image_bytes);
+    let override_verdict = db
+        .get_image_override(image_id, &hash)
+        .await?
+        .and_then(|o| o.verdict());
+    let mut disposition = apply_override(
+        scan_disposition(&result, state.image_autoflag_severity),
+        override_verdict,
+    );
+
+    // Re-check medium-severity flags; disagreement goes to the review queue
+    let mut second = None;
+    if state.image_second_opinion && needs_second_opinion(&result, disposition) {
+        let model = state
+            .image_second_opinion_model
+            .as_deref()
+            .unwrap_or(claude.model());
+        match claude
+            .adjudicate_image(image_bytes, media_type, &result, Some(model))
+            .await
+        {
+            Ok(analysis) => {
+                disposition = second_opinion_disposition(&analysis.result);
+                info!(
+                    image_id = %image_id,
+                    second_is_safe = analysis.result.is_safe,
+                    agreed = disposition == ScanDisposition::Flag,
+                    "second opinion complete"
+                );
+                second = Some(analysis);
+            }
+            Err(e) => {
+                tracing::warn!(image_id = %image_id, error = %e, "second opinion failed, holding for review");
+                disposition = ScanDisposition::NeedsReview;
+            }
+        }
+    }
+
+    let review_state = match disposition {
+        ScanDisposition::NeedsReview => Some("needs_review"),
+        ScanDisposition::Overridden(_) => Some("overridden"),
+        ScanDisposition::Safe | ScanDisposition::Flag => None,
+    };
+
+    // Store scan result for cost tracking; the cost is kept as computed now,
+    // so later price changes don't rewrite what a scan cost
+    let first_cost = estimate_cost_usd(
+        &analysis.model,
+        analysis.usage.input_tokens,
+        analysis.usage.output_tokens,
+    );
+    let scan_id = db
+        .store_image_scan(&NewImageScan {
+            image_id,
+            is_safe: result.is_safe,
+            violated_categories: &result.violated_categories,
+            severity: &result.severity,
+            explanation: &result.explanation,
+            model: &analysis.model,
+            source_url,
+            review_state,
+            input_tokens: analysis.usage.input_tokens,
+            output_tokens: analysis.usage.output_tokens,
+            cost_usd: first_cost,
+            content_hash: &hash,
+            second_opinion_of: None,
+            request_id: analysis.request_id.as_deref(),
+        })
+        .await?;
+
+    let mut cost_usd = first_cost;
+    if let Some(second) = &second {
+        let second_cost = estimate_cost_usd(
+            &second.model,
+            second.usage.input_tokens,
+            second.usage.output_tokens,
+        );
+        db.store_image_scan(&NewImageScan {
+            image_id,
+            is_safe: second.result.is_safe,
+            violated_categories: &second.result.violated_categories,
+            severity: &second.result.severity,
+            explanation: &second.result.explanation,
+            model: &second.model,
+            source_url,
+            review_state: None,
+            input_tokens: second.usage.input_tokens,
+            output_tokens: second.usage.output_tokens,
+            cost_usd: second_cost,
+            content_hash: &hash,
+            second_opinion_of: Some(scan_id),
+            request_id: second.request_id.as_deref(),
+        })
         .await?;
+        cost_usd = cost_usd.zip(second_cost).map(|(a, b)| a + b);
     }
 
-    Ok(Json(ScanImageResponse {
-        is_safe: result.is_safe,
-        reason: if result.is_safe {
+    match disposition {
+        ScanDisposition::Safe => {}
+        ScanDisposition::Flag => {
+            info!(image_id = %image_id, severity = %result.severity, "flagging sensitive image");
+            db.add_sensitive_image(
+                Some(image_id),
+                source_url,
+                Some(&result.explanation),
+                Some("claude-auto"),
+                Some(&result.severity),
+                &result.violated_categories,
+            )
+            .await?;
+
+            if let Some(webhook) = &state.webhook {
+                let urgent = Severity::parse(&result.severity) == Some(Severity::High);
+                webhook.image_flagged(
+                    ImageFlaggedEvent {
+                        image_id: image_id.to_string(),
+                        source_url: source_url.map(str::to_string),
+                        scan_id,
+                        severity: result.severity.clone(),
+                        violated_categories: result.violated_categories.clone(),
+                        explanation: result.explanation.clone(),
+                        admin_url: format!(
+                            "{}/admin/image-scans?image_id={}",
+                            state.public_url,
+                            url_encode(image_id)
+                        ),
+                    },
+                    urgent,
+                );
+            }
+        }
+        ScanDisposition::NeedsReview => {
+            info!(
+                image_id = %image_id,
+                severity = %result.severity,
+                threshold = state.image_autoflag_severity.as_str(),
+                "unsafe image below auto-flag threshold, holding for review"
+            );
+        }
+        ScanDisposition::Overridden(verdict) => {
+            info!(
+                image_id = %image_id,
+                model_is_safe = result.is_safe,
+                override_verdict = verdict.as_str(),
+                "image has a human override, skipping automated action"
+            );
+        }
+    }
+
+    let is_safe = match disposition {
+        ScanDisposition::Overridden(verdict) => verdict == OverrideVerdict::Safe,
+        _ => result.is_safe,
+    };
+
+    Ok(ScanImageResponse {
+        is_safe,
+        reason: if is_safe {
             None
         } else {
             Some(result.explanation)
         },
         severity: result.severity,
         violated_categories: result.violated_categories,
-    }))
+        needs_review: disposition == ScanDisposition::NeedsReview,
+        second_opinion: second.map(|analysis| analysis.result),
+        cost_usd,
+    })
 }
 
 #[cfg(test)]
@@ -373,4 +740,165 @@ mod tests {
         assert!((normalize_score(0.5) - 0.5).abs() < 0.001);
         assert!((normalize_score(0.0) - 0.0).abs() < 0.001);
     }
+
+    fn verdict(is_safe: bool, severity: &str) -> ModerationResult {
+        ModerationResult {
+            is_safe,
+            violated_categories: vec![],
+            severity: severity.to_string(),
+            explanation: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_scan_disposition_threshold() {
+        let threshold = Severity::Medium;
+        assert_eq!(
+            scan_disposition(&verdict(true, "safe"), threshold),
+            ScanDisposition::Safe
+        );
+        assert_eq!(
+            scan_disposition(&verdict(false, "low"), threshold),
+            ScanDisposition::NeedsReview
+        );
+        assert_eq!(
+            scan_disposition(&verdict(false, "medium"), threshold),
+            ScanDisposition::Flag
+        );
+        assert_eq!(
+            scan_disposition(&verdict(false, "high"), threshold),
+            ScanDisposition::Flag
+        );
+        assert_eq!(
+            scan_disposition(&verdict(false, "bogus"), threshold),
+            ScanDisposition::NeedsReview
+        );
+    }
+
+    #[test]
+    fn test_refusal_is_held_for_review() {
+        let refused = ModerationResult::refused(false);
+        let disposition = scan_disposition(&refused, Severity::Low);
+        assert_eq!(disposition, ScanDisposition::NeedsReview);
+        assert!(!needs_second_opinion(&refused, disposition));
+    }
+
+    #[test]
+    fn test_refusal_as_unsafe_is_flagged() {
+        let refused = ModerationResult::refused(true);
+        // high severity clears every auto-flag threshold and skips the second pass
+        let disposition = scan_disposition(&refused, Severity::High);
+        assert_eq!(disposition, ScanDisposition::Flag);
+        assert!(!needs_second_opinion(&refused, disposition));
+        // a human override still wins
+        assert_eq!(
+            apply_override(disposition, Some(OverrideVerdict::Safe)),
+            ScanDisposition::Overridden(OverrideVerdict::Safe)
+        );
+    }
+    #[test]
+    fn test_rescan_after_safe_override_is_not_flagged() {
+        // model still says high severity, but a human cleared the image
+        let disposition = scan_disposition(&verdict(false, "high"), Severity::Medium);
+        assert_eq!(disposition, ScanDisposition::Flag);
+        assert_eq!(
+            apply_override(disposition, Some(OverrideVerdict::Safe)),
+            ScanDisposition::Overridden(OverrideVerdict::Safe)
+        );
+
+        // unsafe override doesn't add a second sensitive entry either
+        assert_eq!(
+            apply_override(disposition, Some(OverrideVerdict::Unsafe)),
+            ScanDisposition::Overridden(OverrideVerdict::Unsafe)
+        );
+
+        assert_eq!(apply_override(disposition, None), ScanDisposition::Flag);
+    }
+
+    #[test]
+    fn test_second_opinion_only_for_medium_flags() {
+        let threshold = Severity::Medium;
+        let medium = verdict(false, "medium");
+        let high = verdict(false, "high");
+        assert!(needs_second_opinion(
+            &medium,
+            scan_disposition(&medium, threshold)
+        ));
+        assert!(!needs_second_opinion(&high, scan_disposition(&high, threshold)));
+        // medium held for review under a high threshold: nothing to re-check
+        assert!(!needs_second_opinion(
+            &medium,
+            scan_disposition(&medium, Severity::High)
+        ));
+    }
+
+    #[test]
+    fn test_second_opinion_must_agree_to_flag() {
+        assert_eq!(
+            second_opinion_disposition(&verdict(false, "medium")),
+            ScanDisposition::Flag
+        );
+        assert_eq!(
+            second_opinion_disposition(&verdict(true, "safe")),
+            ScanDisposition::NeedsReview
+        );
+    }
+
+    #[test]
+    fn test_content_hash_is_stable() {
+        assert_eq!(content_hash(b"artwork"), content_hash(b"artwork"));
+        assert_ne!(content_hash(b"artwork"), content_hash(b"artwork2"));
+        assert_eq!(content_hash(b"").len(), 64);
+    }
+
+    #[tokio::test]
+    async fn test_sensitive_images_not_modified() {
+        let list = || SensitiveImagesResponse {
+            image_ids: vec!["abc123".to_string()],
+            urls: vec!["https://cdn.example/avatar.jpg".to_string()],
+        };
+
+        let response = sensitive_images_response(list(), &HeaderMap::new());
+        assert_eq!(response.status(), StatusCode::OK);
+        let etag = response.headers()[header::ETAG].clone();
+        assert!(etag.to_str().unwrap().starts_with("W/\""));
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["image_ids"], serde_json::json!(["abc123"]));
+
+        let mut headers = HeaderMap::new();
+        headers.insert(header::IF_NONE_MATCH, etag.clone());
+        let response = sensitive_images_response(list(), &headers);
+        assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+        assert_eq!(response.headers()[header::ETAG], etag);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        assert!(body.is_empty());
+
+        // a changed list no longer matches
+        let mut changed = list();
+        changed.urls.clear();
+        assert_eq!(
+            sensitive_images_response(changed, &headers).status(),
+            StatusCode::OK
+        );
+
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            header::IF_NONE_MATCH,
+            format!(
+                "\"other\", {}",
+                etag.to_str().unwrap().trim_start_matches("W/")
+            )
+                .parse()
+                .unwrap(),
+        );
+        assert_eq!(
+            sensitive_images_response(list(), &headers).status(),
+            StatusCode::NOT_MODIFIED
+        );
+    }
 }
@@ -0,0 +1,219 @@
+//! Fetching remote images for moderation, with SSRF protection.
+//!
+//! Avatars live at external URLs (bluesky CDN), so the service can pull them
+//! itself instead of making the backend download and re-upload. Since the URL
+//! is caller-controlled, we only allow https, resolve the host up front and
+//! refuse private/loopback/link-local targets, then pin the connection to the
+//! vetted address so a second DNS answer can't redirect us inward. Audio
+//! sampled for recognition is downloaded through the same checks.
+
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::time::Duration;
+
+use reqwest::{redirect::Policy, Url};
+
+use crate::state::AppError;
+
+/// Hard cap on fetched image size, enforced while streaming.
+pub const MAX_IMAGE_BYTES: usize = 10 * 1024 * 1024;
+
+const FETCH_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// A fetched image ready for analysis.
+#[derive(Debug)]
+pub struct FetchedImage {
+    pub bytes: Vec<u8>,
+    /// Media type sniffed from the bytes (not trusted from the server).
+    pub media_type: &'static str,
+}
+
+/// Fetch an image from an https URL.
+pub async fn fetch_image(raw_url: &str) -> Result<FetchedImage, AppError> {
+    let (client, url) = pinned_client(raw_url, FETCH_TIMEOUT).await?;
+
+    let mut response = client
+        .get(url)
+        .send()
+        .await
+        .map_err(|e| AppError::ImageFetch(format!("request failed: {e}")))?;
+
+    if !response.status().is_success() {
+        return Err(AppError::ImageFetch(format!(
+            "upstream returned {}",
+            response.status()
+        )));
+    }
+
+    if response
+        .content_length()
+        .is_some_and(|len| len > MAX_IMAGE_BYTES as u64)
+    {
+        return Err(AppError::BadRequest(format!(
+            "image exceeds {MAX_IMAGE_BYTES} bytes"
+        )));
+    }
+
+    let mut bytes = Vec::new();
+    while let Some(chunk) = response
+        .chunk()
+        .await
+        .map_err(|e| AppError::ImageFetch(format!("failed to read body: {e}")))?
+    {
+        if bytes.len() + chunk.len() > MAX_IMAGE_BYTES {
+            return Err(AppError::BadRequest(format!(
+                "image exceeds {MAX_IMAGE_BYTES} bytes"
+            )));
+        }
+        bytes.extend_from_slice(&chunk);
+    }
+
+    let media_type = sniff_image_type(&bytes)
+        .ok_or_else(|| AppError::BadRequest("url did not return a supported image".to_string()))?;
+
+    Ok(FetchedImage { bytes, media_type })
+}
+
+/// Check an https URL and build a client that only reaches its host, at the
+/// vetted public address, without following redirects.
+pub async fn pinned_client(
+    raw_url: &str,
+    timeout: Duration,
+) -> Result<(reqwest::Client, Url), AppError> {
+    let url = Url::parse(raw_url)
+        .map_err(|e| AppError::BadRequest(format!("invalid url: {e}")))?;
+    if url.scheme() != "https" {
+        return Err(AppError::BadRequest("only https urls are allowed".to_string()));
+    }
+    let host = url
+        .host_str()
+        .ok_or_else(|| AppError::BadRequest("url has no host".to_string()))?
+        .to_string();
+    let port = url.port_or_known_default().unwrap_or(443);
+
+    let addr = resolve_public_addr(&host, port).await?;
+
+    let client = reqwest::Client::builder()
+        .resolve(&host, addr)
+        .redirect(Policy::none())
+        .timeout(timeout)
+        .build()
+        .map_err(|e| std::io::Error::other(format!("failed to build client: {e}")))?;
+    Ok((client, url))
+}
+
+/// Resolve a host and return the first address, rejecting the lookup if any
+/// answer points at a non-public network.
+async fn resolve_public_addr(host: &str, port: u16) -> Result<SocketAddr, AppError> {
+    let addrs: Vec<SocketAddr> = tokio::net::lookup_host((host, port))
+        .await
+        .map_err(|e| AppError::BadRequest(format!("failed to resolve {host}: {e}")))?
+        .collect();
+
+    if addrs.is_empty() {
+        return Err(AppError::BadRequest(format!("{host} did not resolve")));
+    }
+    if let Some(bad) = addrs.iter().find(|a| !is_public_ip(a.ip())) {
+        return Err(AppError::BadRequest(format!(
+            "{host} resolves to a non-public address ({})",
+            bad.ip()
+        )));
+    }
+    Ok(addrs[0])
+}
+
+/// Whether an address is routable on the public internet.
+fn is_public_ip(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(v4) => is_public_v4(v4),
+        IpAddr::V6(v6) => match v6.to_ipv4_mapped() {
+            Some(v4) => is_public_v4(v4),
+            None => is_public_v6(v6),
+        },
+    }
+}
+
+fn is_public_v4(ip: Ipv4Addr) -> bool {
+    let [a, b, ..] = ip.octets();
+    !(ip.is_private()
+        || ip.is_loopback()
+        || ip.is_link_local()
+        || ip.is_unspecified()
+        || ip.is_broadcast()
+        || ip.is_multicast()
+        || ip.is_documentation()
+        // 0.0.0.0/8 "this network"
+        || a == 0
+        // 100.64.0.0/10 carrier-grade NAT
+        || (a == 100 && (64..128).contains(&b))
+        // 198.18.0.0/15 benchmarking
+        || (a == 198 && (18..20).contains(&b)))
+}
+
+fn is_public_v6(ip: Ipv6Addr) -> bool {
+    let first = ip.segments()[0];
+    !(ip.is_loopback()
+        || ip.is_unspecified()
+        || ip.is_multicast()
+        // fc00::/7 unique local (includes fly's fdaa:: private network)
+        || (first & 0xfe00) == 0xfc00
+        // fe80::/10 link-local
+        || (first & 0xffc0) == 0xfe80)
+}
+
+/// Identify an image by its magic bytes.
+pub fn sniff_image_type(bytes: &[u8]) -> Option<&'static str> {
+    if bytes.starts_with(&[0x89, b'P', b'N', b'G', 0x0d, 0x0a, 0x1a, 0x0a]) {
+        Some("image/png")
+    } else if bytes.starts_with(&[0xff, 0xd8, 0xff]) {
+        Some("image/jpeg")
+    } else if bytes.starts_with(b"GIF87a") || bytes.starts_with(b"GIF89a") {
+        Some("image/gif")
+    } else if bytes.len() >= 12 && &bytes[0..4] == b"RIFF" && &bytes[8..12] == b"WEBP" {
+        Some("image/webp")
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn private_addresses_are_rejected() {
+        for ip in [
+            "127.0.0.1",
+            "10.1.2.3",
+            "172.16.0.1",
+            "192.168.1.1",
+            "169.254.169.254",
+            "100.64.0.1",
+            "0.0.0.0",
+            "::1",
+            "fe80::1",
+            "fdaa::3",
+            "::ffff:127.0.0.1",
+        ] {
+            assert!(!is_public_ip(ip.parse().unwrap()), "{ip} should be blocked");
+        }
+    }
+
+    #[test]
+    fn public_addresses_are_allowed() {
+        for ip in ["1.1.1.1", "104.18.0.1", "2606:4700::1111"] {
+            assert!(is_public_ip(ip.parse().unwrap()), "{ip} should be allowed");
+        }
+    }
+
+    #[test]
+    fn sniffs_common_image_types() {
+        assert_eq!(
+            sniff_image_type(&[0x89, b'P', b'N', b'G', 0x0d, 0x0a, 0x1a, 0x0a, 0]),
+            Some("image/png")
+        );
+        assert_eq!(sniff_image_type(&[0xff, 0xd8, 0xff, 0xe0]), Some("image/jpeg"));
+        assert_eq!(sniff_image_type(b"GIF89a...."), Some("image/gif"));
+        assert_eq!(sniff_image_type(b"RIFF\0\0\0\0WEBPVP8 "), Some("image/webp"));
+        assert_eq!(sniff_image_type(b"<html>"), None);
+    }
+}
@@ -0,0 +1,529 @@
+//! Admin endpoints for Claude image moderation.
+//!
+//! Unsafe verdicts below the auto-flag severity threshold are held in a review
+//! queue instead of being published to sensitive images; admins approve or
+//! flag them here. Overrides record a human verdict that later automated
+//! scans of the same image (or the same bytes) defer to.
+
+use axum::{
+    extract::{Query, State},
+    http::header::CONTENT_TYPE,
+    response::{IntoResponse, Response},
+    Json,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::auth::RequireAdmin;
+use crate::claude::estimate_cost_usd;
+use crate::db::{ImageScanFilter, ImageScanRow, OverrideVerdict};
+use crate::state::{AppError, AppState};
+
+/// Query parameters for the image scan history.
+#[derive(Debug, Deserialize)]
+pub struct ImageScansQuery {
+    pub image_id: Option<String>,
+    /// "safe" or "unsafe"
+    pub verdict: Option<String>,
+    /// "safe", "low", "medium", or "high"
+    pub severity: Option<String>,
+    /// Only scans at or after this time (RFC 3339)
+    pub since: Option<DateTime<Utc>>,
+    /// Only scans before this time (RFC 3339)
+    pub until: Option<DateTime<Utc>>,
+    #[serde(default = "default_limit")]
+    pub limit: i64,
+    #[serde(default)]
+    pub offset: i64,
+}
+
+fn default_limit() -> i64 {
+    50
+}
+
+/// A scan with its estimated cost.
+#[derive(Debug, Serialize)]
+pub struct ImageScanEntry {
+    #[serde(flatten)]
+    pub scan: ImageScanRow,
+    /// Estimated USD cost from token usage (null for unpriced models or
+    /// scans recorded before usage was tracked).
+    pub cost_usd: Option<f64>,
+}
+
+/// Paginated image scan history.
+#[derive(Debug, Serialize)]
+pub struct ImageScansResponse {
+    pub scans: Vec<ImageScanEntry>,
+    pub total: i64,
+    pub limit: i64,
+    pub offset: i64,
+}
+
+/// A held scan with its second opinion, if one was taken.
+#[derive(Debug, Serialize)]
+pub struct ImageReviewEntry {
+    #[serde(flatten)]
+    pub scan: ImageScanRow,
+    /// Second-pass scan that disagreed with this one.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub second_opinion: Option<ImageScanRow>,
+    /// Estimated USD cost across both passes.
+    pub total_cost_usd: Option<f64>,
+}
+
+/// Response listing image scans awaiting review.
+#[derive(Debug, Serialize)]
+pub struct ImageReviewQueueResponse {
+    pub scans: Vec<ImageReviewEntry>,
+}
+
+/// Request to act on a held image scan.
+#[derive(Debug, Deserialize)]
+pub struct ImageReviewActionRequest {
+    pub scan_id: i64,
+    /// "approve" (leave unflagged) or "flag" (publish to sensitive images)
+    pub action: String,
+    /// Who made the decision.
+    pub reviewed_by: Option<String>,
+}
+
+/// Response after acting on a held image scan.
+#[derive(Debug, Serialize)]
+pub struct ImageReviewActionResponse {
+    pub scan_id: i64,
+    pub review_state: String,
+    /// Sensitive image entry created when flagged.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sensitive_image_id: Option<i64>,
+}
+
+/// Request to override the automated verdict for an image.
+#[derive(Debug, Deserialize)]
+pub struct ImageOverrideRequest {
+    /// R2 storage ID of the image
+    pub image_id: Option<String>,
+    /// Hex SHA-256 of the image bytes (matches re-uploads under new ids)
+    pub content_hash: Option<String>,
+    /// "safe" or "unsafe"
+    pub verdict: String,
+    pub reviewed_by: String,
+    pub notes: Option<String>,
+}
+
+/// Response after recording an override.
+#[derive(Debug, Serialize)]
+pub struct ImageOverrideResponse {
+    pub override_id: i64,
+    pub verdict: OverrideVerdict,
+    /// Sensitive entries removed by a safe override.
+    pub removed: u64,
+    /// Sensitive entry created by an unsafe override.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sensitive_image_id: Option<i64>,
+}
+
+/// List image scans held for review.
+pub async fn list_image_review_queue(
+    State(state): State<AppState>,
+) -> Result<Json<ImageReviewQueueResponse>, AppError> {
+    let db = state.db.as_ref().ok_or(AppError::LabelerNotConfigured)?;
+    let held = db.get_image_review_queue().await?;
+
+    let ids: Vec<i64> = held.iter().map(|s| s.id).collect();
+    let mut second_opinions = db.get_second_opinions(&ids).await?;
+
+    let scans = held
+        .into_iter()
+        .map(|scan| {
+            let second_opinion = second_opinions
+                .iter()
+                .position(|s| s.second_opinion_of == Some(scan.id))
+                .map(|i| second_opinions.swap_remove(i));
+            let total_cost_usd = match &second_opinion {
+                Some(second) => scan_cost_usd(&scan)
+                    .zip(scan_cost_usd(second))
+                    .map(|(a, b)| a + b),
+                None => scan_cost_usd(&scan),
+            };
+            ImageReviewEntry {
+                scan,
+                second_opinion,
+                total_cost_usd,
+            }
+        })
+        .collect();
+
+    Ok(Json(ImageReviewQueueResponse { scans }))
+}
+
+/// Approve or flag a held image scan.
+pub async fn resolve_image_review(
+    State(state): State<AppState>,
+    _: RequireAdmin,
+    Json(request): Json<ImageReviewActionRequest>,
+) -> Result<Json<ImageReviewActionResponse>, AppError> {
+    let db = state.db.as_ref().ok_or(AppError::LabelerNotConfigured)?;
+
+    let scan = db
+        .get_image_scan(request.scan_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("image scan {}", request.scan_id)))?;
+
+    if scan.review_state.as_deref() != Some("needs_review") {
+        return Err(AppError::BadRequest(format!(
+            "image scan {} is not awaiting review",
+            request.scan_id
+        )));
+    }
+
+    let reviewed_by = request.reviewed_by.as_deref().unwrap_or("admin");
+
+    tracing::info!(
+        scan_id = scan.id,
+        image_id = %scan.image_id,
+        action = %request.action,
+        reviewed_by,
+        "resolving held image scan"
+    );
+
+    let (review_state, sensitive_image_id) = match request.action.as_str() {
+        "approve" => ("approved", None),
+        "flag" => {
+            let categories: Vec<String> = scan
+                .violated_categories
+                .as_ref()
+                .and_then(|v| serde_json::from_value(v.clone()).ok())
+                .unwrap_or_default();
+            let id = db
+                .add_sensitive_image(
+                    Some(&scan.image_id),
+                    scan.source_url.as_deref(),
+                    scan.explanation.as_deref(),
+                    Some(reviewed_by),
+                    scan.severity.as_deref(),
+                    &categories,
+                )
+                .await?;
+            ("flagged", Some(id))
+        }
+        other => {
+            return Err(AppError::BadRequest(format!(
+                "invalid action: {other} (expected approve or flag)"
+            )))
+        }
+    };
+
+    db.set_image_scan_review_state(scan.id, review_state).await?;
+
+    Ok(Json(ImageReviewActionResponse {
+        scan_id: scan.id,
+        review_state: review_state.to_string(),
+        sensitive_image_id,
+    }))
+}
+
+/// Override the automated verdict for an image.
+///
+/// A safe override removes any sensitive entry for the image and stops
+/// future scans from flagging it; an unsafe override ensures it is flagged.
+/// Either way the decision is kept and written to the audit log.
+pub async fn override_image_scan(
+    State(state): State<AppState>,
+    _: RequireAdmin,
+    Json(request): Json<ImageOverrideRequest>,
+) -> Result<Json<ImageOverrideResponse>, AppError> {
+    let db = state.db.as_ref().ok_or(AppError::LabelerNotConfigured)?;
+
+    let image_id = request.image_id.as_deref().filter(|s| !s.is_empty());
+    let content_hash = request
+        .content_hash
+        .as_deref()
+        .filter(|s| !s.is_empty())
+        .map(|h| h.to_ascii_lowercase());
+    if image_id.is_none() && content_hash.is_none() {
+        return Err(AppError::BadRequest(
+            "at least one of image_id or content_hash must be provided".to_string(),
+        ));
+    }
+    let verdict = OverrideVerdict::from_str(&request.verdict).ok_or_else(|| {
+        AppError::BadRequest(format!(
+            "invalid verdict: {} (expected safe or unsafe)",
+            request.verdict
+        ))
+    })?;
+    if verdict == OverrideVerdict::Unsafe && image_id.is_none() {
+        return Err(AppError::BadRequest(
+            "image_id is required for an unsafe override".to_string(),
+        ));
+    }
+    if request.reviewed_by.trim().is_empty() {
+        return Err(AppError::BadRequest("reviewed_by is required".to_string()));
+    }
+
+    tracing::info!(
+        image_id = ?image_id,
+        content_hash = ?content_hash,
+        verdict = verdict.as_str(),
+        reviewed_by = %request.reviewed_by,
+        "overriding image verdict"
+    );
+
+    let applied = db
+        .apply_image_override(
+            image_id,
+            content_hash.as_deref(),
+            verdict,
+            &request.reviewed_by,
+            request.notes.as_deref(),
+        )
+        .await?;
+
+    Ok(Json(ImageOverrideResponse {
+        override_id: applied.override_id,
+        verdict,
+        removed: applied.removed,
+        sensitive_image_id: applied.sensitive_image_id,
+    }))
+}
+
+/// List image scan history with filters.
+///
+/// Lets admins see why an image was (or wasn't) flagged: the stored Claude
+/// explanation, categories, model, and cost.
+pub async fn list_image_scans(
+    State(state): State<AppState>,
+    Query(query): Query<ImageScansQuery>,
+) -> Result<Json<ImageScansResponse>, AppError> {
+    let db = state.db.as_ref().ok_or(AppError::LabelerNotConfigured)?;
+    Ok(Json(fetch_image_scans(db, &query).await?))
+}
+
+/// Render image scan history as HTML partial for htmx.
+pub async fn list_image_scans_html(
+    State(state): State<AppState>,
+    Query(query): Query<ImageScansQuery>,
+) -> Result<Response, AppError> {
+    let db = state.db.as_ref().ok_or(AppError::LabelerNotConfigured)?;
+    let page = fetch_image_scans(db, &query).await?;
+    let html = render_image_scans(&page, &query, state.image_base_url.as_deref());
+    Ok(([(CONTENT_TYPE, "text/html; charset=utf-8")], html).into_response())
+}
+
+async fn fetch_image_scans(
+    db: &crate::db::LabelDb,
+    query: &ImageScansQuery,
+) -> Result<ImageScansResponse, AppError> {
+    let is_safe = match query.verdict.as_deref() {
+        None | Some("") | Some("all") => None,
+        Some("safe") => Some(true),
+        Some("unsafe") => Some(false),
+        Some(other) => {
+            return Err(AppError::BadRequest(format!(
+                "invalid verdict: {other} (expected safe or unsafe)"
+            )))
+        }
+    };
+    let filter = ImageScanFilter {
+        image_id: query.image_id.as_deref().filter(|s| !s.is_empty()),
+        is_safe,
+        severity: query.severity.as_deref().filter(|s| !s.is_empty()),
+        since: query.since,
+        until: query.until,
+    };
+    let limit = query.limit.clamp(1, 200);
+    let offset = query.offset.max(0);
+
+    let (rows, total) = db.list_image_scans(&filter, limit, offset).await?;
+    let scans = rows
+        .into_iter()
+        .map(|scan| ImageScanEntry {
+            cost_usd: scan_cost_usd(&scan),
+            scan,
+        })
+        .collect();
+
+    Ok(ImageScansResponse {
+        scans,
+        total,
+        limit,
+        offset,
+    })
+}
+
+/// Estimated cost of a stored scan: the cost recorded when it ran, or for
+/// older scans, today's price for its model and token counts.
+fn scan_cost_usd(scan: &ImageScanRow) -> Option<f64> {
+    if scan.cost_usd.is_some() {
+        return scan.cost_usd;
+    }
+    match (&scan.model, scan.input_tokens, scan.output_tokens) {
+        (Some(model), Some(input), Some(output)) => {
+            estimate_cost_usd(model, input.max(0) as u32, output.max(0) as u32)
+        }
+        _ => None,
+    }
+}
+
+/// Render a page of scans with pagination controls.
+fn render_image_scans(
+    page: &ImageScansResponse,
+    query: &ImageScansQuery,
+    image_base_url: Option<&str>,
+) -> String {
+    let header = format!(
+        r#"<div class="filter-row"><span class="filter-count">{} scans</span></div>"#,
+        page.total
+    );
+
+    if page.scans.is_empty() {
+        return format!(r#"{}<div class="empty">no image scans</div>"#, header);
+    }
+
+    let cards: Vec<String> = page
+        .scans
+        .iter()
+        .map(|entry| render_image_scan_card(entry, image_base_url))
+        .collect();
+
+    let mut pager = Vec::new();
+    if page.offset > 0 {
+        pager.push(pager_button(query, (page.offset - page.limit).max(0), "newer"));
+    }
+    if page.offset + page.limit < page.total {
+        pager.push(pager_button(query, page.offset + page.limit, "older"));
+    }
+
+    format!(
+        r#"{}
+{}
+<div class="pager">{}</div>"#,
+        header,
+        cards.join("\n"),
+        pager.join("")
+    )
+}
+
+/// Build a pager button that keeps the current filters.
+fn pager_button(query: &ImageScansQuery, offset: i64, label: &str) -> String {
+    let mut params = vec![
+        format!("limit={}", query.limit),
+        format!("offset={}", offset),
+    ];
+    let filters = [
+        ("image_id", query.image_id.clone()),
+        ("verdict", query.verdict.clone()),
+        ("severity", query.severity.clone()),
+        ("since", query.since.map(|d| d.to_rfc3339())),
+        ("until", query.until.map(|d| d.to_rfc3339())),
+    ];
+    for (key, value) in filters {
+        if let Some(v) = value.filter(|v| !v.is_empty()) {
+            params.push(format!("{}={}", key, url_encode(&v)));
+        }
+    }
+    format!(
+        r##"<button type="button" class="btn btn-secondary" hx-get="/admin/image-scans-html?{}" hx-target="#image-scans-list">{}</button>"##,
+        html_escape(&params.join("&")),
+        label
+    )
+}
+
+/// Render a single scan as a card.
+fn render_image_scan_card(entry: &ImageScanEntry, image_base_url: Option<&str>) -> String {
+    let scan = &entry.scan;
+
+    let image_url = scan.source_url.clone().or_else(|| {
+        image_base_url.map(|base| format!("{}/{}", base, scan.image_id))
+    });
+    let thumbnail = image_url
+        .map(|url| {
+            format!(
+                r#"<a href="{0}" target="_blank" rel="noopener"><img class="scan-thumb" src="{0}" alt="" loading="lazy"></a>"#,
+                html_escape(&url)
+            )
+        })
+        .unwrap_or_default();
+
+    let verdict_badge = if scan.is_safe {
+        r#"<span class="badge resolved">safe</span>"#.to_string()
+    } else {
+        format!(
+            r#"<span class="badge pending">{}</span>"#,
+            html_escape(scan.severity.as_deref().unwrap_or("unsafe"))
+        )
+    };
+    let review_badge = scan
+        .review_state
+        .as_deref()
+        .map(|s| format!(r#"<span class="badge">{}</span>"#, html_escape(s)))
+        .unwrap_or_default();
+
+    let categories: Vec<String> = scan
+        .violated_categories
+        .as_ref()
+        .and_then(|v| serde_json::from_value(v.clone()).ok())
+        .unwrap_or_default();
+    let categories_html = if categories.is_empty() {
+        String::new()
+    } else {
+        format!(
+            r#"<div class="scan-categories">{}</div>"#,
+            html_escape(&categories.join(", "))
+        )
+    };
+
+    let cost = entry
+        .cost_usd
+        .map(|c| format!(" · ${:.4}", c))
+        .unwrap_or_default();
+
+    format!(
+        r#"<div class="flag-card scan-card">
+            <div class="flag-header">
+                {}
+                <div class="track-info">
+                    <div class="uri">{}</div>
+                    <div class="scan-meta">{} · <span title="{}">{}</span>{}</div>
+                </div>
+                <div class="flag-badges">
+                    {}
+                    {}
+                </div>
+            </div>
+            {}
+            <div class="scan-explanation">{}</div>
+        </div>"#,
+        thumbnail,
+        html_escape(&scan.image_id),
+        scan.scanned_at.format("%Y-%m-%d %H:%M UTC"),
+        html_escape(scan.request_id.as_deref().unwrap_or("")),
+        html_escape(scan.model.as_deref().unwrap_or("unknown model")),
+        cost,
+        verdict_badge,
+        review_badge,
+        categories_html,
+        html_escape(scan.explanation.as_deref().unwrap_or(""))
+    )
+}
+
+/// Percent-encode a query parameter value.
+fn url_encode(s: &str) -> String {
+    s.bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+/// Simple HTML escaping.
+fn html_escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#039;")
+}
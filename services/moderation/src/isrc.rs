@@ -0,0 +1,257 @@
+//! ISRC allowlist for catalogs we license.
+//!
+//! AuDD matches against a licensed catalog are expected, so a scan whose
+//! matches are all on allowlisted ISRCs isn't flagged. Entries come from
+//! `MODERATION_ISRC_ALLOWLIST` (fixed until restart) and the `isrc_allowlist`
+//! table (managed through `/admin/isrc-allowlist`). Both are held in memory
+//! so scoring a scan doesn't touch the database; the table is reloaded every
+//! minute so edits made on another replica take effect there too.
+
+use std::collections::HashSet;
+use std::sync::RwLock;
+
+use axum::{extract::State, Json};
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::audd::AuddMatch;
+use crate::auth::{Actor, RequireAdmin};
+use crate::db::IsrcAllowlistRow;
+use crate::state::{AppError, AppState};
+
+/// Allowlisted ISRCs, normalized to uppercase without hyphens.
+#[derive(Debug, Default)]
+pub struct IsrcAllowlist {
+    configured: HashSet<String>,
+    managed: RwLock<HashSet<String>>,
+}
+
+impl IsrcAllowlist {
+    /// Build the allowlist from configured ISRCs. Invalid entries are logged
+    /// and skipped.
+    pub fn new(configured: impl IntoIterator<Item = String>) -> Self {
+        let configured = configured
+            .into_iter()
+            .filter_map(|raw| {
+                let isrc = normalize_isrc(&raw);
+                if isrc.is_none() {
+                    warn!(isrc = %raw, "ignoring invalid ISRC in MODERATION_ISRC_ALLOWLIST");
+                }
+                isrc
+            })
+            .collect();
+        Self {
+            configured,
+            managed: RwLock::default(),
+        }
+    }
+
+    /// Replace the database-managed entries.
+    pub fn set_managed(&self, isrcs: impl IntoIterator<Item = String>) {
+        *self.managed.write().unwrap_or_else(|e| e.into_inner()) = isrcs.into_iter().collect();
+    }
+
+    pub fn insert(&self, isrc: String) {
+        self.managed
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(isrc);
+    }
+
+    pub fn remove(&self, isrc: &str) {
+        self.managed
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(isrc);
+    }
+
+    /// Whether the ISRC was set in `MODERATION_ISRC_ALLOWLIST`.
+    pub fn is_configured(&self, isrc: &str) -> bool {
+        self.configured.contains(isrc)
+    }
+
+    /// Configured ISRCs, sorted.
+    pub fn configured(&self) -> Vec<String> {
+        let mut isrcs: Vec<String> = self.configured.iter().cloned().collect();
+        isrcs.sort();
+        isrcs
+    }
+
+    pub fn contains(&self, isrc: &str) -> bool {
+        let Some(isrc) = normalize_isrc(isrc) else {
+            return false;
+        };
+        self.configured.contains(&isrc)
+            || self
+                .managed
+                .read()
+                .unwrap_or_else(|e| e.into_inner())
+                .contains(&isrc)
+    }
+
+    /// True if there are matches and every one is on an allowlisted ISRC.
+    /// A match without an ISRC can't be vouched for, so it keeps the scan
+    /// flaggable.
+    pub fn covers(&self, matches: &[AuddMatch]) -> bool {
+        !matches.is_empty()
+            && matches
+                .iter()
+                .all(|m| m.isrc.as_deref().is_some_and(|isrc| self.contains(isrc)))
+    }
+}
+
+/// Normalize an ISRC to its 12-character form (`CCXXXYYNNNNN`), accepting
+/// lowercase and the hyphenated display form. Returns `None` if it isn't one.
+pub fn normalize_isrc(raw: &str) -> Option<String> {
+    let isrc: String = raw
+        .trim()
+        .chars()
+        .filter(|c| *c != '-')
+        .map(|c| c.to_ascii_uppercase())
+        .collect();
+    let bytes = isrc.as_bytes();
+    let valid = bytes.len() == 12
+        && bytes[..2].iter().all(u8::is_ascii_uppercase)
+        && bytes[2..5].iter().all(u8::is_ascii_alphanumeric)
+        && bytes[5..].iter().all(u8::is_ascii_digit);
+    valid.then_some(isrc)
+}
+
+fn parse_isrc(raw: &str) -> Result<String, AppError> {
+    normalize_isrc(raw).ok_or_else(|| AppError::BadRequest(format!("invalid ISRC: {raw}")))
+}
+
+// --- handlers ---
+
+/// Response for listing the allowlist.
+#[derive(Debug, Serialize)]
+pub struct IsrcAllowlistResponse {
+    /// Entries managed through the admin endpoints.
+    pub entries: Vec<IsrcAllowlistRow>,
+    /// Entries from `MODERATION_ISRC_ALLOWLIST`, which can't be removed here.
+    pub configured: Vec<String>,
+}
+
+/// List allowlisted ISRCs.
+pub async fn list_allowlist(
+    State(state): State<AppState>,
+) -> Result<Json<IsrcAllowlistResponse>, AppError> {
+    let db = state.db.as_ref().ok_or(AppError::LabelerNotConfigured)?;
+    let entries = db.list_isrc_allowlist().await?;
+    Ok(Json(IsrcAllowlistResponse {
+        entries,
+        configured: state.isrc_allowlist.configured(),
+    }))
+}
+
+/// Request to allowlist an ISRC.
+#[derive(Debug, Deserialize)]
+pub struct AddIsrcRequest {
+    pub isrc: String,
+    /// Which catalog or agreement covers it.
+    #[serde(default)]
+    pub note: String,
+}
+
+/// Allowlist an ISRC. Re-adding one updates its note.
+pub async fn add_isrc(
+    State(state): State<AppState>,
+    _: RequireAdmin,
+    actor: Actor,
+    Json(request): Json<AddIsrcRequest>,
+) -> Result<Json<IsrcAllowlistRow>, AppError> {
+    let db = state.db.as_ref().ok_or(AppError::LabelerNotConfigured)?;
+    let isrc = parse_isrc(&request.isrc)?;
+
+    let row = db
+        .upsert_isrc_allowlist(&isrc, request.note.trim(), actor.as_str())
+        .await?;
+    state.isrc_allowlist.insert(row.isrc.clone());
+    db.record_audit_event(
+        "add_isrc_allowlist",
+        &row.isrc,
+        actor.as_str(),
+        serde_json::json!({ "note": row.note }),
+    )
+    .await?;
+    Ok(Json(row))
+}
+
+/// Request to remove an ISRC from the allowlist.
+#[derive(Debug, Deserialize)]
+pub struct RemoveIsrcRequest {
+    pub isrc: String,
+}
+
+/// Response after removing an ISRC.
+#[derive(Debug, Serialize)]
+pub struct RemoveIsrcResponse {
+    pub removed: bool,
+}
+
+/// Remove an ISRC from the allowlist. Scans already scored keep their result.
+pub async fn remove_isrc(
+    State(state): State<AppState>,
+    _: RequireAdmin,
+    actor: Actor,
+    Json(request): Json<RemoveIsrcRequest>,
+) -> Result<Json<RemoveIsrcResponse>, AppError> {
+    let db = state.db.as_ref().ok_or(AppError::LabelerNotConfigured)?;
+    let isrc = parse_isrc(&request.isrc)?;
+
+    if !db.remove_isrc_allowlist(&isrc).await? {
+        return Err(if state.isrc_allowlist.is_configured(&isrc) {
+            AppError::BadRequest(format!(
+                "{isrc} is set in MODERATION_ISRC_ALLOWLIST; remove it there"
+            ))
+        } else {
+            AppError::NotFound(format!("{isrc} is not allowlisted"))
+        });
+    }
+    state.isrc_allowlist.remove(&isrc);
+    db.record_audit_event(
+        "remove_isrc_allowlist",
+        &isrc,
+        actor.as_str(),
+        serde_json::json!({}),
+    )
+    .await?;
+    Ok(Json(RemoveIsrcResponse { removed: true }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_isrc() {
+        assert_eq!(
+            normalize_isrc("USRC17607839").as_deref(),
+            Some("USRC17607839")
+        );
+        assert_eq!(
+            normalize_isrc(" us-rc1-76-07839 ").as_deref(),
+            Some("USRC17607839")
+        );
+        assert_eq!(normalize_isrc("USRC1760783"), None);
+        assert_eq!(normalize_isrc("1SRC17607839"), None);
+        assert_eq!(normalize_isrc("USRC17A07839"), None);
+    }
+
+    #[test]
+    fn test_configured_and_managed_entries() {
+        let allowlist = IsrcAllowlist::new(["usrc17607839".to_string(), "bogus".to_string()]);
+        assert_eq!(allowlist.configured(), vec!["USRC17607839".to_string()]);
+        assert!(allowlist.contains("US-RC1-76-07839"));
+        assert!(!allowlist.contains("GBAYE0000351"));
+
+        allowlist.insert("GBAYE0000351".to_string());
+        assert!(allowlist.contains("GBAYE0000351"));
+        allowlist.remove("GBAYE0000351");
+        assert!(!allowlist.contains("GBAYE0000351"));
+
+        allowlist.set_managed(["GBAYE0000351".to_string()]);
+        assert!(allowlist.contains("GBAYE0000351"));
+        assert!(allowlist.contains("USRC17607839"));
+    }
+}
@@ -0,0 +1,132 @@
+//! Background maintenance tasks.
+
+use std::{sync::Arc, time::Duration};
+
+use tracing::{info, warn};
+
+use crate::db::LabelDb;
+use crate::isrc::IsrcAllowlist;
+
+use crate::storage::StorageClient;
+
+/// How often the expired-label pruner runs.
+const LABEL_PRUNE_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// How often overdue review batches are marked expired.
+const BATCH_EXPIRY_INTERVAL: Duration = Duration::from_secs(15 * 60);
+
+/// How often the in-memory ISRC allowlist is reloaded from the database.
+const ISRC_ALLOWLIST_RELOAD_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Periodically delete labels that expired more than `older_than_days` ago.
+///
+/// Expired labels are already excluded from active results; this only keeps
+/// the table from growing without bound.
+pub fn spawn_label_pruner(db: Arc<LabelDb>, older_than_days: i64) {
+    info!(older_than_days, "expired label pruning enabled");
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(LABEL_PRUNE_INTERVAL);
+        loop {
+            interval.tick().await;
+            match db.prune_expired_labels(older_than_days).await {
+                Ok(0) => {}
+                Ok(removed) => info!(removed, "pruned expired labels"),
+                Err(e) => warn!(error = %e, "failed to prune expired labels"),
+            }
+        }
+    });
+}
+
+/// Periodically delete resolved labels and reports older than
+/// `retain_days`, keeping monthly counts in `retention_archive`. Screenshots
+/// uploaded with the deleted reports are removed from `storage`.
+pub fn spawn_retention_pruner(
+    db: Arc<LabelDb>,
+    storage: Option<Arc<StorageClient>>,
+    retain_days: i64,
+    every: Duration,
+) {
+    info!(
+        retain_days,
+        interval_secs = every.as_secs(),
+        "retention pruning enabled"
+    );
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(every);
+        loop {
+            interval.tick().await;
+            let pruned = match db.prune_resolved(retain_days).await {
+                Ok(pruned) => pruned,
+                Err(e) => {
+                    warn!(error = %e, "failed to prune resolved labels and reports");
+                    continue;
+                }
+            };
+            info!(
+                labels = pruned.labels,
+                reports = pruned.reports,
+                screenshots = pruned.screenshot_keys.len(),
+                retain_days,
+                "pruned resolved labels and reports"
+            );
+            delete_screenshots(storage.as_deref(), &pruned.screenshot_keys).await;
+        }
+    });
+}
+
+/// Delete report screenshots from the bucket. The rows are already gone, so
+/// a failure only leaves an orphaned object behind; it's logged, not retried.
+async fn delete_screenshots(storage: Option<&StorageClient>, keys: &[String]) {
+    if keys.is_empty() {
+        return;
+    }
+    let Some(storage) = storage else {
+        warn!(
+            count = keys.len(),
+            "storage not configured - pruned reports' screenshots left in the bucket"
+        );
+        return;
+    };
+    for key in keys {
+        if let Err(e) = storage.delete_object(key).await {
+            warn!(error = %e, %key, "failed to delete pruned report screenshot");
+        }
+    }
+}
+
+/// Periodically mark pending review batches past `expires_at` as expired.
+///
+/// Review pages and submissions already treat overdue batches as expired;
+/// this keeps the stored status (and the batch list) in step.
+pub fn spawn_batch_expirer(db: Arc<LabelDb>) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(BATCH_EXPIRY_INTERVAL);
+        loop {
+            interval.tick().await;
+            match db.expire_batches().await {
+                Ok(0) => {}
+                Ok(expired) => info!(expired, "expired stale review batches"),
+                Err(e) => warn!(error = %e, "failed to expire review batches"),
+            }
+        }
+    });
+}
+
+/// Periodically reload the database-managed ISRC allowlist.
+///
+/// Admin edits update the replica that served them immediately; this picks
+/// them up on every other replica within one interval.
+pub fn spawn_isrc_allowlist_reloader(db: Arc<LabelDb>, allowlist: Arc<IsrcAllowlist>) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(ISRC_ALLOWLIST_RELOAD_INTERVAL);
+        // the first tick fires immediately, and startup already loaded it
+        interval.tick().await;
+        loop {
+            interval.tick().await;
+            match db.list_isrc_allowlist().await {
+                Ok(rows) => allowlist.set_managed(rows.into_iter().map(|row| row.isrc)),
+                Err(e) => warn!(error = %e, "failed to reload ISRC allowlist"),
+            }
+        }
+    });
+}
@@ -8,6 +8,15 @@ use chrono::Utc;
 use k256::ecdsa::{signature::Signer, Signature, SigningKey};
 use serde::{Deserialize, Serialize};
 
+/// Label values this labeler emits, for admin tools that pick one.
+pub const LABEL_VALUES: [&str; 5] = [
+    "copyright-violation",
+    "copyright-review",
+    "sensitive-image",
+    "sexual",
+    "porn",
+];
+
 /// ATProto label as defined in com.atproto.label.defs#label.
 ///
 /// Labels are signed by the labeler's `#atproto_label` key.
@@ -148,6 +157,13 @@ struct UnsignedLabel<'a> {
     exp: Option<&'a str>,
 }
 
+/// The namespace of an AT URI's collection, e.g. `fm.plyr.dev` for
+/// `at://did:plc:xxx/fm.plyr.dev.track/yyy`.
+pub fn uri_namespace(uri: &str) -> Option<&str> {
+    let collection = uri.strip_prefix("at://")?.split('/').nth(1)?;
+    collection.rsplit_once('.').map(|(namespace, _)| namespace)
+}
+
 /// Label-related errors.
 #[derive(Debug, thiserror::Error)]
 pub enum LabelError {
@@ -157,15 +173,27 @@ pub enum LabelError {
     #[error("invalid signing key: {0}")]
     InvalidKey(String),
 
+    #[error("signing key does not match {did}: the key derives {derived}")]
+    DidKeyMismatch { did: String, derived: String },
+
     #[error("database error: {0}")]
     Database(#[from] sqlx::Error),
 }
 
+/// Multicodec prefix for a compressed secp256k1 public key (`secp256k1-pub`).
+const SECP256K1_MULTICODEC: [u8; 2] = [0xe7, 0x01];
+
 /// Label signer that holds the signing key and labeler DID.
+///
+/// Labels on URIs in a non-production namespace (e.g. `fm.plyr.dev`) can be
+/// attributed to a separate DID, so test data stays out of the production
+/// label stream.
 #[derive(Clone)]
 pub struct LabelSigner {
     signing_key: SigningKey,
     labeler_did: String,
+    /// `(namespace, did)` pairs.
+    namespace_dids: Vec<(String, String)>,
 }
 
 impl LabelSigner {
@@ -178,14 +206,88 @@ impl LabelSigner {
         Ok(Self {
             signing_key,
             labeler_did: labeler_did.into(),
+            namespace_dids: Vec::new(),
         })
     }
 
+    /// Attribute labels on URIs in each namespace to its own DID.
+    pub fn with_namespace_dids(mut self, namespace_dids: Vec<(String, String)>) -> Self {
+        self.namespace_dids = namespace_dids;
+        self
+    }
+
     /// Get the labeler DID.
     pub fn did(&self) -> &str {
         &self.labeler_did
     }
 
+    /// The DID labels on `uri` are issued as: its namespace's DID if one is
+    /// configured, otherwise the labeler DID.
+    pub fn did_for(&self, uri: &str) -> &str {
+        uri_namespace(uri)
+            .and_then(|namespace| self.namespace_dids.iter().find(|(ns, _)| ns == namespace))
+            .map_or(&self.labeler_did, |(_, did)| did)
+    }
+
+    /// DIDs used only for non-production namespaces.
+    pub fn namespace_dids(&self) -> Vec<String> {
+        self.namespace_dids
+            .iter()
+            .map(|(_, did)| did.clone())
+            .filter(|did| *did != self.labeler_did)
+            .collect()
+    }
+
+    /// Whether this labeler issues labels as `did`.
+    pub fn issues_as(&self, did: &str) -> bool {
+        did == self.labeler_did || self.namespace_dids.iter().any(|(_, d)| d == did)
+    }
+
+    /// Every DID this labeler issues labels as, the labeler DID first.
+    pub fn issuing_dids(&self) -> Vec<String> {
+        let mut dids = vec![self.labeler_did.clone()];
+        for did in self.namespace_dids() {
+            if !dids.contains(&did) {
+                dids.push(did);
+            }
+        }
+        dids
+    }
+
+    /// Public key in the multibase form published as `#atproto_label` in the
+    /// labeler's DID document.
+    pub fn public_key_multibase(&self) -> String {
+        let point = self.signing_key.verifying_key().to_encoded_point(true);
+        let mut bytes = SECP256K1_MULTICODEC.to_vec();
+        bytes.extend_from_slice(point.as_bytes());
+        multibase::encode(multibase::Base::Base58Btc, bytes)
+    }
+
+    /// The `did:key` form of the signing key's public key.
+    pub fn did_key(&self) -> String {
+        format!("did:key:{}", self.public_key_multibase())
+    }
+
+    /// Check the signing key against every `did:key` this signer issues as.
+    ///
+    /// A `did:key` is the public key itself, so a mismatch means labels would
+    /// be signed with a key nobody can verify them against. Other DID methods
+    /// publish the key in their DID document, which `/admin/health` checks.
+    pub fn verify_did_keys(&self) -> Result<(), LabelError> {
+        let derived = self.did_key();
+        let dids = std::iter::once(&self.labeler_did)
+            .chain(self.namespace_dids.iter().map(|(_, did)| did));
+        for did in dids {
+            if did.starts_with("did:key:") && *did != derived {
+                return Err(LabelError::DidKeyMismatch {
+                    did: did.clone(),
+                    derived,
+                });
+            }
+        }
+        Ok(())
+    }
+
     /// Sign an arbitrary label.
     pub fn sign_label(&self, label: Label) -> Result<Label, LabelError> {
         label.sign(&self.signing_key)
@@ -225,4 +327,101 @@ mod tests {
         assert!(label.sig.is_some());
         assert_eq!(label.sig.as_ref().unwrap().len(), 64); // secp256k1 signature is 64 bytes
     }
+
+    #[test]
+    fn test_public_key_multibase() {
+        let key = format!("{:064x}", 1);
+        let signer = LabelSigner::from_hex(&key, "did:plc:test").unwrap();
+        assert_eq!(
+            signer.public_key_multibase(),
+            "zQ3shVc2UkAfJCdc1TR8E66J85h48P43r93q8jGPkPpjF9Ef9"
+        );
+    }
+
+    #[test]
+    fn test_did_key_must_match_signing_key() {
+        let key = format!("{:064x}", 1);
+        let did_key = "did:key:zQ3shVc2UkAfJCdc1TR8E66J85h48P43r93q8jGPkPpjF9Ef9";
+        let signer = LabelSigner::from_hex(&key, did_key).unwrap();
+        assert_eq!(signer.did_key(), did_key);
+        assert!(signer.verify_did_keys().is_ok());
+
+        let other = format!("{:064x}", 2);
+        let signer = LabelSigner::from_hex(&other, did_key).unwrap();
+        match signer.verify_did_keys() {
+            Err(LabelError::DidKeyMismatch { did, derived }) => {
+                assert_eq!(did, did_key);
+                assert_ne!(derived, did_key);
+            }
+            other => panic!("expected a did:key mismatch, got {other:?}"),
+        }
+
+        // did:plc keys live in the DID document; nothing to compare here.
+        let signer = LabelSigner::from_hex(&other, "did:plc:test").unwrap();
+        assert!(signer.verify_did_keys().is_ok());
+
+        let signer = LabelSigner::from_hex(&key, did_key)
+            .unwrap()
+            .with_namespace_dids(vec![("fm.plyr.dev".into(), "did:key:zWrong".into())]);
+        assert!(matches!(
+            signer.verify_did_keys(),
+            Err(LabelError::DidKeyMismatch { did, .. }) if did == "did:key:zWrong"
+        ));
+    }
+
+    #[test]
+    fn test_namespaced_uris_use_their_own_did() {
+        let key = format!("{:064x}", 1);
+        let signer = LabelSigner::from_hex(&key, "did:plc:prod")
+            .unwrap()
+            .with_namespace_dids(vec![
+                ("fm.plyr.dev".to_string(), "did:plc:dev".to_string()),
+                ("fm.plyr.stg".to_string(), "did:plc:stg".to_string()),
+            ]);
+
+        assert_eq!(
+            signer.did_for("at://did:plc:a/fm.plyr.track/1"),
+            "did:plc:prod"
+        );
+        assert_eq!(
+            signer.did_for("at://did:plc:a/fm.plyr.dev.track/1"),
+            "did:plc:dev"
+        );
+        assert_eq!(
+            signer.did_for("at://did:plc:a/fm.plyr.stg.album/1"),
+            "did:plc:stg"
+        );
+        assert_eq!(signer.did_for("not a uri"), "did:plc:prod");
+
+        assert!(signer.issues_as("did:plc:prod"));
+        assert!(signer.issues_as("did:plc:dev"));
+        assert!(!signer.issues_as("did:plc:other"));
+        assert_eq!(signer.namespace_dids(), vec!["did:plc:dev", "did:plc:stg"]);
+        assert_eq!(
+            signer.issuing_dids(),
+            vec!["did:plc:prod", "did:plc:dev", "did:plc:stg"]
+        );
+
+        // without namespace DIDs everything is issued as the labeler
+        let signer = LabelSigner::from_hex(&key, "did:plc:prod").unwrap();
+        assert_eq!(
+            signer.did_for("at://did:plc:a/fm.plyr.dev.track/1"),
+            "did:plc:prod"
+        );
+        assert!(signer.namespace_dids().is_empty());
+    }
+
+    #[test]
+    fn test_uri_namespace() {
+        assert_eq!(
+            uri_namespace("at://did:plc:a/fm.plyr.track/1"),
+            Some("fm.plyr")
+        );
+        assert_eq!(
+            uri_namespace("at://did:plc:a/fm.plyr.dev.track/1"),
+            Some("fm.plyr.dev")
+        );
+        assert_eq!(uri_namespace("at://did:plc:a"), None);
+        assert_eq!(uri_namespace("https://plyr.fm/track/1"), None);
+    }
 }
@@ -1,7 +1,7 @@
 //! plyr.fm moderation service
 //!
 //! Provides:
-//! - AuDD audio fingerprinting for copyright detection
+//! - AuDD audio fingerprinting for copyright detection (by URL or upload)
 //! - ATProto labeler endpoints (queryLabels, subscribeLabels)
 //! - Label emission for copyright violations
 //! - Admin UI for reviewing and resolving flags
@@ -9,13 +9,7 @@
 use std::{net::SocketAddr, sync::Arc};
 
 use anyhow::anyhow;
-use axum::{
-    middleware,
-    routing::{get, post},
-    Router,
-};
 use tokio::{net::TcpListener, sync::broadcast};
-use tower_http::services::ServeDir;
 use tracing::{info, warn};
 
 mod admin;
@@ -24,11 +18,31 @@ mod auth;
 mod claude;
 mod config;
 mod db;
+mod did;
+mod enforcement;
+mod export;
+mod flagged;
 mod handlers;
+mod image_fetch;
+mod images;
+mod isrc;
+mod jobs;
 mod labels;
+mod ratelimit;
+mod reasons;
+mod report_callback;
+mod reporter_hash;
 mod reports;
+mod reverify;
 mod review;
+mod routes;
+mod search;
+mod session;
 mod state;
+mod stats;
+mod storage;
+mod transcoder;
+mod webhook;
 mod xrpc;
 
 pub use state::{AppError, AppState};
@@ -36,15 +50,17 @@ pub use state::{AppError, AppState};
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
     tracing_subscriber::fmt()
-        .with_env_filter(tracing_subscriber::EnvFilter::from_default_env())
+        .with_env_filter(config::log_filter())
         .with_target(false)
         .init();
 
     let config = config::Config::from_env()?;
-    let auth_token = config.auth_token.clone();
+    if config.session_key_generated {
+        warn!("no MODERATION_SESSION_SECRET set - admin sessions end on restart");
+    }
 
     // Initialize labeler components if configured
-    let (db, signer, label_tx) = if config.labeler_enabled() {
+    let (db, signer, label_tx, label_replay_tx) = if config.labeler_enabled() {
         let db = db::LabelDb::connect(config.database_url.as_ref().unwrap()).await?;
         db.migrate().await?;
         info!("labeler database connected and migrated");
@@ -52,14 +68,25 @@ async fn main() -> anyhow::Result<()> {
         let signer = labels::LabelSigner::from_hex(
             config.labeler_signing_key.as_ref().unwrap(),
             config.labeler_did.as_ref().unwrap(),
-        )?;
-        info!(did = %signer.did(), "labeler signer initialized");
+        )?
+        .with_namespace_dids(config.labeler_namespace_dids.clone());
+        signer.verify_did_keys()?;
+        info!(
+            did = %signer.did(),
+            verification_method = %signer.public_key_multibase(),
+            "labeler signer initialized - compare with the DID document's #atproto_label key"
+        );
+        for (namespace, did) in &config.labeler_namespace_dids {
+            info!(namespace = %namespace, did = %did, "labels on namespace issued as separate DID");
+        }
 
         let (tx, _) = broadcast::channel::<(i64, labels::Label)>(1024);
-        (Some(db), Some(signer), Some(tx))
+        let (replay_tx, _) =
+            broadcast::channel::<(i64, labels::Label)>(admin::MAX_REBROADCAST as usize);
+        (Some(db), Some(signer), Some(tx), Some(replay_tx))
     } else {
         warn!("labeler not configured - XRPC endpoints will return 503");
-        (None, None, None)
+        (None, None, None, None)
     };
 
     // Initialize Claude client for image moderation if configured
@@ -67,80 +94,170 @@ async fn main() -> anyhow::Result<()> {
         let client = claude::ClaudeClient::new(
             config.claude_api_key.clone().unwrap(),
             Some(config.claude_model.clone()),
+        )
+        .with_max_tokens(config.claude_max_tokens)
+        .with_structured_outputs_beta(config.claude_structured_outputs_beta.clone());
+        info!(
+            model = %config.claude_model,
+            max_tokens = config.claude_max_tokens,
+            structured_outputs = config.claude_structured_outputs_beta.is_some(),
+            "claude image moderation enabled"
         );
-        info!(model = %config.claude_model, "claude image moderation enabled");
         Some(client)
     } else {
         warn!("claude not configured - /scan-image endpoint will return 503");
         None
     };
 
+    let storage = match config.storage() {
+        Some(storage_config) => {
+            info!(bucket = %storage_config.bucket, "report screenshot uploads enabled");
+            Some(Arc::new(storage::StorageClient::new(storage_config)))
+        }
+        None => {
+            if config.storage_endpoint.is_some() {
+                warn!("MODERATION_STORAGE_ENDPOINT set without a bucket or credentials - ignoring");
+            }
+            None
+        }
+    };
+
+    let db = db.map(Arc::new);
+    if let (Some(db), Some(days)) = (&db, config.label_prune_after_days) {
+        jobs::spawn_label_pruner(db.clone(), days);
+    }
+    if let (Some(db), Some(days)) = (&db, config.retention_days) {
+        let every = std::time::Duration::from_secs(config.retention_interval_hours * 60 * 60);
+        jobs::spawn_retention_pruner(db.clone(), storage.clone(), days, every);
+    }
+    if let Some(db) = &db {
+        jobs::spawn_batch_expirer(db.clone());
+    }
+
+    let isrc_allowlist = Arc::new(isrc::IsrcAllowlist::new(config.isrc_allowlist.clone()));
+    if let Some(db) = &db {
+        let managed = db.list_isrc_allowlist().await?;
+        isrc_allowlist.set_managed(managed.into_iter().map(|row| row.isrc));
+        jobs::spawn_isrc_allowlist_reloader(db.clone(), isrc_allowlist.clone());
+    }
+
+    let webhook = config.webhook().map(|webhook_config| {
+        info!(
+            image_flagged = webhook_config.image_flagged_enabled,
+            "webhook notifications enabled"
+        );
+        Arc::new(webhook::WebhookNotifier::spawn(webhook_config, db.clone()))
+    });
+
+    let enforcement = match (config.enforcement(), &db) {
+        (Some(enforcement_config), Some(db)) => {
+            info!(url = %enforcement_config.url, "enforcement callbacks enabled");
+            let notifier = enforcement::EnforcementNotifier::new(enforcement_config);
+            match notifier.resume_pending(db.clone()).await {
+                Ok(0) => {}
+                Ok(count) => info!(count, "resuming pending enforcement callbacks"),
+                Err(e) => warn!(error = %e, "failed to load pending enforcement callbacks"),
+            }
+            Some(Arc::new(notifier))
+        }
+        (Some(_), None) => {
+            warn!("MODERATION_ENFORCEMENT_URL set but labeler not configured - ignoring");
+            None
+        }
+        (None, _) => None,
+    };
+
+    let report_callback = match (config.report_callback(), &db) {
+        (Some(callback_config), Some(db)) => {
+            info!(url = %callback_config.url, "report resolution callbacks enabled");
+            let notifier = report_callback::ReportCallbackNotifier::new(callback_config);
+            match notifier.resume_pending(db.clone()).await {
+                Ok(0) => {}
+                Ok(count) => info!(count, "resuming pending report callbacks"),
+                Err(e) => warn!(error = %e, "failed to load pending report callbacks"),
+            }
+            Some(Arc::new(notifier))
+        }
+        (Some(_), None) => {
+            warn!("MODERATION_REPORT_RESOLVED_URL set but labeler not configured - ignoring");
+            None
+        }
+        (None, _) => None,
+    };
+
+    let transcoder = config.transcoder().map(|transcoder_config| {
+        info!(url = %transcoder_config.url, "sampled audio scans enabled");
+        Arc::new(transcoder::TranscoderClient::new(transcoder_config))
+    });
+
+    // these borrow the whole config, so they have to come before fields move out
+    let auth = config.auth_tokens();
+    if let (Some(db), Some(sessions)) = (&db, &auth.sessions) {
+        let now = chrono::Utc::now();
+        for (id, expires_at) in db.get_revoked_sessions().await? {
+            sessions.revoke(&id, expires_at, now);
+        }
+    }
+    let reporter_hasher = config.reporter_hasher();
+    if reporter_hasher.is_some() {
+        info!("reporter DIDs are stored hashed");
+    }
+
     let state = AppState {
         audd_api_token: config.audd_api_token,
         audd_api_url: config.audd_api_url,
-        db: db.map(Arc::new),
+        audd_fallback_enabled: config.audd_fallback_enabled,
+        audd_fallback_url: config.audd_fallback_url,
+        db,
         signer: signer.map(Arc::new),
         label_tx,
+        label_replay_tx,
         claude: claude_client.map(Arc::new),
+        image_base_url: config.image_base_url,
+        image_autoflag_severity: config.image_autoflag_severity,
+        image_second_opinion: config.image_second_opinion,
+        image_second_opinion_model: config.image_second_opinion_model,
+        image_refusal_as_unsafe: config.claude_refusal_as_unsafe,
+        public_url: config.public_url,
+        webhook,
+        enforcement,
+        report_callback,
+        transcoder,
+        storage,
         copyright_score_threshold: config.copyright_score_threshold,
         copyright_mix_song_threshold: config.copyright_mix_song_threshold,
+        isrc_allowlist,
+        stats_cache: Arc::new(stats::StatsCache::default()),
+        report_stats_cache: Arc::new(reports::ReportStatsCache::default()),
+        flag_status_cache: Arc::new(flagged::FlagStatusCache::default()),
+        did_resolver: Arc::new(did::DidResolver::new(config.plc_directory_url)),
+        auth,
+        rate_limiter: Arc::new(ratelimit::RateLimiter::new(
+            config.rate_limit_service,
+            config.rate_limit_session,
+            ratelimit::RATE_LIMIT_WINDOW,
+        )),
+        ip_rate_limiter: Arc::new(ratelimit::IpRateLimiter::new(
+            config.xrpc_query_limit,
+            config.xrpc_subscribe_limit,
+            config.login_limit,
+            config.xrpc_rate_limit_allowlist,
+            config.client_ip_header,
+            ratelimit::RATE_LIMIT_WINDOW,
+        )),
+        report_limits: reports::ReportLimits {
+            per_hour: config.reports_per_hour,
+            per_day: config.reports_per_day,
+        },
+        report_escalation: reports::ReportEscalation {
+            threshold: config.report_escalation_threshold,
+            create_batch: config.report_escalation_batch,
+        },
+        reporter_hasher,
+        review_batch_ttl: chrono::Duration::days(config.review_batch_ttl_days),
     };
 
-    let app = Router::new()
-        // Landing page
-        .route("/", get(handlers::landing))
-        // Health check
-        .route("/health", get(handlers::health))
-        // Sensitive images (public)
-        .route("/sensitive-images", get(handlers::get_sensitive_images))
-        // AuDD scanning
-        .route("/scan", post(audd::scan))
-        // Image moderation via Claude
-        .route("/scan-image", post(handlers::scan_image))
-        // Label emission (internal API)
-        .route("/emit-label", post(handlers::emit_label))
-        // Admin UI and API
-        .route("/admin", get(admin::admin_ui))
-        .route("/admin/flags", get(admin::list_flagged))
-        .route("/admin/flags-html", get(admin::list_flagged_html))
-        .route("/admin/resolve", post(admin::resolve_flag))
-        .route("/admin/resolve-htmx", post(admin::resolve_flag_htmx))
-        .route("/admin/context", post(admin::store_context))
-        .route("/admin/active-labels", post(admin::get_active_labels))
-        .route("/admin/labels", post(admin::get_label_values))
-        .route("/admin/labels-by-value", post(admin::get_labels_by_value))
-        .route("/admin/negated-labels", post(admin::get_negated_labels))
-        .route("/admin/sensitive-images", post(admin::add_sensitive_image))
-        .route(
-            "/admin/sensitive-images/remove",
-            post(admin::remove_sensitive_image),
-        )
-        .route("/admin/batches", post(admin::create_batch))
-        // User reports
-        .route("/reports", post(reports::create_report))
-        .route("/admin/reports", get(reports::list_reports))
-        .route("/admin/reports-html", get(reports::list_reports_html))
-        .route("/admin/reports/:id", get(reports::get_report))
-        .route("/admin/reports/:id/resolve", post(reports::resolve_report))
-        // Review endpoints (under admin, auth protected)
-        .route("/admin/review/:id", get(review::review_page))
-        .route("/admin/review/:id/data", get(review::review_data))
-        .route("/admin/review/:id/submit", post(review::submit_review))
-        // Static files (CSS, JS for admin UI)
-        .nest_service("/static", ServeDir::new("static"))
-        // ATProto XRPC endpoints (public)
-        .route(
-            "/xrpc/com.atproto.label.queryLabels",
-            get(xrpc::query_labels),
-        )
-        .route(
-            "/xrpc/com.atproto.label.subscribeLabels",
-            get(xrpc::subscribe_labels),
-        )
-        .layer(middleware::from_fn(move |req, next| {
-            auth::auth_middleware(req, next, auth_token.clone())
-        }))
-        .with_state(state);
+    let app = routes::router(state);
 
     let addr: SocketAddr = format!("{}:{}", config.host, config.port)
         .parse()
@@ -148,6 +265,11 @@ async fn main() -> anyhow::Result<()> {
     info!(%addr, "moderation service listening");
 
     let listener = TcpListener::bind(addr).await?;
-    axum::serve(listener, app).await?;
+    // the peer address is the fallback for per-IP limits on public endpoints
+    axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<SocketAddr>(),
+    )
+    .await?;
     Ok(())
 }
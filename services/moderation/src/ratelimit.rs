@@ -0,0 +1,498 @@
+//! Rate limits: per credential on label-changing endpoints, and per client
+//! IP on the public XRPC endpoints.
+//!
+//! A leaked token or a buggy caller could emit or negate thousands of labels
+//! a minute before anyone notices. Each credential gets a sliding window of
+//! recent requests; the backend's service token (header auth) gets a higher
+//! limit than browser sessions.
+//!
+//! `queryLabels` and `subscribeLabels` need no token, so a scraper could load
+//! the database through them instead, and the login endpoint is where tokens
+//! get guessed. Those are counted per client IP, each endpoint separately,
+//! with an allowlist for internal callers.
+
+use std::collections::{HashMap, VecDeque};
+use std::hash::Hash;
+use std::net::{IpAddr, SocketAddr};
+use std::str::FromStr;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use axum::{
+    async_trait,
+    extract::{ConnectInfo, FromRequestParts, Request, State},
+    http::{header, request::Parts, HeaderName, HeaderValue},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use tracing::debug;
+
+use crate::auth::Caller;
+use crate::state::{AppError, AppState};
+
+/// Window over which requests are counted.
+pub const RATE_LIMIT_WINDOW: Duration = Duration::from_secs(60);
+
+/// Requests per window for header-token callers when unset.
+pub const DEFAULT_SERVICE_LIMIT: usize = 600;
+
+/// Requests per window for browser sessions when unset.
+pub const DEFAULT_SESSION_LIMIT: usize = 60;
+
+/// `queryLabels` requests per window from one IP when unset.
+pub const DEFAULT_QUERY_LABELS_LIMIT: usize = 120;
+
+/// `subscribeLabels` connections per window from one IP when unset. Several
+/// consumers behind one egress IP may each hold a subscription and reconnect
+/// after a deploy, so this is kept higher than the query limit.
+pub const DEFAULT_SUBSCRIBE_LABELS_LIMIT: usize = 240;
+
+/// `/admin/login` attempts per window from one IP when unset.
+pub const DEFAULT_LOGIN_LIMIT: usize = 10;
+
+/// Header the proxy puts the client's address in when unset.
+pub const DEFAULT_CLIENT_IP_HEADER: &str = "fly-client-ip";
+
+/// Sliding windows of recent request times, per key.
+struct Windows<K> {
+    window: Duration,
+    requests: Mutex<HashMap<K, VecDeque<Instant>>>,
+}
+
+impl<K: Clone + Eq + Hash> Windows<K> {
+    fn new(window: Duration) -> Self {
+        Self {
+            window,
+            requests: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Record `cost` requests for `key` at `now`, or return how long to wait
+    /// if they don't all fit under `limit` in the window. All or nothing:
+    /// rejected requests don't count toward the window.
+    fn check(&self, key: &K, limit: usize, cost: usize, now: Instant) -> Result<(), Duration> {
+        let mut requests = self.requests.lock().unwrap_or_else(|e| e.into_inner());
+        // drop keys whose windows have emptied so the map stays small
+        requests.retain(|_, times| {
+            times
+                .back()
+                .is_some_and(|last| now.saturating_duration_since(*last) < self.window)
+        });
+
+        let times = requests.entry(key.clone()).or_default();
+        while times
+            .front()
+            .is_some_and(|first| now.saturating_duration_since(*first) >= self.window)
+        {
+            times.pop_front();
+        }
+        if times.len() + cost > limit {
+            // wait for enough of the oldest requests to slide out; a cost
+            // above the whole limit never fits, so report a full window
+            let must_expire = times.len() + cost - limit;
+            let retry_after = match times.get(must_expire - 1) {
+                Some(oldest) if cost <= limit => self
+                    .window
+                    .saturating_sub(now.saturating_duration_since(*oldest)),
+                _ => self.window,
+            };
+            return Err(retry_after);
+        }
+        times.extend(std::iter::repeat_n(now, cost));
+        Ok(())
+    }
+}
+
+/// Sliding-window request counts per credential.
+pub struct RateLimiter {
+    service_limit: usize,
+    session_limit: usize,
+    windows: Windows<Caller>,
+}
+
+impl RateLimiter {
+    pub fn new(service_limit: usize, session_limit: usize, window: Duration) -> Self {
+        Self {
+            service_limit,
+            session_limit,
+            windows: Windows::new(window),
+        }
+    }
+
+    /// Record a request at `now`, or return how long to wait if the caller
+    /// is over its limit. Rejected requests don't count toward the window.
+    pub fn check(&self, caller: &Caller, now: Instant) -> Result<(), Duration> {
+        self.check_n(caller, 1, now)
+    }
+
+    /// Record `cost` requests at once, for calls that do the work of many
+    /// (one per URI of a bulk resolve). All or nothing: if they don't all fit
+    /// in the caller's remaining allowance, none are recorded.
+    pub fn check_n(&self, caller: &Caller, cost: usize, now: Instant) -> Result<(), Duration> {
+        let limit = match caller {
+            Caller::Token(_) => self.service_limit,
+            Caller::Session(_) => self.session_limit,
+        };
+        self.windows.check(caller, limit, cost, now)
+    }
+}
+
+/// A public endpoint with its own per-IP limit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PublicEndpoint {
+    QueryLabels,
+    SubscribeLabels,
+    Login,
+}
+
+/// An address or CIDR block, e.g. `10.0.0.0/8` or `fdaa::/16`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IpBlock {
+    network: IpAddr,
+    prefix: u8,
+}
+
+impl FromStr for IpBlock {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (addr, prefix) = match s.split_once('/') {
+            Some((addr, prefix)) => (addr, Some(prefix)),
+            None => (s, None),
+        };
+        let network: IpAddr = addr
+            .trim()
+            .parse()
+            .map_err(|_| format!("invalid address in {s}"))?;
+        let max = if network.is_ipv4() { 32 } else { 128 };
+        let prefix = match prefix {
+            Some(prefix) => prefix
+                .trim()
+                .parse()
+                .ok()
+                .filter(|prefix| *prefix <= max)
+                .ok_or_else(|| format!("invalid prefix length in {s}"))?,
+            None => max,
+        };
+        Ok(Self { network, prefix })
+    }
+}
+
+impl IpBlock {
+    pub fn contains(&self, ip: IpAddr) -> bool {
+        // IPv4 clients can show up as IPv4-mapped IPv6 addresses
+        let ip = match ip {
+            IpAddr::V6(v6) => v6.to_ipv4_mapped().map_or(ip, IpAddr::V4),
+            v4 => v4,
+        };
+        let (network, ip, bits) = match (self.network, ip) {
+            (IpAddr::V4(network), IpAddr::V4(ip)) => {
+                (u32::from(network).into(), u32::from(ip).into(), 32)
+            }
+            (IpAddr::V6(network), IpAddr::V6(ip)) => (network.into(), ip.into(), 128),
+            _ => return false,
+        };
+        same_prefix(network, ip, bits, self.prefix)
+    }
+}
+
+/// Whether the first `prefix` of the `bits`-bit addresses `a` and `b` match.
+fn same_prefix(a: u128, b: u128, bits: u32, prefix: u8) -> bool {
+    // a /0 block shifts by the full width, which checked_shr refuses
+    let shift = bits - u32::from(prefix);
+    a.checked_shr(shift).unwrap_or(0) == b.checked_shr(shift).unwrap_or(0)
+}
+
+/// Per-client-IP limits on the public XRPC endpoints and login.
+pub struct IpRateLimiter {
+    query_limit: usize,
+    subscribe_limit: usize,
+    login_limit: usize,
+    /// Addresses that are never limited, e.g. the backend's private network.
+    allowlist: Vec<IpBlock>,
+    /// Header the proxy puts the client's address in. Requests without it
+    /// are counted by the connecting address.
+    client_ip_header: Option<HeaderName>,
+    windows: Windows<(PublicEndpoint, IpAddr)>,
+}
+
+impl IpRateLimiter {
+    /// A limit of 0 turns that endpoint's limit off.
+    pub fn new(
+        query_limit: usize,
+        subscribe_limit: usize,
+        login_limit: usize,
+        allowlist: Vec<IpBlock>,
+        client_ip_header: Option<HeaderName>,
+        window: Duration,
+    ) -> Self {
+        Self {
+            query_limit,
+            subscribe_limit,
+            login_limit,
+            allowlist,
+            client_ip_header,
+            windows: Windows::new(window),
+        }
+    }
+
+    /// Record a request from `ip` at `now`, or return how long to wait if it
+    /// is over the endpoint's limit.
+    pub fn check(
+        &self,
+        endpoint: PublicEndpoint,
+        ip: IpAddr,
+        now: Instant,
+    ) -> Result<(), Duration> {
+        let limit = match endpoint {
+            PublicEndpoint::QueryLabels => self.query_limit,
+            PublicEndpoint::SubscribeLabels => self.subscribe_limit,
+            PublicEndpoint::Login => self.login_limit,
+        };
+        if limit == 0 || self.allowlist.iter().any(|block| block.contains(ip)) {
+            return Ok(());
+        }
+        self.windows.check(&(endpoint, ip), limit, 1, now)
+    }
+
+    /// The client's address: the first one in the client IP header if the
+    /// request has it, otherwise the connecting address.
+    fn client_ip(&self, request: &Request) -> Option<IpAddr> {
+        self.client_ip_header
+            .as_ref()
+            .and_then(|name| request.headers().get(name))
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.split(',').next())
+            .and_then(|ip| ip.trim().parse().ok())
+            .or_else(|| {
+                request
+                    .extensions()
+                    .get::<ConnectInfo<SocketAddr>>()
+                    .map(|ConnectInfo(addr)| addr.ip())
+            })
+    }
+}
+
+/// Middleware limiting `queryLabels` per client IP.
+pub async fn limit_query_labels(
+    State(state): State<AppState>,
+    request: Request,
+    next: Next,
+) -> Response {
+    limit_public(&state, PublicEndpoint::QueryLabels, request, next).await
+}
+
+/// Middleware limiting `subscribeLabels` connections per client IP.
+pub async fn limit_subscribe_labels(
+    State(state): State<AppState>,
+    request: Request,
+    next: Next,
+) -> Response {
+    limit_public(&state, PublicEndpoint::SubscribeLabels, request, next).await
+}
+
+/// Middleware limiting `/admin/login` attempts per client IP, so tokens can't
+/// be guessed at the rate of the other public endpoints.
+pub async fn limit_login(State(state): State<AppState>, request: Request, next: Next) -> Response {
+    limit_public(&state, PublicEndpoint::Login, request, next).await
+}
+
+async fn limit_public(
+    state: &AppState,
+    endpoint: PublicEndpoint,
+    request: Request,
+    next: Next,
+) -> Response {
+    let limiter = &state.ip_rate_limiter;
+    if let Some(ip) = limiter.client_ip(&request) {
+        if let Err(retry_after) = limiter.check(endpoint, ip, Instant::now()) {
+            debug!(%ip, ?endpoint, "public rate limit exceeded");
+            return RateLimitExceeded { retry_after }.into_response();
+        }
+    }
+    next.run(request).await
+}
+
+/// Extractor that counts the request against the caller's rate limit.
+///
+/// Requests without a [`Caller`] (public paths) are not limited.
+#[derive(Debug, Clone, Copy)]
+pub struct RateLimited;
+
+/// Rejection when a caller is over its limit: 429 with `Retry-After`.
+#[derive(Debug)]
+pub struct RateLimitExceeded {
+    pub retry_after: Duration,
+}
+
+impl IntoResponse for RateLimitExceeded {
+    fn into_response(self) -> Response {
+        // round up so clients never retry a moment too early
+        let secs = self.retry_after.as_secs() + u64::from(self.retry_after.subsec_nanos() > 0);
+        let mut response =
+            AppError::TooManyRequests(format!("rate limit exceeded, retry in {secs}s"))
+                .into_response();
+        response
+            .headers_mut()
+            .insert(header::RETRY_AFTER, HeaderValue::from(secs));
+        response
+    }
+}
+
+#[async_trait]
+impl FromRequestParts<AppState> for RateLimited {
+    type Rejection = RateLimitExceeded;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &AppState,
+    ) -> Result<Self, Self::Rejection> {
+        let Some(caller) = parts.extensions.get::<Caller>() else {
+            return Ok(Self);
+        };
+        state
+            .rate_limiter
+            .check(caller, Instant::now())
+            .map(|()| Self)
+            .map_err(|retry_after| RateLimitExceeded { retry_after })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn service() -> Caller {
+        Caller::Token("backend".to_string())
+    }
+
+    fn session() -> Caller {
+        Caller::Session("alice".to_string())
+    }
+
+    #[test]
+    fn test_limit_exceeded_then_recovers() {
+        let limiter = RateLimiter::new(3, 1, RATE_LIMIT_WINDOW);
+        let start = Instant::now();
+        for i in 0..3 {
+            assert!(limiter
+                .check(&service(), start + Duration::from_secs(i))
+                .is_ok());
+        }
+
+        let retry = limiter
+            .check(&service(), start + Duration::from_secs(10))
+            .unwrap_err();
+        assert_eq!(retry, Duration::from_secs(50));
+
+        // the first request slides out of the window
+        assert!(limiter.check(&service(), start + RATE_LIMIT_WINDOW).is_ok());
+        assert!(limiter
+            .check(&service(), start + RATE_LIMIT_WINDOW)
+            .is_err());
+    }
+
+    #[test]
+    fn test_limits_are_per_credential_and_class() {
+        let limiter = RateLimiter::new(2, 1, RATE_LIMIT_WINDOW);
+        let now = Instant::now();
+        assert!(limiter.check(&session(), now).is_ok());
+        assert!(limiter.check(&session(), now).is_err());
+
+        // a different session has its own window
+        assert!(limiter
+            .check(&Caller::Session("bob".to_string()), now)
+            .is_ok());
+
+        // service tokens get the higher limit
+        assert!(limiter.check(&service(), now).is_ok());
+        assert!(limiter.check(&service(), now).is_ok());
+        assert!(limiter.check(&service(), now).is_err());
+    }
+
+    #[test]
+    fn test_bulk_cost_is_all_or_nothing() {
+        let limiter = RateLimiter::new(10, 5, RATE_LIMIT_WINDOW);
+        let start = Instant::now();
+        assert!(limiter.check_n(&session(), 3, start).is_ok());
+
+        // 3 + 3 > 5: rejected, and nothing recorded
+        let retry = limiter
+            .check_n(&session(), 3, start + Duration::from_secs(10))
+            .unwrap_err();
+        assert_eq!(retry, Duration::from_secs(50));
+        assert!(limiter
+            .check_n(&session(), 2, start + Duration::from_secs(10))
+            .is_ok());
+        assert!(limiter
+            .check(&session(), start + Duration::from_secs(10))
+            .is_err());
+
+        // more than the whole limit never fits
+        assert_eq!(
+            limiter.check_n(&service(), 11, start),
+            Err(RATE_LIMIT_WINDOW)
+        );
+        assert!(limiter.check_n(&service(), 10, start).is_ok());
+    }
+
+    #[test]
+    fn test_ip_limits_are_per_endpoint_and_skip_the_allowlist() {
+        let allowlist = vec!["10.0.0.0/8".parse().unwrap(), "fdaa::/16".parse().unwrap()];
+        let limiter = IpRateLimiter::new(2, 1, 1, allowlist, None, RATE_LIMIT_WINDOW);
+        let now = Instant::now();
+        let query = |ip: &str| limiter.check(PublicEndpoint::QueryLabels, ip.parse().unwrap(), now);
+        let subscribe =
+            |ip: &str| limiter.check(PublicEndpoint::SubscribeLabels, ip.parse().unwrap(), now);
+
+        assert!(query("203.0.113.9").is_ok());
+        assert!(query("203.0.113.9").is_ok());
+        assert!(query("203.0.113.9").is_err());
+        // subscriptions and logins have their own windows
+        assert!(subscribe("203.0.113.9").is_ok());
+        assert!(subscribe("203.0.113.9").is_err());
+        let login = |ip: &str| limiter.check(PublicEndpoint::Login, ip.parse().unwrap(), now);
+        assert!(login("203.0.113.9").is_ok());
+        assert!(login("203.0.113.9").is_err());
+
+        for internal in ["10.1.2.3", "fdaa:0:1::3", "::ffff:10.9.9.9"] {
+            for _ in 0..5 {
+                assert!(query(internal).is_ok());
+            }
+        }
+
+        let unlimited = IpRateLimiter::new(0, 0, 0, Vec::new(), None, RATE_LIMIT_WINDOW);
+        let scraper = "203.0.113.9".parse().unwrap();
+        for _ in 0..5 {
+            assert!(unlimited
+                .check(PublicEndpoint::QueryLabels, scraper, now)
+                .is_ok());
+        }
+    }
+
+    #[test]
+    fn test_ip_block_parsing() {
+        let block = |s: &str| s.parse::<IpBlock>();
+        let ip = |s: &str| s.parse::<IpAddr>().unwrap();
+        assert!(block("192.168.0.0/16").unwrap().contains(ip("192.168.4.1")));
+        assert!(!block("192.168.0.0/16").unwrap().contains(ip("192.169.0.1")));
+        assert!(block("127.0.0.1").unwrap().contains(ip("127.0.0.1")));
+        assert!(!block("127.0.0.1").unwrap().contains(ip("127.0.0.2")));
+        assert!(block("0.0.0.0/0").unwrap().contains(ip("8.8.8.8")));
+        assert!(block("::/0").unwrap().contains(ip("2001:db8::1")));
+        assert!(!block("10.0.0.0/8").unwrap().contains(ip("fdaa::1")));
+        assert!(block("10.0.0.0/33").is_err());
+        assert!(block("fdaa::/129").is_err());
+        assert!(block("not-an-ip").is_err());
+    }
+
+    #[test]
+    fn test_rejection_sets_retry_after() {
+        let response = RateLimitExceeded {
+            retry_after: Duration::from_millis(1500),
+        }
+        .into_response();
+        assert_eq!(response.status(), axum::http::StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(response.headers()[header::RETRY_AFTER], "2");
+    }
+}
@@ -0,0 +1,313 @@
+//! Resolution reasons backed by the `resolution_reasons` table, and report
+//! reasons backed by `report_reasons`.
+//!
+//! Reviewers pick a resolution reason when resolving a flag; users pick a
+//! report reason when filing a report. Both lists live in the database so
+//! new reasons don't need a deploy, and UIs fetch them from
+//! `GET /admin/resolution-reasons` and the public `GET /report-reasons`
+//! instead of hardcoding them.
+
+use axum::{
+    extract::{Query, State},
+    Json,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::auth::{Actor, RequireAdmin};
+use crate::db::{LabelDb, ReportReasonRow, ResolutionReason, ResolutionReasonRow};
+use crate::state::{AppError, AppState};
+
+/// Longest accepted reason key.
+const MAX_KEY_LEN: usize = 64;
+
+/// Resolve an incoming reason key to a typed reason, rejecting keys that
+/// aren't active in `resolution_reasons`.
+pub async fn validate_reason(
+    db: &LabelDb,
+    key: Option<&str>,
+) -> Result<Option<ResolutionReason>, AppError> {
+    let Some(key) = key.filter(|k| !k.is_empty()) else {
+        return Ok(None);
+    };
+    let row = db.get_resolution_reason(key).await?;
+    check_reason(key, row.as_ref()).map(Some)
+}
+
+/// A reason key is usable if it exists and is active.
+fn check_reason(
+    key: &str,
+    row: Option<&ResolutionReasonRow>,
+) -> Result<ResolutionReason, AppError> {
+    match row {
+        Some(row) if row.active => Ok(ResolutionReason::from_key(&row.key)),
+        Some(_) => Err(AppError::BadRequest(format!(
+            "resolution reason {key} is no longer active"
+        ))),
+        None => Err(AppError::BadRequest(format!(
+            "unknown resolution reason: {key}"
+        ))),
+    }
+}
+
+/// Keys are lowercase snake_case, as stored in `label_context`.
+fn valid_key(key: &str) -> bool {
+    !key.is_empty()
+        && key.len() <= MAX_KEY_LEN
+        && key.starts_with(|c: char| c.is_ascii_lowercase())
+        && key
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+}
+
+// --- handlers ---
+
+/// Query parameters for listing reasons.
+#[derive(Debug, Deserialize)]
+pub struct ListReasonsParams {
+    #[serde(default)]
+    pub include_inactive: bool,
+}
+
+/// Response for listing reasons.
+#[derive(Debug, Serialize)]
+pub struct ListReasonsResponse {
+    pub reasons: Vec<ResolutionReasonRow>,
+}
+
+/// List resolution reasons for UIs to offer.
+pub async fn list_reasons(
+    State(state): State<AppState>,
+    Query(params): Query<ListReasonsParams>,
+) -> Result<Json<ListReasonsResponse>, AppError> {
+    let db = state.db.as_ref().ok_or(AppError::LabelerNotConfigured)?;
+    let reasons = db.list_resolution_reasons(params.include_inactive).await?;
+    Ok(Json(ListReasonsResponse { reasons }))
+}
+
+/// Request to add (or reactivate) a reason.
+#[derive(Debug, Deserialize)]
+pub struct AddReasonRequest {
+    pub key: String,
+    pub label: String,
+    #[serde(default)]
+    pub description: String,
+}
+
+/// Add a resolution reason. Re-adding a deactivated key reactivates it.
+pub async fn add_reason(
+    State(state): State<AppState>,
+    _: RequireAdmin,
+    actor: Actor,
+    Json(request): Json<AddReasonRequest>,
+) -> Result<Json<ResolutionReasonRow>, AppError> {
+    let db = state.db.as_ref().ok_or(AppError::LabelerNotConfigured)?;
+    if !valid_key(&request.key) {
+        return Err(AppError::BadRequest(format!(
+            "reason key must be lowercase snake_case, at most {MAX_KEY_LEN} characters"
+        )));
+    }
+    let label = request.label.trim();
+    if label.is_empty() {
+        return Err(AppError::BadRequest("label is required".to_string()));
+    }
+
+    let row = db
+        .upsert_resolution_reason(&request.key, label, request.description.trim())
+        .await?;
+    db.record_audit_event(
+        "add_resolution_reason",
+        &row.key,
+        actor.as_str(),
+        serde_json::json!({ "label": row.label, "description": row.description }),
+    )
+    .await?;
+    Ok(Json(row))
+}
+
+/// Request to deactivate a reason.
+#[derive(Debug, Deserialize)]
+pub struct DeactivateReasonRequest {
+    pub key: String,
+}
+
+/// Response after deactivating a reason.
+#[derive(Debug, Serialize)]
+pub struct DeactivateReasonResponse {
+    pub deactivated: bool,
+}
+
+/// Stop offering a reason. Flags already resolved with it keep it.
+pub async fn deactivate_reason(
+    State(state): State<AppState>,
+    _: RequireAdmin,
+    actor: Actor,
+    Json(request): Json<DeactivateReasonRequest>,
+) -> Result<Json<DeactivateReasonResponse>, AppError> {
+    let db = state.db.as_ref().ok_or(AppError::LabelerNotConfigured)?;
+    if !db.deactivate_resolution_reason(&request.key).await? {
+        return Err(AppError::NotFound(format!(
+            "no resolution reason {}",
+            request.key
+        )));
+    }
+    db.record_audit_event(
+        "deactivate_resolution_reason",
+        &request.key,
+        actor.as_str(),
+        serde_json::json!({}),
+    )
+    .await?;
+    Ok(Json(DeactivateReasonResponse { deactivated: true }))
+}
+
+/// Response for listing report reasons.
+#[derive(Debug, Serialize)]
+pub struct ListReportReasonsResponse {
+    pub reasons: Vec<ReportReasonRow>,
+}
+
+/// List the reasons users can report content for. Public, so the frontend
+/// renders the same list we validate against.
+///
+/// GET /report-reasons
+pub async fn list_public_report_reasons(
+    State(state): State<AppState>,
+) -> Result<Json<ListReportReasonsResponse>, AppError> {
+    let db = state.db.as_ref().ok_or(AppError::LabelerNotConfigured)?;
+    let reasons = db.list_report_reasons(false).await?;
+    Ok(Json(ListReportReasonsResponse { reasons }))
+}
+
+/// List report reasons, including inactive ones if asked, to read old
+/// reports by.
+///
+/// GET /admin/report-reasons
+pub async fn list_report_reasons(
+    State(state): State<AppState>,
+    Query(params): Query<ListReasonsParams>,
+) -> Result<Json<ListReportReasonsResponse>, AppError> {
+    let db = state.db.as_ref().ok_or(AppError::LabelerNotConfigured)?;
+    let reasons = db.list_report_reasons(params.include_inactive).await?;
+    Ok(Json(ListReportReasonsResponse { reasons }))
+}
+
+/// Request to add (or reactivate) a report reason.
+#[derive(Debug, Deserialize)]
+pub struct AddReportReasonRequest {
+    pub key: String,
+    pub label: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub requires_target_uri: bool,
+}
+
+/// Add a report reason. Re-adding a deactivated key reactivates it.
+pub async fn add_report_reason(
+    State(state): State<AppState>,
+    _: RequireAdmin,
+    actor: Actor,
+    Json(request): Json<AddReportReasonRequest>,
+) -> Result<Json<ReportReasonRow>, AppError> {
+    let db = state.db.as_ref().ok_or(AppError::LabelerNotConfigured)?;
+    if !valid_key(&request.key) {
+        return Err(AppError::BadRequest(format!(
+            "reason key must be lowercase snake_case, at most {MAX_KEY_LEN} characters"
+        )));
+    }
+    let label = request.label.trim();
+    if label.is_empty() {
+        return Err(AppError::BadRequest("label is required".to_string()));
+    }
+
+    let row = db
+        .upsert_report_reason(
+            &request.key,
+            label,
+            request.description.trim(),
+            request.requires_target_uri,
+        )
+        .await?;
+    db.record_audit_event(
+        "add_report_reason",
+        &row.key,
+        actor.as_str(),
+        serde_json::json!({
+            "label": row.label,
+            "description": row.description,
+            "requires_target_uri": row.requires_target_uri,
+        }),
+    )
+    .await?;
+    Ok(Json(row))
+}
+
+/// Stop accepting a report reason. Reports already filed with it keep it.
+pub async fn deactivate_report_reason(
+    State(state): State<AppState>,
+    _: RequireAdmin,
+    actor: Actor,
+    Json(request): Json<DeactivateReasonRequest>,
+) -> Result<Json<DeactivateReasonResponse>, AppError> {
+    let db = state.db.as_ref().ok_or(AppError::LabelerNotConfigured)?;
+    if !db.deactivate_report_reason(&request.key).await? {
+        return Err(AppError::NotFound(format!(
+            "no report reason {}",
+            request.key
+        )));
+    }
+    db.record_audit_event(
+        "deactivate_report_reason",
+        &request.key,
+        actor.as_str(),
+        serde_json::json!({}),
+    )
+    .await?;
+    Ok(Json(DeactivateReasonResponse { deactivated: true }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn row(key: &str, active: bool) -> ResolutionReasonRow {
+        ResolutionReasonRow {
+            key: key.to_string(),
+            label: key.replace('_', " "),
+            description: String::new(),
+            active,
+            created_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_check_reason() {
+        assert_eq!(
+            check_reason("licensed", Some(&row("licensed", true))).unwrap(),
+            ResolutionReason::Licensed
+        );
+        assert_eq!(
+            check_reason("fair_use", Some(&row("fair_use", true))).unwrap(),
+            ResolutionReason::Custom("fair_use".to_string())
+        );
+        assert!(matches!(
+            check_reason("fair_use", Some(&row("fair_use", false))),
+            Err(AppError::BadRequest(_))
+        ));
+        assert!(matches!(
+            check_reason("made_up", None),
+            Err(AppError::BadRequest(_))
+        ));
+    }
+
+    #[test]
+    fn test_valid_key() {
+        assert!(valid_key("distributor_error"));
+        assert!(valid_key("fair_use2"));
+        assert!(!valid_key(""));
+        assert!(!valid_key("Fair Use"));
+        assert!(!valid_key("_leading"));
+        assert!(!valid_key(&"a".repeat(MAX_KEY_LEN + 1)));
+    }
+}
@@ -0,0 +1,415 @@
+//! Callbacks telling the backend how a user report was closed.
+//!
+//! When a report is resolved or dismissed, the backend is POSTed the outcome
+//! so it can notify the reporter (and anyone who joined the report) in-app.
+//! The payload carries a fixed summary rather than the admin notes, which
+//! stay internal. Delivery state lives on the report row: a report is only
+//! ever notified once, deliveries retry with backoff, and ones still pending
+//! when the service stops are resumed on the next start. A pending delivery
+//! is leased to the replica running it, renewed on every attempt, so a
+//! replica only resumes deliveries whose owner has stopped renewing.
+
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use tracing::{info, warn};
+
+use crate::db::{LabelDb, UserReport};
+use crate::state::AppState;
+use crate::webhook::retry_delay;
+
+/// Header carrying the shared key so the backend can verify the caller.
+pub const REPORT_CALLBACK_KEY_HEADER: &str = "X-Moderation-Report-Key";
+
+/// Delivery attempts before marking a callback as failed.
+const MAX_ATTEMPTS: u32 = 5;
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How long a pending delivery stays with the replica running it without a
+/// new attempt. Well over the longest backoff plus a request timeout.
+const DELIVERY_LEASE: Duration = Duration::from_secs(5 * 60);
+
+/// Report callback settings from config.
+#[derive(Debug, Clone)]
+pub struct ReportCallbackConfig {
+    pub url: String,
+    pub key: Option<String>,
+}
+
+/// Where a resolution callback stands.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CallbackStatus {
+    /// Not yet accepted by the backend; still retrying.
+    Pending,
+    /// The backend accepted the callback.
+    Sent,
+    /// Every attempt failed.
+    Failed,
+}
+
+impl CallbackStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Pending => "pending",
+            Self::Sent => "sent",
+            Self::Failed => "failed",
+        }
+    }
+}
+
+/// Body POSTed to the report callback URL.
+#[derive(Debug, Clone, Serialize)]
+pub struct ReportResolvedEvent {
+    pub report_id: i32,
+    /// The reporter as stored: their DID, or their `hash:` identity when
+    /// reporter hashing is on, which the backend maps back to the DID.
+    pub reporter_did: String,
+    /// Reporters who filed the same report and were folded into it, stored
+    /// the same way.
+    pub supporter_dids: Vec<String>,
+    pub target_type: String,
+    pub target_id: String,
+    pub target_uri: Option<String>,
+    /// `resolved` or `dismissed`.
+    pub status: String,
+    /// False for dismissals, so the backend can word them differently.
+    pub action_taken: bool,
+    /// Reporter-facing summary of the outcome. Never includes admin notes.
+    pub summary: String,
+    pub resolved_at: Option<DateTime<Utc>>,
+}
+
+impl ReportResolvedEvent {
+    /// The event for a closed report, or `None` if it's still open.
+    pub fn new(report: &UserReport, supporter_dids: Vec<String>) -> Option<Self> {
+        let action_taken = match report.status.as_str() {
+            "resolved" => true,
+            "dismissed" => false,
+            _ => return None,
+        };
+        Some(Self {
+            report_id: report.id,
+            reporter_did: report.reporter_did.clone(),
+            supporter_dids,
+            target_type: report.target_type.clone(),
+            target_id: report.target_id.clone(),
+            target_uri: report.target_uri.clone(),
+            status: report.status.clone(),
+            action_taken,
+            summary: resolution_summary(report, action_taken),
+            resolved_at: report.resolved_at,
+        })
+    }
+}
+
+/// What the reporter is told. Built only from the outcome and the target
+/// type, which is validated when the report is filed.
+fn resolution_summary(report: &UserReport, action_taken: bool) -> String {
+    let target = &report.target_type;
+    if !action_taken {
+        format!(
+            "we reviewed the {target} you reported and didn't find a violation of our policies."
+        )
+    } else if report.label_seq.is_some() {
+        format!("we reviewed the {target} you reported and labeled it.")
+    } else {
+        format!("we reviewed the {target} you reported and took action.")
+    }
+}
+
+/// Sends report resolution callbacks to the backend.
+pub struct ReportCallbackNotifier {
+    config: ReportCallbackConfig,
+    http: reqwest::Client,
+}
+
+impl ReportCallbackNotifier {
+    pub fn new(config: ReportCallbackConfig) -> Self {
+        let http = reqwest::Client::builder()
+            .timeout(REQUEST_TIMEOUT)
+            .build()
+            .unwrap_or_default();
+        Self { config, http }
+    }
+
+    /// Mark a closed report's callback as pending and start delivering it.
+    ///
+    /// Does nothing for open reports, or for ones already notified.
+    pub async fn notify(&self, db: Arc<LabelDb>, report: &UserReport) -> Result<(), sqlx::Error> {
+        if ReportResolvedEvent::new(report, Vec::new()).is_none() {
+            return Ok(());
+        }
+        if !db.start_report_callback(report.id, DELIVERY_LEASE).await? {
+            info!(report_id = report.id, "report callback already started");
+            return Ok(());
+        }
+        let supporters = db.get_report_supporters(report.id).await?;
+        if let Some(event) = ReportResolvedEvent::new(report, supporters) {
+            self.spawn_delivery(db, event);
+        }
+        Ok(())
+    }
+
+    /// Restart deliveries left pending by a previous run, skipping any that
+    /// another replica is still delivering.
+    pub async fn resume_pending(&self, db: Arc<LabelDb>) -> Result<usize, sqlx::Error> {
+        let pending = db.claim_pending_report_callbacks(DELIVERY_LEASE).await?;
+        let mut count = 0;
+        for report in pending {
+            let supporters = db.get_report_supporters(report.id).await?;
+            if let Some(event) = ReportResolvedEvent::new(&report, supporters) {
+                self.spawn_delivery(db.clone(), event);
+                count += 1;
+            }
+        }
+        Ok(count)
+    }
+
+    fn spawn_delivery(&self, db: Arc<LabelDb>, event: ReportResolvedEvent) {
+        let http = self.http.clone();
+        let config = self.config.clone();
+        tokio::spawn(async move {
+            let id = event.report_id;
+            deliver(&http, &config, &event, |status, error| {
+                let db = db.clone();
+                async move {
+                    if let Err(e) = db
+                        .record_report_callback_attempt(
+                            id,
+                            status.as_str(),
+                            error.as_deref(),
+                            DELIVERY_LEASE,
+                        )
+                        .await
+                    {
+                        warn!(report_id = id, error = %e, "failed to record report callback");
+                    }
+                }
+            })
+            .await;
+        });
+    }
+}
+
+/// Tell the backend a report was closed, if report callbacks are configured.
+///
+/// Failures are logged; the resolution itself has already been saved.
+pub async fn notify_report_resolved(state: &AppState, report: &UserReport) {
+    let (Some(notifier), Some(db)) = (&state.report_callback, &state.db) else {
+        return;
+    };
+    if let Err(e) = notifier.notify(db.clone(), report).await {
+        warn!(report_id = report.id, error = %e, "failed to queue report callback");
+    }
+}
+
+/// POST the event until the backend accepts it or attempts run out, passing
+/// each attempt's status and error to `record`. Returns the final status.
+async fn deliver<F, Fut>(
+    http: &reqwest::Client,
+    config: &ReportCallbackConfig,
+    event: &ReportResolvedEvent,
+    mut record: F,
+) -> CallbackStatus
+where
+    F: FnMut(CallbackStatus, Option<String>) -> Fut,
+    Fut: Future<Output = ()>,
+{
+    let report_id = event.report_id;
+    for attempt in 1..=MAX_ATTEMPTS {
+        let mut post = http.post(&config.url).json(event);
+        if let Some(key) = &config.key {
+            post = post.header(REPORT_CALLBACK_KEY_HEADER, key);
+        }
+        let error = match post.send().await {
+            Ok(response) if response.status().is_success() => None,
+            Ok(response) => Some(format!("backend returned {}", response.status())),
+            Err(e) => Some(e.to_string()),
+        };
+        let status = attempt_status(error.is_none(), attempt);
+        record(status, error.clone()).await;
+
+        match status {
+            CallbackStatus::Sent => {
+                info!(report_id, attempt, "report callback sent");
+                return status;
+            }
+            CallbackStatus::Failed => {
+                warn!(report_id, attempt, error = ?error, "giving up on report callback");
+                return status;
+            }
+            CallbackStatus::Pending => {
+                warn!(report_id, attempt, error = ?error, "report callback attempt failed");
+                tokio::time::sleep(retry_delay(attempt)).await;
+            }
+        }
+    }
+    CallbackStatus::Failed
+}
+
+/// Status after an attempt: sent on success, failed once attempts run out.
+fn attempt_status(delivered: bool, attempt: u32) -> CallbackStatus {
+    if delivered {
+        CallbackStatus::Sent
+    } else if attempt >= MAX_ATTEMPTS {
+        CallbackStatus::Failed
+    } else {
+        CallbackStatus::Pending
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use axum::{
+        http::{HeaderMap, StatusCode},
+        routing::post,
+        Json, Router,
+    };
+
+    use super::*;
+
+    fn report(status: &str, label_seq: Option<i64>) -> UserReport {
+        UserReport {
+            id: 7,
+            reporter_did: "did:plc:reporter".to_string(),
+            reporter_handle: Some("reporter.bsky.social".to_string()),
+            target_type: "track".to_string(),
+            target_id: "42".to_string(),
+            target_name: Some("some track".to_string()),
+            target_url: None,
+            target_uri: Some("at://did:plc:artist/fm.plyr.track/abc".to_string()),
+            reason: "copyright".to_string(),
+            description: Some("this is my song".to_string()),
+            screenshot_url: None,
+            screenshot_key: None,
+            status: status.to_string(),
+            admin_notes: Some("internal: artist has a sync license on file".to_string()),
+            resolved_by: Some("alice".to_string()),
+            created_at: Utc::now(),
+            updated_at: None,
+            resolved_at: Some(Utc::now()),
+            label_seq,
+            supporter_count: 1,
+        }
+    }
+
+    /// A stand-in backend that answers each callback with the next status in
+    /// `statuses` (then 204s) and keeps every body and key header it got.
+    async fn mock_backend(
+        statuses: Vec<StatusCode>,
+    ) -> (String, Arc<Mutex<Vec<(serde_json::Value, Option<String>)>>>) {
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let app = Router::new().route(
+            "/reports/resolved",
+            post({
+                let received = received.clone();
+                move |headers: HeaderMap, Json(body): Json<serde_json::Value>| {
+                    let received = received.clone();
+                    let statuses = statuses.clone();
+                    async move {
+                        let key = headers
+                            .get(REPORT_CALLBACK_KEY_HEADER)
+                            .and_then(|v| v.to_str().ok())
+                            .map(String::from);
+                        let mut received = received.lock().unwrap();
+                        received.push((body, key));
+                        statuses
+                            .get(received.len() - 1)
+                            .copied()
+                            .unwrap_or(StatusCode::NO_CONTENT)
+                    }
+                }
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/reports/resolved", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await });
+        (url, received)
+    }
+
+    #[test]
+    fn test_attempt_status() {
+        assert_eq!(attempt_status(true, 1), CallbackStatus::Sent);
+        assert_eq!(attempt_status(false, 1), CallbackStatus::Pending);
+        assert_eq!(attempt_status(false, MAX_ATTEMPTS), CallbackStatus::Failed);
+    }
+
+    #[test]
+    fn test_event_distinguishes_dismissals() {
+        let resolved = ReportResolvedEvent::new(&report("resolved", Some(12)), Vec::new()).unwrap();
+        assert!(resolved.action_taken);
+        assert_eq!(
+            resolved.summary,
+            "we reviewed the track you reported and labeled it."
+        );
+
+        let resolved = ReportResolvedEvent::new(&report("resolved", None), Vec::new()).unwrap();
+        assert_eq!(
+            resolved.summary,
+            "we reviewed the track you reported and took action."
+        );
+
+        let dismissed = ReportResolvedEvent::new(&report("dismissed", None), Vec::new()).unwrap();
+        assert!(!dismissed.action_taken);
+        assert!(dismissed.summary.contains("didn't find a violation"));
+
+        for status in ["open", "investigating", "escalated"] {
+            assert!(ReportResolvedEvent::new(&report(status, None), Vec::new()).is_none());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_delivery_retries_after_server_error() {
+        let (url, received) = mock_backend(vec![StatusCode::INTERNAL_SERVER_ERROR]).await;
+        let config = ReportCallbackConfig {
+            url,
+            key: Some("callback-key".to_string()),
+        };
+        let event = ReportResolvedEvent::new(
+            &report("dismissed", None),
+            vec!["did:plc:supporter".to_string()],
+        )
+        .unwrap();
+
+        let attempts = Mutex::new(Vec::new());
+        let status = deliver(&reqwest::Client::new(), &config, &event, |status, error| {
+            attempts.lock().unwrap().push((status, error));
+            async {}
+        })
+        .await;
+
+        assert_eq!(status, CallbackStatus::Sent);
+        let attempts = attempts.into_inner().unwrap();
+        assert_eq!(attempts.len(), 2);
+        assert_eq!(attempts[0].0, CallbackStatus::Pending);
+        assert!(attempts[0].1.as_deref().unwrap().contains("500"));
+        assert_eq!(attempts[1], (CallbackStatus::Sent, None));
+
+        let received = received.lock().unwrap();
+        assert_eq!(received.len(), 2);
+        let (body, key) = &received[1];
+        assert_eq!(key.as_deref(), Some("callback-key"));
+        assert_eq!(body["report_id"], 7);
+        assert_eq!(body["reporter_did"], "did:plc:reporter");
+        assert_eq!(
+            body["supporter_dids"],
+            serde_json::json!(["did:plc:supporter"])
+        );
+        assert_eq!(body["target_type"], "track");
+        assert_eq!(body["target_id"], "42");
+        assert_eq!(body["target_uri"], "at://did:plc:artist/fm.plyr.track/abc");
+        assert_eq!(body["status"], "dismissed");
+        assert_eq!(body["action_taken"], false);
+        // admin notes and the resolving admin stay internal
+        let raw = body.to_string();
+        assert!(!raw.contains("sync license"));
+        assert!(!raw.contains("alice"));
+        assert!(body.get("admin_notes").is_none());
+    }
+}
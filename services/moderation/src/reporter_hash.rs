@@ -0,0 +1,189 @@
+//! Storing reporters as keyed hashes instead of DIDs.
+//!
+//! With `MODERATION_HASH_REPORTERS` on, `POST /reports` stores
+//! `hash:{hex hmac-sha256(did)}` wherever it would have stored the reporter's
+//! DID, and drops their handle. A DID always hashes the same way, so report
+//! limits, duplicate detection and reporter history keep working, but the
+//! database alone can't say who filed a report. The backend learns each
+//! reporter's hash from the create-report response and keeps the mapping;
+//! resolution callbacks carry the hash back to it.
+//!
+//! `POST /admin/reports/hash-reporters` rewrites rows stored before the flag
+//! was turned on.
+
+use axum::{extract::State, Json};
+use hmac::{Hmac, Mac};
+use serde::Serialize;
+use sha2::Sha256;
+use tracing::info;
+
+use crate::auth::{Actor, RequireAdmin};
+use crate::state::{AppError, AppState};
+
+/// Prefix marking a stored reporter as a hash rather than a DID.
+pub const REPORTER_HASH_PREFIX: &str = "hash:";
+
+/// Hex digits of a hash shown in the admin UI.
+const DISPLAY_HASH_CHARS: usize = 12;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Hashes reporter DIDs with a secret key.
+#[derive(Clone)]
+pub struct ReporterHasher {
+    secret: Vec<u8>,
+}
+
+impl std::fmt::Debug for ReporterHasher {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ReporterHasher").finish_non_exhaustive()
+    }
+}
+
+impl ReporterHasher {
+    pub fn new(secret: impl Into<Vec<u8>>) -> Self {
+        Self {
+            secret: secret.into(),
+        }
+    }
+
+    /// The stored identity for `did`.
+    pub fn hash(&self, did: &str) -> String {
+        let mut mac =
+            HmacSha256::new_from_slice(&self.secret).expect("hmac accepts keys of any length");
+        mac.update(did.as_bytes());
+        format!(
+            "{REPORTER_HASH_PREFIX}{}",
+            hex::encode(mac.finalize().into_bytes())
+        )
+    }
+}
+
+/// The identity stored for `reporter`: its hash when hashing is on, unless
+/// it already is one, otherwise the DID itself.
+pub fn stored_reporter(hasher: Option<&ReporterHasher>, reporter: &str) -> String {
+    match hasher {
+        Some(hasher) if !is_hashed(reporter) => hasher.hash(reporter),
+        _ => reporter.to_string(),
+    }
+}
+
+/// Whether a stored reporter is a hash rather than a DID.
+pub fn is_hashed(reporter: &str) -> bool {
+    reporter.starts_with(REPORTER_HASH_PREFIX)
+}
+
+/// A stored reporter shortened for display: a hash prefix, or the DID as is.
+pub fn display_reporter(reporter: &str) -> &str {
+    if is_hashed(reporter) {
+        let end = (REPORTER_HASH_PREFIX.len() + DISPLAY_HASH_CHARS).min(reporter.len());
+        &reporter[..end]
+    } else {
+        reporter
+    }
+}
+
+/// A DID and the hash it was replaced with.
+#[derive(Debug, Serialize)]
+pub struct ReporterMapping {
+    pub reporter_did: String,
+    pub reporter_hash: String,
+}
+
+/// Response after hashing stored reporters.
+#[derive(Debug, Serialize)]
+pub struct HashReportersResponse {
+    /// Reports whose filer was replaced.
+    pub reports: u64,
+    /// Supporter entries replaced.
+    pub supporters: u64,
+    /// Every DID replaced and its hash, for the backend's mapping. This is
+    /// the only place the pairs are returned; nothing here keeps them.
+    pub mapping: Vec<ReporterMapping>,
+}
+
+/// Replace reporter DIDs stored before hashing was turned on with their
+/// hashes, and drop the handles stored with them. Safe to run again: rows
+/// already hashed are left alone.
+///
+/// POST /admin/reports/hash-reporters
+pub async fn hash_existing_reporters(
+    State(state): State<AppState>,
+    _: RequireAdmin,
+    actor: Actor,
+) -> Result<Json<HashReportersResponse>, AppError> {
+    let hasher = state.reporter_hasher.as_ref().ok_or_else(|| {
+        AppError::Conflict("MODERATION_HASH_REPORTERS is off; turn it on first".to_string())
+    })?;
+    let db = state.db.as_ref().ok_or(AppError::LabelerNotConfigured)?;
+
+    let mapping: Vec<(String, String)> = db
+        .list_unhashed_reporters()
+        .await?
+        .into_iter()
+        .map(|did| {
+            let hash = hasher.hash(&did);
+            (did, hash)
+        })
+        .collect();
+    let (reports, supporters) = db.replace_reporters(&mapping).await?;
+
+    info!(
+        reporters = mapping.len(),
+        reports, supporters, "stored reporters hashed"
+    );
+    db.record_audit_event(
+        "hash_reporters",
+        "user-reports",
+        actor.as_str(),
+        serde_json::json!({
+            "reporters": mapping.len(),
+            "reports": reports,
+            "supporters": supporters,
+        }),
+    )
+    .await?;
+
+    Ok(Json(HashReportersResponse {
+        reports,
+        supporters,
+        mapping: mapping
+            .into_iter()
+            .map(|(reporter_did, reporter_hash)| ReporterMapping {
+                reporter_did,
+                reporter_hash,
+            })
+            .collect(),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hashes_are_stable_and_keyed() {
+        let hasher = ReporterHasher::new("secret");
+        let hash = hasher.hash("did:plc:reporter");
+        assert!(is_hashed(&hash));
+        assert_eq!(hash.len(), REPORTER_HASH_PREFIX.len() + 64);
+        assert_eq!(hash, hasher.hash("did:plc:reporter"));
+        assert_ne!(hash, hasher.hash("did:plc:other"));
+        assert_ne!(hash, ReporterHasher::new("other").hash("did:plc:reporter"));
+        assert!(!format!("{hasher:?}").contains("secret"));
+    }
+
+    #[test]
+    fn test_stored_reporter() {
+        let hasher = ReporterHasher::new("secret");
+        let did = "did:plc:reporter";
+        let hash = hasher.hash(did);
+        assert_eq!(stored_reporter(None, did), did);
+        assert_eq!(stored_reporter(Some(&hasher), did), hash);
+        // reporter history links on report cards carry the stored hash
+        assert_eq!(stored_reporter(Some(&hasher), &hash), hash);
+
+        assert_eq!(display_reporter(&hash), &hash[..17]);
+        assert_eq!(display_reporter("did:plc:reporter"), "did:plc:reporter");
+    }
+}
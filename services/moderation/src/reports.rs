@@ -1,16 +1,36 @@
 //! User report handlers for content moderation.
 
 use axum::{
-    extract::{Path, Query, State},
-    http::{header::CONTENT_TYPE, StatusCode},
-    response::{IntoResponse, Response},
-    Json,
+    async_trait,
+    extract::{multipart::Field, FromRequest, Multipart, Path, Query, Request, State},
+    http::{
+        header::{CONTENT_TYPE, WARNING},
+        HeaderName, StatusCode,
+    },
+    response::{IntoResponse, Redirect, Response},
+    Form, Json,
 };
+use chrono::{DateTime, Days, NaiveDate, Utc};
 use serde::{Deserialize, Serialize};
-use tracing::info;
+use std::collections::{BTreeMap, HashSet};
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
 
-use crate::db::UserReport;
-use crate::AppState;
+use crate::admin::{generate_batch_id, html_escape, url_encode};
+use crate::auth::{Actor, RequireAdmin};
+use crate::db::{
+    BulkReportResolution, FiledReport, LabelDb, ReportCount, ReportFilter, ReportReasonRow,
+    ReportWindowStats, ReporterActivity, ReporterReports, ReportsCursor, ReportsPage, StoredLabel,
+    UserReport,
+};
+use crate::image_fetch::{sniff_image_type, MAX_IMAGE_BYTES};
+use crate::labels::{Label, LABEL_VALUES};
+use crate::ratelimit::{RateLimitExceeded, RateLimited};
+use crate::report_callback::notify_report_resolved;
+use crate::reporter_hash::{display_reporter, is_hashed, stored_reporter};
+use crate::stats::StatsCache;
+use crate::webhook::ReportEscalatedEvent;
+use crate::{AppError, AppState};
 
 /// Request to create a new user report.
 #[derive(Debug, Deserialize)]
@@ -33,10 +53,128 @@ pub struct CreateReportRequest {
     pub screenshot_url: Option<String>,
 }
 
+/// A report as sent to `POST /reports`: a JSON [`CreateReportRequest`], or a
+/// multipart form with the same fields as text parts plus an optional
+/// `screenshot` image.
+#[derive(Debug)]
+pub struct ReportSubmission {
+    pub report: CreateReportRequest,
+    pub screenshot: Option<Screenshot>,
+}
+
+/// An uploaded screenshot, already checked to be an image we accept.
+#[derive(Debug)]
+pub struct Screenshot {
+    pub bytes: Vec<u8>,
+    pub media_type: &'static str,
+}
+
+#[async_trait]
+impl<S: Send + Sync> FromRequest<S> for ReportSubmission {
+    type Rejection = Response;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let is_multipart = req
+            .headers()
+            .get(CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .is_some_and(|v| v.starts_with("multipart/form-data"));
+        if !is_multipart {
+            let Json(report) = Json::<CreateReportRequest>::from_request(req, state)
+                .await
+                .map_err(IntoResponse::into_response)?;
+            return Ok(Self {
+                report,
+                screenshot: None,
+            });
+        }
+        let multipart = Multipart::from_request(req, state)
+            .await
+            .map_err(IntoResponse::into_response)?;
+        read_multipart_report(multipart)
+            .await
+            .map_err(|e| (StatusCode::BAD_REQUEST, e).into_response())
+    }
+}
+
+/// Read a multipart report, returning the message for a 400.
+async fn read_multipart_report(mut multipart: Multipart) -> Result<ReportSubmission, String> {
+    let mut fields = serde_json::Map::new();
+    let mut screenshot = None;
+    while let Some(field) = multipart
+        .next_field()
+        .await
+        .map_err(|e| format!("multipart error: {e}"))?
+    {
+        let Some(name) = field.name().map(str::to_string) else {
+            continue;
+        };
+        if name == "screenshot" {
+            screenshot = read_screenshot(field).await?;
+        } else {
+            let value = field
+                .text()
+                .await
+                .map_err(|e| format!("multipart error: {e}"))?;
+            fields.insert(name, serde_json::Value::String(value));
+        }
+    }
+    let report: CreateReportRequest =
+        serde_json::from_value(fields.into()).map_err(|e| format!("invalid report: {e}"))?;
+    if screenshot.is_some() && report.screenshot_url.is_some() {
+        return Err("send either a screenshot or a screenshot_url, not both".to_string());
+    }
+    Ok(ReportSubmission { report, screenshot })
+}
+
+/// Read a `screenshot` part, held to the same size and types as scanned
+/// images. An empty part (a form sent without choosing a file) is no
+/// screenshot.
+async fn read_screenshot(mut field: Field<'_>) -> Result<Option<Screenshot>, String> {
+    let mut bytes = Vec::new();
+    while let Some(chunk) = field
+        .chunk()
+        .await
+        .map_err(|e| format!("multipart error: {e}"))?
+    {
+        if bytes.len() + chunk.len() > MAX_IMAGE_BYTES {
+            return Err(format!("screenshot exceeds {MAX_IMAGE_BYTES} bytes"));
+        }
+        bytes.extend_from_slice(&chunk);
+    }
+    if bytes.is_empty() {
+        return Ok(None);
+    }
+    let media_type = sniff_image_type(&bytes)
+        .ok_or_else(|| "screenshot must be a PNG, JPEG, GIF or WebP image".to_string())?;
+    Ok(Some(Screenshot { bytes, media_type }))
+}
+
 /// Response after creating a report.
 #[derive(Debug, Serialize)]
 pub struct CreateReportResponse {
     pub report_id: i32,
+    /// The report matched an unresolved one and was added to it rather than
+    /// filed separately; `report_id` is the existing report.
+    pub duplicate: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    /// What the reporter is stored as when reporter hashing is on. The
+    /// backend keeps the mapping back to the DID; resolution callbacks carry
+    /// this instead of the DID.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reporter_hash: Option<String>,
+}
+
+impl CreateReportResponse {
+    fn already_reported(report: &UserReport) -> Self {
+        Self {
+            report_id: report.id,
+            duplicate: true,
+            message: Some("you've already reported this; it's being looked at".to_string()),
+            reporter_hash: None,
+        }
+    }
 }
 
 /// Query parameters for listing reports.
@@ -46,8 +184,19 @@ pub struct ListReportsParams {
     pub status: Option<String>,
     #[serde(default)]
     pub target_type: Option<String>,
+    #[serde(default)]
+    pub target_id: Option<String>,
+    #[serde(default)]
+    pub target_uri: Option<String>,
+    /// Text to find in the description or admin notes.
+    #[serde(default)]
+    pub q: Option<String>,
     #[serde(default = "default_limit")]
     pub limit: i64,
+    /// `next_cursor` from the previous page.
+    #[serde(default)]
+    pub cursor: Option<String>,
+    /// Deprecated: use `cursor`. Still honored, with a `Deprecation` header.
     #[serde(default)]
     pub offset: i64,
 }
@@ -61,8 +210,17 @@ fn default_limit() -> i64 {
 pub struct ListReportsResponse {
     pub reports: Vec<UserReport>,
     pub count: usize,
+    /// Pass as `cursor` to fetch the next page (null on the last page).
+    pub next_cursor: Option<String>,
 }
 
+/// `Warning` sent with listings paged by `offset`.
+const OFFSET_DEPRECATION_WARNING: &str =
+    r#"299 - "offset is deprecated and will be removed; page with cursor/next_cursor""#;
+
+/// Reports per page in the admin UI.
+const REPORTS_HTML_PAGE_SIZE: i64 = 50;
+
 /// Request to resolve a report.
 #[derive(Debug, Deserialize)]
 pub struct ResolveReportRequest {
@@ -72,45 +230,391 @@ pub struct ResolveReportRequest {
     pub resolved_by: String,
 }
 
+/// Most reports a single bulk resolve may close.
+pub const MAX_BULK_RESOLVE_REPORTS: usize = 200;
+
+/// Statuses a bulk resolve can close reports with.
+const BULK_RESOLVE_STATUSES: [&str; 2] = ["resolved", "dismissed"];
+
+/// Request to close many reports with the same status and notes.
+#[derive(Debug, Deserialize)]
+pub struct BulkResolveReportsRequest {
+    pub ids: Vec<i32>,
+    /// `resolved` or `dismissed`.
+    pub status: String,
+    #[serde(default)]
+    pub admin_notes: Option<String>,
+    /// Defaults to the acting admin (`X-Moderation-Actor`).
+    #[serde(default)]
+    pub resolved_by: Option<String>,
+}
+
+/// What happened to one report in a bulk resolve.
+#[derive(Debug, Serialize, PartialEq, Eq)]
+pub struct BulkReportOutcome {
+    pub id: i32,
+    pub resolved: bool,
+    /// Why the report was left as it was.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub skipped: Option<String>,
+}
+
+/// Response after a bulk resolve.
+#[derive(Debug, Serialize)]
+pub struct BulkResolveReportsResponse {
+    pub resolved_count: usize,
+    /// One entry per distinct requested id, in request order.
+    pub outcomes: Vec<BulkReportOutcome>,
+}
+
+/// Form posted by the admin UI's report action flow.
+#[derive(Debug, Deserialize)]
+pub struct ResolveReportForm {
+    pub status: String,
+    /// Admin notes; blank means none.
+    #[serde(default)]
+    pub notes: Option<String>,
+}
+
+impl ResolveReportForm {
+    /// Trimmed notes, or `None` if left blank.
+    fn notes(&self) -> Option<&str> {
+        self.notes
+            .as_deref()
+            .map(str::trim)
+            .filter(|n| !n.is_empty())
+    }
+}
+
+/// Request to resolve a report by labeling its target.
+#[derive(Debug, Default, Deserialize)]
+pub struct LabelReportRequest {
+    /// Label value; defaults by the report's reason.
+    #[serde(default)]
+    pub val: Option<String>,
+    #[serde(default)]
+    pub admin_notes: Option<String>,
+}
+
+/// Response after labeling a report's target.
+#[derive(Debug, Serialize)]
+pub struct LabelReportResponse {
+    pub seq: i64,
+    pub val: String,
+    /// False if an active label with this value already existed and was
+    /// linked instead.
+    pub created: bool,
+    pub report: UserReport,
+}
+
+/// Report counts for the admin dashboard.
+#[derive(Debug, Default, Serialize)]
+pub struct ReportStats {
+    /// All reports, by reason.
+    pub by_reason: BTreeMap<String, i64>,
+    /// All reports, by status.
+    pub by_status: BTreeMap<String, i64>,
+    /// Open and escalated reports, by reason.
+    pub open_by_reason: BTreeMap<String, i64>,
+    /// Numbers for a date window: the last week unless one is asked for.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub window: Option<ReportWindowStats>,
+}
+
+/// Days covered by the report statistics when no window is given.
+const REPORT_STATS_DEFAULT_DAYS: u64 = 7;
+
+/// Longest window the report statistics cover.
+const REPORT_STATS_MAX_DAYS: i64 = 366;
+
+/// Report statistics for the most recently requested date window.
+pub type ReportStatsCache = StatsCache<ReportWindowStats, (NaiveDate, NaiveDate)>;
+
+/// Query parameters for report statistics. Both days are included.
+#[derive(Debug, Default, Deserialize)]
+pub struct ReportStatsParams {
+    #[serde(default)]
+    pub from: Option<NaiveDate>,
+    #[serde(default)]
+    pub to: Option<NaiveDate>,
+}
+
+/// The `(from, to)` days the statistics cover. `to` defaults to `today` and
+/// `from` to a week before it.
+fn report_stats_window(
+    params: &ReportStatsParams,
+    today: NaiveDate,
+) -> Result<(NaiveDate, NaiveDate), String> {
+    let to = params.to.unwrap_or(today);
+    let from = params
+        .from
+        .unwrap_or(to - Days::new(REPORT_STATS_DEFAULT_DAYS - 1));
+    if from > to {
+        return Err(format!("from ({from}) is after to ({to})"));
+    }
+    if (to - from).num_days() >= REPORT_STATS_MAX_DAYS {
+        return Err(format!(
+            "window is longer than {REPORT_STATS_MAX_DAYS} days"
+        ));
+    }
+    Ok((from, to))
+}
+
+impl ReportStats {
+    /// Roll up per-(reason, status) counts.
+    fn from_counts(counts: &[ReportCount]) -> Self {
+        let mut stats = Self::default();
+        for c in counts {
+            *stats.by_reason.entry(c.reason.clone()).or_default() += c.count;
+            *stats.by_status.entry(c.status.clone()).or_default() += c.count;
+            if c.status == "open" || c.status == "escalated" {
+                *stats.open_by_reason.entry(c.reason.clone()).or_default() += c.count;
+            }
+        }
+        stats
+    }
+}
+
+/// Newest reports included in a reporter's history.
+const REPORTER_RECENT_REPORTS: i64 = 10;
+
+/// One reporter's track record, for judging how credible their reports are.
+#[derive(Debug, Serialize)]
+pub struct ReporterHistory {
+    pub reporter_did: String,
+    /// Reports they filed.
+    pub total: i64,
+    pub by_status: BTreeMap<String, i64>,
+    pub by_reason: BTreeMap<String, i64>,
+    /// Reports they joined as a supporter rather than filing.
+    pub supported: i64,
+    /// Dismissed reports as a share of closed ones (null until one is closed).
+    pub dismissal_rate: Option<f64>,
+    /// Their newest reports first.
+    pub recent: Vec<UserReport>,
+}
+
+impl ReporterHistory {
+    fn new(reporter_did: String, reports: ReporterReports) -> Self {
+        let stats = ReportStats::from_counts(&reports.counts);
+        let count = |status: &str| stats.by_status.get(status).copied().unwrap_or(0);
+        let dismissed = count("dismissed");
+        let closed = count("resolved") + dismissed;
+        Self {
+            reporter_did,
+            total: stats.by_status.values().sum(),
+            dismissal_rate: (closed > 0).then(|| dismissed as f64 / closed as f64),
+            by_status: stats.by_status,
+            by_reason: stats.by_reason,
+            supported: reports.supported,
+            recent: reports.recent,
+        }
+    }
+
+    fn dismissed(&self) -> i64 {
+        self.by_status.get("dismissed").copied().unwrap_or(0)
+    }
+}
+
+/// Reports one reporter may file per hour when unset.
+pub const DEFAULT_REPORTS_PER_HOUR: i64 = 10;
+
+/// Reports one reporter may file per day when unset.
+pub const DEFAULT_REPORTS_PER_DAY: i64 = 50;
+
+/// Longest report description, in characters.
+const MAX_DESCRIPTION_CHARS: usize = 2000;
+
+/// Longest screenshot URL, in characters.
+const MAX_SCREENSHOT_URL_CHARS: usize = 512;
+
+/// Request body limit for `/reports`: an uploaded screenshot plus the rest of
+/// the form.
+pub const MAX_REPORT_BODY_BYTES: usize = MAX_IMAGE_BYTES + 64 * 1024;
+
+/// How long a link from `/admin/reports/:id/screenshot` works.
+const SCREENSHOT_LINK_TTL: Duration = Duration::from_secs(5 * 60);
+
+/// Bounds on `reporter_did` length. The shortest valid DID is `did:x:y`; the
+/// DID spec caps them at 2KB.
+const MIN_DID_LEN: usize = 7;
+const MAX_DID_LEN: usize = 2048;
+
+/// How many reports one reporter DID may file.
+#[derive(Debug, Clone, Copy)]
+pub struct ReportLimits {
+    pub per_hour: i64,
+    pub per_day: i64,
+}
+
+impl ReportLimits {
+    /// How long the reporter must wait before filing another report, or
+    /// `None` if they're under both limits. The wait runs until their oldest
+    /// report in the exceeded window ages out.
+    fn retry_after(&self, activity: &ReporterActivity, now: DateTime<Utc>) -> Option<Duration> {
+        let wait = |oldest: Option<DateTime<Utc>>, window: chrono::Duration| {
+            (oldest.unwrap_or(now) + window - now)
+                .to_std()
+                .unwrap_or_default()
+        };
+        let mut retry = None;
+        if activity.last_hour >= self.per_hour {
+            retry = Some(wait(activity.oldest_last_hour, chrono::Duration::hours(1)));
+        }
+        if activity.last_day >= self.per_day {
+            retry = retry.max(Some(wait(
+                activity.oldest_last_day,
+                chrono::Duration::days(1),
+            )));
+        }
+        retry
+    }
+}
+
+/// Distinct reporters that escalate an open report when unset.
+pub const DEFAULT_ESCALATION_THRESHOLD: i64 = 5;
+
+/// When an open report has enough reporters to be escalated.
+#[derive(Debug, Clone, Copy)]
+pub struct ReportEscalation {
+    /// Distinct reporters (the filer plus supporters) that escalate a report.
+    pub threshold: i64,
+    /// Also open a review batch for escalated copyright reports about an
+    /// AT-URI.
+    pub create_batch: bool,
+}
+
+impl ReportEscalation {
+    /// Whether `report` has crossed the threshold and isn't escalated yet.
+    /// Only open reports are escalated; one that's being investigated already
+    /// has someone on it.
+    fn should_escalate(&self, report: &UserReport) -> bool {
+        report.status == "open" && report.supporter_count + 1 >= self.threshold
+    }
+
+    /// The AT-URI to open a review batch for, if `report` gets one.
+    fn batch_uri<'a>(&self, report: &'a UserReport) -> Option<&'a str> {
+        if self.create_batch && report.reason == "copyright" {
+            report
+                .target_uri
+                .as_deref()
+                .filter(|uri| uri.starts_with("at://"))
+        } else {
+            None
+        }
+    }
+}
+
+/// Statuses a report can be moved to.
+const REPORT_STATUSES: [&str; 5] = [
+    "open",
+    "escalated",
+    "investigating",
+    "resolved",
+    "dismissed",
+];
+
 /// Create a new user report.
 ///
 /// POST /reports
+///
+/// Called by the backend on behalf of users, with the admin (service) token;
+/// read-only dashboard tokens can't file reports. Each reporter DID is held
+/// to `state.report_limits`; past it the response is 429 with `Retry-After`.
+///
+/// A report matching an unresolved one's target and reason is attached to it
+/// as a supporter and answered with `"duplicate": true` and the existing
+/// `report_id`. Re-reporting something the reporter already reported is a
+/// no-op and doesn't count toward their limits. A report reaching
+/// `state.report_escalation` reporters is escalated.
+///
+/// Sent as a multipart form, a report can carry a `screenshot` image (PNG,
+/// JPEG, GIF or WebP, up to [`MAX_IMAGE_BYTES`]). It's stored in the
+/// configured bucket and only admins can see it, through
+/// `/admin/reports/:id/screenshot`. Only a newly filed report keeps its
+/// screenshot; one added to an existing report drops it.
 pub async fn create_report(
     State(state): State<AppState>,
-    Json(req): Json<CreateReportRequest>,
-) -> Result<Json<CreateReportResponse>, (StatusCode, String)> {
+    _: RequireAdmin,
+    submission: ReportSubmission,
+) -> Result<Json<CreateReportResponse>, Response> {
+    let ReportSubmission {
+        report: req,
+        screenshot,
+    } = submission;
     let db = state.db.as_ref().ok_or_else(|| {
         (
             StatusCode::SERVICE_UNAVAILABLE,
             "database not configured".to_string(),
         )
+            .into_response()
     })?;
 
-    // Validate reason
-    let valid_reasons = ["copyright", "abuse", "spam", "explicit", "other"];
-    if !valid_reasons.contains(&req.reason.as_str()) {
-        return Err((
-            StatusCode::BAD_REQUEST,
-            format!("invalid reason: {}. valid: {:?}", req.reason, valid_reasons),
-        ));
+    validate_report(&req).map_err(|e| (StatusCode::BAD_REQUEST, e).into_response())?;
+    let reason = db.get_report_reason(&req.reason).await.map_err(|e| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("failed to look up report reason: {e}"),
+        )
+            .into_response()
+    })?;
+    check_report_reason(&req, reason.as_ref())
+        .map_err(|e| (StatusCode::BAD_REQUEST, e).into_response())?;
+
+    // with hashing on, everything below sees only the hash, and the handle
+    // isn't kept since it would name the reporter just the same
+    let hasher = state.reporter_hasher.as_ref();
+    let reporter = stored_reporter(hasher, &req.reporter_did);
+    let reporter_handle = req.reporter_handle.as_deref().filter(|_| hasher.is_none());
+    let reporter_hash = hasher.map(|_| reporter.clone());
+
+    let existing = db
+        .find_own_open_report(&reporter, &req.target_type, &req.target_id, &req.reason)
+        .await
+        .map_err(|e| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("failed to check for duplicate reports: {e}"),
+            )
+                .into_response()
+        })?;
+    if let Some(report) = existing {
+        return Ok(Json(CreateReportResponse {
+            reporter_hash,
+            ..CreateReportResponse::already_reported(&report)
+        }));
     }
 
-    // Validate target_type
-    let valid_types = ["track", "artist", "album", "playlist", "tag", "comment"];
-    if !valid_types.contains(&req.target_type.as_str()) {
-        return Err((
-            StatusCode::BAD_REQUEST,
-            format!(
-                "invalid target_type: {}. valid: {:?}",
-                req.target_type, valid_types
-            ),
-        ));
+    let activity = db.get_reporter_activity(&reporter).await.map_err(|e| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("failed to check report limit: {e}"),
+        )
+            .into_response()
+    })?;
+    if let Some(retry_after) = state.report_limits.retry_after(&activity, Utc::now()) {
+        info!(
+            reporter = %reporter,
+            last_hour = activity.last_hour,
+            last_day = activity.last_day,
+            "report limit reached"
+        );
+        return Err(RateLimitExceeded { retry_after }.into_response());
     }
 
-    let report = db
-        .create_report(
-            &req.reporter_did,
-            req.reporter_handle.as_deref(),
+    let screenshot_key = match screenshot {
+        Some(screenshot) => Some(
+            store_screenshot(&state, screenshot)
+                .await
+                .map_err(IntoResponse::into_response)?,
+        ),
+        None => None,
+    };
+
+    let filed = match db
+        .file_report(
+            &reporter,
+            reporter_handle,
             &req.target_type,
             &req.target_id,
             req.target_name.as_deref(),
@@ -119,36 +623,223 @@ pub async fn create_report(
             &req.reason,
             req.description.as_deref(),
             req.screenshot_url.as_deref(),
+            screenshot_key.as_deref(),
         )
         .await
-        .map_err(|e| {
-            (
+    {
+        Ok(filed) => filed,
+        Err(e) => {
+            discard_screenshot(&state, screenshot_key.as_deref()).await;
+            return Err((
                 StatusCode::INTERNAL_SERVER_ERROR,
                 format!("failed to create report: {e}"),
             )
-        })?;
+                .into_response());
+        }
+    };
+    if !matches!(filed, FiledReport::Created(_)) {
+        discard_screenshot(&state, screenshot_key.as_deref()).await;
+    }
 
-    info!(
-        report_id = report.id,
-        reporter = %req.reporter_did,
-        target_type = %req.target_type,
-        target_id = %req.target_id,
-        reason = %req.reason,
-        "user report created"
-    );
+    let response = match filed {
+        FiledReport::Created(report) => {
+            info!(
+                report_id = report.id,
+                reporter = %reporter,
+                target_type = %req.target_type,
+                target_id = %req.target_id,
+                reason = %req.reason,
+                "user report created"
+            );
+            escalate_if_needed(&state, db, &report).await;
+            CreateReportResponse {
+                report_id: report.id,
+                duplicate: false,
+                message: None,
+                reporter_hash: None,
+            }
+        }
+        FiledReport::Supported(report) => {
+            info!(
+                report_id = report.id,
+                reporter = %reporter,
+                supporters = report.supporter_count,
+                "user report added to existing report"
+            );
+            escalate_if_needed(&state, db, &report).await;
+            CreateReportResponse {
+                report_id: report.id,
+                duplicate: true,
+                message: None,
+                reporter_hash: None,
+            }
+        }
+        FiledReport::AlreadyReported(report) => CreateReportResponse::already_reported(&report),
+    };
 
     Ok(Json(CreateReportResponse {
-        report_id: report.id,
+        reporter_hash,
+        ..response
     }))
 }
 
+/// Upload a report screenshot under a random key, returning the key.
+async fn store_screenshot(state: &AppState, screenshot: Screenshot) -> Result<String, AppError> {
+    let storage = state
+        .storage
+        .as_ref()
+        .ok_or(AppError::StorageNotConfigured)?;
+    let key = screenshot_key(screenshot.media_type);
+    storage
+        .put_object(&key, screenshot.bytes, screenshot.media_type)
+        .await?;
+    Ok(key)
+}
+
+/// A fresh storage key for a screenshot of `media_type`.
+fn screenshot_key(media_type: &str) -> String {
+    let ext = media_type.strip_prefix("image/").unwrap_or("bin");
+    format!("report-screenshots/{:032x}.{ext}", rand::random::<u128>())
+}
+
+/// Delete a screenshot uploaded for a report that wasn't filed. Best effort:
+/// a failure leaves an orphaned object and is only logged.
+async fn discard_screenshot(state: &AppState, key: Option<&str>) {
+    let (Some(storage), Some(key)) = (&state.storage, key) else {
+        return;
+    };
+    if let Err(e) = storage.delete_object(key).await {
+        warn!(error = %e, key, "failed to delete unused report screenshot");
+    }
+}
+
+/// Escalate `report` if it has crossed the reporter threshold: mark it
+/// `escalated`, open a review batch if configured, and send the
+/// `report.escalated` webhook, which is kept in `failed_deliveries` with
+/// those writes so it survives a restart. Failures are logged rather than
+/// failing the report that triggered them.
+async fn escalate_if_needed(state: &AppState, db: &LabelDb, report: &UserReport) {
+    let escalation = state.report_escalation;
+    if !escalation.should_escalate(report) {
+        return;
+    }
+    if let Err(e) = escalate(state, db, report, escalation).await {
+        warn!(report_id = report.id, error = %e, "failed to escalate report");
+    }
+}
+
+async fn escalate(
+    state: &AppState,
+    db: &LabelDb,
+    report: &UserReport,
+    escalation: ReportEscalation,
+) -> Result<(), sqlx::Error> {
+    let id = report.id;
+    let batch_id = generate_batch_id();
+    let batch = escalation
+        .batch_uri(report)
+        .map(|uri| (batch_id.as_str(), uri, Utc::now() + state.review_batch_ttl));
+    let opened_batch = batch.is_some().then(|| batch_id.clone());
+    let admin_url = format!("{}/admin/reports/{}", state.public_url, id);
+    // only the filing that flips the status carries out the escalation
+    let Some((report, delivery)) = db
+        .escalate_report(id, escalation.threshold, batch, |report| {
+            state
+                .webhook
+                .as_ref()?
+                .report_escalated(ReportEscalatedEvent {
+                    report_id: id,
+                    target_type: report.target_type.clone(),
+                    target_id: report.target_id.clone(),
+                    target_uri: report.target_uri.clone(),
+                    reason: report.reason.clone(),
+                    reporters: report.supporter_count + 1,
+                    batch_id: opened_batch.clone(),
+                    admin_url,
+                })
+        })
+        .await?
+    else {
+        return Ok(());
+    };
+    info!(
+        report_id = id,
+        reporters = report.supporter_count + 1,
+        batch_id = opened_batch.as_deref(),
+        "user report escalated"
+    );
+
+    if let (Some(webhook), Some(delivery)) = (&state.webhook, delivery) {
+        webhook.deliver_kept(delivery);
+    }
+    Ok(())
+}
+
+/// Check a new report's fields, returning the message for a 400.
+fn validate_report(req: &CreateReportRequest) -> Result<(), String> {
+    let did_len = req.reporter_did.len();
+    if !req.reporter_did.starts_with("did:") || !(MIN_DID_LEN..=MAX_DID_LEN).contains(&did_len) {
+        return Err(format!("invalid reporter_did: {}", req.reporter_did));
+    }
+
+    let valid_types = ["track", "artist", "album", "playlist", "tag", "comment"];
+    if !valid_types.contains(&req.target_type.as_str()) {
+        return Err(format!(
+            "invalid target_type: {}. valid: {:?}",
+            req.target_type, valid_types
+        ));
+    }
+
+    if req
+        .description
+        .as_ref()
+        .is_some_and(|d| d.chars().count() > MAX_DESCRIPTION_CHARS)
+    {
+        return Err(format!(
+            "description is longer than {MAX_DESCRIPTION_CHARS} characters"
+        ));
+    }
+    if req
+        .screenshot_url
+        .as_ref()
+        .is_some_and(|u| u.chars().count() > MAX_SCREENSHOT_URL_CHARS)
+    {
+        return Err(format!(
+            "screenshot_url is longer than {MAX_SCREENSHOT_URL_CHARS} characters"
+        ));
+    }
+    Ok(())
+}
+
+/// Check a new report's reason against its `report_reasons` row, returning
+/// the message for a 400.
+fn check_report_reason(
+    req: &CreateReportRequest,
+    reason: Option<&ReportReasonRow>,
+) -> Result<(), String> {
+    let has_target_uri = req.target_uri.as_deref().is_some_and(|uri| !uri.is_empty());
+    match reason {
+        None => Err(format!(
+            "invalid reason: {}. valid reasons are listed at GET /report-reasons",
+            req.reason
+        )),
+        Some(reason) if !reason.active => {
+            Err(format!("reason {} is no longer accepted", reason.key))
+        }
+        Some(reason) if reason.requires_target_uri && !has_target_uri => {
+            Err(format!("reason {} requires a target_uri", reason.key))
+        }
+        Some(_) => Ok(()),
+    }
+}
+
 /// List user reports with optional filtering.
 ///
 /// GET /admin/reports
 pub async fn list_reports(
     State(state): State<AppState>,
     Query(params): Query<ListReportsParams>,
-) -> Result<Json<ListReportsResponse>, (StatusCode, String)> {
+) -> Result<Response, (StatusCode, String)> {
     let db = state.db.as_ref().ok_or_else(|| {
         (
             StatusCode::SERVICE_UNAVAILABLE,
@@ -158,14 +849,23 @@ pub async fn list_reports(
 
     let limit = params.limit.min(100).max(1);
     let offset = params.offset.max(0);
+    let cursor = parse_reports_cursor(params.cursor.as_deref())?;
+    if cursor.is_some() && offset > 0 {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "pass cursor or offset, not both".to_string(),
+        ));
+    }
 
-    let reports = db
-        .list_reports(
-            params.status.as_deref(),
-            params.target_type.as_deref(),
-            limit,
-            offset,
-        )
+    let filter = ReportFilter {
+        status: non_empty(&params.status),
+        target_type: non_empty(&params.target_type),
+        target_id: non_empty(&params.target_id),
+        target_uri: non_empty(&params.target_uri),
+        q: non_empty(&params.q),
+    };
+    let page = db
+        .list_reports(&filter, cursor, limit, offset)
         .await
         .map_err(|e| {
             (
@@ -174,9 +874,43 @@ pub async fn list_reports(
             )
         })?;
 
-    let count = reports.len();
+    let count = page.reports.len();
+    let body = Json(ListReportsResponse {
+        reports: page.reports,
+        count,
+        next_cursor: page.next_cursor,
+    });
+
+    if offset > 0 {
+        warn!(offset, "report listing paged by deprecated offset");
+        return Ok((
+            [
+                (HeaderName::from_static("deprecation"), "true"),
+                (WARNING, OFFSET_DEPRECATION_WARNING),
+            ],
+            body,
+        )
+            .into_response());
+    }
+    Ok(body.into_response())
+}
+
+/// A filter param, unless it was left blank.
+fn non_empty(value: &Option<String>) -> Option<&str> {
+    value.as_deref().map(str::trim).filter(|v| !v.is_empty())
+}
 
-    Ok(Json(ListReportsResponse { reports, count }))
+/// Parse the `cursor` param of a report listing.
+fn parse_reports_cursor(
+    cursor: Option<&str>,
+) -> Result<Option<ReportsCursor>, (StatusCode, String)> {
+    cursor
+        .filter(|c| !c.is_empty())
+        .map(|c| {
+            ReportsCursor::parse(c)
+                .ok_or_else(|| (StatusCode::BAD_REQUEST, format!("invalid cursor: {}", c)))
+        })
+        .transpose()
 }
 
 /// Get a single report by ID.
@@ -204,53 +938,491 @@ pub async fn get_report(
         .map(Json)
 }
 
+/// Redirect to a short-lived signed link to a report's uploaded screenshot.
+/// Screenshots are never public, and viewers can't get a link either.
+///
+/// GET /admin/reports/:id/screenshot
+pub async fn get_report_screenshot(
+    State(state): State<AppState>,
+    _: RequireAdmin,
+    Path(id): Path<i32>,
+) -> Result<Redirect, AppError> {
+    let db = state.db.as_ref().ok_or(AppError::LabelerNotConfigured)?;
+    let storage = state
+        .storage
+        .as_ref()
+        .ok_or(AppError::StorageNotConfigured)?;
+    let key = db
+        .get_report(id)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("report {id}")))?
+        .screenshot_key
+        .ok_or_else(|| AppError::NotFound(format!("report {id} has no uploaded screenshot")))?;
+    let url = storage.presigned_get_url(&key, SCREENSHOT_LINK_TTL, Utc::now())?;
+    Ok(Redirect::temporary(&url))
+}
+
 /// Resolve a user report.
 ///
 /// POST /admin/reports/:id/resolve
 pub async fn resolve_report(
     State(state): State<AppState>,
-    Path(id): Path<i32>,
-    Json(req): Json<ResolveReportRequest>,
-) -> Result<Json<UserReport>, (StatusCode, String)> {
+    _: RequireAdmin,
+    Path(id): Path<i32>,
+    actor: Actor,
+    Json(req): Json<ResolveReportRequest>,
+) -> Result<Json<UserReport>, (StatusCode, String)> {
+    let report = apply_report_resolution(
+        &state,
+        id,
+        &req.status,
+        req.admin_notes.as_deref(),
+        &req.resolved_by,
+        &actor,
+    )
+    .await?;
+    Ok(Json(report))
+}
+
+/// Resolve a user report from the admin UI and return HTML for htmx.
+///
+/// POST /admin/reports/:id/resolve-htmx
+///
+/// The acting admin (from `X-Moderation-Actor`) is recorded as `resolved_by`.
+pub async fn resolve_report_htmx(
+    State(state): State<AppState>,
+    _: RequireAdmin,
+    Path(id): Path<i32>,
+    actor: Actor,
+    Form(form): Form<ResolveReportForm>,
+) -> Result<Response, (StatusCode, String)> {
+    let report = apply_report_resolution(
+        &state,
+        id,
+        &form.status,
+        form.notes(),
+        actor.as_str(),
+        &actor,
+    )
+    .await?;
+
+    // Return success toast + trigger refresh
+    Ok((
+        [(CONTENT_TYPE, "text/html; charset=utf-8")],
+        [(HeaderName::from_static("hx-trigger"), "reportsUpdated")],
+        report_resolved_toast(&report),
+    )
+        .into_response())
+}
+
+/// Close many reports at once with the same status and notes.
+///
+/// POST /admin/reports/resolve-bulk
+///
+/// Reports already resolved or dismissed are skipped and say so in their
+/// outcome. The rest are closed in one transaction, with an audit entry each,
+/// and then their resolution callbacks are sent. If any id doesn't exist the
+/// response is 404 and nothing is changed.
+pub async fn resolve_reports_bulk(
+    State(state): State<AppState>,
+    _: RequireAdmin,
+    _: RateLimited,
+    actor: Actor,
+    Json(req): Json<BulkResolveReportsRequest>,
+) -> Result<Json<BulkResolveReportsResponse>, AppError> {
+    let db = state.db.as_ref().ok_or(AppError::LabelerNotConfigured)?;
+    let ids = validate_bulk_resolve(&req)?;
+    let resolved_by = req.resolved_by.as_deref().unwrap_or(actor.as_str());
+
+    let resolution = db
+        .resolve_reports(
+            &ids,
+            &req.status,
+            req.admin_notes.as_deref(),
+            resolved_by,
+            actor.as_str(),
+        )
+        .await?;
+    let (resolved, already_closed) = match resolution {
+        BulkReportResolution::Missing(missing) => {
+            return Err(AppError::NotFound(format!(
+                "no reports with ids {missing:?}; nothing was resolved"
+            )));
+        }
+        BulkReportResolution::Closed {
+            resolved,
+            already_closed,
+        } => (resolved, already_closed),
+    };
+
+    info!(
+        requested = ids.len(),
+        resolved = resolved.len(),
+        skipped = already_closed.len(),
+        status = %req.status,
+        resolved_by = %resolved_by,
+        "user reports bulk resolved"
+    );
+    for report in &resolved {
+        notify_report_resolved(&state, report).await;
+    }
+
+    Ok(Json(BulkResolveReportsResponse {
+        resolved_count: resolved.len(),
+        outcomes: bulk_report_outcomes(&ids, &already_closed),
+    }))
+}
+
+/// Check a bulk resolve and return its distinct ids, in request order.
+fn validate_bulk_resolve(req: &BulkResolveReportsRequest) -> Result<Vec<i32>, AppError> {
+    if req.ids.is_empty() {
+        return Err(AppError::BadRequest("ids is empty".to_string()));
+    }
+    if req.ids.len() > MAX_BULK_RESOLVE_REPORTS {
+        return Err(AppError::BadRequest(format!(
+            "at most {MAX_BULK_RESOLVE_REPORTS} reports per bulk resolve"
+        )));
+    }
+    if !BULK_RESOLVE_STATUSES.contains(&req.status.as_str()) {
+        return Err(AppError::BadRequest(format!(
+            "invalid status: {}. valid: {:?}",
+            req.status, BULK_RESOLVE_STATUSES
+        )));
+    }
+    let mut seen = HashSet::new();
+    Ok(req
+        .ids
+        .iter()
+        .copied()
+        .filter(|id| seen.insert(*id))
+        .collect())
+}
+
+/// One outcome per id: resolved, unless it was already closed.
+fn bulk_report_outcomes(ids: &[i32], already_closed: &[(i32, String)]) -> Vec<BulkReportOutcome> {
+    ids.iter()
+        .map(|&id| {
+            let skipped = already_closed
+                .iter()
+                .find(|(closed, _)| *closed == id)
+                .map(|(_, status)| format!("already {status}"));
+            BulkReportOutcome {
+                id,
+                resolved: skipped.is_none(),
+                skipped,
+            }
+        })
+        .collect()
+}
+
+/// Check that `status` is one a report can be moved to.
+fn validate_report_status(status: &str) -> Result<(), (StatusCode, String)> {
+    if REPORT_STATUSES.contains(&status) {
+        Ok(())
+    } else {
+        Err((
+            StatusCode::BAD_REQUEST,
+            format!("invalid status: {}. valid: {:?}", status, REPORT_STATUSES),
+        ))
+    }
+}
+
+/// Update a report's status and record it in the audit log.
+async fn apply_report_resolution(
+    state: &AppState,
+    id: i32,
+    status: &str,
+    admin_notes: Option<&str>,
+    resolved_by: &str,
+    actor: &Actor,
+) -> Result<UserReport, (StatusCode, String)> {
+    let db = state.db.as_ref().ok_or_else(|| {
+        (
+            StatusCode::SERVICE_UNAVAILABLE,
+            "database not configured".to_string(),
+        )
+    })?;
+
+    validate_report_status(status)?;
+
+    let report = db
+        .resolve_report(id, status, admin_notes, resolved_by)
+        .await
+        .map_err(|e| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("failed to resolve report: {e}"),
+            )
+        })?;
+
+    let report = report.ok_or_else(|| (StatusCode::NOT_FOUND, "report not found".to_string()))?;
+
+    db.record_audit_event(
+        "resolve_report",
+        &id.to_string(),
+        actor.as_str(),
+        serde_json::json!({
+            "status": status,
+            "admin_notes": admin_notes,
+            "resolved_by": resolved_by,
+            "supporters": report.supporter_count,
+        }),
+    )
+    .await
+    .map_err(|e| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("failed to record audit event: {e}"),
+        )
+    })?;
+
+    info!(
+        report_id = id,
+        status = %status,
+        resolved_by = %resolved_by,
+        "user report resolved"
+    );
+    notify_report_resolved(state, &report).await;
+
+    Ok(report)
+}
+
+/// The label a report's reason calls for, when there's an obvious one.
+fn default_label(reason: &str) -> Option<&'static str> {
+    match reason {
+        "copyright" => Some("copyright-violation"),
+        "explicit" => Some("sensitive-image"),
+        _ => None,
+    }
+}
+
+/// The label value to emit for `report`: the requested one, or its reason's
+/// default. Must be in the label registry.
+fn report_label_value(report: &UserReport, requested: Option<&str>) -> Result<String, AppError> {
+    let val = match requested.map(str::trim).filter(|v| !v.is_empty()) {
+        Some(val) => val,
+        None => default_label(&report.reason).ok_or_else(|| {
+            AppError::BadRequest(format!(
+                "no default label for reason {}; pass val, one of {:?}",
+                report.reason, LABEL_VALUES
+            ))
+        })?,
+    };
+    if !LABEL_VALUES.contains(&val) {
+        return Err(AppError::BadRequest(format!(
+            "unknown label value: {}. valid: {:?}",
+            val, LABEL_VALUES
+        )));
+    }
+    Ok(val.to_string())
+}
+
+/// Label a report's target and resolve the report.
+///
+/// POST /admin/reports/:id/label
+///
+/// Signs and broadcasts a label on the report's `target_uri` (or links an
+/// already-active one with the same value), stores its seq on the report as
+/// `label_seq`, and marks the report resolved, all in one transaction; a
+/// report resolved by someone else meanwhile gets a 409 and no label.
+/// Reports without a `target_uri` get a 400; label those with `/emit-label`
+/// and the AT-URI.
+pub async fn label_report(
+    State(state): State<AppState>,
+    _: RequireAdmin,
+    _: RateLimited,
+    Path(id): Path<i32>,
+    actor: Actor,
+    Json(req): Json<LabelReportRequest>,
+) -> Result<Json<LabelReportResponse>, AppError> {
+    let db = state.db.as_ref().ok_or(AppError::LabelerNotConfigured)?;
+    let signer = state
+        .signer
+        .as_ref()
+        .ok_or(AppError::LabelerNotConfigured)?;
+
+    let report = db
+        .get_report(id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("report not found".to_string()))?;
+    if report.status == "resolved" || report.status == "dismissed" {
+        return Err(AppError::Conflict(format!(
+            "report {} is already {}",
+            id, report.status
+        )));
+    }
+    let uri = report
+        .target_uri
+        .clone()
+        .filter(|uri| uri.starts_with("at://"))
+        .ok_or_else(|| {
+            AppError::BadRequest(format!(
+                "report {} has no target AT-URI; find the record's at:// URI and use POST /emit-label",
+                id
+            ))
+        })?;
+    let val = report_label_value(&report, req.val.as_deref())?;
+
+    // sign as the source of any label already in force, e.g. a prod label
+    // on a dev-hosted record
+    let src = crate::admin::label_src(db, signer, &uri, &val).await?;
+    let label = signer.sign_label(Label::new(&src, &uri, &val))?;
+    let admin_notes = req
+        .admin_notes
+        .as_deref()
+        .map(str::trim)
+        .filter(|n| !n.is_empty());
+    let (report, stored) = db
+        .resolve_report_with_label(id, &label, admin_notes, actor.as_str())
+        .await?
+        .ok_or_else(|| AppError::Conflict(format!("report {} is already resolved", id)))?;
+    let (seq, created) = match stored {
+        StoredLabel::Created(seq) => {
+            if let Some(tx) = &state.label_tx {
+                let _ = tx.send((seq, label));
+            }
+            (seq, true)
+        }
+        StoredLabel::Existing(active) => (active.seq, false),
+    };
+    info!(report_id = id, uri = %uri, val = %val, seq, created, "labeled reported target");
+    notify_report_resolved(&state, &report).await;
+
+    Ok(Json(LabelReportResponse {
+        seq,
+        val,
+        created,
+        report,
+    }))
+}
+
+/// Toast shown after a report is resolved from the admin UI.
+fn report_resolved_toast(report: &UserReport) -> String {
+    format!(
+        r#"<div id="toast" class="toast success" hx-swap-oob="true">report {}: {}</div>"#,
+        report.id,
+        html_escape(&report.status)
+    )
+}
+
+/// Get report counts grouped by reason and by status, plus reason, target
+/// type, time-to-resolution and dismissal numbers for a date window.
+///
+/// GET /admin/reports/stats?from=2026-01-01&to=2026-01-07
+pub async fn get_report_stats(
+    State(state): State<AppState>,
+    Query(params): Query<ReportStatsParams>,
+) -> Result<Json<ReportStats>, (StatusCode, String)> {
     let db = state.db.as_ref().ok_or_else(|| {
         (
             StatusCode::SERVICE_UNAVAILABLE,
             "database not configured".to_string(),
         )
     })?;
+    let (from, to) = report_stats_window(&params, Utc::now().date_naive())
+        .map_err(|e| (StatusCode::BAD_REQUEST, e))?;
+    let mut stats = fetch_report_stats(db).await?;
+    stats.window = Some(fetch_report_window_stats(&state, db, from, to).await?);
+    Ok(Json(stats))
+}
 
-    // Validate status
-    let valid_statuses = ["open", "investigating", "resolved", "dismissed"];
-    if !valid_statuses.contains(&req.status.as_str()) {
-        return Err((
-            StatusCode::BAD_REQUEST,
-            format!(
-                "invalid status: {}. valid: {:?}",
-                req.status, valid_statuses
-            ),
-        ));
-    }
-
-    let report = db
-        .resolve_report(id, &req.status, req.admin_notes.as_deref(), &req.resolved_by)
+/// Window statistics, served from the cache for a minute.
+async fn fetch_report_window_stats(
+    state: &AppState,
+    db: &LabelDb,
+    from: NaiveDate,
+    to: NaiveDate,
+) -> Result<ReportWindowStats, (StatusCode, String)> {
+    let stats = state
+        .report_stats_cache
+        .get_or_refresh_for((from, to), Instant::now(), || {
+            db.report_window_stats(from, to)
+        })
         .await
         .map_err(|e| {
             (
                 StatusCode::INTERNAL_SERVER_ERROR,
-                format!("failed to resolve report: {e}"),
+                format!("failed to compute report stats: {e}"),
             )
         })?;
+    Ok(stats.as_ref().clone())
+}
 
-    let report = report.ok_or_else(|| (StatusCode::NOT_FOUND, "report not found".to_string()))?;
+async fn fetch_report_stats(db: &crate::db::LabelDb) -> Result<ReportStats, (StatusCode, String)> {
+    let counts = db.report_counts().await.map_err(|e| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("failed to count reports: {e}"),
+        )
+    })?;
+    Ok(ReportStats::from_counts(&counts))
+}
 
-    info!(
-        report_id = id,
-        status = %req.status,
-        resolved_by = %req.resolved_by,
-        "user report resolved"
-    );
+/// A reporter's report counts by status and reason, dismissal rate and
+/// newest reports. `did` may be a DID or a stored reporter hash; with
+/// hashing on, a DID is hashed before the lookup.
+///
+/// GET /admin/reporters/:did
+pub async fn get_reporter_history(
+    State(state): State<AppState>,
+    Path(did): Path<String>,
+) -> Result<Json<ReporterHistory>, (StatusCode, String)> {
+    Ok(Json(
+        fetch_reporter_history(&state, did, REPORTER_RECENT_REPORTS).await?,
+    ))
+}
 
-    Ok(Json(report))
+/// The one-line reporter track record on a report card, loaded lazily so the
+/// listing doesn't run a query per card.
+///
+/// GET /admin/reporters/:did/summary-html
+pub async fn get_reporter_summary_html(
+    State(state): State<AppState>,
+    Path(did): Path<String>,
+) -> Result<Response, (StatusCode, String)> {
+    let history = fetch_reporter_history(&state, did, 0).await?;
+    let html = render_reporter_summary(&history);
+    Ok(([(CONTENT_TYPE, "text/html; charset=utf-8")], html).into_response())
+}
+
+async fn fetch_reporter_history(
+    state: &AppState,
+    did: String,
+    recent: i64,
+) -> Result<ReporterHistory, (StatusCode, String)> {
+    let db = state.db.as_ref().ok_or_else(|| {
+        (
+            StatusCode::SERVICE_UNAVAILABLE,
+            "database not configured".to_string(),
+        )
+    })?;
+    let did = stored_reporter(state.reporter_hasher.as_ref(), &did);
+    let reports = db.get_reporter_reports(&did, recent).await.map_err(|e| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("failed to load reporter history: {e}"),
+        )
+    })?;
+    Ok(ReporterHistory::new(did, reports))
+}
+
+/// Render a reporter's track record as "reporter: 12 reports, 9 dismissed".
+fn render_reporter_summary(history: &ReporterHistory) -> String {
+    let reports = match history.total {
+        1 => "1 report".to_string(),
+        n => format!("{n} reports"),
+    };
+    let supported = if history.supported > 0 {
+        format!(", supported {}", history.supported)
+    } else {
+        String::new()
+    };
+    format!(
+        "reporter: {}, {} dismissed{}",
+        reports,
+        history.dismissed(),
+        supported
+    )
 }
 
 /// Query parameters for HTML reports listing.
@@ -258,6 +1430,47 @@ pub async fn resolve_report(
 pub struct ListReportsHtmlParams {
     #[serde(default = "default_status_filter")]
     pub status: String,
+    /// Set by the "load more" button; renders just the next page of cards.
+    #[serde(default)]
+    pub cursor: Option<String>,
+    #[serde(default)]
+    pub target_id: Option<String>,
+    #[serde(default)]
+    pub target_uri: Option<String>,
+    /// Text to find in the description or admin notes.
+    #[serde(default)]
+    pub q: Option<String>,
+}
+
+impl ListReportsHtmlParams {
+    fn filter(&self) -> ReportFilter<'_> {
+        ReportFilter {
+            // "all" is every status
+            status: Some(self.status.as_str()).filter(|s| *s != "all"),
+            target_id: non_empty(&self.target_id),
+            target_uri: non_empty(&self.target_uri),
+            q: non_empty(&self.q),
+            ..Default::default()
+        }
+    }
+
+    fn is_search(&self) -> bool {
+        let filter = self.filter();
+        filter.target_id.is_some() || filter.target_uri.is_some() || filter.q.is_some()
+    }
+
+    /// The search filters as query params, for links that keep them.
+    fn search_params(&self) -> String {
+        [
+            ("target_id", &self.target_id),
+            ("target_uri", &self.target_uri),
+            ("q", &self.q),
+        ]
+        .iter()
+        .filter_map(|(name, value)| non_empty(value).map(|v| (name, v)))
+        .map(|(name, value)| format!("&amp;{}={}", name, url_encode(value)))
+        .collect()
+    }
 }
 
 fn default_status_filter() -> String {
@@ -267,6 +1480,9 @@ fn default_status_filter() -> String {
 /// Render reports as HTML partial for htmx.
 ///
 /// GET /admin/reports-html
+///
+/// Without a cursor this renders the summary, filter row and first page;
+/// with one it renders just the next page, to replace the "load more" button.
 pub async fn list_reports_html(
     State(state): State<AppState>,
     Query(params): Query<ListReportsHtmlParams>,
@@ -278,15 +1494,10 @@ pub async fn list_reports_html(
         )
     })?;
 
-    // Map "all" to None for status filter
-    let status_filter = if params.status == "all" {
-        None
-    } else {
-        Some(params.status.as_str())
-    };
+    let cursor = parse_reports_cursor(params.cursor.as_deref())?;
 
-    let reports = db
-        .list_reports(status_filter, None, 100, 0)
+    let page = db
+        .list_reports(&params.filter(), cursor, REPORTS_HTML_PAGE_SIZE, 0)
         .await
         .map_err(|e| {
             (
@@ -295,14 +1506,109 @@ pub async fn list_reports_html(
             )
         })?;
 
-    let html = render_reports_list(&reports, &params.status);
+    let html = if cursor.is_some() {
+        render_reports_page(&page, &params)
+    } else {
+        let mut stats = fetch_report_stats(db).await?;
+        let today = Utc::now().date_naive();
+        let week_start = today - Days::new(REPORT_STATS_DEFAULT_DAYS - 1);
+        stats.window = Some(fetch_report_window_stats(&state, db, week_start, today).await?);
+        render_reports_list(&page, &params, &stats)
+    };
 
     Ok(([(CONTENT_TYPE, "text/html; charset=utf-8")], html).into_response())
 }
 
+/// Render the summary bar of open reports by reason and all reports by
+/// status.
+fn render_report_summary(stats: &ReportStats) -> String {
+    let mut open_by_reason: Vec<(&String, &i64)> = stats.open_by_reason.iter().collect();
+    open_by_reason.sort_by(|a, b| b.1.cmp(a.1).then(a.0.cmp(b.0)));
+    let reasons = if open_by_reason.is_empty() {
+        "<span class=\"summary-label\">none</span>".to_string()
+    } else {
+        open_by_reason
+            .iter()
+            .map(|(reason, count)| {
+                format!(
+                    "<span class=\"reason-chip\">{} <strong>{}</strong></span>",
+                    html_escape(reason),
+                    count
+                )
+            })
+            .collect()
+    };
+
+    // known statuses in workflow order, then anything unexpected
+    let mut statuses: Vec<(&str, i64)> = REPORT_STATUSES
+        .iter()
+        .map(|s| (*s, stats.by_status.get(*s).copied().unwrap_or(0)))
+        .collect();
+    statuses.extend(
+        stats
+            .by_status
+            .iter()
+            .filter(|(s, _)| !REPORT_STATUSES.contains(&s.as_str()))
+            .map(|(s, count)| (s.as_str(), *count)),
+    );
+    let statuses = statuses
+        .iter()
+        .map(|(status, count)| format!("{} {}", html_escape(status), count))
+        .collect::<Vec<_>>()
+        .join(" · ");
+
+    let window = stats
+        .window
+        .as_ref()
+        .map(render_report_window)
+        .unwrap_or_default();
+
+    format!(
+        "<div class=\"report-summary\">\
+            <span class=\"summary-label\">open by reason:</span>{}\
+            <span class=\"summary-statuses\">{}</span>{}\
+        </div>",
+        reasons, statuses, window
+    )
+}
+
+/// Render a window's numbers as one line of the summary bar.
+fn render_report_window(window: &ReportWindowStats) -> String {
+    let days = (window.to - window.from).num_days() + 1;
+    let median = window
+        .median_resolution_secs
+        .map_or("—".to_string(), format_duration_secs);
+    let dismissed = window
+        .dismissal_rate
+        .map_or("—".to_string(), |rate| format!("{:.0}%", rate * 100.0));
+    format!(
+        "<span class=\"summary-window\">{} days: {} filed · {} closed · \
+            median {} to close · {} dismissed</span>",
+        days, window.filed, window.closed, median, dismissed
+    )
+}
+
+/// Format seconds as minutes, hours or days, whichever reads best.
+fn format_duration_secs(secs: f64) -> String {
+    let minutes = secs / 60.0;
+    if minutes < 60.0 {
+        format!("{minutes:.0}m")
+    } else if minutes < 48.0 * 60.0 {
+        format!("{:.0}h", minutes / 60.0)
+    } else {
+        format!("{:.1}d", minutes / (24.0 * 60.0))
+    }
+}
+
 /// Render the reports list as HTML with filter controls.
-fn render_reports_list(reports: &[UserReport], current_filter: &str) -> String {
+fn render_reports_list(
+    page: &ReportsPage,
+    params: &ListReportsHtmlParams,
+    stats: &ReportStats,
+) -> String {
+    let current_filter = params.status.as_str();
     let open_active = if current_filter == "open" { " active" } else { "" };
+    let escalated_active = if current_filter == "escalated" { " active" } else { "" };
     let resolved_active = if current_filter == "resolved" || current_filter == "dismissed" {
         " active"
     } else {
@@ -310,27 +1616,73 @@ fn render_reports_list(reports: &[UserReport], current_filter: &str) -> String {
     };
     let all_active = if current_filter == "all" { " active" } else { "" };
 
-    let count = reports.len();
-    let count_label = match current_filter {
-        "open" => format!("{} open", count),
-        "resolved" | "dismissed" => format!("{} closed", count),
-        _ => format!("{} total", count),
+    // closed reports have nothing to select
+    let bulk_resolve = if current_filter == "resolved" || current_filter == "dismissed" {
+        ""
+    } else {
+        "<div class=\"bulk-resolve\">\
+            <button type=\"button\" id=\"report-bulk-btn\" class=\"btn btn-warning\" onclick=\"showReportBulkActions(this)\" disabled>close selected</button>\
+        </div>"
+    };
+
+    let count_label = if params.is_search() {
+        // stats don't know about the search; count what's loaded
+        let more = if page.next_cursor.is_some() { "+" } else { "" };
+        format!("{}{} matching", page.reports.len(), more)
+    } else {
+        // the whole filter's count, not just the loaded page
+        let count: i64 = match current_filter {
+            "all" => stats.by_status.values().sum(),
+            status => stats.by_status.get(status).copied().unwrap_or(0),
+        };
+        match current_filter {
+            "open" => format!("{} open", count),
+            "escalated" => format!("{} escalated", count),
+            "resolved" | "dismissed" => format!("{} closed", count),
+            _ => format!("{} total", count),
+        }
     };
 
+    let search_params = params.search_params();
     let filter_buttons = format!(
         "<div class=\"filter-row\">\
             <span class=\"filter-label\">show:</span>\
-            <button type=\"button\" class=\"filter-btn{}\" hx-get=\"/admin/reports-html?status=open\" hx-target=\"#reports-list\">open</button>\
-            <button type=\"button\" class=\"filter-btn{}\" hx-get=\"/admin/reports-html?status=resolved\" hx-target=\"#reports-list\">closed</button>\
-            <button type=\"button\" class=\"filter-btn{}\" hx-get=\"/admin/reports-html?status=all\" hx-target=\"#reports-list\">all</button>\
+            <button type=\"button\" class=\"filter-btn{}\" hx-get=\"/admin/reports-html?status=open{}\" hx-target=\"#reports-list\">open</button>\
+            <button type=\"button\" class=\"filter-btn{}\" hx-get=\"/admin/reports-html?status=escalated{}\" hx-target=\"#reports-list\">escalated</button>\
+            <button type=\"button\" class=\"filter-btn{}\" hx-get=\"/admin/reports-html?status=resolved{}\" hx-target=\"#reports-list\">closed</button>\
+            <button type=\"button\" class=\"filter-btn{}\" hx-get=\"/admin/reports-html?status=all{}\" hx-target=\"#reports-list\">all</button>\
+            <form class=\"flag-search\" hx-get=\"/admin/reports-html\" hx-target=\"#reports-list\">\
+                <input type=\"hidden\" name=\"status\" value=\"{}\">\
+                <input type=\"search\" name=\"target_id\" value=\"{}\" placeholder=\"target id\">\
+                <input type=\"search\" name=\"target_uri\" value=\"{}\" placeholder=\"target at:// uri\">\
+                <input type=\"search\" name=\"q\" value=\"{}\" placeholder=\"description or notes\">\
+                <button type=\"submit\" class=\"filter-btn\">search</button>\
+            </form>\
+            {}\
             <span class=\"filter-count\">{}</span>\
         </div>",
-        open_active, resolved_active, all_active, count_label,
+        open_active,
+        search_params,
+        escalated_active,
+        search_params,
+        resolved_active,
+        search_params,
+        all_active,
+        search_params,
+        html_escape(current_filter),
+        html_escape(params.target_id.as_deref().unwrap_or_default()),
+        html_escape(params.target_uri.as_deref().unwrap_or_default()),
+        html_escape(params.q.as_deref().unwrap_or_default()),
+        bulk_resolve,
+        count_label,
     );
+    let filter_buttons = format!("{}{}", render_report_summary(stats), filter_buttons);
 
-    if reports.is_empty() {
+    if page.reports.is_empty() {
         let empty_msg = match current_filter {
+            _ if params.is_search() => "no reports match this search",
             "open" => "no open reports",
+            "escalated" => "no escalated reports",
             "resolved" | "dismissed" => "no closed reports",
             _ => "no reports",
         };
@@ -340,18 +1692,45 @@ fn render_reports_list(reports: &[UserReport], current_filter: &str) -> String {
         );
     }
 
-    let cards: Vec<String> = reports.iter().map(render_report_card).collect();
-    format!("{}\n{}", filter_buttons, cards.join("\n"))
+    format!("{}\n{}", filter_buttons, render_reports_page(page, params))
+}
+
+/// Render one page of report cards, followed by a "load more" button that
+/// swaps itself for the next page.
+fn render_reports_page(page: &ReportsPage, params: &ListReportsHtmlParams) -> String {
+    let cards: Vec<String> = page.reports.iter().map(render_report_card).collect();
+    let load_more = page
+        .next_cursor
+        .as_ref()
+        .map(|cursor| {
+            format!(
+                r#"<button type="button" class="btn btn-secondary load-more" hx-get="/admin/reports-html?status={}&amp;cursor={}{}" hx-target="this" hx-swap="outerHTML">load more</button>"#,
+                html_escape(&url_encode(&params.status)),
+                html_escape(&url_encode(cursor)),
+                params.search_params()
+            )
+        })
+        .unwrap_or_default();
+    format!("{}\n{}", cards.join("\n"), load_more)
 }
 
 /// Render a single report card as HTML.
-fn render_report_card(report: &UserReport) -> String {
+pub(crate) fn render_report_card(report: &UserReport) -> String {
     let is_closed = report.status == "resolved" || report.status == "dismissed";
     let resolved_class = if is_closed { " resolved" } else { "" };
+    let select_box = if is_closed {
+        String::new()
+    } else {
+        format!(
+            r#"<input type="checkbox" class="report-select" value="{}" onchange="updateReportBulkSelection()">"#,
+            report.id
+        )
+    };
 
     // Status badge
     let status_badge = match report.status.as_str() {
         "open" => r#"<span class="badge pending">open</span>"#,
+        "escalated" => r#"<span class="badge escalated">escalated</span>"#,
         "investigating" => r#"<span class="badge investigating">investigating</span>"#,
         "resolved" => r#"<span class="badge resolved">resolved</span>"#,
         "dismissed" => r#"<span class="badge dismissed">dismissed</span>"#,
@@ -365,6 +1744,17 @@ fn render_report_card(report: &UserReport) -> String {
         html_escape(&report.reason)
     );
 
+    // Reporter count, when others filed the same report
+    let supporters_badge = if report.supporter_count > 0 {
+        format!(
+            r#"<span class="badge supporters" title="{} more reporters filed the same report">{} reporters</span>"#,
+            report.supporter_count,
+            report.supporter_count + 1
+        )
+    } else {
+        String::new()
+    };
+
     // Target type badge
     let target_badge = format!(
         r#"<span class="badge target">{}</span>"#,
@@ -395,17 +1785,18 @@ fn render_report_card(report: &UserReport) -> String {
         })
         .unwrap_or_default();
 
-    // Screenshot link (if any)
-    let screenshot_html = report
-        .screenshot_url
-        .as_ref()
-        .map(|url| {
-            format!(
-                r#"<a href="{}" target="_blank" rel="noopener" class="screenshot-link">view screenshot</a>"#,
-                html_escape(url)
-            )
-        })
-        .unwrap_or_default();
+    // Screenshot link (if any); uploads go through the signed-link redirect
+    let screenshot_html = match (&report.screenshot_key, &report.screenshot_url) {
+        (Some(_), _) => format!(
+            r#"<a href="/admin/reports/{}/screenshot" target="_blank" rel="noopener" class="screenshot-link">view screenshot</a>"#,
+            report.id
+        ),
+        (None, Some(url)) => format!(
+            r#"<a href="{}" target="_blank" rel="noopener" class="screenshot-link">view screenshot</a>"#,
+            html_escape(url)
+        ),
+        (None, None) => String::new(),
+    };
 
     // Action buttons for open reports
     let action_html = if is_closed {
@@ -413,23 +1804,42 @@ fn render_report_card(report: &UserReport) -> String {
             .resolved_by
             .as_deref()
             .unwrap_or("unknown");
+        let label_html = report
+            .label_seq
+            .map(|seq| format!(r#"<span class="resolution-label">label seq {}</span>"#, seq))
+            .unwrap_or_default();
         format!(
             r#"<div class="resolution-info">
                 <span class="resolution-reason">{} by {}</span>
                 {}
+                {}
             </div>"#,
             html_escape(&report.status),
             html_escape(resolved_by),
+            label_html,
             admin_notes_html
         )
     } else {
+        // the take-action flow offers "emit label" only for reports with an AT-URI
+        let label_attrs = if report
+            .target_uri
+            .as_deref()
+            .is_some_and(|uri| uri.starts_with("at://"))
+        {
+            format!(
+                r#" data-can-label="true" data-default-label="{}""#,
+                default_label(&report.reason).unwrap_or_default()
+            )
+        } else {
+            String::new()
+        };
         format!(
-            r#"<div class="report-actions-flow" data-id="{}">
+            r#"<div class="report-actions-flow" data-id="{}"{}>
                 <button type="button" class="btn btn-secondary" onclick="showReportActions(this)">
                     take action
                 </button>
             </div>"#,
-            report.id
+            report.id, label_attrs
         )
     };
 
@@ -448,19 +1858,25 @@ fn render_report_card(report: &UserReport) -> String {
         _ => html_escape(&report.target_id),
     };
 
-    // Reporter display - link to profile if handle available, otherwise show truncated DID
+    // Reporter display - link to profile if handle available, otherwise show
+    // truncated DID or hash prefix
     let reporter_display = match &report.reporter_handle {
         Some(handle) => format!(
             r#"<a href="https://plyr.fm/u/{}" target="_blank" rel="noopener" class="reporter-link">@{}</a>"#,
             html_escape(handle),
             html_escape(handle)
         ),
+        None if is_hashed(&report.reporter_did) => format!(
+            r#"<code title="reporter stored as a hash">{}</code>"#,
+            html_escape(display_reporter(&report.reporter_did))
+        ),
         None => format!("<code>{}</code>", truncate_did(&report.reporter_did)),
     };
 
     format!(
         r#"<div class="report-card{}">
             <div class="report-header">
+                {}
                 <div class="report-info">
                     <div class="report-target">
                         <strong>{}</strong>: {}
@@ -468,6 +1884,7 @@ fn render_report_card(report: &UserReport) -> String {
                     <div class="report-meta">
                         reported by {} · {}
                     </div>
+                    <div class="report-meta reporter-history" hx-get="/admin/reporters/{}/summary-html" hx-trigger="revealed" hx-swap="innerHTML"></div>
                     {}
                     {}
                 </div>
@@ -475,6 +1892,7 @@ fn render_report_card(report: &UserReport) -> String {
                     {}
                     {}
                     {}
+                    {}
                 </div>
             </div>
             <div class="report-actions">
@@ -482,12 +1900,15 @@ fn render_report_card(report: &UserReport) -> String {
             </div>
         </div>"#,
         resolved_class,
+        select_box,
         html_escape(&report.target_type),
         target_display,
         reporter_display,
         created_at,
+        url_encode(&report.reporter_did),
         description_html,
         screenshot_html,
+        supporters_badge,
         target_badge,
         reason_badge,
         status_badge,
@@ -505,11 +1926,654 @@ fn truncate_did(did: &str) -> String {
     format!("{}…{}", html_escape(prefix), html_escape(suffix))
 }
 
-/// Simple HTML escaping.
-fn html_escape(s: &str) -> String {
-    s.replace('&', "&amp;")
-        .replace('<', "&lt;")
-        .replace('>', "&gt;")
-        .replace('"', "&quot;")
-        .replace('\'', "&#039;")
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, extract::FromRequest, http::Request};
+
+    async fn post_form(body: &str) -> ResolveReportForm {
+        let request = Request::builder()
+            .method("POST")
+            .uri("/admin/reports/7/resolve-htmx")
+            .header(CONTENT_TYPE, "application/x-www-form-urlencoded")
+            .body(Body::from(body.to_string()))
+            .unwrap();
+        let Form(form) = Form::<ResolveReportForm>::from_request(request, &())
+            .await
+            .unwrap();
+        form
+    }
+
+    fn report(status: &str) -> UserReport {
+        UserReport {
+            id: 7,
+            reporter_did: "did:plc:reporter".to_string(),
+            reporter_handle: None,
+            target_type: "track".to_string(),
+            target_id: "42".to_string(),
+            target_name: None,
+            target_url: None,
+            target_uri: None,
+            reason: "spam".to_string(),
+            description: None,
+            screenshot_url: None,
+            screenshot_key: None,
+            status: status.to_string(),
+            admin_notes: None,
+            resolved_by: Some("alice".to_string()),
+            created_at: chrono::Utc::now(),
+            updated_at: None,
+            resolved_at: None,
+            label_seq: None,
+            supporter_count: 0,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_resolve_form_parses_status_and_notes() {
+        let form = post_form("status=dismissed&notes=++duplicate+of+%236++").await;
+        assert_eq!(form.status, "dismissed");
+        assert_eq!(form.notes(), Some("duplicate of #6"));
+        assert!(validate_report_status(&form.status).is_ok());
+
+        // the UI always sends the notes field, possibly empty
+        let form = post_form("status=resolved&notes=").await;
+        assert_eq!(form.notes(), None);
+        let form = post_form("status=resolved").await;
+        assert_eq!(form.notes(), None);
+    }
+
+    #[tokio::test]
+    async fn test_resolve_form_rejects_unknown_status() {
+        let form = post_form("status=deleted&notes=x").await;
+        let (status, message) = validate_report_status(&form.status).unwrap_err();
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert!(message.contains("deleted"));
+    }
+
+    const REPORT_FIELDS: [(&str, &str); 4] = [
+        ("reporter_did", "did:plc:reporter"),
+        ("target_type", "track"),
+        ("target_id", "42"),
+        ("reason", "spam"),
+    ];
+
+    const PNG: &[u8] = &[0x89, b'P', b'N', b'G', 0x0d, 0x0a, 0x1a, 0x0a, 0];
+
+    /// A multipart `/reports` request with text `fields` and, if given, a
+    /// screenshot part.
+    fn multipart_request(fields: &[(&str, &str)], screenshot: Option<&[u8]>) -> Request<Body> {
+        let boundary = "report-boundary";
+        let mut body = Vec::new();
+        for (name, value) in fields {
+            body.extend_from_slice(
+                format!(
+                    "--{boundary}\r\nContent-Disposition: form-data; name=\"{name}\"\r\n\r\n{value}\r\n"
+                )
+                .as_bytes(),
+            );
+        }
+        if let Some(bytes) = screenshot {
+            body.extend_from_slice(
+                format!(
+                    "--{boundary}\r\nContent-Disposition: form-data; name=\"screenshot\"; filename=\"shot.png\"\r\n\r\n"
+                )
+                .as_bytes(),
+            );
+            body.extend_from_slice(bytes);
+            body.extend_from_slice(b"\r\n");
+        }
+        body.extend_from_slice(format!("--{boundary}--\r\n").as_bytes());
+        Request::builder()
+            .method("POST")
+            .uri("/reports")
+            .header(
+                CONTENT_TYPE,
+                format!("multipart/form-data; boundary={boundary}"),
+            )
+            .body(Body::from(body))
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_report_submission_accepts_json_and_multipart() {
+        let request = Request::builder()
+            .method("POST")
+            .uri("/reports")
+            .header(CONTENT_TYPE, "application/json")
+            .body(Body::from(
+                r#"{"reporter_did":"did:plc:reporter","target_type":"track","target_id":"42","reason":"spam"}"#,
+            ))
+            .unwrap();
+        let submission = ReportSubmission::from_request(request, &()).await.unwrap();
+        assert_eq!(submission.report.target_id, "42");
+        assert!(submission.screenshot.is_none());
+
+        let mut fields = REPORT_FIELDS.to_vec();
+        fields.push(("description", "sounds like my song"));
+        let submission = ReportSubmission::from_request(multipart_request(&fields, Some(PNG)), &())
+            .await
+            .unwrap();
+        assert_eq!(submission.report.reason, "spam");
+        assert_eq!(
+            submission.report.description.as_deref(),
+            Some("sounds like my song")
+        );
+        let screenshot = submission.screenshot.unwrap();
+        assert_eq!(screenshot.media_type, "image/png");
+        assert_eq!(screenshot.bytes, PNG);
+
+        // a form sent without choosing a file
+        let request = multipart_request(&REPORT_FIELDS, Some(b"".as_slice()));
+        let submission = ReportSubmission::from_request(request, &()).await.unwrap();
+        assert!(submission.screenshot.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_report_submission_rejects_bad_screenshots() {
+        let status = |request| async move {
+            ReportSubmission::from_request(request, &())
+                .await
+                .unwrap_err()
+                .status()
+        };
+        let not_an_image = multipart_request(&REPORT_FIELDS, Some(b"<html>".as_slice()));
+        assert_eq!(status(not_an_image).await, StatusCode::BAD_REQUEST);
+
+        let mut fields = REPORT_FIELDS.to_vec();
+        fields.push(("screenshot_url", "https://example.com/shot.png"));
+        assert_eq!(
+            status(multipart_request(&fields, Some(PNG))).await,
+            StatusCode::BAD_REQUEST
+        );
+
+        // reason missing
+        assert_eq!(
+            status(multipart_request(&REPORT_FIELDS[..3], None)).await,
+            StatusCode::BAD_REQUEST
+        );
+    }
+
+    #[test]
+    fn test_screenshot_keys_are_random() {
+        let key = screenshot_key("image/jpeg");
+        assert!(key.starts_with("report-screenshots/"));
+        assert!(key.ends_with(".jpeg"));
+        assert_ne!(key, screenshot_key("image/jpeg"));
+    }
+
+    #[test]
+    fn test_report_stats_rollup_and_summary() {
+        let count = |reason: &str, status: &str, count| ReportCount {
+            reason: reason.to_string(),
+            status: status.to_string(),
+            count,
+        };
+        let stats = ReportStats::from_counts(&[
+            count("copyright", "open", 2),
+            count("copyright", "resolved", 5),
+            count("spam", "open", 4),
+            count("spam", "dismissed", 1),
+        ]);
+        assert_eq!(stats.by_reason["copyright"], 7);
+        assert_eq!(stats.by_status["open"], 6);
+        assert_eq!(stats.open_by_reason.get("spam"), Some(&4));
+        assert_eq!(stats.open_by_reason.len(), 2);
+
+        let html = render_report_summary(&stats);
+        // most common open reason first
+        let spam = html.find("spam <strong>4</strong>").unwrap();
+        let copyright = html.find("copyright <strong>2</strong>").unwrap();
+        assert!(spam < copyright);
+        assert!(html.contains("open 6 · escalated 0 · investigating 0 · resolved 5 · dismissed 1"));
+
+        let page = ReportsPage {
+            reports: Vec::new(),
+            next_cursor: None,
+        };
+        let html = render_reports_list(&page, &html_params("open"), &stats);
+        assert!(html.starts_with("<div class=\"report-summary\">"));
+        assert!(html.contains("no open reports"));
+    }
+
+    #[test]
+    fn test_report_stats_window() {
+        let day = |d| NaiveDate::from_ymd_opt(2026, 1, d).unwrap();
+        let window = |from, to| report_stats_window(&ReportStatsParams { from, to }, day(20));
+
+        assert_eq!(window(None, None), Ok((day(14), day(20))));
+        assert_eq!(window(None, Some(day(10))), Ok((day(4), day(10))));
+        assert_eq!(window(Some(day(1)), None), Ok((day(1), day(20))));
+        assert_eq!(window(Some(day(5)), Some(day(5))), Ok((day(5), day(5))));
+        assert!(window(Some(day(6)), Some(day(5))).is_err());
+
+        let year_ago = NaiveDate::from_ymd_opt(2025, 1, 19).unwrap();
+        assert!(window(Some(year_ago), None).is_err());
+        assert!(window(Some(year_ago + Days::new(1)), None).is_ok());
+    }
+
+    #[test]
+    fn test_report_summary_shows_window() {
+        let mut stats = ReportStats::from_counts(&[]);
+        stats.window = Some(ReportWindowStats {
+            from: NaiveDate::from_ymd_opt(2026, 1, 14).unwrap(),
+            to: NaiveDate::from_ymd_opt(2026, 1, 20).unwrap(),
+            filed: 12,
+            closed: 8,
+            dismissed: 2,
+            dismissal_rate: Some(0.25),
+            median_resolution_secs: Some(3.0 * 3600.0),
+            ..Default::default()
+        });
+        let html = render_report_summary(&stats);
+        assert!(html.contains("7 days: 12 filed · 8 closed · median 3h to close · 25% dismissed"));
+
+        stats.window = Some(ReportWindowStats::default());
+        let html = render_report_summary(&stats);
+        assert!(html.contains("median — to close · — dismissed"));
+
+        assert_eq!(format_duration_secs(90.0), "2m");
+        assert_eq!(format_duration_secs(47.0 * 3600.0), "47h");
+        assert_eq!(format_duration_secs(3.0 * 86400.0), "3.0d");
+    }
+
+    #[test]
+    fn test_reports_page_loads_more_with_cursor() {
+        let stats = ReportStats::from_counts(&[ReportCount {
+            reason: "spam".to_string(),
+            status: "open".to_string(),
+            count: 120,
+        }]);
+        let page = ReportsPage {
+            reports: vec![report("open")],
+            next_cursor: Some("1760600000123456:7".to_string()),
+        };
+        let html = render_reports_list(&page, &html_params("open"), &stats);
+        // the count covers the whole filter, not just this page
+        assert!(html.contains("120 open"));
+        let load_more = r#"hx-get="/admin/reports-html?status=open&amp;cursor=1760600000123456%3A7""#;
+        assert!(html.contains(load_more));
+        // the filter and cursor are echoed from the request, so both are
+        // encoded for the URL and escaped for the attribute
+        let html = render_reports_page(&page, &html_params("open\" hx-on=\"x&y"));
+        assert!(html.contains("status=open%22%20hx-on%3D%22x%26y&amp;cursor="));
+
+        let last = ReportsPage {
+            next_cursor: None,
+            ..page
+        };
+        let html = render_reports_page(&last, &html_params("open"));
+        assert!(html.contains("report-card"));
+        assert!(!html.contains("load more"));
+    }
+
+    fn html_params(status: &str) -> ListReportsHtmlParams {
+        ListReportsHtmlParams {
+            status: status.to_string(),
+            cursor: None,
+            target_id: None,
+            target_uri: None,
+            q: None,
+        }
+    }
+
+    #[test]
+    fn test_reports_search_is_kept_across_filters_and_pages() {
+        let params = ListReportsHtmlParams {
+            target_id: Some("4821".to_string()),
+            target_uri: Some("  ".to_string()),
+            q: Some("rip & tear".to_string()),
+            ..html_params("all")
+        };
+        let filter = params.filter();
+        assert_eq!(filter.status, None);
+        assert_eq!(filter.target_id, Some("4821"));
+        assert_eq!(filter.target_uri, None);
+        assert_eq!(filter.q, Some("rip & tear"));
+
+        let stats = ReportStats::from_counts(&[ReportCount {
+            reason: "spam".to_string(),
+            status: "open".to_string(),
+            count: 120,
+        }]);
+        let page = ReportsPage {
+            reports: vec![report("open")],
+            next_cursor: Some("1760600000123456:7".to_string()),
+        };
+        let html = render_reports_list(&page, &params, &stats);
+        let search = "&amp;target_id=4821&amp;q=rip%20%26%20tear";
+        let open_button = format!(r#"hx-get="/admin/reports-html?status=open{search}""#);
+        assert!(html.contains(&open_button));
+        assert!(html.contains(&format!("cursor=1760600000123456%3A7{search}\"")));
+        assert!(html.contains(r#"name="q" value="rip &amp; tear""#));
+        // stats don't cover the search, so only what's loaded is counted;
+        // the summary strip above still shows the overall numbers
+        assert!(html.contains("1+ matching"));
+        assert!(!html.contains("120 total"));
+
+        let empty = ReportsPage {
+            reports: Vec::new(),
+            next_cursor: None,
+        };
+        let html = render_reports_list(&empty, &params, &stats);
+        assert!(html.contains("no reports match this search"));
+    }
+
+    #[test]
+    fn test_report_resolved_toast() {
+        let html = report_resolved_toast(&report("dismissed"));
+        assert!(html.contains(r#"id="toast""#));
+        assert!(html.contains(r#"hx-swap-oob="true""#));
+        assert!(html.contains("report 7: dismissed"));
+    }
+
+    #[test]
+    fn test_report_card_shows_supporters() {
+        let mut report = report("open");
+        assert!(!render_report_card(&report).contains("badge supporters"));
+
+        report.supporter_count = 2;
+        let html = render_report_card(&report);
+        assert!(html.contains(r#"<span class="badge supporters""#));
+        assert!(html.contains(">3 reporters</span>"));
+    }
+
+    #[test]
+    fn test_escalation_threshold() {
+        let escalation = ReportEscalation {
+            threshold: 5,
+            create_batch: true,
+        };
+        let mut report = report("open");
+        report.supporter_count = 3;
+        assert!(!escalation.should_escalate(&report));
+        report.supporter_count = 4;
+        assert!(escalation.should_escalate(&report));
+
+        // already escalated, or someone's on it
+        report.status = "escalated".to_string();
+        assert!(!escalation.should_escalate(&report));
+        assert!(render_report_card(&report).contains(r#"<span class="badge escalated">"#));
+        report.status = "investigating".to_string();
+        assert!(!escalation.should_escalate(&report));
+
+        // batches are only for copyright reports about an AT-URI
+        assert_eq!(escalation.batch_uri(&report), None);
+        report.reason = "copyright".to_string();
+        assert_eq!(escalation.batch_uri(&report), None);
+        report.target_uri = Some("at://did:plc:artist/fm.plyr.track/abc".to_string());
+        assert_eq!(
+            escalation.batch_uri(&report),
+            Some("at://did:plc:artist/fm.plyr.track/abc")
+        );
+        let escalation = ReportEscalation {
+            create_batch: false,
+            ..escalation
+        };
+        assert_eq!(escalation.batch_uri(&report), None);
+    }
+
+    #[test]
+    fn test_report_label_value() {
+        let mut report = report("open");
+        report.reason = "copyright".to_string();
+        assert_eq!(
+            report_label_value(&report, None).unwrap(),
+            "copyright-violation"
+        );
+        assert_eq!(
+            report_label_value(&report, Some("copyright-review")).unwrap(),
+            "copyright-review"
+        );
+        assert!(matches!(
+            report_label_value(&report, Some("made-up")),
+            Err(AppError::BadRequest(_))
+        ));
+
+        report.reason = "explicit".to_string();
+        assert_eq!(
+            report_label_value(&report, Some(" ")).unwrap(),
+            "sensitive-image"
+        );
+
+        // no obvious label for spam; the admin has to pick one
+        report.reason = "spam".to_string();
+        assert!(report_label_value(&report, None).is_err());
+        assert_eq!(report_label_value(&report, Some("porn")).unwrap(), "porn");
+    }
+
+    #[test]
+    fn test_report_card_offers_label_only_with_uri() {
+        let mut report = report("open");
+        report.reason = "copyright".to_string();
+        assert!(!render_report_card(&report).contains("data-can-label"));
+
+        report.target_uri = Some("at://did:plc:artist/fm.plyr.track/1".to_string());
+        let html = render_report_card(&report);
+        assert!(html.contains(r#"data-can-label="true" data-default-label="copyright-violation""#));
+
+        report.status = "resolved".to_string();
+        report.label_seq = Some(42);
+        assert!(render_report_card(&report).contains("label seq 42"));
+    }
+
+    #[test]
+    fn test_report_card_selectable_only_while_open() {
+        assert!(render_report_card(&report("open"))
+            .contains(r#"<input type="checkbox" class="report-select" value="7""#));
+        assert!(render_report_card(&report("escalated")).contains("report-select"));
+        assert!(!render_report_card(&report("dismissed")).contains("report-select"));
+    }
+
+    #[test]
+    fn test_reporter_history() {
+        let count = |reason: &str, status: &str, count| ReportCount {
+            reason: reason.to_string(),
+            status: status.to_string(),
+            count,
+        };
+        let history = ReporterHistory::new(
+            "did:plc:reporter".to_string(),
+            ReporterReports {
+                counts: vec![
+                    count("copyright", "dismissed", 6),
+                    count("copyright", "open", 2),
+                    count("spam", "dismissed", 3),
+                    count("spam", "resolved", 1),
+                ],
+                supported: 4,
+                recent: Vec::new(),
+            },
+        );
+        assert_eq!(history.total, 12);
+        assert_eq!(history.by_reason["copyright"], 8);
+        assert_eq!(history.by_status["dismissed"], 9);
+        // open reports haven't been judged yet
+        assert_eq!(history.dismissal_rate, Some(0.9));
+        assert_eq!(
+            render_reporter_summary(&history),
+            "reporter: 12 reports, 9 dismissed, supported 4"
+        );
+
+        let first = ReporterHistory::new(
+            "did:plc:new".to_string(),
+            ReporterReports {
+                counts: vec![count("spam", "open", 1)],
+                ..Default::default()
+            },
+        );
+        assert_eq!(first.dismissal_rate, None);
+        assert_eq!(
+            render_reporter_summary(&first),
+            "reporter: 1 report, 0 dismissed"
+        );
+
+        // the card loads this lazily rather than the listing querying per card
+        assert!(render_report_card(&report("open")).contains(
+            r#"hx-get="/admin/reporters/did%3Aplc%3Areporter/summary-html" hx-trigger="revealed""#
+        ));
+    }
+
+    fn bulk_request(ids: Vec<i32>, status: &str) -> BulkResolveReportsRequest {
+        BulkResolveReportsRequest {
+            ids,
+            status: status.to_string(),
+            admin_notes: None,
+            resolved_by: None,
+        }
+    }
+
+    #[test]
+    fn test_validate_bulk_resolve() {
+        assert_eq!(
+            validate_bulk_resolve(&bulk_request(vec![3, 1, 3, 2, 1], "resolved")).unwrap(),
+            vec![3, 1, 2]
+        );
+        assert!(validate_bulk_resolve(&bulk_request(vec![1], "dismissed")).is_ok());
+        assert!(validate_bulk_resolve(&bulk_request(vec![], "resolved")).is_err());
+        // moving reports back into the queue isn't a bulk action
+        assert!(validate_bulk_resolve(&bulk_request(vec![1], "investigating")).is_err());
+
+        let too_many = (1..=MAX_BULK_RESOLVE_REPORTS as i32 + 1).collect();
+        assert!(validate_bulk_resolve(&bulk_request(too_many, "resolved")).is_err());
+    }
+
+    #[test]
+    fn test_bulk_report_outcomes_mixed_open_and_closed() {
+        let outcomes = bulk_report_outcomes(
+            &[5, 6, 7],
+            &[(6, "dismissed".to_string()), (7, "resolved".to_string())],
+        );
+        assert_eq!(
+            outcomes,
+            vec![
+                BulkReportOutcome {
+                    id: 5,
+                    resolved: true,
+                    skipped: None,
+                },
+                BulkReportOutcome {
+                    id: 6,
+                    resolved: false,
+                    skipped: Some("already dismissed".to_string()),
+                },
+                BulkReportOutcome {
+                    id: 7,
+                    resolved: false,
+                    skipped: Some("already resolved".to_string()),
+                },
+            ]
+        );
+    }
+
+    fn create_request() -> CreateReportRequest {
+        CreateReportRequest {
+            reporter_did: "did:plc:reporter".to_string(),
+            reporter_handle: None,
+            target_type: "track".to_string(),
+            target_id: "42".to_string(),
+            target_name: None,
+            target_url: None,
+            target_uri: None,
+            reason: "spam".to_string(),
+            description: None,
+            screenshot_url: None,
+        }
+    }
+
+    #[test]
+    fn test_validate_report() {
+        assert!(validate_report(&create_request()).is_ok());
+
+        for did in [
+            "plc:reporter",
+            "did:",
+            "did:x",
+            &format!("did:plc:{}", "a".repeat(2048)),
+        ] {
+            let mut req = create_request();
+            req.reporter_did = did.to_string();
+            assert!(validate_report(&req).is_err(), "{did}");
+        }
+
+        let mut req = create_request();
+        req.description = Some("é".repeat(MAX_DESCRIPTION_CHARS));
+        assert!(validate_report(&req).is_ok());
+        req.description = Some("é".repeat(MAX_DESCRIPTION_CHARS + 1));
+        assert!(validate_report(&req)
+            .unwrap_err()
+            .contains("description is longer than 2000"));
+
+        let mut req = create_request();
+        req.screenshot_url = Some(format!(
+            "https://x/{}",
+            "a".repeat(MAX_SCREENSHOT_URL_CHARS)
+        ));
+        assert!(validate_report(&req)
+            .unwrap_err()
+            .contains("screenshot_url is longer than 512"));
+    }
+
+    #[test]
+    fn test_check_report_reason() {
+        let reason = |key: &str, requires_target_uri, active| ReportReasonRow {
+            key: key.to_string(),
+            label: key.to_string(),
+            description: String::new(),
+            requires_target_uri,
+            active,
+            created_at: Utc::now(),
+        };
+        let mut req = create_request();
+        req.reason = "impersonation".to_string();
+
+        let active = reason("impersonation", false, true);
+        assert!(check_report_reason(&req, Some(&active)).is_ok());
+        assert!(check_report_reason(&req, None)
+            .unwrap_err()
+            .contains("invalid reason: impersonation"));
+        let retired = reason("impersonation", false, false);
+        assert!(check_report_reason(&req, Some(&retired))
+            .unwrap_err()
+            .contains("no longer accepted"));
+
+        let tracklist_fraud = reason("tracklist_fraud", true, true);
+        assert!(check_report_reason(&req, Some(&tracklist_fraud))
+            .unwrap_err()
+            .contains("requires a target_uri"));
+        req.target_uri = Some(String::new());
+        assert!(check_report_reason(&req, Some(&tracklist_fraud)).is_err());
+        req.target_uri = Some("at://did:plc:artist/fm.plyr.track/1".to_string());
+        assert!(check_report_reason(&req, Some(&tracklist_fraud)).is_ok());
+    }
+
+    #[test]
+    fn test_report_limit_boundary() {
+        let limits = ReportLimits {
+            per_hour: 10,
+            per_day: 50,
+        };
+        let now = Utc::now();
+        let activity = |last_hour: i64, last_day: i64| ReporterActivity {
+            last_hour,
+            last_day,
+            oldest_last_hour: Some(now - chrono::Duration::minutes(20)),
+            oldest_last_day: Some(now - chrono::Duration::hours(6)),
+        };
+
+        assert_eq!(limits.retry_after(&activity(9, 49), now), None);
+        assert_eq!(
+            limits.retry_after(&activity(10, 10), now),
+            Some(Duration::from_secs(40 * 60))
+        );
+        assert_eq!(
+            limits.retry_after(&activity(3, 50), now),
+            Some(Duration::from_secs(18 * 3600))
+        );
+        // over both: wait for the later window
+        assert_eq!(
+            limits.retry_after(&activity(10, 50), now),
+            Some(Duration::from_secs(18 * 3600))
+        );
+    }
 }
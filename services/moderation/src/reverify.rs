@@ -0,0 +1,403 @@
+//! Re-verifying sensitive images against the current moderation prompt.
+//!
+//! The prompt changes over time, and images Claude flagged under an older one
+//! may no longer count as violations. `POST /admin/sensitive-images/reverify`
+//! starts a background run that re-scans each Claude-flagged sensitive image
+//! (manual entries are left alone), one every few seconds, and removes the
+//! ones now judged safe. An admin override marking the image unsafe keeps it
+//! flagged.
+//!
+//! Progress is saved in `image_reverify_runs` after every image. A run
+//! interrupted by a restart stops making progress, and triggering the
+//! endpoint again resumes it where it left off. `GET` on the same path
+//! reports the latest run.
+
+use std::future::Future;
+use std::time::Duration;
+
+use axum::{extract::State, Json};
+use serde::Serialize;
+use tracing::{info, warn};
+
+use crate::auth::{Actor, RequireAdmin};
+use crate::claude::{estimate_cost_usd, ClaudeClient, ClaudeError, ImageAnalysis, ModerationResult};
+use crate::db::{
+    LabelDb, NewImageScan, OverrideVerdict, ReverifyOutcome, ReverifyRunRow, SensitiveImageRow,
+};
+use crate::handlers::content_hash;
+use crate::image_fetch::{fetch_image, FetchedImage};
+use crate::state::{AppError, AppState};
+
+/// Pause between images, to keep the re-scan well under Claude's rate
+/// limits.
+const REVERIFY_INTERVAL: Duration = Duration::from_secs(2);
+
+/// An open run with no progress for this many minutes is assumed dead and
+/// can be resumed.
+const REVERIFY_STALE_MINUTES: i64 = 5;
+
+/// Response after starting or resuming a run.
+#[derive(Debug, Serialize)]
+pub struct ReverifyResponse {
+    #[serde(flatten)]
+    pub run: ReverifyRunRow,
+    /// Whether this request resumed an interrupted run.
+    pub resumed: bool,
+}
+
+/// Start re-verifying sensitive images, or resume an interrupted run.
+///
+/// Returns right away; the counts in the response grow as the run proceeds
+/// (see `GET /admin/sensitive-images/reverify`).
+pub async fn start_reverify(
+    State(state): State<AppState>,
+    _: RequireAdmin,
+    actor: Actor,
+) -> Result<Json<ReverifyResponse>, AppError> {
+    let db = state
+        .db
+        .as_ref()
+        .ok_or(AppError::ImageModerationNotConfigured)?;
+    if state.claude.is_none() {
+        return Err(AppError::ImageModerationNotConfigured);
+    }
+
+    let open = db
+        .get_latest_reverify_run()
+        .await?
+        .filter(|run| run.finished_at.is_none());
+    let (run, resumed) = match open {
+        Some(open) => {
+            let stale_after = chrono::Duration::minutes(REVERIFY_STALE_MINUTES);
+            let run = db
+                .resume_reverify_run(open.id, stale_after)
+                .await?
+                .ok_or_else(|| {
+                    AppError::Conflict(format!("re-verification run {} is in progress", open.id))
+                })?;
+            (run, true)
+        }
+        None => {
+            let run = db
+                .create_reverify_run(actor.as_str())
+                .await?
+                .ok_or_else(|| {
+                    AppError::Conflict("a re-verification run is already in progress".to_string())
+                })?;
+            (run, false)
+        }
+    };
+
+    db.record_audit_event(
+        if resumed {
+            "resume_image_reverify"
+        } else {
+            "start_image_reverify"
+        },
+        &run.id.to_string(),
+        actor.as_str(),
+        serde_json::json!({ "last_image_id": run.last_image_id }),
+    )
+    .await?;
+    info!(
+        run_id = run.id,
+        resumed,
+        last_image_id = run.last_image_id,
+        "sensitive image re-verification started"
+    );
+
+    tokio::spawn(run_reverify(state.clone(), run.clone(), actor.0));
+    Ok(Json(ReverifyResponse { run, resumed }))
+}
+
+/// The latest re-verification run, or null if there hasn't been one.
+pub async fn get_reverify_status(
+    State(state): State<AppState>,
+) -> Result<Json<Option<ReverifyRunRow>>, AppError> {
+    let db = state
+        .db
+        .as_ref()
+        .ok_or(AppError::ImageModerationNotConfigured)?;
+    Ok(Json(db.get_latest_reverify_run().await?))
+}
+
+/// Work through the run's remaining images, saving progress after each.
+///
+/// A database error stops the run without finishing it, so it can be
+/// resumed.
+async fn run_reverify(state: AppState, run: ReverifyRunRow, actor: String) {
+    let (Some(db), Some(claude)) = (state.db.clone(), state.claude.clone()) else {
+        return;
+    };
+
+    let mut last_image_id = run.last_image_id;
+    loop {
+        let image = match db.next_reverify_image(last_image_id).await {
+            Ok(Some(image)) => image,
+            Ok(None) => break,
+            Err(e) => {
+                warn!(run_id = run.id, error = %e, "re-verification stopped");
+                return;
+            }
+        };
+
+        let outcome = match reverify_image(
+            &db,
+            &claude,
+            &image,
+            run.id,
+            &actor,
+            state.image_base_url.as_deref(),
+        )
+        .await
+        {
+            Ok(outcome) => outcome,
+            Err(e) => {
+                warn!(
+                    run_id = run.id,
+                    sensitive_image_id = image.id,
+                    error = %e,
+                    "re-verification failed, keeping image flagged"
+                );
+                ReverifyOutcome::Failed
+            }
+        };
+        if let Err(e) = db.record_reverify_progress(run.id, image.id, outcome).await {
+            warn!(run_id = run.id, error = %e, "re-verification stopped");
+            return;
+        }
+        last_image_id = image.id;
+        tokio::time::sleep(REVERIFY_INTERVAL).await;
+    }
+
+    match db.finish_reverify_run(run.id).await {
+        Ok(run) => info!(
+            run_id = run.id,
+            checked = run.checked,
+            cleared = run.cleared,
+            failed = run.failed,
+            "sensitive image re-verification finished"
+        ),
+        Err(e) => warn!(run_id = run.id, error = %e, "failed to finish re-verification run"),
+    }
+}
+
+/// Re-scan one sensitive image, record the scan, and remove the image if
+/// it's now judged safe.
+async fn reverify_image(
+    db: &LabelDb,
+    claude: &ClaudeClient,
+    image: &SensitiveImageRow,
+    run_id: i64,
+    actor: &str,
+    image_base_url: Option<&str>,
+) -> Result<ReverifyOutcome, AppError> {
+    let location = image_location(image, image_base_url).ok_or_else(|| {
+        AppError::BadRequest("image has no url and MODERATION_IMAGE_BASE_URL isn't set".to_string())
+    })?;
+    let (analysis, hash) = rescan(claude, location.clone(), |url| async move {
+        fetch_image(&url).await
+    })
+    .await?;
+    let result = &analysis.result;
+    let scan_image_id = image.image_id.as_deref().unwrap_or(&location);
+
+    db.store_image_scan(&NewImageScan {
+        image_id: scan_image_id,
+        is_safe: result.is_safe,
+        violated_categories: &result.violated_categories,
+        severity: &result.severity,
+        explanation: &result.explanation,
+        model: &analysis.model,
+        source_url: Some(&location),
+        review_state: None,
+        input_tokens: analysis.usage.input_tokens,
+        output_tokens: analysis.usage.output_tokens,
+        cost_usd: estimate_cost_usd(
+            &analysis.model,
+            analysis.usage.input_tokens,
+            analysis.usage.output_tokens,
+        ),
+        content_hash: &hash,
+        second_opinion_of: None,
+        request_id: analysis.request_id.as_deref(),
+    })
+    .await?;
+
+    let override_verdict = db
+        .get_image_override(scan_image_id, &hash)
+        .await?
+        .and_then(|o| o.verdict());
+    if !clears(result, override_verdict) {
+        return Ok(ReverifyOutcome::Kept);
+    }
+
+    db.remove_sensitive_image(image.id).await?;
+    db.record_audit_event(
+        "reverify_clear_image",
+        scan_image_id,
+        actor,
+        serde_json::json!({
+            "run_id": run_id,
+            "sensitive_image_id": image.id,
+            "previous_reason": image.reason,
+            "previous_severity": image.severity,
+            "explanation": result.explanation,
+            "model": analysis.model,
+        }),
+    )
+    .await?;
+    info!(
+        run_id,
+        sensitive_image_id = image.id,
+        image_id = %scan_image_id,
+        "re-verification cleared sensitive image"
+    );
+    Ok(ReverifyOutcome::Cleared)
+}
+
+/// Where to fetch a sensitive image: its URL, or its R2 id under
+/// `image_base_url`.
+fn image_location(image: &SensitiveImageRow, image_base_url: Option<&str>) -> Option<String> {
+    image.url.clone().or_else(|| {
+        image
+            .image_id
+            .as_ref()
+            .zip(image_base_url)
+            .map(|(id, base)| format!("{}/{}", base, id))
+    })
+}
+
+/// Fetch an image and analyze it with the current prompt, returning the
+/// analysis and the image's content hash.
+///
+/// A refusal counts as an unsafe verdict, so the image stays flagged.
+async fn rescan<F, Fut>(
+    claude: &ClaudeClient,
+    location: String,
+    fetch: F,
+) -> Result<(ImageAnalysis, String), AppError>
+where
+    F: FnOnce(String) -> Fut,
+    Fut: Future<Output = Result<FetchedImage, AppError>>,
+{
+    let image = fetch(location).await?;
+    let analysis = match claude.analyze_image(&image.bytes, image.media_type).await {
+        Ok(analysis) => analysis,
+        Err(ClaudeError::Refusal {
+            model,
+            usage,
+            request_id,
+        }) => ImageAnalysis {
+            result: ModerationResult::refused(true),
+            usage,
+            model,
+            request_id,
+        },
+        Err(e) => return Err(e.into()),
+    };
+    Ok((analysis, content_hash(&image.bytes)))
+}
+
+/// Whether a re-scan result clears the image. A human override marking it
+/// unsafe outranks the model.
+fn clears(result: &ModerationResult, override_verdict: Option<OverrideVerdict>) -> bool {
+    result.is_safe && override_verdict != Some(OverrideVerdict::Unsafe)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A stand-in Messages API that always answers with `stop_reason` and
+    /// `verdict` (as the text block).
+    async fn mock_claude(stop_reason: &'static str, verdict: serde_json::Value) -> ClaudeClient {
+        let app = axum::Router::new().route(
+            "/v1/messages",
+            axum::routing::post(move || {
+                let verdict = verdict.clone();
+                async move {
+                    Json(serde_json::json!({
+                        "model": "mock-model",
+                        "content": [{ "type": "text", "text": verdict.to_string() }],
+                        "stop_reason": stop_reason,
+                        "usage": { "input_tokens": 100, "output_tokens": 20 },
+                    }))
+                }
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/v1/messages", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await });
+        ClaudeClient::new("key".to_string(), None).with_api_url(url)
+    }
+
+    fn verdict(is_safe: bool, severity: &str) -> serde_json::Value {
+        serde_json::json!({
+            "is_safe": is_safe,
+            "violated_categories": if is_safe { vec![] } else { vec!["explicit"] },
+            "severity": severity,
+            "explanation": "test verdict",
+        })
+    }
+
+    async fn fetch(url: String) -> Result<FetchedImage, AppError> {
+        assert_eq!(url, "https://cdn.example/abc.png");
+        Ok(FetchedImage {
+            bytes: b"\x89PNG not really".to_vec(),
+            media_type: "image/png",
+        })
+    }
+
+    #[tokio::test]
+    async fn test_rescan_clears_images_now_judged_safe() {
+        let location = "https://cdn.example/abc.png".to_string();
+
+        let claude = mock_claude("end_turn", verdict(true, "safe")).await;
+        let (analysis, hash) = rescan(&claude, location.clone(), fetch).await.unwrap();
+        assert!(analysis.result.is_safe);
+        assert_eq!(analysis.model, "mock-model");
+        assert_eq!(analysis.usage.input_tokens, 100);
+        assert_eq!(hash, content_hash(b"\x89PNG not really"));
+        assert!(clears(&analysis.result, None));
+        assert!(clears(&analysis.result, Some(OverrideVerdict::Safe)));
+        // an admin said unsafe; the model doesn't get to undo that
+        assert!(!clears(&analysis.result, Some(OverrideVerdict::Unsafe)));
+
+        let claude = mock_claude("end_turn", verdict(false, "medium")).await;
+        let (analysis, _) = rescan(&claude, location.clone(), fetch).await.unwrap();
+        assert!(!clears(&analysis.result, None));
+
+        let claude = mock_claude("refusal", serde_json::json!("")).await;
+        let (analysis, _) = rescan(&claude, location, fetch).await.unwrap();
+        assert!(!analysis.result.is_safe);
+        assert!(!clears(&analysis.result, None));
+    }
+
+    #[test]
+    fn test_image_location() {
+        let image = |image_id: Option<&str>, url: Option<&str>| SensitiveImageRow {
+            id: 1,
+            image_id: image_id.map(String::from),
+            url: url.map(String::from),
+            reason: None,
+            flagged_at: chrono::Utc::now(),
+            flagged_by: Some("claude-auto".to_string()),
+            severity: Some("high".to_string()),
+            violated_categories: None,
+        };
+
+        assert_eq!(
+            image_location(&image(Some("abc"), None), Some("https://images.plyr.fm")),
+            Some("https://images.plyr.fm/abc".to_string())
+        );
+        assert_eq!(
+            image_location(
+                &image(None, Some("https://cdn.bsky.app/a.jpg")),
+                Some("https://images.plyr.fm")
+            ),
+            Some("https://cdn.bsky.app/a.jpg".to_string())
+        );
+        assert_eq!(image_location(&image(Some("abc"), None), None), None);
+    }
+}
@@ -3,16 +3,31 @@
 //! These endpoints are behind the same auth as admin endpoints.
 
 use axum::{
-    extract::{Path, State},
+    extract::{Path, Query, State},
     http::header::CONTENT_TYPE,
     response::{IntoResponse, Response},
     Json,
 };
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
 
 use crate::admin::FlaggedTrack;
+use crate::auth::{Actor, Caller, RequireAdmin};
+use crate::db::{
+    BatchDecisionCount, DecisionOutcome, ResolutionReason, ReviewBatch, ReviewDecisionRow,
+};
+use crate::enforcement::enforce_confirmed;
+use crate::ratelimit::RateLimited;
+use crate::reasons::validate_reason;
 use crate::state::{AppError, AppState};
 
+/// Days a batch accepts decisions when `MODERATION_REVIEW_BATCH_TTL_DAYS` is unset.
+pub const DEFAULT_BATCH_TTL_DAYS: i64 = 14;
+
+/// Decisions a review submission may contain.
+const DECISIONS: &[&str] = &["clear", "defer", "confirm"];
+
 /// Response for review page data.
 #[derive(Debug, Serialize)]
 pub struct ReviewPageData {
@@ -25,6 +40,9 @@ pub struct ReviewPageData {
 #[derive(Debug, Deserialize)]
 pub struct SubmitReviewRequest {
     pub decisions: Vec<ReviewDecision>,
+    /// Submit even though another reviewer has claimed the batch.
+    #[serde(default)]
+    pub force: bool,
 }
 
 /// A single review decision.
@@ -33,15 +51,91 @@ pub struct ReviewDecision {
     pub uri: String,
     /// "clear" (false positive), "defer" (acknowledge, no action), "confirm" (real violation)
     pub decision: String,
+    /// Resolution reason key for "clear" decisions; defaults to fingerprint noise.
+    #[serde(default)]
+    pub reason: Option<String>,
+    /// Reviewer notes, kept in the flag's decision history.
+    #[serde(default)]
+    pub notes: Option<String>,
+}
+
+/// How far along a batch's review is.
+#[derive(Debug, Serialize)]
+pub struct BatchProgress {
+    pub batch_id: String,
+    pub status: String,
+    pub total: i64,
+    pub decided: i64,
+    pub pending: i64,
+    /// Decided flags by decision ("clear", "defer", "confirm").
+    pub decisions: BTreeMap<String, i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_decision_at: Option<DateTime<Utc>>,
+}
+
+impl BatchProgress {
+    fn from_counts(batch_id: &str, status: &str, counts: &[BatchDecisionCount]) -> Self {
+        let mut progress = Self {
+            batch_id: batch_id.to_string(),
+            status: status.to_string(),
+            total: 0,
+            decided: 0,
+            pending: 0,
+            decisions: BTreeMap::new(),
+            last_decision_at: None,
+        };
+        for count in counts {
+            progress.total += count.count;
+            if !count.reviewed {
+                progress.pending += count.count;
+                continue;
+            }
+            progress.decided += count.count;
+            let decision = count
+                .decision
+                .clone()
+                .unwrap_or_else(|| "unknown".to_string());
+            *progress.decisions.entry(decision).or_default() += count.count;
+            progress.last_decision_at = progress.last_decision_at.max(count.last_reviewed_at);
+        }
+        progress
+    }
 }
 
 /// Response after submitting review.
 #[derive(Debug, Serialize)]
 pub struct SubmitReviewResponse {
-    pub resolved_count: usize,
+    /// Flags cleared as false positives (negated and resolved).
+    pub cleared: usize,
+    /// Flags confirmed as violations. They stay flagged, and enforcement is
+    /// requested when configured.
+    pub confirmed: usize,
+    /// Flags left for a later review.
+    pub deferred: usize,
+    /// URIs that already had the submitted decision in this batch, e.g. from
+    /// a retry of the same submission. Nothing was written for them.
+    pub already_processed: Vec<String>,
+    /// Decisions that weren't applied because the flag already had a
+    /// different decision in this batch.
+    pub rejected: Vec<RejectedDecision>,
     pub message: String,
 }
 
+/// A submitted decision that wasn't applied.
+#[derive(Debug, Serialize)]
+pub struct RejectedDecision {
+    pub uri: String,
+    pub reason: String,
+}
+
+/// Query parameters for claiming a batch.
+#[derive(Debug, Deserialize)]
+pub struct ClaimBatchParams {
+    /// Take the batch over from another reviewer.
+    #[serde(default)]
+    pub force: bool,
+}
+
 /// Get review page HTML.
 pub async fn review_page(
     State(state): State<AppState>,
@@ -54,8 +148,19 @@ pub async fn review_page(
         .await?
         .ok_or(AppError::NotFound("batch not found".to_string()))?;
 
+    let now = Utc::now();
     let flags = db.get_batch_flags(&batch_id).await?;
-    let html = render_review_page(&batch_id, &flags, &batch.status);
+    let uris: Vec<String> = flags.iter().map(|f| f.uri.clone()).collect();
+    let prior = prior_decisions(db.get_review_decisions(&uris).await?, &batch_id);
+    let closed = batch_closed_reason(&batch, now);
+    let html = render_review_page(
+        &batch_id,
+        &flags,
+        batch_status(&batch, now),
+        closed.as_deref(),
+        batch.active_claim(now),
+        &prior,
+    );
 
     Ok(([(CONTENT_TYPE, "text/html; charset=utf-8")], html).into_response())
 }
@@ -77,14 +182,99 @@ pub async fn review_data(
     Ok(Json(ReviewPageData {
         batch_id,
         flags,
-        status: batch.status,
+        status: batch_status(&batch, Utc::now()).to_string(),
     }))
 }
 
+/// Get a batch's review progress, for tracking outside the review page.
+pub async fn batch_progress(
+    State(state): State<AppState>,
+    Path(batch_id): Path<String>,
+) -> Result<Json<BatchProgress>, AppError> {
+    Ok(Json(fetch_progress(&state, &batch_id).await?))
+}
+
+/// Get a batch's review progress as an HTML fragment, polled by the review
+/// page's submit bar.
+pub async fn batch_progress_html(
+    State(state): State<AppState>,
+    Path(batch_id): Path<String>,
+) -> Result<Response, AppError> {
+    let progress = fetch_progress(&state, &batch_id).await?;
+    let html = render_batch_progress(&progress);
+    Ok(([(CONTENT_TYPE, "text/html; charset=utf-8")], html).into_response())
+}
+
+async fn fetch_progress(state: &AppState, batch_id: &str) -> Result<BatchProgress, AppError> {
+    let db = state.db.as_ref().ok_or(AppError::LabelerNotConfigured)?;
+    let batch = db
+        .get_batch(batch_id)
+        .await?
+        .ok_or(AppError::NotFound("batch not found".to_string()))?;
+    let counts = db.get_batch_decision_counts(batch_id).await?;
+    Ok(BatchProgress::from_counts(
+        batch_id,
+        batch_status(&batch, Utc::now()),
+        &counts,
+    ))
+}
+
+/// Claim a batch for the caller's credential, so decisions from other
+/// reviewers are rejected until it completes or expires. `?force=true` takes
+/// over someone else's claim.
+pub async fn claim_batch(
+    State(state): State<AppState>,
+    _: RequireAdmin,
+    Path(batch_id): Path<String>,
+    Query(params): Query<ClaimBatchParams>,
+    actor: Actor,
+    caller: Caller,
+) -> Result<Json<ReviewBatch>, AppError> {
+    let db = state.db.as_ref().ok_or(AppError::LabelerNotConfigured)?;
+    let caller = caller.id();
+
+    let batch = db
+        .get_batch(&batch_id)
+        .await?
+        .ok_or(AppError::NotFound("batch not found".to_string()))?;
+    let now = Utc::now();
+    check_batch_open(&batch, now)?;
+    check_claim(&batch, &caller, params.force, now)?;
+
+    // the checks above can race another claim; the update re-checks them
+    let claimed = db
+        .claim_batch(
+            &batch_id,
+            &caller,
+            &reviewer_name(&actor, &caller),
+            params.force,
+        )
+        .await?
+        .ok_or_else(|| {
+            AppError::Conflict("batch changed while claiming it; reload and try again".to_string())
+        })?;
+    db.record_audit_event(
+        "claim_batch",
+        &batch_id,
+        actor.as_str(),
+        serde_json::json!({
+            "previous": batch.active_claim(now),
+            "force": params.force,
+            "caller": caller,
+        }),
+    )
+    .await?;
+    Ok(Json(claimed))
+}
+
 /// Submit review decisions.
 pub async fn submit_review(
     State(state): State<AppState>,
+    _: RequireAdmin,
+    _: RateLimited,
     Path(batch_id): Path<String>,
+    actor: Actor,
+    caller: Caller,
     Json(request): Json<SubmitReviewRequest>,
 ) -> Result<Json<SubmitReviewResponse>, AppError> {
     let db = state.db.as_ref().ok_or(AppError::LabelerNotConfigured)?;
@@ -93,14 +283,36 @@ pub async fn submit_review(
         .as_ref()
         .ok_or(AppError::LabelerNotConfigured)?;
 
-    let _batch = db
+    let batch = db
         .get_batch(&batch_id)
         .await?
         .ok_or(AppError::NotFound("batch not found".to_string()))?;
+    let now = Utc::now();
+    // Every flag in a completed batch is decided, so a retried submission
+    // can only come back as already processed; let it through
+    if batch.status != "completed" {
+        check_batch_open(&batch, now)?;
+    }
+    let caller = caller.id();
+    check_claim(&batch, &caller, request.force, now)?;
+    let overridden_claim = batch.claimed_by_other(&caller, now);
+    let reviewer = reviewer_name(&actor, &caller);
 
-    let mut resolved_count = 0;
-
+    // Reject the whole submission before acting on any of it, so a batch
+    // can't be used to resolve flags outside it
+    check_decision_values(&request.decisions)?;
+    let flags = db.get_batch_flags(&batch_id).await?;
+    check_decisions_in_batch(&request.decisions, &flags)?;
+    let mut reasons = Vec::with_capacity(request.decisions.len());
     for decision in &request.decisions {
+        reasons.push(validate_reason(db, decision.reason.as_deref()).await?);
+    }
+
+    let (mut cleared, mut confirmed, mut deferred) = (0, 0, 0);
+    let mut already_processed = Vec::new();
+    let mut rejected = Vec::new();
+
+    for (decision, reason) in request.decisions.iter().zip(reasons) {
         tracing::info!(
             batch_id = %batch_id,
             uri = %decision.uri,
@@ -108,42 +320,94 @@ pub async fn submit_review(
             "processing review decision"
         );
 
-        db.mark_flag_reviewed(&batch_id, &decision.uri, &decision.decision)
-            .await?;
-
-        match decision.decision.as_str() {
+        // Clearing a false positive emits a negation label
+        let negation = match decision.decision.as_str() {
             "clear" => {
-                // False positive - emit negation label to clear the flag
+                let src =
+                    crate::admin::label_src(db, signer, &decision.uri, "copyright-violation")
+                        .await?;
                 let label =
-                    crate::labels::Label::new(signer.did(), &decision.uri, "copyright-violation")
-                        .negated();
-                let label = signer.sign_label(label)?;
-                let seq = db.store_label(&label).await?;
-
-                db.store_resolution(
-                    &decision.uri,
-                    crate::db::ResolutionReason::FingerprintNoise,
-                    Some("batch review: cleared"),
-                )
-                .await?;
-
-                if let Some(tx) = &state.label_tx {
-                    let _ = tx.send((seq, label));
-                }
+                    crate::labels::Label::new(&src, &decision.uri, "copyright-violation").negated();
+                let reason = reason.unwrap_or(ResolutionReason::FingerprintNoise);
+                Some((signer.sign_label(label)?, reason))
+            }
+            // Defer leaves the flag active; confirm records the reviewer and
+            // asks the backend to enforce, below. Nothing else gets past
+            // check_decision_values
+            _ => None,
+        };
 
-                resolved_count += 1;
+        // The decision, negation and resolution are written together, and
+        // only the first decision for a flag sticks
+        let outcome = db
+            .apply_batch_decision(
+                &batch_id,
+                &decision.uri,
+                &decision.decision,
+                &reviewer,
+                decision
+                    .notes
+                    .as_deref()
+                    .map(str::trim)
+                    .filter(|n| !n.is_empty()),
+                negation
+                    .as_ref()
+                    .map(|(label, reason)| (label, reason, "batch review: cleared")),
+            )
+            .await?;
+        let negation_seq = match outcome {
+            DecisionOutcome::Applied { seq } => seq,
+            DecisionOutcome::AlreadyProcessed => {
+                already_processed.push(decision.uri.clone());
+                continue;
             }
-            "defer" => {
-                // Acknowledge but take no action - flag stays active
-                // Just mark as reviewed in the batch, no label changes
-                tracing::info!(uri = %decision.uri, "deferred - no action taken");
+            DecisionOutcome::Conflicting { existing } => {
+                rejected.push(RejectedDecision {
+                    uri: decision.uri.clone(),
+                    reason: format!("already decided as {existing} in this batch"),
+                });
+                continue;
             }
-            "confirm" => {
-                // Real violation - flag stays active, could add enforcement later
-                tracing::info!(uri = %decision.uri, "confirmed as violation");
+            DecisionOutcome::NotInBatch => {
+                rejected.push(RejectedDecision {
+                    uri: decision.uri.clone(),
+                    reason: "not in this batch".to_string(),
+                });
+                continue;
             }
-            _ => {
-                tracing::warn!(uri = %decision.uri, decision = %decision.decision, "unknown decision type");
+        };
+
+        if let (Some(seq), Some((label, _))) = (negation_seq, negation) {
+            if let Some(tx) = &state.label_tx {
+                let _ = tx.send((seq, label));
+            }
+        }
+        match decision.decision.as_str() {
+            "clear" => cleared += 1,
+            "confirm" => confirmed += 1,
+            "defer" => deferred += 1,
+            _ => {}
+        }
+
+        db.record_audit_event(
+            "review_decision",
+            &decision.uri,
+            actor.as_str(),
+            serde_json::json!({
+                "batch_id": batch_id,
+                "decision": decision.decision,
+                "seq": negation_seq,
+                "overrode_claim": overridden_claim,
+                "caller": caller,
+            }),
+        )
+        .await?;
+
+        // The decision is already stored, so an enforcement failure is logged
+        // rather than failing the review
+        if decision.decision == "confirm" {
+            if let Err(e) = enforce_confirmed(&state, db, &decision.uri, &reviewer).await {
+                tracing::warn!(uri = %decision.uri, error = %e, "failed to start enforcement");
             }
         }
     }
@@ -153,23 +417,184 @@ pub async fn submit_review(
         db.update_batch_status(&batch_id, "completed").await?;
     }
 
+    let applied = request.decisions.len() - already_processed.len() - rejected.len();
+    let mut message = format!(
+        "processed {applied} decisions: {cleared} cleared, {confirmed} confirmed, {deferred} deferred"
+    );
+    if !already_processed.is_empty() {
+        message.push_str(&format!(", {} already processed", already_processed.len()));
+    }
+    if !rejected.is_empty() {
+        message.push_str(&format!(", rejected {}", rejected.len()));
+    }
+
     Ok(Json(SubmitReviewResponse {
-        resolved_count,
-        message: format!(
-            "processed {} decisions, resolved {} flags",
-            request.decisions.len(),
-            resolved_count
-        ),
+        cleared,
+        confirmed,
+        deferred,
+        already_processed,
+        rejected,
+        message,
     }))
 }
 
-/// Render the review page.
-fn render_review_page(batch_id: &str, flags: &[FlaggedTrack], status: &str) -> String {
+/// A batch's status, counting pending batches past their deadline as
+/// expired even if the sweeper hasn't marked them yet.
+fn batch_status(batch: &ReviewBatch, now: DateTime<Utc>) -> &str {
+    if batch.is_expired(now) {
+        "expired"
+    } else {
+        &batch.status
+    }
+}
+
+/// Why a batch no longer accepts decisions, if it doesn't.
+fn batch_closed_reason(batch: &ReviewBatch, now: DateTime<Utc>) -> Option<String> {
+    if batch.is_expired(now) {
+        let expired = batch
+            .expires_at
+            .map(|exp| format!(" on {}", exp.format("%Y-%m-%d")))
+            .unwrap_or_default();
+        return Some(format!(
+            "this batch expired{expired}; create a new batch to review any flags still pending"
+        ));
+    }
+    if batch.status == "completed" {
+        return Some("this batch is completed; every flag in it has a decision".to_string());
+    }
+    None
+}
+
+/// Reject submissions to expired or completed batches.
+fn check_batch_open(batch: &ReviewBatch, now: DateTime<Utc>) -> Result<(), AppError> {
+    match batch_closed_reason(batch, now) {
+        Some(reason) => Err(AppError::Conflict(reason)),
+        None => Ok(()),
+    }
+}
+
+/// Reject acting on a batch another credential has claimed, unless forced.
+/// `caller` is the acting `Caller::id`.
+fn check_claim(
+    batch: &ReviewBatch,
+    caller: &str,
+    force: bool,
+    now: DateTime<Utc>,
+) -> Result<(), AppError> {
+    match batch.claimed_by_other(caller, now) {
+        Some(holder) if !force => Err(AppError::Conflict(format!(
+            "this batch is claimed by {holder}; use force to override"
+        ))),
+        _ => Ok(()),
+    }
+}
+
+/// How a reviewer is recorded on claims and decisions: the name they gave
+/// in `X-Moderation-Actor`, tied to the credential that acted, or just the
+/// credential when they gave none.
+fn reviewer_name(actor: &Actor, caller: &str) -> String {
+    if actor.is_unknown() {
+        caller.to_string()
+    } else {
+        format!("{} ({})", actor.as_str(), caller)
+    }
+}
+
+/// Render progress as "12/40 decided", then counts by decision.
+fn render_batch_progress(progress: &BatchProgress) -> String {
+    let mut parts = vec![format!(
+        "<strong>{}/{}</strong> decided",
+        progress.decided, progress.total
+    )];
+    parts.extend(
+        progress
+            .decisions
+            .iter()
+            .map(|(decision, count)| format!("{} {}", count, html_escape(decision))),
+    );
+    if let Some(last) = progress.last_decision_at {
+        parts.push(format!("last {}", last.format("%Y-%m-%d %H:%M UTC")));
+    }
+    parts.join(" · ")
+}
+
+/// Reject decisions other than clear, defer and confirm, before any is applied.
+fn check_decision_values(decisions: &[ReviewDecision]) -> Result<(), AppError> {
+    let unknown: Vec<String> = decisions
+        .iter()
+        .filter(|d| !DECISIONS.contains(&d.decision.as_str()))
+        .map(|d| format!("{} ({})", d.decision, d.uri))
+        .collect();
+    if unknown.is_empty() {
+        Ok(())
+    } else {
+        Err(AppError::BadRequest(format!(
+            "unknown decisions: {} (expected one of {})",
+            unknown.join(", "),
+            DECISIONS.join(", ")
+        )))
+    }
+}
+
+/// Reject decisions for URIs that aren't flags in the batch.
+fn check_decisions_in_batch(
+    decisions: &[ReviewDecision],
+    flags: &[FlaggedTrack],
+) -> Result<(), AppError> {
+    let in_batch: HashSet<&str> = flags.iter().map(|f| f.uri.as_str()).collect();
+    let unknown: Vec<&str> = decisions
+        .iter()
+        .map(|d| d.uri.as_str())
+        .filter(|uri| !in_batch.contains(uri))
+        .collect();
+    if unknown.is_empty() {
+        Ok(())
+    } else {
+        Err(AppError::BadRequest(format!(
+            "not in this batch: {}",
+            unknown.join(", ")
+        )))
+    }
+}
+
+/// Group decisions by URI, leaving out those made in `batch_id` itself.
+fn prior_decisions(
+    decisions: Vec<ReviewDecisionRow>,
+    batch_id: &str,
+) -> HashMap<String, Vec<ReviewDecisionRow>> {
+    let mut prior: HashMap<String, Vec<ReviewDecisionRow>> = HashMap::new();
+    for decision in decisions {
+        if decision.batch_id.as_deref() != Some(batch_id) {
+            prior
+                .entry(decision.uri.clone())
+                .or_default()
+                .push(decision);
+        }
+    }
+    prior
+}
+
+/// Render the review page. A closed batch renders read-only, with the reason
+/// in place of the decision buttons and submit bar. An open batch shows who
+/// has claimed it, with a button to claim (or take over) the batch.
+fn render_review_page(
+    batch_id: &str,
+    flags: &[FlaggedTrack],
+    status: &str,
+    closed: Option<&str>,
+    claimed_by: Option<&str>,
+    prior: &HashMap<String, Vec<ReviewDecisionRow>>,
+) -> String {
+    let read_only = closed.is_some();
     let pending: Vec<_> = flags.iter().filter(|f| !f.resolved).collect();
     let resolved: Vec<_> = flags.iter().filter(|f| f.resolved).collect();
+    let card = |f: &&FlaggedTrack| {
+        let prior = prior.get(&f.uri).map(Vec::as_slice).unwrap_or_default();
+        render_review_card(f, read_only, prior)
+    };
 
-    let pending_cards: Vec<String> = pending.iter().map(|f| render_review_card(f)).collect();
-    let resolved_cards: Vec<String> = resolved.iter().map(|f| render_review_card(f)).collect();
+    let pending_cards: Vec<String> = pending.iter().map(card).collect();
+    let resolved_cards: Vec<String> = resolved.iter().map(card).collect();
 
     let pending_html = if pending_cards.is_empty() {
         "<div class=\"empty\">all flags reviewed!</div>".to_string()
@@ -190,10 +615,49 @@ fn render_review_page(batch_id: &str, flags: &[FlaggedTrack], status: &str) -> S
         )
     };
 
-    let status_badge = if status == "completed" {
-        r#"<span class="badge resolved">completed</span>"#
+    let status_badge = match status {
+        "completed" => r#"<span class="badge resolved">completed</span>"#,
+        "expired" => r#"<span class="badge dismissed">expired</span>"#,
+        _ => "",
+    };
+
+    let closed_notice = closed
+        .map(|reason| {
+            format!(
+                r#"<div class="review-closed">{}</div>"#,
+                html_escape(reason)
+            )
+        })
+        .unwrap_or_default();
+
+    let claim_bar = match (read_only, claimed_by) {
+        (true, _) => String::new(),
+        (false, Some(reviewer)) => format!(
+            r#"<div class="review-claim">claimed by <strong>{}</strong>
+            <button type="button" class="btn btn-secondary" onclick="claimBatch(true)">take over</button>
+        </div>"#,
+            html_escape(reviewer)
+        ),
+        (false, None) => r#"<div class="review-claim">unclaimed
+            <button type="button" class="btn btn-secondary" onclick="claimBatch(false)">claim this batch</button>
+        </div>"#
+            .to_string(),
+    };
+
+    let submit_bar = if read_only {
+        String::new()
     } else {
-        ""
+        format!(
+            r#"<div class="submit-bar">
+            <div class="batch-progress"
+                 hx-get="/admin/batches/{}/progress-html"
+                 hx-trigger="load, every 30s"></div>
+            <button type="submit" class="btn btn-primary" id="submit-btn" disabled>
+                submit decisions
+            </button>
+        </div>"#,
+            html_escape(batch_id)
+        )
     };
 
     format!(
@@ -204,6 +668,7 @@ fn render_review_page(batch_id: &str, flags: &[FlaggedTrack], status: &str) -> S
     <meta name="viewport" content="width=device-width, initial-scale=1">
     <title>review batch - plyr.fm</title>
     <link rel="stylesheet" href="/static/admin.css">
+    <script src="https://unpkg.com/htmx.org@1.9.10"></script>
     <style>{}</style>
 </head>
 <body>
@@ -214,58 +679,60 @@ fn render_review_page(batch_id: &str, flags: &[FlaggedTrack], status: &str) -> S
         batch review: {} pending {}
     </p>
 
-    <div class="auth-section" id="auth-section">
-        <input type="password" id="auth-token" placeholder="auth token"
-               onkeyup="if(event.key==='Enter')authenticate()">
-        <button class="btn btn-primary" onclick="authenticate()">authenticate</button>
-    </div>
+    <form id="review-form">
+        {}
+
+        {}
 
-    <form id="review-form" style="display: none;">
         <div class="flags-list">
             {}
         </div>
 
         {}
 
-        <div class="submit-bar">
-            <button type="submit" class="btn btn-primary" id="submit-btn" disabled>
-                submit decisions
-            </button>
-        </div>
+        {}
     </form>
 
     <script>
         const form = document.getElementById('review-form');
         const submitBtn = document.getElementById('submit-btn');
-        const authSection = document.getElementById('auth-section');
         const batchId = '{}';
 
-        let currentToken = '';
         const decisions = {{}};
 
-        function authenticate() {{
-            const token = document.getElementById('auth-token').value;
-            if (token && token !== '••••••••') {{
-                localStorage.setItem('mod_token', token);
-                currentToken = token;
-                showReviewForm();
-            }}
+        // Name recorded in the audit log, set on the dashboard
+        const actor = localStorage.getItem('mod_actor') || '';
+        function actorHeader() {{
+            return actor ? {{ 'X-Moderation-Actor': actor }} : {{}};
         }}
 
-        function showReviewForm() {{
-            authSection.style.display = 'none';
-            form.style.display = 'block';
+        async function claimBatch(force) {{
+            if (force && !confirm('take this batch over from its current reviewer?')) return;
+            const response = await fetch(`/admin/batches/${{batchId}}/claim${{force ? '?force=true' : ''}}`, {{
+                method: 'POST',
+                headers: actorHeader()
+            }});
+            if (response.status === 401) {{
+                alert('session expired - sign in again');
+                showLogin();
+                return;
+            }}
+            if (response.ok) {{
+                location.reload();
+            }} else {{
+                const err = await response.json();
+                alert('error: ' + (err.message || 'unknown error'));
+            }}
         }}
 
-        // Check for saved token on load
-        const savedToken = localStorage.getItem('mod_token');
-        if (savedToken) {{
-            currentToken = savedToken;
-            document.getElementById('auth-token').value = '••••••••';
-            showReviewForm();
+        // Once the session has expired the server serves the login form in
+        // place of this page
+        function showLogin() {{
+            location.reload();
         }}
 
         function updateSubmitBtn() {{
+            if (!submitBtn) return;
             const count = Object.keys(decisions).length;
             submitBtn.disabled = count === 0;
             submitBtn.textContent = count > 0 ? `submit ${{count}} decision${{count > 1 ? 's' : ''}}` : 'submit decisions';
@@ -288,8 +755,13 @@ fn render_review_page(batch_id: &str, flags: &[FlaggedTrack], status: &str) -> S
             updateSubmitBtn();
         }}
 
-        form.addEventListener('submit', async (e) => {{
+        form.addEventListener('submit', (e) => {{
             e.preventDefault();
+            submitDecisions(false);
+        }});
+
+        async function submitDecisions(force) {{
+            if (!submitBtn) return;
             submitBtn.disabled = true;
             submitBtn.textContent = 'submitting...';
 
@@ -298,20 +770,17 @@ fn render_review_page(batch_id: &str, flags: &[FlaggedTrack], status: &str) -> S
                     method: 'POST',
                     headers: {{
                         'Content-Type': 'application/json',
-                        'X-Moderation-Key': currentToken
+                        ...actorHeader()
                     }},
                     body: JSON.stringify({{
-                        decisions: Object.entries(decisions).map(([uri, decision]) => ({{ uri, decision }}))
+                        decisions: Object.entries(decisions).map(([uri, decision]) => ({{ uri, decision }})),
+                        force
                     }})
                 }});
 
                 if (response.status === 401) {{
-                    localStorage.removeItem('mod_token');
-                    currentToken = '';
-                    authSection.style.display = 'block';
-                    form.style.display = 'none';
-                    document.getElementById('auth-token').value = '';
-                    alert('invalid token');
+                    alert('session expired - sign in again');
+                    showLogin();
                     return;
                 }}
 
@@ -321,7 +790,12 @@ fn render_review_page(batch_id: &str, flags: &[FlaggedTrack], status: &str) -> S
                     location.reload();
                 }} else {{
                     const err = await response.json();
-                    alert('error: ' + (err.message || 'unknown error'));
+                    const message = err.message || 'unknown error';
+                    if (response.status === 409 && message.includes('claimed by') &&
+                        confirm(message + '\n\nsubmit anyway?')) {{
+                        return submitDecisions(true);
+                    }}
+                    alert('error: ' + message);
                     submitBtn.disabled = false;
                     updateSubmitBtn();
                 }}
@@ -330,21 +804,32 @@ fn render_review_page(batch_id: &str, flags: &[FlaggedTrack], status: &str) -> S
                 submitBtn.disabled = false;
                 updateSubmitBtn();
             }}
-        }});
+        }}
     </script>
 </body>
 </html>"#,
         REVIEW_CSS,
         pending.len(),
         status_badge,
+        closed_notice,
+        claim_bar,
         pending_html,
         resolved_html,
+        submit_bar,
         html_escape(batch_id)
     )
 }
 
-/// Render a single review card.
-fn render_review_card(track: &FlaggedTrack) -> String {
+/// Most earlier decisions listed on a review card.
+const PRIOR_DECISIONS_SHOWN: usize = 3;
+
+/// Render a single review card. Read-only cards have no decision buttons.
+/// `prior` holds decisions from other batches and the dashboard, newest first.
+fn render_review_card(
+    track: &FlaggedTrack,
+    read_only: bool,
+    prior: &[ReviewDecisionRow],
+) -> String {
     let ctx = track.context.as_ref();
 
     let title = ctx
@@ -387,13 +872,37 @@ fn render_review_card(track: &FlaggedTrack) -> String {
         })
         .unwrap_or_default();
 
+    let prior_html = if prior.is_empty() {
+        String::new()
+    } else {
+        let items: Vec<String> = prior
+            .iter()
+            .take(PRIOR_DECISIONS_SHOWN)
+            .map(|d| {
+                let notes = d
+                    .notes
+                    .as_deref()
+                    .map(|n| format!(": {}", html_escape(n)))
+                    .unwrap_or_default();
+                format!(
+                    r#"<div class="prior-decision">previously {} {} by {}{}</div>"#,
+                    decision_past_tense(&d.decision),
+                    d.created_at.format("%Y-%m-%d"),
+                    html_escape(&d.actor),
+                    notes
+                )
+            })
+            .collect();
+        format!(r#"<div class="prior-decisions">{}</div>"#, items.join("\n"))
+    };
+
     let resolved_badge = if track.resolved {
         r#"<span class="badge resolved">resolved</span>"#
     } else {
         r#"<span class="badge pending">pending</span>"#
     };
 
-    let action_buttons = if !track.resolved {
+    let action_buttons = if !track.resolved && !read_only {
         format!(
             r#"<div class="flag-actions">
                 <button type="button" class="btn btn-clear" onclick="setDecision('{}', 'clear')">clear</button>
@@ -421,17 +930,28 @@ fn render_review_card(track: &FlaggedTrack) -> String {
             </div>
             {}
             {}
+            {}
         </div>"#,
         if track.resolved { " resolved" } else { "" },
         html_escape(&track.uri),
         title_html,
         html_escape(artist),
         resolved_badge,
+        prior_html,
         matches_html,
         action_buttons
     )
 }
 
+fn decision_past_tense(decision: &str) -> &str {
+    match decision {
+        "clear" => "cleared",
+        "confirm" => "confirmed",
+        "defer" => "deferred",
+        other => other,
+    }
+}
+
 fn html_escape(s: &str) -> String {
     s.replace('&', "&amp;")
         .replace('<', "&lt;")
@@ -510,6 +1030,37 @@ body { padding-bottom: 80px; }
     display: block;
     padding: 14px;
 }
+.submit-bar .batch-progress {
+    max-width: 900px;
+    margin: 0 auto 8px;
+    text-align: center;
+}
+
+/* closed batch notice */
+.review-closed {
+    padding: 12px 16px;
+    margin-bottom: 16px;
+    border: 1px solid var(--border-subtle);
+    border-radius: 6px;
+    background: var(--bg-secondary);
+    color: var(--text-secondary);
+}
+
+/* earlier decisions on a flag */
+.prior-decisions {
+    margin: 8px 0;
+    font-size: 0.85rem;
+    color: var(--text-secondary);
+}
+
+/* batch claim */
+.review-claim {
+    display: flex;
+    align-items: center;
+    gap: 12px;
+    margin-bottom: 16px;
+    color: var(--text-secondary);
+}
 
 /* resolved section */
 .resolved-section {
@@ -524,3 +1075,287 @@ body { padding-bottom: 80px; }
     margin-bottom: 12px;
 }
 "#;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn flag(uri: &str) -> FlaggedTrack {
+        FlaggedTrack {
+            seq: 1,
+            uri: uri.to_string(),
+            val: "copyright-violation".to_string(),
+            created_at: "2026-01-01 00:00:00".to_string(),
+            resolved: false,
+            resolved_at: None,
+            context: None,
+            enforcement: None,
+            label_count: 1,
+        }
+    }
+
+    fn decision(uri: &str) -> ReviewDecision {
+        ReviewDecision {
+            uri: uri.to_string(),
+            decision: "clear".to_string(),
+            reason: None,
+            notes: None,
+        }
+    }
+
+    #[test]
+    fn test_decisions_must_belong_to_batch() {
+        let flags = [
+            flag("at://did:plc:a/fm.plyr.track/1"),
+            flag("at://did:plc:a/fm.plyr.track/2"),
+        ];
+        assert!(
+            check_decisions_in_batch(&[decision("at://did:plc:a/fm.plyr.track/2")], &flags).is_ok()
+        );
+
+        let err = check_decisions_in_batch(
+            &[
+                decision("at://did:plc:a/fm.plyr.track/1"),
+                decision("at://did:plc:other/fm.plyr.track/9"),
+            ],
+            &flags,
+        )
+        .unwrap_err();
+        match err {
+            AppError::BadRequest(msg) => {
+                assert!(msg.contains("at://did:plc:other/fm.plyr.track/9"));
+                assert!(!msg.contains("track/1"));
+            }
+            other => panic!("expected BadRequest, got {other:?}"),
+        }
+    }
+
+    fn batch(status: &str, expires_in: chrono::Duration) -> ReviewBatch {
+        let now = Utc::now();
+        ReviewBatch {
+            id: "b1".to_string(),
+            created_at: now - chrono::Duration::days(14),
+            expires_at: Some(now + expires_in),
+            status: status.to_string(),
+            created_by: None,
+            assigned_to: None,
+            assigned_caller: None,
+        }
+    }
+
+    #[test]
+    fn test_batch_expires_after_deadline() {
+        let now = Utc::now();
+        let open = batch("pending", chrono::Duration::hours(1));
+        assert!(!open.is_expired(now));
+        assert_eq!(batch_status(&open, now), "pending");
+
+        // the sweeper marks the same batches is_expired reports
+        let overdue = batch("pending", -chrono::Duration::hours(1));
+        assert!(overdue.is_expired(now));
+        assert_eq!(batch_status(&overdue, now), "expired");
+
+        // completed batches never flip to expired
+        let completed = batch("completed", -chrono::Duration::hours(1));
+        assert!(!completed.is_expired(now));
+        assert_eq!(batch_status(&completed, now), "completed");
+    }
+
+    #[test]
+    fn test_submit_to_closed_batch_rejected() {
+        let now = Utc::now();
+        assert!(check_batch_open(&batch("pending", chrono::Duration::days(1)), now).is_ok());
+
+        for closed in [
+            batch("pending", -chrono::Duration::days(1)),
+            batch("expired", -chrono::Duration::days(1)),
+        ] {
+            match check_batch_open(&closed, now) {
+                Err(AppError::Conflict(msg)) => assert!(msg.contains("expired")),
+                other => panic!("expected Conflict, got {other:?}"),
+            }
+        }
+        match check_batch_open(&batch("completed", chrono::Duration::days(1)), now) {
+            Err(AppError::Conflict(msg)) => assert!(msg.contains("completed")),
+            other => panic!("expected Conflict, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_closed_batch_renders_read_only() {
+        let flags = [flag("at://did:plc:a/fm.plyr.track/1")];
+        let html = render_review_page("b1", &flags, "pending", None, None, &HashMap::new());
+        assert!(html.contains("setDecision('at://did:plc:a/fm.plyr.track/1', 'clear')"));
+        assert!(html.contains(r#"id="submit-btn""#));
+
+        let html = render_review_page(
+            "b1",
+            &flags,
+            "expired",
+            Some("this batch expired"),
+            None,
+            &HashMap::new(),
+        );
+        assert!(html.contains(r#"<div class="review-closed">this batch expired</div>"#));
+        assert!(html.contains(r#"<span class="badge dismissed">expired</span>"#));
+        assert!(!html.contains("setDecision('at://"));
+        assert!(!html.contains(r#"id="submit-btn""#));
+        assert!(!html.contains(r#"onclick="claimBatch("#));
+    }
+
+    #[test]
+    fn test_reviewer_name_ties_the_actor_to_the_credential() {
+        let named = Actor("alice".to_string());
+        assert_eq!(
+            reviewer_name(&named, "session:aaaa"),
+            "alice (session:aaaa)"
+        );
+        let unnamed = Actor("unknown".to_string());
+        assert_eq!(reviewer_name(&unnamed, "token:bbbb"), "token:bbbb");
+    }
+
+    #[test]
+    fn test_unknown_decisions_are_rejected() {
+        let mut decisions = vec![decision("at://did:plc:a/fm.plyr.track/1")];
+        for value in ["clear", "defer", "confirm"] {
+            decisions[0].decision = value.to_string();
+            assert!(check_decision_values(&decisions).is_ok(), "{value}");
+        }
+
+        decisions.push(ReviewDecision {
+            decision: "approve".to_string(),
+            ..decision("at://did:plc:a/fm.plyr.track/2")
+        });
+        match check_decision_values(&decisions) {
+            Err(AppError::BadRequest(msg)) => {
+                assert!(msg.contains("approve (at://did:plc:a/fm.plyr.track/2)"));
+                assert!(!msg.contains("track/1"));
+            }
+            other => panic!("expected BadRequest, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_review_card_shows_prior_decisions() {
+        let uri = "at://did:plc:a/fm.plyr.track/1";
+        let decided =
+            |id, batch_id: Option<&str>, decision: &str, notes: Option<&str>| ReviewDecisionRow {
+                id,
+                uri: uri.to_string(),
+                batch_id: batch_id.map(String::from),
+                decision: decision.to_string(),
+                actor: "alice".to_string(),
+                notes: notes.map(String::from),
+                created_at: "2024-11-02T12:00:00Z".parse().unwrap(),
+            };
+        let prior = prior_decisions(
+            vec![
+                decided(3, Some("b1"), "defer", None),
+                decided(2, None, "clear", Some("<sample pack>")),
+                decided(1, Some("b0"), "confirm", None),
+            ],
+            "b1",
+        );
+        assert_eq!(prior[uri].len(), 2);
+
+        let html = render_review_page("b1", &[flag(uri)], "pending", None, None, &prior);
+        assert!(html.contains("previously cleared 2024-11-02 by alice: &lt;sample pack&gt;</div>"));
+        assert!(html.contains("previously confirmed 2024-11-02 by alice</div>"));
+        assert!(!html.contains("previously deferred"));
+    }
+
+    #[test]
+    fn test_claimed_batch_rejects_other_reviewers() {
+        let now = Utc::now();
+        let mut claimed = batch("pending", chrono::Duration::days(1));
+        claimed.assigned_to = Some("alice (session:aaaa)".to_string());
+        claimed.assigned_caller = Some("session:aaaa".to_string());
+        assert_eq!(claimed.active_claim(now), Some("alice (session:aaaa)"));
+
+        // the credential decides whose claim it is, not the name shown
+        assert!(check_claim(&claimed, "session:aaaa", false, now).is_ok());
+        assert_eq!(claimed.claimed_by_other("session:aaaa", now), None);
+        match check_claim(&claimed, "session:bbbb", false, now) {
+            Err(AppError::Conflict(msg)) => assert!(msg.contains("claimed by alice")),
+            other => panic!("expected Conflict, got {other:?}"),
+        }
+        assert!(check_claim(&claimed, "alice (session:aaaa)", false, now).is_err());
+        assert!(check_claim(&claimed, "session:bbbb", true, now).is_ok());
+
+        // an overdue batch's claim has lapsed even before the sweeper runs
+        let mut lapsed = batch("pending", -chrono::Duration::hours(1));
+        lapsed.assigned_to = Some("alice (session:aaaa)".to_string());
+        lapsed.assigned_caller = Some("session:aaaa".to_string());
+        assert_eq!(lapsed.active_claim(now), None);
+        assert!(check_claim(&lapsed, "session:bbbb", false, now).is_ok());
+
+        // a claim recorded without a credential doesn't lock anyone out
+        let mut anonymous = batch("pending", chrono::Duration::days(1));
+        anonymous.assigned_to = Some("alice".to_string());
+        assert_eq!(anonymous.active_claim(now), None);
+        assert_eq!(anonymous.claimed_by_other("session:bbbb", now), None);
+        assert!(check_claim(&anonymous, "session:bbbb", false, now).is_ok());
+
+        let flags = [flag("at://did:plc:a/fm.plyr.track/1")];
+        let html = render_review_page(
+            "b1",
+            &flags,
+            "pending",
+            None,
+            Some("alice"),
+            &HashMap::new(),
+        );
+        assert!(html.contains("claimed by <strong>alice</strong>"));
+        assert!(html.contains("claimBatch(true)"));
+        let html = render_review_page("b1", &flags, "pending", None, None, &HashMap::new());
+        assert!(html.contains("claimBatch(false)"));
+    }
+
+    #[test]
+    fn test_partially_decided_batch_progress() {
+        let at = |h: u32| {
+            "2026-03-01T00:00:00Z".parse::<DateTime<Utc>>().unwrap()
+                + chrono::Duration::hours(h.into())
+        };
+        let count =
+            |decision: Option<&str>, count, last: Option<DateTime<Utc>>| BatchDecisionCount {
+                decision: decision.map(String::from),
+                reviewed: decision.is_some(),
+                count,
+                last_reviewed_at: last,
+            };
+        let progress = BatchProgress::from_counts(
+            "b1",
+            "pending",
+            &[
+                count(None, 28, None),
+                count(Some("clear"), 7, Some(at(2))),
+                count(Some("confirm"), 3, Some(at(5))),
+                count(Some("defer"), 2, Some(at(1))),
+            ],
+        );
+        assert_eq!(progress.total, 40);
+        assert_eq!(progress.decided, 12);
+        assert_eq!(progress.pending, 28);
+        assert_eq!(progress.decisions["clear"], 7);
+        assert_eq!(progress.decisions["confirm"], 3);
+        assert_eq!(progress.last_decision_at, Some(at(5)));
+
+        let json = serde_json::to_value(&progress).unwrap();
+        assert_eq!(json["decisions"]["defer"], 2);
+        assert_eq!(json["status"], "pending");
+
+        assert_eq!(
+            render_batch_progress(&progress),
+            "<strong>12/40</strong> decided · 7 clear · 3 confirm · 2 defer · last 2026-03-01 05:00 UTC"
+        );
+
+        let empty = BatchProgress::from_counts("b2", "pending", &[]);
+        assert_eq!(empty.total, 0);
+        assert!(empty.last_decision_at.is_none());
+        assert_eq!(
+            render_batch_progress(&empty),
+            "<strong>0/0</strong> decided"
+        );
+    }
+}
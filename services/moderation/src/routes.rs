@@ -0,0 +1,1595 @@
+//! HTTP routes.
+
+use axum::{
+    extract::DefaultBodyLimit,
+    middleware,
+    routing::{delete, get, post},
+    Router,
+};
+use tower_http::{compression::CompressionLayer, services::ServeDir};
+
+use crate::auth;
+use crate::state::AppState;
+use crate::{
+    admin, audd, did, export, flagged, handlers, images, isrc, ratelimit, reasons, reporter_hash,
+    reports, reverify, review, search, session, stats, webhook, xrpc,
+};
+
+/// Build the service router. Everything outside the public paths in
+/// [`auth::auth_middleware`] requires a token.
+pub fn router(state: AppState) -> Router {
+    let auth_tokens = state.auth.clone();
+    Router::new()
+        // Landing page
+        .route("/", get(handlers::landing))
+        // Health check
+        .route("/health", get(handlers::health))
+        // Sensitive images (public)
+        .route("/sensitive-images", get(handlers::get_sensitive_images))
+        // AuDD scanning
+        .route("/scan", post(audd::scan))
+        .route(
+            "/scan-audio",
+            post(audd::scan_audio).layer(DefaultBodyLimit::max(audd::MAX_AUDIO_BODY_BYTES)),
+        )
+        // Image moderation via Claude
+        .route("/scan-image", post(handlers::scan_image))
+        .route("/scan-image/url", post(handlers::scan_image_url))
+        // Label emission (internal API)
+        .route("/emit-label", post(handlers::emit_label))
+        .route("/is-flagged", get(flagged::is_flagged))
+        // Admin UI and API
+        .route("/admin", get(admin::admin_ui))
+        .route(
+            "/admin/login",
+            post(session::login).layer(middleware::from_fn_with_state(
+                state.clone(),
+                ratelimit::limit_login,
+            )),
+        )
+        .route("/admin/logout", post(session::logout))
+        .route("/admin/health", get(did::admin_health))
+        .route("/admin/stats", get(stats::get_stats))
+        .route("/admin/stats-html", get(stats::get_stats_html))
+        .route("/admin/summary-html", get(stats::get_summary_html))
+        .route("/admin/flags", get(admin::list_flagged))
+        .route("/admin/flags-html", get(admin::list_flagged_html))
+        .route("/admin/flags/export.csv", get(admin::export_flags_csv))
+        .route("/admin/flags/rescan", post(admin::rescan_flag))
+        .route("/admin/flags/detail", get(admin::flag_detail))
+        .route("/admin/flags/detail-html", get(admin::flag_detail_html))
+        .route(
+            "/admin/flags/decisions-html",
+            get(admin::review_decisions_html),
+        )
+        .route("/admin/resolve", post(admin::resolve_flag))
+        .route("/admin/resolve-htmx", post(admin::resolve_flag_htmx))
+        .route("/admin/resolve-bulk", post(admin::resolve_bulk))
+        .route("/admin/unresolve", post(admin::unresolve_flag))
+        .route(
+            "/admin/resolution-reasons",
+            get(reasons::list_reasons).post(reasons::add_reason),
+        )
+        .route(
+            "/admin/resolution-reasons/deactivate",
+            post(reasons::deactivate_reason),
+        )
+        .route(
+            "/admin/report-reasons",
+            get(reasons::list_report_reasons).post(reasons::add_report_reason),
+        )
+        .route(
+            "/admin/report-reasons/deactivate",
+            post(reasons::deactivate_report_reason),
+        )
+        .route(
+            "/admin/isrc-allowlist",
+            get(isrc::list_allowlist).post(isrc::add_isrc),
+        )
+        .route("/admin/isrc-allowlist/remove", post(isrc::remove_isrc))
+        .route("/admin/unresolve-htmx", post(admin::unresolve_flag_htmx))
+        .route("/admin/confirm", post(admin::confirm_flag))
+        .route("/admin/negate-seq", post(admin::negate_seq))
+        .route("/admin/rebroadcast", post(admin::rebroadcast))
+        .route("/admin/context", post(admin::store_context))
+        .route("/admin/context/edit", post(admin::edit_context))
+        .route("/admin/uri-history", get(admin::uri_history))
+        .route("/admin/label-state", get(flagged::label_state))
+        .route("/admin/uri-history-html", get(admin::uri_history_html))
+        .route("/admin/label-timestamps", get(admin::label_timestamps))
+        .route("/admin/active-labels", post(admin::get_active_labels))
+        .route("/admin/labels", post(admin::get_label_values))
+        .route("/admin/labels-by-value", post(admin::get_labels_by_value))
+        .route("/admin/negated-labels", post(admin::get_negated_labels))
+        .route(
+            "/admin/sensitive-images",
+            get(admin::list_sensitive_images).post(admin::add_sensitive_image),
+        )
+        .route(
+            "/admin/sensitive-images-html",
+            get(admin::list_sensitive_images_html),
+        )
+        .route(
+            "/admin/sensitive-images/remove",
+            post(admin::remove_sensitive_image),
+        )
+        .route(
+            "/admin/sensitive-images/reverify",
+            get(reverify::get_reverify_status).post(reverify::start_reverify),
+        )
+        .route(
+            "/admin/batches",
+            get(admin::list_batches).post(admin::create_batch),
+        )
+        .route("/admin/batches-html", get(admin::list_batches_html))
+        .route("/admin/batches/:id", delete(admin::delete_batch))
+        .route("/admin/batches/:id/claim", post(review::claim_batch))
+        .route("/admin/batches/:id/progress", get(review::batch_progress))
+        .route(
+            "/admin/batches/:id/progress-html",
+            get(review::batch_progress_html),
+        )
+        // Image moderation review queue
+        .route("/admin/image-review", get(images::list_image_review_queue))
+        .route(
+            "/admin/image-review/resolve",
+            post(images::resolve_image_review),
+        )
+        .route("/admin/image-scans", get(images::list_image_scans))
+        .route(
+            "/admin/image-scans-html",
+            get(images::list_image_scans_html),
+        )
+        .route(
+            "/admin/image-scans/override",
+            post(images::override_image_scan),
+        )
+        .route("/admin/search", get(search::search))
+        .route("/admin/audit", get(admin::list_audit_log))
+        .route("/admin/audit-html", get(admin::list_audit_log_html))
+        // Older name for /admin/audit, from before every action was logged
+        .route("/admin/audit-log", get(admin::list_audit_log))
+        // Webhook deliveries that ran out of attempts
+        .route(
+            "/admin/failed-deliveries",
+            get(webhook::list_failed_deliveries),
+        )
+        .route(
+            "/admin/failed-deliveries/:id/retry",
+            post(webhook::retry_failed_delivery),
+        )
+        // Full state snapshots (backups and migration)
+        .route("/admin/export", get(export::export_state))
+        .route("/admin/import", post(export::import_state))
+        // User reports
+        .route(
+            "/reports",
+            post(reports::create_report)
+                .layer(DefaultBodyLimit::max(reports::MAX_REPORT_BODY_BYTES)),
+        )
+        .route("/report-reasons", get(reasons::list_public_report_reasons))
+        .route("/admin/reports", get(reports::list_reports))
+        .route("/admin/reports-html", get(reports::list_reports_html))
+        .route("/admin/reports/stats", get(reports::get_report_stats))
+        .route("/admin/reporters/:did", get(reports::get_reporter_history))
+        .route(
+            "/admin/reporters/:did/summary-html",
+            get(reports::get_reporter_summary_html),
+        )
+        .route(
+            "/admin/reports/resolve-bulk",
+            post(reports::resolve_reports_bulk),
+        )
+        .route(
+            "/admin/reports/hash-reporters",
+            post(reporter_hash::hash_existing_reporters),
+        )
+        .route("/admin/reports/:id", get(reports::get_report))
+        .route(
+            "/admin/reports/:id/screenshot",
+            get(reports::get_report_screenshot),
+        )
+        .route("/admin/reports/:id/resolve", post(reports::resolve_report))
+        .route("/admin/reports/:id/label", post(reports::label_report))
+        .route(
+            "/admin/reports/:id/resolve-htmx",
+            post(reports::resolve_report_htmx),
+        )
+        // Review endpoints (under admin, auth protected)
+        .route("/admin/review/:id", get(review::review_page))
+        .route("/admin/review/:id/data", get(review::review_data))
+        .route("/admin/review/:id/submit", post(review::submit_review))
+        // Static files (CSS, JS for admin UI)
+        .nest_service("/static", ServeDir::new("static"))
+        // ATProto XRPC endpoints (public, limited per client IP)
+        .route(
+            "/xrpc/com.atproto.label.queryLabels",
+            get(xrpc::query_labels).layer(middleware::from_fn_with_state(
+                state.clone(),
+                ratelimit::limit_query_labels,
+            )),
+        )
+        .route(
+            "/xrpc/com.atproto.label.subscribeLabels",
+            get(xrpc::subscribe_labels).layer(middleware::from_fn_with_state(
+                state.clone(),
+                ratelimit::limit_subscribe_labels,
+            )),
+        )
+        .layer(middleware::from_fn(move |req, next| {
+            auth::auth_middleware(req, next, auth_tokens.clone())
+        }))
+        // gzip or brotli when the client sends Accept-Encoding. the default
+        // predicate skips images and small bodies, which covers WebSocket
+        // upgrades (their body is empty)
+        .layer(CompressionLayer::new())
+        .with_state(state)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use axum::{
+        body::Body,
+        http::{header, Method, Request, StatusCode},
+    };
+    use chrono::{Duration, Utc};
+    use tower::ServiceExt;
+
+    use super::*;
+    use crate::auth::{AuthTokens, Role};
+    use crate::claude::Severity;
+    use crate::db::{LabelContext, LabelDb, ResolutionReason};
+    use crate::did::DidResolver;
+    use crate::flagged::FlagStatusCache;
+    use crate::isrc::IsrcAllowlist;
+    use crate::labels::{Label, LabelSigner};
+    use crate::ratelimit::{
+        IpBlock, IpRateLimiter, RateLimiter, DEFAULT_CLIENT_IP_HEADER, RATE_LIMIT_WINDOW,
+    };
+    use crate::reporter_hash::{display_reporter, ReporterHasher};
+    use crate::reports::{ReportEscalation, ReportLimits, ReportStatsCache};
+    use crate::session::SessionKey;
+    use crate::stats::StatsCache;
+    use crate::webhook::{WebhookConfig, WebhookNotifier};
+
+    /// Label-changing requests each test credential may make per window.
+    const RATE_LIMIT: usize = 50;
+
+    /// `queryLabels` requests each test client IP may make per window.
+    const QUERY_LABELS_LIMIT: usize = 5;
+
+    /// Login attempts each test client IP may make per window.
+    const LOGIN_LIMIT: usize = 3;
+
+    /// Mutating admin endpoints, which read-only callers must not reach.
+    const ADMIN_ONLY: &[&str] = &[
+        "/emit-label",
+        "/reports",
+        "/admin/flags/rescan",
+        "/admin/resolve",
+        "/admin/resolve-htmx",
+        "/admin/resolve-bulk",
+        "/admin/unresolve",
+        "/admin/unresolve-htmx",
+        "/admin/resolution-reasons",
+        "/admin/resolution-reasons/deactivate",
+        "/admin/report-reasons",
+        "/admin/report-reasons/deactivate",
+        "/admin/isrc-allowlist",
+        "/admin/isrc-allowlist/remove",
+        "/admin/confirm",
+        "/admin/negate-seq",
+        "/admin/rebroadcast",
+        "/admin/context",
+        "/admin/context/edit",
+        "/admin/sensitive-images",
+        "/admin/sensitive-images/remove",
+        "/admin/sensitive-images/reverify",
+        "/admin/batches",
+        "/admin/batches/batch-1/claim",
+        "/admin/image-review/resolve",
+        "/admin/image-scans/override",
+        "/admin/reports/1/resolve",
+        "/admin/reports/1/resolve-htmx",
+        "/admin/reports/1/label",
+        "/admin/reports/resolve-bulk",
+        "/admin/reports/hash-reporters",
+        "/admin/review/batch-1/submit",
+        "/admin/import",
+        "/admin/failed-deliveries/1/retry",
+    ];
+
+    fn test_state() -> AppState {
+        AppState {
+            audd_api_token: String::new(),
+            audd_api_url: String::new(),
+            audd_fallback_enabled: false,
+            audd_fallback_url: String::new(),
+            db: None,
+            signer: None,
+            label_tx: None,
+            label_replay_tx: None,
+            claude: None,
+            image_base_url: None,
+            image_autoflag_severity: Severity::Medium,
+            image_second_opinion: false,
+            image_second_opinion_model: None,
+            image_refusal_as_unsafe: true,
+            public_url: String::new(),
+            webhook: None,
+            enforcement: None,
+            report_callback: None,
+            transcoder: None,
+            storage: None,
+            copyright_score_threshold: 30,
+            copyright_mix_song_threshold: 3,
+            isrc_allowlist: Arc::new(IsrcAllowlist::default()),
+            stats_cache: Arc::new(StatsCache::default()),
+            report_stats_cache: Arc::new(ReportStatsCache::default()),
+            flag_status_cache: Arc::new(FlagStatusCache::default()),
+            did_resolver: Arc::new(DidResolver::new("https://plc.directory")),
+            auth: AuthTokens {
+                admin: Some("admin-token".to_string()),
+                readonly: vec!["viewer-token".to_string()],
+                sessions: Some(SessionKey::new("test-secret", Duration::hours(12))),
+            },
+            rate_limiter: Arc::new(RateLimiter::new(RATE_LIMIT, RATE_LIMIT, RATE_LIMIT_WINDOW)),
+            ip_rate_limiter: Arc::new(IpRateLimiter::new(
+                QUERY_LABELS_LIMIT,
+                QUERY_LABELS_LIMIT * 2,
+                LOGIN_LIMIT,
+                vec!["10.0.0.0/8".parse::<IpBlock>().unwrap()],
+                Some(header::HeaderName::from_static(DEFAULT_CLIENT_IP_HEADER)),
+                RATE_LIMIT_WINDOW,
+            )),
+            report_limits: ReportLimits {
+                per_hour: 10,
+                per_day: 50,
+            },
+            report_escalation: ReportEscalation {
+                threshold: 5,
+                create_batch: false,
+            },
+            reporter_hasher: None,
+            review_batch_ttl: Duration::days(14),
+        }
+    }
+
+    fn app() -> Router {
+        router(test_state())
+    }
+
+    async fn status(method: Method, path: &str, token: &str) -> StatusCode {
+        let request = Request::builder()
+            .method(method)
+            .uri(path)
+            .header("Authorization", format!("Bearer {token}"))
+            .body(Body::empty())
+            .unwrap();
+        app().oneshot(request).await.unwrap().status()
+    }
+
+    #[tokio::test]
+    async fn test_readonly_token_cannot_mutate() {
+        for path in ADMIN_ONLY {
+            assert_eq!(
+                status(Method::POST, path, "viewer-token").await,
+                StatusCode::FORBIDDEN,
+                "{path} accepted a read-only token"
+            );
+        }
+        assert_eq!(
+            status(Method::DELETE, "/admin/batches/batch-1", "viewer-token").await,
+            StatusCode::FORBIDDEN
+        );
+        // a snapshot carries reporter DIDs and notes, so reading it is admin-only
+        assert_eq!(
+            status(Method::GET, "/admin/export", "viewer-token").await,
+            StatusCode::FORBIDDEN
+        );
+        // uploaded screenshots are only ever shown to admins
+        assert_eq!(
+            status(Method::GET, "/admin/reports/1/screenshot", "viewer-token").await,
+            StatusCode::FORBIDDEN
+        );
+    }
+
+    #[tokio::test]
+    async fn test_admin_token_passes_role_check() {
+        for path in ADMIN_ONLY {
+            assert_ne!(
+                status(Method::POST, path, "admin-token").await,
+                StatusCode::FORBIDDEN,
+                "{path} rejected the admin token"
+            );
+        }
+        assert_ne!(
+            status(Method::DELETE, "/admin/batches/batch-1", "admin-token").await,
+            StatusCode::FORBIDDEN
+        );
+        assert_ne!(
+            status(Method::GET, "/admin/export", "admin-token").await,
+            StatusCode::FORBIDDEN
+        );
+        assert_ne!(
+            status(Method::GET, "/admin/reports/1/screenshot", "admin-token").await,
+            StatusCode::FORBIDDEN
+        );
+    }
+
+    #[tokio::test]
+    async fn test_readonly_token_can_read() {
+        for path in [
+            "/admin/health",
+            "/admin/flags",
+            "/admin/summary-html",
+            "/admin/flags/detail?uri=at://did:plc:a/fm.plyr.track/b",
+            "/admin/flags/decisions-html?uri=at://did:plc:a/fm.plyr.track/b",
+            "/admin/label-state?uri=at://did:plc:a/fm.plyr.track/b",
+            "/admin/sensitive-images",
+            "/admin/sensitive-images/reverify",
+            "/admin/reports",
+            "/admin/audit",
+            "/admin/resolution-reasons",
+            "/admin/report-reasons",
+            "/admin/isrc-allowlist",
+            "/admin/batches",
+            "/admin/batches-html",
+            "/admin/search?q=did:plc:a",
+            "/admin/batches/batch-1/progress",
+            "/admin/batches/batch-1/progress-html",
+            "/is-flagged?uri=at://did:plc:a/fm.plyr.track/b",
+        ] {
+            let status = status(Method::GET, path, "viewer-token").await;
+            assert_ne!(status, StatusCode::FORBIDDEN, "{path}");
+            assert_ne!(status, StatusCode::UNAUTHORIZED, "{path}");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_session_cookie_authenticates() {
+        let login = Request::builder()
+            .method(Method::POST)
+            .uri("/admin/login")
+            .header("Content-Type", "application/json")
+            .body(Body::from(r#"{"token":"viewer-token"}"#))
+            .unwrap();
+        let response = app().oneshot(login).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let set_cookie = response.headers()["set-cookie"].to_str().unwrap();
+        assert!(set_cookie.contains("HttpOnly"));
+        let cookie = set_cookie.split(';').next().unwrap().to_string();
+
+        let with_cookie = |method: Method, path: &str, cookie: &str| {
+            Request::builder()
+                .method(method)
+                .uri(path)
+                .header("Cookie", cookie)
+                .body(Body::empty())
+                .unwrap()
+        };
+        let read = app()
+            .oneshot(with_cookie(Method::GET, "/admin/flags", &cookie))
+            .await
+            .unwrap();
+        assert_ne!(read.status(), StatusCode::UNAUTHORIZED);
+        let write = app()
+            .oneshot(with_cookie(Method::POST, "/admin/resolve", &cookie))
+            .await
+            .unwrap();
+        assert_eq!(write.status(), StatusCode::FORBIDDEN);
+
+        let expired = SessionKey::new("test-secret", Duration::hours(-1))
+            .issue(Role::Admin, Utc::now())
+            .0;
+        let response = app()
+            .oneshot(with_cookie(
+                Method::GET,
+                "/admin/flags",
+                &format!("mod_session={expired}"),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn test_logout_revokes_session() {
+        let app = app();
+        let login = Request::builder()
+            .method(Method::POST)
+            .uri("/admin/login")
+            .header("Content-Type", "application/json")
+            .body(Body::from(r#"{"token":"admin-token"}"#))
+            .unwrap();
+        let response = app.clone().oneshot(login).await.unwrap();
+        let set_cookie = response.headers()["set-cookie"].to_str().unwrap();
+        let cookie = set_cookie.split(';').next().unwrap().to_string();
+
+        let with_cookie = |method: Method, path: &str| {
+            Request::builder()
+                .method(method)
+                .uri(path)
+                .header("Cookie", &cookie)
+                .body(Body::empty())
+                .unwrap()
+        };
+        let logout = app
+            .clone()
+            .oneshot(with_cookie(Method::POST, "/admin/logout"))
+            .await
+            .unwrap();
+        assert_eq!(logout.status(), StatusCode::NO_CONTENT);
+
+        // a copy of the cookie kept past logout no longer authenticates
+        let replayed = app
+            .oneshot(with_cookie(Method::GET, "/admin/flags"))
+            .await
+            .unwrap();
+        assert_eq!(replayed.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn test_login_rejects_bad_token() {
+        let login = Request::builder()
+            .method(Method::POST)
+            .uri("/admin/login")
+            .header("Content-Type", "application/json")
+            .body(Body::from(r#"{"token":"nope"}"#))
+            .unwrap();
+        let response = app().oneshot(login).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        assert!(response.headers().get("set-cookie").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_label_endpoints_are_rate_limited() {
+        let app = app();
+        let emit = |token: &str| {
+            Request::builder()
+                .method(Method::POST)
+                .uri("/emit-label")
+                .header("Authorization", format!("Bearer {token}"))
+                .body(Body::empty())
+                .unwrap()
+        };
+        for _ in 0..RATE_LIMIT {
+            let response = app.clone().oneshot(emit("admin-token")).await.unwrap();
+            assert_ne!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        }
+
+        let response = app.clone().oneshot(emit("admin-token")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert!(response.headers().contains_key("retry-after"));
+
+        // read endpoints aren't limited
+        let read = Request::builder()
+            .uri("/admin/flags")
+            .header("Authorization", "Bearer admin-token")
+            .body(Body::empty())
+            .unwrap();
+        let response = app.oneshot(read).await.unwrap();
+        assert_ne!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+    }
+
+    #[tokio::test]
+    async fn test_public_label_endpoints_are_limited_per_ip() {
+        let app = app();
+        let xrpc = |method: &str, ip: &str| {
+            Request::builder()
+                .uri(format!("/xrpc/com.atproto.label.{method}?uriPatterns=*"))
+                .header(DEFAULT_CLIENT_IP_HEADER, ip)
+                .body(Body::empty())
+                .unwrap()
+        };
+        for _ in 0..QUERY_LABELS_LIMIT {
+            let response = app
+                .clone()
+                .oneshot(xrpc("queryLabels", "203.0.113.9"))
+                .await
+                .unwrap();
+            assert_ne!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        }
+
+        let response = app
+            .clone()
+            .oneshot(xrpc("queryLabels", "203.0.113.9"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert!(response.headers().contains_key("retry-after"));
+
+        // other clients, allowlisted addresses and subscriptions are counted
+        // separately
+        for (method, ip) in [
+            ("queryLabels", "198.51.100.4"),
+            ("queryLabels", "10.0.0.7"),
+            ("subscribeLabels", "203.0.113.9"),
+        ] {
+            let response = app.clone().oneshot(xrpc(method, ip)).await.unwrap();
+            assert_ne!(
+                response.status(),
+                StatusCode::TOO_MANY_REQUESTS,
+                "{method} {ip}"
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_login_limited_per_ip() {
+        let app = app();
+        let login = |ip: &str| {
+            Request::builder()
+                .method(Method::POST)
+                .uri("/admin/login")
+                .header("Content-Type", "application/json")
+                .header(DEFAULT_CLIENT_IP_HEADER, ip)
+                .body(Body::from(r#"{"token":"nope"}"#))
+                .unwrap()
+        };
+        for _ in 0..LOGIN_LIMIT {
+            let response = app.clone().oneshot(login("203.0.113.9")).await.unwrap();
+            assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        }
+
+        let response = app.clone().oneshot(login("203.0.113.9")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert!(response.headers().contains_key("retry-after"));
+
+        // other clients have their own count
+        let response = app.clone().oneshot(login("198.51.100.4")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    /// Gunzip a response body.
+    async fn gunzip(response: axum::response::Response) -> Vec<u8> {
+        use std::io::Read;
+
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let mut decoded = Vec::new();
+        flate2::read::GzDecoder::new(&bytes[..])
+            .read_to_end(&mut decoded)
+            .unwrap();
+        decoded
+    }
+
+    #[tokio::test]
+    async fn test_responses_are_compressed_when_accepted() {
+        let get = |encoding: Option<&str>| {
+            let mut request = Request::builder().uri("/");
+            if let Some(encoding) = encoding {
+                request = request.header(header::ACCEPT_ENCODING, encoding);
+            }
+            app().oneshot(request.body(Body::empty()).unwrap())
+        };
+
+        let plain = get(None).await.unwrap();
+        assert!(!plain.headers().contains_key(header::CONTENT_ENCODING));
+        let plain = axum::body::to_bytes(plain.into_body(), usize::MAX)
+            .await
+            .unwrap();
+
+        let gzipped = get(Some("gzip")).await.unwrap();
+        assert_eq!(gzipped.headers()[header::CONTENT_ENCODING], "gzip");
+        assert_eq!(gunzip(gzipped).await, plain);
+
+        let brotli = get(Some("br")).await.unwrap();
+        assert_eq!(brotli.headers()[header::CONTENT_ENCODING], "br");
+    }
+
+    /// Runs against `MODERATION_TEST_DATABASE_URL` when it's set; skipped
+    /// otherwise.
+    #[tokio::test]
+    async fn test_query_labels_compressed_body_is_valid() {
+        let Ok(url) = std::env::var("MODERATION_TEST_DATABASE_URL") else {
+            return;
+        };
+        let db = LabelDb::connect(&url).await.unwrap();
+        db.migrate().await.unwrap();
+
+        let signer = LabelSigner::from_hex(&format!("{:064x}", 1), "did:plc:labeler").unwrap();
+        let repo = format!("did:plc:compressed{}", rand::random::<u32>());
+        let mut stored = Vec::new();
+        for n in 0..200 {
+            let label = signer
+                .sign_label(Label::new(
+                    signer.did(),
+                    format!("at://{repo}/fm.plyr.track/{n}"),
+                    "copyright-violation",
+                ))
+                .unwrap();
+            db.store_label(&label).await.unwrap();
+            stored.push(label);
+        }
+
+        let mut state = test_state();
+        state.db = Some(Arc::new(db));
+        let app = router(state);
+        let query = |encoding: Option<&str>| {
+            let mut request = Request::builder().uri(format!(
+                "/xrpc/com.atproto.label.queryLabels?uriPatterns=at://{repo}/*&limit=250"
+            ));
+            if let Some(encoding) = encoding {
+                request = request.header(header::ACCEPT_ENCODING, encoding);
+            }
+            app.clone().oneshot(request.body(Body::empty()).unwrap())
+        };
+
+        let plain = query(None).await.unwrap();
+        assert_eq!(plain.status(), StatusCode::OK);
+        let plain = axum::body::to_bytes(plain.into_body(), usize::MAX)
+            .await
+            .unwrap();
+
+        let compressed = query(Some("gzip, br;q=0.5")).await.unwrap();
+        assert_eq!(compressed.status(), StatusCode::OK);
+        assert_eq!(compressed.headers()[header::CONTENT_ENCODING], "gzip");
+        assert_eq!(
+            compressed.headers()[header::CONTENT_TYPE],
+            "application/json"
+        );
+        let decoded = gunzip(compressed).await;
+        assert_eq!(decoded, plain);
+
+        // still a queryLabels output: the labels parse back as
+        // com.atproto.label.defs#label with their signatures intact
+        let body: serde_json::Value = serde_json::from_slice(&decoded).unwrap();
+        let labels: Vec<Label> = serde_json::from_value(body["labels"].clone()).unwrap();
+        assert_eq!(labels.len(), stored.len());
+        for label in &labels {
+            let original = stored.iter().find(|l| l.uri == label.uri).unwrap();
+            assert_eq!(label.src, original.src);
+            assert_eq!(label.val, original.val);
+            assert_eq!(label.cts, original.cts);
+            assert_eq!(label.sig, original.sig);
+        }
+    }
+
+    /// Runs against `MODERATION_TEST_DATABASE_URL` when it's set; skipped
+    /// otherwise.
+    #[tokio::test]
+    async fn test_resolve_htmx_returns_resolved_card() {
+        let Ok(url) = std::env::var("MODERATION_TEST_DATABASE_URL") else {
+            return;
+        };
+        let db = LabelDb::connect(&url).await.unwrap();
+        db.migrate().await.unwrap();
+
+        let signer = LabelSigner::from_hex(&format!("{:064x}", 1), "did:plc:labeler").unwrap();
+        let uri = format!(
+            "at://did:plc:resolvehtmx{}/fm.plyr.track/1",
+            rand::random::<u32>()
+        );
+        let flag = signer
+            .sign_label(Label::new(signer.did(), &uri, "copyright-violation"))
+            .unwrap();
+        let seq = db.store_label(&flag).await.unwrap();
+
+        let mut state = test_state();
+        state.db = Some(Arc::new(db));
+        state.signer = Some(Arc::new(signer));
+        let body = format!(
+            "uri={}&val=copyright-violation&reason=fingerprint_noise",
+            uri.replace(':', "%3A").replace('/', "%2F")
+        );
+        let request = Request::builder()
+            .method(Method::POST)
+            .uri("/admin/resolve-htmx")
+            .header("Authorization", "Bearer admin-token")
+            .header("Content-Type", "application/x-www-form-urlencoded")
+            .body(Body::from(body))
+            .unwrap();
+        let response = router(state).oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let html = String::from_utf8(body.to_vec()).unwrap();
+        assert!(html.contains(r#"<div id="toast" class="toast success" hx-swap-oob="true">resolved: fingerprint noise"#));
+        assert!(html.contains(&format!(
+            r#"<div class="flag-card resolved" id="flag-{seq}" hx-swap-oob="true">"#
+        )));
+    }
+
+    /// Runs against `MODERATION_TEST_DATABASE_URL` when it's set; skipped
+    /// otherwise.
+    #[tokio::test]
+    async fn test_report_create_list_resolve() {
+        let Ok(url) = std::env::var("MODERATION_TEST_DATABASE_URL") else {
+            return;
+        };
+        let db = LabelDb::connect(&url).await.unwrap();
+        db.migrate().await.unwrap();
+        let mut state = test_state();
+        state.db = Some(Arc::new(db));
+        let app = router(state);
+
+        let call = |method: Method, path: String, body: Option<serde_json::Value>| {
+            let app = app.clone();
+            async move {
+                let mut request = Request::builder()
+                    .method(method)
+                    .uri(path)
+                    .header("Authorization", "Bearer admin-token")
+                    .header("X-Moderation-Actor", "alice");
+                if body.is_some() {
+                    request = request.header("Content-Type", "application/json");
+                }
+                let body = body.map_or_else(Body::empty, |b| Body::from(b.to_string()));
+                let response = app.oneshot(request.body(body).unwrap()).await.unwrap();
+                assert_eq!(response.status(), StatusCode::OK);
+                let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+                    .await
+                    .unwrap();
+                serde_json::from_slice::<serde_json::Value>(&bytes).unwrap()
+            }
+        };
+
+        let run = rand::random::<u32>();
+        let reporter = format!("did:plc:reporter{run}");
+        let file = |reporter: String| {
+            call(
+                Method::POST,
+                "/reports".to_string(),
+                Some(serde_json::json!({
+                    "reporter_did": reporter,
+                    "target_type": "track",
+                    "target_id": format!("track{run}"),
+                    "reason": "copyright",
+                    "description": "this is my song",
+                })),
+            )
+        };
+        let created = file(reporter.clone()).await;
+        let id = created["report_id"].as_i64().unwrap();
+        assert_eq!(created["duplicate"], false);
+
+        // a second reporter is added to the open report
+        let supported = file(format!("did:plc:supporter{run}")).await;
+        assert_eq!(supported["report_id"], id);
+        assert_eq!(supported["duplicate"], true);
+        assert!(supported.get("message").is_none());
+
+        // re-reporting is a no-op
+        let again = file(reporter.clone()).await;
+        assert_eq!(again["report_id"], id);
+        assert!(again["message"].is_string());
+
+        let open = call(
+            Method::GET,
+            "/admin/reports?status=open&limit=100".to_string(),
+            None,
+        )
+        .await;
+        assert!(open["reports"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .any(|r| r["id"] == id
+                && r["reporter_did"] == reporter.as_str()
+                && r["supporter_count"] == 1));
+
+        let resolved = call(
+            Method::POST,
+            format!("/admin/reports/{id}/resolve"),
+            Some(serde_json::json!({
+                "status": "resolved",
+                "admin_notes": "label emitted",
+                "resolved_by": "alice",
+            })),
+        )
+        .await;
+        assert_eq!(resolved["status"], "resolved");
+
+        let report = call(Method::GET, format!("/admin/reports/{id}"), None).await;
+        assert_eq!(report["status"], "resolved");
+        assert_eq!(report["admin_notes"], "label emitted");
+        assert!(report["resolved_at"].is_string());
+        assert_eq!(report["supporter_count"], 1);
+
+        // once resolved, the same report starts a new one
+        let refiled = file(format!("did:plc:supporter{run}")).await;
+        assert_ne!(refiled["report_id"], id);
+        assert_eq!(refiled["duplicate"], false);
+    }
+
+    /// Runs against `MODERATION_TEST_DATABASE_URL` when it's set; skipped
+    /// otherwise.
+    #[tokio::test]
+    async fn test_reports_limited_per_reporter() {
+        let Ok(url) = std::env::var("MODERATION_TEST_DATABASE_URL") else {
+            return;
+        };
+        let db = LabelDb::connect(&url).await.unwrap();
+        db.migrate().await.unwrap();
+        let mut state = test_state();
+        state.db = Some(Arc::new(db));
+        state.report_limits = ReportLimits {
+            per_hour: 2,
+            per_day: 50,
+        };
+        let app = router(state);
+
+        // each filing is about a different track, so none is a duplicate
+        let file = |reporter: String, track: u32, description: String| {
+            let app = app.clone();
+            async move {
+                let body = serde_json::json!({
+                    "reporter_did": reporter,
+                    "target_type": "track",
+                    "target_id": track.to_string(),
+                    "reason": "spam",
+                    "description": description,
+                });
+                let request = Request::builder()
+                    .method(Method::POST)
+                    .uri("/reports")
+                    .header("Authorization", "Bearer admin-token")
+                    .header("Content-Type", "application/json")
+                    .body(Body::from(body.to_string()))
+                    .unwrap();
+                app.oneshot(request).await.unwrap()
+            }
+        };
+
+        let run = rand::random::<u32>();
+        let reporter = format!("did:plc:busy{run}");
+        for track in 0..2 {
+            let response = file(reporter.clone(), track, "spam".to_string()).await;
+            assert_eq!(response.status(), StatusCode::OK);
+        }
+        let limited = file(reporter.clone(), 2, "spam".to_string()).await;
+        assert_eq!(limited.status(), StatusCode::TOO_MANY_REQUESTS);
+        let retry_after: u64 = limited.headers()["retry-after"]
+            .to_str()
+            .unwrap()
+            .parse()
+            .unwrap();
+        assert!((1..=3600).contains(&retry_after));
+
+        // the limit is per reporter, and oversized fields fail before counting
+        let other = format!("did:plc:quiet{run}");
+        let response = file(other.clone(), 3, "x".repeat(2001)).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let response = file(other, 3, "spam".to_string()).await;
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    /// Runs against `MODERATION_TEST_DATABASE_URL` when it's set; skipped otherwise.
+    #[tokio::test]
+    async fn test_report_escalates_at_threshold() {
+        let Ok(url) = std::env::var("MODERATION_TEST_DATABASE_URL") else {
+            return;
+        };
+        let db = LabelDb::connect(&url).await.unwrap();
+        db.migrate().await.unwrap();
+        let db = Arc::new(db);
+        // nothing listens here, so the escalation webhook stays undelivered
+        let webhook = |url: &str| {
+            Arc::new(WebhookNotifier::spawn(
+                WebhookConfig {
+                    url: url.to_string(),
+                    secret: None,
+                    image_flagged_enabled: false,
+                    report_escalated_enabled: true,
+                },
+                Some(db.clone()),
+            ))
+        };
+        let mut state = test_state();
+        state.db = Some(db.clone());
+        state.webhook = Some(webhook("http://127.0.0.1:9/hook"));
+        state.report_escalation = ReportEscalation {
+            threshold: 3,
+            create_batch: true,
+        };
+        let app = router(state.clone());
+
+        let call = |method: Method, path: String, body: Option<serde_json::Value>| {
+            let app = app.clone();
+            async move {
+                let mut request = Request::builder()
+                    .method(method)
+                    .uri(path)
+                    .header("Authorization", "Bearer admin-token");
+                if body.is_some() {
+                    request = request.header("Content-Type", "application/json");
+                }
+                let body = body.map_or_else(Body::empty, |b| Body::from(b.to_string()));
+                let response = app.oneshot(request.body(body).unwrap()).await.unwrap();
+                assert_eq!(response.status(), StatusCode::OK);
+                let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+                    .await
+                    .unwrap();
+                serde_json::from_slice::<serde_json::Value>(&bytes).unwrap()
+            }
+        };
+
+        let run = rand::random::<u32>();
+        let file = |n: u32| {
+            call(
+                Method::POST,
+                "/reports".to_string(),
+                Some(serde_json::json!({
+                    "reporter_did": format!("did:plc:reporter{run}x{n}"),
+                    "target_type": "track",
+                    "target_id": format!("track{run}"),
+                    "target_uri": format!("at://did:plc:artist{run}/fm.plyr.track/abc"),
+                    "reason": "copyright",
+                })),
+            )
+        };
+        let id = file(1).await["report_id"].as_i64().unwrap();
+        file(2).await;
+        let report = call(Method::GET, format!("/admin/reports/{id}"), None).await;
+        assert_eq!(report["status"], "open");
+
+        // the third distinct reporter crosses the threshold
+        let third = file(3).await;
+        assert_eq!(third["report_id"], id);
+        let report = call(Method::GET, format!("/admin/reports/{id}"), None).await;
+        assert_eq!(report["status"], "escalated");
+
+        // later reporters still join the escalated report, which escalates once
+        let fourth = file(4).await;
+        assert_eq!(fourth["report_id"], id);
+        assert_eq!(fourth["duplicate"], true);
+        let audit = call(
+            Method::GET,
+            format!("/admin/audit-log?action=escalate_report&target={id}"),
+            None,
+        )
+        .await;
+        let entries = audit["entries"].as_array().unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0]["details"]["reporters"], 3);
+        let batch_id = entries[0]["details"]["batch_id"].as_str().unwrap();
+
+        let batches = call(Method::GET, "/admin/batches?limit=200".to_string(), None).await;
+        assert!(batches["batches"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .any(|b| b["id"] == batch_id && b["total"] == 1));
+
+        // the webhook was kept with the escalation, before any attempt
+        let deliveries = call(
+            Method::GET,
+            "/admin/failed-deliveries?limit=200".to_string(),
+            None,
+        )
+        .await;
+        let delivery = deliveries["deliveries"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|d| d["payload"]["data"]["report_id"] == id)
+            .expect("the escalation webhook is kept")
+            .clone();
+        assert_eq!(delivery["event"], "report.escalated");
+        assert_eq!(delivery["target"], "http://127.0.0.1:9/hook");
+        assert_eq!(delivery["payload"]["data"]["reporters"], 3);
+        assert_eq!(delivery["payload"]["data"]["batch_id"], batch_id);
+
+        // once the webhook points elsewhere, retrying it is refused
+        state.webhook = Some(webhook("http://127.0.0.1:9/other"));
+        let request = Request::builder()
+            .method(Method::POST)
+            .uri(format!("/admin/failed-deliveries/{}/retry", delivery["id"]))
+            .header("Authorization", "Bearer admin-token")
+            .body(Body::empty())
+            .unwrap();
+        let response = router(state).oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+    }
+
+    /// Runs against `MODERATION_TEST_DATABASE_URL` when it's set; skipped otherwise.
+    #[tokio::test]
+    async fn test_label_report_emits_and_resolves() {
+        let Ok(url) = std::env::var("MODERATION_TEST_DATABASE_URL") else {
+            return;
+        };
+        let db = Arc::new(LabelDb::connect(&url).await.unwrap());
+        db.migrate().await.unwrap();
+        let signer = LabelSigner::from_hex(&format!("{:064x}", 1), "did:plc:labeler").unwrap();
+        let mut state = test_state();
+        state.db = Some(db.clone());
+        state.signer = Some(Arc::new(signer));
+        let app = router(state);
+
+        let run = rand::random::<u32>();
+        let uri = format!("at://did:plc:labelreport{run}/fm.plyr.track/1");
+        let file = |target_uri: Option<&str>| {
+            let db = db.clone();
+            let target_uri = target_uri.map(str::to_string);
+            async move {
+                match db
+                    .file_report(
+                        &format!("did:plc:reporter{run}"),
+                        None,
+                        "track",
+                        &format!("labelreport{run}-{}", target_uri.is_some()),
+                        None,
+                        None,
+                        target_uri.as_deref(),
+                        "copyright",
+                        None,
+                        None,
+                        None,
+                    )
+                    .await
+                    .unwrap()
+                {
+                    crate::db::FiledReport::Created(report) => report.id,
+                    other => panic!("expected a new report, got {other:?}"),
+                }
+            }
+        };
+        let label = |id: i32| {
+            let app = app.clone();
+            async move {
+                let request = Request::builder()
+                    .method(Method::POST)
+                    .uri(format!("/admin/reports/{id}/label"))
+                    .header("Authorization", "Bearer admin-token")
+                    .header("X-Moderation-Actor", "alice")
+                    .header("Content-Type", "application/json")
+                    .body(Body::from("{}"))
+                    .unwrap();
+                let response = app.oneshot(request).await.unwrap();
+                let status = response.status();
+                let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+                    .await
+                    .unwrap();
+                (
+                    status,
+                    serde_json::from_slice::<serde_json::Value>(&bytes).unwrap(),
+                )
+            }
+        };
+
+        // copyright reports default to copyright-violation
+        let id = file(Some(&uri)).await;
+        let (status, body) = label(id).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["val"], "copyright-violation");
+        assert_eq!(body["created"], true);
+        assert_eq!(body["report"]["status"], "resolved");
+        assert_eq!(body["report"]["resolved_by"], "alice");
+        assert_eq!(body["report"]["label_seq"], body["seq"]);
+        let active = db
+            .get_active_label("did:plc:labeler", &uri, "copyright-violation")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(Some(active.seq), body["seq"].as_i64());
+
+        // a resolved report can't be labeled again
+        let (status, _) = label(id).await;
+        assert_eq!(status, StatusCode::CONFLICT);
+
+        // no URI, no label
+        let id = file(None).await;
+        let (status, body) = label(id).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert!(body["message"].as_str().unwrap().contains("/emit-label"));
+    }
+
+    /// Runs against `MODERATION_TEST_DATABASE_URL` when it's set; skipped otherwise.
+    #[tokio::test]
+    async fn test_rebroadcast_pushes_stored_seqs() {
+        let Ok(url) = std::env::var("MODERATION_TEST_DATABASE_URL") else {
+            return;
+        };
+        let db = LabelDb::connect(&url).await.unwrap();
+        db.migrate().await.unwrap();
+
+        let signer = LabelSigner::from_hex(&format!("{:064x}", 1), "did:plc:labeler").unwrap();
+        let uri = format!(
+            "at://did:plc:rebroadcast{}/fm.plyr.track/1",
+            rand::random::<u32>()
+        );
+        let mut seqs = Vec::new();
+        for val in ["copyright-review", "copyright-violation", "sensitive-image"] {
+            let label = signer
+                .sign_label(Label::new(signer.did(), &uri, val))
+                .unwrap();
+            seqs.push(db.store_label(&label).await.unwrap());
+        }
+
+        let (tx, mut rx) = tokio::sync::broadcast::channel(16);
+        let mut state = test_state();
+        state.db = Some(Arc::new(db));
+        state.label_replay_tx = Some(tx);
+        let request = Request::builder()
+            .method(Method::POST)
+            .uri("/admin/rebroadcast")
+            .header("Authorization", "Bearer admin-token")
+            .header("Content-Type", "application/json")
+            .body(Body::from(format!(
+                r#"{{"since_seq": {}, "limit": 10}}"#,
+                seqs[0]
+            )))
+            .unwrap();
+        let response = router(state).oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(body["subscribers"], 1);
+
+        // other tests may store labels concurrently, so ours are a subsequence
+        let sent: Vec<i64> = body["seqs"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|s| s.as_i64().unwrap())
+            .collect();
+        assert!(sent.windows(2).all(|w| w[0] < w[1]));
+        assert!(sent.iter().all(|&s| s > seqs[0]));
+        assert!(sent.contains(&seqs[1]) && sent.contains(&seqs[2]));
+
+        for &expected in &sent {
+            let (seq, label) = rx.try_recv().unwrap();
+            assert_eq!(seq, expected);
+            if seq == seqs[2] {
+                assert_eq!(label.uri, uri);
+                assert_eq!(label.val, "sensitive-image");
+                assert!(label.sig.is_some());
+            }
+        }
+        assert!(rx.try_recv().is_err());
+    }
+
+    /// Runs against `MODERATION_TEST_DATABASE_URL` when it's set; skipped otherwise.
+    #[tokio::test]
+    async fn test_report_listing_offset_is_deprecated() {
+        let Ok(url) = std::env::var("MODERATION_TEST_DATABASE_URL") else {
+            return;
+        };
+        let db = LabelDb::connect(&url).await.unwrap();
+        db.migrate().await.unwrap();
+        let mut state = test_state();
+        state.db = Some(Arc::new(db));
+        let app = router(state);
+        let get = |uri: &str| {
+            let request = Request::builder()
+                .uri(uri)
+                .header("Authorization", "Bearer viewer-token")
+                .body(Body::empty())
+                .unwrap();
+            app.clone().oneshot(request)
+        };
+
+        let response = get("/admin/reports?limit=1").await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(response.headers().get("deprecation").is_none());
+
+        let response = get("/admin/reports?limit=1&offset=1").await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()["deprecation"], "true");
+        assert!(response.headers()[header::WARNING]
+            .to_str()
+            .unwrap()
+            .contains("cursor"));
+
+        let response = get("/admin/reports?cursor=1760600000123456:7&offset=1")
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let response = get("/admin/reports?cursor=yesterday").await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    /// Runs against `MODERATION_TEST_DATABASE_URL` when it's set; skipped otherwise.
+    #[tokio::test]
+    async fn test_report_reasons_seeded_and_inactive_rejected() {
+        let Ok(url) = std::env::var("MODERATION_TEST_DATABASE_URL") else {
+            return;
+        };
+        let db = Arc::new(LabelDb::connect(&url).await.unwrap());
+        db.migrate().await.unwrap();
+        // seeding is idempotent
+        db.migrate().await.unwrap();
+        let keys: Vec<String> = db
+            .list_report_reasons(false)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|r| r.key)
+            .collect();
+        for key in ["copyright", "abuse", "spam", "explicit", "other"] {
+            assert_eq!(keys.iter().filter(|k| *k == key).count(), 1, "{key}");
+        }
+
+        let mut state = test_state();
+        state.db = Some(db.clone());
+        let app = router(state);
+        let run = rand::random::<u32>();
+        let key = format!("reasontest{run}");
+        let file = |reason: String| {
+            let app = app.clone();
+            async move {
+                let body = serde_json::json!({
+                    "reporter_did": format!("did:plc:reasons{run}"),
+                    "target_type": "track",
+                    "target_id": format!("reasons{run}-{reason}"),
+                    "reason": reason,
+                });
+                let request = Request::builder()
+                    .method(Method::POST)
+                    .uri("/reports")
+                    .header("Authorization", "Bearer admin-token")
+                    .header("Content-Type", "application/json")
+                    .body(Body::from(body.to_string()))
+                    .unwrap();
+                app.oneshot(request).await.unwrap().status()
+            }
+        };
+
+        db.upsert_report_reason(&key, "test reason", "", false)
+            .await
+            .unwrap();
+        assert_eq!(file(key.clone()).await, StatusCode::OK);
+
+        // once deactivated, new reports can't use it
+        assert!(db.deactivate_report_reason(&key).await.unwrap());
+        assert_eq!(file(key.clone()).await, StatusCode::BAD_REQUEST);
+        assert!(!db
+            .list_report_reasons(false)
+            .await
+            .unwrap()
+            .iter()
+            .any(|r| r.key == key));
+        assert_eq!(file(format!("missing{run}")).await, StatusCode::BAD_REQUEST);
+    }
+
+    /// Runs against `MODERATION_TEST_DATABASE_URL` when it's set; skipped otherwise.
+    #[tokio::test]
+    async fn test_export_import_round_trip() {
+        let Ok(url) = std::env::var("MODERATION_TEST_DATABASE_URL") else {
+            return;
+        };
+        let db = LabelDb::connect(&url).await.unwrap();
+        db.migrate().await.unwrap();
+
+        // a small dataset tagged with this run: a label with a resolved
+        // context, a sensitive image and a supported report
+        let tag = format!("export{}", rand::random::<u32>());
+        let uri = format!("at://did:plc:{tag}/fm.plyr.track/1");
+        let signer = LabelSigner::from_hex(&format!("{:064x}", 1), "did:plc:labeler").unwrap();
+        let label = signer
+            .sign_label(Label::new(signer.did(), &uri, "copyright-violation"))
+            .unwrap();
+        db.store_label(&label).await.unwrap();
+        let context = |notes: &str| LabelContext {
+            track_title: Some("Exported".to_string()),
+            resolution_reason: Some(ResolutionReason::FingerprintNoise),
+            resolution_notes: Some(notes.to_string()),
+            ..Default::default()
+        };
+        db.store_context(&uri, &context("before export"))
+            .await
+            .unwrap();
+        db.add_sensitive_image(
+            Some(&tag),
+            None,
+            Some("round trip"),
+            Some("alice"),
+            Some("high"),
+            &["violence".to_string()],
+        )
+        .await
+        .unwrap();
+
+        let mut state = test_state();
+        state.db = Some(Arc::new(db.clone()));
+        let app = router(state);
+        let call = |method: Method, path: String, body: String| {
+            let app = app.clone();
+            async move {
+                let request = Request::builder()
+                    .method(method)
+                    .uri(path)
+                    .header("Authorization", "Bearer admin-token")
+                    .header("X-Moderation-Actor", "alice")
+                    .header("Content-Type", "application/json")
+                    .body(Body::from(body))
+                    .unwrap();
+                let response = app.oneshot(request).await.unwrap();
+                assert_eq!(response.status(), StatusCode::OK);
+                let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+                    .await
+                    .unwrap();
+                String::from_utf8(bytes.to_vec()).unwrap()
+            }
+        };
+
+        let mut report_id = None;
+        for reporter in ["reporter", "supporter"] {
+            let filed = call(
+                Method::POST,
+                "/reports".to_string(),
+                serde_json::json!({
+                    "reporter_did": format!("did:plc:{reporter}{tag}"),
+                    "target_type": "track",
+                    "target_id": tag,
+                    "reason": "copyright",
+                })
+                .to_string(),
+            )
+            .await;
+            let filed: serde_json::Value = serde_json::from_str(&filed).unwrap();
+            report_id = filed["report_id"].as_i64();
+        }
+        let report_id = report_id.unwrap();
+
+        // the header, then only this run's lines (the database is shared)
+        let snapshot = || {
+            let (call, tag) = (&call, &tag);
+            async move {
+                let export = call(Method::GET, "/admin/export".to_string(), String::new()).await;
+                let mut lines = export.lines();
+                let header = lines.next().unwrap().to_string();
+                let ours: Vec<String> = lines
+                    .filter(|line| line.contains(tag.as_str()))
+                    .map(String::from)
+                    .collect();
+                (header, ours)
+            }
+        };
+        let (header, exported) = snapshot().await;
+        assert!(header.starts_with(r#"{"kind":"header","version":1"#));
+        let kind = |line: &String| {
+            serde_json::from_str::<serde_json::Value>(line).unwrap()["kind"].clone()
+        };
+        let kinds: Vec<_> = exported.iter().map(kind).collect();
+        assert_eq!(
+            kinds,
+            ["label", "label_context", "sensitive_image", "report"]
+        );
+        let audit = call(
+            Method::GET,
+            "/admin/audit-log?action=export_state&actor=alice".to_string(),
+            String::new(),
+        )
+        .await;
+        let audit: serde_json::Value = serde_json::from_str(&audit).unwrap();
+        assert!(!audit["entries"].as_array().unwrap().is_empty());
+
+        // change what the snapshot holds, then restore it; a second import
+        // changes nothing
+        db.store_context(&uri, &context("after export"))
+            .await
+            .unwrap();
+        call(
+            Method::POST,
+            format!("/admin/reports/{report_id}/resolve"),
+            serde_json::json!({"status": "dismissed", "resolved_by": "alice"}).to_string(),
+        )
+        .await;
+        let document = format!("{header}\n{}\n", exported.join("\n"));
+        for _ in 0..2 {
+            let imported = call(Method::POST, "/admin/import".to_string(), document.clone()).await;
+            let imported: serde_json::Value = serde_json::from_str(&imported).unwrap();
+            assert_eq!(
+                imported["imported"],
+                serde_json::json!({
+                    "labels": 1,
+                    "label_contexts": 1,
+                    "sensitive_images": 1,
+                    "reports": 1,
+                })
+            );
+        }
+
+        let (_, restored) = snapshot().await;
+        assert_eq!(restored, exported);
+        assert!(restored[1].contains("before export"));
+        assert!(restored[3].contains(r#""status":"open""#));
+        assert!(restored[3].contains(&format!(r#""supporters":["did:plc:supporter{tag}"]"#)));
+    }
+
+    #[tokio::test]
+    async fn test_hashing_existing_reporters_needs_the_flag() {
+        let request = Request::builder()
+            .method(Method::POST)
+            .uri("/admin/reports/hash-reporters")
+            .header("Authorization", "Bearer admin-token")
+            .body(Body::empty())
+            .unwrap();
+        let response = app().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+    }
+
+    /// Runs against `MODERATION_TEST_DATABASE_URL` when it's set; skipped otherwise.
+    #[tokio::test]
+    async fn test_reports_store_hashed_reporters() {
+        let Ok(url) = std::env::var("MODERATION_TEST_DATABASE_URL") else {
+            return;
+        };
+        let db = LabelDb::connect(&url).await.unwrap();
+        db.migrate().await.unwrap();
+        let hasher = ReporterHasher::new("test-secret");
+        let mut state = test_state();
+        state.db = Some(Arc::new(db));
+        state.reporter_hasher = Some(hasher.clone());
+        let app = router(state);
+
+        let send = |method: Method, path: String, body: Option<serde_json::Value>| {
+            let app = app.clone();
+            async move {
+                let mut request = Request::builder()
+                    .method(method)
+                    .uri(path)
+                    .header("Authorization", "Bearer admin-token");
+                if body.is_some() {
+                    request = request.header("Content-Type", "application/json");
+                }
+                let body = body.map_or_else(Body::empty, |b| Body::from(b.to_string()));
+                let response = app.oneshot(request.body(body).unwrap()).await.unwrap();
+                assert_eq!(response.status(), StatusCode::OK);
+                axum::body::to_bytes(response.into_body(), usize::MAX)
+                    .await
+                    .unwrap()
+            }
+        };
+        let call = |method: Method, path: String, body: Option<serde_json::Value>| {
+            let response = send(method, path, body);
+            async move { serde_json::from_slice::<serde_json::Value>(&response.await).unwrap() }
+        };
+
+        let run = rand::random::<u32>();
+        let reporter = format!("did:plc:private{run}");
+        let hash = hasher.hash(&reporter);
+        let file = || {
+            call(
+                Method::POST,
+                "/reports".to_string(),
+                Some(serde_json::json!({
+                    "reporter_did": reporter,
+                    "reporter_handle": "private.bsky.social",
+                    "target_type": "track",
+                    "target_id": format!("track{run}"),
+                    "reason": "spam",
+                })),
+            )
+        };
+        let created = file().await;
+        let id = created["report_id"].as_i64().unwrap();
+        assert_eq!(created["reporter_hash"], hash.as_str());
+
+        // duplicate detection works on the hash
+        let again = file().await;
+        assert_eq!(again["report_id"], id);
+        assert!(again["message"].is_string());
+        assert_eq!(again["reporter_hash"], hash.as_str());
+
+        let report = call(Method::GET, format!("/admin/reports/{id}"), None).await;
+        assert_eq!(report["reporter_did"], hash.as_str());
+        assert!(report["reporter_handle"].is_null());
+
+        // history answers to the hash and to the DID it came from
+        for key in [&hash, &reporter] {
+            let history = call(Method::GET, format!("/admin/reporters/{key}"), None).await;
+            assert_eq!(history["reporter_did"], hash.as_str());
+            assert_eq!(history["total"], 1);
+        }
+
+        let html = send(
+            Method::GET,
+            format!("/admin/reports-html?status=open&target_id=track{run}"),
+            None,
+        )
+        .await;
+        let html = String::from_utf8_lossy(&html);
+        assert!(html.contains(display_reporter(&hash)));
+        assert!(!html.contains(&reporter));
+    }
+}
@@ -0,0 +1,299 @@
+//! Search across copyright flags and user reports for one account.
+//!
+//! `GET /admin/search?q=` takes an artist DID or handle and returns the
+//! account's flagged tracks and the reports about (or filed by) it as one
+//! HTML list. Results are grouped by target, so a track that was both flagged
+//! and reported shows up once.
+
+use std::collections::HashMap;
+
+use axum::{
+    extract::{Query, State},
+    http::header::CONTENT_TYPE,
+    response::{IntoResponse, Response},
+};
+use serde::Deserialize;
+
+use crate::admin::{html_escape, render_flag_card, FlaggedTrack};
+use crate::db::UserReport;
+use crate::reports::render_report_card;
+use crate::state::{AppError, AppState};
+
+/// Most flags, and most reports, returned for one search.
+const SEARCH_LIMIT: i64 = 100;
+
+/// Query parameters for search.
+#[derive(Debug, Deserialize)]
+pub struct SearchParams {
+    #[serde(default)]
+    pub q: String,
+}
+
+/// What a search box entry refers to.
+#[derive(Debug, PartialEq, Eq)]
+enum SearchTerm {
+    Did(String),
+    /// Lowercased, without a leading `@`.
+    Handle(String),
+}
+
+impl SearchTerm {
+    fn parse(q: &str) -> Option<Self> {
+        let q = q.trim().trim_start_matches('@');
+        if q.is_empty() {
+            None
+        } else if q.starts_with("did:") {
+            Some(Self::Did(q.to_string()))
+        } else {
+            Some(Self::Handle(q.to_ascii_lowercase()))
+        }
+    }
+}
+
+/// Everything found for one target: its flag, if it's a flagged track, and
+/// the reports about it.
+#[derive(Debug)]
+struct TargetResults {
+    target: String,
+    flag: Option<FlaggedTrack>,
+    reports: Vec<UserReport>,
+}
+
+/// Search flags and reports for an artist DID or handle, as HTML for htmx.
+pub async fn search(
+    State(state): State<AppState>,
+    Query(params): Query<SearchParams>,
+) -> Result<Response, AppError> {
+    let db = state.db.as_ref().ok_or(AppError::LabelerNotConfigured)?;
+
+    let html = match SearchTerm::parse(&params.q) {
+        None => r#"<div class="empty">search by artist DID or handle</div>"#.to_string(),
+        Some(term) => {
+            // a handle only appears in flag context and some reports, so
+            // look up the DIDs it's known by to find the rest
+            let (dids, handle) = match &term {
+                SearchTerm::Did(did) => (vec![did.clone()], None),
+                SearchTerm::Handle(handle) => (
+                    db.artist_dids_for_handle(handle).await?,
+                    Some(handle.as_str()),
+                ),
+            };
+            let flags = db.search_flags(&dids, handle, SEARCH_LIMIT).await?;
+            // reports they filed are stored under their hash when hashing is on
+            let mut report_dids = dids.clone();
+            if let Some(hasher) = &state.reporter_hasher {
+                report_dids.extend(dids.iter().map(|did| hasher.hash(did)));
+            }
+            let reports = db
+                .search_reports(&report_dids, handle, SEARCH_LIMIT)
+                .await?;
+            render_search_results(params.q.trim(), &group_by_target(flags, reports))
+        }
+    };
+
+    Ok(([(CONTENT_TYPE, "text/html; charset=utf-8")], html).into_response())
+}
+
+/// Group flags and reports by target. Flags come first, newest first, and
+/// only the newest flag per track is kept; reports join their track's group
+/// by URI, or by track id when the report has no URI.
+fn group_by_target(flags: Vec<FlaggedTrack>, reports: Vec<UserReport>) -> Vec<TargetResults> {
+    let mut groups: Vec<TargetResults> = Vec::new();
+    let mut index: HashMap<String, usize> = HashMap::new();
+    let mut track_uris: HashMap<String, String> = HashMap::new();
+
+    for flag in flags {
+        if index.contains_key(&flag.uri) {
+            continue;
+        }
+        if let Some(track_id) = flag.context.as_ref().and_then(|c| c.track_id) {
+            track_uris.insert(track_id.to_string(), flag.uri.clone());
+        }
+        index.insert(flag.uri.clone(), groups.len());
+        groups.push(TargetResults {
+            target: flag.uri.clone(),
+            flag: Some(flag),
+            reports: Vec::new(),
+        });
+    }
+
+    for report in reports {
+        let target = report_target(&report, &track_uris);
+        match index.get(&target) {
+            Some(&i) => groups[i].reports.push(report),
+            None => {
+                index.insert(target.clone(), groups.len());
+                groups.push(TargetResults {
+                    target,
+                    flag: None,
+                    reports: vec![report],
+                });
+            }
+        }
+    }
+
+    groups
+}
+
+/// The key a report is grouped under.
+fn report_target(report: &UserReport, track_uris: &HashMap<String, String>) -> String {
+    if let Some(uri) = &report.target_uri {
+        return uri.clone();
+    }
+    if report.target_type == "track" {
+        if let Some(uri) = track_uris.get(&report.target_id) {
+            return uri.clone();
+        }
+    }
+    format!("{}:{}", report.target_type, report.target_id)
+}
+
+fn render_search_results(query: &str, groups: &[TargetResults]) -> String {
+    if groups.is_empty() {
+        return format!(
+            r#"<div class="empty">no flags or reports for {}</div>"#,
+            html_escape(query)
+        );
+    }
+
+    let flag_count = groups.iter().filter(|g| g.flag.is_some()).count();
+    let report_count: usize = groups.iter().map(|g| g.reports.len()).sum();
+    let sections: Vec<String> = groups
+        .iter()
+        .map(|group| {
+            let flag_html = group
+                .flag
+                .as_ref()
+                .map(render_flag_card)
+                .unwrap_or_default();
+            let reports_html: Vec<String> = group.reports.iter().map(render_report_card).collect();
+            format!(
+                r#"<section class="search-target">
+                    <div class="search-target-head"><code>{}</code></div>
+                    {}
+                    {}
+                </section>"#,
+                html_escape(&group.target),
+                flag_html,
+                reports_html.join("\n")
+            )
+        })
+        .collect();
+
+    format!(
+        r#"<div class="filter-count">{} flagged tracks · {} reports · {} targets</div>
+        {}"#,
+        flag_count,
+        report_count,
+        groups.len(),
+        sections.join("\n")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::LabelContext;
+
+    const TRACK: &str = "at://did:plc:artist/fm.plyr.track/abc";
+
+    fn flag(seq: i64, uri: &str, track_id: i64) -> FlaggedTrack {
+        FlaggedTrack {
+            seq,
+            uri: uri.to_string(),
+            val: "copyright-violation".to_string(),
+            created_at: "2026-01-01 00:00:00".to_string(),
+            resolved: false,
+            resolved_at: None,
+            context: Some(LabelContext {
+                track_id: Some(track_id),
+                track_title: Some("Song".to_string()),
+                artist_handle: Some("artist.bsky.social".to_string()),
+                artist_did: Some("did:plc:artist".to_string()),
+                ..Default::default()
+            }),
+            enforcement: None,
+            label_count: 1,
+        }
+    }
+
+    fn report(id: i32, target_type: &str, target_id: &str, target_uri: Option<&str>) -> UserReport {
+        UserReport {
+            id,
+            reporter_did: "did:plc:reporter".to_string(),
+            reporter_handle: None,
+            target_type: target_type.to_string(),
+            target_id: target_id.to_string(),
+            target_name: None,
+            target_url: None,
+            target_uri: target_uri.map(String::from),
+            reason: "copyright".to_string(),
+            description: None,
+            screenshot_url: None,
+            screenshot_key: None,
+            status: "open".to_string(),
+            admin_notes: None,
+            resolved_by: None,
+            created_at: chrono::Utc::now(),
+            updated_at: None,
+            resolved_at: None,
+            label_seq: None,
+            supporter_count: 0,
+        }
+    }
+
+    #[test]
+    fn test_parse_search_term() {
+        assert_eq!(
+            SearchTerm::parse(" did:plc:artist "),
+            Some(SearchTerm::Did("did:plc:artist".to_string()))
+        );
+        assert_eq!(
+            SearchTerm::parse("@Artist.bsky.social"),
+            Some(SearchTerm::Handle("artist.bsky.social".to_string()))
+        );
+        assert_eq!(SearchTerm::parse(" @ "), None);
+    }
+
+    #[test]
+    fn test_flags_and_reports_grouped_by_target() {
+        let flags = vec![flag(9, TRACK, 42), flag(3, TRACK, 42)];
+        let reports = vec![
+            report(1, "track", "42", Some(TRACK)),
+            // no URI, matched to the flagged track by id
+            report(2, "track", "42", None),
+            report(3, "artist", "did:plc:artist", None),
+        ];
+
+        let groups = group_by_target(flags, reports);
+        assert_eq!(groups.len(), 2);
+
+        assert_eq!(groups[0].target, TRACK);
+        assert_eq!(groups[0].flag.as_ref().map(|f| f.seq), Some(9));
+        let ids: Vec<i32> = groups[0].reports.iter().map(|r| r.id).collect();
+        assert_eq!(ids, [1, 2]);
+
+        assert_eq!(groups[1].target, "artist:did:plc:artist");
+        assert!(groups[1].flag.is_none());
+        assert_eq!(groups[1].reports.len(), 1);
+    }
+
+    #[test]
+    fn test_search_results_render_both_types() {
+        let groups = group_by_target(
+            vec![flag(9, TRACK, 42)],
+            vec![
+                report(1, "track", "42", Some(TRACK)),
+                report(3, "artist", "did:plc:artist", None),
+            ],
+        );
+        let html = render_search_results("did:plc:artist", &groups);
+        assert!(html.contains("1 flagged tracks · 2 reports · 2 targets"));
+        assert_eq!(html.matches(r#"class="search-target""#).count(), 2);
+        assert!(html.contains(r#"class="flag-card"#));
+        assert_eq!(html.matches(r#"class="report-card"#).count(), 2);
+
+        let html = render_search_results("<nobody>", &[]);
+        assert!(html.contains("no flags or reports for &lt;nobody&gt;"));
+    }
+}
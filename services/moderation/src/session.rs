@@ -0,0 +1,389 @@
+//! Short-lived browser sessions for the admin and review pages.
+//!
+//! `POST /admin/login` exchanges a moderation token for a signed session
+//! (`{role}.{expires_at}.{id}.{hmac}`) set as an httpOnly cookie, so the pages
+//! never keep the long-lived key in localStorage. The auth middleware accepts
+//! the cookie wherever it accepts a token header, and serves [`login_page`]
+//! in place of the dashboard and review pages until the browser has one.
+//! `POST /admin/logout` revokes the session's id, so a copied cookie stops
+//! working too.
+
+use axum::{
+    extract::State,
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+
+use crate::auth::{Actor, Role};
+use crate::state::{AppError, AppState};
+
+/// Cookie carrying the session token.
+pub const SESSION_COOKIE: &str = "mod_session";
+
+/// Session lifetime when `MODERATION_SESSION_TTL_HOURS` is unset.
+pub const DEFAULT_SESSION_TTL_HOURS: i64 = 12;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Why a session cookie was rejected.
+#[derive(Debug, PartialEq, Eq, thiserror::Error)]
+pub enum SessionError {
+    #[error("malformed session")]
+    Malformed,
+
+    #[error("invalid session signature")]
+    BadSignature,
+
+    #[error("session expired")]
+    Expired,
+
+    #[error("session revoked")]
+    Revoked,
+}
+
+/// A verified session.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Session {
+    pub role: Role,
+    /// Random per-session id, revoked on logout
+    pub id: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// Signs and verifies session tokens, and remembers revoked session ids
+/// until they would have expired anyway.
+#[derive(Clone)]
+pub struct SessionKey {
+    secret: Vec<u8>,
+    ttl: Duration,
+    revoked: Arc<Mutex<HashMap<String, DateTime<Utc>>>>,
+}
+
+impl std::fmt::Debug for SessionKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SessionKey")
+            .field("ttl", &self.ttl)
+            .finish_non_exhaustive()
+    }
+}
+
+impl SessionKey {
+    pub fn new(secret: impl Into<Vec<u8>>, ttl: Duration) -> Self {
+        Self {
+            secret: secret.into(),
+            ttl,
+            revoked: Arc::default(),
+        }
+    }
+
+    /// Issue a session for `role`, returning the token and its expiry.
+    pub fn issue(&self, role: Role, now: DateTime<Utc>) -> (String, DateTime<Utc>) {
+        let expires_at = now + self.ttl;
+        let id = hex::encode(rand::random::<[u8; 16]>());
+        let payload = format!("{}.{}.{}", role.as_str(), expires_at.timestamp(), id);
+        let signature = hex::encode(self.mac(&payload).finalize().into_bytes());
+        (format!("{payload}.{signature}"), expires_at)
+    }
+
+    /// Check a session's signature, expiry and revocation.
+    pub fn verify(&self, token: &str, now: DateTime<Utc>) -> Result<Session, SessionError> {
+        let (payload, signature) = token.rsplit_once('.').ok_or(SessionError::Malformed)?;
+        let signature = hex::decode(signature).map_err(|_| SessionError::Malformed)?;
+        self.mac(payload)
+            .verify_slice(&signature)
+            .map_err(|_| SessionError::BadSignature)?;
+
+        let mut parts = payload.splitn(3, '.');
+        let (Some(role), Some(expires_at), Some(id)) = (parts.next(), parts.next(), parts.next())
+        else {
+            return Err(SessionError::Malformed);
+        };
+        let role = Role::from_str(role).ok_or(SessionError::Malformed)?;
+        let expires_at = expires_at
+            .parse()
+            .ok()
+            .and_then(|secs| Utc.timestamp_opt(secs, 0).single())
+            .ok_or(SessionError::Malformed)?;
+        if now >= expires_at {
+            return Err(SessionError::Expired);
+        }
+        if self.revoked.lock().unwrap().contains_key(id) {
+            return Err(SessionError::Revoked);
+        }
+        Ok(Session {
+            role,
+            id: id.to_string(),
+            expires_at,
+        })
+    }
+
+    /// Reject `id` from now on. Entries are dropped once their session would
+    /// have expired anyway.
+    pub fn revoke(&self, id: &str, expires_at: DateTime<Utc>, now: DateTime<Utc>) {
+        let mut revoked = self.revoked.lock().unwrap();
+        revoked.retain(|_, expires_at| *expires_at > now);
+        if expires_at > now {
+            revoked.insert(id.to_string(), expires_at);
+        }
+    }
+
+    fn mac(&self, payload: &str) -> HmacSha256 {
+        let mut mac =
+            HmacSha256::new_from_slice(&self.secret).expect("hmac accepts keys of any length");
+        mac.update(payload.as_bytes());
+        mac
+    }
+}
+
+/// The session cookie's value, if the request carries one.
+pub fn session_cookie(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get_all(header::COOKIE)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split(';'))
+        .find_map(|pair| {
+            let (name, value) = pair.trim().split_once('=')?;
+            (name == SESSION_COOKIE).then_some(value)
+        })
+}
+
+/// `Set-Cookie` value for a session. `max_age` of zero clears the cookie.
+///
+/// `Lax` rather than `Strict` so a review link opened from chat arrives
+/// signed in; every endpoint that changes state is a POST or DELETE, which
+/// `Lax` still withholds the cookie from cross-site.
+fn set_cookie(value: &str, max_age: i64) -> String {
+    format!(
+        "{SESSION_COOKIE}={value}; Path=/admin; Max-Age={max_age}; HttpOnly; Secure; SameSite=Lax"
+    )
+}
+
+/// The login form, served with `status` in place of a page the browser
+/// isn't signed in for. Signing in reloads the page it was served for.
+pub fn login_page(status: StatusCode) -> Response {
+    (
+        status,
+        [(header::CONTENT_TYPE, "text/html; charset=utf-8")],
+        LOGIN_PAGE,
+    )
+        .into_response()
+}
+
+const LOGIN_PAGE: &str = r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+    <meta charset="utf-8">
+    <meta name="viewport" content="width=device-width, initial-scale=1">
+    <title>sign in · plyr.fm moderation</title>
+    <link rel="stylesheet" href="/static/admin.css">
+</head>
+<body>
+    <h1>moderation</h1>
+    <p class="subtitle">sign in to continue</p>
+
+    <form class="auth-section" id="login-form">
+        <input type="text" id="auth-actor" placeholder="your name (audit log)">
+        <input type="password" id="auth-token" placeholder="auth token" autofocus>
+        <button type="submit" class="btn btn-primary" style="margin-left: 10px">
+            authenticate
+        </button>
+        <div class="login-error" id="login-error"></div>
+    </form>
+
+    <script>
+        const actorInput = document.getElementById('auth-actor');
+        actorInput.value = localStorage.getItem('mod_actor') || '';
+
+        // Exchange the token for an httpOnly session cookie, then load the
+        // page this form stood in for
+        document.getElementById('login-form').addEventListener('submit', async (e) => {
+            e.preventDefault();
+            localStorage.setItem('mod_actor', actorInput.value.trim());
+            const token = document.getElementById('auth-token').value;
+            if (!token) return;
+            const response = await fetch('/admin/login', {
+                method: 'POST',
+                headers: { 'Content-Type': 'application/json' },
+                body: JSON.stringify({ token })
+            });
+            if (!response.ok) {
+                document.getElementById('login-error').textContent = 'invalid token';
+                return;
+            }
+            location.reload();
+        });
+    </script>
+</body>
+</html>
+"#;
+
+// --- handlers ---
+
+/// Request to start a session.
+#[derive(Debug, Deserialize)]
+pub struct LoginRequest {
+    pub token: String,
+}
+
+/// Response after starting a session.
+#[derive(Debug, Serialize)]
+pub struct LoginResponse {
+    pub role: &'static str,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// Exchange a moderation token for a session cookie.
+///
+/// Failed attempts are written to the audit log.
+pub async fn login(
+    State(state): State<AppState>,
+    actor: Actor,
+    Json(request): Json<LoginRequest>,
+) -> Result<Response, AppError> {
+    let sessions = state
+        .auth
+        .sessions
+        .as_ref()
+        .ok_or_else(|| AppError::BadRequest("sessions are not enabled".to_string()))?;
+    let Some(role) = state.auth.role_for(&request.token) else {
+        if let Some(db) = &state.db {
+            db.record_audit_event(
+                "login_failed",
+                "session",
+                actor.as_str(),
+                serde_json::json!({}),
+            )
+            .await?;
+        }
+        return Err(AppError::Unauthorized("invalid token".to_string()));
+    };
+
+    let now = Utc::now();
+    let (token, expires_at) = sessions.issue(role, now);
+    let cookie = set_cookie(&token, (expires_at - now).num_seconds());
+    let body = LoginResponse {
+        role: role.as_str(),
+        expires_at,
+    };
+    Ok(([(header::SET_COOKIE, header_value(cookie)?)], Json(body)).into_response())
+}
+
+/// Revoke the caller's session, if it has a valid one, and clear the cookie.
+///
+/// The revocation is kept in memory and, when the labeler database is
+/// configured, persisted so it survives a restart.
+pub async fn logout(State(state): State<AppState>, headers: HeaderMap) -> Result<Response, AppError> {
+    let now = Utc::now();
+    let session = match (session_cookie(&headers), &state.auth.sessions) {
+        (Some(cookie), Some(sessions)) => sessions.verify(cookie, now).ok().map(|s| (sessions, s)),
+        _ => None,
+    };
+    if let Some((sessions, session)) = session {
+        sessions.revoke(&session.id, session.expires_at, now);
+        if let Some(db) = &state.db {
+            db.revoke_session(&session.id, session.expires_at).await?;
+        }
+    }
+
+    Ok((
+        StatusCode::NO_CONTENT,
+        [(header::SET_COOKIE, header_value(set_cookie("", 0))?)],
+    )
+        .into_response())
+}
+
+fn header_value(value: String) -> Result<HeaderValue, AppError> {
+    HeaderValue::try_from(value).map_err(|e| AppError::BadRequest(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key() -> SessionKey {
+        SessionKey::new("test-secret", Duration::hours(DEFAULT_SESSION_TTL_HOURS))
+    }
+
+    #[test]
+    fn test_session_round_trips() {
+        let now = Utc::now();
+        let (token, expires_at) = key().issue(Role::ReadOnly, now);
+        assert_eq!(expires_at, now + Duration::hours(12));
+        assert_eq!(key().verify(&token, now).unwrap().role, Role::ReadOnly);
+        assert_eq!(
+            key()
+                .verify(&token, now + Duration::hours(11))
+                .unwrap()
+                .role,
+            Role::ReadOnly
+        );
+    }
+
+    #[test]
+    fn test_session_expires() {
+        let now = Utc::now();
+        let (token, _) = key().issue(Role::Admin, now);
+        assert_eq!(
+            key().verify(&token, now + Duration::hours(12)),
+            Err(SessionError::Expired)
+        );
+    }
+
+    #[test]
+    fn test_tampered_session_rejected() {
+        let now = Utc::now();
+        let (token, _) = key().issue(Role::ReadOnly, now);
+        let forged = token.replacen("readonly", "admin", 1);
+        assert_eq!(key().verify(&forged, now), Err(SessionError::BadSignature));
+
+        let other = SessionKey::new("other-secret", Duration::hours(1));
+        assert_eq!(other.verify(&token, now), Err(SessionError::BadSignature));
+        assert_eq!(key().verify("garbage", now), Err(SessionError::Malformed));
+    }
+
+    #[test]
+    fn test_revoked_session_rejected() {
+        let now = Utc::now();
+        let key = key();
+        let (token, _) = key.issue(Role::Admin, now);
+        let (other, _) = key.issue(Role::Admin, now);
+        let session = key.verify(&token, now).unwrap();
+        key.revoke(&session.id, session.expires_at, now);
+        assert_eq!(key.verify(&token, now), Err(SessionError::Revoked));
+        // other sessions are unaffected
+        assert!(key.verify(&other, now).is_ok());
+
+        // entries are pruned once the session would have expired
+        let later = now + Duration::hours(13);
+        key.revoke("unrelated", later + Duration::hours(1), later);
+        assert_eq!(key.revoked.lock().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_session_cookie_parsing() {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            header::COOKIE,
+            HeaderValue::from_static("theme=dark; mod_session=abc.123.ff"),
+        );
+        assert_eq!(session_cookie(&headers), Some("abc.123.ff"));
+        assert_eq!(session_cookie(&HeaderMap::new()), None);
+    }
+
+    #[test]
+    fn test_logout_cookie_clears_session() {
+        let cookie = set_cookie("", 0);
+        assert!(cookie.starts_with("mod_session=;"));
+        assert!(cookie.contains("Max-Age=0"));
+        assert!(cookie.contains("HttpOnly"));
+        assert!(cookie.contains("SameSite=Lax"));
+    }
+}
@@ -1,33 +1,100 @@
 //! Application state and error types.
 
 use std::sync::Arc;
+use std::time::Duration;
 
 use axum::{
-    http::StatusCode,
+    http::{header, HeaderValue, StatusCode},
     response::{IntoResponse, Response},
     Json,
 };
 use tokio::sync::broadcast;
 use tracing::error;
 
-use crate::claude::ClaudeClient;
+use crate::auth::AuthTokens;
+use crate::claude::{ClaudeClient, ClaudeError, Severity};
 use crate::db::LabelDb;
+use crate::did::DidResolver;
+use crate::enforcement::EnforcementNotifier;
+use crate::flagged::FlagStatusCache;
+use crate::isrc::IsrcAllowlist;
 use crate::labels::{Label, LabelError, LabelSigner};
+use crate::ratelimit::{IpRateLimiter, RateLimiter};
+use crate::report_callback::ReportCallbackNotifier;
+use crate::reporter_hash::ReporterHasher;
+use crate::reports::{ReportEscalation, ReportLimits, ReportStatsCache};
+use crate::stats::StatsCache;
+use crate::storage::StorageClient;
+use crate::transcoder::TranscoderClient;
+use crate::webhook::WebhookNotifier;
 
 /// Shared application state.
 #[derive(Clone)]
 pub struct AppState {
     pub audd_api_token: String,
     pub audd_api_url: String,
+    /// Whether to retry empty recognitions with the fallback mode
+    pub audd_fallback_enabled: bool,
+    pub audd_fallback_url: String,
     pub db: Option<Arc<LabelDb>>,
     pub signer: Option<Arc<LabelSigner>>,
     pub label_tx: Option<broadcast::Sender<(i64, Label)>>,
+    /// Stored labels re-sent by `/admin/rebroadcast`. Kept apart from
+    /// `label_tx` because subscribers drop seqs at or below their position there.
+    pub label_replay_tx: Option<broadcast::Sender<(i64, Label)>>,
     /// Claude client for image moderation (if configured)
     pub claude: Option<Arc<ClaudeClient>>,
+    /// Public base URL for R2 images (admin thumbnails)
+    pub image_base_url: Option<String>,
+    /// Minimum severity at which unsafe images are auto-flagged
+    pub image_autoflag_severity: Severity,
+    /// Require a second model pass to agree before auto-flagging medium severity
+    pub image_second_opinion: bool,
+    /// Model for the second pass (defaults to the primary model)
+    pub image_second_opinion_model: Option<String>,
+    /// Treat a Claude refusal as a high-severity unsafe verdict
+    pub image_refusal_as_unsafe: bool,
+    /// Public base URL of this service (for links in notifications)
+    pub public_url: String,
+    /// Outbound webhook notifier (if configured)
+    pub webhook: Option<Arc<WebhookNotifier>>,
+    /// Enforcement callbacks for confirmed flags (if configured)
+    pub enforcement: Option<Arc<EnforcementNotifier>>,
+    /// Callbacks telling the backend a user report was closed (if configured)
+    pub report_callback: Option<Arc<ReportCallbackNotifier>>,
+    /// Cuts sample ranges out of audio before recognition (if configured)
+    pub transcoder: Option<Arc<TranscoderClient>>,
+    /// Bucket for screenshots uploaded with reports (if configured)
+    pub storage: Option<Arc<StorageClient>>,
     /// Minimum percentage of matches that must belong to a single song to flag
     pub copyright_score_threshold: i32,
     /// Minimum count of distinct sustained songs to flag as a mix
     pub copyright_mix_song_threshold: usize,
+    /// ISRCs whose matches don't flag a scan
+    pub isrc_allowlist: Arc<IsrcAllowlist>,
+    /// Recently computed admin dashboard stats
+    pub stats_cache: Arc<StatsCache>,
+    /// Recently computed report statistics for a date window
+    pub report_stats_cache: Arc<ReportStatsCache>,
+    /// Recent `/is-flagged` answers
+    pub flag_status_cache: Arc<FlagStatusCache>,
+    /// Labeler DID resolution for the admin health check
+    pub did_resolver: Arc<DidResolver>,
+    /// Tokens and session key accepted for protected endpoints
+    pub auth: AuthTokens,
+    /// Per-credential limits on label-changing endpoints
+    pub rate_limiter: Arc<RateLimiter>,
+    /// Per-client-IP limits on the public XRPC endpoints
+    pub ip_rate_limiter: Arc<IpRateLimiter>,
+    /// How many reports each reporter DID may file
+    pub report_limits: ReportLimits,
+    /// When reports with many reporters are escalated
+    pub report_escalation: ReportEscalation,
+    /// Hashes reporter DIDs before they're stored, when reporter privacy
+    /// mode is on
+    pub reporter_hasher: Option<ReporterHasher>,
+    /// How long a review batch accepts decisions after it's created
+    pub review_batch_ttl: chrono::Duration,
 }
 
 /// Application error type.
@@ -36,8 +103,15 @@ pub enum AppError {
     #[error("audd error: {0}")]
     Audd(String),
 
+    /// AuDD refused the request for being over its rate limit or quota.
+    #[error("audd rate limited")]
+    AuddRateLimited { retry_after: Option<Duration> },
+
     #[error("claude error: {0}")]
-    Claude(String),
+    Claude(#[from] ClaudeError),
+
+    #[error("image fetch error: {0}")]
+    ImageFetch(String),
 
     #[error("image moderation not configured")]
     ImageModerationNotConfigured,
@@ -45,12 +119,42 @@ pub enum AppError {
     #[error("labeler not configured")]
     LabelerNotConfigured,
 
+    #[error("transcoder error: {0}")]
+    Transcoder(String),
+
+    #[error("audio fetch error: {0}")]
+    AudioFetch(String),
+
+    #[error("transcoder not configured")]
+    TranscoderNotConfigured,
+
+    #[error("storage error: {0}")]
+    Storage(String),
+
+    #[error("storage not configured")]
+    StorageNotConfigured,
+
+    #[error("webhook not configured")]
+    WebhookNotConfigured,
+
     #[error("bad request: {0}")]
     BadRequest(String),
 
     #[error("not found: {0}")]
     NotFound(String),
 
+    #[error("conflict: {0}")]
+    Conflict(String),
+
+    #[error("unauthorized: {0}")]
+    Unauthorized(String),
+
+    #[error("forbidden: {0}")]
+    Forbidden(String),
+
+    #[error("too many requests: {0}")]
+    TooManyRequests(String),
+
     #[error("label error: {0}")]
     Label(#[from] LabelError),
 
@@ -66,15 +170,42 @@ impl IntoResponse for AppError {
         error!(error = %self, "request failed");
         let (status, error_type) = match &self {
             AppError::Audd(_) => (StatusCode::BAD_GATEWAY, "AuddError"),
-            AppError::Claude(_) => (StatusCode::BAD_GATEWAY, "ClaudeError"),
+            AppError::AuddRateLimited { .. } => {
+                (StatusCode::SERVICE_UNAVAILABLE, "AuddRateLimited")
+            }
+            // a refusal is about the image, so retrying the same upload won't help
+            AppError::Claude(ClaudeError::Refusal { .. }) => {
+                (StatusCode::UNPROCESSABLE_ENTITY, "ClaudeRefusal")
+            }
+            AppError::Claude(ClaudeError::Truncated) => {
+                (StatusCode::BAD_GATEWAY, "ClaudeTruncated")
+            }
+            AppError::Claude(ClaudeError::Api(_)) => (StatusCode::BAD_GATEWAY, "ClaudeError"),
+            AppError::ImageFetch(_) => (StatusCode::BAD_GATEWAY, "ImageFetchError"),
             AppError::ImageModerationNotConfigured => {
                 (StatusCode::SERVICE_UNAVAILABLE, "ImageModerationNotConfigured")
             }
             AppError::LabelerNotConfigured => {
                 (StatusCode::SERVICE_UNAVAILABLE, "LabelerNotConfigured")
             }
+            AppError::Transcoder(_) => (StatusCode::BAD_GATEWAY, "TranscoderError"),
+            AppError::AudioFetch(_) => (StatusCode::BAD_GATEWAY, "AudioFetchError"),
+            AppError::TranscoderNotConfigured => {
+                (StatusCode::SERVICE_UNAVAILABLE, "TranscoderNotConfigured")
+            }
+            AppError::Storage(_) => (StatusCode::BAD_GATEWAY, "StorageError"),
+            AppError::StorageNotConfigured => {
+                (StatusCode::SERVICE_UNAVAILABLE, "StorageNotConfigured")
+            }
+            AppError::WebhookNotConfigured => {
+                (StatusCode::SERVICE_UNAVAILABLE, "WebhookNotConfigured")
+            }
             AppError::BadRequest(_) => (StatusCode::BAD_REQUEST, "BadRequest"),
             AppError::NotFound(_) => (StatusCode::NOT_FOUND, "NotFound"),
+            AppError::Conflict(_) => (StatusCode::CONFLICT, "Conflict"),
+            AppError::Unauthorized(_) => (StatusCode::UNAUTHORIZED, "Unauthorized"),
+            AppError::Forbidden(_) => (StatusCode::FORBIDDEN, "Forbidden"),
+            AppError::TooManyRequests(_) => (StatusCode::TOO_MANY_REQUESTS, "TooManyRequests"),
             AppError::Label(_) => (StatusCode::INTERNAL_SERVER_ERROR, "LabelError"),
             AppError::Database(_) => (StatusCode::INTERNAL_SERVER_ERROR, "DatabaseError"),
             AppError::Io(_) => (StatusCode::INTERNAL_SERVER_ERROR, "IoError"),
@@ -83,6 +214,17 @@ impl IntoResponse for AppError {
             "error": error_type,
             "message": self.to_string()
         });
-        (status, Json(body)).into_response()
+        let mut response = (status, Json(body)).into_response();
+        if let AppError::AuddRateLimited {
+            retry_after: Some(retry_after),
+        } = self
+        {
+            // round up so callers never retry a moment too early
+            let secs = retry_after.as_secs() + u64::from(retry_after.subsec_nanos() > 0);
+            response
+                .headers_mut()
+                .insert(header::RETRY_AFTER, HeaderValue::from(secs));
+        }
+        response
     }
 }
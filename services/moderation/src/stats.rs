@@ -0,0 +1,358 @@
+//! Dashboard statistics for the admin UI header.
+//!
+//! Stats come from a handful of aggregate queries and are cached in memory
+//! for a minute, so dashboard refreshes don't each hit the database. The
+//! summary header above them is a single query, polled by the dashboard.
+
+use std::future::Future;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use axum::{
+    extract::State,
+    http::header::CONTENT_TYPE,
+    response::{IntoResponse, Response},
+    Json,
+};
+use tokio::sync::Mutex;
+
+use crate::admin::html_escape;
+use crate::db::{DashboardStats, ResolutionReason, SummaryCounts, STATS_DAYS};
+use crate::state::{AppError, AppState};
+
+/// How long computed stats are served from memory.
+const STATS_CACHE_TTL: Duration = Duration::from_secs(60);
+
+/// Most recently computed stats, dashboard stats by default. `K` is what
+/// the stats were computed for, e.g. a date window; a request for anything
+/// else recomputes.
+pub struct StatsCache<T = DashboardStats, K = ()> {
+    entry: Mutex<Option<(Instant, K, Arc<T>)>>,
+}
+
+impl<T, K> Default for StatsCache<T, K> {
+    fn default() -> Self {
+        Self {
+            entry: Mutex::new(None),
+        }
+    }
+}
+
+impl<T> StatsCache<T> {
+    /// Return the cached stats if still fresh at `now`, otherwise refresh.
+    async fn get_or_refresh<F, Fut>(&self, now: Instant, refresh: F) -> Result<Arc<T>, sqlx::Error>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<T, sqlx::Error>>,
+    {
+        self.get_or_refresh_for((), now, refresh).await
+    }
+}
+
+impl<T, K: PartialEq> StatsCache<T, K> {
+    /// Return the cached stats if they're for `key` and still fresh at `now`,
+    /// otherwise refresh.
+    ///
+    /// The lock is held while refreshing, so concurrent requests on a stale
+    /// cache wait for a single computation instead of each running it.
+    pub async fn get_or_refresh_for<F, Fut>(
+        &self,
+        key: K,
+        now: Instant,
+        refresh: F,
+    ) -> Result<Arc<T>, sqlx::Error>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<T, sqlx::Error>>,
+    {
+        let mut entry = self.entry.lock().await;
+        if let Some((computed_at, cached_key, stats)) = entry.as_ref() {
+            if *cached_key == key && now.saturating_duration_since(*computed_at) < STATS_CACHE_TTL {
+                return Ok(stats.clone());
+            }
+        }
+        let stats = Arc::new(refresh().await?);
+        *entry = Some((now, key, stats.clone()));
+        Ok(stats)
+    }
+}
+
+/// Get dashboard statistics.
+pub async fn get_stats(State(state): State<AppState>) -> Result<Json<DashboardStats>, AppError> {
+    let stats = fetch_stats(&state).await?;
+    Ok(Json(stats.as_ref().clone()))
+}
+
+/// Render dashboard statistics as an HTML strip for htmx.
+pub async fn get_stats_html(State(state): State<AppState>) -> Result<Response, AppError> {
+    let stats = fetch_stats(&state).await?;
+    let html = render_stats(&stats);
+    Ok(([(CONTENT_TYPE, "text/html; charset=utf-8")], html).into_response())
+}
+
+/// Header counts; `None` for anything the service isn't configured for.
+#[derive(Debug, Default)]
+struct Summary {
+    pending_flags: Option<i64>,
+    open_reports: Option<i64>,
+    image_review_queue: Option<i64>,
+    resolved_today: Option<i64>,
+}
+
+impl Summary {
+    /// The image queue only counts when image scanning is configured.
+    fn from_counts(counts: SummaryCounts, image_scanning: bool) -> Self {
+        Self {
+            pending_flags: Some(counts.pending_flags),
+            open_reports: Some(counts.open_reports),
+            image_review_queue: image_scanning.then_some(counts.image_review_queue),
+            resolved_today: Some(counts.resolved_today),
+        }
+    }
+}
+
+/// Render the dashboard summary header as HTML for htmx. Counts that don't
+/// apply (no labeler database, no image scanning) show as dashes.
+pub async fn get_summary_html(State(state): State<AppState>) -> Result<Response, AppError> {
+    let summary = match state.db.as_ref() {
+        Some(db) => Summary::from_counts(db.get_summary_counts().await?, state.claude.is_some()),
+        None => Summary::default(),
+    };
+    let html = render_summary(&summary);
+    Ok(([(CONTENT_TYPE, "text/html; charset=utf-8")], html).into_response())
+}
+
+async fn fetch_stats(state: &AppState) -> Result<Arc<DashboardStats>, AppError> {
+    let db = state.db.as_ref().ok_or(AppError::LabelerNotConfigured)?;
+    let stats = state
+        .stats_cache
+        .get_or_refresh(Instant::now(), || db.get_dashboard_stats())
+        .await?;
+    Ok(stats)
+}
+
+/// Render the stats strip shown above the dashboard tabs.
+fn render_stats(stats: &DashboardStats) -> String {
+    let max_per_day = stats
+        .flags_per_day
+        .iter()
+        .map(|day| day.count)
+        .max()
+        .unwrap_or(0)
+        .max(1);
+    let total_recent: i64 = stats.flags_per_day.iter().map(|day| day.count).sum();
+    let bars: String = stats
+        .flags_per_day
+        .iter()
+        .map(|day| {
+            format!(
+                "<span class=\"spark-bar\" style=\"height: {}%\" title=\"{}: {}\"></span>",
+                day.count * 100 / max_per_day,
+                day.date,
+                day.count
+            )
+        })
+        .collect();
+
+    let reasons = if stats.resolution_reasons.is_empty() {
+        "<span class=\"stat-label\">no resolutions yet</span>".to_string()
+    } else {
+        stats
+            .resolution_reasons
+            .iter()
+            .map(|r| {
+                let label = match ResolutionReason::from_key(&r.reason) {
+                    ResolutionReason::Custom(key) => key.replace('_', " "),
+                    reason => reason.label().to_string(),
+                };
+                format!(
+                    "<span class=\"reason-chip\">{} <strong>{}</strong></span>",
+                    html_escape(&label),
+                    r.count
+                )
+            })
+            .collect()
+    };
+
+    format!(
+        r#"<div class="stats-strip">
+            <div class="stat"><span class="stat-value">{}</span><span class="stat-label">pending flags</span></div>
+            <div class="stat"><span class="stat-value">{}</span><span class="stat-label">resolved flags</span></div>
+            <div class="stat"><span class="stat-value">{}</span><span class="stat-label">open reports</span></div>
+            <div class="stat"><span class="stat-value">{}</span><span class="stat-label">sensitive images (7d)</span></div>
+            <div class="stat stat-wide">
+                <div class="sparkline">{}</div>
+                <span class="stat-label">{} flags in the last {} days</span>
+            </div>
+            <div class="stat stat-wide"><div class="reason-chips">{}</div></div>
+        </div>"#,
+        stats.pending_flags,
+        stats.resolved_flags,
+        stats.open_reports,
+        stats.sensitive_images_this_week,
+        bars,
+        total_recent,
+        STATS_DAYS,
+        reasons
+    )
+}
+
+/// Render the summary header strip.
+fn render_summary(summary: &Summary) -> String {
+    let count = |value: Option<i64>| value.map_or("—".to_string(), |n| n.to_string());
+    format!(
+        r#"<div class="summary-strip">
+            <span class="summary-item"><strong>{}</strong> pending flags</span>
+            <span class="summary-item"><strong>{}</strong> open reports</span>
+            <span class="summary-item"><strong>{}</strong> images to review</span>
+            <span class="summary-item"><strong>{}</strong> resolved today</span>
+        </div>"#,
+        count(summary.pending_flags),
+        count(summary.open_reports),
+        count(summary.image_review_queue),
+        count(summary.resolved_today)
+    )
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{DailyCount, ReasonCount};
+    use chrono::NaiveDate;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn sample_stats(pending_flags: i64) -> DashboardStats {
+        DashboardStats {
+            pending_flags,
+            resolved_flags: 7,
+            flags_per_day: vec![
+                DailyCount {
+                    date: NaiveDate::from_ymd_opt(2026, 1, 1).unwrap(),
+                    count: 4,
+                },
+                DailyCount {
+                    date: NaiveDate::from_ymd_opt(2026, 1, 2).unwrap(),
+                    count: 1,
+                },
+            ],
+            resolution_reasons: vec![
+                ReasonCount {
+                    reason: "licensed".to_string(),
+                    count: 5,
+                },
+                ReasonCount {
+                    reason: "<odd>".to_string(),
+                    count: 2,
+                },
+            ],
+            open_reports: 2,
+            sensitive_images_this_week: 1,
+        }
+    }
+
+    #[test]
+    fn test_render_stats() {
+        let html = render_stats(&sample_stats(3));
+
+        assert!(html.contains(">3</span><span class=\"stat-label\">pending flags"));
+        assert!(html.contains("height: 100%\" title=\"2026-01-01: 4\""));
+        assert!(html.contains("height: 25%\" title=\"2026-01-02: 1\""));
+        assert!(html.contains("5 flags in the last 30 days"));
+        assert!(html.contains("licensed <strong>5</strong>"));
+        assert!(html.contains("&lt;odd&gt;"));
+    }
+
+    #[test]
+    fn test_render_stats_without_flags() {
+        let mut stats = sample_stats(0);
+        stats.flags_per_day.iter_mut().for_each(|day| day.count = 0);
+        stats.resolution_reasons.clear();
+
+        let html = render_stats(&stats);
+        assert!(html.contains("height: 0%"));
+        assert!(html.contains("no resolutions yet"));
+    }
+
+    #[test]
+    fn test_render_summary() {
+        let counts = SummaryCounts {
+            pending_flags: 4,
+            open_reports: 2,
+            image_review_queue: 5,
+            resolved_today: 1,
+        };
+        let html = render_summary(&Summary::from_counts(counts.clone(), true));
+        assert!(html.contains("<strong>4</strong> pending flags"));
+        assert!(html.contains("<strong>2</strong> open reports"));
+        assert!(html.contains("<strong>5</strong> images to review"));
+        assert!(html.contains("<strong>1</strong> resolved today"));
+
+        // image scanning not configured
+        let html = render_summary(&Summary::from_counts(counts, false));
+        assert!(html.contains("<strong>4</strong> pending flags"));
+        assert!(html.contains("<strong>—</strong> images to review"));
+
+        // labeler not configured
+        let html = render_summary(&Summary::default());
+        assert_eq!(html.matches("<strong>—</strong>").count(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_stats_cache_expires_after_ttl() {
+        let cache = StatsCache::<DashboardStats>::default();
+        let refreshes = &AtomicUsize::new(0);
+        let refresh = || async move {
+            let n = refreshes.fetch_add(1, Ordering::SeqCst) as i64;
+            Ok(sample_stats(n))
+        };
+        let start = Instant::now();
+
+        let first = cache.get_or_refresh(start, refresh).await.unwrap();
+        let cached = cache
+            .get_or_refresh(start + Duration::from_secs(59), refresh)
+            .await
+            .unwrap();
+        assert_eq!(first.pending_flags, 0);
+        assert_eq!(cached.pending_flags, 0);
+        assert_eq!(refreshes.load(Ordering::SeqCst), 1);
+
+        let refreshed = cache
+            .get_or_refresh(start + STATS_CACHE_TTL, refresh)
+            .await
+            .unwrap();
+        assert_eq!(refreshed.pending_flags, 1);
+        assert_eq!(refreshes.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_stats_cache_recomputes_for_another_key() {
+        let cache = StatsCache::<DashboardStats, &str>::default();
+        let now = Instant::now();
+
+        cache
+            .get_or_refresh_for("this week", now, || async { Ok(sample_stats(1)) })
+            .await
+            .unwrap();
+        let other = cache
+            .get_or_refresh_for("last week", now, || async { Ok(sample_stats(2)) })
+            .await
+            .unwrap();
+        assert_eq!(other.pending_flags, 2);
+    }
+
+    #[tokio::test]
+    async fn test_stats_cache_does_not_store_errors() {
+        let cache = StatsCache::<DashboardStats>::default();
+        let now = Instant::now();
+
+        let failed = cache
+            .get_or_refresh(now, || async { Err(sqlx::Error::PoolTimedOut) })
+            .await;
+        assert!(failed.is_err());
+
+        let stats = cache
+            .get_or_refresh(now, || async { Ok(sample_stats(9)) })
+            .await
+            .unwrap();
+        assert_eq!(stats.pending_flags, 9);
+    }
+}
@@ -0,0 +1,308 @@
+//! Trimming audio with the transcoder service before recognition.
+//!
+//! AuDD recognizes from the start of the file, so a long DJ set or a track
+//! with a long intro can scan clean. A scan that asks for a `sample` has the
+//! audio downloaded here, cut to that range by the transcoder
+//! (`POST /transcode?target=mp3&start=..&duration=..`), and the cut uploaded
+//! to AuDD as a file instead of the whole URL. The download is spooled to a
+//! temp file and streamed on to the transcoder, so a long source is never
+//! held in memory.
+
+use std::path::Path;
+use std::time::Duration;
+
+use serde::Deserialize;
+use tokio::io::AsyncWriteExt;
+use tokio_util::io::ReaderStream;
+use tracing::info;
+
+use crate::audd::{TempUpload, MAX_AUDIO_UPLOAD_BYTES};
+use crate::image_fetch::pinned_client;
+use crate::state::AppError;
+
+/// Seconds sampled when a range gives only a start.
+pub const DEFAULT_SAMPLE_SECS: f64 = 60.0;
+
+/// Longest sample accepted. AuDD bills per 12 seconds of audio, so a long
+/// sample costs as much as scanning the whole file.
+pub const MAX_SAMPLE_SECS: f64 = 600.0;
+
+/// Covers downloading and cutting a long source.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Part of a track to recognize.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct SampleRange {
+    /// Seconds into the audio the sample starts at.
+    pub start_secs: f64,
+    /// Length of the sample in seconds.
+    #[serde(default = "default_sample_secs")]
+    pub duration_secs: f64,
+}
+
+fn default_sample_secs() -> f64 {
+    DEFAULT_SAMPLE_SECS
+}
+
+impl SampleRange {
+    /// Reject ranges the transcoder would refuse or that cost more than
+    /// scanning the whole file.
+    pub fn validate(&self) -> Result<(), AppError> {
+        if !self.start_secs.is_finite() || self.start_secs < 0.0 {
+            return Err(AppError::BadRequest(format!(
+                "sample start_secs must be 0 or more, got {}",
+                self.start_secs
+            )));
+        }
+        if !(self.duration_secs > 0.0 && self.duration_secs <= MAX_SAMPLE_SECS) {
+            return Err(AppError::BadRequest(format!(
+                "sample duration_secs must be above 0 and at most {MAX_SAMPLE_SECS}, got {}",
+                self.duration_secs
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Where the transcoder lives and how to authenticate to it.
+#[derive(Debug, Clone)]
+pub struct TranscoderConfig {
+    pub url: String,
+    pub auth_token: Option<String>,
+}
+
+/// Client for the transcoder's `/transcode` endpoint.
+pub struct TranscoderClient {
+    http: reqwest::Client,
+    config: TranscoderConfig,
+}
+
+impl TranscoderClient {
+    pub fn new(config: TranscoderConfig) -> Self {
+        Self {
+            http: reqwest::Client::builder()
+                .timeout(REQUEST_TIMEOUT)
+                .build()
+                .unwrap_or_default(),
+            config,
+        }
+    }
+
+    /// Download `audio_url` and cut `range` out of it as an mp3.
+    pub async fn sample_url(
+        &self,
+        audio_url: &str,
+        range: &SampleRange,
+    ) -> Result<Vec<u8>, AppError> {
+        let spool = TempUpload::new();
+        fetch_audio(audio_url, spool.path()).await?;
+        let filename = audio_url
+            .rsplit('/')
+            .next()
+            .filter(|name| !name.is_empty())
+            .unwrap_or("audio");
+        let file = tokio::fs::File::open(spool.path()).await?;
+        let audio = reqwest::Body::wrap_stream(ReaderStream::new(file));
+        self.trim(audio, filename, range).await
+    }
+
+    /// Cut `range` out of `audio` as an mp3.
+    pub async fn trim(
+        &self,
+        audio: reqwest::Body,
+        filename: &str,
+        range: &SampleRange,
+    ) -> Result<Vec<u8>, AppError> {
+        let url = format!("{}/transcode", self.config.url.trim_end_matches('/'));
+        let form = reqwest::multipart::Form::new().part(
+            "file",
+            reqwest::multipart::Part::stream(audio).file_name(filename.to_string()),
+        );
+        let mut request = self
+            .http
+            .post(&url)
+            .query(&[
+                ("target", "mp3".to_string()),
+                ("start", range.start_secs.to_string()),
+                ("duration", range.duration_secs.to_string()),
+            ])
+            .multipart(form);
+        if let Some(token) = &self.config.auth_token {
+            request = request.bearer_auth(token);
+        }
+
+        let response = request
+            .send()
+            .await
+            .map_err(|e| AppError::Transcoder(format!("request failed: {e}")))?;
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(AppError::Transcoder(format!("{status}: {body}")));
+        }
+        let sample = response
+            .bytes()
+            .await
+            .map_err(|e| AppError::Transcoder(format!("failed to read sample: {e}")))?;
+        info!(
+            start_secs = range.start_secs,
+            duration_secs = range.duration_secs,
+            bytes = sample.len(),
+            "audio sampled for recognition"
+        );
+        Ok(sample.to_vec())
+    }
+}
+
+/// Download audio for trimming to `path`, refusing anything over the upload
+/// limit. The URL is caller-controlled, so it gets the same SSRF checks as
+/// images.
+async fn fetch_audio(raw_url: &str, path: &Path) -> Result<(), AppError> {
+    let (client, url) = pinned_client(raw_url, REQUEST_TIMEOUT).await?;
+    let mut response = client
+        .get(url)
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| AppError::AudioFetch(e.to_string()))?;
+    let mut file = tokio::fs::File::create(path).await?;
+    let mut size_bytes = 0;
+    while let Some(chunk) = response
+        .chunk()
+        .await
+        .map_err(|e| AppError::AudioFetch(e.to_string()))?
+    {
+        size_bytes += chunk.len();
+        if size_bytes > MAX_AUDIO_UPLOAD_BYTES {
+            return Err(AppError::AudioFetch(format!(
+                "audio is larger than {MAX_AUDIO_UPLOAD_BYTES} bytes"
+            )));
+        }
+        file.write_all(&chunk).await?;
+    }
+    file.flush().await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::sync::{Arc, Mutex};
+
+    use axum::{
+        extract::{Multipart, Query},
+        http::{HeaderMap, StatusCode},
+        routing::post,
+        Router,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_sample_range_validation() {
+        let range = |start_secs, duration_secs| SampleRange {
+            start_secs,
+            duration_secs,
+        };
+        assert!(range(0.0, DEFAULT_SAMPLE_SECS).validate().is_ok());
+        assert!(range(1800.0, MAX_SAMPLE_SECS).validate().is_ok());
+        assert!(range(-5.0, 60.0).validate().is_err());
+        assert!(range(f64::INFINITY, 60.0).validate().is_err());
+        assert!(range(0.0, 0.0).validate().is_err());
+        assert!(range(0.0, f64::NAN).validate().is_err());
+        assert!(range(0.0, MAX_SAMPLE_SECS + 1.0).validate().is_err());
+
+        let parsed: SampleRange =
+            serde_json::from_value(serde_json::json!({ "start_secs": 900 })).unwrap();
+        assert_eq!(parsed, range(900.0, DEFAULT_SAMPLE_SECS));
+    }
+
+    /// What the stand-in transcoder saw: query, auth header, uploaded bytes.
+    type Seen = Arc<Mutex<Vec<(HashMap<String, String>, Option<String>, Vec<u8>)>>>;
+
+    /// A stand-in transcoder answering `/transcode` with `status` and a fixed
+    /// body.
+    async fn mock_transcoder(status: StatusCode) -> (String, Seen) {
+        let seen: Seen = Arc::default();
+        let app = Router::new().route(
+            "/transcode",
+            post({
+                let seen = seen.clone();
+                move |Query(query): Query<HashMap<String, String>>,
+                      headers: HeaderMap,
+                      mut multipart: Multipart| {
+                    let seen = seen.clone();
+                    async move {
+                        let field = multipart.next_field().await.unwrap().unwrap();
+                        assert_eq!(field.name(), Some("file"));
+                        assert_eq!(field.file_name(), Some("audio.wav"));
+                        let upload = field.bytes().await.unwrap().to_vec();
+                        let auth = headers
+                            .get("authorization")
+                            .and_then(|v| v.to_str().ok())
+                            .map(String::from);
+                        seen.lock().unwrap().push((query, auth, upload));
+                        (status, "sampled")
+                    }
+                }
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await });
+        (url, seen)
+    }
+
+    #[tokio::test]
+    async fn test_trim_goes_through_the_transcoder() {
+        let (url, seen) = mock_transcoder(StatusCode::OK).await;
+        let client = TranscoderClient::new(TranscoderConfig {
+            url: format!("{url}/"),
+            auth_token: Some("secret".to_string()),
+        });
+        let range = SampleRange {
+            start_secs: 900.0,
+            duration_secs: 45.5,
+        };
+
+        let sample = client
+            .trim(b"RIFF....WAVE".to_vec().into(), "audio.wav", &range)
+            .await
+            .unwrap();
+        assert_eq!(sample, b"sampled");
+
+        let seen = seen.lock().unwrap();
+        let (query, auth, upload) = &seen[0];
+        assert_eq!(query["target"], "mp3");
+        assert_eq!(query["start"], "900");
+        assert_eq!(query["duration"], "45.5");
+        assert_eq!(auth.as_deref(), Some("Bearer secret"));
+        assert_eq!(upload, b"RIFF....WAVE");
+    }
+
+    #[tokio::test]
+    async fn test_transcoder_errors_are_reported() {
+        let (url, _) = mock_transcoder(StatusCode::BAD_REQUEST).await;
+        let client = TranscoderClient::new(TranscoderConfig {
+            url,
+            auth_token: None,
+        });
+        let range = SampleRange {
+            start_secs: 0.0,
+            duration_secs: 30.0,
+        };
+
+        let err = client
+            .trim(b"RIFF....WAVE".to_vec().into(), "audio.wav", &range)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, AppError::Transcoder(message) if message.contains("400")));
+
+        // the audio URL is caller-controlled; the transcoder is never reached
+        let err = client
+            .sample_url("http://127.0.0.1/audio.wav", &range)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, AppError::BadRequest(_)));
+    }
+}
@@ -0,0 +1,598 @@
+//! Outbound webhook notifications for moderation events.
+//!
+//! Events are POSTed as JSON envelopes (`{"event": ..., "sent_at": ..., "data": ...}`)
+//! to a single configured URL. Each event type has its own enable flag.
+//! Routine deliveries go through a background queue; urgent ones are sent
+//! immediately on their own task. Both retry with backoff. A delivery that
+//! runs out of attempts, or doesn't fit in the queue, is kept in
+//! `failed_deliveries` so it can be looked at and retried from
+//! `/admin/failed-deliveries`.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum::{
+    extract::{Path, Query, State},
+    Json,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc;
+use tracing::{info, warn};
+
+use crate::auth::{Actor, RequireAdmin};
+use crate::db::{FailedDelivery, LabelDb};
+use crate::state::{AppError, AppState};
+
+/// Delivery attempts before giving up on an event.
+const MAX_ATTEMPTS: u32 = 4;
+
+const DELIVERY_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Queued (non-urgent) deliveries held before new ones are dropped.
+const QUEUE_CAPACITY: usize = 256;
+
+/// Event types the service can notify about.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WebhookEvent {
+    /// Claude image moderation auto-flagged an image.
+    ImageFlagged,
+    /// A user report reached the escalation threshold of distinct reporters.
+    ReportEscalated,
+}
+
+impl WebhookEvent {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::ImageFlagged => "image.flagged",
+            Self::ReportEscalated => "report.escalated",
+        }
+    }
+}
+
+/// Payload for `image.flagged`. Never includes the image bytes.
+#[derive(Debug, Clone, Serialize)]
+pub struct ImageFlaggedEvent {
+    pub image_id: String,
+    pub source_url: Option<String>,
+    pub scan_id: i64,
+    pub severity: String,
+    pub violated_categories: Vec<String>,
+    pub explanation: String,
+    /// Admin API link to the scan history for this image.
+    pub admin_url: String,
+}
+
+/// Payload for `report.escalated`.
+#[derive(Debug, Clone, Serialize)]
+pub struct ReportEscalatedEvent {
+    pub report_id: i32,
+    pub target_type: String,
+    pub target_id: String,
+    pub target_uri: Option<String>,
+    pub reason: String,
+    /// Distinct reporters: the original filer plus supporters.
+    pub reporters: i64,
+    /// Review batch opened for the report, if one was.
+    pub batch_id: Option<String>,
+    /// Admin API link to the report.
+    pub admin_url: String,
+}
+
+#[derive(Debug, Serialize)]
+struct Envelope<T: Serialize> {
+    event: &'static str,
+    sent_at: DateTime<Utc>,
+    data: T,
+}
+
+/// Webhook settings from config.
+#[derive(Debug, Clone)]
+pub struct WebhookConfig {
+    pub url: String,
+    /// Sent as `X-Moderation-Webhook-Secret` so the receiver can verify origin.
+    pub secret: Option<String>,
+    pub image_flagged_enabled: bool,
+    pub report_escalated_enabled: bool,
+}
+
+impl WebhookConfig {
+    /// Whether deliveries for an event type are turned on.
+    pub fn enabled_for(&self, event: WebhookEvent) -> bool {
+        match event {
+            WebhookEvent::ImageFlagged => self.image_flagged_enabled,
+            WebhookEvent::ReportEscalated => self.report_escalated_enabled,
+        }
+    }
+}
+
+/// Sends webhook notifications.
+pub struct WebhookNotifier {
+    config: WebhookConfig,
+    http: reqwest::Client,
+    queue: mpsc::Sender<serde_json::Value>,
+    /// Where failed deliveries are kept. Without it they're only logged.
+    db: Option<Arc<LabelDb>>,
+}
+
+impl WebhookNotifier {
+    /// Create a notifier and start its background delivery worker.
+    pub fn spawn(config: WebhookConfig, db: Option<Arc<LabelDb>>) -> Self {
+        let http = reqwest::Client::builder()
+            .timeout(DELIVERY_TIMEOUT)
+            .build()
+            .unwrap_or_default();
+        let (queue, mut rx) = mpsc::channel::<serde_json::Value>(QUEUE_CAPACITY);
+
+        let worker_http = http.clone();
+        let worker_config = config.clone();
+        let worker_db = db.clone();
+        tokio::spawn(async move {
+            while let Some(body) = rx.recv().await {
+                deliver_or_keep(
+                    &worker_http,
+                    &worker_config,
+                    worker_db.as_deref(),
+                    &body,
+                    MAX_ATTEMPTS,
+                )
+                .await;
+            }
+        });
+
+        Self {
+            config,
+            http,
+            queue,
+            db,
+        }
+    }
+
+    /// Notify that an image was auto-flagged.
+    ///
+    /// High-severity flags are delivered immediately on their own task rather
+    /// than waiting behind the queue.
+    pub fn image_flagged(&self, event: ImageFlaggedEvent, urgent: bool) {
+        self.send(WebhookEvent::ImageFlagged, event, urgent);
+    }
+
+    /// Build the `report.escalated` delivery for an escalation, if that event
+    /// is turned on: the target URL and the envelope. The escalation keeps it
+    /// in `failed_deliveries` in the same transaction and then hands the row
+    /// to [`Self::deliver_kept`], so a committed escalation isn't lost to a
+    /// restart before its webhook goes out.
+    pub fn report_escalated(
+        &self,
+        event: ReportEscalatedEvent,
+    ) -> Option<(String, serde_json::Value)> {
+        let body = self.envelope(WebhookEvent::ReportEscalated, event)?;
+        Some((self.config.url.clone(), body))
+    }
+
+    /// Deliver a delivery kept before it was sent, on its own task, and
+    /// record the outcome on its row: delivered, or the last error so it
+    /// stays listed for a retry.
+    pub fn deliver_kept(&self, delivery: FailedDelivery) {
+        let http = self.http.clone();
+        let config = self.config.clone();
+        let db = self.db.clone();
+        tokio::spawn(async move {
+            let (attempts, error) =
+                match deliver(&http, &config, &delivery.payload, MAX_ATTEMPTS).await {
+                    Ok(attempt) => (attempt, None),
+                    Err(e) => (MAX_ATTEMPTS, Some(e)),
+                };
+            let Some(db) = db else {
+                return;
+            };
+            if let Err(e) = db
+                .record_delivery_attempts(delivery.id, attempts as i32, error.as_deref())
+                .await
+            {
+                warn!(id = delivery.id, error = %e, "failed to record webhook delivery");
+            }
+        });
+    }
+
+    /// The URL deliveries currently go to.
+    pub fn url(&self) -> &str {
+        &self.config.url
+    }
+
+    fn send<T: Serialize>(&self, event: WebhookEvent, data: T, urgent: bool) {
+        let Some(body) = self.envelope(event, data) else {
+            return;
+        };
+
+        if urgent {
+            let http = self.http.clone();
+            let config = self.config.clone();
+            let db = self.db.clone();
+            tokio::spawn(async move {
+                deliver_or_keep(&http, &config, db.as_deref(), &body, MAX_ATTEMPTS).await;
+            });
+        } else if let Err(e) = self.queue.try_send(body) {
+            warn!(event = event.as_str(), "webhook queue full, dropping event");
+            if let Some(db) = self.db.clone() {
+                let target = self.config.url.clone();
+                let body = e.into_inner();
+                tokio::spawn(async move {
+                    keep_failed(&db, &target, &body, "webhook queue full", 0).await;
+                });
+            }
+        }
+    }
+
+    /// The envelope for an event, or `None` if the event is turned off (or,
+    /// logged, if it doesn't serialize).
+    fn envelope<T: Serialize>(&self, event: WebhookEvent, data: T) -> Option<serde_json::Value> {
+        if !self.config.enabled_for(event) {
+            return None;
+        }
+        match serde_json::to_value(Envelope {
+            event: event.as_str(),
+            sent_at: Utc::now(),
+            data,
+        }) {
+            Ok(body) => Some(body),
+            Err(e) => {
+                warn!(event = event.as_str(), error = %e, "failed to serialize webhook event");
+                None
+            }
+        }
+    }
+
+    /// Send a kept delivery again, once, to the configured URL.
+    pub async fn retry(&self, delivery: &FailedDelivery) -> Result<(), String> {
+        post(&self.http, &self.config, &delivery.payload).await
+    }
+}
+
+/// Deliver a body, keeping it in `failed_deliveries` if every attempt fails.
+async fn deliver_or_keep(
+    http: &reqwest::Client,
+    config: &WebhookConfig,
+    db: Option<&LabelDb>,
+    body: &serde_json::Value,
+    attempts: u32,
+) {
+    if let Err(error) = deliver(http, config, body, attempts).await {
+        if let Some(db) = db {
+            keep_failed(db, &config.url, body, &error, attempts as i32).await;
+        }
+    }
+}
+
+async fn keep_failed(
+    db: &LabelDb,
+    target: &str,
+    body: &serde_json::Value,
+    error: &str,
+    attempts: i32,
+) {
+    let event = body["event"].as_str().unwrap_or("unknown");
+    match db
+        .record_failed_delivery(target, body, error, attempts)
+        .await
+    {
+        Ok(id) => info!(event, id, "failed webhook delivery kept for retry"),
+        Err(e) => warn!(event, error = %e, "failed to record failed webhook delivery"),
+    }
+}
+
+/// POST a body, retrying with backoff on failure. Returns the attempt that
+/// got through, or the last error if none did.
+async fn deliver(
+    http: &reqwest::Client,
+    config: &WebhookConfig,
+    body: &serde_json::Value,
+    attempts: u32,
+) -> Result<u32, String> {
+    let event = body["event"].as_str().unwrap_or("unknown");
+    let mut last_error = String::new();
+    for attempt in 1..=attempts {
+        match post(http, config, body).await {
+            Ok(()) => {
+                info!(event, attempt, "webhook delivered");
+                return Ok(attempt);
+            }
+            Err(e) => {
+                warn!(event, attempt, error = %e, "webhook delivery failed");
+                last_error = e;
+            }
+        }
+        if attempt < attempts {
+            tokio::time::sleep(retry_delay(attempt)).await;
+        }
+    }
+    warn!(event, "giving up on webhook delivery");
+    Err(last_error)
+}
+
+/// One POST of a body. Rejections carry the status and response body.
+async fn post(
+    http: &reqwest::Client,
+    config: &WebhookConfig,
+    body: &serde_json::Value,
+) -> Result<(), String> {
+    let mut request = http.post(&config.url).json(body);
+    if let Some(secret) = &config.secret {
+        request = request.header("X-Moderation-Webhook-Secret", secret);
+    }
+    let response = request.send().await.map_err(|e| e.to_string())?;
+    let status = response.status();
+    if status.is_success() {
+        return Ok(());
+    }
+    let text = response.text().await.unwrap_or_default();
+    Err(format!("{status}: {text}"))
+}
+
+/// Backoff before the next attempt: 1s, 2s, 4s, ...
+pub(crate) fn retry_delay(attempt: u32) -> Duration {
+    Duration::from_secs(1 << (attempt - 1).min(5))
+}
+
+/// Query parameters for listing failed deliveries.
+#[derive(Debug, Deserialize)]
+pub struct FailedDeliveriesQuery {
+    /// Also list deliveries a retry got through.
+    #[serde(default)]
+    pub include_delivered: bool,
+    #[serde(default = "default_limit")]
+    pub limit: i64,
+}
+
+fn default_limit() -> i64 {
+    50
+}
+
+#[derive(Debug, Serialize)]
+pub struct FailedDeliveriesResponse {
+    pub deliveries: Vec<FailedDelivery>,
+}
+
+/// Webhook deliveries that failed every attempt, newest first.
+///
+/// GET /admin/failed-deliveries
+pub async fn list_failed_deliveries(
+    State(state): State<AppState>,
+    Query(query): Query<FailedDeliveriesQuery>,
+) -> Result<Json<FailedDeliveriesResponse>, AppError> {
+    let db = state.db.as_ref().ok_or(AppError::LabelerNotConfigured)?;
+    let deliveries = db
+        .list_failed_deliveries(query.include_delivered, query.limit.clamp(1, 200))
+        .await?;
+    Ok(Json(FailedDeliveriesResponse { deliveries }))
+}
+
+/// Response after retrying a failed delivery.
+#[derive(Debug, Serialize)]
+pub struct RetryDeliveryResponse {
+    pub delivered: bool,
+    pub delivery: FailedDelivery,
+}
+
+/// Send a failed delivery again, once. Deliveries kept for another URL than
+/// the configured webhook are refused with 409 rather than sent to a target
+/// they weren't meant for. A retry that fails too is recorded on the row
+/// rather than returned as an error.
+///
+/// POST /admin/failed-deliveries/:id/retry
+pub async fn retry_failed_delivery(
+    State(state): State<AppState>,
+    _: RequireAdmin,
+    actor: Actor,
+    Path(id): Path<i64>,
+) -> Result<Json<RetryDeliveryResponse>, AppError> {
+    let db = state.db.as_ref().ok_or(AppError::LabelerNotConfigured)?;
+    let webhook = state
+        .webhook
+        .as_ref()
+        .ok_or(AppError::WebhookNotConfigured)?;
+
+    let delivery = db
+        .get_failed_delivery(id)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("failed delivery {id} not found")))?;
+    if delivery.delivered_at.is_some() {
+        return Err(AppError::Conflict(format!(
+            "failed delivery {id} was already delivered"
+        )));
+    }
+    // only send the payload where it was meant to go
+    if delivery.target != webhook.url() {
+        return Err(AppError::Conflict(format!(
+            "failed delivery {id} was for {}, but the webhook now goes to {}",
+            delivery.target,
+            webhook.url()
+        )));
+    }
+
+    let outcome = webhook.retry(&delivery).await;
+    let delivery = db
+        .record_delivery_retry(id, outcome.as_ref().err().map(String::as_str))
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("failed delivery {id} not found")))?;
+    let delivered = outcome.is_ok();
+
+    db.record_audit_event(
+        "retry_delivery",
+        &id.to_string(),
+        actor.as_str(),
+        serde_json::json!({
+            "event": delivery.event,
+            "delivered": delivered,
+            "error": outcome.err(),
+        }),
+    )
+    .await?;
+    info!(id, event = %delivery.event, delivered, "failed webhook delivery retried");
+
+    Ok(Json(RetryDeliveryResponse {
+        delivered,
+        delivery,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{http::StatusCode, routing::post, Router};
+
+    use super::*;
+
+    /// A stand-in receiver answering every delivery with `status`.
+    async fn mock_receiver(status: StatusCode) -> WebhookConfig {
+        let app = Router::new().route("/hook", post(move || async move { (status, "down") }));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await });
+        WebhookConfig {
+            url,
+            secret: None,
+            image_flagged_enabled: true,
+            report_escalated_enabled: true,
+        }
+    }
+
+    fn escalated_body() -> serde_json::Value {
+        serde_json::json!({
+            "event": WebhookEvent::ReportEscalated.as_str(),
+            "sent_at": Utc::now(),
+            "data": {"report_id": 1},
+        })
+    }
+
+    #[test]
+    fn test_retry_delay_backs_off() {
+        assert_eq!(retry_delay(1), Duration::from_secs(1));
+        assert_eq!(retry_delay(2), Duration::from_secs(2));
+        assert_eq!(retry_delay(3), Duration::from_secs(4));
+        assert_eq!(retry_delay(20), Duration::from_secs(32));
+    }
+
+    #[test]
+    fn test_image_flagged_envelope() {
+        let body = serde_json::to_value(Envelope {
+            event: WebhookEvent::ImageFlagged.as_str(),
+            sent_at: Utc::now(),
+            data: ImageFlaggedEvent {
+                image_id: "abc123".to_string(),
+                source_url: None,
+                scan_id: 7,
+                severity: "high".to_string(),
+                violated_categories: vec!["gore".to_string()],
+                explanation: "graphic violence".to_string(),
+                admin_url: "https://moderation.plyr.fm/admin/image-scans?image_id=abc123"
+                    .to_string(),
+            },
+        })
+        .unwrap();
+
+        assert_eq!(body["event"], "image.flagged");
+        assert_eq!(body["data"]["severity"], "high");
+        assert_eq!(body["data"]["violated_categories"][0], "gore");
+        let keys: Vec<&str> = body["data"]
+            .as_object()
+            .unwrap()
+            .keys()
+            .map(String::as_str)
+            .collect();
+        assert!(!keys.iter().any(|k| k.contains("bytes") || *k == "image"));
+    }
+
+    #[test]
+    fn test_event_enable_flags() {
+        let config = WebhookConfig {
+            url: "https://example.com/hook".to_string(),
+            secret: None,
+            image_flagged_enabled: false,
+            report_escalated_enabled: true,
+        };
+        assert!(!config.enabled_for(WebhookEvent::ImageFlagged));
+        assert!(config.enabled_for(WebhookEvent::ReportEscalated));
+    }
+
+    #[tokio::test]
+    async fn test_deliver_returns_the_last_error() {
+        let http = reqwest::Client::new();
+        let config = mock_receiver(StatusCode::SERVICE_UNAVAILABLE).await;
+        let err = deliver(&http, &config, &escalated_body(), 1)
+            .await
+            .unwrap_err();
+        assert!(err.contains("503") && err.contains("down"), "{err}");
+
+        let config = mock_receiver(StatusCode::NO_CONTENT).await;
+        assert!(deliver(&http, &config, &escalated_body(), 1).await.is_ok());
+    }
+
+    /// Runs against `MODERATION_TEST_DATABASE_URL` when it's set; skipped
+    /// otherwise.
+    #[tokio::test]
+    async fn test_failed_delivery_is_kept() {
+        let Ok(url) = std::env::var("MODERATION_TEST_DATABASE_URL") else {
+            return;
+        };
+        let db = LabelDb::connect(&url).await.unwrap();
+        db.migrate().await.unwrap();
+
+        let http = reqwest::Client::new();
+        let config = mock_receiver(StatusCode::INTERNAL_SERVER_ERROR).await;
+        let body = escalated_body();
+        deliver_or_keep(&http, &config, Some(&db), &body, 1).await;
+
+        let kept = db.list_failed_deliveries(false, 200).await.unwrap();
+        let delivery = kept
+            .iter()
+            .find(|d| d.target == config.url)
+            .expect("the failed delivery is kept");
+        assert_eq!(delivery.event, "report.escalated");
+        assert_eq!(delivery.payload, body);
+        assert_eq!(delivery.attempts, 1);
+        assert!(delivery.error.contains("500"), "{}", delivery.error);
+        assert!(delivery.delivered_at.is_none());
+
+        // a successful retry takes it off the default list
+        let retried = db
+            .record_delivery_retry(delivery.id, None)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(retried.attempts, 2);
+        assert!(retried.delivered_at.is_some());
+        let kept = db.list_failed_deliveries(false, 200).await.unwrap();
+        assert!(!kept.iter().any(|d| d.id == delivery.id));
+    }
+
+    /// Runs against `MODERATION_TEST_DATABASE_URL` when it's set; skipped
+    /// otherwise.
+    #[tokio::test]
+    async fn test_kept_delivery_marked_delivered() {
+        let Ok(url) = std::env::var("MODERATION_TEST_DATABASE_URL") else {
+            return;
+        };
+        let db = Arc::new(LabelDb::connect(&url).await.unwrap());
+        db.migrate().await.unwrap();
+
+        let config = mock_receiver(StatusCode::NO_CONTENT).await;
+        let id = db
+            .record_failed_delivery(&config.url, &escalated_body(), "not delivered yet", 0)
+            .await
+            .unwrap();
+        let kept = db.get_failed_delivery(id).await.unwrap().unwrap();
+        WebhookNotifier::spawn(config, Some(db.clone())).deliver_kept(kept);
+
+        let mut delivered = None;
+        for _ in 0..50 {
+            let delivery = db.get_failed_delivery(id).await.unwrap().unwrap();
+            if delivery.delivered_at.is_some() {
+                delivered = Some(delivery);
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        let delivered = delivered.expect("the delivery is marked delivered");
+        assert_eq!(delivered.attempts, 1);
+        assert!(delivered.retried_at.is_none());
+    }
+}
@@ -1,5 +1,6 @@
 //! ATProto XRPC endpoints for the labeler protocol.
 
+use std::future::Future;
 use std::sync::Arc;
 
 use axum::{
@@ -16,7 +17,7 @@ use tokio::sync::broadcast;
 use tokio_stream::wrappers::BroadcastStream;
 use tracing::error;
 
-use crate::db::LabelDb;
+use crate::db::{LabelDb, LabelRow};
 use crate::labels::Label;
 use crate::state::{AppError, AppState};
 
@@ -27,21 +28,38 @@ use crate::state::{AppError, AppState};
 pub struct QueryLabelsParams {
     pub uri_patterns: String, // comma-separated
     pub sources: Option<String>,
+    /// Comma-separated label values to restrict results to.
+    pub vals: Option<String>,
     pub cursor: Option<String>,
     pub limit: Option<i64>,
+    /// Also count every matching label from the cursor on. Off by default
+    /// since counting large tables is expensive.
+    #[serde(default, alias = "with_count")]
+    pub with_count: bool,
 }
 
 #[derive(Debug, Serialize)]
 pub struct QueryLabelsResponse {
     pub cursor: Option<String>,
     pub labels: Vec<Label>,
+    /// Labels matching the query from the cursor on, if `with_count` was set.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub approximate_total: Option<i64>,
 }
 
 #[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct SubscribeLabelsParams {
     pub cursor: Option<i64>,
+    /// Also stream labels issued as non-production namespace DIDs, which are
+    /// left out by default.
+    #[serde(default)]
+    pub include_namespaced: bool,
 }
 
+/// Rows fetched per backfill query, bounding memory for far-behind cursors.
+const BACKFILL_CHUNK_SIZE: i64 = 500;
+
 #[derive(Serialize)]
 struct SubscribeLabelsMessage {
     seq: i64,
@@ -65,12 +83,16 @@ pub async fn query_labels(
     let sources: Option<Vec<String>> = params
         .sources
         .map(|s| s.split(',').map(|s| s.trim().to_string()).collect());
+    let vals: Option<Vec<String>> = params
+        .vals
+        .map(|s| s.split(',').map(|s| s.trim().to_string()).collect());
     let limit = params.limit.unwrap_or(50).clamp(1, 250);
 
     let (rows, cursor) = db
         .query_labels(
             &uri_patterns,
             sources.as_deref(),
+            vals.as_deref(),
             params.cursor.as_deref(),
             limit,
         )
@@ -78,7 +100,27 @@ pub async fn query_labels(
 
     let labels: Vec<Label> = rows.iter().map(|r| r.to_label()).collect();
 
-    Ok(Json(QueryLabelsResponse { cursor, labels }))
+    // counted separately, so labels emitted between the two queries can make
+    // this slightly off
+    let approximate_total = if params.with_count {
+        Some(
+            db.count_labels(
+                &uri_patterns,
+                sources.as_deref(),
+                vals.as_deref(),
+                params.cursor.as_deref(),
+            )
+            .await?,
+        )
+    } else {
+        None
+    };
+
+    Ok(Json(QueryLabelsResponse {
+        cursor,
+        labels,
+        approximate_total,
+    }))
 }
 
 /// WebSocket subscription for real-time label updates.
@@ -92,48 +134,84 @@ pub async fn subscribe_labels(
         .label_tx
         .clone()
         .ok_or(AppError::LabelerNotConfigured)?;
+    let replay_tx = state
+        .label_replay_tx
+        .clone()
+        .ok_or(AppError::LabelerNotConfigured)?;
+    let hidden_sources = match (&state.signer, params.include_namespaced) {
+        (Some(signer), false) => signer.namespace_dids(),
+        _ => Vec::new(),
+    };
 
-    Ok(ws.on_upgrade(move |socket| handle_subscribe(socket, db, label_tx, params.cursor)))
+    Ok(ws.on_upgrade(move |socket| {
+        handle_subscribe(
+            socket,
+            db,
+            label_tx,
+            replay_tx,
+            params.cursor,
+            hidden_sources,
+        )
+    }))
+}
+
+/// Whether a label goes out on the firehose.
+fn is_streamed(label: &Label, hidden_sources: &[String]) -> bool {
+    !hidden_sources.contains(&label.src)
 }
 
 async fn handle_subscribe(
     mut socket: WebSocket,
     db: Arc<LabelDb>,
     label_tx: broadcast::Sender<(i64, Label)>,
+    replay_tx: broadcast::Sender<(i64, Label)>,
     cursor: Option<i64>,
+    hidden_sources: Vec<String>,
 ) {
+    // Subscribe before backfilling so labels emitted meanwhile are buffered;
+    // anything already covered by the backfill is skipped by seq below.
+    let rx = label_tx.subscribe();
+    let mut stream = BroadcastStream::new(rx);
+    let mut replays = BroadcastStream::new(replay_tx.subscribe());
+
     // If cursor provided, backfill from that point
     let start_seq = if let Some(c) = cursor {
-        // Send historical labels first
-        match db.get_labels_since(c, 1000).await {
-            Ok(rows) => {
-                for row in &rows {
-                    let msg = SubscribeLabelsMessage {
-                        seq: row.seq,
-                        labels: vec![row.to_label()],
-                    };
-                    if let Ok(json) = serde_json::to_string(&msg) {
-                        if socket.send(Message::Text(json)).await.is_err() {
-                            return;
-                        }
+        // Send historical labels first, one chunk at a time
+        let mut backfill = Backfill::new(c, BACKFILL_CHUNK_SIZE);
+        loop {
+            let rows = match backfill
+                .next_chunk(|since, limit| db.get_labels_since(since, limit))
+                .await
+            {
+                Ok(Some(rows)) => rows,
+                Ok(None) => break,
+                Err(e) => {
+                    error!(error = %e, "failed to backfill labels");
+                    return;
+                }
+            };
+            for row in &rows {
+                let label = row.to_label();
+                if !is_streamed(&label, &hidden_sources) {
+                    continue;
+                }
+                let msg = SubscribeLabelsMessage {
+                    seq: row.seq,
+                    labels: vec![label],
+                };
+                if let Ok(json) = serde_json::to_string(&msg) {
+                    if socket.send(Message::Text(json)).await.is_err() {
+                        return;
                     }
                 }
-                rows.last().map(|r| r.seq).unwrap_or(c)
-            }
-            Err(e) => {
-                error!(error = %e, "failed to backfill labels");
-                return;
             }
         }
+        backfill.cursor()
     } else {
         // Start from current position
         db.get_latest_seq().await.unwrap_or(0)
     };
 
-    // Subscribe to live updates
-    let rx = label_tx.subscribe();
-    let mut stream = BroadcastStream::new(rx);
-
     let mut last_seq = start_seq;
 
     loop {
@@ -142,7 +220,7 @@ async fn handle_subscribe(
             Some(result) = stream.next() => {
                 match result {
                     Ok((seq, label)) => {
-                        if seq > last_seq {
+                        if seq > last_seq && is_streamed(&label, &hidden_sources) {
                             let msg = SubscribeLabelsMessage {
                                 seq,
                                 labels: vec![label],
@@ -158,6 +236,23 @@ async fn handle_subscribe(
                     Err(_) => continue, // Lagged, skip
                 }
             }
+            // Rebroadcasts keep their original seq and bypass `last_seq`;
+            // consumers that already applied a seq can ignore the repeat.
+            Some(result) = replays.next() => {
+                if let Ok((seq, label)) = result {
+                    if is_streamed(&label, &hidden_sources) {
+                        let msg = SubscribeLabelsMessage {
+                            seq,
+                            labels: vec![label],
+                        };
+                        if let Ok(json) = serde_json::to_string(&msg) {
+                            if socket.send(Message::Text(json)).await.is_err() {
+                                break;
+                            }
+                        }
+                    }
+                }
+            }
             // Check for client disconnect
             msg = socket.recv() => {
                 match msg {
@@ -173,3 +268,128 @@ async fn handle_subscribe(
         }
     }
 }
+
+/// Pages through labels after a cursor in bounded chunks.
+///
+/// Each chunk is sent before the next is fetched, so a cursor of 0 on a large
+/// table never holds more than one chunk in memory. Labels inserted while
+/// paging are picked up by later chunks; paging stops at the first short chunk.
+struct Backfill {
+    cursor: i64,
+    chunk_size: i64,
+    done: bool,
+}
+
+impl Backfill {
+    fn new(cursor: i64, chunk_size: i64) -> Self {
+        Self {
+            cursor,
+            chunk_size,
+            done: false,
+        }
+    }
+
+    /// Seq of the last label returned (or the starting cursor).
+    fn cursor(&self) -> i64 {
+        self.cursor
+    }
+
+    /// Fetch the next chunk, or `None` once caught up.
+    async fn next_chunk<F, Fut>(&mut self, fetch: F) -> Result<Option<Vec<LabelRow>>, sqlx::Error>
+    where
+        F: FnOnce(i64, i64) -> Fut,
+        Fut: Future<Output = Result<Vec<LabelRow>, sqlx::Error>>,
+    {
+        if self.done {
+            return Ok(None);
+        }
+        let rows = fetch(self.cursor, self.chunk_size).await?;
+        if (rows.len() as i64) < self.chunk_size {
+            self.done = true;
+        }
+        match rows.last() {
+            Some(last) => {
+                self.cursor = last.seq;
+                Ok(Some(rows))
+            }
+            None => Ok(None),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn row(seq: i64) -> LabelRow {
+        LabelRow {
+            seq,
+            src: "did:plc:labeler".to_string(),
+            uri: format!("at://did:plc:artist/fm.plyr.track/{seq}"),
+            cid: None,
+            val: "copyright-violation".to_string(),
+            neg: false,
+            cts: Utc::now(),
+            exp: None,
+            sig: vec![],
+        }
+    }
+
+    #[tokio::test]
+    async fn test_backfill_far_behind_cursor_is_chunked() {
+        let table: Vec<LabelRow> = (1..=2_345).map(row).collect();
+        let mut backfill = Backfill::new(0, 500);
+        let mut sent = Vec::new();
+        let mut fetches = 0;
+
+        while let Some(chunk) = backfill
+            .next_chunk(|since, limit| {
+                fetches += 1;
+                let rows: Vec<LabelRow> = table
+                    .iter()
+                    .filter(|r| r.seq > since)
+                    .take(limit as usize)
+                    .cloned()
+                    .collect();
+                async move { Ok(rows) }
+            })
+            .await
+            .unwrap()
+        {
+            assert!(chunk.len() <= 500);
+            sent.extend(chunk.into_iter().map(|r| r.seq));
+        }
+
+        assert_eq!(sent, (1..=2_345).collect::<Vec<_>>());
+        assert_eq!(backfill.cursor(), 2_345);
+        // four full chunks plus the short final one
+        assert_eq!(fetches, 5);
+    }
+
+    #[tokio::test]
+    async fn test_backfill_caught_up_cursor_sends_nothing() {
+        let mut backfill = Backfill::new(42, 500);
+        let chunk = backfill
+            .next_chunk(|_, _| async { Ok(Vec::new()) })
+            .await
+            .unwrap();
+        assert!(chunk.is_none());
+        assert_eq!(backfill.cursor(), 42);
+    }
+
+    #[test]
+    fn test_namespaced_labels_are_hidden_from_the_firehose() {
+        let prod = row(1).to_label();
+        let mut dev = row(2);
+        dev.uri = "at://did:plc:artist/fm.plyr.dev.track/2".to_string();
+        dev.src = "did:plc:labeler-dev".to_string();
+        let dev = dev.to_label();
+
+        let hidden = vec!["did:plc:labeler-dev".to_string()];
+        assert!(is_streamed(&prod, &hidden));
+        assert!(!is_streamed(&dev, &hidden));
+        // includeNamespaced=true hides nothing
+        assert!(is_streamed(&dev, &[]));
+    }
+}
@@ -1,14 +1,22 @@
-// Set up auth header listener first (before any htmx requests)
-let currentToken = null;
+// Set up actor header listener first (before any htmx requests).
+// Auth rides on the httpOnly session cookie set by /admin/login.
 let currentFilter = 'pending'; // track current filter state for flags
+let currentArtist = ''; // track artist handle/did search for flags
+let currentQuery = ''; // track title/match text search for flags
+let currentSort = 'newest'; // track sort order for flags
 let currentReportStatus = 'open'; // track current status filter for reports
 let currentTab = 'copyright'; // track current tab
 let reportsLoaded = false; // track if reports have been loaded
+let imagesLoaded = false; // track if image scans have been loaded
+let currentActor = localStorage.getItem('mod_actor') || ''; // name recorded in the audit log
+
+// Header identifying who is acting, for the audit log (omitted when unset)
+function actorHeader() {
+    return currentActor ? { 'X-Moderation-Actor': currentActor } : {};
+}
 
 document.body.addEventListener('htmx:configRequest', function(evt) {
-    if (currentToken) {
-        evt.detail.headers['X-Moderation-Key'] = currentToken;
-    }
+    Object.assign(evt.detail.headers, actorHeader());
 });
 
 // Track filter changes via htmx
@@ -19,6 +27,13 @@ document.body.addEventListener('htmx:afterRequest', function(evt) {
     if (filterMatch) {
         currentFilter = filterMatch[1];
     }
+    // Track flags artist search
+    if (url.startsWith('/admin/flags-html')) {
+        const params = new URLSearchParams(url.split('?')[1] || '');
+        currentArtist = params.get('did') || params.get('artist') || '';
+        currentQuery = params.get('q') || '';
+        currentSort = params.get('sort') || 'newest';
+    }
     // Track reports status filter
     const statusMatch = url.match(/status=(\w+)/);
     if (statusMatch) {
@@ -26,36 +41,45 @@ document.body.addEventListener('htmx:afterRequest', function(evt) {
     }
 });
 
-function showMain() {
-    document.getElementById('main-content').style.display = 'block';
+// The server only serves this page to a signed-in browser, and serves the
+// login form in its place once the session has expired
+function showLogin() {
+    location.reload();
 }
 
-function authenticate() {
-    const token = document.getElementById('auth-token').value;
-    if (token && token !== '••••••••') {
-        localStorage.setItem('mod_token', token);
-        currentToken = token;
-        showMain();
-        htmx.trigger('#flags-list', 'load');
-    }
+function setActor(name) {
+    currentActor = name.trim();
+    localStorage.setItem('mod_actor', currentActor);
 }
 
-// Check for saved token on load
-const savedToken = localStorage.getItem('mod_token');
-if (savedToken) {
-    document.getElementById('auth-token').value = '••••••••';
-    currentToken = savedToken;
-    showMain();
-    // Trigger load after DOM is ready and htmx is initialized
-    setTimeout(() => htmx.trigger('#flags-list', 'load'), 0);
+async function logout() {
+    await fetch('/admin/logout', { method: 'POST' });
+    showLogin();
 }
 
+document.getElementById('auth-actor').value = currentActor;
+loadReasons();
+
 // Handle auth errors
 document.body.addEventListener('htmx:responseError', function(evt) {
     if (evt.detail.xhr.status === 401) {
-        localStorage.removeItem('mod_token');
-        currentToken = null;
-        showToast('invalid token', 'error');
+        showLogin();
+    } else if (evt.detail.xhr.status === 403) {
+        showToast('read-only token: this action needs admin access', 'error');
+    } else if (evt.detail.xhr.status === 409) {
+        // e.g. deleting a batch that already has decisions
+        let message = 'conflict';
+        try {
+            message = JSON.parse(evt.detail.xhr.responseText).message;
+        } catch (e) {}
+        showToast(message, 'error');
+    } else if (evt.detail.pathInfo?.requestPath === '/admin/flags/rescan') {
+        // cooldown, missing audio url and AuDD errors come back as JSON
+        let message = 'rescan failed';
+        try {
+            message = JSON.parse(evt.detail.xhr.responseText).message;
+        } catch (e) {}
+        showToast(message, 'error');
     }
 });
 
@@ -67,14 +91,31 @@ function showToast(message, type) {
     setTimeout(() => { toast.style.display = 'none'; }, 3000);
 }
 
-// Reason options for false positive resolution
-const REASONS = [
-    { value: 'original_artist', label: 'original artist' },
-    { value: 'licensed', label: 'licensed' },
-    { value: 'fingerprint_noise', label: 'fp noise' },
-    { value: 'cover_version', label: 'cover/remix' },
-    { value: 'other', label: 'other' }
-];
+// Reason options for false positive resolution, loaded from the server
+let REASONS = [];
+
+// Reasons whose resolutions need notes (mirrors ResolutionReason::requires_notes)
+const NOTES_REQUIRED = ['licensed'];
+
+function escapeHtml(s) {
+    const div = document.createElement('div');
+    div.textContent = s;
+    return div.innerHTML;
+}
+
+async function loadReasons() {
+    const response = await fetch('/admin/resolution-reasons');
+    if (!response.ok) {
+        showToast('failed to load resolution reasons', 'error');
+        return;
+    }
+    const data = await response.json();
+    REASONS = data.reasons.map(r => ({
+        value: r.key,
+        label: r.label,
+        requiresNotes: NOTES_REQUIRED.includes(r.key)
+    }));
+}
 
 // Step 1 -> Step 2: Show reason selection buttons
 function showReasonSelect(btn) {
@@ -85,7 +126,7 @@ function showReasonSelect(btn) {
         <div class="reason-select">
             ${REASONS.map(r => `
                 <button type="button" class="reason-btn" data-reason="${r.value}" onclick="selectReason(this, '${r.value}')">
-                    ${r.label}
+                    ${escapeHtml(r.label)}
                 </button>
             `).join('')}
             <button type="button" class="reason-btn cancel" onclick="cancelResolve(this)">✕</button>
@@ -96,12 +137,18 @@ function showReasonSelect(btn) {
 // Step 2 -> Step 3: Show confirmation
 function selectReason(btn, reason) {
     const flow = btn.closest('.resolve-flow');
-    const reasonLabel = REASONS.find(r => r.value === reason)?.label || reason;
+    const selected = REASONS.find(r => r.value === reason);
+    const reasonLabel = selected?.label || reason;
+    // Licensed flags stay on record, so ask where the license lives
+    const notesInput = selected?.requiresNotes
+        ? '<input type="text" class="resolve-notes" placeholder="licensing notes (required)">'
+        : '';
 
     // Replace with confirmation
     flow.innerHTML = `
         <div class="confirm-step">
-            <span class="confirm-text">resolve as <strong>${reasonLabel}</strong>?</span>
+            <span class="confirm-text">resolve as <strong>${escapeHtml(reasonLabel)}</strong>?</span>
+            ${notesInput}
             <button type="button" class="btn btn-confirm" onclick="confirmResolve(this, '${reason}')">confirm</button>
             <button type="button" class="reason-btn cancel" onclick="cancelResolve(this)">cancel</button>
         </div>
@@ -113,6 +160,13 @@ function confirmResolve(btn, reason) {
     const flow = btn.closest('.resolve-flow');
     const uri = flow.dataset.uri;
     const val = flow.dataset.val;
+    const notesInput = flow.querySelector('.resolve-notes');
+    const notes = notesInput ? notesInput.value.trim() : '';
+    if (notesInput && !notes) {
+        showToast('licensing notes are required', 'error');
+        notesInput.focus();
+        return;
+    }
 
     // Show loading state
     btn.disabled = true;
@@ -123,16 +177,23 @@ function confirmResolve(btn, reason) {
     params.append('uri', uri);
     params.append('val', val);
     params.append('reason', reason);
+    if (notes) {
+        params.append('notes', notes);
+    }
 
     fetch('/admin/resolve-htmx', {
         method: 'POST',
         headers: {
-            'X-Moderation-Key': currentToken,
+            ...actorHeader(),
             'Content-Type': 'application/x-www-form-urlencoded'
         },
         body: params
     })
     .then(response => {
+        if (response.status === 401) {
+            showLogin();
+            throw new Error('session expired');
+        }
         if (response.ok) {
             return response.text();
         }
@@ -144,8 +205,10 @@ function confirmResolve(btn, reason) {
         if (match) {
             showToast(match[0], 'success');
         }
-        // Refresh flags list with current filter
-        refreshFlagsList();
+        // Swap the resolved card in place; refresh the list if it isn't there
+        if (!swapFlagCard(html)) {
+            refreshFlagsList();
+        }
     })
     .catch(err => {
         showToast('failed to resolve: ' + err.message, 'error');
@@ -153,9 +216,71 @@ function confirmResolve(btn, reason) {
     });
 }
 
+// Replace a flag card with the out-of-band card in a response, keeping the
+// list and scroll position as they are. Returns false if there was nothing
+// to swap.
+function swapFlagCard(html) {
+    const doc = new DOMParser().parseFromString(html, 'text/html');
+    const card = doc.querySelector('.flag-card[hx-swap-oob]');
+    const current = card && document.getElementById(card.id);
+    if (!current) {
+        return false;
+    }
+    card.removeAttribute('hx-swap-oob');
+    current.replaceWith(card);
+    htmx.process(card);
+    return true;
+}
+
+// Confirm a flag as a real violation (or retry a failed enforcement)
+function confirmFlag(btn) {
+    const uri = btn.dataset.uri;
+    if (!confirm(`confirm ${uri} as a copyright violation?`)) {
+        return;
+    }
+
+    btn.disabled = true;
+    btn.textContent = '...';
+
+    fetch('/admin/confirm', {
+        method: 'POST',
+        headers: {
+            ...actorHeader(),
+            'Content-Type': 'application/json'
+        },
+        body: JSON.stringify({ uri })
+    })
+    .then(response => {
+        if (response.status === 401) {
+            showLogin();
+            throw new Error('session expired');
+        }
+        if (response.ok) {
+            return response.json();
+        }
+        throw new Error('Failed to confirm');
+    })
+    .then(data => {
+        showToast(data.message, 'success');
+        refreshFlagsList();
+    })
+    .catch(err => {
+        showToast('failed: ' + err.message, 'error');
+        refreshFlagsList();
+    });
+}
+
+// Refresh the stats strip (served from a 60s server-side cache)
+function refreshStats() {
+    htmx.ajax('GET', '/admin/stats-html', '#stats-strip');
+}
+
 // Refresh flags list preserving current filter
 function refreshFlagsList() {
-    htmx.ajax('GET', `/admin/flags-html?filter=${currentFilter}`, '#flags-list');
+    refreshStats();
+    const artist = currentArtist ? `&artist=${encodeURIComponent(currentArtist)}` : '';
+    const q = currentQuery ? `&q=${encodeURIComponent(currentQuery)}` : '';
+    htmx.ajax('GET', `/admin/flags-html?filter=${currentFilter}&sort=${currentSort}${artist}${q}`, '#flags-list');
 }
 
 // Cancel: restore original button
@@ -168,6 +293,163 @@ function cancelResolve(btn) {
     `;
 }
 
+// Track context edit: swap the edit button for fields prefilled from the card
+function showContextEdit(btn) {
+    const form = btn.closest('.context-edit');
+    form.innerHTML = `
+        <input type="text" class="edit-title" placeholder="track title">
+        <input type="text" class="edit-handle" placeholder="artist handle">
+        <input type="number" class="edit-track-id" placeholder="track id">
+        <button type="button" class="btn btn-confirm" onclick="saveContextEdit(this)">save</button>
+        <button type="button" class="reason-btn cancel" onclick="cancelContextEdit(this)">cancel</button>
+    `;
+    // set as properties so quotes in stored titles can't break the markup
+    form.querySelector('.edit-title').value = form.dataset.title;
+    form.querySelector('.edit-handle').value = form.dataset.handle;
+    form.querySelector('.edit-track-id').value = form.dataset.trackId;
+}
+
+// Send only the fields that changed, so the rest of the context is kept
+function saveContextEdit(btn) {
+    const form = btn.closest('.context-edit');
+    const body = { uri: form.dataset.uri };
+    const title = form.querySelector('.edit-title').value.trim();
+    const handle = form.querySelector('.edit-handle').value.trim();
+    const trackId = form.querySelector('.edit-track-id').value.trim();
+    if (title && title !== form.dataset.title) body.track_title = title;
+    if (handle && handle !== form.dataset.handle) body.artist_handle = handle;
+    if (trackId && trackId !== form.dataset.trackId) body.track_id = Number(trackId);
+    if (Object.keys(body).length === 1) {
+        cancelContextEdit(btn);
+        return;
+    }
+
+    btn.disabled = true;
+    btn.textContent = '...';
+
+    fetch('/admin/context/edit', {
+        method: 'POST',
+        headers: {
+            ...actorHeader(),
+            'Content-Type': 'application/json'
+        },
+        body: JSON.stringify(body)
+    })
+    .then(response => {
+        if (response.status === 401) {
+            showLogin();
+            throw new Error('session expired');
+        }
+        if (response.ok) {
+            return response.json();
+        }
+        throw new Error('Failed to update context');
+    })
+    .then(() => {
+        showToast('track context updated', 'success');
+        refreshFlagsList();
+    })
+    .catch(err => {
+        showToast('failed: ' + err.message, 'error');
+        cancelContextEdit(btn);
+    });
+}
+
+function cancelContextEdit(btn) {
+    const form = btn.closest('.context-edit');
+    form.innerHTML = `
+        <button type="button" class="btn btn-secondary" onclick="showContextEdit(this)">edit</button>
+    `;
+}
+
+// Bulk resolve: URIs of the checked flag cards
+function selectedFlagUris() {
+    return Array.from(document.querySelectorAll('.flag-select:checked')).map(cb => cb.value);
+}
+
+// Keep the bulk button's count in sync with the checkboxes
+function updateBulkSelection() {
+    const btn = document.getElementById('bulk-resolve-btn');
+    if (!btn) return;
+    const count = selectedFlagUris().length;
+    btn.disabled = count === 0;
+    btn.textContent = count ? `resolve selected (${count})` : 'resolve selected';
+}
+
+// Bulk step 1 -> 2: pick a reason shared by every selected flag
+function showBulkReasonSelect(btn) {
+    const bulk = btn.closest('.bulk-resolve');
+    bulk.innerHTML = `
+        <div class="reason-select">
+            ${REASONS.map(r => `
+                <button type="button" class="reason-btn" onclick="confirmBulkResolve(this, '${r.value}')">
+                    ${escapeHtml(r.label)}
+                </button>
+            `).join('')}
+            <button type="button" class="reason-btn cancel" onclick="cancelBulkResolve(this)">✕</button>
+        </div>
+    `;
+}
+
+// Bulk step 2: confirm and submit
+function confirmBulkResolve(btn, reason) {
+    const uris = selectedFlagUris();
+    const selected = REASONS.find(r => r.value === reason);
+    const reasonLabel = selected?.label || reason;
+    if (!uris.length || !confirm(`resolve ${uris.length} flags as ${reasonLabel}?`)) {
+        cancelBulkResolve(btn);
+        return;
+    }
+    let notes = null;
+    if (selected?.requiresNotes) {
+        notes = (prompt('licensing notes (required)') || '').trim();
+        if (!notes) {
+            cancelBulkResolve(btn);
+            return;
+        }
+    }
+
+    fetch('/admin/resolve-bulk', {
+        method: 'POST',
+        headers: {
+            ...actorHeader(),
+            'Content-Type': 'application/json'
+        },
+        body: JSON.stringify({ uris, reason, notes })
+    })
+    .then(response => {
+        if (response.status === 401) {
+            showLogin();
+            throw new Error('session expired');
+        }
+        if (response.ok) {
+            return response.json();
+        }
+        throw new Error('Failed to resolve');
+    })
+    .then(data => {
+        const failed = data.outcomes.length - data.resolved_count;
+        const message = failed
+            ? `resolved ${data.resolved_count}, ${failed} failed`
+            : `resolved ${data.resolved_count}`;
+        showToast(message, failed ? 'error' : 'success');
+        refreshFlagsList();
+    })
+    .catch(err => {
+        showToast('failed to resolve: ' + err.message, 'error');
+        cancelBulkResolve(btn);
+    });
+}
+
+// Bulk cancel: restore the button
+function cancelBulkResolve(btn) {
+    const bulk = btn.closest('.bulk-resolve');
+    bulk.innerHTML = `
+        <button type="button" id="bulk-resolve-btn" class="btn btn-warning" onclick="showBulkReasonSelect(this)">resolve selected</button>
+    `;
+    updateBulkSelection();
+}
+
 // Tab switching
 function switchTab(tab) {
     currentTab = tab;
@@ -187,6 +469,27 @@ function switchTab(tab) {
         reportsLoaded = true;
         refreshReportsList();
     }
+    if (tab === 'images' && !imagesLoaded) {
+        imagesLoaded = true;
+        refreshImagesList();
+        refreshSensitiveImagesList();
+    }
+    if (tab === 'batches') {
+        refreshBatchesList();
+    }
+    if (tab === 'audit') {
+        refreshAuditLog();
+    }
+}
+
+// Refresh review batches and their progress
+function refreshBatchesList() {
+    htmx.ajax('GET', '/admin/batches-html', '#batches-list');
+}
+
+// Refresh recent audit log entries
+function refreshAuditLog() {
+    htmx.ajax('GET', '/admin/audit-html', '#audit-list');
 }
 
 // Refresh reports list
@@ -194,25 +497,74 @@ function refreshReportsList() {
     htmx.ajax('GET', `/admin/reports-html?status=${currentReportStatus}`, '#reports-list');
 }
 
-// Placeholder for sensitive images (future)
+// Refresh image scan history
 function refreshImagesList() {
-    showToast('images refresh coming soon', 'success');
+    htmx.ajax('GET', '/admin/image-scans-html', '#image-scans-list');
+}
+
+// Refresh sensitive images list
+function refreshSensitiveImagesList() {
+    htmx.ajax('GET', '/admin/sensitive-images-html', '#sensitive-images-list');
+}
+
+// Remove a sensitive image entry
+function removeSensitiveImage(btn) {
+    const id = parseInt(btn.dataset.id, 10);
+    if (!confirm(`remove sensitive image ${id}?`)) {
+        return;
+    }
+
+    btn.disabled = true;
+    btn.textContent = '...';
+
+    fetch('/admin/sensitive-images/remove', {
+        method: 'POST',
+        headers: {
+            ...actorHeader(),
+            'Content-Type': 'application/json'
+        },
+        body: JSON.stringify({ id })
+    })
+    .then(response => {
+        if (response.status === 401) {
+            showLogin();
+            throw new Error('session expired');
+        }
+        if (response.ok) {
+            return response.json();
+        }
+        throw new Error('Failed to remove image');
+    })
+    .then(data => {
+        showToast(data.message, 'success');
+        refreshSensitiveImagesList();
+    })
+    .catch(err => {
+        showToast('failed: ' + err.message, 'error');
+        btn.disabled = false;
+        btn.textContent = 'remove';
+    });
 }
 
 // Report action options
 const REPORT_ACTIONS = [
     { value: 'resolved', label: 'resolve' },
     { value: 'dismissed', label: 'dismiss' },
-    { value: 'investigating', label: 'investigating' }
+    { value: 'investigating', label: 'investigating' },
+    { value: 'label', label: 'emit label', needsUri: true }
 ];
 
+// keep in step with labels::LABEL_VALUES
+const LABEL_VALUES = ['copyright-violation', 'copyright-review', 'sensitive-image', 'sexual', 'porn'];
+
 // Show report action buttons
 function showReportActions(btn) {
     const flow = btn.closest('.report-actions-flow');
+    const actions = REPORT_ACTIONS.filter(a => !a.needsUri || flow.dataset.canLabel);
 
     flow.innerHTML = `
         <div class="reason-select">
-            ${REPORT_ACTIONS.map(a => `
+            ${actions.map(a => `
                 <button type="button" class="reason-btn" onclick="selectReportAction(this, '${a.value}')">
                     ${a.label}
                 </button>
@@ -226,9 +578,15 @@ function showReportActions(btn) {
 function selectReportAction(btn, action) {
     const flow = btn.closest('.report-actions-flow');
     const actionLabel = REPORT_ACTIONS.find(a => a.value === action)?.label || action;
+    const labelSelect = action === 'label' ? `
+            <select class="label-select">
+                ${LABEL_VALUES.map(v => `
+                    <option value="${v}"${v === flow.dataset.defaultLabel ? ' selected' : ''}>${v}</option>
+                `).join('')}
+            </select>` : '';
 
     flow.innerHTML = `
-        <div class="confirm-step">
+        <div class="confirm-step">${labelSelect}
             <input type="text" class="notes-input" placeholder="admin notes (optional)" id="report-notes-${flow.dataset.id}">
             <button type="button" class="btn btn-confirm" onclick="confirmReportAction(this, '${action}')">
                 ${actionLabel}
@@ -248,26 +606,71 @@ function confirmReportAction(btn, action) {
     btn.disabled = true;
     btn.textContent = '...';
 
-    fetch(`/admin/reports/${reportId}/resolve`, {
+    if (action === 'label') {
+        emitReportLabel(btn, reportId, flow.querySelector('.label-select').value, notes);
+        return;
+    }
+
+    // resolved_by comes from the actor header on the server
+    const params = new URLSearchParams();
+    params.append('status', action);
+    params.append('notes', notes);
+
+    fetch(`/admin/reports/${reportId}/resolve-htmx`, {
+        method: 'POST',
+        headers: {
+            ...actorHeader(),
+            'Content-Type': 'application/x-www-form-urlencoded'
+        },
+        body: params
+    })
+    .then(response => {
+        if (response.status === 401) {
+            showLogin();
+            throw new Error('session expired');
+        }
+        if (response.ok) {
+            return response.text();
+        }
+        throw new Error('Failed to resolve report');
+    })
+    .then(html => {
+        // Parse and show toast from response
+        const match = html.match(/report \d+: [^<]+/);
+        showToast(match ? match[0] : `report ${action}`, 'success');
+        refreshReportsList();
+    })
+    .catch(err => {
+        showToast('failed: ' + err.message, 'error');
+        cancelReportAction(btn);
+    });
+}
+
+// Emit a label against the reported uri; the server resolves the report
+function emitReportLabel(btn, reportId, val, notes) {
+    fetch(`/admin/reports/${reportId}/label`, {
         method: 'POST',
         headers: {
-            'X-Moderation-Key': currentToken,
+            ...actorHeader(),
             'Content-Type': 'application/json'
         },
-        body: JSON.stringify({
-            status: action,
-            admin_notes: notes || null,
-            resolved_by: 'admin' // TODO: track actual admin identity
-        })
+        body: JSON.stringify({ val, admin_notes: notes || null })
     })
     .then(response => {
+        if (response.status === 401) {
+            showLogin();
+            throw new Error('session expired');
+        }
         if (response.ok) {
             return response.json();
         }
-        throw new Error('Failed to resolve report');
+        return response.json()
+            .catch(() => ({}))
+            .then(body => { throw new Error(body.message || 'Failed to emit label'); });
     })
     .then(data => {
-        showToast(`report ${action}`, 'success');
+        const verb = data.created ? 'labeled' : 'already labeled';
+        showToast(`report ${reportId}: ${verb} ${data.val}`, 'success');
         refreshReportsList();
     })
     .catch(err => {
@@ -276,6 +679,86 @@ function confirmReportAction(btn, action) {
     });
 }
 
+// Bulk close: ids of the checked report cards
+function selectedReportIds() {
+    return Array.from(document.querySelectorAll('.report-select:checked')).map(cb => parseInt(cb.value, 10));
+}
+
+// Keep the report bulk button's count in sync with the checkboxes
+function updateReportBulkSelection() {
+    const btn = document.getElementById('report-bulk-btn');
+    if (!btn) return;
+    const count = selectedReportIds().length;
+    btn.disabled = count === 0;
+    btn.textContent = count ? `close selected (${count})` : 'close selected';
+}
+
+// Bulk step 1 -> 2: pick resolve or dismiss, with shared notes
+function showReportBulkActions(btn) {
+    const bulk = btn.closest('.bulk-resolve');
+    bulk.innerHTML = `
+        <div class="confirm-step">
+            <input type="text" class="notes-input" placeholder="admin notes (optional)">
+            <button type="button" class="reason-btn" onclick="confirmReportBulk(this, 'resolved')">resolve</button>
+            <button type="button" class="reason-btn" onclick="confirmReportBulk(this, 'dismissed')">dismiss</button>
+            <button type="button" class="reason-btn cancel" onclick="cancelReportBulk(this)">✕</button>
+        </div>
+    `;
+}
+
+// Bulk step 2: confirm and submit; resolved_by comes from the actor header
+function confirmReportBulk(btn, status) {
+    const ids = selectedReportIds();
+    const verb = status === 'dismissed' ? 'dismiss' : 'resolve';
+    if (!ids.length || !confirm(`${verb} ${ids.length} reports?`)) {
+        cancelReportBulk(btn);
+        return;
+    }
+    const notes = btn.closest('.bulk-resolve').querySelector('.notes-input').value.trim();
+
+    fetch('/admin/reports/resolve-bulk', {
+        method: 'POST',
+        headers: {
+            ...actorHeader(),
+            'Content-Type': 'application/json'
+        },
+        body: JSON.stringify({ ids, status, admin_notes: notes || null })
+    })
+    .then(response => {
+        if (response.status === 401) {
+            showLogin();
+            throw new Error('session expired');
+        }
+        if (response.ok) {
+            return response.json();
+        }
+        return response.json()
+            .catch(() => ({}))
+            .then(body => { throw new Error(body.message || 'Failed to close reports'); });
+    })
+    .then(data => {
+        const skipped = data.outcomes.length - data.resolved_count;
+        const message = skipped
+            ? `${status} ${data.resolved_count}, ${skipped} already closed`
+            : `${status} ${data.resolved_count}`;
+        showToast(message, 'success');
+        refreshReportsList();
+    })
+    .catch(err => {
+        showToast('failed: ' + err.message, 'error');
+        cancelReportBulk(btn);
+    });
+}
+
+// Bulk cancel: restore the button
+function cancelReportBulk(btn) {
+    const bulk = btn.closest('.bulk-resolve');
+    bulk.innerHTML = `
+        <button type="button" id="report-bulk-btn" class="btn btn-warning" onclick="showReportBulkActions(this)">close selected</button>
+    `;
+    updateReportBulkSelection();
+}
+
 // Cancel report action
 function cancelReportAction(btn) {
     const flow = btn.closest('.report-actions-flow');
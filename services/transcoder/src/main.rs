@@ -1,29 +1,546 @@
 use std::{
+    collections::{BTreeMap, HashMap},
     env,
+    fmt::Write as _,
+    future::Future,
     net::SocketAddr,
     path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
 };
 
 use anyhow::anyhow;
 use axum::{
     body::Body,
-    extract::{DefaultBodyLimit, Multipart, Query, Request},
-    http::{header, HeaderValue, StatusCode},
+    extract::{DefaultBodyLimit, Multipart, Path as UrlPath, Query, Request, State},
+    http::{header, HeaderMap, HeaderValue, StatusCode},
     middleware::{self, Next},
     response::{IntoResponse, Response},
     routing::{get, post},
     Json, Router,
 };
+use hmac::{Hmac, Mac};
 use sanitize_filename::sanitize;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use tempfile::TempDir;
-use tokio::{fs::File, io::AsyncWriteExt, net::TcpListener, process::Command};
-use tokio_util::io::ReaderStream;
+use tokio::{
+    fs::File,
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpListener,
+    process::Command,
+    sync::{OwnedSemaphorePermit, Semaphore},
+};
+use tokio_util::{io::ReaderStream, task::TaskTracker};
 use tracing::{error, info, warn};
+use tracing_subscriber::{filter::LevelFilter, EnvFilter};
+
+mod store;
+
+use store::JobStore;
 
 #[derive(Debug, Deserialize, Default)]
 struct TranscodeParams {
     target: Option<String>,
+    /// output bitrate for lossy targets, in kbps ("192" or "192k")
+    bitrate: Option<String>,
+    /// AAC profile for the m4a target: "lc" (default) or "he"
+    aac_profile: Option<String>,
+    /// run the encode in the background and POST the result here instead of
+    /// returning the output in the response
+    callback_url: Option<String>,
+    /// seconds into the source to start the output at
+    start: Option<f64>,
+    /// seconds of audio to keep, from `start` (or the beginning)
+    duration: Option<f64>,
+}
+
+/// lossy bitrate bounds accepted on `?bitrate=`, in kbps.
+const MIN_BITRATE_KBPS: u32 = 32;
+const MAX_BITRATE_KBPS: u32 = 320;
+
+/// HE-AAC (SBR) only pays off at low bitrates; above this LC sounds better
+/// for the same size, so we refuse the combination rather than silently
+/// producing a worse file.
+const HE_AAC_MAX_KBPS: u32 = 96;
+const HE_AAC_DEFAULT_KBPS: u32 = 64;
+
+/// the only encoder ffmpeg has for HE-AAC. it's non-free, so the static
+/// ffmpeg in our image and distro packages ship without it.
+const HE_AAC_ENCODER: &str = "libfdk_aac";
+
+/// longest filename stem (in chars) echoed back in Content-Disposition.
+const MAX_DOWNLOAD_STEM_CHARS: usize = 120;
+
+/// how long to keep answering (with 503s) after a shutdown signal before we
+/// stop accepting connections, so the load balancer sees health fail first.
+const DRAIN_GRACE: Duration = Duration::from_secs(5);
+
+/// `Retry-After` sent with transcodes rejected while draining, in seconds.
+const DRAIN_RETRY_AFTER_SECS: u64 = 5;
+
+/// upper bounds of the output/input size ratio buckets. a lossless source
+/// lands well under 0.25 as mp3; anything over 1 means the output grew.
+const SIZE_RATIO_BUCKETS: &[f64] = &[0.05, 0.1, 0.25, 0.5, 0.75, 1.0, 1.5, 2.0, 4.0];
+
+/// upper bounds of the encode wall-time buckets, in seconds.
+const ENCODE_SECONDS_BUCKETS: &[f64] = &[0.5, 1.0, 2.5, 5.0, 10.0, 30.0, 60.0, 120.0, 300.0];
+
+/// finished async jobs, and their output files, are dropped this long after
+/// they complete.
+const JOB_TTL_SECS: i64 = 60 * 60;
+
+/// async jobs encoding at once unless `TRANSCODER_MAX_ASYNC_JOBS` says
+/// otherwise. further `?callback_url=` requests get a 429.
+const DEFAULT_MAX_ASYNC_JOBS: usize = 4;
+
+/// how long shutdown waits for running async jobs (and their callbacks)
+/// after the server has stopped.
+const JOB_DRAIN_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// attempts at POSTing a job result to its callback URL, 1s then 2s apart.
+const CALLBACK_ATTEMPTS: u32 = 3;
+const CALLBACK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// header carrying `sha256=<hex HMAC-SHA256 of the body>` on job callbacks.
+const CALLBACK_SIGNATURE_HEADER: &str = "X-Transcoder-Signature";
+
+#[derive(Clone)]
+struct AppState {
+    /// set once shutdown starts; new transcodes are refused from then on.
+    draining: Arc<AtomicBool>,
+    metrics: Arc<Mutex<Metrics>>,
+    /// async transcodes by job id, with their output files.
+    jobs: Arc<Mutex<HashMap<String, Job>>>,
+    /// background tasks of async jobs, waited on at shutdown.
+    job_tasks: TaskTracker,
+    /// one permit per async job that may be encoding at once.
+    job_slots: Arc<Semaphore>,
+
+    /// persisted job status, when `TRANSCODER_JOBS_DATABASE_URL` is set. lets
+    /// `GET /jobs/:id` answer for jobs from before a restart.
+    store: Option<JobStore>,
+    /// client for job callbacks.
+    http: reqwest::Client,
+    /// key callbacks are signed with; unsigned when unset (local dev).
+    callback_secret: Option<String>,
+    /// whether ffmpeg has `HE_AAC_ENCODER`; without it `aac_profile=he` is
+    /// refused.
+    he_aac: bool,
+}
+
+impl Default for AppState {
+    fn default() -> Self {
+        Self {
+            draining: Default::default(),
+            metrics: Default::default(),
+            jobs: Default::default(),
+            job_tasks: TaskTracker::new(),
+            job_slots: Arc::new(Semaphore::new(DEFAULT_MAX_ASYNC_JOBS)),
+            store: None,
+            http: Default::default(),
+            callback_secret: None,
+            he_aac: false,
+        }
+    }
+}
+
+impl AppState {
+    fn is_draining(&self) -> bool {
+        self.draining.load(Ordering::SeqCst)
+    }
+
+    fn check_not_draining(&self) -> Result<(), AppError> {
+        if self.is_draining() {
+            return Err(AppError::Draining);
+        }
+        Ok(())
+    }
+
+    fn jobs(&self) -> std::sync::MutexGuard<'_, HashMap<String, Job>> {
+        self.jobs.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn job_report(&self, job_id: &str) -> Option<JobReport> {
+        self.jobs().get(job_id).map(|job| job.report(job_id))
+    }
+
+    /// take a slot for a new async job, or 429 when all are encoding.
+    fn reserve_job_slot(&self) -> Result<OwnedSemaphorePermit, AppError> {
+        self.job_slots
+            .clone()
+            .try_acquire_owned()
+            .map_err(|_| AppError::TooManyJobs)
+    }
+
+    /// stop taking jobs and wait, up to `JOB_DRAIN_TIMEOUT`, for the running
+    /// ones to finish and deliver their callbacks.
+    async fn wait_for_jobs(&self) {
+        self.job_tasks.close();
+        if self.job_tasks.is_empty() {
+            return;
+        }
+        info!(running = self.job_tasks.len(), "waiting for async jobs");
+        if tokio::time::timeout(JOB_DRAIN_TIMEOUT, self.job_tasks.wait())
+            .await
+            .is_err()
+        {
+            warn!(
+                running = self.job_tasks.len(),
+                "async jobs still running at shutdown, abandoning them"
+            );
+        }
+    }
+
+    /// write a job's latest state to the store, if there is one. a failed
+    /// write is logged rather than failing the job.
+    async fn persist_job(&self, report: &JobReport) {
+        let Some(store) = &self.store else {
+            return;
+        };
+        if let Err(e) = store.save(report).await {
+            warn!(job_id = report.job_id, error = %e, "failed to persist job status");
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum JobStatus {
+    /// accepted, encode not started yet.
+    Submitted,
+    Running,
+    Succeeded,
+    Failed,
+}
+
+impl JobStatus {
+    fn as_str(self) -> &'static str {
+        match self {
+            JobStatus::Submitted => "submitted",
+            JobStatus::Running => "running",
+            JobStatus::Succeeded => "succeeded",
+            JobStatus::Failed => "failed",
+        }
+    }
+
+    fn parse(s: &str) -> Option<Self> {
+        match s {
+            "submitted" => Some(JobStatus::Submitted),
+            "running" => Some(JobStatus::Running),
+            "succeeded" => Some(JobStatus::Succeeded),
+            "failed" => Some(JobStatus::Failed),
+            _ => None,
+        }
+    }
+}
+
+/// an async transcode started with `?callback_url=`.
+struct Job {
+    target: String,
+    /// upload filename stem, for the output's Content-Disposition.
+    original_name: String,
+    status: JobStatus,
+    /// unix seconds.
+    submitted_at: i64,
+    finished_at: Option<i64>,
+    output: Option<JobOutput>,
+    error: Option<String>,
+    /// holds the upload and the output; deleted when the job is dropped.
+    _dir: TempDir,
+}
+
+struct JobOutput {
+    path: PathBuf,
+    bytes: u64,
+    sha256: String,
+}
+
+impl Job {
+    fn report(&self, job_id: &str) -> JobReport {
+        JobReport {
+            job_id: job_id.to_string(),
+            status: self.status,
+            target: self.target.clone(),
+            output_url: self
+                .output
+                .as_ref()
+                .map(|_| format!("/jobs/{job_id}/output")),
+            bytes: self.output.as_ref().map(|o| o.bytes),
+            sha256: self.output.as_ref().map(|o| o.sha256.clone()),
+            error: self.error.clone(),
+            submitted_at: self.submitted_at,
+            finished_at: self.finished_at,
+        }
+    }
+}
+
+/// job state returned by `GET /jobs/:id` and POSTed to the callback URL.
+/// `output_url` is a path on this service, fetched with the same token.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct JobReport {
+    job_id: String,
+    status: JobStatus,
+    target: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    output_url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    bytes: Option<u64>,
+    /// hex SHA-256 of the output file.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    sha256: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+    /// unix seconds.
+    submitted_at: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    finished_at: Option<i64>,
+}
+
+fn unix_now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs() as i64)
+}
+
+/// prometheus histogram with fixed bucket bounds.
+#[derive(Debug, Clone)]
+struct Histogram {
+    bounds: &'static [f64],
+    /// observations per bucket (not cumulative); the last is `+Inf`.
+    counts: Vec<u64>,
+    sum: f64,
+}
+
+impl Histogram {
+    fn new(bounds: &'static [f64]) -> Self {
+        Self {
+            bounds,
+            counts: vec![0; bounds.len() + 1],
+            sum: 0.0,
+        }
+    }
+
+    fn observe(&mut self, value: f64) {
+        let bucket = self
+            .bounds
+            .iter()
+            .position(|bound| value <= *bound)
+            .unwrap_or(self.bounds.len());
+        self.counts[bucket] += 1;
+        self.sum += value;
+    }
+
+    fn render(&self, out: &mut String, name: &str, target: &str) {
+        let mut cumulative = 0;
+        for (i, count) in self.counts.iter().enumerate() {
+            cumulative += count;
+            let le = self
+                .bounds
+                .get(i)
+                .map_or_else(|| "+Inf".to_string(), f64::to_string);
+            let _ = writeln!(
+                out,
+                "{name}_bucket{{target=\"{target}\",le=\"{le}\"}} {cumulative}"
+            );
+        }
+        let _ = writeln!(out, "{name}_sum{{target=\"{target}\"}} {}", self.sum);
+        let _ = writeln!(out, "{name}_count{{target=\"{target}\"}} {cumulative}");
+    }
+}
+
+/// per-target-format transcode stats.
+#[derive(Debug, Clone)]
+struct FormatMetrics {
+    succeeded: u64,
+    failed: u64,
+    /// output bytes / input bytes, for successful transcodes.
+    size_ratio: Histogram,
+    /// ffmpeg wall time in seconds, for successful transcodes.
+    encode_seconds: Histogram,
+}
+
+impl Default for FormatMetrics {
+    fn default() -> Self {
+        Self {
+            succeeded: 0,
+            failed: 0,
+            size_ratio: Histogram::new(SIZE_RATIO_BUCKETS),
+            encode_seconds: Histogram::new(ENCODE_SECONDS_BUCKETS),
+        }
+    }
+}
+
+/// transcode stats served on `/metrics`, keyed by target format. only
+/// formats `codec_args` accepts get here, so the label set stays small.
+#[derive(Debug, Default)]
+struct Metrics {
+    formats: BTreeMap<String, FormatMetrics>,
+}
+
+impl Metrics {
+    fn record_success(
+        &mut self,
+        target: &str,
+        input_bytes: u64,
+        output_bytes: u64,
+        elapsed: Duration,
+    ) {
+        let format = self.formats.entry(target.to_string()).or_default();
+        format.succeeded += 1;
+        format.encode_seconds.observe(elapsed.as_secs_f64());
+        if input_bytes > 0 {
+            format
+                .size_ratio
+                .observe(output_bytes as f64 / input_bytes as f64);
+        }
+    }
+
+    fn record_failure(&mut self, target: &str) {
+        self.formats.entry(target.to_string()).or_default().failed += 1;
+    }
+
+    /// prometheus text exposition format.
+    fn render(&self) -> String {
+        let mut out = String::new();
+        out.push_str(
+            "# HELP transcoder_transcodes_total ffmpeg runs by target format and outcome.\n",
+        );
+        out.push_str("# TYPE transcoder_transcodes_total counter\n");
+        for (target, format) in &self.formats {
+            for (outcome, count) in [("ok", format.succeeded), ("failed", format.failed)] {
+                let _ = writeln!(
+                    out,
+                    "transcoder_transcodes_total{{target=\"{target}\",outcome=\"{outcome}\"}} {count}"
+                );
+            }
+        }
+
+        out.push_str(
+            "# HELP transcoder_size_ratio output/input size ratio of successful transcodes.\n",
+        );
+        out.push_str("# TYPE transcoder_size_ratio histogram\n");
+        for (target, format) in &self.formats {
+            format
+                .size_ratio
+                .render(&mut out, "transcoder_size_ratio", target);
+        }
+
+        out.push_str(
+            "# HELP transcoder_encode_seconds ffmpeg wall time of successful transcodes.\n",
+        );
+        out.push_str("# TYPE transcoder_encode_seconds histogram\n");
+        for (target, format) in &self.formats {
+            format
+                .encode_seconds
+                .render(&mut out, "transcoder_encode_seconds", target);
+        }
+        out
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+enum AacProfile {
+    #[default]
+    Lc,
+    He,
+}
+
+impl AacProfile {
+    fn parse(s: &str) -> Result<Self, AppError> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "lc" => Ok(Self::Lc),
+            "he" => Ok(Self::He),
+            other => Err(AppError::BadRequest(format!(
+                "unsupported aac_profile: {} (expected \"lc\" or \"he\")",
+                other
+            ))),
+        }
+    }
+}
+
+/// encoder knobs resolved from the request query.
+#[derive(Debug, Default)]
+struct EncodeOptions {
+    bitrate_kbps: Option<u32>,
+    aac_profile: AacProfile,
+    start_secs: Option<f64>,
+    duration_secs: Option<f64>,
+}
+
+impl EncodeOptions {
+    fn from_params(params: &TranscodeParams) -> Result<Self, AppError> {
+        let bitrate_kbps = params.bitrate.as_deref().map(parse_bitrate).transpose()?;
+        let aac_profile = params
+            .aac_profile
+            .as_deref()
+            .map(AacProfile::parse)
+            .transpose()?
+            .unwrap_or_default();
+        let start_secs = params
+            .start
+            .map(|s| check_seconds("start", s))
+            .transpose()?;
+        let duration_secs = params
+            .duration
+            .map(|d| check_seconds("duration", d))
+            .transpose()?;
+        if duration_secs == Some(0.0) {
+            return Err(AppError::BadRequest("duration must be above 0".to_string()));
+        }
+        Ok(Self {
+            bitrate_kbps,
+            aac_profile,
+            start_secs,
+            duration_secs,
+        })
+    }
+
+    /// ffmpeg arguments for trimming: the seek goes before `-i` so ffmpeg
+    /// skips ahead in the input instead of decoding up to the start, and the
+    /// duration after it limits the output.
+    fn trim_args(&self) -> (Vec<String>, Vec<String>) {
+        let seek = self
+            .start_secs
+            .map(|s| vec!["-ss".to_string(), s.to_string()])
+            .unwrap_or_default();
+        let limit = self
+            .duration_secs
+            .map(|d| vec!["-t".to_string(), d.to_string()])
+            .unwrap_or_default();
+        (seek, limit)
+    }
+}
+
+/// a time offset or length in seconds: finite and not negative.
+fn check_seconds(name: &str, secs: f64) -> Result<f64, AppError> {
+    if !secs.is_finite() || secs < 0.0 {
+        return Err(AppError::BadRequest(format!(
+            "{name} must be a number of seconds, got {secs}"
+        )));
+    }
+    Ok(secs)
+}
+
+fn parse_bitrate(raw: &str) -> Result<u32, AppError> {
+    let trimmed = raw.trim();
+    let digits = trimmed
+        .strip_suffix('k')
+        .or_else(|| trimmed.strip_suffix('K'))
+        .unwrap_or(trimmed);
+    let kbps: u32 = digits
+        .parse()
+        .map_err(|_| AppError::BadRequest(format!("invalid bitrate: {}", raw)))?;
+    if !(MIN_BITRATE_KBPS..=MAX_BITRATE_KBPS).contains(&kbps) {
+        return Err(AppError::BadRequest(format!(
+            "bitrate must be between {}k and {}k, got {}k",
+            MIN_BITRATE_KBPS, MAX_BITRATE_KBPS, kbps
+        )));
+    }
+    Ok(kbps)
 }
 
 #[derive(Debug, serde::Serialize)]
@@ -31,10 +548,31 @@ struct HealthResponse {
     status: &'static str,
 }
 
+/// tracing filter: `RUST_LOG` when set, otherwise `LOG_LEVEL` (trace, debug,
+/// info, warn or error) for every module. with neither, only errors are logged.
+fn log_filter() -> EnvFilter {
+    let rust_log = env::var("RUST_LOG").ok();
+    let log_level = env::var("LOG_LEVEL").ok();
+    match base_log_level(rust_log.as_deref(), log_level.as_deref()) {
+        Some(level) => EnvFilter::builder()
+            .with_default_directive(level.into())
+            .parse_lossy(""),
+        None => EnvFilter::from_default_env(),
+    }
+}
+
+/// level from `LOG_LEVEL`, unless a non-empty `RUST_LOG` overrides it
+fn base_log_level(rust_log: Option<&str>, log_level: Option<&str>) -> Option<LevelFilter> {
+    if rust_log.is_some_and(|v| !v.trim().is_empty()) {
+        return None;
+    }
+    log_level?.trim().parse().ok()
+}
+
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
     tracing_subscriber::fmt()
-        .with_env_filter(tracing_subscriber::EnvFilter::from_default_env())
+        .with_env_filter(log_filter())
         .with_target(false)
         .init();
 
@@ -43,15 +581,55 @@ async fn main() -> anyhow::Result<()> {
         .and_then(|v| v.parse().ok())
         .unwrap_or(512 * 1024 * 1024); // 512MB default
 
+    let max_async_jobs: usize = env::var("TRANSCODER_MAX_ASYNC_JOBS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(DEFAULT_MAX_ASYNC_JOBS);
+
     let auth_token = env::var("TRANSCODER_AUTH_TOKEN").ok();
+    let mut state = AppState {
+        job_slots: Arc::new(Semaphore::new(max_async_jobs)),
+        // callers already hold the auth token, so it doubles as the
+        // signing key unless a separate one is set
+        callback_secret: env::var("TRANSCODER_CALLBACK_SECRET")
+            .ok()
+            .or_else(|| auth_token.clone()),
+        he_aac: ffmpeg_has_encoder(HE_AAC_ENCODER).await,
+        ..Default::default()
+    };
+    if !state.he_aac {
+        info!(
+            encoder = HE_AAC_ENCODER,
+            "ffmpeg has no HE-AAC encoder, aac_profile=he will be refused"
+        );
+    }
+    if let Ok(url) = env::var("TRANSCODER_JOBS_DATABASE_URL") {
+        let instance_id = instance_id();
+        let store = JobStore::connect(&url, &instance_id)
+            .await
+            .map_err(|e| anyhow!("failed to open job store: {e}"))?;
+        let interrupted = store.fail_interrupted(unix_now()).await?;
+        if interrupted > 0 {
+            warn!(
+                interrupted,
+                instance_id, "marked jobs left unfinished by the last process as failed"
+            );
+        }
+        tokio::spawn(renew_job_leases(store.clone()));
+        state.store = Some(store);
+    }
 
     let app = Router::new()
         .route("/health", get(health))
+        .route("/metrics", get(metrics))
         .route("/transcode", post(transcode))
+        .route("/jobs/:id", get(get_job))
+        .route("/jobs/:id/output", get(get_job_output))
         .layer(middleware::from_fn(move |req, next| {
             auth_middleware(req, next, auth_token.clone())
         }))
-        .layer(DefaultBodyLimit::max(max_upload_bytes));
+        .layer(DefaultBodyLimit::max(max_upload_bytes))
+        .with_state(state.clone());
 
     let port: u16 = env::var("TRANSCODER_PORT")
         .ok()
@@ -61,13 +639,54 @@ async fn main() -> anyhow::Result<()> {
     let addr: SocketAddr = format!("{}:{}", host, port)
         .parse()
         .map_err(|e| anyhow!("invalid bind addr: {e}"))?;
-    info!(%addr, max_upload_bytes, "transcoder listening");
+    info!(%addr, max_upload_bytes, max_async_jobs, "transcoder listening");
 
     let listener = TcpListener::bind(addr).await?;
-    axum::serve(listener, app).await?;
+    axum::serve(listener, app)
+        .with_graceful_shutdown(shutdown_signal(state.clone()))
+        .await?;
+    state.wait_for_jobs().await;
     Ok(())
 }
 
+/// wait for ctrl-c or SIGTERM, then flip to draining. returning hands off to
+/// axum, which stops accepting and lets in-flight transcodes finish.
+async fn shutdown_signal(state: AppState) {
+    let ctrl_c = async {
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            error!(error = %e, "failed to listen for ctrl-c");
+            std::future::pending::<()>().await;
+        }
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut sigterm) => {
+                sigterm.recv().await;
+            }
+            Err(e) => {
+                error!(error = %e, "failed to listen for SIGTERM");
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {},
+        _ = terminate => {},
+    }
+
+    state.draining.store(true, Ordering::SeqCst);
+    info!(
+        grace_secs = DRAIN_GRACE.as_secs(),
+        "shutdown requested, draining"
+    );
+    tokio::time::sleep(DRAIN_GRACE).await;
+}
+
 async fn auth_middleware(
     req: Request,
     next: Next,
@@ -84,41 +703,149 @@ async fn auth_middleware(
         return Ok(next.run(req).await);
     };
 
-    // check for X-Transcoder-Key header
-    let token = req
-        .headers()
-        .get("X-Transcoder-Key")
-        .and_then(|v| v.to_str().ok());
+    check_token(req.headers(), &expected_token)?;
+    Ok(next.run(req).await)
+}
+
+/// validate the caller's token from `Authorization: Bearer` or, for existing
+/// clients, `X-Transcoder-Key`. the bearer header wins when both are sent.
+fn check_token(headers: &HeaderMap, expected_token: &str) -> Result<(), StatusCode> {
+    let bearer = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "));
+    let token = bearer.or_else(|| {
+        headers
+            .get("X-Transcoder-Key")
+            .and_then(|v| v.to_str().ok())
+    });
 
     match token {
-        Some(t) if t == expected_token => Ok(next.run(req).await),
+        Some(t) if constant_time_eq(t.as_bytes(), expected_token.as_bytes()) => Ok(()),
         Some(_) => {
             warn!("invalid auth token provided");
             Err(StatusCode::UNAUTHORIZED)
         }
         None => {
-            warn!("missing X-Transcoder-Key header");
+            warn!("missing Authorization or X-Transcoder-Key header");
             Err(StatusCode::UNAUTHORIZED)
         }
     }
 }
 
-async fn health() -> Json<HealthResponse> {
-    Json(HealthResponse { status: "ok" })
+/// compare secrets without returning early on the first differing byte.
+/// only the length leaks, which the token format already makes public.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter().zip(b).fold(0u8, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+/// 503 while draining so the load balancer stops routing here.
+async fn health(State(state): State<AppState>) -> (StatusCode, Json<HealthResponse>) {
+    if state.is_draining() {
+        (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(HealthResponse { status: "draining" }),
+        )
+    } else {
+        (StatusCode::OK, Json(HealthResponse { status: "ok" }))
+    }
+}
+
+async fn metrics(State(state): State<AppState>) -> Response {
+    let body = state
+        .metrics
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .render();
+    (
+        [(
+            header::CONTENT_TYPE,
+            HeaderValue::from_static("text/plain; version=0.0.4"),
+        )],
+        body,
+    )
+        .into_response()
 }
 
 async fn transcode(
+    State(state): State<AppState>,
     Query(params): Query<TranscodeParams>,
+    headers: HeaderMap,
     mut multipart: Multipart,
 ) -> Result<Response, AppError> {
-    let target_ext = params.target.unwrap_or_else(|| "mp3".to_string());
+    // refuse before reading the upload; ffmpeg would be killed mid-encode
+    state.check_not_draining()?;
+    let encode_opts = EncodeOptions::from_params(&params)?;
+    let callback_url = params
+        .callback_url
+        .as_deref()
+        .map(parse_callback_url)
+        .transpose()?;
+    let target_ext = target_format(params.target, &headers);
+    if target_ext == "m4a" && encode_opts.aac_profile == AacProfile::He && !state.he_aac {
+        return Err(AppError::BadRequest(format!(
+            "aac_profile=he needs an ffmpeg built with {HE_AAC_ENCODER}, which this transcoder doesn't have"
+        )));
+    }
+    let job_slot = match callback_url {
+        Some(_) => {
+            // an async job can't answer with a 400 once it's accepted
+            codec_args(&target_ext, &encode_opts)?;
+            Some(state.reserve_job_slot()?)
+        }
+        None => None,
+    };
 
     let temp_dir =
         tempfile::tempdir().map_err(|e| AppError::Io(format!("failed to create temp dir: {e}")))?;
-    let (input_path, original_name) = write_upload_to_disk(&mut multipart, &temp_dir).await?;
+    let (input_path, original_name, input_bytes) =
+        write_upload_to_disk(&mut multipart, &temp_dir).await?;
 
     let output_path = temp_dir.path().join(format!("output.{}", target_ext));
-    run_ffmpeg(&input_path, &output_path, &target_ext).await?;
+
+    if let (Some(callback_url), Some(job_slot)) = (callback_url, job_slot) {
+        let encoded = {
+            let state = state.clone();
+            let target_ext = target_ext.clone();
+            async move {
+                encode(
+                    &state,
+                    &input_path,
+                    &output_path,
+                    &target_ext,
+                    &encode_opts,
+                    input_bytes,
+                )
+                .await
+                .map(|_| output_path)
+            }
+        };
+        let job_id = start_job(
+            &state,
+            job_slot,
+            temp_dir,
+            original_name,
+            target_ext,
+            callback_url,
+            encoded,
+        )
+        .await;
+        let report = state.job_report(&job_id);
+        return Ok((StatusCode::ACCEPTED, Json(report)).into_response());
+    }
+
+    encode(
+        &state,
+        &input_path,
+        &output_path,
+        &target_ext,
+        &encode_opts,
+        input_bytes,
+    )
+    .await?;
 
     // stream the output file back rather than reading it all into a Vec. a
     // long lossless source produces a large output (a ~90-min WAV is ~900MB),
@@ -129,42 +856,471 @@ async fn transcode(
     let file = File::open(&output_path)
         .await
         .map_err(|e| AppError::Io(format!("failed to open output file: {e}")))?;
-    let body = Body::from_stream(ReaderStream::new(file));
+    output_response(file, &original_name, &target_ext)
+}
+
+/// run ffmpeg and record the outcome in the metrics. returns the output size.
+async fn encode(
+    state: &AppState,
+    input_path: &Path,
+    output_path: &Path,
+    target_ext: &str,
+    opts: &EncodeOptions,
+    input_bytes: u64,
+) -> Result<u64, AppError> {
+    let started = Instant::now();
+    let encoded = run_ffmpeg(input_path, output_path, target_ext, opts).await;
+    let elapsed = started.elapsed();
+    if let Err(AppError::Ffmpeg(_)) = &encoded {
+        state
+            .metrics
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .record_failure(target_ext);
+    }
+    encoded?;
 
-    let media_type = match target_ext.as_str() {
-        "mp3" => "audio/mpeg",
-        "wav" => "audio/wav",
-        "m4a" => "audio/mp4",
-        other => {
+    let output_bytes = tokio::fs::metadata(output_path)
+        .await
+        .map_err(|e| AppError::Io(format!("failed to stat output file: {e}")))?
+        .len();
+    state
+        .metrics
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .record_success(target_ext, input_bytes, output_bytes, elapsed);
+    Ok(output_bytes)
+}
+
+/// 200 response streaming an encoded file as a download.
+fn output_response(
+    file: File,
+    original_name: &str,
+    target_ext: &str,
+) -> Result<Response, AppError> {
+    let media_type = match find_target(target_ext) {
+        Some(target) => target.media_type,
+        None => {
             info!(
-                target = other,
+                target = target_ext,
                 "unknown target format, defaulting to octet-stream"
             );
             "application/octet-stream"
         }
     };
 
-    let download_name = format!("{}.{}", original_name, target_ext);
-    let response = Response::builder()
+    Response::builder()
         .status(StatusCode::OK)
         .header(header::CONTENT_TYPE, HeaderValue::from_static(media_type))
         .header(
             header::CONTENT_DISPOSITION,
-            HeaderValue::from_str(&format!("attachment; filename=\"{}\"", download_name))
+            HeaderValue::from_str(&content_disposition(original_name, target_ext))
                 .unwrap_or_else(|_| HeaderValue::from_static("attachment")),
         )
-        .body(body)
-        .map_err(|e| AppError::Http(e.to_string()))?;
+        .body(Body::from_stream(ReaderStream::new(file)))
+        .map_err(|e| AppError::Http(e.to_string()))
+}
+
+/// `callback_url` must be an absolute http(s) URL.
+fn parse_callback_url(raw: &str) -> Result<String, AppError> {
+    let url = reqwest::Url::parse(raw)
+        .map_err(|e| AppError::BadRequest(format!("invalid callback_url: {e}")))?;
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err(AppError::BadRequest(
+            "callback_url must be an http or https URL".into(),
+        ));
+    }
+    Ok(url.into())
+}
+
+/// this instance's name in the job store: `TRANSCODER_INSTANCE_ID`, else the
+/// fly machine id, which both survive a restart. with neither set, a random
+/// id; jobs from before a restart are then only failed once their lease runs
+/// out.
+fn instance_id() -> String {
+    env::var("TRANSCODER_INSTANCE_ID")
+        .or_else(|_| env::var("FLY_MACHINE_ID"))
+        .unwrap_or_else(|_| format!("{:016x}", rand::random::<u64>()))
+}
+
+/// keep this instance's unfinished jobs leased, and fail jobs whose instance
+/// stopped renewing theirs.
+async fn renew_job_leases(store: JobStore) {
+    let mut ticks = tokio::time::interval(store::LEASE_RENEW_INTERVAL);
+    loop {
+        ticks.tick().await;
+        let now = unix_now();
+        if let Err(e) = store.renew_leases(now).await {
+            warn!(error = %e, "failed to renew job leases");
+        }
+        match store.fail_expired(now).await {
+            Ok(0) => {}
+            Ok(expired) => warn!(expired, "marked jobs with lapsed leases as failed"),
+            Err(e) => warn!(error = %e, "failed to check for lapsed job leases"),
+        }
+    }
+}
 
-    Ok(response)
+/// register an async job, persisting it as submitted, and run `encoded` in
+/// the background. `slot` is held until the encode finishes; `dir` holds the
+/// job's files and lives as long as the job; `encoded` yields the output path.
+async fn start_job<F>(
+    state: &AppState,
+    slot: OwnedSemaphorePermit,
+    dir: TempDir,
+    original_name: String,
+    target: String,
+    callback_url: String,
+    encoded: F,
+) -> String
+where
+    F: Future<Output = Result<PathBuf, AppError>> + Send + 'static,
+{
+    let job_id = format!("{:016x}", rand::random::<u64>());
+    let now = unix_now();
+    {
+        let mut jobs = state.jobs();
+        jobs.retain(|_, job| job.finished_at.is_none_or(|at| now - at < JOB_TTL_SECS));
+        jobs.insert(
+            job_id.clone(),
+            Job {
+                target,
+                original_name,
+                status: JobStatus::Submitted,
+                submitted_at: now,
+                finished_at: None,
+                output: None,
+                error: None,
+                _dir: dir,
+            },
+        );
+    }
+    if let Some(report) = state.job_report(&job_id) {
+        state.persist_job(&report).await;
+    }
+    info!(job_id, "async transcode submitted");
+    state.job_tasks.spawn(finish_job(
+        state.clone(),
+        slot,
+        job_id.clone(),
+        callback_url,
+        encoded,
+    ));
+    job_id
 }
 
+/// wait for a job's encode, record the result, and POST it to the callback.
+async fn finish_job<F>(
+    state: AppState,
+    slot: OwnedSemaphorePermit,
+    job_id: String,
+    callback_url: String,
+    encoded: F,
+) where
+    F: Future<Output = Result<PathBuf, AppError>>,
+{
+    if let Some(store) = &state.store {
+        if let Err(e) = store.prune(unix_now() - JOB_TTL_SECS).await {
+            warn!(error = %e, "failed to prune persisted jobs");
+        }
+    }
+
+    // `encoded` starts ffmpeg when it's first polled
+    let running = state.jobs().get_mut(&job_id).map(|job| {
+        job.status = JobStatus::Running;
+        job.report(&job_id)
+    });
+    if let Some(report) = running {
+        state.persist_job(&report).await;
+    }
+
+    let result = match encoded.await {
+        Ok(path) => sha256_file(&path).await.map(|(sha256, bytes)| JobOutput {
+            path,
+            bytes,
+            sha256,
+        }),
+        Err(e) => Err(e),
+    };
+    drop(slot);
+
+    let report = {
+        let mut jobs = state.jobs();
+        let Some(job) = jobs.get_mut(&job_id) else {
+            return;
+        };
+        job.finished_at = Some(unix_now());
+        match result {
+            Ok(output) => {
+                job.status = JobStatus::Succeeded;
+                job.output = Some(output);
+            }
+            Err(e) => {
+                error!(job_id, error = %e, "async transcode failed");
+                job.status = JobStatus::Failed;
+                job.error = Some(e.to_string());
+            }
+        }
+        job.report(&job_id)
+    };
+
+    state.persist_job(&report).await;
+    deliver_callback(
+        &state.http,
+        &callback_url,
+        state.callback_secret.as_deref(),
+        &report,
+    )
+    .await;
+}
+
+/// POST a job report, retrying with backoff on failure. with a `secret`, the
+/// body's HMAC goes in `CALLBACK_SIGNATURE_HEADER` so the receiver can tell
+/// the callback came from us.
+async fn deliver_callback(
+    http: &reqwest::Client,
+    url: &str,
+    secret: Option<&str>,
+    report: &JobReport,
+) {
+    let job_id = report.job_id.as_str();
+    let body = match serde_json::to_vec(report) {
+        Ok(body) => body,
+        Err(e) => {
+            error!(job_id, error = %e, "failed to serialize job report");
+            return;
+        }
+    };
+    let signature = secret.map(|secret| callback_signature(secret, &body));
+    for attempt in 1..=CALLBACK_ATTEMPTS {
+        let mut request = http
+            .post(url)
+            .timeout(CALLBACK_TIMEOUT)
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(body.clone());
+        if let Some(signature) = &signature {
+            request = request.header(CALLBACK_SIGNATURE_HEADER, signature);
+        }
+        match request.send().await {
+            Ok(response) if response.status().is_success() => {
+                info!(job_id, attempt, "job callback delivered");
+                return;
+            }
+            Ok(response) => {
+                warn!(job_id, attempt, status = %response.status(), "job callback rejected");
+            }
+            Err(e) => {
+                warn!(job_id, attempt, error = %e, "job callback failed");
+            }
+        }
+        if attempt < CALLBACK_ATTEMPTS {
+            tokio::time::sleep(Duration::from_secs(1 << (attempt - 1))).await;
+        }
+    }
+    error!(job_id, "giving up on job callback");
+}
+
+/// `sha256=<hex>` HMAC-SHA256 of a callback body under `secret`.
+fn callback_signature(secret: &str, body: &[u8]) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("hmac accepts keys of any length");
+    mac.update(body);
+    format!("sha256={}", to_hex(&mac.finalize().into_bytes()))
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().fold(String::new(), |mut hex, b| {
+        let _ = write!(hex, "{b:02x}");
+        hex
+    })
+}
+
+/// hex SHA-256 and size of a file, read in chunks.
+async fn sha256_file(path: &Path) -> Result<(String, u64), AppError> {
+    let mut file = File::open(path)
+        .await
+        .map_err(|e| AppError::Io(format!("failed to open output file: {e}")))?;
+    let mut hasher = Sha256::new();
+    let mut buf = vec![0u8; 64 * 1024];
+    let mut bytes = 0u64;
+    loop {
+        let n = file
+            .read(&mut buf)
+            .await
+            .map_err(|e| AppError::Io(format!("failed to read output file: {e}")))?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+        bytes += n as u64;
+    }
+    Ok((to_hex(&hasher.finalize()), bytes))
+}
+
+async fn get_job(
+    State(state): State<AppState>,
+    UrlPath(job_id): UrlPath<String>,
+) -> Result<Json<JobReport>, AppError> {
+    if let Some(report) = state.job_report(&job_id) {
+        return Ok(Json(report));
+    }
+    let Some(store) = &state.store else {
+        return Err(AppError::NotFound(format!("no job {job_id}")));
+    };
+    store
+        .get(&job_id)
+        .await
+        .map_err(|e| AppError::Store(e.to_string()))?
+        .map(Json)
+        .ok_or_else(|| AppError::NotFound(format!("no job {job_id}")))
+}
+
+/// the encoded file of a succeeded job.
+async fn get_job_output(
+    State(state): State<AppState>,
+    UrlPath(job_id): UrlPath<String>,
+) -> Result<Response, AppError> {
+    let held = state.jobs().get(&job_id).map(|job| {
+        job.output.as_ref().map(|output| {
+            (
+                output.path.clone(),
+                job.original_name.clone(),
+                job.target.clone(),
+            )
+        })
+    });
+    let (path, original_name, target) = match held {
+        Some(Some(output)) => output,
+        Some(None) => {
+            return Err(AppError::NotFound(format!("job {job_id} has no output")));
+        }
+        None => {
+            // "no job" unless it was persisted before a restart, in which
+            // case its output went with the old process's temp dir
+            let _persisted = get_job(State(state), UrlPath(job_id.clone())).await?;
+            return Err(AppError::NotFound(format!(
+                "output of job {job_id} did not survive a restart"
+            )));
+        }
+    };
+    let file = File::open(&path)
+        .await
+        .map_err(|e| AppError::Io(format!("failed to open output file: {e}")))?;
+    output_response(file, &original_name, &target)
+}
+
+/// output format for a transcode: `?target=` if given, else the best audio
+/// type in `Accept` that we can encode, else mp3.
+fn target_format(target: Option<String>, headers: &HeaderMap) -> String {
+    target
+        .or_else(|| {
+            headers
+                .get(header::ACCEPT)
+                .and_then(|v| v.to_str().ok())
+                .and_then(target_from_accept)
+                .map(String::from)
+        })
+        .unwrap_or_else(|| "mp3".to_string())
+}
+
+/// pick a target extension from an `Accept` header value.
+///
+/// entries are tried in order of their `q` weight (first listed wins a tie);
+/// types we don't encode, wildcards and `q=0` entries are skipped.
+fn target_from_accept(accept: &str) -> Option<&'static str> {
+    let mut best: Option<(f32, &'static str)> = None;
+    for entry in accept.split(',') {
+        let mut parts = entry.split(';');
+        let mime = parts.next().unwrap_or_default().trim();
+        let q = parts
+            .filter_map(|p| p.trim().strip_prefix("q="))
+            .find_map(|q| q.trim().parse::<f32>().ok())
+            .unwrap_or(1.0);
+        let Some(ext) = extension_for_mime(mime) else {
+            continue;
+        };
+        if q > 0.0 && best.is_none_or(|(best_q, _)| q > best_q) {
+            best = Some((q, ext));
+        }
+    }
+    best.map(|(_, ext)| ext)
+}
+
+/// target extension for an audio MIME type we can encode to.
+fn extension_for_mime(mime: &str) -> Option<&'static str> {
+    let mime = mime.to_ascii_lowercase();
+    TARGETS
+        .iter()
+        .find(|target| target.accept.contains(&mime.as_str()))
+        .map(|target| target.ext)
+}
+
+/// `attachment` Content-Disposition for `{stem}.{ext}`.
+///
+/// the stem comes from the uploaded filename, so control characters are
+/// dropped and it's capped at `MAX_DOWNLOAD_STEM_CHARS`. the quoted
+/// `filename` is an ASCII-only fallback; `filename*` carries the real name
+/// percent-encoded as UTF-8 (RFC 6266 / RFC 5987).
+fn content_disposition(stem: &str, ext: &str) -> String {
+    let stem: String = stem
+        .chars()
+        .filter(|c| !c.is_control())
+        .take(MAX_DOWNLOAD_STEM_CHARS)
+        .collect();
+    let stem = match stem.trim() {
+        "" => "track",
+        s => s,
+    };
+    let name = format!("{}.{}", stem, ext);
+
+    let ascii_fallback: String = name
+        .chars()
+        .map(|c| match c {
+            '"' | '\\' => '_',
+            c if c.is_ascii() => c,
+            _ => '_',
+        })
+        .collect();
+
+    format!(
+        "attachment; filename=\"{}\"; filename*=UTF-8''{}",
+        ascii_fallback,
+        percent_encode_attr(&name)
+    )
+}
+
+/// percent-encode everything outside RFC 5987's `attr-char` set.
+fn percent_encode_attr(s: &str) -> String {
+    s.bytes()
+        .map(|b| match b {
+            b'A'..=b'Z'
+            | b'a'..=b'z'
+            | b'0'..=b'9'
+            | b'!'
+            | b'#'
+            | b'$'
+            | b'&'
+            | b'+'
+            | b'-'
+            | b'.'
+            | b'^'
+            | b'_'
+            | b'`'
+            | b'|'
+            | b'~' => (b as char).to_string(),
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+/// returns the input path, the upload's filename stem, and its size in bytes.
 async fn write_upload_to_disk(
     multipart: &mut Multipart,
     temp_dir: &TempDir,
-) -> Result<(PathBuf, String), AppError> {
+) -> Result<(PathBuf, String, u64), AppError> {
     let mut file_path: Option<PathBuf> = None;
     let mut original_name: Option<String> = None;
+    let mut bytes_written: u64 = 0;
 
     while let Some(mut field) = multipart
         .next_field()
@@ -197,6 +1353,7 @@ async fn write_upload_to_disk(
             file.write_all(&chunk)
                 .await
                 .map_err(|e| AppError::Io(format!("failed to write chunk: {e}")))?;
+            bytes_written += chunk.len() as u64;
         }
         file.flush()
             .await
@@ -214,42 +1371,46 @@ async fn write_upload_to_disk(
     }
 
     match (file_path, original_name) {
-        (Some(path), Some(name)) => Ok((path, name)),
+        (Some(path), Some(name)) => Ok((path, name, bytes_written)),
         _ => Err(AppError::BadRequest(
             "multipart form must include a 'file' field".into(),
         )),
     }
 }
 
-async fn run_ffmpeg(input: &Path, output: &Path, target_ext: &str) -> Result<(), AppError> {
-    let mut cmd = Command::new("ffmpeg");
-    cmd.arg("-y").arg("-i").arg(input);
-
-    match target_ext {
-        "mp3" => {
-            cmd.args(["-acodec", "libmp3lame", "-b:a", "320k", "-ar", "44100"]);
-        }
-        "wav" => {
-            // compatibility remux: 16-bit little-endian PCM is the universal
-            // browser-playable floor. we deliberately do NOT force a sample
-            // rate or channel count — preserving the source keeps this a near-
-            // instant PCM rewrap (e.g. AIFF pcm_s16be -> WAV pcm_s16le is a
-            // byte-swap), instead of a full resample. the lossless master is
-            // retained separately by the caller, so 16-bit here is a delivery
-            // rendition, not the archival copy.
-            cmd.args(["-acodec", "pcm_s16le"]);
-        }
-        "m4a" => {
-            cmd.args(["-acodec", "aac", "-b:a", "256k", "-ar", "44100"]);
-        }
-        other => {
-            return Err(AppError::BadRequest(format!(
-                "unsupported target format: {}",
-                other
-            )));
+/// whether the installed ffmpeg lists `name` among its encoders.
+async fn ffmpeg_has_encoder(name: &str) -> bool {
+    match Command::new("ffmpeg")
+        .args(["-hide_banner", "-encoders"])
+        .output()
+        .await
+    {
+        Ok(output) => lists_encoder(&String::from_utf8_lossy(&output.stdout), name),
+        Err(e) => {
+            warn!(error = %e, "failed to list ffmpeg encoders");
+            false
         }
     }
+}
+
+/// whether `ffmpeg -encoders` output has an encoder called `name`. each
+/// line is capability flags, the encoder name, then its description.
+fn lists_encoder(listing: &str, name: &str) -> bool {
+    listing
+        .lines()
+        .any(|line| line.split_whitespace().nth(1) == Some(name))
+}
 
+async fn run_ffmpeg(
+    input: &Path,
+    output: &Path,
+    target_ext: &str,
+    opts: &EncodeOptions,
+) -> Result<(), AppError> {
+    let (seek, limit) = opts.trim_args();
+    let mut cmd = Command::new("ffmpeg");
+    cmd.arg("-y").args(seek).arg("-i").arg(input).args(limit);
+    cmd.args(codec_args(target_ext, opts)?);
     cmd.arg(output);
 
     let output_res = cmd
@@ -266,30 +1427,796 @@ async fn run_ffmpeg(input: &Path, output: &Path, target_ext: &str) -> Result<(),
     Ok(())
 }
 
+/// a format `/transcode` can produce.
+struct Target {
+    /// file extension, and the `?target=` value that picks it.
+    ext: &'static str,
+    /// Content-Type of the output.
+    media_type: &'static str,
+    /// `Accept` types that pick it when there's no `?target=`.
+    accept: &'static [&'static str],
+    /// ffmpeg encoder arguments.
+    codec_args: fn(&EncodeOptions) -> Result<Vec<String>, AppError>,
+}
+
+/// every target format. encoding, the `supported` list in unsupported-target
+/// errors, output content types and `Accept` matching all read this, so a
+/// format is added here and nowhere else.
+const TARGETS: &[Target] = &[
+    Target {
+        ext: "mp3",
+        media_type: "audio/mpeg",
+        accept: &["audio/mpeg", "audio/mp3"],
+        codec_args: mp3_args,
+    },
+    Target {
+        ext: "wav",
+        media_type: "audio/wav",
+        accept: &["audio/wav", "audio/wave", "audio/x-wav", "audio/vnd.wave"],
+        codec_args: wav_args,
+    },
+    Target {
+        ext: "m4a",
+        media_type: "audio/mp4",
+        accept: &["audio/mp4", "audio/m4a", "audio/x-m4a"],
+        codec_args: m4a_args,
+    },
+];
+
+fn find_target(ext: &str) -> Option<&'static Target> {
+    TARGETS.iter().find(|target| target.ext == ext)
+}
+
+/// build the encoder arguments for a target format.
+fn codec_args(target_ext: &str, opts: &EncodeOptions) -> Result<Vec<String>, AppError> {
+    let target = find_target(target_ext)
+        .ok_or_else(|| AppError::UnsupportedTarget(target_ext.to_string()))?;
+    (target.codec_args)(opts)
+}
+
+fn mp3_args(opts: &EncodeOptions) -> Result<Vec<String>, AppError> {
+    let kbps = opts.bitrate_kbps.unwrap_or(320);
+    Ok(vec![
+        "-acodec".into(),
+        "libmp3lame".into(),
+        "-b:a".into(),
+        format!("{}k", kbps),
+        "-ar".into(),
+        "44100".into(),
+    ])
+}
+
+fn wav_args(_opts: &EncodeOptions) -> Result<Vec<String>, AppError> {
+    // compatibility remux: 16-bit little-endian PCM is the universal
+    // browser-playable floor. we deliberately do NOT force a sample
+    // rate or channel count — preserving the source keeps this a near-
+    // instant PCM rewrap (e.g. AIFF pcm_s16be -> WAV pcm_s16le is a
+    // byte-swap), instead of a full resample. the lossless master is
+    // retained separately by the caller, so 16-bit here is a delivery
+    // rendition, not the archival copy. bitrate doesn't apply to PCM.
+    Ok(vec!["-acodec".into(), "pcm_s16le".into()])
+}
+
+fn m4a_args(opts: &EncodeOptions) -> Result<Vec<String>, AppError> {
+    let args = match opts.aac_profile {
+        AacProfile::Lc => {
+            let kbps = opts.bitrate_kbps.unwrap_or(256);
+            vec![
+                "-acodec".into(),
+                "aac".into(),
+                "-b:a".into(),
+                format!("{}k", kbps),
+                "-ar".into(),
+                "44100".into(),
+            ]
+        }
+        AacProfile::He => {
+            let kbps = opts.bitrate_kbps.unwrap_or(HE_AAC_DEFAULT_KBPS);
+            if kbps > HE_AAC_MAX_KBPS {
+                return Err(AppError::BadRequest(format!(
+                    "aac_profile=he requires a bitrate of at most {}k, got {}k",
+                    HE_AAC_MAX_KBPS, kbps
+                )));
+            }
+            // ffmpeg's native aac encoder has no SBR support (it rejects
+            // `-profile:a aac_he`), so HE-AAC needs libfdk_aac. the
+            // handler refuses the profile when ffmpeg doesn't have it.
+            vec![
+                "-acodec".into(),
+                HE_AAC_ENCODER.into(),
+                "-profile:a".into(),
+                "aac_he".into(),
+                "-b:a".into(),
+                format!("{}k", kbps),
+                "-ar".into(),
+                "44100".into(),
+            ]
+        }
+    };
+    Ok(args)
+}
+
 #[derive(Debug, thiserror::Error)]
 enum AppError {
     #[error("bad request: {0}")]
     BadRequest(String),
+    #[error("not found: {0}")]
+    NotFound(String),
+    #[error("unsupported target format: {0}")]
+    UnsupportedTarget(String),
     #[error("io error: {0}")]
     Io(String),
     #[error("http error: {0}")]
     Http(String),
     #[error("ffmpeg error: {0}")]
     Ffmpeg(String),
+    #[error("job store error: {0}")]
+    Store(String),
+    #[error("shutting down, retry on another instance")]
+    Draining,
+    #[error("too many async jobs running, retry later")]
+    TooManyJobs,
 }
 
 impl IntoResponse for AppError {
     fn into_response(self) -> Response {
+        if let AppError::Draining = self {
+            warn!("rejecting transcode while draining");
+            let body = serde_json::json!({ "error": self.to_string() });
+            return (
+                StatusCode::SERVICE_UNAVAILABLE,
+                [(
+                    header::RETRY_AFTER,
+                    HeaderValue::from(DRAIN_RETRY_AFTER_SECS),
+                )],
+                Json(body),
+            )
+                .into_response();
+        }
+
         tracing::error!(error = %self, "request failed");
         let status = match self {
-            AppError::BadRequest(_) => StatusCode::BAD_REQUEST,
-            AppError::Io(_) | AppError::Http(_) | AppError::Ffmpeg(_) => {
+            AppError::BadRequest(_) | AppError::UnsupportedTarget(_) => StatusCode::BAD_REQUEST,
+            AppError::NotFound(_) => StatusCode::NOT_FOUND,
+            AppError::Io(_) | AppError::Http(_) | AppError::Ffmpeg(_) | AppError::Store(_) => {
                 StatusCode::INTERNAL_SERVER_ERROR
             }
+            AppError::Draining => StatusCode::SERVICE_UNAVAILABLE,
+            AppError::TooManyJobs => StatusCode::TOO_MANY_REQUESTS,
         };
-        let body = serde_json::json!({
+        let mut body = serde_json::json!({
             "error": self.to_string(),
         });
+        if let AppError::UnsupportedTarget(_) = self {
+            let supported: Vec<&str> = TARGETS.iter().map(|target| target.ext).collect();
+            body["supported"] = serde_json::json!(supported);
+        }
         (status, Json(body)).into_response()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn opts(bitrate: Option<&str>, aac_profile: Option<&str>) -> EncodeOptions {
+        EncodeOptions::from_params(&TranscodeParams {
+            target: None,
+            bitrate: bitrate.map(str::to_string),
+            aac_profile: aac_profile.map(str::to_string),
+            ..Default::default()
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn trim_seeks_before_the_input_and_limits_the_output() {
+        let trim = |start, duration| {
+            EncodeOptions::from_params(&TranscodeParams {
+                start,
+                duration,
+                ..Default::default()
+            })
+        };
+
+        let (seek, limit) = trim(Some(90.0), Some(12.5)).unwrap().trim_args();
+        assert_eq!(seek, ["-ss", "90"]);
+        assert_eq!(limit, ["-t", "12.5"]);
+
+        let (seek, limit) = trim(None, None).unwrap().trim_args();
+        assert!(seek.is_empty() && limit.is_empty());
+
+        assert!(trim(Some(-1.0), None).is_err());
+        assert!(trim(Some(f64::NAN), None).is_err());
+        assert!(trim(None, Some(0.0)).is_err());
+        assert!(trim(Some(0.0), Some(30.0)).is_ok());
+    }
+
+    #[test]
+    fn m4a_defaults_to_lc_at_256k() {
+        let args = codec_args("m4a", &opts(None, None)).unwrap();
+        assert_eq!(args, ["-acodec", "aac", "-b:a", "256k", "-ar", "44100"]);
+    }
+
+    #[test]
+    fn m4a_he_profile_sets_aac_he() {
+        let args = codec_args("m4a", &opts(Some("48k"), Some("he"))).unwrap();
+        assert_eq!(
+            args,
+            [
+                "-acodec",
+                "libfdk_aac",
+                "-profile:a",
+                "aac_he",
+                "-b:a",
+                "48k",
+                "-ar",
+                "44100"
+            ]
+        );
+    }
+
+    #[test]
+    fn finds_encoders_in_ffmpeg_listing() {
+        let listing = "Encoders:\n \
+             A..... = Audio\n \
+             ------\n \
+             A....D aac                  AAC (Advanced Audio Coding)\n \
+             A....D libmp3lame           libmp3lame MP3 (MPEG audio layer 3)\n";
+        assert!(lists_encoder(listing, "aac"));
+        assert!(lists_encoder(listing, "libmp3lame"));
+        assert!(!lists_encoder(listing, HE_AAC_ENCODER));
+        // descriptions mentioning a name don't count
+        assert!(!lists_encoder(listing, "AAC"));
+    }
+
+    #[test]
+    fn m4a_he_profile_rejects_high_bitrate() {
+        let err = codec_args("m4a", &opts(Some("192"), Some("he"))).unwrap_err();
+        assert!(matches!(err, AppError::BadRequest(_)));
+    }
+
+    #[test]
+    fn every_supported_target_encodes() {
+        for target in TARGETS {
+            let ext = target.ext;
+            assert!(codec_args(ext, &opts(None, None)).is_ok(), "{ext}");
+            for mime in target.accept {
+                assert_eq!(extension_for_mime(mime), Some(ext), "{mime}");
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn unsupported_target_lists_supported_formats() {
+        let err = codec_args("ogg", &opts(None, None)).unwrap_err();
+        let response = err.into_response();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(
+            body,
+            serde_json::json!({
+                "error": "unsupported target format: ogg",
+                "supported": ["mp3", "wav", "m4a"],
+            })
+        );
+    }
+
+    #[test]
+    fn mp3_honors_bitrate() {
+        let args = codec_args("mp3", &opts(Some("192"), None)).unwrap();
+        assert!(args.windows(2).any(|w| w == ["-b:a", "192k"]));
+    }
+
+    fn accept(value: &'static str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::ACCEPT, HeaderValue::from_static(value));
+        headers
+    }
+
+    #[test]
+    fn target_comes_from_accept_when_param_is_absent() {
+        assert_eq!(target_format(None, &accept("audio/wav")), "wav");
+        assert_eq!(target_format(None, &accept("audio/x-m4a")), "m4a");
+        // highest q wins, and types we can't encode are skipped
+        assert_eq!(
+            target_format(
+                None,
+                &accept("audio/flac, audio/mpeg;q=0.5, audio/mp4;q=0.8")
+            ),
+            "m4a"
+        );
+        assert_eq!(
+            target_format(None, &accept("audio/wav;q=0, audio/mp4;q=0.1")),
+            "m4a"
+        );
+    }
+
+    #[test]
+    fn target_param_wins_over_accept() {
+        assert_eq!(
+            target_format(Some("wav".to_string()), &accept("audio/mp4")),
+            "wav"
+        );
+    }
+
+    #[test]
+    fn target_defaults_to_mp3() {
+        assert_eq!(target_format(None, &HeaderMap::new()), "mp3");
+        assert_eq!(target_format(None, &accept("*/*")), "mp3");
+        assert_eq!(target_format(None, &accept("audio/flac")), "mp3");
+    }
+
+    #[test]
+    fn content_disposition_encodes_unicode() {
+        let header = content_disposition("café ♫", "mp3");
+        assert_eq!(
+            header,
+            "attachment; filename=\"caf_ _.mp3\"; filename*=UTF-8''caf%C3%A9%20%E2%99%AB.mp3"
+        );
+        assert!(HeaderValue::from_str(&header).is_ok());
+    }
+
+    #[test]
+    fn content_disposition_strips_control_and_quote_chars() {
+        let header = content_disposition("bad\r\nname\"\\", "wav");
+        assert!(header.starts_with("attachment; filename=\"badname__.wav\";"));
+        assert!(header.ends_with("filename*=UTF-8''badname%22%5C.wav"));
+        assert!(HeaderValue::from_str(&header).is_ok());
+
+        let header = content_disposition("\u{7}\t ", "mp3");
+        assert!(header.starts_with("attachment; filename=\"track.mp3\";"));
+    }
+
+    #[test]
+    fn content_disposition_caps_long_names() {
+        let header = content_disposition(&"ü".repeat(1000), "m4a");
+        let fallback = format!("{}.m4a", "_".repeat(MAX_DOWNLOAD_STEM_CHARS));
+        assert!(header.contains(&format!("filename=\"{}\"", fallback)));
+        let encoded = format!("{}.m4a", "%C3%BC".repeat(MAX_DOWNLOAD_STEM_CHARS));
+        assert!(header.ends_with(&encoded));
+        assert!(HeaderValue::from_str(&header).is_ok());
+    }
+
+    #[test]
+    fn bitrate_is_bounded() {
+        assert!(parse_bitrate("16k").is_err());
+        assert!(parse_bitrate("640").is_err());
+        assert!(parse_bitrate("abc").is_err());
+        assert_eq!(parse_bitrate("128K").unwrap(), 128);
+    }
+
+    #[test]
+    fn log_level_is_overridden_by_rust_log() {
+        assert_eq!(
+            base_log_level(None, Some("debug")),
+            Some(LevelFilter::DEBUG)
+        );
+        assert_eq!(
+            base_log_level(Some(""), Some("warn")),
+            Some(LevelFilter::WARN)
+        );
+        assert_eq!(base_log_level(None, Some("loud")), None);
+        assert_eq!(
+            base_log_level(Some("transcoder=trace"), Some("error")),
+            None
+        );
+    }
+
+    fn auth_headers(pairs: &[(&'static str, &str)]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        for (name, value) in pairs {
+            headers.insert(*name, HeaderValue::from_str(value).unwrap());
+        }
+        headers
+    }
+
+    #[test]
+    fn bearer_token_is_accepted() {
+        let headers = auth_headers(&[("authorization", "Bearer secret")]);
+        assert_eq!(check_token(&headers, "secret"), Ok(()));
+    }
+
+    #[test]
+    fn transcoder_key_header_still_works() {
+        let headers = auth_headers(&[("x-transcoder-key", "secret")]);
+        assert_eq!(check_token(&headers, "secret"), Ok(()));
+    }
+
+    #[test]
+    fn bearer_is_preferred_over_transcoder_key() {
+        let headers = auth_headers(&[
+            ("authorization", "Bearer wrong"),
+            ("x-transcoder-key", "secret"),
+        ]);
+        assert_eq!(
+            check_token(&headers, "secret"),
+            Err(StatusCode::UNAUTHORIZED)
+        );
+    }
+
+    #[test]
+    fn mismatched_or_missing_token_is_rejected() {
+        for headers in [
+            auth_headers(&[("authorization", "Bearer nope")]),
+            auth_headers(&[("x-transcoder-key", "secre")]),
+            auth_headers(&[("authorization", "Basic secret")]),
+            HeaderMap::new(),
+        ] {
+            assert_eq!(
+                check_token(&headers, "secret"),
+                Err(StatusCode::UNAUTHORIZED)
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn draining_fails_health() {
+        let state = AppState::default();
+        let (status, Json(body)) = health(State(state.clone())).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body.status, "ok");
+
+        state.draining.store(true, Ordering::SeqCst);
+        let (status, Json(body)) = health(State(state)).await;
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(body.status, "draining");
+    }
+
+    #[test]
+    fn histogram_buckets_are_cumulative() {
+        let mut ratio = Histogram::new(SIZE_RATIO_BUCKETS);
+        ratio.observe(0.125);
+        ratio.observe(0.25);
+        ratio.observe(9.0);
+
+        let mut out = String::new();
+        ratio.render(&mut out, "transcoder_size_ratio", "mp3");
+        assert!(out.contains("transcoder_size_ratio_bucket{target=\"mp3\",le=\"0.1\"} 0\n"));
+        assert!(out.contains("transcoder_size_ratio_bucket{target=\"mp3\",le=\"0.25\"} 2\n"));
+        assert!(out.contains("transcoder_size_ratio_bucket{target=\"mp3\",le=\"4\"} 2\n"));
+        assert!(out.contains("transcoder_size_ratio_bucket{target=\"mp3\",le=\"+Inf\"} 3\n"));
+        assert!(out.contains("transcoder_size_ratio_sum{target=\"mp3\"} 9.375\n"));
+        assert!(out.contains("transcoder_size_ratio_count{target=\"mp3\"} 3\n"));
+    }
+
+    #[test]
+    fn metrics_are_labeled_by_target_format() {
+        let mut metrics = Metrics::default();
+        metrics.record_success("mp3", 40_000_000, 8_000_000, Duration::from_millis(3_000));
+        metrics.record_success("wav", 10_000_000, 0, Duration::from_millis(200));
+        metrics.record_success("wav", 0, 1_000, Duration::from_millis(200));
+        metrics.record_failure("wav");
+
+        let out = metrics.render();
+        assert!(out.contains("# TYPE transcoder_size_ratio histogram\n"));
+        assert!(out.contains("# TYPE transcoder_encode_seconds histogram\n"));
+        assert!(out.contains("transcoder_transcodes_total{target=\"mp3\",outcome=\"ok\"} 1\n"));
+        assert!(out.contains("transcoder_transcodes_total{target=\"wav\",outcome=\"ok\"} 2\n"));
+        assert!(out.contains("transcoder_transcodes_total{target=\"wav\",outcome=\"failed\"} 1\n"));
+        assert!(out.contains("transcoder_size_ratio_bucket{target=\"mp3\",le=\"0.25\"} 1\n"));
+        assert!(out.contains("transcoder_encode_seconds_bucket{target=\"mp3\",le=\"2.5\"} 0\n"));
+        assert!(out.contains("transcoder_encode_seconds_bucket{target=\"mp3\",le=\"5\"} 1\n"));
+        // an empty upload has no ratio
+        assert!(out.contains("transcoder_size_ratio_count{target=\"wav\"} 1\n"));
+        assert!(out.contains("transcoder_encode_seconds_count{target=\"wav\"} 2\n"));
+    }
+
+    /// a server that forwards each job callback it receives, with its
+    /// signature header, to the channel.
+    async fn callback_receiver() -> (
+        String,
+        tokio::sync::mpsc::Receiver<(Option<String>, axum::body::Bytes)>,
+    ) {
+        let (tx, rx) = tokio::sync::mpsc::channel(4);
+        let app = Router::new().route(
+            "/done",
+            post(move |headers: HeaderMap, body: axum::body::Bytes| {
+                let tx = tx.clone();
+                async move {
+                    let signature = headers
+                        .get(CALLBACK_SIGNATURE_HEADER)
+                        .map(|v| v.to_str().unwrap().to_string());
+                    tx.send((signature, body)).await.unwrap();
+                    StatusCode::NO_CONTENT
+                }
+            }),
+        );
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/done", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        (url, rx)
+    }
+
+    async fn next_callback(
+        callbacks: &mut tokio::sync::mpsc::Receiver<(Option<String>, axum::body::Bytes)>,
+    ) -> JobReport {
+        let (_, body) = tokio::time::timeout(Duration::from_secs(5), callbacks.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        serde_json::from_slice(&body).unwrap()
+    }
+
+    #[tokio::test]
+    async fn callback_fires_when_job_completes() {
+        let (callback_url, mut callbacks) = callback_receiver().await;
+        let state = AppState::default();
+        let dir = tempfile::tempdir().unwrap();
+        let output = dir.path().join("output.mp3");
+        let encoded = {
+            let (state, output) = (state.clone(), output.clone());
+            async move {
+                // the job only counts as running once its encode starts
+                assert!(state
+                    .jobs()
+                    .values()
+                    .all(|job| job.status == JobStatus::Running));
+                tokio::fs::write(&output, b"encoded audio").await.unwrap();
+                Ok(output)
+            }
+        };
+
+        let job_id = start_job(
+            &state,
+            state.reserve_job_slot().unwrap(),
+            dir,
+            "song".to_string(),
+            "mp3".to_string(),
+            callback_url,
+            encoded,
+        )
+        .await;
+        let accepted = state.job_report(&job_id).unwrap();
+        assert_eq!(accepted.status, JobStatus::Submitted);
+        let report = next_callback(&mut callbacks).await;
+        assert_eq!(report.job_id, job_id);
+        assert_eq!(report.status, JobStatus::Succeeded);
+        assert_eq!(report.target, "mp3");
+        assert_eq!(report.bytes, Some(13));
+        let expected: String = Sha256::digest(b"encoded audio")
+            .iter()
+            .map(|b| format!("{b:02x}"))
+            .collect();
+        assert_eq!(report.sha256, Some(expected));
+        assert_eq!(report.output_url, Some(format!("/jobs/{job_id}/output")));
+
+        let Json(polled) = get_job(State(state.clone()), UrlPath(job_id.clone()))
+            .await
+            .unwrap();
+        assert_eq!(polled.status, JobStatus::Succeeded);
+        let response = get_job_output(State(state), UrlPath(job_id)).await.unwrap();
+        assert_eq!(response.headers()[header::CONTENT_TYPE], "audio/mpeg");
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        assert_eq!(&body[..], b"encoded audio");
+    }
+
+    #[tokio::test]
+    async fn callback_reports_failed_jobs() {
+        let (callback_url, mut callbacks) = callback_receiver().await;
+        let state = AppState::default();
+        let job_id = start_job(
+            &state,
+            state.reserve_job_slot().unwrap(),
+            tempfile::tempdir().unwrap(),
+            "song".to_string(),
+            "wav".to_string(),
+            callback_url,
+            async { Err(AppError::Ffmpeg("invalid data found".to_string())) },
+        )
+        .await;
+        let report = next_callback(&mut callbacks).await;
+        assert_eq!(report.status, JobStatus::Failed);
+        assert_eq!(
+            report.error.as_deref(),
+            Some("ffmpeg error: invalid data found")
+        );
+        assert!(report.output_url.is_none());
+        assert!(matches!(
+            get_job_output(State(state), UrlPath(job_id)).await,
+            Err(AppError::NotFound(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn persisted_jobs_survive_a_restart() {
+        let db_dir = tempfile::tempdir().unwrap();
+        let url = format!(
+            "sqlite://{}?mode=rwc",
+            db_dir.path().join("jobs.db").display()
+        );
+        let (callback_url, mut callbacks) = callback_receiver().await;
+        let state = AppState {
+            store: Some(JobStore::connect(&url, "instance-a").await.unwrap()),
+            ..AppState::default()
+        };
+        let dir = tempfile::tempdir().unwrap();
+        let output = dir.path().join("output.wav");
+        let encoded = {
+            let output = output.clone();
+            async move {
+                tokio::fs::write(&output, b"pcm").await.unwrap();
+                Ok(output)
+            }
+        };
+        let job_id = start_job(
+            &state,
+            state.reserve_job_slot().unwrap(),
+            dir,
+            "song".to_string(),
+            "wav".to_string(),
+            callback_url,
+            encoded,
+        )
+        .await;
+        let finished = next_callback(&mut callbacks).await;
+
+        // a job the old process never finished, and one another instance
+        // sharing the store is still running
+        let unfinished = |job_id: &str| JobReport {
+            job_id: job_id.to_string(),
+            status: JobStatus::Running,
+            target: "mp3".to_string(),
+            output_url: None,
+            bytes: None,
+            sha256: None,
+            error: None,
+            submitted_at: unix_now(),
+            finished_at: None,
+        };
+        state.persist_job(&unfinished("interrupted")).await;
+        let other = JobStore::connect(&url, "instance-b").await.unwrap();
+        other.save(&unfinished("elsewhere")).await.unwrap();
+        drop(state);
+
+        // restart: a fresh process with nothing in memory
+        let store = JobStore::connect(&url, "instance-a").await.unwrap();
+        assert_eq!(store.fail_interrupted(unix_now()).await.unwrap(), 1);
+        let elsewhere = store.get("elsewhere").await.unwrap().unwrap();
+        assert_eq!(elsewhere.status, JobStatus::Running);
+
+        // the other instance's job is only given up on once it stops
+        // renewing the lease
+        let later = unix_now() + 100;
+        assert_eq!(other.renew_leases(later).await.unwrap(), 1);
+        assert_eq!(store.fail_expired(later + 100).await.unwrap(), 0);
+        assert_eq!(store.fail_expired(later + 200).await.unwrap(), 1);
+        let elsewhere = store.get("elsewhere").await.unwrap().unwrap();
+        assert_eq!(elsewhere.status, JobStatus::Failed);
+        assert_eq!(elsewhere.error.as_deref(), Some(store::INTERRUPTED));
+
+        let state = AppState {
+            store: Some(store),
+            ..AppState::default()
+        };
+
+        let Json(polled) = get_job(State(state.clone()), UrlPath(job_id.clone()))
+            .await
+            .unwrap();
+        assert_eq!(polled.status, JobStatus::Succeeded);
+        assert_eq!(polled.target, "wav");
+        assert_eq!(polled.bytes, Some(3));
+        assert_eq!(polled.sha256, finished.sha256);
+        assert_eq!(polled.submitted_at, finished.submitted_at);
+        assert!(polled.finished_at.is_some());
+        assert!(polled.output_url.is_none());
+        match get_job_output(State(state.clone()), UrlPath(job_id)).await {
+            Err(AppError::NotFound(msg)) => assert!(msg.contains("restart"), "{msg}"),
+            other => panic!("expected 404, got {:?}", other.map(|r| r.status())),
+        }
+
+        let Json(polled) = get_job(State(state.clone()), UrlPath("interrupted".to_string()))
+            .await
+            .unwrap();
+        assert_eq!(polled.status, JobStatus::Failed);
+        assert_eq!(polled.error.as_deref(), Some(store::INTERRUPTED));
+        assert!(matches!(
+            get_job(State(state), UrlPath("missing".to_string())).await,
+            Err(AppError::NotFound(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn callbacks_are_signed_with_the_shared_secret() {
+        let (callback_url, mut callbacks) = callback_receiver().await;
+        let state = AppState {
+            callback_secret: Some("s3cret".to_string()),
+            ..Default::default()
+        };
+        start_job(
+            &state,
+            state.reserve_job_slot().unwrap(),
+            tempfile::tempdir().unwrap(),
+            "song".to_string(),
+            "wav".to_string(),
+            callback_url,
+            async { Err(AppError::Ffmpeg("invalid data found".to_string())) },
+        )
+        .await;
+        let (signature, body) = tokio::time::timeout(Duration::from_secs(5), callbacks.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        let mut mac = Hmac::<Sha256>::new_from_slice(b"s3cret").unwrap();
+        mac.update(&body);
+        let expected = format!("sha256={}", to_hex(&mac.finalize().into_bytes()));
+        assert_eq!(signature, Some(expected));
+        assert_ne!(
+            callback_signature("other", &body),
+            callback_signature("s3cret", &body)
+        );
+    }
+
+    #[tokio::test]
+    async fn async_jobs_are_capped_and_drained() {
+        let (callback_url, mut callbacks) = callback_receiver().await;
+        let state = AppState {
+            job_slots: Arc::new(Semaphore::new(1)),
+            ..Default::default()
+        };
+        let (release, released) = tokio::sync::oneshot::channel::<()>();
+        let dir = tempfile::tempdir().unwrap();
+        let output = dir.path().join("output.wav");
+        let encoded = {
+            let output = output.clone();
+            async move {
+                released.await.unwrap();
+                tokio::fs::write(&output, b"encoded audio").await.unwrap();
+                Ok(output)
+            }
+        };
+        start_job(
+            &state,
+            state.reserve_job_slot().unwrap(),
+            dir,
+            "song".to_string(),
+            "wav".to_string(),
+            callback_url,
+            encoded,
+        )
+        .await;
+        assert!(matches!(
+            state.reserve_job_slot(),
+            Err(AppError::TooManyJobs)
+        ));
+
+        let drained = tokio::spawn({
+            let state = state.clone();
+            async move { state.wait_for_jobs().await }
+        });
+        release.send(()).unwrap();
+        tokio::time::timeout(Duration::from_secs(5), drained)
+            .await
+            .unwrap()
+            .unwrap();
+        // the job finished, and delivered its callback, before the wait ended
+        assert_eq!(callbacks.try_recv().map(|_| ()), Ok(()));
+        assert!(state.reserve_job_slot().is_ok());
+    }
+
+    #[test]
+    fn callback_url_must_be_http() {
+        assert!(parse_callback_url("https://api.plyr.fm/transcodes/done").is_ok());
+        assert!(parse_callback_url("file:///etc/passwd").is_err());
+        assert!(parse_callback_url("/relative").is_err());
+    }
+
+    #[test]
+    fn draining_rejects_transcodes_with_retry_after() {
+        let state = AppState::default();
+        assert!(state.check_not_draining().is_ok());
+
+        state.draining.store(true, Ordering::SeqCst);
+        let response = state.check_not_draining().unwrap_err().into_response();
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(
+            response.headers()[header::RETRY_AFTER],
+            DRAIN_RETRY_AFTER_SECS.to_string()
+        );
+    }
+}
@@ -0,0 +1,185 @@
+//! optional persistence of async job status, so `GET /jobs/:id` keeps
+//! answering across a restart. the backend is picked by the URL scheme in
+//! `TRANSCODER_JOBS_DATABASE_URL` (`sqlite:` or `postgres:`).
+//!
+//! several instances can share one store. each row records the instance
+//! running the job and a lease that instance keeps renewing, so a starting
+//! instance only fails jobs that were its own or whose owner stopped renewing.
+
+use std::time::Duration;
+
+use sqlx::{any::AnyPoolOptions, AnyPool, Row};
+
+use crate::{unix_now, JobReport, JobStatus};
+
+/// error recorded on jobs that were still running when the process stopped.
+pub const INTERRUPTED: &str = "interrupted by a transcoder restart";
+
+/// how often an instance renews the lease on its unfinished jobs.
+pub const LEASE_RENEW_INTERVAL: Duration = Duration::from_secs(30);
+
+/// seconds after its last renewal that a job's lease runs out. a few missed
+/// renewals are tolerated before the job is given up on.
+const LEASE_SECS: i64 = 120;
+
+const SCHEMA: &str = "CREATE TABLE IF NOT EXISTS transcode_jobs (
+    job_id TEXT PRIMARY KEY,
+    status TEXT NOT NULL,
+    target TEXT NOT NULL,
+    bytes BIGINT,
+    sha256 TEXT,
+    error TEXT,
+    submitted_at BIGINT NOT NULL,
+    finished_at BIGINT,
+    instance_id TEXT NOT NULL,
+    lease_expires_at BIGINT NOT NULL
+)";
+
+#[derive(Clone)]
+pub struct JobStore {
+    pool: AnyPool,
+    /// the instance whose jobs this store writes.
+    instance_id: String,
+}
+
+impl JobStore {
+    /// connect and create the table if needed. for a new SQLite file, pass
+    /// `?mode=rwc` so it gets created. `instance_id` must stay the same
+    /// across restarts of one instance and differ between instances.
+    pub async fn connect(url: &str, instance_id: &str) -> Result<Self, sqlx::Error> {
+        sqlx::any::install_default_drivers();
+        let pool = AnyPoolOptions::new()
+            .max_connections(4)
+            .connect(url)
+            .await?;
+        sqlx::query(SCHEMA).execute(&pool).await?;
+        Ok(Self {
+            pool,
+            instance_id: instance_id.to_string(),
+        })
+    }
+
+    /// insert a job or overwrite its row with the latest report, taking a
+    /// fresh lease on it for this instance.
+    pub async fn save(&self, report: &JobReport) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "INSERT INTO transcode_jobs
+                (job_id, status, target, bytes, sha256, error, submitted_at, finished_at,
+                 instance_id, lease_expires_at)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+             ON CONFLICT (job_id) DO UPDATE SET
+                status = excluded.status,
+                bytes = excluded.bytes,
+                sha256 = excluded.sha256,
+                error = excluded.error,
+                finished_at = excluded.finished_at,
+                instance_id = excluded.instance_id,
+                lease_expires_at = excluded.lease_expires_at",
+        )
+        .bind(&report.job_id)
+        .bind(report.status.as_str())
+        .bind(&report.target)
+        .bind(report.bytes.map(|b| b as i64))
+        .bind(&report.sha256)
+        .bind(&report.error)
+        .bind(report.submitted_at)
+        .bind(report.finished_at)
+        .bind(&self.instance_id)
+        .bind(unix_now() + LEASE_SECS)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// a stored job. `output_url` is never set: output files live in temp
+    /// dirs and don't outlast the process that wrote them.
+    pub async fn get(&self, job_id: &str) -> Result<Option<JobReport>, sqlx::Error> {
+        let row = sqlx::query(
+            "SELECT job_id, status, target, bytes, sha256, error, submitted_at, finished_at
+             FROM transcode_jobs WHERE job_id = $1",
+        )
+        .bind(job_id)
+        .fetch_optional(&self.pool)
+        .await?;
+        let Some(row) = row else {
+            return Ok(None);
+        };
+        let status: String = row.try_get("status")?;
+        Ok(Some(JobReport {
+            job_id: row.try_get("job_id")?,
+            status: JobStatus::parse(&status).ok_or_else(|| {
+                sqlx::Error::Decode(format!("unknown job status {status:?}").into())
+            })?,
+            target: row.try_get("target")?,
+            output_url: None,
+            bytes: row.try_get::<Option<i64>, _>("bytes")?.map(|b| b as u64),
+            sha256: row.try_get("sha256")?,
+            error: row.try_get("error")?,
+            submitted_at: row.try_get("submitted_at")?,
+            finished_at: row.try_get("finished_at")?,
+        }))
+    }
+
+    /// at startup, mark unfinished jobs left by this instance's previous
+    /// process as failed, along with any whose lease ran out. their encodes
+    /// died with the process that ran them, so they'll never finish. jobs
+    /// other live instances are running are left alone. returns how many
+    /// were marked.
+    pub async fn fail_interrupted(&self, now: i64) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query(
+            "UPDATE transcode_jobs SET status = $1, error = $2, finished_at = $3
+             WHERE status IN ($4, $5) AND (instance_id = $6 OR lease_expires_at < $3)",
+        )
+        .bind(JobStatus::Failed.as_str())
+        .bind(INTERRUPTED)
+        .bind(now)
+        .bind(JobStatus::Submitted.as_str())
+        .bind(JobStatus::Running.as_str())
+        .bind(&self.instance_id)
+        .execute(&self.pool)
+        .await?;
+        Ok(result.rows_affected())
+    }
+
+    /// mark unfinished jobs whose lease ran out as failed: the instance
+    /// running them stopped without coming back. returns how many were
+    /// marked.
+    pub async fn fail_expired(&self, now: i64) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query(
+            "UPDATE transcode_jobs SET status = $1, error = $2, finished_at = $3
+             WHERE status IN ($4, $5) AND lease_expires_at < $3",
+        )
+        .bind(JobStatus::Failed.as_str())
+        .bind(INTERRUPTED)
+        .bind(now)
+        .bind(JobStatus::Submitted.as_str())
+        .bind(JobStatus::Running.as_str())
+        .execute(&self.pool)
+        .await?;
+        Ok(result.rows_affected())
+    }
+
+    /// extend the lease on this instance's unfinished jobs.
+    pub async fn renew_leases(&self, now: i64) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query(
+            "UPDATE transcode_jobs SET lease_expires_at = $1
+             WHERE instance_id = $2 AND status IN ($3, $4)",
+        )
+        .bind(now + LEASE_SECS)
+        .bind(&self.instance_id)
+        .bind(JobStatus::Submitted.as_str())
+        .bind(JobStatus::Running.as_str())
+        .execute(&self.pool)
+        .await?;
+        Ok(result.rows_affected())
+    }
+
+    /// drop jobs that finished before `cutoff` (unix seconds).
+    pub async fn prune(&self, cutoff: i64) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query("DELETE FROM transcode_jobs WHERE finished_at < $1")
+            .bind(cutoff)
+            .execute(&self.pool)
+            .await?;
+        Ok(result.rows_affected())
+    }
+}